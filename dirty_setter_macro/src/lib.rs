@@ -1,6 +1,9 @@
 use proc_macro::TokenStream;
 use quote::{quote, format_ident};
-use syn::{parse_macro_input, DeriveInput, Data, Fields, FieldsNamed};
+use syn::{
+    parenthesized, parse_macro_input, punctuated::Punctuated, Attribute, Data, DeriveInput, Expr,
+    Field, Fields, FieldsNamed, Ident, LitStr, Token, Type,
+};
 
 #[proc_macro_derive(DirtySetter, attributes(dirty_setter))]
 pub fn dirty_macro_derive(input: TokenStream) -> TokenStream {
@@ -21,92 +24,195 @@ fn impl_dirty_macro(ast: &DeriveInput) -> TokenStream {
         _ => panic!("This macro only works with structs"),
     };
 
-    let dirty_fields: Vec<_> = fields.iter()
-        .filter(|field| field.attrs.iter().any(|attr| attr.path().is_ident("dirty_setter")))
+    let dirty_fields: syn::Result<Vec<DirtyField>> = fields
+        .iter()
+        .filter_map(|field| {
+            let attr = field.attrs.iter().find(|attr| attr.path().is_ident("dirty_setter"))?;
+            Some(DirtyField::parse(field, attr))
+        })
         .collect();
+    let dirty_fields = match dirty_fields {
+        Ok(dirty_fields) => dirty_fields,
+        Err(e) => return e.to_compile_error().into(),
+    };
 
-    let setters = fields.iter()
-        .filter(|field| field.attrs.iter().any(|attr| attr.path().is_ident("dirty_setter")))
-        .map(|field| {
-            let field_name = &field.ident;
-            let field_type = &field.ty;
-            let setter_name = format_ident!("set_{}", field_name.as_ref().unwrap());
-
+    let setters = dirty_fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let field_type = &field.ty;
+        let key = &field.key;
+        let setter_name = format_ident!("set_{}", field_name);
+        let clamp = field.clamp_value(quote!(value));
+        let record = (!field.skip_history).then(|| {
             quote! {
-                pub fn #setter_name(&mut self, value: #field_type) -> &mut Self {
-                    let old_value = serde_json::json!({
-                        stringify!(#field_name): self.#field_name
-                    });
+                let old_value = serde_json::json!({ #key: self.#field_name });
+                let new_value = serde_json::json!({ #key: value });
+                let id = self.id().value().to_owned();
 
-                    self.#field_name = value.clone();
+                if let Some(app) = &self.app {
+                    let item = ObjectHistoryItem::new(id, old_value, new_value);
+                    app.record_history(HistoryItem::ObjectUpdate(item));
+                }
+            }
+        });
 
-                    let value = serde_json::json!({
-                        stringify!(#field_name): value
-                    });
+        quote! {
+            pub fn #setter_name(&mut self, value: #field_type) -> &mut Self {
+                #clamp
+                // 值没有变化时不产生历史记录
+                if self.#field_name == value {
+                    return self;
+                }
 
-                    let id = self.id().value().to_owned();
+                #record
 
-                    if let Some(app) = &self.app {
-                        let item = ObjectHistoryItem::new(id, old_value, value);
-                        app.history.borrow_mut().push(HistoryItem::ObjectUpdate(item));
-                    }
+                self.#field_name = value;
+                self.set_dirty();
+                self
+            }
+        }
+    });
 
-                    self.set_dirty();
-                    self
-                }
+    let batch_fields = dirty_fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let key = &field.key;
+        let clamp = field.clamp_value(quote!(value));
+        let record = (!field.skip_history).then(|| {
+            quote! {
+                old_value[#key] = serde_json::json!(self.#field_name);
+                update[#key] = serde_json::json!(value);
             }
         });
+        quote! {
+            if let Some(value) = updates.#field_name {
+                #clamp
+                if value != self.#field_name {
+                    #record
+                    self.#field_name = value;
+                    changed = true;
+                }
+            }
+        }
+    });
 
-    let field_names = dirty_fields.iter().map(|field| &field.ident);
-    let field_types = dirty_fields.iter().map(|field| &field.ty);
-
-    let batch_setter_field_names = field_names.clone();
-    let batch_setter_field_types = field_types.clone();
-
-    let dirty_field_names = field_names.clone();
-    
     let batch_setter = quote! {
         pub fn set_multiple(&mut self, updates: DirtyUpdates) -> &mut Self {
             let mut update = serde_json::json!({});
             let mut old_value = serde_json::json!({});
-            #(
-                if let Some(value) = updates.#field_names {
-                    old_value[stringify!(#field_names)] = serde_json::json!(self.#field_names);
-                    self.#field_names = value.clone();
-                    update[stringify!(#field_names)] = serde_json::json!(value);
-                }
-            )*
+            let mut changed = false;
+            #(#batch_fields)*
 
             if !update.as_object().unwrap().is_empty() {
                 let id = self.id().value().to_owned();
-               
+
                 if let Some(app) = &self.app {
                     let item = ObjectHistoryItem::new(id, old_value, update);
-                    app.history.borrow_mut().push(HistoryItem::ObjectUpdate(item));
+                    app.record_history(HistoryItem::ObjectUpdate(item));
                 }
-
+            }
+            if changed {
                 self.set_dirty();
             }
             self
         }
     };
 
+    let update_fields = dirty_fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let clamp = field.clamp_value(quote!(value));
+        quote! {
+            if let Some(value) = update_value.#field_name {
+                #clamp
+                self.#field_name = value;
+            }
+        }
+    });
+
     let update_method = quote! {
+        // 撤销/重做时直接写入字段，不再记录历史，但需要标记为脏
         fn update(&mut self, data: serde_json::Value) {
-            let update_value: DirtyUpdates = serde_json::from_value(data).unwrap();
-            #(
-                if let Some(value) = update_value.#dirty_field_names {
-                    self.#dirty_field_names = value;
-                }
-            )*
+            let Ok(update_value) = serde_json::from_value::<DirtyUpdates>(data) else {
+                return;
+            };
+            #(#update_fields)*
+            self.set_dirty();
         }
     };
 
-    
+    let getters = dirty_fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let field_type = &field.ty;
+        let getter_name = format_ident!("get_{}", field_name);
+        quote! {
+            pub fn #getter_name(&self) -> #field_type {
+                self.#field_name.clone()
+            }
+        }
+    });
+
+    // 属性反射，名称与历史记录中的键名一致
+    let property_keys = dirty_fields.iter().map(|field| &field.key);
+    let property_arms = dirty_fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let key = &field.key;
+        quote! {
+            #key => Some(serde_json::json!(self.#field_name)),
+        }
+    });
+
+    let property_schemas = dirty_fields.iter().map(DirtyField::schema);
+
+    let reflection = quote! {
+        pub fn property_names() -> &'static [&'static str] {
+            &[#(#property_keys),*]
+        }
+
+        pub fn property_schema() -> Vec<crate::element::PropertySchema> {
+            vec![#(#property_schemas),*]
+        }
+
+        pub fn get_property(&self, name: &str) -> Option<serde_json::Value> {
+            match name {
+                #(#property_arms)*
+                _ => None,
+            }
+        }
+    };
+
+    // 通过 RenderControl 排队，在下一帧渲染前按优先级应用，不记录历史
+    let queue_method = quote! {
+        pub fn queue_updates(
+            &self,
+            updates: DirtyUpdates,
+            priority: crate::render_control::UpdatePriority,
+        ) {
+            let Some(app) = &self.app else {
+                return;
+            };
+            let Ok(data) = serde_json::to_value(&updates) else {
+                return;
+            };
+            let update_type =
+                crate::render_control::UpdateType::ObjectUpdate(self.id().value().to_owned());
+            let body = crate::render_control::UpdateBody::new(update_type, data).with_priority(priority);
+            app.post_update(crate::render_control::UpdateMessage::Update(body));
+        }
+    };
+
+    // 更新数据与历史记录使用相同的键名
+    let update_struct_fields = dirty_fields.iter().map(|field| {
+        let field_name = &field.ident;
+        let field_type = &field.ty;
+        let key = &field.key;
+        quote! {
+            #[serde(rename = #key, skip_serializing_if = "Option::is_none")]
+            pub #field_name: Option<#field_type>,
+        }
+    });
+
     let updates_struct = quote! {
-        #[derive(Default, serde::Deserialize)]
+        #[derive(Default, serde::Serialize, serde::Deserialize)]
         pub struct DirtyUpdates {
-            #(pub #batch_setter_field_names: Option<#batch_setter_field_types>,)*
+            #(#update_struct_fields)*
         }
     };
 
@@ -114,11 +220,156 @@ fn impl_dirty_macro(ast: &DeriveInput) -> TokenStream {
         #updates_struct
 
         impl #name {
+            #(#getters)*
+            #reflection
             #(#setters)*
             #batch_setter
+            #queue_method
             #update_method
         }
     };
     
     gen.into()
 }
+
+// 字段上的 #[dirty_setter(...)] 选项：
+// skip_history 只标记为脏不记录历史；rename = "..." 指定历史记录和 update 数据中的键名；
+// clamp(min, max) 写入前把值限制在范围内；color 标记字符串字段为颜色；
+// options("a", "b") 列出枚举字段序列化后的可选值。后两者只影响 property_schema
+struct DirtyField {
+    ident: Ident,
+    ty: Type,
+    key: String,
+    skip_history: bool,
+    clamp: Option<(Expr, Expr)>,
+    color: bool,
+    options: Vec<LitStr>,
+}
+
+impl DirtyField {
+    fn parse(field: &Field, attr: &Attribute) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("named field");
+        let mut dirty_field = DirtyField {
+            key: ident.to_string(),
+            ty: field.ty.clone(),
+            ident,
+            skip_history: false,
+            clamp: None,
+            color: false,
+            options: Vec::new(),
+        };
+
+        if matches!(attr.meta, syn::Meta::Path(_)) {
+            return Ok(dirty_field);
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip_history") {
+                dirty_field.skip_history = true;
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                let key: LitStr = meta.value()?.parse()?;
+                dirty_field.key = key.value();
+                Ok(())
+            } else if meta.path.is_ident("clamp") {
+                let content;
+                parenthesized!(content in meta.input);
+                let bounds = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+                let mut bounds = bounds.into_iter();
+                match (bounds.next(), bounds.next(), bounds.next()) {
+                    (Some(min), Some(max), None) => {
+                        dirty_field.clamp = Some((min, max));
+                        Ok(())
+                    }
+                    _ => Err(meta.error("expected clamp(min, max)")),
+                }
+            } else if meta.path.is_ident("color") {
+                dirty_field.color = true;
+                Ok(())
+            } else if meta.path.is_ident("options") {
+                let content;
+                parenthesized!(content in meta.input);
+                let options = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                dirty_field.options = options.into_iter().collect();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported dirty_setter option"))
+            }
+        })?;
+
+        Ok(dirty_field)
+    }
+
+    fn schema(&self) -> proc_macro2::TokenStream {
+        let key = &self.key;
+        let (inner, nullable) = match option_inner(&self.ty) {
+            Some(inner) => (inner, true),
+            None => (&self.ty, false),
+        };
+        let kind = if self.color {
+            quote!(Color)
+        } else if !self.options.is_empty() {
+            quote!(Enum)
+        } else {
+            match last_segment(inner).as_deref() {
+                Some("f32" | "f64" | "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize") => {
+                    quote!(Number)
+                }
+                Some("bool") => quote!(Boolean),
+                Some("String") => quote!(String),
+                Some("Vec") => quote!(Array),
+                _ => quote!(Object),
+            }
+        };
+        let (min, max) = match &self.clamp {
+            Some((min, max)) => (quote!(Some((#min) as f64)), quote!(Some((#max) as f64))),
+            None => (quote!(None), quote!(None)),
+        };
+        let options = &self.options;
+        let history = !self.skip_history;
+        quote! {
+            crate::element::PropertySchema {
+                name: #key,
+                kind: crate::element::PropertyKind::#kind,
+                nullable: #nullable,
+                min: #min,
+                max: #max,
+                options: &[#(#options),*],
+                history: #history,
+            }
+        }
+    }
+
+    fn clamp_value(&self, value: proc_macro2::TokenStream) -> Option<proc_macro2::TokenStream> {
+        self.clamp.as_ref().map(|(min, max)| {
+            quote! {
+                let #value = #value.clamp(#min, #max);
+            }
+        })
+    }
+}
+
+fn last_segment(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+// Option<T> 中的 T
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
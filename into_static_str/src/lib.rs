@@ -2,39 +2,90 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Data, Fields};
+use syn::{parse_macro_input, Data, DeriveInput, Expr, ExprLit, Fields, Lit, Variant};
 
-#[proc_macro_derive(IntoStaticStr)]
+// 变体默认转换为 "Enum::Variant"，可以用 #[str = "custom-name"] 指定；
+// 带数据的变体转换时忽略数据，from_str 无法构造它们
+#[proc_macro_derive(IntoStaticStr, attributes(str))]
 pub fn into_static_str(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
 
     let variants = match input.data {
         Data::Enum(ref data) => &data.variants,
-        _ => panic!("IntoStaticStr can only be derived for enums"),
+        _ => {
+            return syn::Error::new_spanned(&input, "IntoStaticStr can only be derived for enums")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let names: syn::Result<Vec<String>> = variants
+        .iter()
+        .map(|variant| variant_str(name, variant))
+        .collect();
+    let names = match names {
+        Ok(names) => names,
+        Err(e) => return e.to_compile_error().into(),
     };
 
-    let match_arms = variants.iter().map(|v| {
+    let match_arms = variants.iter().zip(&names).map(|(v, str_name)| {
         let variant_name = &v.ident;
         match &v.fields {
-            Fields::Unit => {
-                quote! {
-                    #name::#variant_name => stringify!(#name::#variant_name)
-                }
-            },
-            _ => panic!("IntoStaticStr can only be derived for unit variants"),
+            Fields::Unit => quote! { #name::#variant_name => #str_name },
+            _ => quote! { #name::#variant_name { .. } => #str_name },
         }
     });
 
+    let from_str_arms = variants
+        .iter()
+        .zip(&names)
+        .filter(|(v, _)| matches!(v.fields, Fields::Unit))
+        .map(|(v, str_name)| {
+            let variant_name = &v.ident;
+            quote! { #str_name => Ok(#name::#variant_name) }
+        });
+
     let expanded = quote! {
-        impl Into<&'static str> for #name {
-            fn into(self) -> &'static str {
-                match self {
+        impl From<&#name> for &'static str {
+            fn from(value: &#name) -> &'static str {
+                match value {
                     #(#match_arms),*
                 }
             }
         }
+
+        impl From<#name> for &'static str {
+            fn from(value: #name) -> &'static str {
+                (&value).into()
+            }
+        }
+
+        impl std::str::FromStr for #name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    #(#from_str_arms,)*
+                    _ => Err(format!("unknown {}: {}", stringify!(#name), s)),
+                }
+            }
+        }
     };
 
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+fn variant_str(enum_name: &syn::Ident, variant: &Variant) -> syn::Result<String> {
+    let Some(attr) = variant.attrs.iter().find(|attr| attr.path().is_ident("str")) else {
+        return Ok(format!("{}::{}", enum_name, variant.ident));
+    };
+
+    match &attr.meta.require_name_value()?.value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(value),
+            ..
+        }) => Ok(value.value()),
+        value => Err(syn::Error::new_spanned(value, "expected #[str = \"...\"]")),
+    }
+}
@@ -1,4 +1,6 @@
+mod inertia;
 mod qwen;
+mod spring;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -7,12 +9,16 @@ use std::fmt::Debug;
 use std::rc::Rc;
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::StreamExt;
+use wasm_bindgen_futures::spawn_local;
 use wasm_timer::Instant;
-use web_sys::console;
 
 use crate::element::Renderable;
+use crate::log::{log_error, log_trace};
+use crate::scene_manager::SceneManager;
 
+pub use inertia::*;
 pub use qwen::*;
+pub use spring::*;
 
 #[derive(Debug, Clone)]
 pub enum AnimationValue {
@@ -24,6 +30,17 @@ pub enum AnimationValue {
     Matrix([f64; 6]),
 }
 
+impl AnimationValue {
+    // 由属性反射得到的 JSON 值转换，只支持数值和字符串
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        match value {
+            serde_json::Value::Number(number) => number.as_f64().map(AnimationValue::Float),
+            serde_json::Value::String(string) => Some(AnimationValue::String(string.clone())),
+            _ => None,
+        }
+    }
+}
+
 pub trait Animatable {
     fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
         HashMap::new()
@@ -71,21 +88,148 @@ pub trait Animation: Debug {
     ) -> AnimationStatus;
     fn get_progress_values(&self) -> HashMap<String, AnimationValue>;
     fn get_properties(&self) -> Vec<String>;
+    // 直接跳到动画结束状态
+    fn finish(&mut self);
+    // 各属性当前每秒的变化量，被打断时交给接替它的动画；没有速度概念的动画返回空
+    fn get_velocities(&self) -> HashMap<String, AnimationValue> {
+        HashMap::new()
+    }
+}
+
+pub type AnimationId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationState {
+    Running,
+    Paused,
+    Finishing,
+    Cancelled,
+    Completed,
+}
+
+struct AnimationControl {
+    state: AnimationState,
+    on_complete: Vec<Box<dyn FnOnce()>>,
+}
+
+impl Debug for AnimationControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AnimationControl {{ state: {:?}, on_complete: {} }}",
+            self.state,
+            self.on_complete.len()
+        )
+    }
+}
+
+impl AnimationControl {
+    fn is_done(&self) -> bool {
+        matches!(
+            self.state,
+            AnimationState::Cancelled | AnimationState::Completed
+        )
+    }
+
+    fn complete(&mut self) {
+        self.state = AnimationState::Completed;
+        let callbacks: Vec<Box<dyn FnOnce()>> = self.on_complete.drain(..).collect();
+        if callbacks.is_empty() {
+            return;
+        }
+        // 回调可能会再次访问 AnimationManager，放到微任务里执行避免重复借用
+        spawn_local(async move {
+            for callback in callbacks {
+                callback();
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationHandle {
+    id: AnimationId,
+    control: Rc<RefCell<AnimationControl>>,
+}
+
+impl AnimationHandle {
+    fn new(id: AnimationId) -> Self {
+        Self {
+            id,
+            control: Rc::new(RefCell::new(AnimationControl {
+                state: AnimationState::Running,
+                on_complete: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn id(&self) -> AnimationId {
+        self.id
+    }
+
+    pub fn state(&self) -> AnimationState {
+        self.control.borrow().state
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.control.borrow().is_done()
+    }
+
+    pub fn pause(&self) {
+        let mut control = self.control.borrow_mut();
+        if control.state == AnimationState::Running {
+            control.state = AnimationState::Paused;
+        }
+    }
+
+    pub fn resume(&self) {
+        let mut control = self.control.borrow_mut();
+        if control.state == AnimationState::Paused {
+            control.state = AnimationState::Running;
+        }
+    }
+
+    pub fn cancel(&self) {
+        let mut control = self.control.borrow_mut();
+        if !control.is_done() {
+            control.state = AnimationState::Cancelled;
+            control.on_complete.clear();
+        }
+    }
+
+    pub fn finish(&self) {
+        let mut control = self.control.borrow_mut();
+        if !control.is_done() {
+            control.state = AnimationState::Finishing;
+        }
+    }
+
+    pub fn on_complete(&self, callback: impl FnOnce() + 'static) -> &Self {
+        self.control.borrow_mut().on_complete.push(Box::new(callback));
+        self
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnimationTarget {
+    Object(String),
+    Scene,
+}
 
 #[derive(Debug)]
 struct AnimationEntry {
     animation: Box<dyn Animation>,
-    object_id: String,
+    target: AnimationTarget,
+    handle: AnimationHandle,
 }
 
 #[derive(Debug)]
 pub struct AnimationManager {
     pub init: bool,
     animations: Vec<AnimationEntry>,
-    queued_animations: VecDeque<(String, Box<dyn Animation>)>,
+    queued_animations: VecDeque<AnimationEntry>,
     last_update: Instant,
+    next_id: AnimationId,
 
     sender: Sender<bool>,
     receiver: Receiver<bool>,
@@ -101,6 +245,7 @@ impl AnimationManager {
             animations: Vec::new(),
             queued_animations: VecDeque::new(),
             last_update: Instant::now(),
+            next_id: 0,
             sender,
             receiver,
 
@@ -108,30 +253,148 @@ impl AnimationManager {
         }
     }
 
-    pub fn add_animation(&mut self, object_id: String, animation: Box<dyn Animation>) {
-        // console::log_1(&"add_animation".into());
-        self.animations.push(AnimationEntry {
+    fn create_entry(
+        &mut self,
+        target: AnimationTarget,
+        animation: Box<dyn Animation>,
+    ) -> AnimationEntry {
+        self.next_id += 1;
+        AnimationEntry {
             animation,
-            object_id,
-        });
+            target,
+            handle: AnimationHandle::new(self.next_id),
+        }
+    }
+
+    pub fn add_target_animation(
+        &mut self,
+        target: AnimationTarget,
+        animation: Box<dyn Animation>,
+    ) -> AnimationHandle {
+        let entry = self.create_entry(target, animation);
+        let handle = entry.handle.clone();
+        self.animations.push(entry);
 
         if self.init {
             self.sender();
         }
+        handle
+    }
+
+    pub fn add_animation(
+        &mut self,
+        object_id: String,
+        animation: Box<dyn Animation>,
+    ) -> AnimationHandle {
+        self.add_target_animation(AnimationTarget::Object(object_id), animation)
+    }
+
+    pub fn add_scene_animation(&mut self, animation: Box<dyn Animation>) -> AnimationHandle {
+        self.add_target_animation(AnimationTarget::Scene, animation)
+    }
+
+    pub fn queue_target_animation(
+        &mut self,
+        target: AnimationTarget,
+        animation: Box<dyn Animation>,
+    ) -> AnimationHandle {
+        let entry = self.create_entry(target, animation);
+        let handle = entry.handle.clone();
+        self.queued_animations.push_back(entry);
+        handle
+    }
+
+    pub fn queue_animation(
+        &mut self,
+        object_id: String,
+        animation: Box<dyn Animation>,
+    ) -> AnimationHandle {
+        self.queue_target_animation(AnimationTarget::Object(object_id), animation)
+    }
+
+    pub fn queue_scene_animation(&mut self, animation: Box<dyn Animation>) -> AnimationHandle {
+        self.queue_target_animation(AnimationTarget::Scene, animation)
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &AnimationEntry> {
+        self.animations.iter().chain(self.queued_animations.iter())
+    }
+
+    pub fn get_handle(&self, id: AnimationId) -> Option<AnimationHandle> {
+        self.entries()
+            .find(|entry| entry.handle.id == id)
+            .map(|entry| entry.handle.clone())
+    }
+
+    pub fn get_target_handles(&self, target: &AnimationTarget) -> Vec<AnimationHandle> {
+        self.entries()
+            .filter(|entry| &entry.target == target)
+            .map(|entry| entry.handle.clone())
+            .collect()
     }
 
-    pub fn queue_animation(&mut self, object_id: String, animation: Box<dyn Animation>) {
-        self.queued_animations.push_back((object_id, animation));
+    pub fn get_object_handles(&self, object_id: &str) -> Vec<AnimationHandle> {
+        self.get_target_handles(&AnimationTarget::Object(object_id.to_string()))
+    }
+
+    pub fn get_scene_handles(&self) -> Vec<AnimationHandle> {
+        self.get_target_handles(&AnimationTarget::Scene)
+    }
+
+    // 取消对象上包含 properties 中任一属性的动画，返回这些属性被打断时的速度，
+    // 新的弹簧动画从这个速度继续
+    pub fn interrupt_object(&self, object_id: &str, properties: &[String]) -> HashMap<String, AnimationValue> {
+        let target = AnimationTarget::Object(object_id.to_string());
+        let mut velocities = HashMap::new();
+        for entry in self.entries().filter(|entry| entry.target == target && entry.handle.is_active()) {
+            let animated = entry.animation.get_properties();
+            if !properties.iter().any(|name| animated.contains(name)) {
+                continue;
+            }
+            velocities.extend(
+                entry
+                    .animation
+                    .get_velocities()
+                    .into_iter()
+                    .filter(|(name, _)| properties.contains(name)),
+            );
+            entry.handle.cancel();
+        }
+        velocities
+    }
+
+    pub fn pause_object(&self, object_id: &str) {
+        self.get_object_handles(object_id)
+            .iter()
+            .for_each(AnimationHandle::pause);
+    }
+
+    pub fn resume_object(&self, object_id: &str) {
+        self.get_object_handles(object_id)
+            .iter()
+            .for_each(AnimationHandle::resume);
+    }
+
+    pub fn cancel_object(&self, object_id: &str) {
+        self.get_object_handles(object_id)
+            .iter()
+            .for_each(AnimationHandle::cancel);
+    }
+
+    pub fn finish_object(&self, object_id: &str) {
+        self.get_object_handles(object_id)
+            .iter()
+            .for_each(AnimationHandle::finish);
     }
 
     pub fn update(
         &mut self,
         objects: HashMap<String, Rc<RefCell<Box<dyn Renderable>>>>,
+        scene: Option<&RefCell<SceneManager>>,
     ) -> Result<(), AnimationError> {
-        // console::log_1(&"update".into());
         // 如果没有初始化，则进行初始化
         if !self.init {
-            console::log_1(&"init".into());
+            log_trace!("animation manager init");
             self.init = true;
             self.sender();
             return Ok(());
@@ -144,20 +407,53 @@ impl AnimationManager {
         let mut completed_indices = Vec::new();
 
         for (index, entry) in self.animations.iter_mut().enumerate() {
-            if let Some(object) = objects.get(&entry.object_id) {
-                let properties = entry.animation.get_properties();
-                let current_values = object.borrow().get_properties(&properties);
+            let state = entry.handle.state();
+            match state {
+                AnimationState::Paused => continue,
+                AnimationState::Cancelled | AnimationState::Completed => {
+                    completed_indices.push(index);
+                    continue;
+                }
+                _ => {}
+            }
+
+            // 动画目标可以是某个对象，也可以是场景本身（缩放/平移/旋转）
+            let properties = entry.animation.get_properties();
+            let current_values = match &entry.target {
+                AnimationTarget::Object(object_id) => objects
+                    .get(object_id)
+                    .map(|object| object.borrow().get_properties(&properties)),
+                AnimationTarget::Scene => scene.map(|scene| scene.borrow().get_properties(&properties)),
+            };
+            let Some(current_values) = current_values else {
+                entry.handle.cancel();
+                completed_indices.push(index);
+                continue;
+            };
+
+            let status = if state == AnimationState::Finishing {
+                entry.animation.finish();
+                AnimationStatus::Completed
+            } else {
+                entry.animation.update(delta, &current_values)
+            };
 
-                match entry.animation.update(delta, &current_values) {
-                    AnimationStatus::InProgress(progress) => {
-                        let new_values = entry.animation.get_progress_values();
+            let new_values = entry.animation.get_progress_values();
+            match &entry.target {
+                AnimationTarget::Object(object_id) => {
+                    if let Some(object) = objects.get(object_id) {
                         object.borrow_mut().set_properties(new_values)?;
                     }
-                    AnimationStatus::Completed => {
-                        completed_indices.push(index);
+                }
+                AnimationTarget::Scene => {
+                    if let Some(scene) = scene {
+                        scene.borrow_mut().set_properties(new_values)?;
                     }
                 }
-            } else {
+            }
+
+            if let AnimationStatus::Completed = status {
+                entry.handle.control.borrow_mut().complete();
                 completed_indices.push(index);
             }
         }
@@ -166,11 +462,8 @@ impl AnimationManager {
             self.sender();
         }
 
-        while let Some((object_id, animation)) = self.queued_animations.pop_front() {
-            self.animations.push(AnimationEntry {
-                animation,
-                object_id,
-            });
+        while let Some(entry) = self.queued_animations.pop_front() {
+            self.animations.push(entry);
             self.sender();
         }
         
@@ -184,6 +477,9 @@ impl AnimationManager {
     }
 
     pub fn clear_all_animations(&mut self) {
+        for entry in self.entries() {
+            entry.handle.cancel();
+        }
         self.animations.clear();
         self.queued_animations.clear();
     }
@@ -197,11 +493,10 @@ impl AnimationManager {
     }
 
     pub fn sender(&mut self) {
-        // console::log_1(&"sender".into());
         let now = Instant::now();
         if now.duration_since(self.last_send).as_secs_f64() >= 0.008 {
             if let Err(e) = self.sender.try_send(self.is_empty()) {
-                console::error_1(&format!("Failed to send animation update: {}", e).into());
+                log_error!("Failed to send animation update: {}", e);
             }
             self.last_send = now;
         }
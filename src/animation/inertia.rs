@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use super::{Animation, AnimationStatus, AnimationValue};
+
+// 与弹簧动画相同的固定步长和单帧上限
+const STEP: f64 = 1.0 / 120.0;
+const MAX_DELTA: f64 = 0.064;
+
+// 松手后的惯性滑动：二维属性（如场景的 "offset"）按速度继续移动，速度按指数衰减，
+// 低于 rest_speed 时停止。每帧在属性的当前值上累加位移，期间其他来源的修改不会被覆盖
+#[derive(Debug)]
+pub struct InertiaAnimation {
+    property: String,
+    velocity: (f64, f64),
+    initial_speed: f64,
+    // 每秒衰减的比例系数，越大停得越快
+    friction: f64,
+    rest_speed: f64,
+    value: Option<(f64, f64)>,
+    finished: bool,
+}
+
+impl InertiaAnimation {
+    pub fn new(property: impl Into<String>, velocity: (f64, f64), friction: f64, rest_speed: f64) -> Self {
+        Self {
+            property: property.into(),
+            velocity,
+            initial_speed: velocity.0.hypot(velocity.1),
+            friction: friction.max(0.0),
+            rest_speed: rest_speed.max(f64::EPSILON),
+            value: None,
+            finished: false,
+        }
+    }
+
+    fn speed(&self) -> f64 {
+        self.velocity.0.hypot(self.velocity.1)
+    }
+
+    // 半隐式欧拉：先衰减速度再移动
+    fn step(&mut self, position: &mut (f64, f64), dt: f64) {
+        let decay = (-self.friction * dt).exp();
+        self.velocity.0 *= decay;
+        self.velocity.1 *= decay;
+        position.0 += self.velocity.0 * dt;
+        position.1 += self.velocity.1 * dt;
+    }
+}
+
+impl Animation for InertiaAnimation {
+    fn update(&mut self, delta: f64, current_values: &HashMap<String, AnimationValue>) -> AnimationStatus {
+        if self.finished {
+            return AnimationStatus::Completed;
+        }
+        let Some(AnimationValue::Vector2D(mut position)) = current_values.get(&self.property).cloned() else {
+            self.finish();
+            return AnimationStatus::Completed;
+        };
+
+        let mut remaining = delta.clamp(0.0, MAX_DELTA);
+        while remaining > 0.0 && self.speed() >= self.rest_speed {
+            let dt = remaining.min(STEP);
+            self.step(&mut position, dt);
+            remaining -= dt;
+        }
+        self.value = Some(position);
+
+        if self.speed() < self.rest_speed {
+            self.finish();
+            return AnimationStatus::Completed;
+        }
+        // 没有固定时长，用速度的衰减程度估算进度
+        AnimationStatus::InProgress(1.0 - self.speed() / self.initial_speed.max(f64::EPSILON))
+    }
+
+    fn get_progress_values(&self) -> HashMap<String, AnimationValue> {
+        self.value
+            .map(|value| (self.property.clone(), AnimationValue::Vector2D(value)))
+            .into_iter()
+            .collect()
+    }
+
+    fn get_properties(&self) -> Vec<String> {
+        vec![self.property.clone()]
+    }
+
+    // 停在当前位置，不再继续滑动
+    fn finish(&mut self) {
+        self.velocity = (0.0, 0.0);
+        self.finished = true;
+    }
+}
@@ -3,16 +3,37 @@ use std::fmt::Debug;
 
 use super::{AnimationValue, Animation, AnimationStatus};
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationRepeat {
+    Count(u32),
+    Infinite,
+}
+
+impl Default for AnimationRepeat {
+    fn default() -> Self {
+        AnimationRepeat::Count(0)
+    }
+}
+
 pub struct QwenAnimation {
     properties: HashMap<String, (AnimationValue, AnimationValue)>, // (start, end)
     duration: f64,
     elapsed: f64,
     easing: Box<dyn Fn(f64) -> f64 >,
+    // repeat 表示额外播放的次数, Count(1) 即总共播放两遍
+    repeat: AnimationRepeat,
+    yoyo: bool,
+    delay: f64,
+    finished: bool,
 }
 
 impl Debug for QwenAnimation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "QwenAnimation {{ properties: {:?}, duration: {:?} }}", self.properties, self.duration)
+        write!(
+            f,
+            "QwenAnimation {{ properties: {:?}, duration: {:?}, repeat: {:?}, yoyo: {:?}, delay: {:?} }}",
+            self.properties, self.duration, self.repeat, self.yoyo, self.delay
+        )
     }
 }
 
@@ -23,23 +44,30 @@ impl Animation for QwenAnimation {
         current_values: &HashMap<String, AnimationValue>,
     ) -> AnimationStatus {
         self.elapsed += delta;
-        if self.elapsed >= self.duration {
-            // Animation completed
-            return AnimationStatus::Completed;
+        let active = self.elapsed - self.delay;
+        if active < 0.0 {
+            return AnimationStatus::InProgress(0.0);
         }
-        let progress = self.elapsed / self.duration;
 
-        AnimationStatus::InProgress(progress)
+        match self.repeat {
+            AnimationRepeat::Count(count) => {
+                let total = self.duration * (count as f64 + 1.0);
+                if active >= total {
+                    self.finished = true;
+                    return AnimationStatus::Completed;
+                }
+                AnimationStatus::InProgress(active / total)
+            }
+            AnimationRepeat::Infinite => AnimationStatus::InProgress(self.iteration_progress()),
+        }
     }
 
     fn get_progress_values(&self) -> HashMap<String, AnimationValue> {
-        let raw_progress = if self.duration > 0.0 {
-            (self.elapsed / self.duration).clamp(0.0, 1.0)
-        } else {
-            1.0
-        };
-        
-        let eased_progress = (self.easing)(raw_progress);
+        // 延迟期间不写入起始值，避免对象在动画开始前就跳回起点
+        if !self.finished && self.elapsed < self.delay {
+            return HashMap::new();
+        }
+        let eased_progress = (self.easing)(self.iteration_progress());
 
         self.properties.iter().map(|(k, (start, end))| {
             let value = match (start, end) {
@@ -86,6 +114,10 @@ impl Animation for QwenAnimation {
     fn get_properties(&self) -> Vec<String> {
         self.properties.keys().cloned().collect()
     }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
 }
 
 impl QwenAnimation {
@@ -95,18 +127,54 @@ impl QwenAnimation {
             duration,
             elapsed: 0.0,
             easing: Box::new(|x| x.powf(2.0)), // Linear easing by default
+            repeat: AnimationRepeat::default(),
+            yoyo: false,
+            delay: 0.0,
+            finished: false,
         }
     }
 
     fn set_easing(&mut self, easing: Box<dyn Fn(f64) -> f64 >) {
         self.easing = easing;
     }
+
+    fn is_reversed(&self, iteration: u32) -> bool {
+        self.yoyo && iteration % 2 == 1
+    }
+
+    // 当前这一遍的进度 (0~1)，已考虑 delay、repeat 和 yoyo
+    fn iteration_progress(&self) -> f64 {
+        if self.finished {
+            let last_iteration = match self.repeat {
+                AnimationRepeat::Count(count) => count,
+                AnimationRepeat::Infinite => 0,
+            };
+            return if self.is_reversed(last_iteration) { 0.0 } else { 1.0 };
+        }
+
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+
+        let active = (self.elapsed - self.delay).max(0.0);
+        let iteration = (active / self.duration).floor();
+        let local = active / self.duration - iteration;
+
+        if self.is_reversed(iteration as u32) {
+            1.0 - local
+        } else {
+            local
+        }
+    }
 }
 
 pub struct QwenAnimationBuilder {
     duration: f64,
     properties: HashMap<String, (AnimationValue, AnimationValue)>,
     easing: Option<Box<dyn Fn(f64) -> f64 >>,
+    repeat: AnimationRepeat,
+    yoyo: bool,
+    delay: f64,
 }
 
 impl QwenAnimationBuilder {
@@ -115,9 +183,27 @@ impl QwenAnimationBuilder {
             duration,
             properties: HashMap::new(),
             easing: None,
+            repeat: AnimationRepeat::default(),
+            yoyo: false,
+            delay: 0.0,
         }
     }
 
+    pub fn set_repeat(mut self, repeat: AnimationRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn set_yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    pub fn set_delay(mut self, delay: f64) -> Self {
+        self.delay = delay.max(0.0);
+        self
+    }
+
     pub fn add_property(mut self, name: &str, start: AnimationValue, end: AnimationValue) -> Self {
         self.properties.insert(name.to_string(), (start, end));
         self
@@ -131,9 +217,81 @@ impl QwenAnimationBuilder {
     pub fn build(self) -> QwenAnimation {
         let mut animation = QwenAnimation::new(self.duration);
         animation.properties = self.properties;
+        animation.repeat = self.repeat;
+        animation.yoyo = self.yoyo;
+        animation.delay = self.delay;
         if let Some(easing) = self.easing {
             animation.set_easing(easing);
         }
         animation
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linear(duration: f64) -> QwenAnimationBuilder {
+        QwenAnimationBuilder::new(duration)
+            .add_property("x", AnimationValue::Float(0.0), AnimationValue::Float(100.0))
+            .set_easing(Box::new(|t| t))
+    }
+
+    fn x(animation: &QwenAnimation) -> Option<f64> {
+        match animation.get_progress_values().get("x") {
+            Some(AnimationValue::Float(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn count_repeat_plays_extra_iterations() {
+        let mut animation = linear(1.0).set_repeat(AnimationRepeat::Count(2)).build();
+        let current = HashMap::new();
+
+        assert!(matches!(animation.update(1.5, &current), AnimationStatus::InProgress(_)));
+        assert!((x(&animation).unwrap() - 50.0).abs() < 1e-9);
+        assert!(matches!(animation.update(1.0, &current), AnimationStatus::InProgress(_)));
+        assert!(matches!(animation.update(0.5, &current), AnimationStatus::Completed));
+        assert_eq!(x(&animation), Some(100.0));
+    }
+
+    #[test]
+    fn infinite_repeat_never_completes() {
+        let mut animation = linear(1.0).set_repeat(AnimationRepeat::Infinite).build();
+        let current = HashMap::new();
+
+        for _ in 0..10 {
+            assert!(matches!(animation.update(0.75, &current), AnimationStatus::InProgress(_)));
+        }
+        // 7.5 秒后处于第 8 遍的一半
+        assert!((x(&animation).unwrap() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn yoyo_reverses_odd_iterations() {
+        let mut animation = linear(1.0)
+            .set_repeat(AnimationRepeat::Count(1))
+            .set_yoyo(true)
+            .build();
+        let current = HashMap::new();
+
+        animation.update(0.25, &current);
+        assert!((x(&animation).unwrap() - 25.0).abs() < 1e-9);
+        animation.update(1.0, &current);
+        assert!((x(&animation).unwrap() - 75.0).abs() < 1e-9);
+        assert!(matches!(animation.update(1.0, &current), AnimationStatus::Completed));
+        assert_eq!(x(&animation), Some(0.0));
+    }
+
+    #[test]
+    fn delay_holds_values_until_elapsed() {
+        let mut animation = linear(1.0).set_delay(0.5).build();
+        let current = HashMap::new();
+
+        assert!(matches!(animation.update(0.25, &current), AnimationStatus::InProgress(p) if p == 0.0));
+        assert!(animation.get_progress_values().is_empty());
+        animation.update(0.5, &current);
+        assert!((x(&animation).unwrap() - 25.0).abs() < 1e-9);
+    }
+}
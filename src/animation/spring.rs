@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use super::{Animation, AnimationStatus, AnimationValue};
+
+// 物理积分使用固定步长，保证不同帧率下结果一致
+const STEP: f64 = 1.0 / 120.0;
+// 单帧最多模拟的时间，避免切回标签页时一次性积分过长
+const MAX_DELTA: f64 = 0.064;
+
+#[derive(Debug, Clone)]
+struct SpringState {
+    template: AnimationValue,
+    value: Vec<f64>,
+    velocity: Vec<f64>,
+    target: Vec<f64>,
+    initialized: bool,
+}
+
+impl SpringState {
+    fn new(target: &AnimationValue) -> Option<Self> {
+        let target_components = to_components(target)?;
+        Some(Self {
+            template: target.clone(),
+            value: target_components.clone(),
+            velocity: vec![0.0; target_components.len()],
+            target: target_components,
+            initialized: false,
+        })
+    }
+
+    fn init_from(&mut self, start: &AnimationValue) {
+        if let Some(components) = to_components(start) {
+            if components.len() == self.target.len() {
+                self.value = components;
+            }
+        }
+        self.initialized = true;
+    }
+
+    fn is_at_rest(&self, rest_speed: f64, rest_delta: f64) -> bool {
+        self.velocity.iter().all(|v| v.abs() < rest_speed)
+            && self
+                .value
+                .iter()
+                .zip(self.target.iter())
+                .all(|(x, t)| (x - t).abs() < rest_delta)
+    }
+}
+
+fn to_components(value: &AnimationValue) -> Option<Vec<f64>> {
+    match value {
+        AnimationValue::Int(v) => Some(vec![*v as f64]),
+        AnimationValue::Float(v) => Some(vec![*v]),
+        AnimationValue::Vector2D((x, y)) => Some(vec![*x, *y]),
+        AnimationValue::Color((r, g, b, a)) => {
+            Some(vec![*r as f64, *g as f64, *b as f64, *a as f64])
+        }
+        AnimationValue::Matrix(m) => Some(m.to_vec()),
+        AnimationValue::String(_) => None,
+    }
+}
+
+fn from_components(template: &AnimationValue, components: &[f64]) -> AnimationValue {
+    let channel = |v: f64| v.round().clamp(0.0, 255.0) as u8;
+    match template {
+        AnimationValue::Int(_) => AnimationValue::Int(components[0].round() as i32),
+        AnimationValue::Float(_) => AnimationValue::Float(components[0]),
+        AnimationValue::Vector2D(_) => AnimationValue::Vector2D((components[0], components[1])),
+        AnimationValue::Color(_) => AnimationValue::Color((
+            channel(components[0]),
+            channel(components[1]),
+            channel(components[2]),
+            channel(components[3]),
+        )),
+        AnimationValue::Matrix(_) => {
+            let mut matrix = [0.0; 6];
+            matrix.copy_from_slice(&components[..6]);
+            AnimationValue::Matrix(matrix)
+        }
+        AnimationValue::String(_) => template.clone(),
+    }
+}
+
+pub struct SpringAnimation {
+    properties: HashMap<String, SpringState>,
+    stiffness: f64,
+    damping: f64,
+    mass: f64,
+    rest_speed: f64,
+    rest_delta: f64,
+    finished: bool,
+}
+
+impl Debug for SpringAnimation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SpringAnimation {{ properties: {:?}, stiffness: {:?}, damping: {:?}, mass: {:?} }}",
+            self.properties.keys().collect::<Vec<_>>(),
+            self.stiffness,
+            self.damping,
+            self.mass
+        )
+    }
+}
+
+impl SpringAnimation {
+    fn step(&mut self, dt: f64) {
+        for state in self.properties.values_mut() {
+            for i in 0..state.value.len() {
+                let displacement = state.value[i] - state.target[i];
+                let spring_force = -self.stiffness * displacement;
+                let damping_force = -self.damping * state.velocity[i];
+                let acceleration = (spring_force + damping_force) / self.mass;
+                state.velocity[i] += acceleration * dt;
+                state.value[i] += state.velocity[i] * dt;
+            }
+        }
+    }
+
+    fn settle(&mut self) {
+        for state in self.properties.values_mut() {
+            state.value = state.target.clone();
+            state.velocity.iter_mut().for_each(|v| *v = 0.0);
+        }
+        self.finished = true;
+    }
+}
+
+impl Animation for SpringAnimation {
+    fn update(
+        &mut self,
+        delta: f64,
+        current_values: &HashMap<String, AnimationValue>,
+    ) -> AnimationStatus {
+        if self.finished {
+            return AnimationStatus::Completed;
+        }
+
+        for (name, state) in self.properties.iter_mut() {
+            if !state.initialized {
+                match current_values.get(name) {
+                    Some(current) => state.init_from(current),
+                    None => state.initialized = true,
+                }
+            }
+        }
+
+        let mut remaining = delta.clamp(0.0, MAX_DELTA);
+        while remaining > 0.0 {
+            let dt = remaining.min(STEP);
+            self.step(dt);
+            remaining -= dt;
+        }
+
+        let at_rest = self
+            .properties
+            .values()
+            .all(|state| state.is_at_rest(self.rest_speed, self.rest_delta));
+        if at_rest {
+            self.settle();
+            return AnimationStatus::Completed;
+        }
+
+        // 弹簧没有固定时长，用剩余位移粗略估算进度
+        let (moved, total) = self.properties.values().fold((0.0, 0.0), |acc, state| {
+            let remaining: f64 = state
+                .value
+                .iter()
+                .zip(state.target.iter())
+                .map(|(x, t)| (x - t).abs())
+                .sum();
+            (acc.0 + remaining, acc.1 + remaining + self.rest_delta)
+        });
+        AnimationStatus::InProgress(if total > 0.0 { 1.0 - moved / total } else { 1.0 })
+    }
+
+    fn get_progress_values(&self) -> HashMap<String, AnimationValue> {
+        self.properties
+            .iter()
+            .filter(|(_, state)| state.initialized)
+            .map(|(k, state)| (k.clone(), from_components(&state.template, &state.value)))
+            .collect()
+    }
+
+    fn get_properties(&self) -> Vec<String> {
+        self.properties.keys().cloned().collect()
+    }
+
+    fn finish(&mut self) {
+        for state in self.properties.values_mut() {
+            state.initialized = true;
+        }
+        self.settle();
+    }
+
+    // 当前速度，用于打断后交给下一个弹簧动画继续（velocity carry-over）
+    fn get_velocities(&self) -> HashMap<String, AnimationValue> {
+        self.properties
+            .iter()
+            .map(|(k, state)| (k.clone(), from_components(&state.template, &state.velocity)))
+            .collect()
+    }
+}
+
+pub struct SpringAnimationBuilder {
+    properties: HashMap<String, SpringState>,
+    stiffness: f64,
+    damping: f64,
+    mass: f64,
+    rest_speed: f64,
+    rest_delta: f64,
+}
+
+impl Default for SpringAnimationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpringAnimationBuilder {
+    pub fn new() -> Self {
+        SpringAnimationBuilder {
+            properties: HashMap::new(),
+            stiffness: 170.0,
+            damping: 26.0,
+            mass: 1.0,
+            rest_speed: 0.01,
+            rest_delta: 0.01,
+        }
+    }
+
+    // 只指定目标值，起点取动画开始时对象的当前值
+    pub fn add_property(mut self, name: &str, target: AnimationValue) -> Self {
+        if let Some(state) = SpringState::new(&target) {
+            self.properties.insert(name.to_string(), state);
+        }
+        self
+    }
+
+    // 初始速度（每秒的变化量），用于松手时接上拖动的速度；需在 add_property 之后调用
+    pub fn set_velocity(mut self, name: &str, velocity: AnimationValue) -> Self {
+        if let (Some(state), Some(components)) =
+            (self.properties.get_mut(name), to_components(&velocity))
+        {
+            if components.len() == state.velocity.len() {
+                state.velocity = components;
+            }
+        }
+        self
+    }
+
+    pub fn set_velocities(mut self, velocities: HashMap<String, AnimationValue>) -> Self {
+        for (name, velocity) in velocities {
+            self = self.set_velocity(&name, velocity);
+        }
+        self
+    }
+
+    pub fn set_stiffness(mut self, stiffness: f64) -> Self {
+        self.stiffness = stiffness.max(0.0);
+        self
+    }
+
+    pub fn set_damping(mut self, damping: f64) -> Self {
+        self.damping = damping.max(0.0);
+        self
+    }
+
+    pub fn set_mass(mut self, mass: f64) -> Self {
+        self.mass = mass.max(f64::EPSILON);
+        self
+    }
+
+    pub fn build(self) -> SpringAnimation {
+        SpringAnimation {
+            properties: self.properties,
+            stiffness: self.stiffness,
+            damping: self.damping,
+            mass: self.mass,
+            rest_speed: self.rest_speed,
+            rest_delta: self.rest_delta,
+            finished: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::AnimationManager;
+
+    fn run(animation: &mut SpringAnimation, start: f64, frame: f64) -> Vec<f64> {
+        let current = HashMap::from([("x".to_string(), AnimationValue::Float(start))]);
+        let mut values = Vec::new();
+        while let AnimationStatus::InProgress(_) = animation.update(frame, &current) {
+            let Some(AnimationValue::Float(x)) = animation.get_progress_values().get("x").cloned() else {
+                panic!("missing progress value");
+            };
+            values.push(x);
+            assert!(values.len() < 10_000, "spring never settled");
+        }
+        values
+    }
+
+    #[test]
+    fn settles_on_target_from_current_value() {
+        let mut animation = SpringAnimationBuilder::new()
+            .add_property("x", AnimationValue::Float(100.0))
+            .build();
+        let values = run(&mut animation, 0.0, 1.0 / 60.0);
+        assert!(values[0] > 0.0 && values[0] < 100.0);
+        assert!(matches!(
+            animation.get_progress_values().get("x"),
+            Some(AnimationValue::Float(x)) if *x == 100.0
+        ));
+    }
+
+    // 初始速度背离目标时先继续朝原方向移动
+    #[test]
+    fn carries_initial_velocity() {
+        let mut animation = SpringAnimationBuilder::new()
+            .add_property("x", AnimationValue::Float(0.0))
+            .set_velocity("x", AnimationValue::Float(-500.0))
+            .build();
+        let values = run(&mut animation, 0.0, 1.0 / 60.0);
+        assert!(values[0] < 0.0);
+    }
+
+    // 固定步长积分，帧率不影响轨迹
+    #[test]
+    fn independent_of_frame_rate() {
+        let build = || {
+            SpringAnimationBuilder::new()
+                .set_damping(10.0)
+                .add_property("x", AnimationValue::Float(100.0))
+                .build()
+        };
+        let at_30 = run(&mut build(), 0.0, 1.0 / 30.0);
+        let at_60 = run(&mut build(), 0.0, 1.0 / 60.0);
+        assert!(at_30.len() > 10);
+        for (value, same_time) in at_30.iter().zip(at_60.iter().skip(1).step_by(2)) {
+            assert!((value - same_time).abs() < 1e-9);
+        }
+    }
+
+    // 被打断的弹簧把当前速度交给接替它的弹簧，新弹簧先沿原方向继续移动
+    #[test]
+    fn replacing_spring_carries_velocity() {
+        let mut manager = AnimationManager::new();
+        let first = SpringAnimationBuilder::new()
+            .add_property("x", AnimationValue::Float(100.0))
+            .set_velocity("x", AnimationValue::Float(800.0))
+            .build();
+        let handle = manager.add_animation("object".to_string(), Box::new(first));
+
+        let carried = manager.interrupt_object("object", &["x".to_string()]);
+        assert!(!handle.is_active());
+        assert!(matches!(carried.get("x"), Some(AnimationValue::Float(v)) if *v == 800.0));
+
+        let mut second = SpringAnimationBuilder::new()
+            .add_property("x", AnimationValue::Float(0.0))
+            .set_velocities(carried)
+            .build();
+        let values = run(&mut second, 0.0, 1.0 / 60.0);
+        assert!(values[0] > 0.0);
+    }
+
+    #[test]
+    fn reports_velocity_towards_target() {
+        let mut animation = SpringAnimationBuilder::new()
+            .add_property("x", AnimationValue::Float(100.0))
+            .build();
+        let current = HashMap::from([("x".to_string(), AnimationValue::Float(0.0))]);
+        animation.update(1.0 / 60.0, &current);
+        assert!(matches!(animation.get_velocities().get("x"), Some(AnimationValue::Float(v)) if *v > 0.0));
+    }
+}
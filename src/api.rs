@@ -0,0 +1,1505 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+use web_sys::js_sys::{Array, Function, Object, Promise, Proxy, Reflect, Uint8Array};
+
+use crate::animation::AnimationHandle;
+use crate::app::{App, AppOptions, FrameScriptId, ObjectAnimation, SpringOptions, StyleKind};
+use crate::boolean_ops::BooleanOp;
+use crate::bounding_box::BoundingBox;
+use crate::element::{register_script_element, registry};
+use crate::error::EditingError;
+use crate::events::AppEvent;
+use crate::fonts::FontDescriptors;
+use crate::helper::to_js_value;
+use crate::layout::LayoutOptions;
+use crate::log::log_error;
+use crate::render_control::{UpdateBody, UpdateMessage, UpdatePriority, UpdateType};
+use crate::render_loop::RenderLoopOptions;
+use crate::renderer::{Renderer, RendererProxy};
+use crate::scene_manager::{FrameHookId, HitTestMode};
+use crate::sprite::{SpriteFrames, SpriteSheet};
+use crate::op_log::OpLog;
+use crate::session::SessionEncoding;
+use crate::sync::{Operation, Presence, SyncAdapter};
+
+// 暴露给 JS 的 App 外观，内部状态仍由 Rust 侧的 App 管理
+#[wasm_bindgen(js_name = App)]
+pub struct JsApp {
+    app: App,
+}
+
+#[wasm_bindgen(js_class = App)]
+impl JsApp {
+    // options 可省略，字段见 AppOptions，例如 { supersampling: 1, hitTestMode: "geometry", background: "#fff" }
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas_id: String, options: JsValue) -> Result<JsApp, JsValue> {
+        let options: AppOptions = if options.is_undefined() || options.is_null() {
+            AppOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)?
+        };
+        Ok(Self {
+            app: App::new(canvas_id, options),
+        })
+    }
+
+    // 传 undefined 恢复透明背景
+    #[wasm_bindgen(js_name = setBackground)]
+    pub fn set_background(&self, background: Option<String>) {
+        self.app.set_background(background);
+    }
+
+    // { primary: "#3366ff", ... }，以 "$primary" 形式引用的颜色在绘制时解析，未定义的变量不绘制；
+    // 切换主题立即重绘，不修改对象也不进入历史
+    #[wasm_bindgen(js_name = setTheme)]
+    pub fn set_theme(&self, theme: JsValue) -> Result<(), JsValue> {
+        self.app.set_theme(serde_wasm_bindgen::from_value(theme)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getTheme)]
+    pub fn get_theme(&self) -> JsValue {
+        to_js_value(&self.app.theme())
+    }
+
+    // { kind: "none" | "lines" | "dots", spacing, color, majorEvery, majorColor, dotSize }，spacing 为世界单位，
+    // 缩小到过密时按 majorEvery 倍合并；网格只绘制可见区域，不出现在导出中
+    #[wasm_bindgen(js_name = setGrid)]
+    pub fn set_grid(&self, options: JsValue) -> Result<(), JsValue> {
+        self.app.set_grid(serde_wasm_bindgen::from_value(options)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getGrid)]
+    pub fn get_grid(&self) -> JsValue {
+        to_js_value(&self.app.grid_options())
+    }
+
+    // { enabled, thickness, margin, minThumb, color, hideDelayMs, fadeMs }，长度为屏幕像素；
+    // 内容超出可见区域时显示，视图变化后出现，停止操作 hideDelayMs 后在 fadeMs 内淡出，拖动滑块可平移
+    #[wasm_bindgen(js_name = setScrollbars)]
+    pub fn set_scrollbars(&self, options: JsValue) -> Result<(), JsValue> {
+        self.app.set_scrollbars(serde_wasm_bindgen::from_value(options)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getScrollbars)]
+    pub fn get_scrollbars(&self) -> JsValue {
+        to_js_value(&self.app.scrollbar_options())
+    }
+
+    // { unit: "px" | "mm" | "m", scale }，scale 为每个文档单位对应的世界单位数，省略时按 96 dpi 换算；
+    // 对象仍以世界坐标存储，单位只用于测量、尺寸标签、worldToUnits/unitsToWorld 和 exportRegionInUnits
+    #[wasm_bindgen(js_name = setUnits)]
+    pub fn set_units(&self, units: JsValue) -> Result<(), JsValue> {
+        self.app.set_units(serde_wasm_bindgen::from_value(units)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getUnits)]
+    pub fn get_units(&self) -> JsValue {
+        to_js_value(&self.app.units())
+    }
+
+    #[wasm_bindgen(js_name = worldToUnits)]
+    pub fn world_to_units(&self, x: f64, y: f64) -> JsValue {
+        let units = self.app.units();
+        to_js_value(&serde_json::json!({ "x": units.to_units(x), "y": units.to_units(y) }))
+    }
+
+    #[wasm_bindgen(js_name = unitsToWorld)]
+    pub fn units_to_world(&self, x: f64, y: f64) -> JsValue {
+        let units = self.app.units();
+        to_js_value(&serde_json::json!({ "x": units.to_world(x), "y": units.to_world(y) }))
+    }
+
+    #[wasm_bindgen(js_name = setZoomLimits)]
+    pub fn set_zoom_limits(&self, min_zoom: f64, max_zoom: f64) {
+        self.app.set_zoom_limits(min_zoom, max_zoom);
+    }
+
+    // [min, max]
+    #[wasm_bindgen(js_name = getZoomLimits)]
+    pub fn get_zoom_limits(&self) -> Vec<f64> {
+        let (min_zoom, max_zoom) = self.app.zoom_limits();
+        vec![min_zoom, max_zoom]
+    }
+
+    // 显示画布相对设备像素的倍数，限制在 0.5 ~ 4 之间；修改后画布立即按新尺寸重建
+    #[wasm_bindgen(js_name = setSupersampling)]
+    pub fn set_supersampling(&self, factor: f64) -> Result<(), JsValue> {
+        Ok(self.app.set_supersampling(factor)?)
+    }
+
+    #[wasm_bindgen(js_name = getSupersampling)]
+    pub fn get_supersampling(&self) -> f64 {
+        self.app.supersampling()
+    }
+
+    // hit 画布相对显示画布的分辨率，0.1 ~ 1，越低越省内存但细小对象越难点中
+    #[wasm_bindgen(js_name = setHitResolution)]
+    pub fn set_hit_resolution(&self, resolution: f64) -> Result<(), JsValue> {
+        Ok(self.app.set_hit_resolution(resolution)?)
+    }
+
+    #[wasm_bindgen(js_name = getHitResolution)]
+    pub fn get_hit_resolution(&self) -> f64 {
+        self.app.hit_resolution()
+    }
+
+    // 相邻且样式相同的图形合并为一次填充/描边，默认开启；排查绘制问题时可关闭
+    #[wasm_bindgen(js_name = setBatching)]
+    pub fn set_batching(&self, enabled: bool) {
+        self.app.set_batching(enabled);
+    }
+
+    // { enabled, threshold, frameBudgetMs }，可见对象数超过 threshold 时先快速绘制，
+    // 之后每帧用 frameBudgetMs 毫秒逐步补全细节
+    #[wasm_bindgen(js_name = setProgressiveOptions)]
+    pub fn set_progressive_options(&self, options: JsValue) -> Result<(), JsValue> {
+        self.app.set_progressive_options(serde_wasm_bindgen::from_value(options)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getProgressiveOptions)]
+    pub fn get_progressive_options(&self) -> JsValue {
+        to_js_value(&self.app.progressive_options())
+    }
+
+    // 描边两侧额外可点中的屏幕像素，对 pointer_events 为 "stroke" 的元素和线条生效
+    #[wasm_bindgen(js_name = setHitTolerance)]
+    pub fn set_hit_tolerance(&self, tolerance: f64) {
+        self.app.set_hit_tolerance(tolerance);
+    }
+
+    #[wasm_bindgen(js_name = getHitTolerance)]
+    pub fn get_hit_tolerance(&self) -> f64 {
+        self.app.hit_tolerance()
+    }
+
+    // "canvas" 按 hit 画布的颜色拾取，"geometry" 只按包围盒查询，不绘制 hit 画布
+    #[wasm_bindgen(js_name = setHitTestMode)]
+    pub fn set_hit_test_mode(&self, mode: JsValue) -> Result<(), JsValue> {
+        let mode: HitTestMode = serde_wasm_bindgen::from_value(mode)?;
+        self.app.set_hit_test_mode(mode);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getHitTestMode)]
+    pub fn get_hit_test_mode(&self) -> JsValue {
+        to_js_value(&self.app.hit_test_mode())
+    }
+
+    // 为 false 时右键仍显示浏览器菜单，"contextmenu" 事件照常触发
+    #[wasm_bindgen(js_name = setSuppressContextMenu)]
+    pub fn set_suppress_context_menu(&self, suppress: bool) {
+        self.app.set_suppress_context_menu(suppress);
+    }
+
+    pub fn init(&mut self) -> Result<(), JsValue> {
+        self.app.init()?;
+        Ok(())
+    }
+
+    // options 可省略，例如 { fpsCap: 30 }
+    pub fn start(&self, options: JsValue) -> Result<(), JsValue> {
+        let options: RenderLoopOptions = if options.is_undefined() || options.is_null() {
+            RenderLoopOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)?
+        };
+        self.app.start(options);
+        Ok(())
+    }
+
+    // 运行中修改帧率上限，传 undefined 取消限制
+    #[wasm_bindgen(js_name = setFpsCap)]
+    pub fn set_fps_cap(&self, fps_cap: Option<f64>) {
+        self.app.set_fps_cap(fps_cap);
+    }
+
+    #[wasm_bindgen(js_name = isRunning)]
+    pub fn is_running(&self) -> bool {
+        self.app.is_running()
+    }
+
+    // callback(renderer, delta) 在场景绘制之前以屏幕坐标调用，返回的 id 用于 removeFrameHook
+    #[wasm_bindgen(js_name = beforeRender)]
+    pub fn before_render(&self, callback: Function) -> FrameHookId {
+        self.app.before_render(Self::frame_hook(callback))
+    }
+
+    // callback(renderer, delta) 在场景绘制之后调用，适合绘制 HUD
+    #[wasm_bindgen(js_name = afterRender)]
+    pub fn after_render(&self, callback: Function) -> FrameHookId {
+        self.app.after_render(Self::frame_hook(callback))
+    }
+
+    #[wasm_bindgen(js_name = removeFrameHook)]
+    pub fn remove_frame_hook(&self, id: FrameHookId) -> bool {
+        self.app.remove_frame_hook(id)
+    }
+
+    // { frame, objects_drawn, objects_culled, draw_calls, render_ms, hit_canvas_ms, fps }
+    #[wasm_bindgen(js_name = getRenderStats)]
+    pub fn get_render_stats(&self) -> JsValue {
+        to_js_value(&self.app.get_render_stats())
+    }
+
+    #[wasm_bindgen(js_name = setShowStats)]
+    pub fn set_show_stats(&self, show: bool) {
+        self.app.set_show_stats(show);
+    }
+
+    // 显示包围盒、对象 id、旋转中心和脏区域
+    #[wasm_bindgen(js_name = setDebug)]
+    pub fn set_debug(&self, enabled: bool) {
+        self.app.set_debug(enabled);
+    }
+
+    #[wasm_bindgen(js_name = isDebug)]
+    pub fn is_debug(&self) -> bool {
+        self.app.is_debug()
+    }
+
+    // 把 hit 画布叠加显示在场景上，用于排查点选问题
+    #[wasm_bindgen(js_name = setDebugHitCanvas)]
+    pub fn set_debug_hit_canvas(&self, show: bool) {
+        self.app.set_debug_hit_canvas(show);
+    }
+
+    // 需要在 init 之前调用，传 undefined 取消
+    #[wasm_bindgen(js_name = setRenderWorker)]
+    pub fn set_render_worker(&self, script_url: Option<String>) -> Result<(), JsValue> {
+        self.app.set_render_worker(script_url)?;
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        self.app.stop();
+    }
+
+    pub fn destroy(&self) {
+        self.app.destroy();
+    }
+
+    // 所有对象包围盒的并集 { x, y, width, height }，没有对象时返回 undefined
+    #[wasm_bindgen(js_name = getDocumentBounds)]
+    pub fn get_document_bounds(&self) -> JsValue {
+        self.app
+            .document_bounds()
+            .map_or(JsValue::UNDEFINED, |bounds| to_js_value(&bounds))
+    }
+
+    // { objectCount, objectsByType, bounds, dirty, history: { undoDepth, redoDepth, bytes },
+    // memory: { canvasBytes, imageCount, imageBytes } }，内存为估算值
+    #[wasm_bindgen(js_name = getDocumentInfo)]
+    pub fn get_document_info(&self) -> JsValue {
+        to_js_value(&self.app.get_document_info())
+    }
+
+    // 上次 markSaved 之后是否有未保存的修改
+    #[wasm_bindgen(js_name = isDirty)]
+    pub fn is_dirty(&self) -> bool {
+        self.app.is_dirty()
+    }
+
+    #[wasm_bindgen(js_name = markSaved)]
+    pub fn mark_saved(&self) {
+        self.app.mark_saved();
+    }
+
+    // 事件名支持 "ready"、"resize"、"historychange"、"objectdown"、"objectmove"、"objectup"、"strokeend"、"shapeend"、
+    // "fontload"、"imageload"、"imageerror"、"contextmenu"、"viewportchange"、"scenechange"、"documentchange"、"dirtychange"、
+    // "saved"、"replayend"、"measure"
+    pub fn on(&self, event_name: &str, callback: &Function) -> Result<(), JsValue> {
+        self.app.events.add_listener(Self::event_name(event_name), callback)
+    }
+
+    pub fn off(&self, event_name: &str, callback: &Function) -> Result<(), JsValue> {
+        self.app
+            .events
+            .remove_listener(Self::event_name(event_name), callback)
+    }
+
+    pub fn undo(&self) -> bool {
+        self.app.undo()
+    }
+
+    pub fn redo(&self) -> bool {
+        self.app.redo()
+    }
+
+    // 为 true 时平移、缩放、旋转也可以撤销
+    #[wasm_bindgen(js_name = setRecordCameraHistory)]
+    pub fn set_record_camera_history(&self, enabled: bool) {
+        self.app.set_record_camera_history(enabled);
+    }
+
+    // 平移工具松手后是否按拖动速度惯性滑动，滑动中再次按下会立即停止
+    #[wasm_bindgen(js_name = setPanInertia)]
+    pub fn set_pan_inertia(&self, enabled: bool) {
+        self.app.set_pan_inertia(enabled);
+    }
+
+    // 视图的后退/前进，与撤销重做相互独立
+    #[wasm_bindgen(js_name = viewBack)]
+    pub fn view_back(&self) -> bool {
+        self.app.view_back()
+    }
+
+    #[wasm_bindgen(js_name = viewForward)]
+    pub fn view_forward(&self) -> bool {
+        self.app.view_forward()
+    }
+
+    #[wasm_bindgen(js_name = canViewBack)]
+    pub fn can_view_back(&self) -> bool {
+        self.app.scene_manager.borrow().can_view_back()
+    }
+
+    #[wasm_bindgen(js_name = canViewForward)]
+    pub fn can_view_forward(&self) -> bool {
+        self.app.scene_manager.borrow().can_view_forward()
+    }
+
+    #[wasm_bindgen(js_name = canUndo)]
+    pub fn can_undo(&self) -> bool {
+        self.app.can_undo()
+    }
+
+    #[wasm_bindgen(js_name = canRedo)]
+    pub fn can_redo(&self) -> bool {
+        self.app.can_redo()
+    }
+
+    // 为 true 时指针落在该对象上会先于普通拾取和当前工具触发 "objectdown"，后声明的优先，用于工具浮层等
+    #[wasm_bindgen(js_name = setCapturesInput)]
+    pub fn set_captures_input(&self, id: &str, captures: bool) -> Result<(), JsValue> {
+        Ok(self.app.set_captures_input(id, captures)?)
+    }
+
+    #[wasm_bindgen(js_name = capturesInput)]
+    pub fn captures_input(&self, id: &str) -> bool {
+        self.app.captures_input(id)
+    }
+
+    // 按下命中对象后，该对象捕获指针直到抬起：期间的 "objectmove" 和 "objectup" 都发给它，即使指针已离开对象
+    #[wasm_bindgen(js_name = getPointerCapture)]
+    pub fn get_pointer_capture(&self) -> Option<String> {
+        self.app.pointer_capture()
+    }
+
+    // 提前结束捕获，之后的抬起按普通拾取派发
+    #[wasm_bindgen(js_name = releasePointerCapture)]
+    pub fn release_pointer_capture(&self) {
+        self.app.release_pointer_capture();
+    }
+
+    #[wasm_bindgen(js_name = addTag)]
+    pub fn add_tag(&self, id: &str, tag: String) -> Result<(), JsValue> {
+        self.app.object_manager.borrow().update_meta(id, |meta| {
+            meta.add_tag(tag);
+        })?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = removeTag)]
+    pub fn remove_tag(&self, id: &str, tag: &str) -> Result<(), JsValue> {
+        self.app.object_manager.borrow().update_meta(id, |meta| {
+            meta.remove_tag(tag);
+        })?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = findByTag)]
+    pub fn find_by_tag(&self, tag: &str) -> Vec<String> {
+        self.app.find_by_tag(tag)
+    }
+
+    // value 为 null 时删除该键
+    #[wasm_bindgen(js_name = setMetadata)]
+    pub fn set_metadata(&self, id: &str, key: String, value: JsValue) -> Result<(), JsValue> {
+        let value: serde_json::Value = serde_wasm_bindgen::from_value(value)?;
+        self.app
+            .object_manager
+            .borrow()
+            .update_meta(id, |meta| meta.set(key, value))?;
+        Ok(())
+    }
+
+    // 返回 { tags, data }，对象不存在时为 undefined
+    #[wasm_bindgen(js_name = getMetadata)]
+    pub fn get_metadata(&self, id: &str) -> JsValue {
+        match self.app.object_manager.borrow().get_meta(id) {
+            Some(meta) => to_js_value(&meta),
+            None => JsValue::UNDEFINED,
+        }
+    }
+
+    // { id, type, properties: [{ name, type, nullable, min?, max?, options?, history, value }] }，
+    // type 为 number、boolean、string、color、enum、array 或 object
+    #[wasm_bindgen(js_name = describeObject)]
+    pub fn describe_object(&self, id: &str) -> Result<JsValue, JsValue> {
+        Ok(to_js_value(&self.app.describe_object(id)?))
+    }
+
+    // { matrix: [a, b, c, d, e, f], translate, rotation, scale, skew }，矩阵为对象在所在坐标系下的完整变换（含锚点），
+    // rotation 为度，分解时 skew[1] 固定为 0
+    #[wasm_bindgen(js_name = getObjectTransform)]
+    pub fn get_object_transform(&self, id: &str) -> Result<JsValue, JsValue> {
+        Ok(to_js_value(&self.app.object_transform(id)?))
+    }
+
+    // matrix 为 [a, b, c, d, e, f]，分解后写回对象的位置、缩放、斜切和旋转，作为一次撤销
+    #[wasm_bindgen(js_name = setObjectTransform)]
+    pub fn set_object_transform(&self, id: &str, matrix: JsValue) -> Result<(), JsValue> {
+        let matrix: [f64; 6] = serde_wasm_bindgen::from_value(matrix)?;
+        Ok(self.app.set_object_transform(id, matrix)?)
+    }
+
+    // 世界坐标矩形内（按包围盒）的对象 id，按 z 顺序从下到上
+    #[wasm_bindgen(js_name = queryRect)]
+    pub fn query_rect(&self, x: f64, y: f64, width: f64, height: f64) -> Vec<String> {
+        self.app
+            .object_manager
+            .borrow()
+            .query_rect(&BoundingBox::new(x, y, width, height))
+    }
+
+    #[wasm_bindgen(js_name = queryPoint)]
+    pub fn query_point(&self, x: f64, y: f64) -> Vec<String> {
+        self.app.object_manager.borrow().query_point(x, y)
+    }
+
+    // 世界坐标处按形状命中的对象 id，从最上层到最下层，可用于 Alt+点击在重叠对象间切换
+    #[wasm_bindgen(js_name = hitTestAll)]
+    pub fn hit_test_all(&self, x: f64, y: f64) -> Vec<String> {
+        self.app
+            .scene_manager
+            .borrow()
+            .hit_test_all(x, y)
+            .iter()
+            .map(|object| object.borrow().id().value().to_string())
+            .collect()
+    }
+
+    // { zoom, offset: [x, y], rotation, size: [width, height], dpr }，rotation 为弧度
+    #[wasm_bindgen(js_name = getViewport)]
+    pub fn get_viewport(&self) -> JsValue {
+        to_js_value(&self.app.scene_manager.borrow().get_viewport())
+    }
+
+    // 画布 CSS 像素 -> 世界坐标 { x, y }，视图矩阵不可逆时返回 undefined
+    #[wasm_bindgen(js_name = screenToWorld)]
+    pub fn screen_to_world(&self, x: f64, y: f64) -> JsValue {
+        match self.app.scene_manager.borrow().screen_to_world(x, y) {
+            Some((x, y)) => to_js_value(&serde_json::json!({ "x": x, "y": y })),
+            None => JsValue::UNDEFINED,
+        }
+    }
+
+    #[wasm_bindgen(js_name = worldToScreen)]
+    pub fn world_to_screen(&self, x: f64, y: f64) -> JsValue {
+        let (x, y) = self.app.scene_manager.borrow().world_to_screen(x, y);
+        to_js_value(&serde_json::json!({ "x": x, "y": y }))
+    }
+
+    // 取画布 CSS 像素处的颜色 { r, g, b, a, hex }，size 为取样区域边长，默认 1；超出画布时返回 undefined，画布由 worker 绘制时抛出错误
+    #[wasm_bindgen(js_name = pickColor)]
+    pub fn pick_color(&self, x: f64, y: f64, size: Option<u32>) -> Result<JsValue, JsValue> {
+        Ok(match self.app.pick_color(x, y, size.unwrap_or(1))? {
+            Some(color) => to_js_value(&serde_json::json!({
+                "r": color.r,
+                "g": color.g,
+                "b": color.b,
+                "a": color.a,
+                "hex": color.to_hex(),
+            })),
+            None => JsValue::UNDEFINED,
+        })
+    }
+
+    // 直接传入鼠标事件的 clientX/clientY
+    #[wasm_bindgen(js_name = clientToWorld)]
+    pub fn client_to_world(&self, client_x: f64, client_y: f64) -> JsValue {
+        match self.app.scene_manager.borrow().client_to_world(client_x, client_y) {
+            Some((x, y)) => to_js_value(&serde_json::json!({ "x": x, "y": y })),
+            None => JsValue::UNDEFINED,
+        }
+    }
+
+    // 从对象池取出元素，data 为需要设置的属性；适合粒子等频繁增删的场景，不进入历史
+    pub fn acquire(&self, element_type: &str, data: JsValue) -> Result<String, JsValue> {
+        let data: serde_json::Value = serde_wasm_bindgen::from_value(data)?;
+        Ok(self.app.acquire(element_type, data)?)
+    }
+
+    pub fn release(&self, id: &str) -> bool {
+        self.app.release(id)
+    }
+
+    // constraint 形如 { type: "pin_to_viewport", anchor_x: 0, anchor_y: 0, offset_x: 16, offset_y: 16 }、
+    // { type: "fixed_screen_size" } 或 { type: "follow", target: id, offset_x: 0, offset_y: -20 }
+    #[wasm_bindgen(js_name = addConstraint)]
+    pub fn add_constraint(&self, id: &str, constraint: JsValue) -> Result<(), JsValue> {
+        let constraint = serde_wasm_bindgen::from_value(constraint)?;
+        Ok(self.app.add_constraint(id, constraint)?)
+    }
+
+    #[wasm_bindgen(js_name = getConstraints)]
+    pub fn get_constraints(&self, id: &str) -> JsValue {
+        to_js_value(&self.app.constraints(id))
+    }
+
+    #[wasm_bindgen(js_name = clearConstraints)]
+    pub fn clear_constraints(&self, id: &str) -> bool {
+        self.app.clear_constraints(id)
+    }
+
+    // tool 可以是 "select"、"rect"、"ellipse"、"line"、"text"、"pan"、"measure" 这样的名称，
+    // 也可以带选项，如 { type: "pencil", stroke: "#333", strokeWidth: 4 }、{ type: "rect", fill: "#fc0" }
+    // 或 { type: "eraser", mode: "split", radius: 12 }；
+    // 画完一笔后触发 "strokeend"，参数为 { id }；拖拽或单击创建图形后触发 "shapeend"，参数为 { id, type }
+    #[wasm_bindgen(js_name = setTool)]
+    pub fn set_tool(&self, tool: JsValue) -> Result<(), JsValue> {
+        let tool = match tool.as_string() {
+            Some(name) => serde_json::from_value(serde_json::json!({ "type": name }))
+                .map_err(EditingError::from)?,
+            None => serde_wasm_bindgen::from_value(tool)?,
+        };
+        self.app.set_tool(tool);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getTool)]
+    pub fn get_tool(&self) -> JsValue {
+        to_js_value(&self.app.tool())
+    }
+
+    // 测量工具和尺寸标签的显示方式，如 { precision: 2, dimensions: true }，长度按文档单位显示；
+    // 测量工具拖拽结束后触发 "measure"，参数同 getMeasurement
+    #[wasm_bindgen(js_name = setMeasureOptions)]
+    pub fn set_measure_options(&self, options: JsValue) -> Result<(), JsValue> {
+        self.app.set_measure_options(serde_wasm_bindgen::from_value(options)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getMeasureOptions)]
+    pub fn get_measure_options(&self) -> JsValue {
+        to_js_value(&self.app.measure_options())
+    }
+
+    // { from, to, distance, angle, unit }，distance 已换算为文档单位，没有测量时返回 undefined
+    #[wasm_bindgen(js_name = getMeasurement)]
+    pub fn get_measurement(&self) -> JsValue {
+        self.app
+            .measurement()
+            .map_or(JsValue::UNDEFINED, |measurement| to_js_value(&measurement))
+    }
+
+    #[wasm_bindgen(js_name = clearMeasure)]
+    pub fn clear_measure(&self) {
+        self.app.clear_measure();
+    }
+
+    // 在文本对象上打开编辑框；双击文本时会自动打开，Ctrl/Cmd+Enter 或失去焦点提交，Escape 取消
+    #[wasm_bindgen(js_name = editText)]
+    pub fn edit_text(&self, id: &str) -> Result<(), JsValue> {
+        Ok(self.app.begin_text_edit(id)?)
+    }
+
+    #[wasm_bindgen(js_name = finishTextEdit)]
+    pub fn finish_text_edit(&self, commit: bool) {
+        self.app.finish_text_edit(commit);
+    }
+
+    // 加载网络字体，完成后使用该字体的文本会重新测量并重绘，同时触发 "fontload" 事件，参数为 { family, status }；
+    // descriptors 可省略，例如 { weight: "700", style: "italic" }
+    #[wasm_bindgen(js_name = loadFont)]
+    pub fn load_font(&self, family: String, source: String, descriptors: JsValue) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let descriptors = if descriptors.is_undefined() || descriptors.is_null() {
+                FontDescriptors::default()
+            } else {
+                serde_wasm_bindgen::from_value(descriptors)?
+            };
+            app.load_font(&family, &source, descriptors).await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    // 返回 "loading"、"loaded"、"failed"，未通过 loadFont 加载过的字体返回 undefined
+    #[wasm_bindgen(js_name = getFontStatus)]
+    pub fn get_font_status(&self, family: &str) -> JsValue {
+        match self.app.font_status(family) {
+            Some(status) => to_js_value(&status),
+            None => JsValue::UNDEFINED,
+        }
+    }
+
+    #[wasm_bindgen(js_name = fontsReady)]
+    pub fn fonts_ready(&self) -> bool {
+        self.app.fonts_ready()
+    }
+
+    // 预先加载图片，resolve 为加载失败的 URL 数组；每张图片完成时触发 "imageload" 或 "imageerror"
+    #[wasm_bindgen(js_name = preload)]
+    pub fn preload(&self, urls: Vec<String>) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let failed = app.preload_images(&urls).await;
+            Ok(to_js_value(&failed))
+        })
+    }
+
+    // 把世界坐标区域导出为 PNG Blob，scale 为每个世界单位的像素数，默认 1
+    #[wasm_bindgen(js_name = exportRegion)]
+    pub fn export_region(&self, x: f64, y: f64, width: f64, height: f64, scale: Option<f64>) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let region = BoundingBox::new(x, y, width, height);
+            Ok(app.export_region(region, scale.unwrap_or(1.0)).await?.into())
+        })
+    }
+
+    // 区域以文档单位给出，pixelsPerUnit 为每个文档单位对应的输出像素
+    #[wasm_bindgen(js_name = exportRegionInUnits)]
+    pub fn export_region_in_units(&self, x: f64, y: f64, width: f64, height: f64, pixels_per_unit: f64) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let region = BoundingBox::new(x, y, width, height);
+            Ok(app.export_region_in_units(region, pixels_per_unit).await?.into())
+        })
+    }
+
+    // 全部内容的 PNG Blob，区域由内容决定，padding 为四周留白（世界单位）；文档为空时 resolve 为 undefined
+    #[wasm_bindgen(js_name = exportDocument)]
+    pub fn export_document(&self, padding: Option<f64>, scale: Option<f64>) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let blob = app
+                .export_document(padding.unwrap_or(0.0), scale.unwrap_or(1.0))
+                .await?;
+            Ok(blob.map_or(JsValue::UNDEFINED, JsValue::from))
+        })
+    }
+
+    // 只包含选中对象的 PNG Blob，padding 为四周留白（世界单位）；没有选中对象时 resolve 为 undefined
+    #[wasm_bindgen(js_name = exportSelection)]
+    pub fn export_selection(&self, padding: Option<f64>) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let blob = app.export_selection(padding.unwrap_or(0.0)).await?;
+            Ok(blob.map_or(JsValue::UNDEFINED, JsValue::from))
+        })
+    }
+
+    // 返回 "loading"、"loaded"、"failed"，未加载过的图片返回 undefined
+    #[wasm_bindgen(js_name = getImageStatus)]
+    pub fn get_image_status(&self, url: &str) -> JsValue {
+        match self.app.image_status(url) {
+            Some(status) => to_js_value(&status),
+            None => JsValue::UNDEFINED,
+        }
+    }
+
+    #[wasm_bindgen(js_name = evictImage)]
+    pub fn evict_image(&self, url: &str) -> bool {
+        self.app.evict_image(url)
+    }
+
+    // 播放 video 元素，播放期间自动逐帧重绘；浏览器拒绝播放时 Promise 会 reject
+    #[wasm_bindgen(js_name = playVideo)]
+    pub fn play_video(&self, id: String) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            app.play_video(&id).await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    #[wasm_bindgen(js_name = pauseVideo)]
+    pub fn pause_video(&self, id: &str) -> Result<(), JsValue> {
+        Ok(self.app.pause_video(id)?)
+    }
+
+    #[wasm_bindgen(js_name = isVideoPlaying)]
+    pub fn is_video_playing(&self, id: &str) -> bool {
+        self.app.is_video_playing(id)
+    }
+
+    // 加载图集图片并以 name 注册；frames 为 { 帧名: { x, y, width, height } }，
+    // 或等大网格 { frameWidth, frameHeight, columns, count }，帧名为序号 "0"、"1"...
+    #[wasm_bindgen(js_name = loadSpriteSheet)]
+    pub fn load_sprite_sheet(&self, name: String, url: String, frames: JsValue) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let frames: SpriteFrames = serde_wasm_bindgen::from_value(frames)?;
+            let sheet = SpriteSheet::new(app.load_image(&url).await?).with_layout(frames);
+            app.add_sprite_sheet(&name, sheet);
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    #[wasm_bindgen(js_name = removeSpriteSheet)]
+    pub fn remove_sprite_sheet(&self, name: &str) -> bool {
+        self.app.remove_sprite_sheet(name)
+    }
+
+    // 需要先调用 start 启动渲染循环
+    #[wasm_bindgen(js_name = playSprite)]
+    pub fn play_sprite(&self, id: &str) -> Result<(), JsValue> {
+        self.app.play_sprite(id)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = stopSprite)]
+    pub fn stop_sprite(&self, id: &str) {
+        self.app.stop_sprite(id);
+    }
+
+    // app.animate(id).to({ x: 100 }).duration(300).easing("ease-out").start()，需要先调用 start 启动渲染循环
+    pub fn animate(&self, id: &str) -> JsObjectAnimation {
+        JsObjectAnimation {
+            animation: self.app.animate(id),
+        }
+    }
+
+    // 正在编辑的文本对象 id，没有时返回 undefined
+    #[wasm_bindgen(js_name = getEditingText)]
+    pub fn get_editing_text(&self) -> Option<String> {
+        self.app.editing_text()
+    }
+
+    // options 可省略，例如 { algorithm: "layered", direction: "left_right", spacing: 60, duration: 0.5 }
+    #[wasm_bindgen(js_name = autoLayout)]
+    pub fn auto_layout(&self, ids: Vec<String>, options: JsValue) -> Result<(), JsValue> {
+        let options = if options.is_undefined() || options.is_null() {
+            LayoutOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)?
+        };
+        Ok(self.app.auto_layout(&ids, options)?)
+    }
+
+    #[wasm_bindgen(js_name = finishLayout)]
+    pub fn finish_layout(&self) {
+        self.app.finish_layout();
+    }
+
+    // 在浏览器空闲时预先创建元素，直到池中有 count 个
+    #[wasm_bindgen(js_name = warmPool)]
+    pub fn warm_pool(&self, element_type: &str, count: usize) -> Result<(), JsValue> {
+        Ok(self.app.warm_pool(element_type, count)?)
+    }
+
+    // 每种类型最多保留的回收对象数
+    #[wasm_bindgen(js_name = setMaxPoolSize)]
+    pub fn set_max_pool_size(&self, size: usize) {
+        self.app.set_max_pool_size(size);
+    }
+
+    // 历史变化后在空闲时调用 callback({ objects, history })，传 undefined 关闭
+    #[wasm_bindgen(js_name = setAutosave)]
+    pub fn set_autosave(&self, callback: Option<Function>) {
+        self.app.set_autosave(callback);
+    }
+
+    // 注册由 JS 绘制的元素类型，draw(renderer, { id, width, height, props }) 在元素局部坐标下绘制
+    #[wasm_bindgen(js_name = registerElement)]
+    pub fn register_element(element_type: &str, draw: Function) {
+        register_script_element(element_type, draw);
+    }
+
+    // 之后无法再创建该类型的对象，已有对象不受影响
+    #[wasm_bindgen(js_name = unregisterElement)]
+    pub fn unregister_element(element_type: &str) -> bool {
+        registry::unregister_element(element_type)
+    }
+
+    #[wasm_bindgen(js_name = isElementRegistered)]
+    pub fn is_element_registered(element_type: &str) -> bool {
+        registry::is_registered(element_type)
+    }
+
+    // data 为需要覆盖的属性，可用 id 指定对象 id（不能与已有对象重复），返回新对象的 id
+    #[wasm_bindgen(js_name = addElement)]
+    pub fn add_element(&self, element_type: &str, data: JsValue) -> Result<String, JsValue> {
+        let data: serde_json::Value = serde_wasm_bindgen::from_value(data)?;
+        Ok(self.app.create(element_type, data)?)
+    }
+
+    #[wasm_bindgen(js_name = removeElement)]
+    pub fn remove_element(&self, id: &str) -> bool {
+        self.app.remove(id).is_some()
+    }
+
+    // 把对象移入 frame，保持原来的位置，之后随 frame 移动并被裁剪；作为一次撤销
+    #[wasm_bindgen(js_name = addToFrame)]
+    pub fn add_to_frame(&self, frame_id: &str, ids: Vec<String>) -> Result<bool, JsValue> {
+        Ok(self.app.add_to_frame(frame_id, &ids)?)
+    }
+
+    #[wasm_bindgen(js_name = removeFromFrame)]
+    pub fn remove_from_frame(&self, ids: Vec<String>) -> Result<bool, JsValue> {
+        Ok(self.app.remove_from_frame(&ids)?)
+    }
+
+    // 对象所在的 frame，顶层对象返回 undefined
+    #[wasm_bindgen(js_name = getParentFrame)]
+    pub fn get_parent_frame(&self, id: &str) -> Option<String> {
+        self.app.parent_frame(id)
+    }
+
+    // 把对象放入新的主组件并返回其 id；实例按主组件的当前内容绘制，修改主组件后所有实例随之更新
+    #[wasm_bindgen(js_name = createComponent)]
+    pub fn create_component(&self, ids: Vec<String>) -> Result<Option<String>, JsValue> {
+        Ok(self.app.create_component(&ids)?)
+    }
+
+    #[wasm_bindgen(js_name = createInstance)]
+    pub fn create_instance(&self, component_id: &str, x: f64, y: f64) -> Result<String, JsValue> {
+        Ok(self.app.create_instance(component_id, x, y)?)
+    }
+
+    // 覆盖实例中主组件对象 targetId 的填充色，fill 为 null 时恢复
+    #[wasm_bindgen(js_name = setInstanceFill)]
+    pub fn set_instance_fill(&self, instance_id: &str, target_id: &str, fill: Option<String>) -> Result<bool, JsValue> {
+        Ok(self.app.set_instance_fill(instance_id, target_id, fill)?)
+    }
+
+    #[wasm_bindgen(js_name = getComponents)]
+    pub fn get_components(&self) -> Vec<String> {
+        self.app.components()
+    }
+
+    #[wasm_bindgen(js_name = getInstances)]
+    pub fn get_instances(&self, component_id: &str) -> Vec<String> {
+        self.app.instances_of(component_id)
+    }
+
+    // kind 为 "fill"、"stroke" 或 "text"，properties 如 { fill: "#f00" }、{ stroke, stroke_width }、
+    // { font_family, font_size, font_weight, line_height, align }，其他属性被忽略；返回样式 id
+    #[wasm_bindgen(js_name = createStyle)]
+    pub fn create_style(&self, name: &str, kind: String, properties: JsValue) -> Result<String, JsValue> {
+        let kind: StyleKind = serde_json::from_value(serde_json::Value::String(kind)).map_err(EditingError::from)?;
+        Ok(self.app.create_style(name, kind, serde_wasm_bindgen::from_value(properties)?)?)
+    }
+
+    // 合并修改样式的属性并更新所有引用它的对象，作为一次撤销；返回发生变化的对象数
+    #[wasm_bindgen(js_name = updateStyle)]
+    pub fn update_style(&self, id: &str, properties: JsValue) -> Result<usize, JsValue> {
+        Ok(self.app.update_style(id, serde_wasm_bindgen::from_value(properties)?)?)
+    }
+
+    #[wasm_bindgen(js_name = renameStyle)]
+    pub fn rename_style(&self, id: &str, name: &str) -> Result<bool, JsValue> {
+        Ok(self.app.rename_style(id, name)?)
+    }
+
+    #[wasm_bindgen(js_name = removeStyle)]
+    pub fn remove_style(&self, id: &str) -> Result<bool, JsValue> {
+        Ok(self.app.remove_style(id)?)
+    }
+
+    // 对象引用样式并应用其属性，替换同类的原有引用；返回发生变化的对象数
+    #[wasm_bindgen(js_name = applyStyle)]
+    pub fn apply_style(&self, id: &str, ids: Vec<String>) -> Result<usize, JsValue> {
+        Ok(self.app.apply_style(id, &ids)?)
+    }
+
+    #[wasm_bindgen(js_name = detachStyle)]
+    pub fn detach_style(&self, ids: Vec<String>, kind: String) -> Result<(), JsValue> {
+        let kind: StyleKind = serde_json::from_value(serde_json::Value::String(kind)).map_err(EditingError::from)?;
+        Ok(self.app.detach_style(&ids, kind)?)
+    }
+
+    // [{ id, name, kind, properties }]
+    #[wasm_bindgen(js_name = getStyles)]
+    pub fn get_styles(&self) -> JsValue {
+        to_js_value(&self.app.styles())
+    }
+
+    #[wasm_bindgen(js_name = getStyledObjects)]
+    pub fn get_styled_objects(&self, id: &str) -> Vec<String> {
+        self.app.styled_objects(id)
+    }
+
+    // 从自动保存的 styles 恢复样式库，不记录历史
+    #[wasm_bindgen(js_name = loadStyles)]
+    pub fn load_styles(&self, styles: JsValue) -> Result<(), JsValue> {
+        self.app.load_styles(serde_wasm_bindgen::from_value(styles)?);
+        Ok(())
+    }
+
+    // op 为 "union"、"subtract"、"intersect" 或 "xor"，ids 可以是 path、rect、ellipse；
+    // 结果为新的 path 并替换原图形，作为一次撤销。不足两个图形或结果为空时返回 undefined
+    #[wasm_bindgen(js_name = booleanOperation)]
+    pub fn boolean_operation(&self, ids: Vec<String>, op: String) -> Result<Option<String>, JsValue> {
+        let op: BooleanOp = serde_json::from_value(serde_json::Value::String(op)).map_err(EditingError::from)?;
+        Ok(self.app.boolean_operation(&ids, op)?)
+    }
+
+    // 进入 path 的编辑模式：拖拽锚点和控制点修改曲线，点击曲线插入点，Alt+点击锚点切换平滑/尖角，
+    // Ctrl/Cmd+点击锚点删除；每次拖拽为一次撤销，点击路径外或切换工具时退出
+    #[wasm_bindgen(js_name = editPath)]
+    pub fn edit_path(&self, id: &str) -> Result<(), JsValue> {
+        Ok(self.app.edit_path(id)?)
+    }
+
+    #[wasm_bindgen(js_name = exitPathEdit)]
+    pub fn exit_path_edit(&self) {
+        self.app.exit_path_edit();
+    }
+
+    #[wasm_bindgen(js_name = getEditingPath)]
+    pub fn get_editing_path(&self) -> Option<String> {
+        self.app.editing_path()
+    }
+
+    #[wasm_bindgen(js_name = getSelectedPathPoint)]
+    pub fn get_selected_path_point(&self) -> Option<usize> {
+        self.app.selected_path_point()
+    }
+
+    // 点数已是最少（开放路径 2 个、封闭路径 3 个）或序号越界时返回 false
+    #[wasm_bindgen(js_name = removePathPoint)]
+    pub fn remove_path_point(&self, index: usize) -> Result<bool, JsValue> {
+        Ok(self.app.remove_path_point(index)?)
+    }
+
+    #[wasm_bindgen(js_name = togglePathAnchor)]
+    pub fn toggle_path_anchor(&self, index: usize) -> Result<bool, JsValue> {
+        Ok(self.app.toggle_path_anchor(index)?)
+    }
+
+    // patches 为 { id: { 属性: 值 } }，作为一次撤销立即应用，返回发生变化的对象数
+    #[wasm_bindgen(js_name = updateMany)]
+    pub fn update_many(&self, patches: JsValue) -> Result<usize, JsValue> {
+        let patches: serde_json::Map<String, serde_json::Value> =
+            serde_wasm_bindgen::from_value(patches)?;
+        Ok(self.app.update_many(patches.into_iter().collect())?)
+    }
+
+    // 开始协作同步，本地修改以 { clientId, seq, clock, items, sequence } 的形式传给 send，由宿主通过任意通道转发；
+    // 其他客户端收到后调用 receiveOperation
+    // sendPresence 可省略，传入时以 { clientId, name, cursor: [x, y] | null, selection } 发送本地指针和选中状态，
+    // 其他客户端收到后调用 receivePresence
+    #[wasm_bindgen(js_name = startSync)]
+    pub fn start_sync(&self, client_id: String, send: Function, send_presence: Option<Function>) {
+        self.app.start_sync(client_id, JsSyncAdapter { send, send_presence });
+    }
+
+    #[wasm_bindgen(js_name = stopSync)]
+    pub fn stop_sync(&self) {
+        self.app.stop_sync();
+    }
+
+    #[wasm_bindgen(js_name = receiveOperation)]
+    pub fn receive_operation(&self, operation: JsValue) -> Result<(), JsValue> {
+        let operation: Operation = serde_wasm_bindgen::from_value(operation)?;
+        self.app.receive_operation(operation);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = receivePresence)]
+    pub fn receive_presence(&self, presence: JsValue) -> Result<(), JsValue> {
+        let presence: Presence = serde_wasm_bindgen::from_value(presence)?;
+        self.app.receive_presence(presence);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = removePresence)]
+    pub fn remove_presence(&self, client_id: String) -> bool {
+        self.app.remove_presence(&client_id)
+    }
+
+    #[wasm_bindgen(js_name = setPresenceName)]
+    pub fn set_presence_name(&self, name: Option<String>) {
+        self.app.set_presence_name(name);
+    }
+
+    // 缓存对象的属性更新，在下一帧渲染前统一应用；不进入历史，适合高频的外部数据同步。
+    // priority 为 "input"、"animation" 或 "background"（默认）
+    #[wasm_bindgen(js_name = queueUpdate)]
+    pub fn queue_update(
+        &self,
+        id: &str,
+        data: JsValue,
+        priority: Option<String>,
+    ) -> Result<(), JsValue> {
+        let data: serde_json::Value = serde_wasm_bindgen::from_value(data)?;
+        let priority = match priority {
+            Some(priority) => priority.parse::<UpdatePriority>()?,
+            None => UpdatePriority::default(),
+        };
+        let body = UpdateBody::new(UpdateType::ObjectUpdate(id.to_string()), data)
+            .with_priority(priority);
+        self.app.post_update(UpdateMessage::Update(body));
+        Ok(())
+    }
+
+    // 排队更新的最短应用间隔（毫秒），0 表示每帧应用；输入优先级的更新不受限制
+    #[wasm_bindgen(js_name = setUpdateInterval)]
+    pub fn set_update_interval(&self, interval_ms: f64) {
+        self.app.set_update_interval(interval_ms);
+    }
+
+    // 返回 { received, merged, dropped, applied, deferred }
+    #[wasm_bindgen(js_name = updateStats)]
+    pub fn update_stats(&self) -> JsValue {
+        to_js_value(&self.app.render_control.borrow().stats())
+    }
+
+    // 已注册的元素类型，包括内置类型
+    #[wasm_bindgen(js_name = elementTypes)]
+    pub fn element_types() -> Vec<String> {
+        registry::registered_types()
+    }
+
+    pub fn select(&self, ids: Vec<String>) {
+        self.app.select(ids);
+    }
+
+    pub fn selection(&self) -> Vec<String> {
+        self.app.selection()
+    }
+
+    #[wasm_bindgen(js_name = clearSelection)]
+    pub fn clear_selection(&self) {
+        self.app.clear_selection();
+    }
+
+    pub fn copy(&self) -> usize {
+        self.app.copy_selection()
+    }
+
+    pub fn cut(&self) -> usize {
+        self.app.cut()
+    }
+
+    // 返回新对象的 id
+    pub fn paste(&self, dx: f64, dy: f64) -> Result<Vec<String>, JsValue> {
+        Ok(self.app.paste((dx, dy))?)
+    }
+
+    pub fn duplicate(&self, id: &str) -> Result<String, JsValue> {
+        Ok(self.app.duplicate(id)?)
+    }
+
+    // 读取系统剪贴板后粘贴，返回 resolve 为新 id 数组的 Promise
+    #[wasm_bindgen(js_name = pasteFromClipboard)]
+    pub fn paste_from_clipboard(&self, dx: f64, dy: f64) -> Promise {
+        let app = self.app.clone();
+        future_to_promise(async move {
+            let ids = app.paste_from_system_clipboard((dx, dy)).await?;
+            Ok(serde_wasm_bindgen::to_value(&ids)?)
+        })
+    }
+
+    // 命名当前的历史单元，如 "Move rectangle"
+    #[wasm_bindgen(js_name = setHistoryLabel)]
+    pub fn set_history_label(&self, label: String) {
+        self.app.history.borrow_mut().set_label(label);
+    }
+
+    pub fn checkpoint(&self, label: String) {
+        self.app.history.borrow_mut().checkpoint(label);
+    }
+
+    // 传 0 表示不限制
+    #[wasm_bindgen(js_name = setHistoryLimits)]
+    pub fn set_history_limits(&self, max_units: usize, max_bytes: usize) {
+        self.app.history.borrow_mut().set_limits(max_units, max_bytes);
+    }
+
+    // 返回 { position, entries }，用于驱动时间轴滑块
+    #[wasm_bindgen(js_name = historyTimeline)]
+    pub fn history_timeline(&self) -> Result<JsValue, JsValue> {
+        let timeline = self.app.history.borrow_mut().get_timeline();
+        serde_wasm_bindgen::to_value(&timeline).map_err(|e| e.into())
+    }
+
+    #[wasm_bindgen(js_name = seekHistory)]
+    pub fn seek_history(&self, position: usize) -> bool {
+        self.app.history.borrow_mut().seek(position)
+    }
+
+    // 把对象属性绑定到数据键，key 可用 "." 访问嵌套字段；transform(value, key) 可省略，返回值作为属性值
+    #[wasm_bindgen(js_name = bindProperty)]
+    pub fn bind_property(
+        &self,
+        id: &str,
+        property: &str,
+        key: &str,
+        transform: Option<Function>,
+    ) -> Result<(), JsValue> {
+        Ok(self.app.bind_property(id, property, key, transform)?)
+    }
+
+    #[wasm_bindgen(js_name = unbindProperty)]
+    pub fn unbind_property(&self, id: &str, property: &str) -> bool {
+        self.app.unbind_property(id, property)
+    }
+
+    #[wasm_bindgen(js_name = setData)]
+    pub fn set_data(&self, key: &str, value: JsValue) -> Result<(), JsValue> {
+        let value: serde_json::Value = serde_wasm_bindgen::from_value(value)?;
+        self.app.set_data(key, value);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getData)]
+    pub fn get_data(&self, key: &str) -> JsValue {
+        self.app
+            .get_data(key)
+            .map_or(JsValue::UNDEFINED, |value| to_js_value(&value))
+    }
+
+    // 返回可观察的数据对象，给它的顶层字段赋值或删除字段时自动调用 setData；
+    // 修改嵌套字段不会被察觉，需要重新给顶层字段赋值
+    #[wasm_bindgen(js_name = createDataSource)]
+    pub fn create_data_source(&self, initial: JsValue) -> Result<JsValue, JsValue> {
+        let target = if initial.is_undefined() || initial.is_null() {
+            Object::new()
+        } else {
+            initial.dyn_into::<Object>()?
+        };
+        for entry in Object::entries(&target).iter() {
+            let entry: Array = entry.into();
+            if let Some(key) = entry.get(0).as_string() {
+                self.set_data(&key, entry.get(1))?;
+            }
+        }
+
+        let app = self.app.clone();
+        let set = Closure::<dyn FnMut(Object, JsValue, JsValue) -> Result<bool, JsValue>>::new(
+            move |target: Object, key: JsValue, value: JsValue| {
+                Reflect::set(&target, &key, &value)?;
+                if let Some(key) = key.as_string() {
+                    let value: serde_json::Value = serde_wasm_bindgen::from_value(value)?;
+                    app.set_data(&key, value);
+                }
+                Ok(true)
+            },
+        );
+        let app = self.app.clone();
+        let delete = Closure::<dyn FnMut(Object, JsValue) -> Result<bool, JsValue>>::new(
+            move |target: Object, key: JsValue| {
+                Reflect::delete_property(&target, &key)?;
+                if let Some(key) = key.as_string() {
+                    app.set_data(&key, serde_json::Value::Null);
+                }
+                Ok(true)
+            },
+        );
+        let handler = Object::new();
+        // 闭包交给 JS 管理，随数据对象一起被回收
+        Reflect::set(&handler, &"set".into(), &set.into_js_value())?;
+        Reflect::set(&handler, &"deleteProperty".into(), &delete.into_js_value())?;
+        Ok(Proxy::new(&target, &handler).into())
+    }
+
+    // script 每帧以 (app, { time, delta, frame }) 调用，app 为只能查询对象、修改属性、创建对象和启动动画的 AppProxy；
+    // 返回的 id 用于 removeFrameScript
+    #[wasm_bindgen(js_name = addFrameScript)]
+    pub fn add_frame_script(&self, script: Function) -> FrameScriptId {
+        self.app.add_frame_script(script)
+    }
+
+    #[wasm_bindgen(js_name = removeFrameScript)]
+    pub fn remove_frame_script(&self, id: FrameScriptId) -> bool {
+        self.app.remove_frame_script(id)
+    }
+
+    // 暂停对象上正在进行的所有动画
+    #[wasm_bindgen(js_name = pauseAnimations)]
+    pub fn pause_animations(&self, id: &str) {
+        self.app.pause_animations(id);
+    }
+
+    #[wasm_bindgen(js_name = resumeAnimations)]
+    pub fn resume_animations(&self, id: &str) {
+        self.app.resume_animations(id);
+    }
+
+    #[wasm_bindgen(js_name = cancelAnimations)]
+    pub fn cancel_animations(&self, id: &str) {
+        self.app.cancel_animations(id);
+    }
+
+    // 下一帧直接跳到终点
+    #[wasm_bindgen(js_name = finishAnimations)]
+    pub fn finish_animations(&self, id: &str) {
+        self.app.finish_animations(id);
+    }
+
+    // 开始录制操作日志，以当前文档为起点
+    #[wasm_bindgen(js_name = startRecording)]
+    pub fn start_recording(&self) {
+        self.app.start_recording();
+    }
+
+    // 返回日志的 JSON 字符串，可保存后交给 replay；没有在录制时返回 undefined
+    #[wasm_bindgen(js_name = stopRecording)]
+    pub fn stop_recording(&self) -> Result<Option<String>, JsValue> {
+        match self.app.stop_recording() {
+            Some(log) => Ok(Some(log.to_json()?)),
+            None => Ok(None),
+        }
+    }
+
+    #[wasm_bindgen(js_name = isRecording)]
+    pub fn is_recording(&self) -> bool {
+        self.app.is_recording()
+    }
+
+    // 替换当前文档并回放日志，speed 默认 1，传入 Infinity 立即应用全部操作；结束时触发 "replayend"
+    pub fn replay(&self, log: &str, speed: Option<f64>) -> Result<(), JsValue> {
+        let log = OpLog::from_json(log)?;
+        self.app.replay(log, speed.unwrap_or(1.0));
+        Ok(())
+    }
+
+    // 导出正在进行的录制，encoding 为 "json"（默认，返回字符串）或 "binary"（返回 Uint8Array）；
+    // 没有在录制时返回 undefined
+    #[wasm_bindgen(js_name = exportSession)]
+    pub fn export_session(&self, encoding: Option<String>) -> Result<JsValue, JsValue> {
+        let encoding: SessionEncoding = match encoding {
+            Some(name) => serde_json::from_value(serde_json::Value::String(name)).map_err(EditingError::from)?,
+            None => SessionEncoding::default(),
+        };
+        let Some(data) = self.app.export_session(encoding)? else {
+            return Ok(JsValue::UNDEFINED);
+        };
+        Ok(match encoding {
+            SessionEncoding::Json => JsValue::from_str(&String::from_utf8_lossy(&data)),
+            SessionEncoding::Binary => Uint8Array::from(data.as_slice()).into(),
+        })
+    }
+
+    // data 为 exportSession 导出的字符串或 Uint8Array，其余同 replay
+    #[wasm_bindgen(js_name = replaySession)]
+    pub fn replay_session(&self, data: JsValue, speed: Option<f64>) -> Result<(), JsValue> {
+        let data = match data.as_string() {
+            Some(json) => json.into_bytes(),
+            None => data
+                .dyn_into::<Uint8Array>()
+                .map_err(|_| EditingError::InvalidSession("expected a string or Uint8Array".to_string()))?
+                .to_vec(),
+        };
+        Ok(self.app.replay_session(&data, speed.unwrap_or(1.0))?)
+    }
+
+    #[wasm_bindgen(js_name = stopReplay)]
+    pub fn stop_replay(&self) -> bool {
+        self.app.stop_replay()
+    }
+
+    #[wasm_bindgen(js_name = isReplaying)]
+    pub fn is_replaying(&self) -> bool {
+        self.app.is_replaying()
+    }
+
+    #[wasm_bindgen(js_name = pauseReplay)]
+    pub fn pause_replay(&self) -> bool {
+        self.app.pause_replay()
+    }
+
+    #[wasm_bindgen(js_name = resumeReplay)]
+    pub fn resume_replay(&self) -> bool {
+        self.app.resume_replay()
+    }
+
+    // position 为日志时间（毫秒）
+    #[wasm_bindgen(js_name = seekReplay)]
+    pub fn seek_replay(&self, position: f64) -> bool {
+        self.app.seek_replay(position)
+    }
+
+    #[wasm_bindgen(js_name = setReplaySpeed)]
+    pub fn set_replay_speed(&self, speed: f64) -> bool {
+        self.app.set_replay_speed(speed)
+    }
+
+    // { position, duration, speed, paused, finished }，没有加载回放时返回 null
+    #[wasm_bindgen(js_name = getReplayStatus)]
+    pub fn get_replay_status(&self) -> JsValue {
+        to_js_value(&self.app.replay_status())
+    }
+
+    #[wasm_bindgen(js_name = serializeHistory)]
+    pub fn serialize_history(&self) -> Result<String, JsValue> {
+        Ok(self.app.history.borrow_mut().serialize()?)
+    }
+
+    // 需在恢复文档之后调用，保证历史记录与对象状态一致
+    #[wasm_bindgen(js_name = restoreHistory)]
+    pub fn restore_history(&self, data: &str) -> Result<(), JsValue> {
+        Ok(self.app.history.borrow_mut().deserialize(data)?)
+    }
+
+    #[wasm_bindgen(js_name = historySummary)]
+    pub fn history_summary(&self) -> Result<JsValue, JsValue> {
+        self.app.history.borrow().get_history_summary()
+    }
+
+    // 跳回 historySummary 中某个条目完成后的状态
+    #[wasm_bindgen(js_name = jumpToCheckpoint)]
+    pub fn jump_to_checkpoint(&self, index: usize) -> bool {
+        self.app.history.borrow_mut().jump_to_checkpoint(index)
+    }
+}
+
+// 把本地操作交给 JS 回调发送
+struct JsSyncAdapter {
+    send: Function,
+    send_presence: Option<Function>,
+}
+
+impl SyncAdapter for JsSyncAdapter {
+    fn send(&self, operation: &Operation) {
+        if let Err(e) = self.send.call1(&JsValue::NULL, &to_js_value(operation)) {
+            log_error!("Sync send callback failed: {:?}", e);
+        }
+    }
+
+    fn send_presence(&self, presence: &Presence) {
+        let Some(send_presence) = &self.send_presence else {
+            return;
+        };
+        if let Err(e) = send_presence.call1(&JsValue::NULL, &to_js_value(presence)) {
+            log_error!("Sync presence callback failed: {:?}", e);
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = ObjectAnimation)]
+pub struct JsObjectAnimation {
+    animation: ObjectAnimation,
+}
+
+// 每个方法消耗自身并返回新的构建器，JS 侧按链式调用使用
+#[wasm_bindgen(js_class = ObjectAnimation)]
+impl JsObjectAnimation {
+    pub fn to(self, properties: JsValue) -> Result<JsObjectAnimation, JsValue> {
+        let properties: serde_json::Value = serde_wasm_bindgen::from_value(properties)?;
+        Ok(Self {
+            animation: self.animation.to(properties),
+        })
+    }
+
+    // 毫秒
+    pub fn duration(self, duration: f64) -> JsObjectAnimation {
+        Self {
+            animation: self.animation.duration(duration),
+        }
+    }
+
+    // 毫秒
+    pub fn delay(self, delay: f64) -> JsObjectAnimation {
+        Self {
+            animation: self.animation.delay(delay),
+        }
+    }
+
+    // "linear"、"ease-in"、"ease-out"、"ease-in-out"，以及 "-cubic"、"-elastic" 变体
+    pub fn easing(self, name: &str) -> JsObjectAnimation {
+        Self {
+            animation: self.animation.easing(name),
+        }
+    }
+
+    // options 可省略，例如 { stiffness: 170, damping: 26, mass: 1 }
+    pub fn spring(self, options: JsValue) -> Result<JsObjectAnimation, JsValue> {
+        let options: SpringOptions = if options.is_undefined() || options.is_null() {
+            SpringOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)?
+        };
+        Ok(Self {
+            animation: self.animation.spring(options),
+        })
+    }
+
+    // { x: 800 }，每秒的变化量
+    pub fn velocity(self, velocity: JsValue) -> Result<JsObjectAnimation, JsValue> {
+        let velocity: serde_json::Value = serde_wasm_bindgen::from_value(velocity)?;
+        Ok(Self {
+            animation: self.animation.velocity(velocity),
+        })
+    }
+
+    pub fn start(self) -> Result<JsAnimationHandle, JsValue> {
+        Ok(JsAnimationHandle {
+            handle: self.animation.start()?,
+        })
+    }
+}
+
+#[wasm_bindgen(js_name = AnimationHandle)]
+pub struct JsAnimationHandle {
+    handle: AnimationHandle,
+}
+
+#[wasm_bindgen(js_class = AnimationHandle)]
+impl JsAnimationHandle {
+    pub fn id(&self) -> u64 {
+        self.handle.id()
+    }
+
+    pub fn pause(&self) {
+        self.handle.pause();
+    }
+
+    pub fn resume(&self) {
+        self.handle.resume();
+    }
+
+    // 停在当前值，不会调用 onComplete
+    pub fn cancel(&self) {
+        self.handle.cancel();
+    }
+
+    // 下一帧直接跳到终点
+    pub fn finish(&self) {
+        self.handle.finish();
+    }
+
+    #[wasm_bindgen(js_name = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.handle.is_active()
+    }
+
+    #[wasm_bindgen(js_name = onComplete)]
+    pub fn on_complete(&self, callback: Function) {
+        self.handle.on_complete(move || {
+            if let Err(e) = callback.call0(&JsValue::NULL) {
+                log_error!("Animation onComplete callback failed: {:?}", e);
+            }
+        });
+    }
+}
+
+impl JsApp {
+    fn frame_hook(callback: Function) -> impl FnMut(&dyn Renderer, f64) + 'static {
+        move |renderer, delta| {
+            let proxy = RendererProxy::new();
+            let recorder = proxy.recorder();
+            if let Err(e) = callback.call2(&JsValue::NULL, &JsValue::from(proxy), &JsValue::from(delta)) {
+                log_error!("Frame hook failed: {:?}", e);
+                return;
+            }
+            recorder.replay(renderer);
+        }
+    }
+
+    fn event_name(name: &str) -> &str {
+        match AppEvent::from_js_name(name) {
+            Some(event) => event.into(),
+            None => name,
+        }
+    }
+}
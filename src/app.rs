@@ -1,72 +1,365 @@
 use std::cell::{RefCell, Cell};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::rc::Rc;
+use std::time::Duration;
+use serde_json::{json, Value};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::console;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::js_sys::Function;
+use web_sys::MouseEvent;
 
+use crate::animation::{AnimationHandle, AnimationManager, AnimationValue};
+use crate::bounding_box::BoundingBox;
 use crate::element::Renderable;
-use crate::events::{get_event_system, AppEvent};
-use crate::helper::request_animation_frame;
-use crate::history::History;
+use crate::clipboard::{Clipboard, ClipboardEntry};
+use crate::constraints::{Constraint, Constraints};
+use crate::error::{EditingError, EditingResult};
+use crate::events::{AppEvent, EventSystem};
+use crate::fonts::FontManager;
+use crate::image_cache::ImageCache;
+use crate::element::{create_default_element, ObjectId};
+use crate::helper::{easing, now_ms, request_animation_frame, to_js_value};
+use crate::history::{History, HistoryItem, ObjectHistoryItem};
+use crate::idle_scheduler::IdleScheduler;
+use crate::layout::{compute_layout, LayoutOptions};
+use crate::log::{log_debug, log_error, log_info, log_warn};
 use crate::object_manager::ObjectManager;
-use crate::scene_manager::SceneManager;
-use crate::scene_manager::SceneManagerOptions;
+use crate::op_log::OpLogRecorder;
+use crate::render_control::{RenderControl, UpdateBody, UpdateMessage, UpdatePriority, UpdateType};
+use crate::render_loop::{RenderLoop, RenderLoopOptions};
+use crate::render_stats::RenderStats;
+use crate::renderer::Renderer;
+use crate::scene_manager::{DocumentUnits, GridOptions, ProgressiveOptions, SceneManagerOptions, ScrollbarOptions};
+use crate::sync::SyncSession;
+use crate::scene_manager::{FrameHookId, FrameHookStage, HitTestMode, PixelColor, SceneManager};
+
+mod animate;
+mod bindings;
+mod boolean;
+mod capture;
+mod components;
+mod document;
+mod export;
+mod fonts;
+mod frames;
+mod images;
+mod inertia;
+mod measure;
+mod options;
+mod path_edit;
+mod recording;
+mod scripts;
+mod sprites;
+mod styles;
+mod sync;
+mod text_edit;
+mod tools;
+mod transform;
+mod video;
+
+pub use animate::{ObjectAnimation, SpringOptions};
+pub use options::AppOptions;
+pub use scripts::FrameScriptId;
+pub use styles::StyleKind;
+pub use tools::Tool;
+
+use bindings::DataBindings;
+use capture::PointerCapture;
+use recording::ActiveReplay;
+use scripts::FrameScripts;
+use styles::StyleLibrary;
+use measure::MeasureState;
+use path_edit::PathEdit;
+use text_edit::TextEditor;
+use tools::{ActiveErase, ActivePan, ActiveShape, ActiveStroke};
+
+// 每帧用于应用后台更新的时间上限，输入和动画更新不受限制
+const BACKGROUND_UPDATE_BUDGET_MS: f64 = 4.0;
+
+// 原地复制时副本相对原对象的偏移
+const DUPLICATE_OFFSET: (f64, f64) = (10.0, 10.0);
+
+// 空闲任务的名字，同名任务排队期间只执行最新的一次
+const IDLE_HIT_CANVAS: &str = "hit_canvas";
+const IDLE_AUTOSAVE: &str = "autosave";
+const IDLE_WARM_POOL: &str = "warm_pool";
+
+// (id, 起点, 终点)
+type LayoutMove = (String, (f64, f64), (f64, f64));
+
+// 进行中的自动布局过渡，结束时把起点到终点的移动记录为一个历史单元
+#[derive(Debug)]
+struct LayoutTransition {
+    moves: Vec<LayoutMove>,
+    // 毫秒
+    started: f64,
+    duration: f64,
+}
 
 #[derive(Debug, Clone)]
 pub struct App {
     pub history: Rc<RefCell<History>>,
     pub object_manager: Rc<RefCell<ObjectManager>>,
     pub scene_manager: Rc<RefCell<SceneManager>>,
+    pub animation_manager: Rc<RefCell<AnimationManager>>,
+    pub events: Rc<EventSystem>,
+    pub render_control: Rc<RefCell<RenderControl>>,
+    render_loop: Rc<RefCell<RenderLoop>>,
     render_requested: Rc<Cell<bool>>,
+    refine_scheduled: Rc<Cell<bool>>,
+    selection: Rc<RefCell<Vec<String>>>,
+    clipboard: Rc<RefCell<Clipboard>>,
+    fonts: Rc<RefCell<FontManager>>,
+    images: Rc<RefCell<ImageCache>>,
+    idle_scheduler: IdleScheduler,
+    constraints: Rc<RefCell<Constraints>>,
+    autosave: Rc<RefCell<Option<Function>>>,
+    layout_transition: Rc<RefCell<Option<LayoutTransition>>>,
+    tool: Rc<RefCell<Tool>>,
+    stroke: Rc<RefCell<Option<ActiveStroke>>>,
+    erasing: Rc<RefCell<Option<ActiveErase>>>,
+    shape: Rc<RefCell<Option<ActiveShape>>>,
+    panning: Rc<RefCell<Option<ActivePan>>>,
+    text_editor: Rc<RefCell<Option<TextEditor>>>,
+    path_edit: Rc<RefCell<Option<PathEdit>>>,
+    measure: Rc<RefCell<MeasureState>>,
+    playing_videos: Rc<RefCell<HashSet<String>>>,
+    sprite_playback: Rc<RefCell<HashMap<String, AnimationHandle>>>,
+    suppress_context_menu: Rc<Cell<bool>>,
+    document_dirty: Rc<Cell<bool>>,
+    // 已排队的 "documentchange" 通知，以及其中是否需要附带 "dirtychange"
+    document_change_pending: Rc<Cell<bool>>,
+    dirty_pending: Rc<Cell<bool>>,
+    sync: Rc<RefCell<Option<SyncSession>>>,
+    recorder: Rc<RefCell<Option<OpLogRecorder>>>,
+    replay: Rc<RefCell<Option<Rc<RefCell<ActiveReplay>>>>>,
+    frame_scripts: Rc<RefCell<FrameScripts>>,
+    bindings: Rc<RefCell<DataBindings>>,
+    styles: Rc<RefCell<StyleLibrary>>,
+    input_capture: Rc<RefCell<Vec<String>>>,
+    pointer_capture: Rc<RefCell<Option<PointerCapture>>>,
+    pan_inertia: Rc<Cell<bool>>,
+    inertia: Rc<RefCell<Option<AnimationHandle>>>,
 }
 
 impl App {
-    pub fn new(canvas_id: String) -> Self {
+    pub fn new(canvas_id: String, options: AppOptions) -> Self {
         let object_manager = Rc::new(RefCell::new(ObjectManager::new()));
-        let mut options = SceneManagerOptions::default();
-        options.canvas_id = canvas_id;
-        options.object_manager = object_manager.clone();
+        let mut scene_options = SceneManagerOptions::default();
+        scene_options.canvas_id = canvas_id;
+        scene_options.object_manager = object_manager.clone();
+        options.apply_to(&mut scene_options);
+
+        let scene_manager = Rc::new(RefCell::new(SceneManager::new(scene_options)));
 
-        let scene_manager = Rc::new(RefCell::new(SceneManager::new(options)));
+        let mut history = History::new();
+        history.set_limits(options.max_history_units, options.max_history_bytes);
+
+        let mut render_control = RenderControl::new();
+        render_control.set_flush_interval(Duration::from_secs_f64(
+            options.update_interval_ms.max(0.0) / 1000.0,
+        ));
 
         Self {
-            history: Rc::new(RefCell::new(History::new())),
+            history: Rc::new(RefCell::new(history)),
             object_manager: object_manager,
             scene_manager: scene_manager,
+            animation_manager: Rc::new(RefCell::new(AnimationManager::new())),
+            events: Rc::new(EventSystem::new()),
+            render_control: Rc::new(RefCell::new(render_control)),
+            render_loop: Rc::new(RefCell::new(RenderLoop::new())),
             render_requested: Rc::new(Cell::new(false)),
+            refine_scheduled: Rc::new(Cell::new(false)),
+            selection: Rc::new(RefCell::new(Vec::new())),
+            clipboard: Rc::new(RefCell::new(Clipboard::new())),
+            fonts: Rc::new(RefCell::new(FontManager::new())),
+            images: Rc::new(RefCell::new(ImageCache::new())),
+            idle_scheduler: IdleScheduler::new(),
+            constraints: Rc::new(RefCell::new(Constraints::new())),
+            autosave: Rc::new(RefCell::new(None)),
+            layout_transition: Rc::new(RefCell::new(None)),
+            tool: Rc::new(RefCell::new(Tool::default())),
+            stroke: Rc::new(RefCell::new(None)),
+            erasing: Rc::new(RefCell::new(None)),
+            shape: Rc::new(RefCell::new(None)),
+            panning: Rc::new(RefCell::new(None)),
+            text_editor: Rc::new(RefCell::new(None)),
+            path_edit: Rc::new(RefCell::new(None)),
+            measure: Rc::new(RefCell::new(MeasureState::default())),
+            playing_videos: Rc::new(RefCell::new(HashSet::new())),
+            sprite_playback: Rc::new(RefCell::new(HashMap::new())),
+            suppress_context_menu: Rc::new(Cell::new(options.suppress_context_menu)),
+            document_dirty: Rc::new(Cell::new(false)),
+            document_change_pending: Rc::new(Cell::new(false)),
+            dirty_pending: Rc::new(Cell::new(false)),
+            sync: Rc::new(RefCell::new(None)),
+            recorder: Rc::new(RefCell::new(None)),
+            replay: Rc::new(RefCell::new(None)),
+            frame_scripts: Rc::new(RefCell::new(FrameScripts::default())),
+            bindings: Rc::new(RefCell::new(DataBindings::default())),
+            styles: Rc::new(RefCell::new(StyleLibrary::new())),
+            input_capture: Rc::new(RefCell::new(Vec::new())),
+            pointer_capture: Rc::new(RefCell::new(None)),
+            pan_inertia: Rc::new(Cell::new(options.pan_inertia)),
+            inertia: Rc::new(RefCell::new(None)),
         }
     }
 
-    pub fn init(&mut self) -> Result<(), JsValue> {
+    pub fn init(&mut self) -> EditingResult<()> {
         self.scene_manager.borrow_mut().init()?;
-        self.scene_manager.borrow_mut().set_context_type("2d")?;
 
         self.scene_manager.borrow_mut().attach(self);
         self.history.borrow_mut().attach(&self);
         self.object_manager.borrow_mut().attach(self);
 
-        let _ = get_event_system().emit(AppEvent::READY.into(), &JsValue::NULL);
+        let app = self.clone();
+        self.scene_manager
+            .borrow()
+            .observe_resize(move || app.handle_resize())?;
+        self.install_object_events();
+
+        let _ = self.events.emit(AppEvent::READY.into(), &JsValue::NULL);
+        log_info!("App initialized");
         Ok(())
     }
 
     pub fn request_render(&self) {
-        let render_requested = self.render_requested.clone();
-        let scene_manager = self.scene_manager.clone();
+        if self.render_requested.get() {
+            return;
+        }
+        self.render_requested.set(true);
+
+        // 渲染循环运行时由循环统一处理，不再单独注册 rAF
+        if self.render_loop.borrow().is_active() {
+            return;
+        }
 
-        let closure = Closure::wrap(Box::new(move || {
-            if render_requested.get() {
-                scene_manager.borrow_mut().render();
-                render_requested.set(false);
+        let app = self.clone();
+        let closure = Closure::once_into_js(move || {
+            if app.render_requested.get() {
+                app.run_frame_scripts();
+                let deferred = app.apply_pending_updates();
+                let playing = app.refresh_videos();
+                app.prepare_frame();
+                app.scene_manager.borrow().render();
+                app.render_requested.set(false);
+                app.schedule_hit_refresh();
+                // 播放中的视频、移动中的协作者指针和帧脚本每帧都要重绘
+                if deferred
+                    || playing
+                    || app.has_frame_scripts()
+                    || app.scene_manager.borrow().is_presence_animating()
+                    || app.is_measure_lingering()
+                    || app.scene_manager.borrow().is_scrollbar_visible()
+                {
+                    app.request_render();
+                }
             }
-        }) as Box<dyn FnMut()>);
+            app.schedule_refinement();
+        });
+        request_animation_frame(closure.unchecked_ref());
+    }
 
-        if !self.render_requested.get() {
-            self.render_requested.set(true);
-            request_animation_frame(closure.as_ref().unchecked_ref());
+    // 提交一条更新消息，在下一帧渲染前应用；ForceUpdate 会立即应用已缓存的消息
+    pub fn post_update(&self, message: UpdateMessage) {
+        if let UpdateMessage::Update(body) = &message {
+            self.broadcast(&[HistoryItem::from(body)], false);
         }
+        let forced = {
+            let mut render_control = self.render_control.borrow_mut();
+            render_control.add_message(message);
+            render_control.is_forced()
+        };
+        if forced {
+            self.apply_pending_updates();
+        }
+        self.request_render();
+    }
+
+    // 返回 true 表示还有未应用的更新（未到刷新间隔或超出帧预算），需要再安排一帧
+    fn apply_pending_updates(&self) -> bool {
+        let messages = {
+            let mut render_control = self.render_control.borrow_mut();
+            if !render_control.is_due() {
+                return !render_control.is_empty();
+            }
+            render_control.drain()
+        };
+
+        let start = now_ms();
+        let mut messages = messages.into_iter();
+        while let Some(message) = messages.next() {
+            if let UpdateMessage::Update(body) = &message {
+                if body.priority == UpdatePriority::Background
+                    && now_ms() - start > BACKGROUND_UPDATE_BUDGET_MS
+                {
+                    let rest = std::iter::once(message).chain(messages).collect();
+                    self.render_control.borrow_mut().requeue(rest);
+                    return true;
+                }
+            }
+            self.apply_update(&message);
+        }
+        false
+    }
+
+    // 限制排队更新的应用频率，如外部数据同步只需 30Hz 时传入 33ms
+    pub fn set_update_interval(&self, interval_ms: f64) {
+        let interval = Duration::from_secs_f64(interval_ms.max(0.0) / 1000.0);
+        self.render_control.borrow_mut().set_flush_interval(interval);
+    }
+
+    fn apply_update(&self, message: &UpdateMessage) {
+        let UpdateMessage::Update(body) = message else {
+            return;
+        };
+        match body.update_type {
+            UpdateType::SceneUpdate => {
+                if let Err(e) = self.scene_manager.borrow_mut().update_scene(body.data.clone()) {
+                    log_error!("Failed to apply scene update: {}", e);
+                }
+            }
+            UpdateType::ObjectUpdate(_) => self
+                .object_manager
+                .borrow_mut()
+                .update_object_from_message(std::slice::from_ref(message)),
+        }
+    }
+
+    // 渲染循环未运行时，逐帧继续完成分帧绘制
+    fn schedule_refinement(&self) {
+        if self.refine_scheduled.get()
+            || self.render_loop.borrow().is_active()
+            || !self.scene_manager.borrow().is_refining()
+        {
+            return;
+        }
+        self.refine_scheduled.set(true);
+
+        let app = self.clone();
+        let closure = Closure::once_into_js(move || {
+            app.refine_scheduled.set(false);
+            // 已有新的整帧渲染在排队，剩余部分交给它重绘
+            if app.render_requested.get() {
+                return;
+            }
+            app.scene_manager.borrow().refine();
+            app.schedule_refinement();
+        });
+        request_animation_frame(closure.unchecked_ref());
+    }
+
+    pub fn set_progressive_options(&self, options: ProgressiveOptions) {
+        self.scene_manager
+            .borrow_mut()
+            .set_progressive_options(options);
+        self.request_render();
+    }
 
-        closure.forget();
+    pub fn progressive_options(&self) -> ProgressiveOptions {
+        self.scene_manager.borrow().progressive_options()
     }
 
     pub fn is_support_type(&self, context_type: &str) -> bool {
@@ -85,29 +378,973 @@ impl App {
         }
     }
 
+    pub fn set_batching(&self, enabled: bool) {
+        self.scene_manager.borrow_mut().set_batching(enabled);
+        self.request_render();
+    }
+
+    pub fn set_supersampling(&self, factor: f64) -> EditingResult<()> {
+        self.scene_manager.borrow_mut().set_supersampling(factor)?;
+        self.request_render();
+        Ok(())
+    }
+
+    pub fn supersampling(&self) -> f64 {
+        self.scene_manager.borrow().supersampling()
+    }
+
+    // 在 worker 中绘制显示画布，script_url 为加载了本模块并创建 RenderWorkerHost 的 worker 脚本
+    pub fn set_render_worker(&self, script_url: Option<String>) -> EditingResult<()> {
+        self.scene_manager.borrow_mut().set_render_worker(script_url)
+    }
+
+    pub fn set_background(&self, background: Option<String>) {
+        self.scene_manager.borrow_mut().set_background(background);
+        self.request_render();
+    }
+
+    // 颜色变量（如 "$primary"）在绘制时按主题解析，切换主题后整个场景以新颜色重绘，对象数据不变
+    pub fn set_theme(&self, theme: HashMap<String, String>) {
+        self.scene_manager.borrow().theme().set(theme);
+        self.request_render();
+    }
+
+    pub fn theme(&self) -> HashMap<String, String> {
+        self.scene_manager.borrow().theme().colors()
+    }
+
+    pub fn set_grid(&self, options: GridOptions) {
+        self.scene_manager.borrow_mut().set_grid(options);
+        self.request_render();
+    }
+
+    pub fn grid_options(&self) -> GridOptions {
+        self.scene_manager.borrow().grid_options()
+    }
+
+    pub fn set_scrollbars(&self, options: ScrollbarOptions) {
+        self.scene_manager.borrow().set_scrollbars(options);
+        self.request_render();
+    }
+
+    pub fn scrollbar_options(&self) -> ScrollbarOptions {
+        self.scene_manager.borrow().scrollbar_options()
+    }
+
+    // 只影响测量、尺寸标签和按单位换算的坐标与导出，对象的世界坐标不变
+    pub fn set_units(&self, units: DocumentUnits) {
+        self.scene_manager.borrow_mut().set_units(units);
+        self.request_render();
+    }
+
+    pub fn units(&self) -> DocumentUnits {
+        self.scene_manager.borrow().units()
+    }
+
+    pub fn set_zoom_limits(&self, min_zoom: f64, max_zoom: f64) {
+        self.scene_manager
+            .borrow_mut()
+            .set_zoom_limits(min_zoom, max_zoom);
+        self.request_render();
+    }
+
+    pub fn zoom_limits(&self) -> (f64, f64) {
+        self.scene_manager.borrow().zoom_limits()
+    }
+
+    pub fn set_hit_resolution(&self, resolution: f64) -> EditingResult<()> {
+        self.scene_manager.borrow_mut().set_hit_resolution(resolution)?;
+        self.request_render();
+        Ok(())
+    }
+
+    pub fn hit_resolution(&self) -> f64 {
+        self.scene_manager.borrow().hit_resolution()
+    }
+
+    pub fn set_record_camera_history(&self, enabled: bool) {
+        self.scene_manager.borrow_mut().set_record_camera_history(enabled);
+    }
+
+    pub fn view_back(&self) -> bool {
+        self.scene_manager.borrow_mut().view_back()
+    }
+
+    pub fn view_forward(&self) -> bool {
+        self.scene_manager.borrow_mut().view_forward()
+    }
+
+    // 屏幕像素，缩放时保持不变
+    pub fn set_hit_tolerance(&self, tolerance: f64) {
+        self.scene_manager.borrow_mut().set_hit_tolerance(tolerance);
+        self.request_render();
+    }
+
+    pub fn hit_tolerance(&self) -> f64 {
+        self.scene_manager.borrow().hit_tolerance()
+    }
+
+    pub fn set_hit_test_mode(&self, mode: HitTestMode) {
+        self.scene_manager.borrow_mut().set_hit_test_mode(mode);
+        self.request_render();
+    }
+
+    pub fn hit_test_mode(&self) -> HitTestMode {
+        self.scene_manager.borrow().hit_test_mode()
+    }
+
+    // 吸管工具：读取已绘制的显示画布而非 hit 画布，size 大于 1 时取 size x size 区域的平均色
+    pub fn pick_color(&self, screen_x: f64, screen_y: f64, size: u32) -> EditingResult<Option<PixelColor>> {
+        self.scene_manager.borrow().pick_color(screen_x, screen_y, size)
+    }
+
+    // 鼠标按下/抬起命中对象时通知 JS，payload 带有对象的标签和元数据
+    fn install_object_events(&self) {
+        let app = self.clone();
+        let mut scene_manager = self.scene_manager.borrow_mut();
+        scene_manager.set_on_mouse_down(move |event| app.handle_pointer_down(event));
+        let app = self.clone();
+        scene_manager.set_on_mouse_move(move |event| app.handle_pointer_move(event));
+        let app = self.clone();
+        scene_manager.set_on_mouse_up(move |event| app.handle_pointer_up(event));
+        let app = self.clone();
+        scene_manager.set_on_mouse_leave(move |event| app.handle_pointer_leave(event));
+        let app = self.clone();
+        scene_manager.set_on_double_click(move |event| app.handle_double_click(event));
+        let app = self.clone();
+        scene_manager.set_on_context_menu(move |event| app.handle_context_menu(event));
+    }
+
+    pub fn set_suppress_context_menu(&self, suppress: bool) {
+        self.suppress_context_menu.set(suppress);
+    }
+
+    // 右键时把命中的对象和坐标交给宿主，由宿主渲染自己的菜单；没有命中对象时 id 为 null
+    fn handle_context_menu(&self, event: &MouseEvent) {
+        if self.suppress_context_menu.get() {
+            event.prevent_default();
+        }
+        let (object, world) = match self.scene_manager.try_borrow() {
+            Ok(scene_manager) => (
+                scene_manager.get_trigger_object(event),
+                scene_manager.client_to_world(event.client_x() as f64, event.client_y() as f64),
+            ),
+            Err(_) => return,
+        };
+        let object = object.map(|object| object.borrow().event_payload());
+        let (world_x, world_y) = world.unzip();
+        let payload = serde_json::json!({
+            "id": object.as_ref().map(|object| object["id"].clone()),
+            "object": object,
+            "x": world_x,
+            "y": world_y,
+            "screenX": event.offset_x(),
+            "screenY": event.offset_y(),
+            "clientX": event.client_x(),
+            "clientY": event.client_y(),
+        });
+        let _ = self.events.emit(AppEvent::CONTEXT_MENU.into(), &to_js_value(&payload));
+    }
+
+    fn emit_object_event(&self, event_type: AppEvent, event: &MouseEvent) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        let object = self.scene_manager.try_borrow().ok()?.get_trigger_object(event)?;
+        self.emit_pointer_event(event_type, &object, event);
+        Some(object)
+    }
+
+    fn handle_resize(&self) {
+        let resized = match self.scene_manager.try_borrow_mut() {
+            Ok(mut scene_manager) => scene_manager.handle_resize(),
+            Err(_) => return,
+        };
+        match resized {
+            Ok(Some((width, height))) => {
+                let payload = to_js_value(&serde_json::json!({
+                    "width": width,
+                    "height": height,
+                }));
+                let _ = self.events.emit(AppEvent::RESIZE.into(), &payload);
+                self.request_render();
+            }
+            Ok(None) => {}
+            Err(e) => log_error!("Failed to handle resize: {}", e),
+        }
+    }
+
+    pub fn destroy(&self) {
+        self.render_loop.borrow_mut().stop();
+        self.animation_manager.borrow_mut().clear_all_animations();
+        self.layout_transition.borrow_mut().take();
+        self.stroke.borrow_mut().take();
+        self.erasing.borrow_mut().take();
+        self.shape.borrow_mut().take();
+        self.panning.borrow_mut().take();
+        self.playing_videos.borrow_mut().clear();
+        self.sprite_playback.borrow_mut().clear();
+        self.images.borrow_mut().clear();
+        self.destroy_text_editor();
+        self.path_edit.borrow_mut().take();
+        *self.measure.borrow_mut() = MeasureState::default();
+        self.render_requested.set(false);
+        self.idle_scheduler.clear();
+        self.autosave.borrow_mut().take();
+        self.constraints.borrow_mut().clear();
+        self.document_dirty.set(false);
+        self.dirty_pending.set(false);
+        self.sync.borrow_mut().take();
+        self.recorder.borrow_mut().take();
+        self.replay.borrow_mut().take();
+        *self.frame_scripts.borrow_mut() = FrameScripts::default();
+        *self.bindings.borrow_mut() = DataBindings::default();
+        self.styles.borrow_mut().clear();
+        self.input_capture.borrow_mut().clear();
+        self.pointer_capture.borrow_mut().take();
+        self.inertia.borrow_mut().take();
+
+        self.selection.borrow_mut().clear();
+        self.clipboard.borrow_mut().set(Vec::new());
+        self.object_manager.borrow_mut().destroy();
+        self.history.borrow_mut().clear();
+        self.history.borrow_mut().detach();
+        self.events.clear_all_listeners();
+
+        // 可能在帧回调或鼠标回调中被调用，Closure 延后到当前回调结束后再释放
+        let render_loop = self.render_loop.clone();
+        let scene_manager = self.scene_manager.clone();
+        spawn_local(async move {
+            render_loop.borrow_mut().uninstall();
+            scene_manager.borrow_mut().destroy();
+        });
+    }
+
+    // 在浏览器空闲时执行，不占用渲染帧
+    pub fn schedule_idle(&self, key: impl Into<String>, task: impl FnOnce() + 'static) {
+        self.idle_scheduler.schedule(key, task);
+    }
+
+    // 拾取前仍会按需重绘，这里只是提前在空闲时把 hit 画布准备好
+    fn schedule_hit_refresh(&self) {
+        let scene_manager = self.scene_manager.clone();
+        self.idle_scheduler.schedule(IDLE_HIT_CANVAS, move || {
+            if let Ok(scene_manager) = scene_manager.try_borrow() {
+                scene_manager.ensure_hit_canvas();
+            }
+        });
+    }
+
+    // 历史变化后在空闲时序列化文档和历史，交给回调保存
+    pub fn set_autosave(&self, callback: Option<Function>) {
+        *self.autosave.borrow_mut() = callback;
+        if self.autosave.borrow().is_none() {
+            self.idle_scheduler.cancel(IDLE_AUTOSAVE);
+        }
+    }
+
+    pub fn schedule_autosave(&self) {
+        if self.autosave.borrow().is_none() {
+            return;
+        }
+        let app = self.clone();
+        self.idle_scheduler.schedule(IDLE_AUTOSAVE, move || app.autosave());
+    }
+
+    fn autosave(&self) {
+        let Some(callback) = self.autosave.borrow().clone() else {
+            return;
+        };
+        let history = match self.history.try_borrow_mut() {
+            Ok(mut history) => history.serialize(),
+            Err(_) => return self.schedule_autosave(),
+        };
+        let history = match history {
+            Ok(history) => history,
+            Err(e) => {
+                log_error!("Autosave failed: {}", e);
+                return;
+            }
+        };
+        let objects: Vec<Value> = self
+            .object_manager
+            .borrow()
+            .iter()
+            .map(|(_, object)| object.borrow().to_value())
+            .collect();
+        let styles = self.styles();
+        let payload = to_js_value(&serde_json::json!({
+            "objects": objects,
+            "styles": styles,
+            "history": history,
+        }));
+        if let Err(e) = callback.call1(&JsValue::NULL, &payload) {
+            log_error!("Autosave callback failed: {:?}", e);
+        }
+    }
+
+    // 回放历史时 History 已被借用，回放引起的修改本来也不需要记录
+    pub fn record_history(&self, item: HistoryItem) {
+        if let Ok(mut history) = self.history.try_borrow_mut() {
+            history.push(item);
+        }
+    }
+
+    pub fn undo(&self) -> bool {
+        self.history.borrow_mut().undo()
+    }
+
+    pub fn redo(&self) -> bool {
+        self.history.borrow_mut().redo()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.borrow().can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.borrow().can_redo()
+    }
+
     pub fn reset_to_initial_state(&self) {
+        self.selection.borrow_mut().clear();
         self.object_manager.borrow_mut().clear();
         self.scene_manager.borrow_mut().reset_to_initial_state();
     }
 }
 
 impl App {
-    pub fn add(&self, mut object: impl Renderable + 'static) {
-        object.attach(self);
-        self.object_manager.borrow_mut().add(Box::new(object));
+    // 从对象池取出一个元素并用 data 设置属性，不记录历史
+    pub fn acquire(&self, element_type: &str, data: Value) -> EditingResult<String> {
+        self.object_manager
+            .borrow_mut()
+            .acquire(element_type, data, || create_default_element(element_type))
+    }
+
+    // 把元素放回对象池，不记录历史
+    pub fn release(&self, id: &str) -> bool {
+        self.selection.borrow_mut().retain(|selected| selected != id);
+        // 池中的对象会以同一个 id 被重新取出，不能带着旧约束
+        self.constraints.borrow_mut().remove(id);
+        self.object_manager.borrow_mut().release(id)
+    }
+
+    // 每种类型最多保留的回收对象数，超出的部分立即丢弃
+    pub fn set_max_pool_size(&self, size: usize) {
+        self.object_manager.borrow_mut().set_max_pool_size(size);
+    }
+
+    // 在空闲时预先创建元素放入对象池，count 为池中希望保有的数量
+    pub fn warm_pool(&self, element_type: &str, count: usize) -> EditingResult<()> {
+        // 先创建一个，类型未注册时直接返回错误
+        let object = create_default_element(element_type)?;
+        if self.object_manager.borrow().pool_size(element_type) >= count {
+            return Ok(());
+        }
+        self.object_manager.borrow_mut().fill_pool(object);
+        self.schedule_warm_pool(element_type.to_string(), count);
+        Ok(())
+    }
+
+    fn schedule_warm_pool(&self, element_type: String, count: usize) {
+        let app = self.clone();
+        let key = format!("{}:{}", IDLE_WARM_POOL, element_type);
+        self.idle_scheduler.schedule(key, move || {
+            let Ok(mut object_manager) = app.object_manager.try_borrow_mut() else {
+                app.schedule_warm_pool(element_type, count);
+                return;
+            };
+            if object_manager.pool_size(&element_type) >= count {
+                return;
+            }
+            let filled = match create_default_element(&element_type) {
+                Ok(object) => object_manager.fill_pool(object),
+                Err(e) => {
+                    log_error!("Failed to warm pool: {}", e);
+                    return;
+                }
+            };
+            drop(object_manager);
+            if filled {
+                app.schedule_warm_pool(element_type, count);
+            }
+        });
+    }
+
+    pub fn find_by_tag(&self, tag: &str) -> Vec<String> {
+        self.object_manager.borrow().find_by_tag(tag)
+    }
+
+    pub fn select(&self, ids: Vec<String>) {
+        let object_manager = self.object_manager.borrow();
+        *self.selection.borrow_mut() = ids
+            .into_iter()
+            .filter(|id| object_manager.contains(id))
+            .collect();
+        self.notify_presence();
+    }
+
+    pub fn selection(&self) -> Vec<String> {
+        self.selection.borrow().clone()
+    }
+
+    pub fn clear_selection(&self) {
+        self.selection.borrow_mut().clear();
+        self.notify_presence();
+    }
+
+    // 复制选中对象，返回复制的数量；同时尝试写入系统剪贴板
+    pub fn copy_selection(&self) -> usize {
+        let entries: Vec<ClipboardEntry> = {
+            let object_manager = self.object_manager.borrow();
+            self.selection
+                .borrow()
+                .iter()
+                .filter_map(|id| object_manager.get(id))
+                .map(|object| ClipboardEntry::from_object(object.borrow().as_ref()))
+                .collect()
+        };
+        let count = entries.len();
+        if count == 0 {
+            return 0;
+        }
+
+        self.clipboard.borrow_mut().set(entries);
+        match self.clipboard.borrow().to_text() {
+            Ok(text) => write_system_clipboard(&text),
+            Err(e) => log_warn!("Failed to serialize clipboard: {}", e),
+        }
+        count
+    }
+
+    pub fn cut(&self) -> usize {
+        let count = self.copy_selection();
+        if count == 0 {
+            return 0;
+        }
+
+        let ids = std::mem::take(&mut *self.selection.borrow_mut());
+        self.history.borrow_mut().begin_transaction("Cut");
+        for id in ids.iter() {
+            self.object_manager.borrow_mut().remove(id);
+        }
+        self.history.borrow_mut().commit();
+        self.notify_presence();
+        self.request_render();
+        count
+    }
+
+    // 粘贴剪贴板内容，第 n 次粘贴偏移 n * offset；新对象成为当前选中
+    pub fn paste(&self, offset: (f64, f64)) -> EditingResult<Vec<String>> {
+        let (entries, n) = {
+            let mut clipboard = self.clipboard.borrow_mut();
+            if clipboard.is_empty() {
+                return Ok(Vec::new());
+            }
+            let n = clipboard.next_paste() as f64;
+            (clipboard.entries().to_vec(), n)
+        };
+        let ids = self.insert_entries(&entries, offset.0 * n, offset.1 * n, "Paste")?;
+        *self.selection.borrow_mut() = ids.clone();
+        self.notify_presence();
+        Ok(ids)
+    }
+
+    pub fn duplicate(&self, id: &str) -> EditingResult<String> {
+        let object = self
+            .object_manager
+            .borrow()
+            .get(id)
+            .ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        let entry = ClipboardEntry::from_object(object.borrow().as_ref());
+        let (dx, dy) = DUPLICATE_OFFSET;
+        let ids = self.insert_entries(&[entry], dx, dy, "Duplicate")?;
+        Ok(ids.into_iter().next().unwrap_or_default())
+    }
+
+    // 从系统剪贴板读取本编辑器格式的数据后粘贴
+    pub async fn paste_from_system_clipboard(&self, offset: (f64, f64)) -> EditingResult<Vec<String>> {
+        if let Some(text) = read_system_clipboard().await {
+            if let Some(entries) = Clipboard::parse_text(&text) {
+                self.clipboard.borrow_mut().set(entries);
+            }
+        }
+        self.paste(offset)
+    }
+
+    // 先全部构建成功再插入，作为一个历史单元
+    fn insert_entries(
+        &self,
+        entries: &[ClipboardEntry],
+        dx: f64,
+        dy: f64,
+        label: &str,
+    ) -> EditingResult<Vec<String>> {
+        let elements = entries
+            .iter()
+            .map(|entry| entry.instantiate(dx, dy))
+            .collect::<EditingResult<Vec<_>>>()?;
+
+        let ids = elements
+            .iter()
+            .map(|element| element.id().value().to_string())
+            .collect();
+        // 任何一个加入失败时撤回已加入的副本，不留下半次粘贴
+        self.history.borrow_mut().begin_transaction(label);
+        let added = elements
+            .into_iter()
+            .try_for_each(|element| self.object_manager.borrow_mut().add(element));
+        if let Err(e) = added {
+            self.history.borrow_mut().rollback();
+            return Err(e);
+        }
+        self.history.borrow_mut().commit();
+        self.request_render();
+        Ok(ids)
+    }
+}
+
+fn write_system_clipboard(text: &str) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let promise = window.navigator().clipboard().write_text(text);
+    spawn_local(async move {
+        if let Err(e) = JsFuture::from(promise).await {
+            log_debug!("System clipboard write rejected: {:?}", e);
+        }
+    });
+}
+
+async fn read_system_clipboard() -> Option<String> {
+    let promise = web_sys::window()?.navigator().clipboard().read_text();
+    match JsFuture::from(promise).await {
+        Ok(text) => text.as_string(),
+        Err(e) => {
+            log_debug!("System clipboard read rejected: {:?}", e);
+            None
+        }
+    }
+}
+
+impl App {
+    pub fn start(&self, options: RenderLoopOptions) {
+        if !self.render_loop.borrow().has_closures() {
+            let app = self.clone();
+            let frame_closure = Closure::wrap(Box::new(move |timestamp: f64| {
+                app.on_animation_frame(timestamp);
+            }) as Box<dyn FnMut(f64)>);
+
+            let render_loop = self.render_loop.clone();
+            let visibility_closure = Closure::wrap(Box::new(move || {
+                let hidden = web_sys::window()
+                    .and_then(|w| w.document())
+                    .map(|d| d.visibility_state() == web_sys::VisibilityState::Hidden)
+                    .unwrap_or(false);
+                render_loop.borrow_mut().set_hidden(hidden);
+            }) as Box<dyn FnMut()>);
+
+            self.render_loop
+                .borrow_mut()
+                .install(frame_closure, visibility_closure);
+        }
+        self.render_loop.borrow_mut().start(options);
+    }
+
+    pub fn stop(&self) {
+        self.render_loop.borrow_mut().stop();
+        // 循环运行期间的重绘请求没有单独注册 rAF，停止后改为单次渲染处理，否则之后的请求都会被跳过
+        if self.render_requested.replace(false) {
+            self.request_render();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.render_loop.borrow().is_running()
+    }
+
+    pub fn set_fps_cap(&self, fps_cap: Option<f64>) {
+        self.render_loop.borrow_mut().set_fps_cap(fps_cap);
+    }
+
+    pub fn before_render(&self, hook: impl FnMut(&dyn Renderer, f64) + 'static) -> FrameHookId {
+        self.scene_manager
+            .borrow()
+            .add_frame_hook(FrameHookStage::BeforeRender, hook)
+    }
+
+    pub fn after_render(&self, hook: impl FnMut(&dyn Renderer, f64) + 'static) -> FrameHookId {
+        self.scene_manager
+            .borrow()
+            .add_frame_hook(FrameHookStage::AfterRender, hook)
+    }
+
+    pub fn remove_frame_hook(&self, id: FrameHookId) -> bool {
+        self.scene_manager.borrow().remove_frame_hook(id)
+    }
+
+    pub fn get_render_stats(&self) -> RenderStats {
+        self.scene_manager.borrow().get_render_stats()
+    }
+
+    pub fn set_show_stats(&self, show: bool) {
+        self.scene_manager.borrow_mut().set_show_stats(show);
         self.request_render();
     }
 
+    pub fn set_debug(&self, enabled: bool) {
+        self.scene_manager.borrow_mut().set_debug(enabled);
+        self.request_render();
+    }
+
+    pub fn set_debug_hit_canvas(&self, show: bool) {
+        self.scene_manager.borrow_mut().set_debug_hit_canvas(show);
+        self.request_render();
+    }
+
+    pub fn is_debug(&self) -> bool {
+        self.scene_manager.borrow().debug_options().enabled
+    }
+
+    fn on_animation_frame(&self, timestamp: f64) {
+        // 动画按各自的时钟推进，这里只关心本帧是否被 FPS 限制跳过
+        let due = self.render_loop.borrow_mut().begin_frame(timestamp).is_some();
+        if due {
+            self.tick();
+        }
+        self.render_loop.borrow_mut().schedule();
+    }
+
+    fn tick(&self) {
+        self.run_frame_scripts();
+        let deferred = self.apply_pending_updates();
+        let animating = !self.animation_manager.borrow().is_empty();
+        if animating {
+            let objects = self
+                .object_manager
+                .borrow()
+                .iter()
+                .map(|(id, object)| (id.clone(), object.clone()))
+                .collect();
+            if let Err(e) = self
+                .animation_manager
+                .borrow_mut()
+                .update(objects, Some(&self.scene_manager))
+            {
+                log_error!("Animation update failed: {:?}", e);
+            }
+        }
+        let animating = self.step_layout() || animating;
+
+        let playing = self.refresh_videos();
+        if animating || playing || self.render_requested.get() {
+            self.prepare_frame();
+            self.scene_manager.borrow().render();
+            self.render_requested.set(false);
+            self.schedule_hit_refresh();
+        } else if self.scene_manager.borrow().is_refining() {
+            self.scene_manager.borrow().refine();
+        }
+        if deferred {
+            self.render_requested.set(true);
+        }
+    }
+}
+
+impl App {
+    // 重新排列选中的节点，连线只作为边参与计算；过渡结束后整个布局作为一个历史单元
+    pub fn auto_layout(&self, ids: &[String], options: LayoutOptions) -> EditingResult<()> {
+        self.finish_layout();
+
+        let mut nodes: Vec<(String, (f64, f64), BoundingBox)> = Vec::new();
+        let mut edges = Vec::new();
+        {
+            let object_manager = self.object_manager.borrow();
+            for id in ids {
+                let object = object_manager
+                    .get(id)
+                    .ok_or_else(|| EditingError::ObjectNotFound(id.clone()))?;
+                let object = object.borrow();
+                if !object.dependencies().is_empty() || nodes.iter().any(|(node, ..)| node == id) {
+                    continue;
+                }
+                nodes.push((id.clone(), object.position(), object.bounding_box()));
+            }
+
+            let index = |id: &str| nodes.iter().position(|(node, ..)| node == id);
+            for (_, object) in object_manager.iter() {
+                if let [source, target] = object.borrow().dependencies().as_slice() {
+                    if let (Some(source), Some(target)) = (index(source), index(target)) {
+                        edges.push((source, target));
+                    }
+                }
+            }
+        }
+        if nodes.is_empty() {
+            return Ok(());
+        }
+
+        let bounds: Vec<BoundingBox> = nodes.iter().map(|(_, _, bounds)| *bounds).collect();
+        let centers = compute_layout(&bounds, &edges, &options);
+        // 布局按包围盒中心计算，换算回对象自身的位置
+        let targets: Vec<(String, f64, f64)> = nodes
+            .iter()
+            .zip(centers)
+            .map(|((id, (x, y), bounds), (cx, cy))| {
+                let (bx, by) = bounds.center();
+                (id.clone(), x + cx - bx, y + cy - by)
+            })
+            .collect();
+
+        let moves = nodes
+            .into_iter()
+            .zip(targets)
+            .map(|((id, origin, _), (_, x, y))| (id, origin, (x, y)))
+            .collect();
+        *self.layout_transition.borrow_mut() = Some(LayoutTransition {
+            moves,
+            started: now_ms(),
+            duration: options.duration * 1000.0,
+        });
+        if options.duration <= 0.0 {
+            self.finish_layout();
+        }
+        self.request_render();
+        Ok(())
+    }
+
+    // 立即结束进行中的布局过渡：节点直接移到目标位置，整个布局记录为一个历史单元
+    pub fn finish_layout(&self) {
+        let Some(transition) = self.layout_transition.borrow_mut().take() else {
+            return;
+        };
+        let targets: Vec<(String, f64, f64)> = transition
+            .moves
+            .iter()
+            .map(|(id, _, (x, y))| (id.clone(), *x, *y))
+            .collect();
+        self.apply_layout_targets(&targets);
+
+        let mut history = self.history.borrow_mut();
+        history.begin_transaction("Auto layout");
+        for (id, (x, y), (target_x, target_y)) in transition.moves {
+            // 过渡期间被删除的节点不记录
+            if !self.contains(&id) {
+                continue;
+            }
+            let item = ObjectHistoryItem::new(
+                id,
+                json!({ "x": x, "y": y }),
+                json!({ "x": target_x, "y": target_y }),
+            );
+            history.push(HistoryItem::ObjectUpdate(item));
+        }
+        history.commit();
+        drop(history);
+        self.request_render();
+    }
+
+    // 推进布局过渡，返回本帧是否移动了节点；到达终点时结束过渡
+    fn step_layout(&self) -> bool {
+        let positions: Vec<(String, f64, f64)> = {
+            let transition = self.layout_transition.borrow();
+            let Some(transition) = transition.as_ref() else {
+                return false;
+            };
+            let progress = (now_ms() - transition.started) / transition.duration;
+            if progress < 1.0 {
+                let t = easing::ease_in_out_quad(progress.max(0.0));
+                transition
+                    .moves
+                    .iter()
+                    .map(|(id, (x, y), (target_x, target_y))| {
+                        (id.clone(), x + (target_x - x) * t, y + (target_y - y) * t)
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        };
+        if positions.is_empty() {
+            self.finish_layout();
+        } else {
+            self.apply_layout_targets(&positions);
+        }
+        true
+    }
+
+    // 过渡中逐帧的移动不记录历史，结束时由 finish_layout 统一记录
+    fn apply_layout_targets(&self, targets: &[(String, f64, f64)]) {
+        let _suspended = self.history.borrow().suspend();
+        let object_manager = self.object_manager.borrow();
+        for (id, x, y) in targets {
+            let Some(object) = object_manager.get(id) else {
+                continue;
+            };
+            let properties = HashMap::from([
+                ("x".to_string(), AnimationValue::Float(*x)),
+                ("y".to_string(), AnimationValue::Float(*y)),
+            ]);
+            let result = object.borrow_mut().set_properties(properties);
+            if let Err(e) = result {
+                log_warn!("Failed to move {} to its layout position: {:?}", id, e);
+            }
+        }
+    }
+}
+
+impl App {
+    // id 与场景中已有的对象重复时返回 DuplicateObjectId
+    pub fn add(&self, object: impl Renderable + 'static) -> EditingResult<()> {
+        self.object_manager.borrow_mut().add(Box::new(object))?;
+        self.request_render();
+        Ok(())
+    }
+
+    // 按类型名创建元素（含注册的自定义类型），data 覆盖默认属性，返回新对象的 id；
+    // data 中的 id 字段用作对象 id（如外部数据模型的主键），与已有对象重复时返回错误
+    pub fn create(&self, element_type: &str, data: Value) -> EditingResult<String> {
+        let mut object = create_default_element(element_type)?;
+        if let Some(id) = data.get("id").and_then(Value::as_str) {
+            *object.id_mut() = ObjectId::from_string(id)?;
+        }
+        object.update(data);
+        let id = object.id().value().to_string();
+        self.object_manager.borrow_mut().add(object)?;
+        self.request_render();
+        Ok(id)
+    }
+
+    // 删除框架时其中的子对象一并删除，作为一次撤销
     pub fn remove(&self, id: &str) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        let descendants = self.frame_descendants(id);
+        if descendants.is_empty() {
+            let res = self.object_manager.borrow_mut().remove(id);
+            self.request_render();
+            return res;
+        }
+        self.history.borrow_mut().begin_transaction("remove");
+        for child in &descendants {
+            self.object_manager.borrow_mut().remove(child);
+        }
         let res = self.object_manager.borrow_mut().remove(id);
+        self.history.borrow_mut().commit();
         self.request_render();
         res
     }
 
+    pub fn add_constraint(&self, id: &str, constraint: Constraint) -> EditingResult<()> {
+        if !self.object_manager.borrow().contains(id) {
+            return Err(EditingError::ObjectNotFound(id.to_string()));
+        }
+        self.constraints.borrow_mut().add(id, constraint);
+        self.request_render();
+        Ok(())
+    }
+
+    pub fn constraints(&self, id: &str) -> Vec<Constraint> {
+        self.constraints.borrow().get(id).to_vec()
+    }
+
+    pub fn clear_constraints(&self, id: &str) -> bool {
+        self.constraints.borrow_mut().remove(id)
+    }
+
+    // 渲染前先求解约束，再按端点的最终位置更新连线
+    fn prepare_frame(&self) {
+        self.apply_constraints();
+        if let Ok(object_manager) = self.object_manager.try_borrow() {
+            object_manager.update_dependents();
+        }
+        self.refresh_path_handles();
+        self.refresh_measure_overlay();
+    }
+
+    // 场景变换和对象位置此时都已是本帧的最终值
+    fn apply_constraints(&self) {
+        let constraints = self.constraints.borrow();
+        if constraints.is_empty() {
+            return;
+        }
+        let (Ok(object_manager), Ok(scene_manager)) =
+            (self.object_manager.try_borrow(), self.scene_manager.try_borrow())
+        else {
+            return;
+        };
+        constraints.apply(&object_manager, &scene_manager);
+    }
+
     pub fn get(&self, id: &str) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
         self.object_manager.borrow().get(id)
     }
 
+    // 对象所有可编辑属性的描述及当前值，供宿主自动生成属性面板；插件元素同样适用
+    pub fn describe_object(&self, id: &str) -> EditingResult<Value> {
+        let object = self
+            .get(id)
+            .ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        let object = object.borrow();
+        let properties: Vec<Value> = object
+            .schema()
+            .into_iter()
+            .map(|schema| {
+                let mut property = json!(schema);
+                property["value"] = object.property(schema.name).unwrap_or(Value::Null);
+                property
+            })
+            .collect();
+        Ok(json!({
+            "id": id,
+            "type": object.get_type(),
+            "properties": properties,
+        }))
+    }
+
+    // 一次修改多个对象的属性，如表格式的批量编辑：作为一条历史记录，立即在同一次刷新中应用并只重绘一次。
+    // 任一 id 不存在时不做任何修改；patch 中对象没有的属性被忽略。返回实际发生变化的对象数
+    pub fn update_many(&self, patches: Vec<(String, Value)>) -> EditingResult<usize> {
+        let mut changes = Vec::with_capacity(patches.len());
+        for (id, patch) in patches {
+            let object = self
+                .get(&id)
+                .ok_or_else(|| EditingError::ObjectNotFound(id.clone()))?;
+            let Value::Object(patch) = patch else {
+                let message = format!("patch for '{}' must be an object", id);
+                return Err(EditingError::SerdeError(serde::de::Error::custom(message)));
+            };
+            let object = object.borrow();
+            let mut undo_data = json!({});
+            let mut redo_data = json!({});
+            for (key, value) in patch {
+                match object.property(&key) {
+                    Some(old_value) if old_value != value => {
+                        undo_data[&key] = old_value;
+                        redo_data[&key] = value;
+                    }
+                    _ => {}
+                }
+            }
+            if redo_data.as_object().is_some_and(|data| !data.is_empty()) {
+                changes.push((id, undo_data, redo_data));
+            }
+        }
+        if changes.is_empty() {
+            return Ok(0);
+        }
+
+        self.history.borrow_mut().begin_transaction("Update");
+        {
+            let mut render_control = self.render_control.borrow_mut();
+            for (id, undo_data, redo_data) in &changes {
+                let item = ObjectHistoryItem::new(id.clone(), undo_data.clone(), redo_data.clone());
+                self.record_history(HistoryItem::ObjectUpdate(item));
+                let body = UpdateBody::new(UpdateType::ObjectUpdate(id.clone()), redo_data.clone())
+                    .with_priority(UpdatePriority::Input);
+                render_control.add_message(UpdateMessage::Update(body));
+            }
+        }
+        self.history.borrow_mut().commit();
+        self.post_update(UpdateMessage::ForceUpdate);
+        Ok(changes.len())
+    }
+
     pub fn contains(&self, id: &str) -> bool {
         self.object_manager.borrow().contains(id)
     }
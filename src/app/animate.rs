@@ -0,0 +1,178 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::App;
+use crate::animation::{
+    Animation, AnimationHandle, AnimationTarget, AnimationValue, QwenAnimationBuilder, SpringAnimationBuilder,
+};
+use crate::error::{EditingError, EditingResult};
+use crate::helper::easing;
+use crate::log::log_warn;
+
+// 未指定时长时的默认值（毫秒）
+const DEFAULT_DURATION_MS: f64 = 300.0;
+
+// 弹簧参数，省略的字段使用 SpringAnimationBuilder 的默认值
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SpringOptions {
+    pub stiffness: Option<f64>,
+    pub damping: Option<f64>,
+    pub mass: Option<f64>,
+}
+
+// 对象动画的链式构建器：app.animate(id).to(json!({ "x": 100 })).duration(300.0).easing("ease-out").start()，
+// 起始值取自 start 时对象的当前属性
+#[derive(Debug)]
+pub struct ObjectAnimation {
+    app: App,
+    id: String,
+    targets: Map<String, Value>,
+    duration_ms: f64,
+    delay_ms: f64,
+    easing: fn(f64) -> f64,
+    spring: Option<SpringOptions>,
+    velocity: Map<String, Value>,
+}
+
+impl ObjectAnimation {
+    // 可多次调用，同名属性以最后一次为准；只支持数值和字符串属性
+    pub fn to(mut self, properties: Value) -> Self {
+        match properties {
+            Value::Object(properties) => self.targets.extend(properties),
+            other => log_warn!("Animation targets must be an object, got {}", other),
+        }
+        self
+    }
+
+    pub fn duration(mut self, duration_ms: f64) -> Self {
+        self.duration_ms = duration_ms.max(0.0);
+        self
+    }
+
+    pub fn delay(mut self, delay_ms: f64) -> Self {
+        self.delay_ms = delay_ms.max(0.0);
+        self
+    }
+
+    // 名字见 easing::by_name，未知的名字保持原来的缓动
+    pub fn easing(mut self, name: &str) -> Self {
+        match easing::by_name(name) {
+            Some(easing) => self.easing = easing,
+            None => log_warn!("Unknown easing: {}", name),
+        }
+        self
+    }
+
+    // 改用弹簧动画，持续时间由物理过程决定，duration、delay 和 easing 不再生效；
+    // 对象上同一属性正在进行的动画会被取消，弹簧从它当前的速度继续
+    pub fn spring(mut self, options: SpringOptions) -> Self {
+        self.spring = Some(options);
+        self
+    }
+
+    // 弹簧动画的初始速度（每秒的变化量），例如松手时接上拖动的速度；对缓动动画无效
+    pub fn velocity(mut self, velocity: Value) -> Self {
+        match velocity {
+            Value::Object(velocity) => self.velocity.extend(velocity),
+            other => log_warn!("Animation velocity must be an object, got {}", other),
+        }
+        self
+    }
+
+    pub fn start(self) -> EditingResult<AnimationHandle> {
+        let object = self
+            .app
+            .get(&self.id)
+            .ok_or_else(|| EditingError::ObjectNotFound(self.id.clone()))?;
+        let names: Vec<String> = self.targets.keys().cloned().collect();
+        let current = object.borrow().get_properties(&names);
+
+        let animation: Box<dyn Animation> = match &self.spring {
+            Some(options) => {
+                let mut builder = SpringAnimationBuilder::new();
+                if let Some(stiffness) = options.stiffness {
+                    builder = builder.set_stiffness(stiffness);
+                }
+                if let Some(damping) = options.damping {
+                    builder = builder.set_damping(damping);
+                }
+                if let Some(mass) = options.mass {
+                    builder = builder.set_mass(mass);
+                }
+                for (name, target) in &self.targets {
+                    let (Some(_), Some(end)) = (current.get(name), AnimationValue::from_json(target)) else {
+                        return Err(EditingError::UnsupportedProperty(name.clone()));
+                    };
+                    builder = builder.add_property(name, end);
+                }
+                // 接替同一属性上的动画，从被打断的弹簧当前的速度继续；显式指定的速度优先
+                let carried = self.app.animation_manager.borrow().interrupt_object(&self.id, &names);
+                builder = builder.set_velocities(carried);
+                for (name, velocity) in &self.velocity {
+                    if let Some(velocity) = AnimationValue::from_json(velocity) {
+                        builder = builder.set_velocity(name, velocity);
+                    }
+                }
+                Box::new(builder.build())
+            }
+            None => {
+                let mut builder = QwenAnimationBuilder::new(self.duration_ms / 1000.0)
+                    .set_delay(self.delay_ms / 1000.0)
+                    .set_easing(Box::new(self.easing));
+                for (name, target) in &self.targets {
+                    let (Some(start), Some(end)) = (current.get(name), AnimationValue::from_json(target)) else {
+                        return Err(EditingError::UnsupportedProperty(name.clone()));
+                    };
+                    builder = builder.add_property(name, start.clone(), end);
+                }
+                Box::new(builder.build())
+            }
+        };
+
+        let handle = self
+            .app
+            .animation_manager
+            .borrow_mut()
+            .add_target_animation(AnimationTarget::Object(self.id), animation);
+        self.app.request_render();
+        Ok(handle)
+    }
+}
+
+impl App {
+    // 暂停对象上正在进行的所有动画
+    pub fn pause_animations(&self, id: &str) {
+        self.animation_manager.borrow().pause_object(id);
+    }
+
+    pub fn resume_animations(&self, id: &str) {
+        self.animation_manager.borrow().resume_object(id);
+        self.request_render();
+    }
+
+    // 停在当前值
+    pub fn cancel_animations(&self, id: &str) {
+        self.animation_manager.borrow().cancel_object(id);
+    }
+
+    // 下一帧直接跳到终点
+    pub fn finish_animations(&self, id: &str) {
+        self.animation_manager.borrow().finish_object(id);
+        self.request_render();
+    }
+
+    // 动画由渲染循环推进（App::start）
+    pub fn animate(&self, id: &str) -> ObjectAnimation {
+        ObjectAnimation {
+            app: self.clone(),
+            id: id.to_string(),
+            targets: Map::new(),
+            duration_ms: DEFAULT_DURATION_MS,
+            delay_ms: 0.0,
+            easing: easing::ease_in_out_quad,
+            spring: None,
+            velocity: Map::new(),
+        }
+    }
+}
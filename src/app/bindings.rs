@@ -0,0 +1,145 @@
+use serde_json::{json, Map, Value};
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Function;
+
+use super::App;
+use crate::error::{EditingError, EditingResult};
+use crate::helper::to_js_value;
+use crate::log::log_error;
+use crate::render_control::{UpdateBody, UpdateMessage, UpdateType};
+
+// 对象属性与数据键的绑定；key 可以用 "." 访问嵌套字段，如 "metrics.cpu"
+#[derive(Debug, Clone)]
+struct Binding {
+    object_id: String,
+    property: String,
+    key: String,
+    // 把数据值转换为属性值，如按比例换算柱高
+    transform: Option<Function>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct DataBindings {
+    data: Map<String, Value>,
+    bindings: Vec<Binding>,
+}
+
+impl DataBindings {
+    fn lookup(&self, key: &str) -> Option<&Value> {
+        let mut parts = key.split('.');
+        let mut value = self.data.get(parts.next()?)?;
+        for part in parts {
+            value = match value {
+                Value::Object(map) => map.get(part)?,
+                Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+// 修改 key 会影响绑定到它本身及其下嵌套字段的属性
+fn affects(changed: &str, bound: &str) -> bool {
+    bound == changed
+        || bound
+            .strip_prefix(changed)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+impl App {
+    // 把对象属性绑定到数据键，数据变化时属性经排队更新应用，不进入历史；
+    // 同一属性重复绑定时替换原来的绑定。数据中已有该键时立即应用一次
+    pub fn bind_property(
+        &self,
+        id: &str,
+        property: &str,
+        key: &str,
+        transform: Option<Function>,
+    ) -> EditingResult<()> {
+        if !self.contains(id) {
+            return Err(EditingError::ObjectNotFound(id.to_string()));
+        }
+        let binding = Binding {
+            object_id: id.to_string(),
+            property: property.to_string(),
+            key: key.to_string(),
+            transform,
+        };
+        let current = {
+            let mut bindings = self.bindings.borrow_mut();
+            bindings
+                .bindings
+                .retain(|existing| existing.object_id != id || existing.property != property);
+            bindings.bindings.push(binding.clone());
+            bindings.lookup(key).cloned()
+        };
+        // 转换函数可能回调 App，在释放借用后调用
+        if let Some(update) = current.and_then(|value| bound_update(&binding, &value)) {
+            self.post_update(update);
+        }
+        Ok(())
+    }
+
+    // 解除绑定，属性保留当前值
+    pub fn unbind_property(&self, id: &str, property: &str) -> bool {
+        let mut bindings = self.bindings.borrow_mut();
+        let before = bindings.bindings.len();
+        bindings
+            .bindings
+            .retain(|binding| binding.object_id != id || binding.property != property);
+        before != bindings.bindings.len()
+    }
+
+    // 修改数据源的顶层键，更新所有绑定到它（及其嵌套字段）的属性；value 为 null 时删除该键
+    pub fn set_data(&self, key: &str, value: Value) {
+        let affected: Vec<(Binding, Value)> = {
+            let mut bindings = self.bindings.borrow_mut();
+            if value.is_null() {
+                bindings.data.remove(key);
+            } else {
+                bindings.data.insert(key.to_string(), value);
+            }
+            // 已删除的对象保留绑定，撤销删除后继续更新
+            let object_manager = self.object_manager.borrow();
+            bindings
+                .bindings
+                .iter()
+                .filter(|binding| {
+                    affects(key, &binding.key) && object_manager.contains(&binding.object_id)
+                })
+                .filter_map(|binding| Some((binding.clone(), bindings.lookup(&binding.key)?.clone())))
+                .collect()
+        };
+        for (binding, value) in affected {
+            if let Some(update) = bound_update(&binding, &value) {
+                self.post_update(update);
+            }
+        }
+    }
+
+    pub fn get_data(&self, key: &str) -> Option<Value> {
+        self.bindings.borrow().lookup(key).cloned()
+    }
+}
+
+fn bound_update(binding: &Binding, value: &Value) -> Option<UpdateMessage> {
+    let value = match &binding.transform {
+        Some(transform) => {
+            let result = transform
+                .call2(&JsValue::NULL, &to_js_value(value), &JsValue::from_str(&binding.key))
+                .and_then(|result| serde_wasm_bindgen::from_value(result).map_err(JsValue::from));
+            match result {
+                Ok(value) => value,
+                Err(e) => {
+                    log_error!("Binding transform for {}.{} failed: {:?}", binding.object_id, binding.property, e);
+                    return None;
+                }
+            }
+        }
+        None => value.clone(),
+    };
+    let data = json!({ binding.property.as_str(): value });
+    let body = UpdateBody::new(UpdateType::ObjectUpdate(binding.object_id.clone()), data);
+    Some(UpdateMessage::Update(body))
+}
@@ -0,0 +1,89 @@
+use serde_json::{json, Value};
+
+use super::App;
+use crate::boolean_ops::{boolean_op, BooleanOp, Contour};
+use crate::bounding_box::BoundingBox;
+use crate::element::{PathPoint, Renderable};
+use crate::error::{EditingError, EditingResult};
+
+impl App {
+    // 按绘制顺序从下到上依次对图形做布尔运算，减去时从最下面的图形中减去其余图形。
+    // 结果作为新的封闭 Path 加入并成为选中，原图形被删除，整体为一次撤销；样式取自最下面的图形。
+    // 支持 path、rect、ellipse；不足两个图形或结果为空时不做修改，返回 None
+    pub fn boolean_operation(&self, ids: &[String], op: BooleanOp) -> EditingResult<Option<String>> {
+        if let Some(missing) = ids.iter().find(|id| !self.contains(id)) {
+            return Err(EditingError::ObjectNotFound(missing.clone()));
+        }
+        let mut operands: Vec<(String, Vec<Contour>)> = Vec::new();
+        let mut style = json!({});
+        for object in self.get_objects() {
+            let object = object.borrow();
+            let id = object.id().value();
+            if !ids.iter().any(|candidate| candidate == id) {
+                continue;
+            }
+            let outline = object.outline();
+            if outline.is_empty() {
+                return Err(EditingError::UnsupportedElement(object.get_type().to_string()));
+            }
+            if operands.is_empty() {
+                style = result_style(&**object);
+            }
+            operands.push((id.to_string(), outline));
+        }
+        let Some(((_, first), rest)) = operands.split_first().filter(|(_, rest)| !rest.is_empty()) else {
+            return Ok(None);
+        };
+        let result = rest
+            .iter()
+            .fold(first.clone(), |result, (_, outline)| boolean_op(&result, outline, op));
+        if result.is_empty() {
+            return Ok(None);
+        }
+
+        // 以结果的左上角为 Path 的位置
+        let corners: Vec<(f64, f64)> = result.iter().flatten().copied().collect();
+        let bounds = BoundingBox::from_points(&corners);
+        let to_points = |ring: &Contour| -> Vec<PathPoint> {
+            ring.iter()
+                .map(|(x, y)| PathPoint::new(x - bounds.x, y - bounds.y, 1.0))
+                .collect()
+        };
+        let mut rings = result.iter().map(to_points);
+        let mut data = style;
+        data["x"] = json!(bounds.x);
+        data["y"] = json!(bounds.y);
+        data["points"] = json!(rings.next());
+        data["contours"] = json!(rings.collect::<Vec<_>>());
+        data["closed"] = json!(true);
+        data["smooth"] = json!(false);
+
+        self.history.borrow_mut().begin_transaction(op.name());
+        let created = self.create("path", data);
+        if created.is_ok() {
+            for (id, _) in &operands {
+                self.object_manager.borrow_mut().remove(id);
+            }
+        }
+        self.history.borrow_mut().commit();
+        let id = created?;
+        self.select(vec![id.clone()]);
+        self.request_render();
+        Ok(Some(id))
+    }
+}
+
+// 沿用填充、描边和不透明度；没有填充的笔画用描边色填充
+fn result_style(object: &dyn Renderable) -> Value {
+    let stroke = object.property("stroke").unwrap_or_else(|| json!("black"));
+    let fill = object
+        .property("fill")
+        .filter(|fill| fill.as_str().is_some_and(|fill| !fill.is_empty()))
+        .unwrap_or_else(|| stroke.clone());
+    json!({
+        "fill": fill,
+        "stroke": stroke,
+        "stroke_width": object.property("stroke_width").unwrap_or_else(|| json!(0.0)),
+        "opacity": object.property("opacity").unwrap_or_else(|| json!(1.0)),
+    })
+}
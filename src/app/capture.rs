@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use web_sys::MouseEvent;
+
+use super::tools::{capture_pointer, pointer_id};
+use super::App;
+use crate::element::Renderable;
+use crate::error::{EditingError, EditingResult};
+use crate::events::AppEvent;
+use crate::helper::to_js_value;
+
+// 按下时命中的对象捕获这个指针，抬起或取消之前的移动和抬起都发给它，即使指针已离开对象
+#[derive(Debug)]
+pub(super) struct PointerCapture {
+    pointer_id: Option<i32>,
+    object_id: String,
+}
+
+impl App {
+    // 声明对象优先接收输入（如工具的浮层）：指针落在它上面时先于普通拾取和当前工具得到事件，
+    // 后声明的优先；对象删除后自动失效
+    pub fn set_captures_input(&self, id: &str, captures: bool) -> EditingResult<()> {
+        if !self.contains(id) {
+            return Err(EditingError::ObjectNotFound(id.to_string()));
+        }
+        let mut input_capture = self.input_capture.borrow_mut();
+        input_capture.retain(|other| other != id);
+        if captures {
+            input_capture.push(id.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn captures_input(&self, id: &str) -> bool {
+        self.input_capture.borrow().iter().any(|other| other == id)
+    }
+
+    // 当前捕获指针的对象
+    pub fn pointer_capture(&self) -> Option<String> {
+        self.pointer_capture
+            .borrow()
+            .as_ref()
+            .map(|capture| capture.object_id.clone())
+    }
+
+    pub fn release_pointer_capture(&self) {
+        self.pointer_capture.borrow_mut().take();
+    }
+
+    // 指针下优先接收输入的对象，命中时派发 "objectdown" 并捕获指针，返回 true 表示事件已被处理
+    pub(super) fn capturing_pointer_down(&self, event: &MouseEvent) -> bool {
+        let object = {
+            let mut input_capture = self.input_capture.borrow_mut();
+            input_capture.retain(|id| self.contains(id));
+            if input_capture.is_empty() {
+                return false;
+            }
+            let Some((x, y)) = self.event_to_world(event) else {
+                return false;
+            };
+            let Ok(scene_manager) = self.scene_manager.try_borrow() else {
+                return false;
+            };
+            let hits = scene_manager.hit_test_all(x, y);
+            input_capture.iter().rev().find_map(|id| {
+                hits.iter()
+                    .find(|object| object.borrow().id().value() == id.as_str())
+                    .cloned()
+            })
+        };
+        let Some(object) = object else {
+            return false;
+        };
+        self.emit_pointer_event(AppEvent::OBJECT_DOWN, &object, event);
+        self.begin_pointer_capture(&object, event);
+        true
+    }
+
+    pub(super) fn begin_pointer_capture(&self, object: &Rc<RefCell<Box<dyn Renderable>>>, event: &MouseEvent) {
+        capture_pointer(event);
+        *self.pointer_capture.borrow_mut() = Some(PointerCapture {
+            pointer_id: pointer_id(event),
+            object_id: object.borrow().id().value().to_string(),
+        });
+    }
+
+    // 捕获中的指针移动时派发 "objectmove"，返回 true 表示事件已被处理
+    pub(super) fn captured_pointer_move(&self, event: &MouseEvent) -> bool {
+        let Some(object) = self.captured_object(event) else {
+            return false;
+        };
+        self.emit_pointer_event(AppEvent::OBJECT_MOVE, &object, event);
+        true
+    }
+
+    // 捕获中的指针抬起时把 "objectup" 发给捕获它的对象并结束捕获，返回 true 表示事件已被处理
+    pub(super) fn captured_pointer_up(&self, event: &MouseEvent) -> bool {
+        let Some(object) = self.captured_object(event) else {
+            return false;
+        };
+        self.release_pointer_capture();
+        self.emit_pointer_event(AppEvent::OBJECT_UP, &object, event);
+        true
+    }
+
+    // 其他指针的事件照常处理；捕获的对象已被删除时结束捕获
+    fn captured_object(&self, event: &MouseEvent) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        let object_id = {
+            let capture = self.pointer_capture.borrow();
+            let capture = capture.as_ref()?;
+            if capture.pointer_id != pointer_id(event) {
+                return None;
+            }
+            capture.object_id.clone()
+        };
+        let object = self.get(&object_id);
+        if object.is_none() {
+            self.release_pointer_capture();
+        }
+        object
+    }
+
+    pub(super) fn emit_pointer_event(
+        &self,
+        event_type: AppEvent,
+        object: &Rc<RefCell<Box<dyn Renderable>>>,
+        event: &MouseEvent,
+    ) {
+        let mut payload = object.borrow().event_payload();
+        payload["x"] = event.offset_x().into();
+        payload["y"] = event.offset_y().into();
+        let _ = self.events.emit(event_type.into(), &to_js_value(&payload));
+    }
+}
@@ -0,0 +1,106 @@
+use std::any::Any;
+
+use serde_json::json;
+
+use super::App;
+use crate::element::{Frame, Instance, Renderable};
+use crate::error::{EditingError, EditingResult};
+
+impl App {
+    // 把对象放入一个新的主组件（透明、不裁剪的 Frame），对象保持原来的位置；整体为一次撤销。
+    // 没有对象时返回 None
+    pub fn create_component(&self, ids: &[String]) -> EditingResult<Option<String>> {
+        let bounds = {
+            let object_manager = self.object_manager.borrow();
+            if let Some(missing) = ids.iter().find(|id| !object_manager.contains(id)) {
+                return Err(EditingError::ObjectNotFound(missing.clone()));
+            }
+            object_manager.refresh_spatial_index();
+            ids.iter()
+                .filter_map(|id| object_manager.extent(id))
+                .reduce(|bounds, other| bounds.union(&other))
+        };
+        let Some(bounds) = bounds else {
+            return Ok(None);
+        };
+
+        self.history.borrow_mut().begin_transaction("create_component");
+        let result = self
+            .create(
+                "frame",
+                json!({
+                    "x": bounds.x,
+                    "y": bounds.y,
+                    "width": bounds.width,
+                    "height": bounds.height,
+                    "fill": "transparent",
+                    "stroke_width": 0.0,
+                    "clip_content": false,
+                    "component": true,
+                }),
+            )
+            .and_then(|id| self.add_to_frame(&id, ids).map(|_| id));
+        self.history.borrow_mut().commit();
+        result.map(Some)
+    }
+
+    // 在 (x, y) 处创建引用主组件的实例，大小与主组件相同
+    pub fn create_instance(&self, component_id: &str, x: f64, y: f64) -> EditingResult<String> {
+        let object = self
+            .get(component_id)
+            .ok_or_else(|| EditingError::ObjectNotFound(component_id.to_string()))?;
+        if !is_component(&**object.borrow()) {
+            return Err(EditingError::UnsupportedElement(object.borrow().get_type().to_string()));
+        }
+        self.create("instance", json!({ "component": component_id, "x": x, "y": y }))
+    }
+
+    // 覆盖实例中主组件对象 target_id 的填充色，fill 为 None 时恢复为主组件的颜色；记录历史
+    pub fn set_instance_fill(&self, instance_id: &str, target_id: &str, fill: Option<String>) -> EditingResult<bool> {
+        let object = self
+            .get(instance_id)
+            .ok_or_else(|| EditingError::ObjectNotFound(instance_id.to_string()))?;
+        let mut object = object.borrow_mut();
+        let object_type = object.get_type().to_string();
+        let Some(instance) = (&mut **object as &mut dyn Any).downcast_mut::<Instance>() else {
+            return Err(EditingError::UnsupportedElement(object_type));
+        };
+        let mut overrides = instance.fill_overrides.clone();
+        let changed = match fill {
+            Some(fill) => overrides.insert(target_id.to_string(), fill.clone()) != Some(fill),
+            None => overrides.remove(target_id).is_some(),
+        };
+        if changed {
+            instance.set_fill_overrides(overrides);
+            drop(object);
+            self.request_render();
+        }
+        Ok(changed)
+    }
+
+    // 所有主组件的 id
+    pub fn components(&self) -> Vec<String> {
+        self.object_manager
+            .borrow()
+            .iter()
+            .filter(|(_, object)| is_component(&**object.borrow()))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    // 引用该主组件的所有实例
+    pub fn instances_of(&self, component_id: &str) -> Vec<String> {
+        self.object_manager
+            .borrow()
+            .iter()
+            .filter(|(_, object)| object.borrow().instance_of() == Some(component_id))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
+
+fn is_component(object: &dyn Renderable) -> bool {
+    (object as &dyn Any)
+        .downcast_ref::<Frame>()
+        .is_some_and(|frame| frame.component)
+}
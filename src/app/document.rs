@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::json;
+use wasm_bindgen_futures::spawn_local;
+
+use super::App;
+use crate::bounding_box::BoundingBox;
+use crate::events::AppEvent;
+use crate::helper::to_js_value;
+use crate::scene_manager::DocumentUnits;
+
+// 文档概况，用于诊断面板；内存均为估算值（字节）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentInfo {
+    pub object_count: usize,
+    pub objects_by_type: BTreeMap<String, usize>,
+    // 所有对象包围盒的并集，没有对象时为 None
+    pub bounds: Option<BoundingBox>,
+    pub units: DocumentUnits,
+    pub dirty: bool,
+    pub history: HistoryInfo,
+    pub memory: MemoryInfo,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryInfo {
+    pub undo_depth: usize,
+    pub redo_depth: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryInfo {
+    // 显示画布和 hit 画布
+    pub canvas_bytes: usize,
+    pub image_count: usize,
+    pub image_bytes: usize,
+}
+
+impl App {
+    // 由 History 在记录、撤销、重做和回滚时调用；同一轮事件循环内的多次变化只触发一次 "documentchange"，
+    // 由已保存变为未保存时再触发 "dirtychange"
+    pub(crate) fn notify_document_change(&self) {
+        let became_dirty = !self.document_dirty.replace(true);
+        self.dirty_pending.set(self.dirty_pending.get() || became_dirty);
+        if self.document_change_pending.replace(true) {
+            return;
+        }
+
+        let app = self.clone();
+        spawn_local(async move {
+            app.document_change_pending.set(false);
+            let dirty = app.document_dirty.get();
+            let payload = to_js_value(&json!({ "dirty": dirty }));
+            let _ = app.events.emit(AppEvent::DOCUMENT_CHANGE.into(), &payload);
+            // 期间已调用 mark_saved 时不再通知
+            if app.dirty_pending.replace(false) && dirty {
+                let _ = app.events.emit(AppEvent::DIRTY_CHANGE.into(), &payload);
+            }
+        });
+    }
+
+    // 画布没有固定大小，文档范围为所有顶层对象（连同框架中的子对象）范围的并集，没有对象时为 None
+    pub fn document_bounds(&self) -> Option<BoundingBox> {
+        self.object_manager.borrow().document_bounds()
+    }
+
+    pub fn get_document_info(&self) -> DocumentInfo {
+        let mut objects_by_type = BTreeMap::new();
+        let object_count = {
+            let object_manager = self.object_manager.borrow();
+            for (_, object) in object_manager.iter() {
+                let object = object.borrow();
+                *objects_by_type.entry(object.get_type().to_string()).or_insert(0) += 1;
+            }
+            object_manager.len()
+        };
+        let bounds = self.document_bounds();
+
+        let history = {
+            let history = self.history.borrow();
+            let (undo_depth, redo_depth) = history.depth();
+            HistoryInfo {
+                undo_depth,
+                redo_depth,
+                bytes: history.approx_size(),
+            }
+        };
+        let (image_count, image_bytes) = self.images.borrow().memory_estimate();
+
+        DocumentInfo {
+            object_count,
+            objects_by_type,
+            bounds,
+            units: self.units(),
+            dirty: self.is_dirty(),
+            history,
+            memory: MemoryInfo {
+                canvas_bytes: self.scene_manager.borrow().canvas_memory(),
+                image_count,
+                image_bytes,
+            },
+        }
+    }
+
+    // 上次 mark_saved 之后文档是否有修改；撤销回保存时的状态仍算作有修改
+    pub fn is_dirty(&self) -> bool {
+        self.document_dirty.get()
+    }
+
+    // 宿主保存成功后调用，触发 "saved"，之前有未保存修改时还会触发 "dirtychange"
+    pub fn mark_saved(&self) {
+        let was_dirty = self.document_dirty.replace(false);
+        self.dirty_pending.set(false);
+        let payload = to_js_value(&json!({ "dirty": false }));
+        if was_dirty {
+            let _ = self.events.emit(AppEvent::DIRTY_CHANGE.into(), &payload);
+        }
+        let _ = self.events.emit(AppEvent::SAVED.into(), &payload);
+    }
+}
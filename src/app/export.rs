@@ -0,0 +1,59 @@
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, OffscreenCanvas};
+
+use super::App;
+use crate::bounding_box::BoundingBox;
+use crate::error::{EditingError, EditingResult};
+
+impl App {
+    // 把世界坐标区域导出为 PNG，scale 为每个世界单位对应的输出像素，用于缩略图、分享等
+    pub async fn export_region(&self, region: BoundingBox, scale: f64) -> EditingResult<Blob> {
+        let canvas = self.scene_manager.borrow().render_region(region, scale, None)?;
+        to_png(canvas).await
+    }
+
+    // 区域以文档单位给出，pixels_per_unit 为每个文档单位对应的输出像素，例如平面图按每米 100 像素导出
+    pub async fn export_region_in_units(&self, region: BoundingBox, pixels_per_unit: f64) -> EditingResult<Blob> {
+        let units = self.units();
+        let world = BoundingBox::new(
+            units.to_world(region.x),
+            units.to_world(region.y),
+            units.to_world(region.width),
+            units.to_world(region.height),
+        );
+        self.export_region(world, pixels_per_unit / units.world_per_unit()).await
+    }
+
+    // 导出全部内容，区域为文档范围向外扩展 padding（世界单位）；文档为空时返回 None
+    pub async fn export_document(&self, padding: f64, scale: f64) -> EditingResult<Option<Blob>> {
+        let Some(bounds) = self.document_bounds() else {
+            return Ok(None);
+        };
+        self.export_region(bounds.expand(padding.max(0.0)), scale).await.map(Some)
+    }
+
+    // 只导出选中的对象，区域为它们的包围盒向外扩展 padding（世界单位）；没有选中对象时返回 None
+    pub async fn export_selection(&self, padding: f64) -> EditingResult<Option<Blob>> {
+        let ids = self.selection();
+        let Some(bounds) = ids
+            .iter()
+            .filter_map(|id| self.object_manager.borrow().extent(id))
+            .reduce(|bounds, other| bounds.union(&other))
+        else {
+            return Ok(None);
+        };
+
+        let canvas = self
+            .scene_manager
+            .borrow()
+            .render_region(bounds.expand(padding.max(0.0)), 1.0, Some(&ids))?;
+        to_png(canvas).await.map(Some)
+    }
+}
+
+async fn to_png(canvas: OffscreenCanvas) -> EditingResult<Blob> {
+    let blob = JsFuture::from(canvas.convert_to_blob()?).await?;
+    blob.dyn_into::<Blob>()
+        .map_err(|_| EditingError::Dom("convertToBlob did not return a Blob".to_string()))
+}
@@ -0,0 +1,117 @@
+use serde_json::json;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{FontFace, FontFaceDescriptors};
+
+use super::App;
+use crate::error::{EditingError, EditingResult};
+use crate::events::AppEvent;
+use crate::fonts::{font_uses_family, invalidate_metrics, FontDescriptors, FontStatus};
+use crate::helper::to_js_value;
+use crate::log::log_warn;
+
+impl App {
+    // 通过 FontFace 加载网络字体并加入 document.fonts；source 为 URL 或 CSS src 值（如 "local(Inter)"）
+    pub async fn load_font(
+        &self,
+        family: &str,
+        source: &str,
+        descriptors: FontDescriptors,
+    ) -> EditingResult<()> {
+        if self.fonts.borrow().status(family) == Some(FontStatus::Loaded) {
+            return Ok(());
+        }
+        self.fonts.borrow_mut().set_status(family, FontStatus::Loading);
+
+        match load_font_face(family, source, &descriptors).await {
+            Ok(()) => {
+                self.fonts.borrow_mut().set_status(family, FontStatus::Loaded);
+                self.refresh_font(family);
+                self.emit_font_event(family, FontStatus::Loaded);
+                Ok(())
+            }
+            Err(e) => {
+                log_warn!("Failed to load font {}: {}", family, e);
+                self.fonts.borrow_mut().set_status(family, FontStatus::Failed);
+                self.emit_font_event(family, FontStatus::Failed);
+                Err(e)
+            }
+        }
+    }
+
+    pub fn font_status(&self, family: &str) -> Option<FontStatus> {
+        self.fonts.borrow().status(family)
+    }
+
+    pub fn fonts_ready(&self) -> bool {
+        self.fonts.borrow().is_ready()
+    }
+
+    // 使用该字体的文本重新测量并重绘
+    fn refresh_font(&self, family: &str) {
+        invalidate_metrics(family);
+        for object in self.object_manager.borrow().get_objects() {
+            let Ok(mut object) = object.try_borrow_mut() else {
+                continue;
+            };
+            let uses_family = object
+                .property("font_family")
+                .and_then(|font_family| font_family.as_str().map(|f| font_uses_family(f, family)))
+                .unwrap_or(false);
+            if uses_family {
+                object.set_dirty();
+            }
+        }
+        self.layout_text_editor();
+        self.request_render();
+    }
+
+    fn emit_font_event(&self, family: &str, status: FontStatus) {
+        let payload = to_js_value(&json!({ "family": family, "status": status }));
+        let _ = self.events.emit(AppEvent::FONT_LOAD.into(), &payload);
+    }
+}
+
+async fn load_font_face(
+    family: &str,
+    source: &str,
+    descriptors: &FontDescriptors,
+) -> EditingResult<()> {
+    let source = if source.starts_with("url(") || source.starts_with("local(") {
+        source.to_string()
+    } else {
+        format!("url({:?})", source)
+    };
+    let face = FontFace::new_with_str_and_descriptors(family, &source, &to_web_descriptors(descriptors))?;
+    let loaded = JsFuture::from(face.load()?).await?;
+    let face = loaded
+        .dyn_into::<FontFace>()
+        .map_err(|_| EditingError::Dom("FontFace.load() resolved to a non-FontFace".to_string()))?;
+    web_sys::window()
+        .ok_or(EditingError::WindowNotFound)?
+        .document()
+        .ok_or(EditingError::DocumentNotFound)?
+        .fonts()
+        .add(&face)?;
+    Ok(())
+}
+
+fn to_web_descriptors(descriptors: &FontDescriptors) -> FontFaceDescriptors {
+    let web = FontFaceDescriptors::new();
+    if let Some(weight) = &descriptors.weight {
+        web.set_weight(weight);
+    }
+    if let Some(style) = &descriptors.style {
+        web.set_style(style);
+    }
+    if let Some(stretch) = &descriptors.stretch {
+        web.set_stretch(stretch);
+    }
+    if let Some(unicode_range) = &descriptors.unicode_range {
+        web.set_unicode_range(unicode_range);
+    }
+    if let Some(display) = &descriptors.display {
+        web.set_display(display);
+    }
+    web
+}
@@ -0,0 +1,155 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nalgebra as na;
+
+use super::App;
+use crate::element::{Frame, Renderable};
+use crate::error::{EditingError, EditingResult};
+use crate::math::convert_1x6_to_3x3;
+
+impl App {
+    // 把对象移入框架，放在框架中已有子对象的上面；对象保持原来的位置不动，之后随框架移动并被裁剪。
+    // 已在其他框架中的对象先移出，框架自身、它的上级框架和连线不会被移入。整体为一次撤销
+    pub fn add_to_frame(&self, frame_id: &str, ids: &[String]) -> EditingResult<bool> {
+        let object_manager = self.object_manager.borrow();
+        object_manager.refresh_spatial_index();
+        let frame = object_manager
+            .get(frame_id)
+            .ok_or_else(|| EditingError::ObjectNotFound(frame_id.to_string()))?;
+        if let Some(missing) = ids.iter().find(|id| !object_manager.contains(id)) {
+            return Err(EditingError::ObjectNotFound(missing.clone()));
+        }
+        let frame_transform = {
+            let frame = frame.borrow();
+            if !(&**frame as &dyn Any).is::<Frame>() {
+                return Err(EditingError::UnsupportedElement(frame.get_type().to_string()));
+            }
+            object_manager.parent_transform(frame_id) * convert_1x6_to_3x3(frame.calc_transform())
+        };
+        let Some(to_frame) = frame_transform.try_inverse() else {
+            return Ok(false);
+        };
+
+        let ancestors = object_manager.ancestors(frame_id);
+        let mut moved: Vec<(String, Option<String>, (f64, f64))> = Vec::new();
+        for id in ids {
+            let Some(object) = object_manager.get(id) else {
+                continue;
+            };
+            let skip = id == frame_id
+                || ancestors.contains(id)
+                || moved.iter().any(|(other, ..)| other == id)
+                || is_connector(&**object.borrow());
+            if skip {
+                continue;
+            }
+            let (x, y) = object.borrow().get_position();
+            let world = object_manager.parent_transform(id) * na::Vector3::new(x, y, 1.0);
+            let local = to_frame * world;
+            moved.push((id.clone(), object_manager.parent_of(id), (local.x, local.y)));
+        }
+        if moved.is_empty() {
+            return Ok(false);
+        }
+
+        self.history.borrow_mut().begin_transaction("add_to_frame");
+        for (id, parent, (x, y)) in &moved {
+            if let Some(parent) = parent {
+                update_children(object_manager.get(parent), |children| children.retain(|child| child != id));
+            }
+            if let Some(object) = object_manager.get(id) {
+                object.borrow_mut().set_position(*x, *y);
+            }
+        }
+        update_children(Some(frame), |children| {
+            children.retain(|child| !moved.iter().any(|(id, ..)| id == child));
+            children.extend(moved.iter().map(|(id, ..)| id.clone()));
+        });
+        self.history.borrow_mut().commit();
+        drop(object_manager);
+        self.request_render();
+        Ok(true)
+    }
+
+    // 把对象移出所在的框架，成为顶层对象并保持原来的位置；不在框架中的对象忽略。整体为一次撤销
+    pub fn remove_from_frame(&self, ids: &[String]) -> EditingResult<bool> {
+        let object_manager = self.object_manager.borrow();
+        object_manager.refresh_spatial_index();
+        if let Some(missing) = ids.iter().find(|id| !object_manager.contains(id)) {
+            return Err(EditingError::ObjectNotFound(missing.clone()));
+        }
+        let mut moved: Vec<(String, String, (f64, f64))> = Vec::new();
+        for id in ids {
+            let Some(parent) = object_manager.parent_of(id) else {
+                continue;
+            };
+            if moved.iter().any(|(other, ..)| other == id) {
+                continue;
+            }
+            let Some(object) = object_manager.get(id) else {
+                continue;
+            };
+            let (x, y) = object.borrow().get_position();
+            let world = object_manager.parent_transform(id) * na::Vector3::new(x, y, 1.0);
+            moved.push((id.clone(), parent, (world.x, world.y)));
+        }
+        if moved.is_empty() {
+            return Ok(false);
+        }
+
+        self.history.borrow_mut().begin_transaction("remove_from_frame");
+        for (id, parent, (x, y)) in &moved {
+            update_children(object_manager.get(parent), |children| children.retain(|child| child != id));
+            if let Some(object) = object_manager.get(id) {
+                object.borrow_mut().set_position(*x, *y);
+            }
+        }
+        self.history.borrow_mut().commit();
+        drop(object_manager);
+        self.request_render();
+        Ok(true)
+    }
+
+    // 所在的框架，顶层对象为 None
+    pub fn parent_frame(&self, id: &str) -> Option<String> {
+        let object_manager = self.object_manager.borrow();
+        object_manager.refresh_spatial_index();
+        object_manager.parent_of(id)
+    }
+
+    // 框架中的所有子对象，包括嵌套框架中的，用于删除框架时一并删除
+    pub(super) fn frame_descendants(&self, id: &str) -> Vec<String> {
+        let object_manager = self.object_manager.borrow();
+        object_manager.refresh_spatial_index();
+        let mut descendants = Vec::new();
+        let mut stack = object_manager.children_of(id);
+        while let Some(child) = stack.pop() {
+            if child == id || descendants.contains(&child) {
+                continue;
+            }
+            stack.extend(object_manager.children_of(&child));
+            descendants.push(child);
+        }
+        descendants
+    }
+}
+
+// 位置由其他对象决定的元素（连线）；组件实例依赖主组件的只是大小
+fn is_connector(object: &dyn Renderable) -> bool {
+    object.instance_of().is_none() && !object.dependencies().is_empty()
+}
+
+// 通过记录历史的 setter 修改框架的子对象列表
+fn update_children(frame: Option<Rc<RefCell<Box<dyn Renderable>>>>, update: impl FnOnce(&mut Vec<String>)) {
+    let Some(frame) = frame else {
+        return;
+    };
+    let mut frame = frame.borrow_mut();
+    if let Some(frame) = (&mut **frame as &mut dyn Any).downcast_mut::<Frame>() {
+        let mut children = frame.children.clone();
+        update(&mut children);
+        frame.set_children(children);
+    }
+}
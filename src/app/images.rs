@@ -0,0 +1,65 @@
+use futures::future::join_all;
+use serde_json::json;
+
+use super::App;
+use crate::error::{EditingError, EditingResult};
+use crate::events::AppEvent;
+use crate::helper::to_js_value;
+use crate::image::Image;
+use crate::image_cache::ImageStatus;
+use crate::log::log_warn;
+
+impl App {
+    // 加载并缓存图片，同一 URL 正在加载时等待同一次加载的结果；
+    // 加载完成后重绘并触发 "imageload"，失败时触发 "imageerror"，事件只由发起加载的一方触发
+    pub async fn load_image(&self, url: &str) -> EditingResult<Image<'static>> {
+        let waiting = {
+            let mut images = self.images.borrow_mut();
+            if let Some(image) = images.get(url) {
+                return Ok(image);
+            }
+            images.begin(url)
+        };
+        if let Some(receiver) = waiting {
+            let result = receiver
+                .await
+                .map_err(|_| EditingError::Dom(format!("Image load cancelled: {}", url)))?;
+            return result.map_err(EditingError::Dom);
+        }
+
+        match Image::from_url(url).await {
+            Ok(image) => {
+                self.images.borrow_mut().finish(url, Ok(image.clone()));
+                let payload = to_js_value(&json!({ "url": url }));
+                let _ = self.events.emit(AppEvent::IMAGE_LOAD.into(), &payload);
+                self.request_render();
+                Ok(image)
+            }
+            Err(e) => {
+                log_warn!("Failed to load image {}: {}", url, e);
+                self.images.borrow_mut().finish(url, Err(e.to_string()));
+                let payload = to_js_value(&json!({ "url": url, "error": e.to_string() }));
+                let _ = self.events.emit(AppEvent::IMAGE_ERROR.into(), &payload);
+                Err(e)
+            }
+        }
+    }
+
+    // 在显示场景前预先加载，返回加载失败的 URL
+    pub async fn preload_images(&self, urls: &[String]) -> Vec<String> {
+        let results = join_all(urls.iter().map(|url| self.load_image(url))).await;
+        urls.iter()
+            .zip(results)
+            .filter_map(|(url, result)| result.is_err().then(|| url.clone()))
+            .collect()
+    }
+
+    pub fn image_status(&self, url: &str) -> Option<ImageStatus> {
+        self.images.borrow().status(url)
+    }
+
+    // 移除缓存，下次 load_image 会重新请求
+    pub fn evict_image(&self, url: &str) -> bool {
+        self.images.borrow_mut().remove(url)
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+use super::App;
+use crate::animation::InertiaAnimation;
+
+// 只用松手前这段时间（毫秒）内的移动估算速度，停顿后再松手不会滑动
+const VELOCITY_WINDOW_MS: f64 = 100.0;
+// 速度每秒按 e^-PAN_FRICTION 衰减
+const PAN_FRICTION: f64 = 4.0;
+// 屏幕像素/秒：松手速度低于 MIN_FLING_SPEED 时不滑动，滑动中低于 REST_SPEED 时停止
+const MIN_FLING_SPEED: f64 = 150.0;
+const REST_SPEED: f64 = 10.0;
+
+// 拖动平移时记录最近的位移，(事件时间戳, dx, dy)
+#[derive(Debug, Default)]
+pub(super) struct VelocityTracker {
+    samples: VecDeque<(f64, f64, f64)>,
+}
+
+impl VelocityTracker {
+    pub(super) fn add(&mut self, time_ms: f64, dx: f64, dy: f64) {
+        self.samples.push_back((time_ms, dx, dy));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, ..)| time_ms - time > VELOCITY_WINDOW_MS)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    // 每秒的位移；第一个样本的位移发生在窗口之前，只用来确定起始时间
+    pub(super) fn velocity(&self, now_ms: f64) -> (f64, f64) {
+        let recent: Vec<_> = self
+            .samples
+            .iter()
+            .filter(|(time, ..)| now_ms - time <= VELOCITY_WINDOW_MS)
+            .collect();
+        let (Some(first), Some(last)) = (recent.first(), recent.last()) else {
+            return (0.0, 0.0);
+        };
+        let elapsed = (last.0 - first.0) / 1000.0;
+        if recent.len() < 2 || elapsed <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let (dx, dy) = recent[1..]
+            .iter()
+            .fold((0.0, 0.0), |(x, y), (_, dx, dy)| (x + dx, y + dy));
+        (dx / elapsed, dy / elapsed)
+    }
+}
+
+impl App {
+    // 为 false 时松手立即停止，不做惯性滑动
+    pub fn set_pan_inertia(&self, enabled: bool) {
+        self.pan_inertia.set(enabled);
+        if !enabled {
+            self.stop_inertia();
+        }
+    }
+
+    // velocity 为场景偏移每秒的变化量，与 SceneManager::pan 的参数相同
+    pub(super) fn start_inertia(&self, velocity: (f64, f64)) {
+        self.stop_inertia();
+        if !self.pan_inertia.get() {
+            return;
+        }
+        let zoom = self.scene_manager.borrow().zoom();
+        if velocity.0.hypot(velocity.1) * zoom < MIN_FLING_SPEED {
+            return;
+        }
+        let animation = InertiaAnimation::new("offset", velocity, PAN_FRICTION, REST_SPEED / zoom);
+        let handle = self
+            .animation_manager
+            .borrow_mut()
+            .add_scene_animation(Box::new(animation));
+        *self.inertia.borrow_mut() = Some(handle);
+        self.request_render();
+    }
+
+    // 新的按下会打断滑动，视图停在当前位置
+    pub fn stop_inertia(&self) {
+        if let Some(handle) = self.inertia.borrow_mut().take() {
+            handle.cancel();
+        }
+    }
+}
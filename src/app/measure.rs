@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use web_sys::MouseEvent;
+
+use super::tools::{capture_pointer, pointer_id};
+use super::App;
+use crate::element::Renderable;
+use crate::events::AppEvent;
+use crate::helper::{now_ms, to_js_value};
+use crate::scene_manager::{DimensionLabel, DocumentUnit, MeasureOptions, MeasureOverlay, Ruler};
+
+// 尺寸停止变化后标签继续显示的时长（毫秒）
+const DIMENSION_LINGER_MS: f64 = 800.0;
+
+#[derive(Debug, Default)]
+pub(super) struct MeasureState {
+    options: MeasureOptions,
+    ruler: Option<Ruler>,
+    // 正在拖拽测量线的指针
+    pub(super) dragging: Option<i32>,
+    // 上一帧选中对象的 [宽, 高, 旋转]
+    last: HashMap<String, [f64; 3]>,
+    visible_until: f64,
+}
+
+// 测量结果，距离已换算为文档单位
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Measurement {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    pub distance: f64,
+    pub angle: f64,
+    pub unit: DocumentUnit,
+}
+
+impl App {
+    pub fn set_measure_options(&self, options: MeasureOptions) {
+        self.measure.borrow_mut().options = options;
+        self.request_render();
+    }
+
+    pub fn measure_options(&self) -> MeasureOptions {
+        self.measure.borrow().options.clone()
+    }
+
+    // 当前测量线的结果，没有测量时为 None
+    pub fn measurement(&self) -> Option<Measurement> {
+        let ruler = self.measure.borrow().ruler?;
+        let units = self.scene_manager.borrow().units();
+        Some(Measurement {
+            from: ruler.from,
+            to: ruler.to,
+            distance: units.to_units(ruler.distance()),
+            angle: ruler.angle(),
+            unit: units.unit,
+        })
+    }
+
+    pub fn clear_measure(&self) {
+        let mut measure = self.measure.borrow_mut();
+        measure.dragging = None;
+        if measure.ruler.take().is_some() {
+            self.request_render();
+        }
+    }
+
+    // 尺寸标签显示期间需要继续绘制，到期后的下一帧把标签去掉
+    pub(super) fn is_measure_lingering(&self) -> bool {
+        self.measure
+            .try_borrow()
+            .is_ok_and(|measure| measure.visible_until > now_ms())
+    }
+
+    pub(super) fn begin_measure(&self, event: &MouseEvent) {
+        let Some(point) = self.event_to_world(event) else {
+            return;
+        };
+        capture_pointer(event);
+        let mut measure = self.measure.borrow_mut();
+        measure.dragging = Some(pointer_id(event).unwrap_or_default());
+        measure.ruler = Some(Ruler { from: point, to: point });
+        self.request_render();
+    }
+
+    // 按住 Shift 时约束为 45° 的倍数
+    pub(super) fn extend_measure(&self, event: &MouseEvent) {
+        let Some(point) = self.event_to_world(event) else {
+            return;
+        };
+        let mut measure = self.measure.borrow_mut();
+        let dragging = measure.dragging;
+        let Some(ruler) = measure.ruler.as_mut().filter(|_| dragging.is_some()) else {
+            return;
+        };
+        if pointer_id(event).is_some_and(|id| Some(id) != dragging) {
+            return;
+        }
+        let (mut dx, mut dy) = (point.0 - ruler.from.0, point.1 - ruler.from.1);
+        if event.shift_key() {
+            let step = std::f64::consts::FRAC_PI_4;
+            let angle = (dy.atan2(dx) / step).round() * step;
+            let length = dx.hypot(dy);
+            (dx, dy) = (length * angle.cos(), length * angle.sin());
+        }
+        ruler.to = (ruler.from.0 + dx, ruler.from.1 + dy);
+        self.request_render();
+    }
+
+    // 抬起后测量线保留到下一次测量或切换工具，并触发 "measure" 事件
+    pub(super) fn finish_measure(&self) {
+        if self.measure.borrow_mut().dragging.take().is_none() {
+            return;
+        }
+        if let Some(measurement) = self.measurement() {
+            let _ = self
+                .events
+                .emit(AppEvent::MEASURE.into(), &to_js_value(&measurement));
+        }
+    }
+
+    // 每帧更新测量覆盖层；选中对象的宽高或旋转变化时显示尺寸标签
+    pub(super) fn refresh_measure_overlay(&self) {
+        let (Ok(mut measure), Ok(object_manager), Ok(scene_manager)) = (
+            self.measure.try_borrow_mut(),
+            self.object_manager.try_borrow(),
+            self.scene_manager.try_borrow(),
+        ) else {
+            return;
+        };
+        let mut current = HashMap::new();
+        let mut dimensions = Vec::new();
+        if measure.options.dimensions {
+            for id in self.selection.borrow().iter() {
+                let Some(object) = object_manager.get(id) else {
+                    continue;
+                };
+                let Ok(object) = object.try_borrow() else {
+                    continue;
+                };
+                let (width, height) = object_size(&**object);
+                let rotation = object.get_rotation();
+                current.insert(id.clone(), [width, height, rotation]);
+                let Some(bounds) = object_manager.world_bounds(id) else {
+                    continue;
+                };
+                dimensions.push(DimensionLabel {
+                    bounds,
+                    width,
+                    height,
+                    rotation,
+                });
+            }
+        }
+        // 刚选中的对象不算变化
+        let changed = current
+            .iter()
+            .any(|(id, size)| measure.last.get(id).is_some_and(|last| last != size));
+        let now = now_ms();
+        if changed {
+            measure.visible_until = now + DIMENSION_LINGER_MS;
+        }
+        measure.last = current;
+        if measure.visible_until <= now {
+            dimensions.clear();
+        }
+        scene_manager.set_measure_overlay(MeasureOverlay {
+            options: measure.options.clone(),
+            ruler: measure.ruler,
+            dimensions,
+        });
+    }
+}
+
+// 未旋转时的宽高（含缩放）；没有 width/height 属性的对象用包围盒
+fn object_size(object: &dyn Renderable) -> (f64, f64) {
+    let bounds = object.bounding_box();
+    let (scale_x, scale_y) = object.get_scale();
+    let size = |name: &str, scale: f64, fallback: f64| {
+        object
+            .property(name)
+            .and_then(|value| value.as_f64())
+            .map_or(fallback, |value| value * scale.abs())
+    };
+    (
+        size("width", scale_x, bounds.width),
+        size("height", scale_y, bounds.height),
+    )
+}
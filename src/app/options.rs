@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::history::{DEFAULT_MAX_BYTES, DEFAULT_MAX_UNITS};
+use crate::scene_manager::{
+    CanvasContextType, DocumentUnits, GridOptions, HitTestMode, ProgressiveOptions, SceneManagerOptions, ScrollbarOptions, DEFAULT_HIT_TOLERANCE,
+    DEFAULT_MAX_ZOOM, DEFAULT_MIN_ZOOM,
+};
+
+// 创建 App 时的配置，JS 侧传入的对象字段为驼峰命名，缺省字段使用默认值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AppOptions {
+    pub context_type: CanvasContextType,
+    pub supersampling: f64,
+    pub hit_resolution: f64,
+    pub hit_test_mode: HitTestMode,
+    // 描边两侧额外可点中的屏幕像素
+    pub hit_tolerance: f64,
+    // 0 表示不限制
+    pub max_history_units: usize,
+    pub max_history_bytes: usize,
+    // 排队更新的最短应用间隔（毫秒）
+    pub update_interval_ms: f64,
+    // 每帧先用该颜色填充画布，None 为透明
+    pub background: Option<String>,
+    // 背景网格，如 { kind: "dots", spacing: 24 }
+    pub grid: GridOptions,
+    // 视口滚动条，如 { enabled: true, hideDelayMs: 1000 }
+    pub scrollbars: ScrollbarOptions,
+    // 可见对象很多时分帧绘制，如 { threshold: 2000, frameBudgetMs: 6 }
+    pub progressive: ProgressiveOptions,
+    // 文档的长度单位和比例，如 { unit: "m", scale: 50 }
+    pub units: DocumentUnits,
+    pub min_zoom: f64,
+    pub max_zoom: f64,
+    // 平移、缩放、旋转是否可以撤销，默认不进入文档历史
+    pub record_camera_history: bool,
+    // 右键时阻止浏览器菜单，由宿主根据 "contextmenu" 事件自行显示
+    pub suppress_context_menu: bool,
+    // 平移工具松手后按拖动速度继续惯性滑动
+    pub pan_inertia: bool,
+}
+
+impl Default for AppOptions {
+    fn default() -> Self {
+        Self {
+            context_type: CanvasContextType::Canvas2d,
+            supersampling: 2.0,
+            hit_resolution: 1.0,
+            hit_test_mode: HitTestMode::Canvas,
+            hit_tolerance: DEFAULT_HIT_TOLERANCE,
+            max_history_units: DEFAULT_MAX_UNITS,
+            max_history_bytes: DEFAULT_MAX_BYTES,
+            update_interval_ms: 0.0,
+            background: None,
+            grid: GridOptions::default(),
+            scrollbars: ScrollbarOptions::default(),
+            progressive: ProgressiveOptions::default(),
+            units: DocumentUnits::default(),
+            min_zoom: DEFAULT_MIN_ZOOM,
+            max_zoom: DEFAULT_MAX_ZOOM,
+            record_camera_history: false,
+            suppress_context_menu: true,
+            pan_inertia: true,
+        }
+    }
+}
+
+impl AppOptions {
+    pub(super) fn apply_to(&self, options: &mut SceneManagerOptions) {
+        options.context_type = Some(self.context_type.clone());
+        options.supersampling = self.supersampling;
+        options.hit_resolution = self.hit_resolution;
+        options.hit_test_mode = self.hit_test_mode;
+        options.hit_tolerance = self.hit_tolerance;
+        options.background = self.background.clone();
+        options.grid = self.grid.clone();
+        options.scrollbars = self.scrollbars.clone();
+        options.progressive = self.progressive;
+        options.units = self.units;
+        options.min_zoom = self.min_zoom;
+        options.max_zoom = self.max_zoom;
+        options.record_camera_history = self.record_camera_history;
+    }
+}
@@ -0,0 +1,430 @@
+use std::any::Any;
+
+use serde_json::json;
+use web_sys::MouseEvent;
+
+use super::tools::{capture_pointer, pointer_id};
+use super::App;
+use crate::element::{Path, PathPoint};
+use crate::error::{EditingError, EditingResult};
+use crate::history::{HistoryItem, ObjectHistoryItem};
+use crate::scene_manager::{AnchorHandle, HandleHit, PathHandles};
+
+// 锚点、控制点和曲线的命中半径（屏幕像素）
+const HANDLE_HIT_RADIUS: f64 = 6.0;
+const CURVE_SAMPLES: usize = 32;
+
+// 路径编辑模式：显示 Path 的锚点和控制点，拖拽修改，每次手势记录为一条历史
+#[derive(Debug)]
+pub(super) struct PathEdit {
+    id: String,
+    selected: Option<usize>,
+    drag: Option<PathDrag>,
+}
+
+#[derive(Debug)]
+struct PathDrag {
+    pointer_id: i32,
+    target: HandleHit,
+    // 按下时的世界坐标
+    origin: (f64, f64),
+    // 手势开始前的点，作为撤销数据
+    before: Vec<PathPoint>,
+    // 拖拽的起始状态，插入点和补全控制柄之后的点
+    start: Vec<PathPoint>,
+}
+
+// 编辑所需的 Path 快照，segments 为世界坐标，第 i 段从 points[i] 出发
+struct PathSnapshot {
+    origin: (f64, f64),
+    points: Vec<PathPoint>,
+    segments: Vec<[(f64, f64); 4]>,
+    closed: bool,
+    stroke_width: f64,
+}
+
+impl PathSnapshot {
+    fn anchor(&self, index: usize) -> (f64, f64) {
+        let point = &self.points[index];
+        (self.origin.0 + point.x, self.origin.1 + point.y)
+    }
+
+    // 实际绘制出的两侧控制点；与锚点重合时视为没有
+    fn controls(&self, index: usize) -> [Option<(f64, f64)>; 2] {
+        let anchor = self.anchor(index);
+        let incoming = match index {
+            0 if self.closed => self.segments.last(),
+            0 => None,
+            _ => self.segments.get(index - 1),
+        };
+        let control_in = incoming.map(|segment| segment[2]).filter(|control| *control != anchor);
+        let control_out = self
+            .segments
+            .get(index)
+            .map(|segment| segment[1])
+            .filter(|control| *control != anchor);
+        [control_in, control_out]
+    }
+
+    // 把自动计算的控制点写成显式控制柄，之后修改相邻点时这一点的曲线形状不变
+    fn materialize(&self, points: &mut [PathPoint], index: usize) {
+        let anchor = self.anchor(index);
+        let relative = |control: (f64, f64)| (control.0 - anchor.0, control.1 - anchor.1);
+        let [control_in, control_out] = self.controls(index);
+        let point = &mut points[index];
+        point.handle_in = point.handle_in.or(control_in.map(relative));
+        point.handle_out = point.handle_out.or(control_out.map(relative));
+    }
+
+    fn handles(&self, selected: Option<usize>) -> PathHandles {
+        let anchors = (0..self.points.len())
+            .map(|index| {
+                let [control_in, control_out] = self.controls(index);
+                AnchorHandle {
+                    point: self.anchor(index),
+                    control_in,
+                    control_out,
+                    corner: self.points[index].corner,
+                }
+            })
+            .collect();
+        PathHandles { anchors, selected }
+    }
+
+    // 曲线上离 point 最近的位置 (段序号, t, 距离)
+    fn nearest(&self, point: (f64, f64)) -> Option<(usize, f64, f64)> {
+        let mut best: Option<(usize, f64, f64)> = None;
+        for (index, segment) in self.segments.iter().enumerate() {
+            for step in 0..=CURVE_SAMPLES {
+                let t = step as f64 / CURVE_SAMPLES as f64;
+                let (x, y) = split_bezier(segment, t)[2];
+                let distance = (x - point.0).hypot(y - point.1);
+                if best.is_none_or(|(_, _, nearest)| distance < nearest) {
+                    best = Some((index, t, distance));
+                }
+            }
+        }
+        best
+    }
+
+    fn min_points(&self) -> usize {
+        if self.closed { 3 } else { 2 }
+    }
+}
+
+impl App {
+    // 进入 Path 的编辑模式，已在编辑的路径会先退出
+    pub fn edit_path(&self, id: &str) -> EditingResult<()> {
+        let object = self
+            .get(id)
+            .ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        {
+            let object = object.borrow();
+            if (&**object as &dyn Any).downcast_ref::<Path>().is_none() {
+                return Err(EditingError::UnsupportedElement(object.get_type().to_string()));
+            }
+        }
+        self.exit_path_edit();
+        *self.path_edit.borrow_mut() = Some(PathEdit {
+            id: id.to_string(),
+            selected: None,
+            drag: None,
+        });
+        self.refresh_path_handles();
+        self.request_render();
+        Ok(())
+    }
+
+    // 正在拖拽时先提交这次修改
+    pub fn exit_path_edit(&self) {
+        self.finish_path_drag();
+        if self.path_edit.borrow_mut().take().is_none() {
+            return;
+        }
+        if let Ok(scene_manager) = self.scene_manager.try_borrow() {
+            scene_manager.set_path_handles(None);
+        }
+        self.request_render();
+    }
+
+    pub fn editing_path(&self) -> Option<String> {
+        self.path_edit.borrow().as_ref().map(|edit| edit.id.clone())
+    }
+
+    pub fn selected_path_point(&self) -> Option<usize> {
+        self.path_edit.borrow().as_ref().and_then(|edit| edit.selected)
+    }
+
+    // 删除正在编辑的路径上的一个点，开放路径至少保留 2 个点，封闭路径至少 3 个
+    pub fn remove_path_point(&self, index: usize) -> EditingResult<bool> {
+        let (id, snapshot) = self.editing_snapshot()?;
+        if index >= snapshot.points.len() || snapshot.points.len() <= snapshot.min_points() {
+            return Ok(false);
+        }
+        let mut points = snapshot.points.clone();
+        points.remove(index);
+        if let Some(edit) = self.path_edit.borrow_mut().as_mut() {
+            edit.selected = None;
+        }
+        self.commit_path_points(&id, &snapshot.points, points);
+        Ok(true)
+    }
+
+    // 在平滑和尖角之间切换：尖角去掉控制柄，平滑点沿相邻两点的方向生成对称的控制柄
+    pub fn toggle_path_anchor(&self, index: usize) -> EditingResult<bool> {
+        let (id, snapshot) = self.editing_snapshot()?;
+        let count = snapshot.points.len();
+        if index >= count {
+            return Ok(false);
+        }
+        let mut points = snapshot.points.clone();
+        let point = &mut points[index];
+        if point.corner {
+            let neighbour = |offset: isize| {
+                let other = index as isize + offset;
+                let other = match snapshot.closed {
+                    true => other.rem_euclid(count as isize) as usize,
+                    false => other.clamp(0, count as isize - 1) as usize,
+                };
+                snapshot.points[other]
+            };
+            let (prev, next) = (neighbour(-1), neighbour(1));
+            let tangent = ((next.x - prev.x) / 6.0, (next.y - prev.y) / 6.0);
+            point.corner = false;
+            point.handle_in = Some((-tangent.0, -tangent.1));
+            point.handle_out = Some(tangent);
+        } else {
+            point.corner = true;
+            point.handle_in = None;
+            point.handle_out = None;
+        }
+        self.commit_path_points(&id, &snapshot.points, points);
+        Ok(true)
+    }
+
+    // 每帧按路径的当前状态更新锚点覆盖层，路径被删除（例如撤销）时退出编辑
+    pub(super) fn refresh_path_handles(&self) {
+        let Some((id, selected)) = self
+            .path_edit
+            .borrow()
+            .as_ref()
+            .map(|edit| (edit.id.clone(), edit.selected))
+        else {
+            return;
+        };
+        let Some(snapshot) = self.path_snapshot(&id) else {
+            self.exit_path_edit();
+            return;
+        };
+        if let Ok(scene_manager) = self.scene_manager.try_borrow() {
+            scene_manager.set_path_handles(Some(snapshot.handles(selected)));
+        }
+    }
+
+    // 返回 true 表示事件已被编辑模式处理；点在路径外时退出编辑，交给当前工具
+    pub(super) fn path_edit_pointer_down(&self, event: &MouseEvent) -> bool {
+        let Ok((id, snapshot)) = self.editing_snapshot() else {
+            return false;
+        };
+        let Some(point) = self.event_to_world(event) else {
+            return false;
+        };
+        let radius = HANDLE_HIT_RADIUS / self.scene_manager.borrow().zoom();
+        let selected = self.selected_path_point();
+        let hit = snapshot.handles(selected).hit(point, radius);
+
+        let mut start = snapshot.points.clone();
+        let target = match hit {
+            Some(HandleHit::Anchor(index)) if event.alt_key() => {
+                let _ = self.toggle_path_anchor(index);
+                return true;
+            }
+            Some(HandleHit::Anchor(index)) if event.ctrl_key() || event.meta_key() => {
+                let _ = self.remove_path_point(index);
+                return true;
+            }
+            Some(HandleHit::Anchor(index)) => HandleHit::Anchor(index),
+            Some(control @ (HandleHit::ControlIn(index) | HandleHit::ControlOut(index))) => {
+                snapshot.materialize(&mut start, index);
+                control
+            }
+            None => {
+                let reach = radius + snapshot.stroke_width / 2.0;
+                match snapshot.nearest(point).filter(|(_, _, distance)| *distance <= reach) {
+                    Some((segment, t, _)) => HandleHit::Anchor(insert_point(&snapshot, &mut start, segment, t)),
+                    None => {
+                        self.exit_path_edit();
+                        return false;
+                    }
+                }
+            }
+        };
+
+        let (HandleHit::Anchor(index) | HandleHit::ControlIn(index) | HandleHit::ControlOut(index)) = target;
+        if let Some(edit) = self.path_edit.borrow_mut().as_mut() {
+            edit.selected = Some(index);
+            edit.drag = Some(PathDrag {
+                pointer_id: pointer_id(event).unwrap_or_default(),
+                target,
+                origin: point,
+                before: snapshot.points,
+                start: start.clone(),
+            });
+        }
+        capture_pointer(event);
+        self.apply_path_points(&id, start);
+        true
+    }
+
+    pub(super) fn path_edit_pointer_move(&self, event: &MouseEvent) -> bool {
+        let Some(point) = self.event_to_world(event) else {
+            return false;
+        };
+        let (id, points) = {
+            let edit = self.path_edit.borrow();
+            let Some((edit, drag)) = edit.as_ref().and_then(|edit| Some((edit, edit.drag.as_ref()?))) else {
+                return false;
+            };
+            if pointer_id(event).is_some_and(|id| id != drag.pointer_id) {
+                return true;
+            }
+            let delta = (point.0 - drag.origin.0, point.1 - drag.origin.1);
+            (edit.id.clone(), drag_points(drag, delta))
+        };
+        self.apply_path_points(&id, points);
+        true
+    }
+
+    pub(super) fn path_edit_pointer_up(&self, event: &MouseEvent) -> bool {
+        if !self.path_edit_pointer_move(event) {
+            return false;
+        }
+        self.finish_path_drag();
+        true
+    }
+
+    // 拖拽结束，整个手势记录为一条历史
+    pub(super) fn finish_path_drag(&self) {
+        let Some((id, before)) = self
+            .path_edit
+            .borrow_mut()
+            .as_mut()
+            .and_then(|edit| Some((edit.id.clone(), edit.drag.take()?.before)))
+        else {
+            return;
+        };
+        if let Some(snapshot) = self.path_snapshot(&id) {
+            self.commit_path_points(&id, &before, snapshot.points);
+        }
+    }
+
+    fn editing_snapshot(&self) -> EditingResult<(String, PathSnapshot)> {
+        let id = self
+            .editing_path()
+            .ok_or(EditingError::NotInitialized("PathEdit"))?;
+        let snapshot = self
+            .path_snapshot(&id)
+            .ok_or_else(|| EditingError::ObjectNotFound(id.clone()))?;
+        Ok((id, snapshot))
+    }
+
+    fn path_snapshot(&self, id: &str) -> Option<PathSnapshot> {
+        let object = self.get(id)?;
+        let object = object.try_borrow().ok()?;
+        let path = (&**object as &dyn Any).downcast_ref::<Path>()?;
+        Some(PathSnapshot {
+            origin: (path.x, path.y),
+            points: path.points.clone(),
+            segments: path.edit_segments(),
+            closed: path.closed,
+            stroke_width: path.stroke_width,
+        })
+    }
+
+    // 拖拽过程中直接写入，不记录历史
+    fn apply_path_points(&self, id: &str, points: Vec<PathPoint>) {
+        if let Some(object) = self.get(id) {
+            object.borrow_mut().update(json!({ "points": points }));
+        }
+        self.refresh_path_handles();
+        self.request_render();
+    }
+
+    fn commit_path_points(&self, id: &str, before: &[PathPoint], after: Vec<PathPoint>) {
+        if before != after.as_slice() {
+            let item = ObjectHistoryItem::new(
+                id.to_string(),
+                json!({ "points": before }),
+                json!({ "points": after }),
+            );
+            self.record_history(HistoryItem::ObjectUpdate(item));
+        }
+        self.apply_path_points(id, after);
+    }
+}
+
+// 按拖拽目标和位移计算新的点；控制柄为相对锚点的偏移，移动锚点时一起移动
+fn drag_points(drag: &PathDrag, delta: (f64, f64)) -> Vec<PathPoint> {
+    let mut points = drag.start.clone();
+    let moved = |(x, y): (f64, f64)| (x + delta.0, y + delta.1);
+    match drag.target {
+        HandleHit::Anchor(index) => {
+            let point = &mut points[index];
+            (point.x, point.y) = moved((point.x, point.y));
+        }
+        HandleHit::ControlIn(index) | HandleHit::ControlOut(index) => {
+            let point = &mut points[index];
+            let incoming = matches!(drag.target, HandleHit::ControlIn(_));
+            let (dragged, opposite) = match incoming {
+                true => (&mut point.handle_in, &mut point.handle_out),
+                false => (&mut point.handle_out, &mut point.handle_in),
+            };
+            let handle = moved(dragged.unwrap_or_default());
+            *dragged = Some(handle);
+            // 平滑点保持两侧控制柄共线，另一侧长度不变
+            if !point.corner {
+                if let Some((ox, oy)) = *opposite {
+                    let length = ox.hypot(oy);
+                    let current = handle.0.hypot(handle.1);
+                    if current > f64::EPSILON {
+                        *opposite = Some((-handle.0 / current * length, -handle.1 / current * length));
+                    }
+                }
+            }
+        }
+    }
+    points
+}
+
+// 在第 segment 段的 t 处插入一个点并返回它的序号，用 de Casteljau 分割保持曲线形状不变
+fn insert_point(snapshot: &PathSnapshot, points: &mut Vec<PathPoint>, segment: usize, t: f64) -> usize {
+    let next = (segment + 1) % points.len();
+    // 两端的另一侧控制点会随新点改变，先固定下来
+    snapshot.materialize(points, segment);
+    snapshot.materialize(points, next);
+
+    let [out_start, in_new, at, out_new, in_end] = split_bezier(&snapshot.segments[segment], t);
+    let local = |(x, y): (f64, f64)| (x - snapshot.origin.0, y - snapshot.origin.1);
+    let relative = |control: (f64, f64), anchor: (f64, f64)| (control.0 - anchor.0, control.1 - anchor.1);
+    let start_anchor = snapshot.anchor(segment);
+    let end_anchor = snapshot.anchor(next);
+    points[segment].handle_out = Some(relative(out_start, start_anchor));
+    points[next].handle_in = Some(relative(in_end, end_anchor));
+
+    let pressure = points[segment].pressure + (points[next].pressure - points[segment].pressure) * t;
+    let (x, y) = local(at);
+    let mut point = PathPoint::new(x, y, pressure);
+    point.handle_in = Some(relative(in_new, at));
+    point.handle_out = Some(relative(out_new, at));
+    points.insert(segment + 1, point);
+    segment + 1
+}
+
+// de Casteljau 分割：(起点侧控制点, 新点入控制点, 新点, 新点出控制点, 终点侧控制点)
+fn split_bezier(segment: &[(f64, f64); 4], t: f64) -> [(f64, f64); 5] {
+    let lerp = |a: (f64, f64), b: (f64, f64)| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+    let [p0, p1, p2, p3] = *segment;
+    let (a, b, c) = (lerp(p0, p1), lerp(p1, p2), lerp(p2, p3));
+    let (d, e) = (lerp(a, b), lerp(b, c));
+    [a, d, lerp(d, e), e, c]
+}
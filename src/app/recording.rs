@@ -0,0 +1,324 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use super::App;
+use crate::error::EditingResult;
+use crate::events::AppEvent;
+use crate::helper::{now_ms, request_animation_frame};
+use crate::history::{ElementHistoryItem, HistoryItem, SceneHistoryItem};
+use crate::op_log::{OpLog, OpLogRecorder, RecordedOperation};
+use crate::scene_manager::SceneDirtyData;
+use crate::session::{decode_session, encode_session, SessionEncoding};
+
+// 加载的回放；播放结束后保留，仍可跳转或重新播放
+#[derive(Debug)]
+pub(super) struct ActiveReplay {
+    // 日志开始时的状态，向前跳转时从这里重新应用
+    initial: Vec<HistoryItem>,
+    operations: Vec<RecordedOperation>,
+    // 已应用的操作数
+    next: usize,
+    // 当前回放到的日志时间（毫秒）
+    position: f64,
+    duration: f64,
+    speed: f64,
+    paused: bool,
+    // 上一次推进时的 now_ms()
+    last_tick: f64,
+}
+
+impl ActiveReplay {
+    // 取出时间不晚于 position 的未应用操作
+    fn take_due(&mut self) -> Vec<Vec<HistoryItem>> {
+        let due = self.operations[self.next..]
+            .iter()
+            .take_while(|operation| operation.time <= self.position)
+            .map(|operation| operation.items.clone())
+            .collect::<Vec<_>>();
+        self.next += due.len();
+        due
+    }
+
+    fn is_finished(&self) -> bool {
+        self.next == self.operations.len() && self.position >= self.duration
+    }
+}
+
+// 回放进度，时间均为日志时间（毫秒）
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayStatus {
+    pub position: f64,
+    pub duration: f64,
+    pub speed: f64,
+    pub paused: bool,
+    pub finished: bool,
+}
+
+// 只记录视图，回放时不改变画布尺寸
+fn view_value(data: &SceneDirtyData) -> Value {
+    let view = SceneDirtyData {
+        height: None,
+        width: None,
+        dpr: None,
+        ..data.clone()
+    };
+    serde_json::to_value(view).unwrap_or(Value::Null)
+}
+
+impl App {
+    // 开始录制操作日志：记录当前文档作为起点，之后的添加、删除、属性修改、视图变化和远端操作都按时间记录。
+    // 会停止进行中的回放
+    pub fn start_recording(&self) {
+        self.stop_replay();
+        let scene = view_value(&self.scene_manager.borrow().get_dirty_data());
+        let objects = self
+            .get_objects()
+            .iter()
+            .map(|object| {
+                let object = object.borrow();
+                ElementHistoryItem::new(
+                    object.id().value().to_string(),
+                    object.get_type().to_string(),
+                    object.to_value(),
+                )
+            })
+            .collect();
+        let started_at = web_sys::js_sys::Date::now();
+        *self.recorder.borrow_mut() = Some(OpLogRecorder::new(now_ms(), started_at, scene, objects));
+    }
+
+    // 没有在录制时返回 None
+    pub fn stop_recording(&self) -> Option<OpLog> {
+        let recorder = self.recorder.borrow_mut().take()?;
+        Some(recorder.finish(now_ms()))
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.borrow().is_some()
+    }
+
+    // 由 broadcast 调用；视图变化改由 record_camera 记录，避免开启相机历史时重复
+    pub(crate) fn record_operation(&self, items: &[HistoryItem], undo: bool) {
+        let mut recorder = self.recorder.borrow_mut();
+        let Some(recorder) = recorder.as_mut() else {
+            return;
+        };
+        let items: Vec<HistoryItem> = if undo {
+            items.iter().rev().map(HistoryItem::inverse).collect()
+        } else {
+            items.to_vec()
+        };
+        let items = items
+            .into_iter()
+            .filter(|item| !matches!(item, HistoryItem::SceneUpdate(_)))
+            .collect();
+        recorder.record(now_ms(), items);
+    }
+
+    pub(crate) fn record_camera(&self, old_data: &SceneDirtyData, new_data: &SceneDirtyData) {
+        let mut recorder = self.recorder.borrow_mut();
+        let Some(recorder) = recorder.as_mut() else {
+            return;
+        };
+        let item = SceneHistoryItem::new(view_value(old_data), view_value(new_data));
+        recorder.record(now_ms(), vec![HistoryItem::SceneUpdate(item)]);
+    }
+
+    // 清空当前文档和历史，恢复到日志开始时的状态后按记录的时间依次应用；speed 为播放倍速，
+    // f64::INFINITY 表示立即应用全部操作，非正数按 1 处理。播放到结尾时触发 "replayend"。
+    // 会停止进行中的录制
+    pub fn replay(&self, log: OpLog, speed: f64) {
+        self.stop_replay();
+        self.stop_recording();
+
+        let mut initial = vec![HistoryItem::SceneUpdate(SceneHistoryItem::new(Value::Null, log.scene))];
+        initial.extend(log.objects.into_iter().map(HistoryItem::AddElement));
+        let mut operations = log.operations;
+        operations.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let duration = operations.last().map_or(0.0, |operation| operation.time).max(log.duration);
+
+        self.restore_replay_start(&initial);
+        let replay = Rc::new(RefCell::new(ActiveReplay {
+            initial,
+            operations,
+            next: 0,
+            position: 0.0,
+            duration,
+            speed: if speed > 0.0 { speed } else { 1.0 },
+            paused: false,
+            last_tick: now_ms(),
+        }));
+        *self.replay.borrow_mut() = Some(replay.clone());
+        self.advance_replay(&replay);
+    }
+
+    // 回放 export_session 导出的数据，JSON 和二进制格式均可
+    pub fn replay_session(&self, data: &[u8], speed: f64) -> EditingResult<()> {
+        self.replay(decode_session(data)?, speed);
+        Ok(())
+    }
+
+    // 导出录制到目前为止的会话，录制继续进行；没有在录制时返回 None
+    pub fn export_session(&self, encoding: SessionEncoding) -> EditingResult<Option<Vec<u8>>> {
+        let Some(log) = self.recorder.borrow().as_ref().map(|recorder| recorder.snapshot(now_ms())) else {
+            return Ok(None);
+        };
+        encode_session(log, encoding).map(Some)
+    }
+
+    // 卸载回放，已应用的操作保留；没有加载回放时返回 false
+    pub fn stop_replay(&self) -> bool {
+        self.replay.borrow_mut().take().is_some()
+    }
+
+    // 是否正在播放，暂停和播放结束时为 false
+    pub fn is_replaying(&self) -> bool {
+        self.replay
+            .borrow()
+            .as_ref()
+            .is_some_and(|replay| !replay.borrow().paused)
+    }
+
+    pub fn pause_replay(&self) -> bool {
+        let Some(replay) = self.replay.borrow().clone() else {
+            return false;
+        };
+        let mut replay = replay.borrow_mut();
+        let was_playing = !replay.paused;
+        replay.paused = true;
+        was_playing
+    }
+
+    // 继续播放，已播放到结尾时从头开始
+    pub fn resume_replay(&self) -> bool {
+        let Some(replay) = self.replay.borrow().clone() else {
+            return false;
+        };
+        if !replay.borrow().paused {
+            return false;
+        }
+        if replay.borrow().is_finished() {
+            self.seek_replay(0.0);
+        }
+        {
+            let mut replay = replay.borrow_mut();
+            replay.paused = false;
+            replay.last_tick = now_ms();
+        }
+        self.advance_replay(&replay);
+        true
+    }
+
+    // 跳转到日志时间 position（毫秒），向前跳转时从开始状态重新应用；不改变暂停状态
+    pub fn seek_replay(&self, position: f64) -> bool {
+        let Some(replay) = self.replay.borrow().clone() else {
+            return false;
+        };
+        let rewind = {
+            let mut replay = replay.borrow_mut();
+            let position = position.clamp(0.0, replay.duration);
+            let rewind = position < replay.position;
+            if rewind {
+                replay.next = 0;
+            }
+            replay.position = position;
+            replay.last_tick = now_ms();
+            rewind.then(|| replay.initial.clone())
+        };
+        if let Some(initial) = rewind {
+            self.restore_replay_start(&initial);
+        }
+        let due = replay.borrow_mut().take_due();
+        self.apply_replay_operations(due);
+        true
+    }
+
+    pub fn set_replay_speed(&self, speed: f64) -> bool {
+        let Some(replay) = self.replay.borrow().clone() else {
+            return false;
+        };
+        if speed > 0.0 {
+            replay.borrow_mut().speed = speed;
+        }
+        true
+    }
+
+    pub fn replay_status(&self) -> Option<ReplayStatus> {
+        let replay = self.replay.borrow().clone()?;
+        let replay = replay.borrow();
+        Some(ReplayStatus {
+            position: replay.position,
+            duration: replay.duration,
+            speed: replay.speed,
+            paused: replay.paused,
+            finished: replay.is_finished(),
+        })
+    }
+
+    fn restore_replay_start(&self, initial: &[HistoryItem]) {
+        self.selection.borrow_mut().clear();
+        self.object_manager.borrow_mut().clear();
+        self.history.borrow_mut().clear();
+        self.history.borrow().apply_unrecorded(self, initial.to_vec());
+        self.notify_document_change();
+        self.request_render();
+    }
+
+    fn apply_replay_operations(&self, operations: Vec<Vec<HistoryItem>>) {
+        if operations.is_empty() {
+            return;
+        }
+        for items in operations {
+            self.history.borrow().apply_unrecorded(self, items);
+        }
+        self.notify_document_change();
+        self.request_render();
+    }
+
+    // 按经过的时间推进并应用到期的操作，未暂停时在下一帧继续；已被卸载或替换的回放不再继续
+    fn advance_replay(&self, replay: &Rc<RefCell<ActiveReplay>>) {
+        let is_current = |app: &App| {
+            app.replay
+                .borrow()
+                .as_ref()
+                .is_some_and(|current| Rc::ptr_eq(current, replay))
+        };
+        if !is_current(self) || replay.borrow().paused {
+            return;
+        }
+
+        let due = {
+            let mut replay = replay.borrow_mut();
+            let now = now_ms();
+            let position = if replay.speed.is_finite() {
+                replay.position + (now - replay.last_tick) * replay.speed
+            } else {
+                f64::INFINITY
+            };
+            replay.position = position.min(replay.duration);
+            replay.last_tick = now;
+            replay.take_due()
+        };
+        self.apply_replay_operations(due);
+
+        // 回调中可能已暂停、卸载或开始了新的回放
+        if !is_current(self) || replay.borrow().paused {
+            return;
+        }
+        if replay.borrow().is_finished() {
+            replay.borrow_mut().paused = true;
+            let _ = self.events.emit(AppEvent::REPLAY_END.into(), &JsValue::NULL);
+            return;
+        }
+        let app = self.clone();
+        let replay = replay.clone();
+        let closure = Closure::once_into_js(move || app.advance_replay(&replay));
+        request_animation_frame(closure.unchecked_ref());
+    }
+}
@@ -0,0 +1,225 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+use web_sys::js_sys::Function;
+
+use super::App;
+use crate::bounding_box::BoundingBox;
+use crate::error::EditingError;
+use crate::helper::{now_ms, to_js_value};
+use crate::log::log_error;
+
+pub type FrameScriptId = u64;
+
+#[derive(Debug, Default)]
+pub(super) struct FrameScripts {
+    next_id: FrameScriptId,
+    scripts: Vec<(FrameScriptId, Function)>,
+    // 正在执行的这一批脚本，执行期间它们不在 scripts 中
+    running: Vec<FrameScriptId>,
+    // 执行期间被移除的脚本，执行结束后再从列表中去掉
+    removed: Vec<FrameScriptId>,
+    // 所有帧共用一个代理，只在执行期间启用；没有脚本时释放，避免代理持有的 App 一直不被回收
+    proxy: Option<JsValue>,
+    active: Rc<Cell<bool>>,
+    frame: u64,
+    // 上一次执行的 now_ms()，用于计算 delta
+    last_run: Option<f64>,
+}
+
+// 传给帧脚本的受限 App：只能查询对象、修改属性、创建/回收对象和启动动画。
+// 属性修改直接生效，不进入历史也不会同步；只在帧脚本执行期间有效，其余时间的调用不再有任何效果
+#[wasm_bindgen(js_name = AppProxy)]
+pub struct AppProxy {
+    app: App,
+    active: Rc<Cell<bool>>,
+}
+
+impl AppProxy {
+    fn app(&self) -> Option<&App> {
+        self.active.get().then_some(&self.app)
+    }
+}
+
+#[wasm_bindgen(js_class = AppProxy)]
+impl AppProxy {
+    // 按绘制顺序从下到上的全部对象 id
+    pub fn objects(&self) -> Vec<String> {
+        let Some(app) = self.app() else {
+            return Vec::new();
+        };
+        app.get_objects()
+            .iter()
+            .map(|object| object.borrow().id().value().to_string())
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = findByTag)]
+    pub fn find_by_tag(&self, tag: &str) -> Vec<String> {
+        self.app().map(|app| app.find_by_tag(tag)).unwrap_or_default()
+    }
+
+    // 包围盒与世界坐标矩形相交的对象
+    #[wasm_bindgen(js_name = queryRect)]
+    pub fn query_rect(&self, x: f64, y: f64, width: f64, height: f64) -> Vec<String> {
+        let Some(app) = self.app() else {
+            return Vec::new();
+        };
+        app.object_manager
+            .borrow()
+            .query_rect(&BoundingBox::new(x, y, width, height))
+    }
+
+    // 对象不存在或没有该属性时返回 undefined
+    pub fn get(&self, id: &str, name: &str) -> JsValue {
+        self.app()
+            .and_then(|app| app.get(id))
+            .and_then(|object| object.borrow().property(name))
+            .map_or(JsValue::UNDEFINED, |value| to_js_value(&value))
+    }
+
+    // props 为 { 属性名: 值 }，对象不存在时返回 false
+    pub fn set(&self, id: &str, props: JsValue) -> Result<bool, JsValue> {
+        let Some(app) = self.app() else {
+            return Ok(false);
+        };
+        let props: Value = serde_wasm_bindgen::from_value(props)?;
+        if !props.is_object() {
+            return Err(EditingError::SerdeError(serde::de::Error::custom("props must be an object")).into());
+        }
+        Ok(app
+            .object_manager
+            .borrow_mut()
+            .update_object(id.to_string(), props)
+            .is_ok())
+    }
+
+    // 从对象池创建对象，不进入历史；返回新对象的 id
+    pub fn create(&self, element_type: &str, data: JsValue) -> Result<Option<String>, JsValue> {
+        let Some(app) = self.app() else {
+            return Ok(None);
+        };
+        let data: Value = if data.is_undefined() || data.is_null() {
+            json!({})
+        } else {
+            serde_wasm_bindgen::from_value(data)?
+        };
+        Ok(Some(app.acquire(element_type, data)?))
+    }
+
+    // 回收 create 创建的对象
+    pub fn remove(&self, id: &str) -> bool {
+        self.app().is_some_and(|app| app.release(id))
+    }
+
+    // 把属性动画到 props，easing 为缓动函数名，省略时使用默认值
+    pub fn animate(&self, id: &str, props: JsValue, duration_ms: f64, easing: Option<String>) -> Result<bool, JsValue> {
+        let Some(app) = self.app() else {
+            return Ok(false);
+        };
+        let props: Value = serde_wasm_bindgen::from_value(props)?;
+        let mut animation = app.animate(id).to(props).duration(duration_ms);
+        if let Some(easing) = easing {
+            animation = animation.easing(&easing);
+        }
+        animation.start()?;
+        Ok(true)
+    }
+}
+
+impl App {
+    // 注册每帧执行的脚本，在应用排队更新和绘制之前以 (app, { time, delta, frame }) 调用，
+    // app 为 AppProxy，delta 单位为秒。有脚本时每帧都会重绘；脚本抛出异常后会被移除
+    pub fn add_frame_script(&self, script: Function) -> FrameScriptId {
+        let id = {
+            let mut scripts = self.frame_scripts.borrow_mut();
+            scripts.next_id += 1;
+            let id = scripts.next_id;
+            scripts.scripts.push((id, script));
+            id
+        };
+        self.request_render();
+        id
+    }
+
+    pub fn remove_frame_script(&self, id: FrameScriptId) -> bool {
+        let mut scripts = self.frame_scripts.borrow_mut();
+        let before = scripts.scripts.len();
+        scripts.scripts.retain(|(script_id, _)| *script_id != id);
+        let mut found = before != scripts.scripts.len();
+        if scripts.running.contains(&id) {
+            // 在脚本回调中移除正在执行的脚本，等这一帧执行完再去掉
+            found = !scripts.removed.contains(&id);
+            scripts.removed.push(id);
+        } else if scripts.running.is_empty() && scripts.scripts.is_empty() {
+            scripts.last_run = None;
+            scripts.proxy = None;
+        }
+        found
+    }
+
+    pub(super) fn has_frame_scripts(&self) -> bool {
+        !self.frame_scripts.borrow().scripts.is_empty()
+    }
+
+    pub(super) fn run_frame_scripts(&self) {
+        // 先取出再执行，允许脚本在回调中注册/移除其他脚本
+        let (mut running, info) = {
+            let mut scripts = self.frame_scripts.borrow_mut();
+            if scripts.scripts.is_empty() {
+                return;
+            }
+            scripts.removed.clear();
+            let now = now_ms();
+            let delta = scripts.last_run.map_or(0.0, |last| (now - last) / 1000.0);
+            scripts.last_run = Some(now);
+            scripts.frame += 1;
+            let info = to_js_value(&json!({ "time": now, "delta": delta, "frame": scripts.frame }));
+            let running = std::mem::take(&mut scripts.scripts);
+            scripts.running = running.iter().map(|(id, _)| *id).collect();
+            (running, info)
+        };
+
+        let (proxy, active) = {
+            let mut scripts = self.frame_scripts.borrow_mut();
+            let active = scripts.active.clone();
+            let proxy = scripts
+                .proxy
+                .get_or_insert_with(|| {
+                    AppProxy {
+                        app: self.clone(),
+                        active: active.clone(),
+                    }
+                    .into()
+                })
+                .clone();
+            (proxy, active)
+        };
+        active.set(true);
+        running.retain(|(id, script)| match script.call2(&JsValue::NULL, &proxy, &info) {
+            Ok(_) => true,
+            Err(e) => {
+                log_error!("Frame script {} failed and was removed: {:?}", id, e);
+                self.frame_scripts.borrow_mut().removed.push(*id);
+                false
+            }
+        });
+        active.set(false);
+
+        let mut scripts = self.frame_scripts.borrow_mut();
+        let added = std::mem::take(&mut scripts.scripts);
+        let removed = std::mem::take(&mut scripts.removed);
+        scripts.running.clear();
+        running.retain(|(id, _)| !removed.contains(id));
+        running.extend(added);
+        scripts.scripts = running;
+        if scripts.scripts.is_empty() {
+            scripts.last_run = None;
+            scripts.proxy = None;
+        }
+        drop(scripts);
+        self.request_render();
+    }
+}
@@ -0,0 +1,65 @@
+use std::any::Any;
+
+use super::App;
+use crate::animation::{AnimationHandle, AnimationRepeat, AnimationValue, QwenAnimationBuilder};
+use crate::element::Sprite;
+use crate::error::{EditingError, EditingResult};
+use crate::helper::easing;
+use crate::sprite::{register_sprite_sheet, unregister_sprite_sheet, SpriteSheet};
+
+impl App {
+    // 同名图集会被替换，使用该图集的 Sprite 随之重绘
+    pub fn add_sprite_sheet(&self, name: &str, sheet: SpriteSheet) {
+        register_sprite_sheet(name, sheet);
+        self.request_render();
+    }
+
+    pub fn remove_sprite_sheet(&self, name: &str) -> bool {
+        let removed = unregister_sprite_sheet(name);
+        if removed {
+            self.request_render();
+        }
+        removed
+    }
+
+    // 从第一帧开始按 fps 播放，loop 为 false 时停在最后一帧；帧由渲染循环推进（App::start）
+    pub fn play_sprite(&self, id: &str) -> EditingResult<AnimationHandle> {
+        self.stop_sprite(id);
+        let (count, fps, looped) = {
+            let object = self
+                .get(id)
+                .ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+            let mut object = object.borrow_mut();
+            let object_type = object.get_type().to_string();
+            let sprite = (&mut **object as &mut dyn Any)
+                .downcast_mut::<Sprite>()
+                .ok_or(EditingError::UnsupportedElement(object_type))?;
+            sprite.set_frame(0.0);
+            (sprite.frames.len() as f64, sprite.fps, sprite.looped)
+        };
+
+        let duration = if fps > 0.0 { count / fps } else { 0.0 };
+        let repeat = if looped { AnimationRepeat::Infinite } else { AnimationRepeat::Count(0) };
+        let animation = QwenAnimationBuilder::new(duration)
+            .add_property("frame", AnimationValue::Float(0.0), AnimationValue::Float(count))
+            .set_easing(Box::new(easing::linear))
+            .set_repeat(repeat)
+            .build();
+        let handle = self
+            .animation_manager
+            .borrow_mut()
+            .add_animation(id.to_string(), Box::new(animation));
+        self.sprite_playback
+            .borrow_mut()
+            .insert(id.to_string(), handle.clone());
+        self.request_render();
+        Ok(handle)
+    }
+
+    // 停在当前帧
+    pub fn stop_sprite(&self, id: &str) {
+        if let Some(handle) = self.sprite_playback.borrow_mut().remove(id) {
+            handle.cancel();
+        }
+    }
+}
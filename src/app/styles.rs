@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::App;
+use crate::element::METADATA_KEY;
+use crate::error::{EditingError, EditingResult};
+use crate::helper::generate_id;
+use crate::history::{HistoryItem, ObjectHistoryItem, StyleHistoryItem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StyleKind {
+    Fill,
+    Stroke,
+    Text,
+}
+
+impl StyleKind {
+    // 样式可以包含的元素属性，对象没有的属性在应用时忽略
+    fn properties(self) -> &'static [&'static str] {
+        match self {
+            StyleKind::Fill => &["fill"],
+            StyleKind::Stroke => &["stroke", "stroke_width"],
+            StyleKind::Text => &["font_family", "font_size", "font_weight", "line_height", "align"],
+        }
+    }
+
+    // 对象在 meta 中记录所引用样式的键，每类样式最多引用一个
+    fn meta_key(self) -> &'static str {
+        match self {
+            StyleKind::Fill => "fill_style",
+            StyleKind::Stroke => "stroke_style",
+            StyleKind::Text => "text_style",
+        }
+    }
+}
+
+// 文档中的命名样式，修改后所有引用它的对象随之更新
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedStyle {
+    pub id: String,
+    pub name: String,
+    pub kind: StyleKind,
+    pub properties: Map<String, Value>,
+}
+
+pub(super) type StyleLibrary = BTreeMap<String, SharedStyle>;
+
+impl App {
+    // 新建命名样式，properties 中不属于该类样式的属性被忽略；记录历史
+    pub fn create_style(&self, name: &str, kind: StyleKind, properties: Value) -> EditingResult<String> {
+        let style = SharedStyle {
+            id: generate_id(),
+            name: name.to_string(),
+            kind,
+            properties: style_properties(kind, properties)?,
+        };
+        let id = style.id.clone();
+        self.commit_style(&id, Some(style))?;
+        Ok(id)
+    }
+
+    // 修改样式的属性并同步到所有引用它的对象，整体为一次撤销。返回实际发生变化的对象数
+    pub fn update_style(&self, id: &str, properties: Value) -> EditingResult<usize> {
+        let mut style = self.style(id).ok_or_else(|| EditingError::StyleNotFound(id.to_string()))?;
+        let patch = style_properties(style.kind, properties)?;
+        style.properties.extend(patch.clone());
+
+        self.history.borrow_mut().begin_transaction("update_style");
+        let result = self.commit_style(id, Some(style)).and_then(|_| {
+            let patches = self
+                .styled_objects(id)
+                .into_iter()
+                .map(|object_id| (object_id, Value::Object(patch.clone())))
+                .collect();
+            self.update_many(patches)
+        });
+        self.history.borrow_mut().commit();
+        result
+    }
+
+    pub fn rename_style(&self, id: &str, name: &str) -> EditingResult<bool> {
+        let mut style = self.style(id).ok_or_else(|| EditingError::StyleNotFound(id.to_string()))?;
+        if style.name == name {
+            return Ok(false);
+        }
+        style.name = name.to_string();
+        self.commit_style(id, Some(style))?;
+        Ok(true)
+    }
+
+    // 删除样式，引用它的对象保留当前的属性值；撤销删除后引用重新生效
+    pub fn remove_style(&self, id: &str) -> EditingResult<bool> {
+        if !self.styles.borrow().contains_key(id) {
+            return Ok(false);
+        }
+        self.commit_style(id, None)?;
+        Ok(true)
+    }
+
+    // 让对象引用样式并立即应用样式的属性，替换同类的原有引用；引用和属性修改整体为一次撤销
+    pub fn apply_style(&self, id: &str, ids: &[String]) -> EditingResult<usize> {
+        let style = self.style(id).ok_or_else(|| EditingError::StyleNotFound(id.to_string()))?;
+        if let Some(missing) = ids.iter().find(|object_id| !self.contains(object_id)) {
+            return Err(EditingError::ObjectNotFound(missing.clone()));
+        }
+        let patches = ids
+            .iter()
+            .map(|object_id| (object_id.clone(), Value::Object(style.properties.clone())))
+            .collect();
+        self.history.borrow_mut().begin_transaction("apply_style");
+        let result = ids
+            .iter()
+            .try_for_each(|object_id| self.set_style_reference(object_id, style.kind, Some(id)))
+            .and_then(|_| self.update_many(patches));
+        self.history.borrow_mut().commit();
+        result
+    }
+
+    // 解除对象对该类样式的引用，属性保持不变；整体为一次撤销
+    pub fn detach_style(&self, ids: &[String], kind: StyleKind) -> EditingResult<()> {
+        if let Some(missing) = ids.iter().find(|object_id| !self.contains(object_id)) {
+            return Err(EditingError::ObjectNotFound(missing.clone()));
+        }
+        self.history.borrow_mut().begin_transaction("detach_style");
+        let result = ids
+            .iter()
+            .try_for_each(|object_id| self.set_style_reference(object_id, kind, None));
+        self.history.borrow_mut().commit();
+        result
+    }
+
+    pub fn style(&self, id: &str) -> Option<SharedStyle> {
+        self.styles.borrow().get(id).cloned()
+    }
+
+    pub fn styles(&self) -> Vec<SharedStyle> {
+        self.styles.borrow().values().cloned().collect()
+    }
+
+    // 引用该样式的所有对象
+    pub fn styled_objects(&self, id: &str) -> Vec<String> {
+        let Some(kind) = self.styles.borrow().get(id).map(|style| style.kind) else {
+            return Vec::new();
+        };
+        self.object_manager
+            .borrow()
+            .iter()
+            .filter(|(_, object)| {
+                object.borrow().meta().get(kind.meta_key()).and_then(Value::as_str) == Some(id)
+            })
+            .map(|(object_id, _)| object_id.clone())
+            .collect()
+    }
+
+    // 用保存的样式替换当前样式库，如从自动保存恢复文档时；不记录历史
+    pub fn load_styles(&self, styles: Vec<SharedStyle>) {
+        *self.styles.borrow_mut() = styles.into_iter().map(|style| (style.id.clone(), style)).collect();
+    }
+
+    // 撤销、重做和远端同步时应用样式定义，Null 表示删除
+    pub(crate) fn apply_style_data(&self, id: &str, data: &Value) -> EditingResult<()> {
+        let mut styles = self.styles.borrow_mut();
+        if data.is_null() {
+            styles.remove(id);
+        } else {
+            styles.insert(id.to_string(), serde_json::from_value(data.clone())?);
+        }
+        Ok(())
+    }
+
+    // 修改对象 meta 中的样式引用并记录历史，撤销和同步时由 update_object 写回 meta
+    fn set_style_reference(&self, object_id: &str, kind: StyleKind, style_id: Option<&str>) -> EditingResult<()> {
+        let key = kind.meta_key();
+        let old = self
+            .object_manager
+            .borrow()
+            .get_meta(object_id)
+            .ok_or_else(|| EditingError::ObjectNotFound(object_id.to_string()))?
+            .get(key)
+            .cloned()
+            .unwrap_or(Value::Null);
+        let new = style_id.map_or(Value::Null, |id| Value::String(id.to_string()));
+        if old == new {
+            return Ok(());
+        }
+        self.object_manager
+            .borrow()
+            .update_meta(object_id, |meta| meta.set(key, new.clone()))?;
+        let item = ObjectHistoryItem::new(
+            object_id.to_string(),
+            json!({ METADATA_KEY: { key: old } }),
+            json!({ METADATA_KEY: { key: new } }),
+        );
+        self.record_history(HistoryItem::ObjectUpdate(item));
+        Ok(())
+    }
+
+    fn commit_style(&self, id: &str, style: Option<SharedStyle>) -> EditingResult<()> {
+        let undo_data = serde_json::to_value(self.style(id))?;
+        let redo_data = serde_json::to_value(&style)?;
+        self.apply_style_data(id, &redo_data)?;
+        let item = StyleHistoryItem::new(id.to_string(), undo_data, redo_data);
+        self.record_history(HistoryItem::StyleUpdate(item));
+        Ok(())
+    }
+}
+
+fn style_properties(kind: StyleKind, properties: Value) -> EditingResult<Map<String, Value>> {
+    let Value::Object(mut properties) = properties else {
+        let message = "style properties must be an object";
+        return Err(EditingError::SerdeError(serde::de::Error::custom(message)));
+    };
+    properties.retain(|key, _| kind.properties().contains(&key.as_str()));
+    Ok(properties)
+}
@@ -0,0 +1,144 @@
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+use wasm_bindgen_futures::spawn_local;
+
+use super::App;
+use crate::history::{HistoryItem, ObjectHistoryItem};
+use crate::log::log_warn;
+use crate::sync::{Operation, Presence, SyncAdapter, SyncSession};
+
+impl App {
+    // 开始与其他客户端同步：本地的历史记录、撤销重做和排队更新都会编号后通过 adapter 发出，
+    // 收到的远端操作通过 receive_operation 合并。client_id 需在协作的客户端之间唯一，
+    // 各客户端需从同一份文档开始同步
+    pub fn start_sync(&self, client_id: impl Into<String>, adapter: impl SyncAdapter + 'static) {
+        let objects = self
+            .get_objects()
+            .iter()
+            .map(|object| {
+                let object = object.borrow();
+                let points = object.property("points").and_then(|points| points.as_array().cloned());
+                (object.id().value().to_string(), points)
+            })
+            .collect();
+        *self.sync.borrow_mut() = Some(SyncSession::new(client_id, Rc::new(adapter), objects));
+        self.notify_presence();
+    }
+
+    // 未发出的操作会被丢弃，其他协作者的指针和选中框不再显示
+    pub fn stop_sync(&self) {
+        self.sync.borrow_mut().take();
+        self.scene_manager.borrow().clear_presence();
+        self.request_render();
+    }
+
+    // 显示在其他协作者画面中指针旁的名称
+    pub fn set_presence_name(&self, name: Option<String>) {
+        if let Some(session) = self.sync.borrow_mut().as_mut() {
+            session.set_name(name);
+        }
+        self.notify_presence();
+    }
+
+    pub(super) fn set_local_cursor(&self, cursor: Option<(f64, f64)>) {
+        if let Some(session) = self.sync.borrow_mut().as_mut() {
+            session.set_cursor(cursor);
+        }
+        self.notify_presence();
+    }
+
+    // 指针或选中状态变化后调用；同一轮事件循环内的多次变化只发送一次最新状态
+    pub(crate) fn notify_presence(&self) {
+        let should_flush = match self.sync.borrow_mut().as_mut() {
+            Some(session) => session.schedule_presence(),
+            None => return,
+        };
+        if should_flush {
+            let app = self.clone();
+            spawn_local(async move {
+                let selection = app.selection();
+                let Some((adapter, presence)) =
+                    app.sync.borrow_mut().as_mut().and_then(|session| session.take_presence(selection))
+                else {
+                    return;
+                };
+                adapter.send_presence(&presence);
+            });
+        }
+    }
+
+    // 显示其他协作者的指针和选中框，指针在两次更新之间平滑移动
+    pub fn receive_presence(&self, presence: Presence) {
+        match self.sync.borrow().as_ref() {
+            Some(session) if session.client_id() != presence.client_id => {}
+            _ => return,
+        }
+        self.scene_manager.borrow().update_presence(presence);
+        self.request_render();
+    }
+
+    // 协作者断开连接时由宿主调用
+    pub fn remove_presence(&self, client_id: &str) -> bool {
+        let removed = self.scene_manager.borrow().remove_presence(client_id);
+        if removed {
+            self.request_render();
+        }
+        removed
+    }
+
+    // 由 History 和 post_update 调用；同一轮事件循环内产生的操作在之后按顺序一起发出
+    pub(crate) fn broadcast(&self, items: &[HistoryItem], undo: bool) {
+        self.record_operation(items, undo);
+        let should_flush = match self.sync.borrow_mut().as_mut() {
+            Some(session) => session.queue(items, undo),
+            None => return,
+        };
+        if should_flush {
+            let app = self.clone();
+            spawn_local(async move {
+                let Some((adapter, operations)) = app.sync.borrow_mut().as_mut().map(SyncSession::take_outbox)
+                else {
+                    return;
+                };
+                for operation in &operations {
+                    adapter.send(operation);
+                }
+            });
+        }
+    }
+
+    // 合并并应用远端操作，不进入本地历史；乱序到达的操作会等前面的到齐后再应用。
+    // 同一属性的并发修改以 Lamport 时间较晚的为准，绘制顺序和路径点按序列合并，各客户端最终一致
+    pub fn receive_operation(&self, operation: Operation) {
+        let ready = match self.sync.borrow_mut().as_mut() {
+            Some(session) => session.receive(operation),
+            None => return,
+        };
+        if ready.is_empty() {
+            return;
+        }
+        for operation in ready {
+            let Some(changes) = self.sync.borrow_mut().as_mut().map(|session| session.integrate(operation)) else {
+                return;
+            };
+            // 远端修改也写入操作日志，其中的绘制顺序变化无法表示为历史记录，不会被记录
+            let mut recorded = changes.items.clone();
+            self.history.borrow().apply_unrecorded(self, changes.items);
+            if let Some(z_order) = changes.z_order {
+                self.object_manager.borrow_mut().reorder(&z_order);
+            }
+            for (id, points) in changes.points {
+                let data = json!({ "points": points });
+                if let Err(err) = self.object_manager.borrow_mut().update_object(id.clone(), data.clone()) {
+                    log_warn!("Failed to apply remote points: {:?}", err);
+                    continue;
+                }
+                recorded.push(HistoryItem::ObjectUpdate(ObjectHistoryItem::new(id, Value::Null, data)));
+            }
+            self.record_operation(&recorded, false);
+        }
+        self.notify_document_change();
+        self.request_render();
+    }
+}
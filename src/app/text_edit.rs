@@ -0,0 +1,316 @@
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlTextAreaElement, KeyboardEvent, MouseEvent};
+
+use super::{App, Tool};
+use crate::element::{Text, TextAlignment, Transformable};
+use crate::error::{EditingError, EditingResult};
+use crate::math::{convert_1x6_to_3x3, convert_3x3_to_1x6};
+use crate::history::{HistoryItem, ObjectHistoryItem};
+use crate::log::log_warn;
+
+// 事件类型和对应的回调，移除时需要两者
+type Listener = (&'static str, Closure<dyn FnMut(Event)>);
+
+// 覆盖在文本上的隐藏 textarea，输入法组合、光标和选区都交给浏览器处理；
+// 文本本身仍由画布绘制，textarea 只显示光标
+pub(super) struct TextEditor {
+    textarea: HtmlTextAreaElement,
+    // 监听只注册一次，避免在回调执行中释放 Closure
+    listeners: Vec<Listener>,
+    session: Option<TextEditSession>,
+}
+
+#[derive(Debug)]
+struct TextEditSession {
+    id: String,
+    // 编辑前的文本，取消时恢复，提交时作为撤销数据
+    original: String,
+}
+
+impl Debug for TextEditor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TextEditor {{ session: {:?} }}", self.session)
+    }
+}
+
+impl TextEditor {
+    fn remove(&mut self) {
+        for (event_type, listener) in self.listeners.drain(..) {
+            let _ = self
+                .textarea
+                .remove_event_listener_with_callback(event_type, listener.as_ref().unchecked_ref());
+        }
+        self.textarea.remove();
+    }
+}
+
+impl App {
+    // 在文本对象上打开编辑框，已有编辑会先提交
+    pub fn begin_text_edit(&self, id: &str) -> EditingResult<()> {
+        self.finish_text_edit(true);
+        let object = self
+            .get(id)
+            .ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        let original = {
+            let object = object.borrow();
+            let Some(text) = (&**object as &dyn Any).downcast_ref::<Text>() else {
+                return Err(EditingError::UnsupportedElement(object.get_type().to_string()));
+            };
+            text.text.clone()
+        };
+
+        self.ensure_text_editor()?;
+        let textarea = {
+            let mut editor = self.text_editor.borrow_mut();
+            let editor = editor.as_mut().ok_or(EditingError::NotInitialized("TextEditor"))?;
+            editor.session = Some(TextEditSession {
+                id: id.to_string(),
+                original: original.clone(),
+            });
+            editor.textarea.clone()
+        };
+        textarea.set_value(&original);
+        self.layout_text_editor();
+        let _ = textarea.style().set_property("display", "block");
+        let _ = textarea.focus();
+        textarea.select();
+        Ok(())
+    }
+
+    // commit 为 false 时恢复编辑前的文本；提交的修改记录为一条历史
+    pub fn finish_text_edit(&self, commit: bool) {
+        let (session, textarea) = {
+            let Ok(mut editor) = self.text_editor.try_borrow_mut() else {
+                return;
+            };
+            let Some(editor) = editor.as_mut() else {
+                return;
+            };
+            let Some(session) = editor.session.take() else {
+                return;
+            };
+            (session, editor.textarea.clone())
+        };
+        // 先结束会话再隐藏，blur 回调进来时已经没有会话
+        let value = textarea.value();
+        let _ = textarea.style().set_property("display", "none");
+        let _ = textarea.blur();
+
+        let Some(object) = self.get(&session.id) else {
+            return;
+        };
+        if commit && value != session.original {
+            let item = ObjectHistoryItem::new(
+                session.id.clone(),
+                json!({ "text": session.original }),
+                json!({ "text": value }),
+            );
+            self.record_history(HistoryItem::ObjectUpdate(item));
+            object.borrow_mut().update(json!({ "text": value }));
+        } else {
+            object.borrow_mut().update(json!({ "text": session.original }));
+        }
+        self.request_render();
+    }
+
+    pub fn editing_text(&self) -> Option<String> {
+        let editor = self.text_editor.try_borrow().ok()?;
+        editor.as_ref()?.session.as_ref().map(|session| session.id.clone())
+    }
+
+    pub(super) fn destroy_text_editor(&self) {
+        self.finish_text_edit(false);
+        if let Some(mut editor) = self.text_editor.borrow_mut().take() {
+            editor.remove();
+        }
+    }
+
+    pub(super) fn handle_double_click(&self, event: &MouseEvent) {
+        if !matches!(*self.tool.borrow(), Tool::Select | Tool::Text(_)) {
+            return;
+        }
+        let object = match self.scene_manager.try_borrow() {
+            Ok(scene_manager) => scene_manager.get_trigger_object(event),
+            Err(_) => return,
+        };
+        let Some(object) = object else {
+            return;
+        };
+        let id = {
+            let object = object.borrow();
+            if object.get_type() != "text" {
+                return;
+            }
+            object.id().value().to_string()
+        };
+        event.prevent_default();
+        if let Err(e) = self.begin_text_edit(&id) {
+            log_warn!("Failed to edit text {}: {:?}", id, e);
+        }
+    }
+
+    // 输入时实时更新对象，不记录历史
+    fn on_text_input(&self) {
+        let Some((id, value)) = self.text_editor.borrow().as_ref().and_then(|editor| {
+            let session = editor.session.as_ref()?;
+            Some((session.id.clone(), editor.textarea.value()))
+        }) else {
+            return;
+        };
+        let Some(object) = self.get(&id) else {
+            // 编辑中对象被删除
+            self.finish_text_edit(false);
+            return;
+        };
+        object.borrow_mut().update(json!({ "text": value }));
+        self.layout_text_editor();
+        self.request_render();
+    }
+
+    fn on_text_keydown(&self, event: &KeyboardEvent) {
+        // 输入法组合中的按键交给输入法
+        if event.is_composing() {
+            return;
+        }
+        match event.key().as_str() {
+            "Escape" => {
+                event.prevent_default();
+                self.finish_text_edit(false);
+            }
+            // 普通 Enter 输入换行，Ctrl/Cmd+Enter 提交
+            "Enter" if event.ctrl_key() || event.meta_key() => {
+                event.prevent_default();
+                self.finish_text_edit(true);
+            }
+            _ => {}
+        }
+    }
+
+    // 让编辑框与文本在屏幕上的位置、旋转和缩放一致
+    pub(super) fn layout_text_editor(&self) {
+        let Some(id) = self.editing_text() else {
+            return;
+        };
+        let Some(object) = self.get(&id) else {
+            return;
+        };
+        let Some(view) = self
+            .scene_manager
+            .try_borrow()
+            .ok()
+            .and_then(|scene_manager| scene_manager.world_to_client_matrix())
+        else {
+            return;
+        };
+        let object = object.borrow();
+        let Some(text) = (&**object as &dyn Any).downcast_ref::<Text>() else {
+            return;
+        };
+        let Some(textarea) = self.text_editor.borrow().as_ref().map(|editor| editor.textarea.clone()) else {
+            return;
+        };
+
+        let local = convert_1x6_to_3x3(text.calc_transform());
+        let matrix = convert_3x3_to_1x6(view * local);
+        let layout = text.layout();
+        let style = textarea.style();
+        let transform = format!(
+            "matrix({}, {}, {}, {}, {}, {})",
+            matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5]
+        );
+        let _ = style.set_property("transform", &transform);
+        // 自动换行时交给浏览器按相同宽度折行，光标位置与画布上的排版一致；
+        // 不换行时多留一个字宽，光标在行尾时不会被裁掉
+        let (width, white_space) = match text.max_width.filter(|width| *width > 0.0) {
+            Some(max_width) => (max_width, "pre-wrap"),
+            None => (layout.width + text.font_size, "pre"),
+        };
+        let align = match text.align {
+            TextAlignment::Left => "left",
+            TextAlignment::Center => "center",
+            TextAlignment::Right => "right",
+        };
+        let _ = style.set_property("width", &format!("{}px", width));
+        let _ = style.set_property("height", &format!("{}px", layout.height.max(layout.line_height)));
+        let _ = style.set_property("white-space", white_space);
+        let _ = style.set_property("text-align", align);
+        let _ = style.set_property("font", &text.font());
+        let _ = style.set_property("line-height", &format!("{}px", layout.line_height));
+        let _ = style.set_property("caret-color", &text.fill);
+    }
+
+    fn ensure_text_editor(&self) -> EditingResult<()> {
+        if self.text_editor.borrow().is_some() {
+            return Ok(());
+        }
+        let document = web_sys::window()
+            .ok_or(EditingError::WindowNotFound)?
+            .document()
+            .ok_or(EditingError::DocumentNotFound)?;
+        let textarea = document
+            .create_element("textarea")?
+            .dyn_into::<HtmlTextAreaElement>()
+            .map_err(|_| EditingError::Dom("Failed to create textarea".to_string()))?;
+        textarea.set_attribute("spellcheck", "false")?;
+        textarea.set_attribute("autocomplete", "off")?;
+        let style = textarea.style();
+        for (name, value) in [
+            ("display", "none"),
+            ("position", "fixed"),
+            ("left", "0"),
+            ("top", "0"),
+            ("transform-origin", "0 0"),
+            ("margin", "0"),
+            ("padding", "0"),
+            ("border", "none"),
+            ("outline", "none"),
+            ("resize", "none"),
+            ("overflow", "hidden"),
+            ("background", "transparent"),
+            ("color", "transparent"),
+            ("z-index", "1000"),
+        ] {
+            style.set_property(name, value)?;
+        }
+        document
+            .body()
+            .ok_or_else(|| EditingError::Dom("Failed to get body".to_string()))?
+            .append_child(&textarea)?;
+
+        let mut listeners = Vec::new();
+        let app = self.clone();
+        listeners.push((
+            "input",
+            Closure::wrap(Box::new(move |_: Event| app.on_text_input()) as Box<dyn FnMut(Event)>),
+        ));
+        let app = self.clone();
+        listeners.push((
+            "keydown",
+            Closure::wrap(Box::new(move |event: Event| {
+                if let Some(event) = event.dyn_ref::<KeyboardEvent>() {
+                    app.on_text_keydown(event);
+                }
+            }) as Box<dyn FnMut(Event)>),
+        ));
+        let app = self.clone();
+        listeners.push((
+            "blur",
+            Closure::wrap(Box::new(move |_: Event| app.finish_text_edit(true)) as Box<dyn FnMut(Event)>),
+        ));
+        for (event_type, listener) in &listeners {
+            textarea.add_event_listener_with_callback(event_type, listener.as_ref().unchecked_ref())?;
+        }
+
+        *self.text_editor.borrow_mut() = Some(TextEditor {
+            textarea,
+            listeners,
+            session: None,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,762 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, MouseEvent, PointerEvent};
+
+use super::inertia::VelocityTracker;
+use super::App;
+use crate::bounding_box::BoundingBox;
+use crate::element::{erase_path, simplify_path, Path, PathPoint, Renderable};
+use crate::events::AppEvent;
+use crate::helper::to_js_value;
+use crate::history::{HistoryItem, ObjectHistoryItem};
+use crate::log::log_warn;
+
+// 当前的指针交互模式，JS 侧传入 { type: "pencil", stroke: "#333", strokeWidth: 4 } 这样的对象
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Tool {
+    // 默认模式，只派发对象的按下/抬起事件，以及按下后拖动时的移动事件
+    #[default]
+    Select,
+    Pencil(PencilOptions),
+    Eraser(EraserOptions),
+    // 拖拽创建图形，按住 Shift 约束为正方形/圆形或 45° 的倍数
+    Rect(ShapeOptions),
+    Ellipse(ShapeOptions),
+    Line(ShapeOptions),
+    // 单击处放置文本
+    Text(TextToolOptions),
+    // 拖拽平移视图
+    Pan,
+    // 拖拽测量两点间的距离和角度，按住 Shift 约束为 45° 的倍数
+    Measure,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ShapeOptions {
+    pub fill: String,
+    pub stroke: String,
+    pub stroke_width: f64,
+    pub opacity: f64,
+}
+
+impl Default for ShapeOptions {
+    fn default() -> Self {
+        Self {
+            fill: "blue".to_string(),
+            stroke: "black".to_string(),
+            stroke_width: 2.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TextToolOptions {
+    pub text: String,
+    pub font_size: f64,
+    pub font_family: String,
+    pub fill: String,
+}
+
+impl Default for TextToolOptions {
+    fn default() -> Self {
+        Self {
+            text: "Text".to_string(),
+            font_size: 16.0,
+            font_family: "sans-serif".to_string(),
+            fill: "black".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PencilOptions {
+    pub stroke: String,
+    pub stroke_width: f64,
+    pub opacity: f64,
+    // 简化时允许的偏差（屏幕像素），越大点越少
+    pub tolerance: f64,
+    pub smooth: bool,
+    // 手写笔或触摸提供压力时按压力调整线宽
+    pub pressure: bool,
+}
+
+impl Default for PencilOptions {
+    fn default() -> Self {
+        Self {
+            stroke: "black".to_string(),
+            stroke_width: 2.0,
+            opacity: 1.0,
+            tolerance: 1.5,
+            smooth: true,
+            pressure: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EraserMode {
+    // 碰到的笔画整条删除
+    #[default]
+    Stroke,
+    // 只擦掉经过的部分，笔画可能被分成几段
+    Split,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EraserOptions {
+    pub mode: EraserMode,
+    // 橡皮擦半径（屏幕像素）
+    pub radius: f64,
+}
+
+impl Default for EraserOptions {
+    fn default() -> Self {
+        Self {
+            mode: EraserMode::Stroke,
+            radius: 10.0,
+        }
+    }
+}
+
+// 一次擦除手势，按下到抬起之间的所有修改作为一个历史单元
+#[derive(Debug)]
+pub(super) struct ActiveErase {
+    pointer_id: i32,
+    options: EraserOptions,
+    // 上一次处理到的世界坐标，移动过快时按线段擦除不会漏掉
+    last: (f64, f64),
+}
+
+// 拖拽距离小于该值（屏幕像素）时视为误触，不创建图形
+const MIN_SHAPE_DRAG: f64 = 2.0;
+
+// 正在拖拽创建的图形，预览对象从对象池取出，抬起时才真正加入场景
+#[derive(Debug)]
+pub(super) struct ActiveShape {
+    pointer_id: i32,
+    element_type: &'static str,
+    preview_id: String,
+    options: ShapeOptions,
+    // 世界坐标
+    origin: (f64, f64),
+    current: (f64, f64),
+    constrain: bool,
+}
+
+// 拖拽平移，整个手势作为一个历史单元
+#[derive(Debug)]
+pub(super) struct ActivePan {
+    pointer_id: i32,
+    // 上一次的 clientX/clientY
+    last: (f64, f64),
+    velocity: VelocityTracker,
+}
+
+// 正在绘制的笔画，预览对象从对象池取出，不记录历史
+#[derive(Debug)]
+pub(super) struct ActiveStroke {
+    pointer_id: i32,
+    preview_id: String,
+    options: PencilOptions,
+    // 世界坐标
+    points: Vec<PathPoint>,
+}
+
+impl App {
+    pub fn set_tool(&self, tool: Tool) {
+        self.cancel_stroke();
+        self.finish_erase();
+        self.cancel_shape();
+        self.finish_pan();
+        self.exit_path_edit();
+        self.clear_measure();
+        *self.tool.borrow_mut() = tool;
+    }
+
+    pub fn tool(&self) -> Tool {
+        self.tool.borrow().clone()
+    }
+
+    pub(super) fn handle_pointer_down(&self, event: &MouseEvent) {
+        self.stop_inertia();
+        if self.scrollbar_pointer_down(event)
+            || self.capturing_pointer_down(event)
+            || self.path_edit_pointer_down(event)
+        {
+            return;
+        }
+        let tool = self.tool.borrow().clone();
+        match tool {
+            Tool::Select => {
+                if let Some(object) = self.emit_object_event(AppEvent::OBJECT_DOWN, event) {
+                    self.begin_pointer_capture(&object, event);
+                }
+            }
+            Tool::Pencil(options) => self.begin_stroke(event, options),
+            Tool::Eraser(options) => self.begin_erase(event, options),
+            Tool::Rect(options) => self.begin_shape(event, "rect", options),
+            Tool::Ellipse(options) => self.begin_shape(event, "ellipse", options),
+            Tool::Line(options) => self.begin_shape(event, "line", options),
+            Tool::Text(options) => self.place_text(event, options),
+            Tool::Pan => self.begin_pan(event),
+            Tool::Measure => self.begin_measure(event),
+        }
+    }
+
+    pub(super) fn handle_pointer_move(&self, event: &MouseEvent) {
+        self.set_local_cursor(self.event_to_world(event));
+        if self.scrollbar_pointer_move(event)
+            || self.captured_pointer_move(event)
+            || self.path_edit_pointer_move(event)
+        {
+            return;
+        }
+        self.extend_stroke(event);
+        self.extend_erase(event);
+        self.extend_shape(event);
+        self.extend_pan(event);
+        self.extend_measure(event);
+    }
+
+    pub(super) fn handle_pointer_up(&self, event: &MouseEvent) {
+        if self.scene_manager.borrow().scrollbar_pointer_up()
+            || self.captured_pointer_up(event)
+            || self.path_edit_pointer_up(event)
+        {
+            return;
+        }
+        if self.stroke.borrow().is_some() {
+            self.extend_stroke(event);
+            self.finish_stroke();
+            return;
+        }
+        if self.erasing.borrow().is_some() {
+            self.extend_erase(event);
+            self.finish_erase();
+            return;
+        }
+        if self.shape.borrow().is_some() {
+            self.extend_shape(event);
+            self.finish_shape();
+            return;
+        }
+        if self.panning.borrow().is_some() {
+            self.extend_pan(event);
+            self.release_pan(event);
+            return;
+        }
+        if self.measure.borrow().dragging.is_some() {
+            self.extend_measure(event);
+            self.finish_measure();
+            return;
+        }
+        if *self.tool.borrow() == Tool::Select {
+            self.emit_object_event(AppEvent::OBJECT_UP, event);
+        }
+    }
+
+    // 捕获指针后只有 pointercancel 会走到这里，此时保留已经画出的部分
+    pub(super) fn handle_pointer_leave(&self, _event: &MouseEvent) {
+        self.set_local_cursor(None);
+        self.scene_manager.borrow().scrollbar_pointer_up();
+        self.release_pointer_capture();
+        self.finish_path_drag();
+        self.finish_stroke();
+        self.finish_erase();
+        self.finish_shape();
+        self.finish_pan();
+        self.finish_measure();
+    }
+
+    // 滚动条在所有工具之上，按在滑块上时拖动滚动条而不交给当前工具
+    fn scrollbar_pointer_down(&self, event: &MouseEvent) -> bool {
+        let hit = self
+            .scene_manager
+            .borrow()
+            .scrollbar_pointer_down(event.client_x() as f64, event.client_y() as f64);
+        if hit {
+            capture_pointer(event);
+        }
+        hit
+    }
+
+    fn scrollbar_pointer_move(&self, event: &MouseEvent) -> bool {
+        if !self.scene_manager.borrow().is_scrollbar_dragging() {
+            return false;
+        }
+        self.scene_manager
+            .borrow_mut()
+            .scrollbar_pointer_move(event.client_x() as f64, event.client_y() as f64)
+    }
+
+    fn begin_stroke(&self, event: &MouseEvent, options: PencilOptions) {
+        self.cancel_stroke();
+        let pointer_id = pointer_id(event);
+        let Some(point) = self.stroke_point(event, &options) else {
+            return;
+        };
+        let data = json!({
+            "x": 0.0,
+            "y": 0.0,
+            "points": [point],
+            "stroke": options.stroke,
+            "stroke_width": options.stroke_width,
+            "opacity": options.opacity,
+            "smooth": options.smooth,
+        });
+        let preview_id = match self.acquire("path", data) {
+            Ok(id) => id,
+            Err(e) => {
+                log_warn!("Failed to start stroke: {:?}", e);
+                return;
+            }
+        };
+
+        capture_pointer(event);
+
+        *self.stroke.borrow_mut() = Some(ActiveStroke {
+            pointer_id: pointer_id.unwrap_or_default(),
+            preview_id,
+            options,
+            points: vec![point],
+        });
+        self.request_render();
+    }
+
+    fn extend_stroke(&self, event: &MouseEvent) {
+        let preview = {
+            let mut stroke = self.stroke.borrow_mut();
+            let Some(stroke) = stroke.as_mut() else {
+                return;
+            };
+            if pointer_id(event).is_some_and(|id| id != stroke.pointer_id) {
+                return;
+            }
+            let Some(point) = self.stroke_point(event, &stroke.options) else {
+                return;
+            };
+            if stroke.points.last() == Some(&point) {
+                return;
+            }
+            stroke.points.push(point);
+            (stroke.preview_id.clone(), json!({ "points": stroke.points }))
+        };
+        if let Some(object) = self.get(&preview.0) {
+            object.borrow_mut().update(preview.1);
+            self.request_render();
+        }
+    }
+
+    // 简化后的笔画作为新对象加入场景，记录为一条历史
+    fn finish_stroke(&self) {
+        let Some(stroke) = self.stroke.borrow_mut().take() else {
+            return;
+        };
+        self.release(&stroke.preview_id);
+
+        let zoom = self.scene_manager.borrow().zoom();
+        let points = simplify_path(&stroke.points, stroke.options.tolerance / zoom);
+        let Some(origin) = points.first().copied() else {
+            return;
+        };
+        let local = points
+            .iter()
+            .map(|point| PathPoint::new(point.x - origin.x, point.y - origin.y, point.pressure))
+            .collect();
+        let mut path = Path::new(origin.x, origin.y, local);
+        path.stroke = stroke.options.stroke;
+        path.stroke_width = stroke.options.stroke_width;
+        path.opacity = stroke.options.opacity;
+        path.smooth = stroke.options.smooth;
+
+        let id = path.id().value().to_string();
+        if let Err(e) = self.add(path) {
+            log_warn!("Failed to add stroke: {}", e);
+            return;
+        }
+        let _ = self
+            .events
+            .emit(AppEvent::STROKE_END.into(), &to_js_value(&json!({ "id": id })));
+    }
+
+    fn cancel_stroke(&self) {
+        if let Some(stroke) = self.stroke.borrow_mut().take() {
+            self.release(&stroke.preview_id);
+            self.request_render();
+        }
+    }
+
+    fn stroke_point(&self, event: &MouseEvent, options: &PencilOptions) -> Option<PathPoint> {
+        let (x, y) = self.event_to_world(event)?;
+        let pressure = event
+            .dyn_ref::<PointerEvent>()
+            // 鼠标按下时固定报告 0.5，不代表真实压力
+            .filter(|event| options.pressure && event.pointer_type() != "mouse")
+            .map(|event| event.pressure() as f64)
+            .filter(|pressure| *pressure > 0.0)
+            .unwrap_or(1.0);
+        Some(PathPoint::new(x, y, pressure))
+    }
+}
+
+impl App {
+    fn begin_erase(&self, event: &MouseEvent, options: EraserOptions) {
+        self.finish_erase();
+        let Some(point) = self.event_to_world(event) else {
+            return;
+        };
+        capture_pointer(event);
+        self.history.borrow_mut().begin_transaction("Erase");
+        *self.erasing.borrow_mut() = Some(ActiveErase {
+            pointer_id: pointer_id(event).unwrap_or_default(),
+            options,
+            last: point,
+        });
+        self.erase_segment(point, point);
+    }
+
+    fn extend_erase(&self, event: &MouseEvent) {
+        let segment = {
+            let mut erasing = self.erasing.borrow_mut();
+            let Some(erasing) = erasing.as_mut() else {
+                return;
+            };
+            if pointer_id(event).is_some_and(|id| id != erasing.pointer_id) {
+                return;
+            }
+            let Some(point) = self.event_to_world(event) else {
+                return;
+            };
+            let from = std::mem::replace(&mut erasing.last, point);
+            (from, point)
+        };
+        self.erase_segment(segment.0, segment.1);
+    }
+
+    fn finish_erase(&self) {
+        if self.erasing.borrow_mut().take().is_some() {
+            self.history.borrow_mut().commit();
+        }
+    }
+
+    // 擦除扫过线段 from -> to 的笔画，坐标为世界坐标
+    fn erase_segment(&self, from: (f64, f64), to: (f64, f64)) {
+        let Some(options) = self.erasing.borrow().as_ref().map(|erasing| erasing.options.clone()) else {
+            return;
+        };
+        let radius = options.radius / self.scene_manager.borrow().zoom();
+        let area = BoundingBox::from_points(&[from, to]).expand(radius);
+        let candidates = self.object_manager.borrow().query_rect(&area);
+
+        let mut changed = false;
+        for id in candidates {
+            let Some(object) = self.get(&id) else {
+                continue;
+            };
+            let (data, pieces) = {
+                let object = object.borrow();
+                if object.get_type() != "path" {
+                    continue;
+                }
+                let data = object.to_value();
+                // 封闭图形（如布尔运算的结果）不参与擦除
+                if data["closed"].as_bool().unwrap_or(false) {
+                    continue;
+                }
+                let Ok(points) = serde_json::from_value::<Vec<PathPoint>>(data["points"].clone()) else {
+                    continue;
+                };
+                let (x, y) = object.position();
+                let smooth = data["smooth"].as_bool().unwrap_or(true);
+                // 整条删除时碰到墨迹就算擦到
+                let reach = match options.mode {
+                    EraserMode::Stroke => radius + data["stroke_width"].as_f64().unwrap_or(0.0) / 2.0,
+                    EraserMode::Split => radius,
+                };
+                let local = |(px, py): (f64, f64)| (px - x, py - y);
+                match erase_path(&points, smooth, local(from), local(to), reach) {
+                    Some(pieces) => (data, pieces),
+                    None => continue,
+                }
+            };
+
+            changed = true;
+            let mut pieces = pieces.into_iter();
+            let first = match options.mode {
+                EraserMode::Stroke => None,
+                EraserMode::Split => pieces.next(),
+            };
+            let Some(first) = first else {
+                self.remove(&id);
+                continue;
+            };
+
+            // 第一段保留在原对象上，其余各段作为新笔画加入
+            let item = ObjectHistoryItem::new(
+                id.clone(),
+                json!({ "points": data["points"] }),
+                json!({ "points": first }),
+            );
+            self.record_history(HistoryItem::ObjectUpdate(item));
+            object.borrow_mut().update(json!({ "points": first }));
+            for piece in pieces {
+                let mut piece_data = data.clone();
+                piece_data["points"] = json!(piece);
+                if let Err(e) = self.create("path", piece_data) {
+                    log_warn!("Failed to split stroke {}: {:?}", id, e);
+                }
+            }
+        }
+        if changed {
+            self.request_render();
+        }
+    }
+
+    pub(super) fn event_to_world(&self, event: &MouseEvent) -> Option<(f64, f64)> {
+        self.scene_manager
+            .try_borrow()
+            .ok()?
+            .client_to_world(event.client_x() as f64, event.client_y() as f64)
+    }
+}
+
+impl App {
+    fn begin_shape(&self, event: &MouseEvent, element_type: &'static str, options: ShapeOptions) {
+        self.cancel_shape();
+        let Some(origin) = self.event_to_world(event) else {
+            return;
+        };
+        let data = shape_data(element_type, &options, origin, origin, false);
+        let preview_id = match self.acquire(element_type, data) {
+            Ok(id) => id,
+            Err(e) => {
+                log_warn!("Failed to start {}: {:?}", element_type, e);
+                return;
+            }
+        };
+
+        capture_pointer(event);
+
+        *self.shape.borrow_mut() = Some(ActiveShape {
+            pointer_id: pointer_id(event).unwrap_or_default(),
+            element_type,
+            preview_id,
+            options,
+            origin,
+            current: origin,
+            constrain: event.shift_key(),
+        });
+        self.request_render();
+    }
+
+    fn extend_shape(&self, event: &MouseEvent) {
+        let preview = {
+            let mut shape = self.shape.borrow_mut();
+            let Some(shape) = shape.as_mut() else {
+                return;
+            };
+            if pointer_id(event).is_some_and(|id| id != shape.pointer_id) {
+                return;
+            }
+            let Some(point) = self.event_to_world(event) else {
+                return;
+            };
+            shape.current = point;
+            shape.constrain = event.shift_key();
+            let data = shape_data(
+                shape.element_type,
+                &shape.options,
+                shape.origin,
+                shape.current,
+                shape.constrain,
+            );
+            (shape.preview_id.clone(), data)
+        };
+        if let Some(object) = self.get(&preview.0) {
+            object.borrow_mut().update(preview.1);
+            self.request_render();
+        }
+    }
+
+    // 预览放回对象池，按最终尺寸创建正式对象并记录历史
+    fn finish_shape(&self) {
+        let Some(shape) = self.shape.borrow_mut().take() else {
+            return;
+        };
+        self.release(&shape.preview_id);
+        self.request_render();
+
+        let zoom = self.scene_manager.borrow().zoom();
+        let (dx, dy) = (shape.current.0 - shape.origin.0, shape.current.1 - shape.origin.1);
+        if dx.hypot(dy) * zoom < MIN_SHAPE_DRAG {
+            return;
+        }
+        let data = shape_data(
+            shape.element_type,
+            &shape.options,
+            shape.origin,
+            shape.current,
+            shape.constrain,
+        );
+        match self.create(shape.element_type, data) {
+            Ok(id) => self.emit_shape_end(&id, shape.element_type),
+            Err(e) => log_warn!("Failed to create {}: {:?}", shape.element_type, e),
+        }
+    }
+
+    fn cancel_shape(&self) {
+        if let Some(shape) = self.shape.borrow_mut().take() {
+            self.release(&shape.preview_id);
+            self.request_render();
+        }
+    }
+
+    fn place_text(&self, event: &MouseEvent, options: TextToolOptions) {
+        let Some((x, y)) = self.event_to_world(event) else {
+            return;
+        };
+        let data = json!({
+            "x": x,
+            "y": y,
+            "text": options.text,
+            "font_size": options.font_size,
+            "font_family": options.font_family,
+            "fill": options.fill,
+        });
+        match self.create("text", data) {
+            Ok(id) => self.emit_shape_end(&id, "text"),
+            Err(e) => log_warn!("Failed to create text: {:?}", e),
+        }
+    }
+
+    fn emit_shape_end(&self, id: &str, element_type: &str) {
+        let payload = to_js_value(&json!({ "id": id, "type": element_type }));
+        let _ = self.events.emit(AppEvent::SHAPE_END.into(), &payload);
+    }
+}
+
+impl App {
+    fn begin_pan(&self, event: &MouseEvent) {
+        self.finish_pan();
+        capture_pointer(event);
+        self.history.borrow_mut().begin_transaction("Pan");
+        *self.panning.borrow_mut() = Some(ActivePan {
+            pointer_id: pointer_id(event).unwrap_or_default(),
+            last: (event.client_x() as f64, event.client_y() as f64),
+            velocity: VelocityTracker::default(),
+        });
+    }
+
+    // 让按下时光标下的世界坐标始终跟随光标
+    fn extend_pan(&self, event: &MouseEvent) {
+        let last = {
+            let mut panning = self.panning.borrow_mut();
+            let Some(panning) = panning.as_mut() else {
+                return;
+            };
+            if pointer_id(event).is_some_and(|id| id != panning.pointer_id) {
+                return;
+            }
+            let now = (event.client_x() as f64, event.client_y() as f64);
+            std::mem::replace(&mut panning.last, now)
+        };
+        let delta = {
+            let Ok(mut scene_manager) = self.scene_manager.try_borrow_mut() else {
+                return;
+            };
+            let from = scene_manager.client_to_world(last.0, last.1);
+            let to = scene_manager.client_to_world(event.client_x() as f64, event.client_y() as f64);
+            let (Some(from), Some(to)) = (from, to) else {
+                return;
+            };
+            let delta = (to.0 - from.0, to.1 - from.1);
+            if delta != (0.0, 0.0) {
+                scene_manager.pan(delta.0, delta.1);
+            }
+            delta
+        };
+        if let Some(panning) = self.panning.borrow_mut().as_mut() {
+            panning.velocity.add(event.time_stamp(), delta.0, delta.1);
+        }
+    }
+
+    fn finish_pan(&self) {
+        if self.panning.borrow_mut().take().is_some() {
+            self.history.borrow_mut().commit();
+        }
+    }
+
+    // 抬起时按最近的拖动速度继续惯性滑动
+    fn release_pan(&self, event: &MouseEvent) {
+        let Some(panning) = self.panning.borrow_mut().take() else {
+            return;
+        };
+        self.history.borrow_mut().commit();
+        self.start_inertia(panning.velocity.velocity(event.time_stamp()));
+    }
+}
+
+// 由拖拽起点和当前点得到图形属性，坐标为世界坐标
+fn shape_data(
+    element_type: &str,
+    options: &ShapeOptions,
+    origin: (f64, f64),
+    current: (f64, f64),
+    constrain: bool,
+) -> Value {
+    let (mut dx, mut dy) = (current.0 - origin.0, current.1 - origin.1);
+    if element_type == "line" {
+        if constrain {
+            let step = std::f64::consts::FRAC_PI_4;
+            let angle = (dy.atan2(dx) / step).round() * step;
+            let length = dx.hypot(dy);
+            (dx, dy) = (length * angle.cos(), length * angle.sin());
+        }
+        return json!({
+            "x1": origin.0,
+            "y1": origin.1,
+            "x2": origin.0 + dx,
+            "y2": origin.1 + dy,
+            "stroke": options.stroke,
+            "stroke_width": options.stroke_width,
+            "opacity": options.opacity,
+        });
+    }
+
+    if constrain {
+        let side = dx.abs().max(dy.abs());
+        (dx, dy) = (side.copysign(dx), side.copysign(dy));
+    }
+    json!({
+        "x": origin.0.min(origin.0 + dx),
+        "y": origin.1.min(origin.1 + dy),
+        "width": dx.abs(),
+        "height": dy.abs(),
+        "fill": options.fill,
+        "stroke": options.stroke,
+        "stroke_width": options.stroke_width,
+        "opacity": options.opacity,
+    })
+}
+
+// 拖出画布后仍能收到移动事件
+pub(super) fn capture_pointer(event: &MouseEvent) {
+    if let (Some(target), Some(pointer_id)) = (
+        event.target().and_then(|target| target.dyn_into::<Element>().ok()),
+        pointer_id(event),
+    ) {
+        let _ = target.set_pointer_capture(pointer_id);
+    }
+}
+
+pub(super) fn pointer_id(event: &MouseEvent) -> Option<i32> {
+    event.dyn_ref::<PointerEvent>().map(PointerEvent::pointer_id)
+}
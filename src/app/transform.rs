@@ -0,0 +1,45 @@
+use nalgebra as na;
+use serde::Serialize;
+
+use super::App;
+use crate::error::{EditingError, EditingResult};
+use crate::math::decompose_transform;
+
+// 对象在所在坐标系（容器或场景）下的变换矩阵，与 calc_transform 相同（包括锚点），
+// 以及分解出的平移、旋转（度）、缩放和斜切，供变换控件显示；分解时 skew_y 固定为 0
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectTransform {
+    pub matrix: [f64; 6],
+    pub translate: (f64, f64),
+    pub rotation: f64,
+    pub scale: (f64, f64),
+    pub skew: (f64, f64),
+}
+
+impl App {
+    pub fn object_transform(&self, id: &str) -> EditingResult<ObjectTransform> {
+        let object = self.get(id).ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        let matrix = object.borrow().calc_transform();
+        let parts = decompose_transform(matrix);
+        Ok(ObjectTransform {
+            matrix: [matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5]],
+            translate: parts.translate,
+            rotation: parts.rotation.to_degrees(),
+            scale: parts.scale,
+            skew: parts.skew,
+        })
+    }
+
+    // 按完整矩阵 [a, b, c, d, e, f] 设置对象的位置、缩放、斜切和旋转，整体为一次撤销；
+    // 不支持的分量（如文字的斜切、路径的旋转）被忽略
+    pub fn set_object_transform(&self, id: &str, matrix: [f64; 6]) -> EditingResult<()> {
+        let object = self.get(id).ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        self.history.borrow_mut().begin_transaction("set_transform");
+        object
+            .borrow_mut()
+            .apply_transform(na::Matrix1x6::from_row_slice(&matrix));
+        self.history.borrow_mut().commit();
+        self.request_render();
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use std::any::Any;
+
+use wasm_bindgen_futures::JsFuture;
+
+use super::App;
+use crate::element::Video;
+use crate::error::{EditingError, EditingResult};
+
+impl App {
+    // 开始播放，播放期间每帧重绘；浏览器拒绝播放（如未静音的自动播放）时返回错误
+    pub async fn play_video(&self, id: &str) -> EditingResult<()> {
+        let promise = self.with_video(id, |video| video.play())??;
+        self.playing_videos.borrow_mut().insert(id.to_string());
+        self.request_render();
+        if let Err(e) = JsFuture::from(promise).await {
+            self.playing_videos.borrow_mut().remove(id);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    pub fn pause_video(&self, id: &str) -> EditingResult<()> {
+        self.with_video(id, |video| video.pause())??;
+        self.playing_videos.borrow_mut().remove(id);
+        self.request_render();
+        Ok(())
+    }
+
+    pub fn is_video_playing(&self, id: &str) -> bool {
+        self.with_video(id, |video| video.is_playing()).unwrap_or(false)
+    }
+
+    // 去掉已暂停、播放结束或被删除的视频，返回是否还有视频在播放
+    pub(super) fn refresh_videos(&self) -> bool {
+        let Ok(mut playing) = self.playing_videos.try_borrow_mut() else {
+            return false;
+        };
+        if playing.is_empty() {
+            return false;
+        }
+        playing.retain(|id| self.with_video(id, |video| video.is_playing()).unwrap_or(false));
+        !playing.is_empty()
+    }
+
+    fn with_video<T>(&self, id: &str, f: impl FnOnce(&Video) -> T) -> EditingResult<T> {
+        let object = self
+            .get(id)
+            .ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        let object = object.borrow();
+        let video = (&**object as &dyn Any)
+            .downcast_ref::<Video>()
+            .ok_or_else(|| EditingError::UnsupportedElement(object.get_type().to_string()))?;
+        Ok(f(video))
+    }
+}
@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::element::Renderable;
+use crate::renderer::Renderer;
+
+// 少于该数量的连续同样式对象不值得合并
+const MIN_BATCH_SIZE: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchStyle {
+    pub fill: String,
+    pub stroke: String,
+    pub stroke_width: f64,
+    pub opacity: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchPass {
+    Fill,
+    Stroke,
+}
+
+pub enum RenderRun {
+    Single(Rc<RefCell<Box<dyn Renderable>>>),
+    Batch(BatchStyle, Vec<Rc<RefCell<Box<dyn Renderable>>>>),
+}
+
+// 正在累积的一段同样式对象
+type Run = (BatchStyle, Vec<Rc<RefCell<Box<dyn Renderable>>>>);
+
+// 只合并相邻的同样式对象，保证绘制顺序不变
+pub fn group_runs(objects: Vec<Rc<RefCell<Box<dyn Renderable>>>>) -> Vec<RenderRun> {
+    let mut runs = Vec::new();
+    let mut current: Option<Run> = None;
+
+    for object in objects {
+        let style = object.borrow().batch_style();
+        match (style, current.as_mut()) {
+            (Some(style), Some((current_style, members))) if *current_style == style => {
+                members.push(object);
+            }
+            (Some(style), _) => {
+                flush_run(&mut runs, current.take());
+                current = Some((style, vec![object]));
+            }
+            (None, _) => {
+                flush_run(&mut runs, current.take());
+                runs.push(RenderRun::Single(object));
+            }
+        }
+    }
+    flush_run(&mut runs, current);
+    runs
+}
+
+fn flush_run(runs: &mut Vec<RenderRun>, run: Option<Run>) {
+    let Some((style, members)) = run else {
+        return;
+    };
+    if members.len() >= MIN_BATCH_SIZE {
+        runs.push(RenderRun::Batch(style, members));
+    } else {
+        runs.extend(members.into_iter().map(RenderRun::Single));
+    }
+}
+
+pub fn draw_batch(
+    renderer: &dyn Renderer,
+    style: &BatchStyle,
+    objects: &[Rc<RefCell<Box<dyn Renderable>>>],
+) {
+    renderer.save();
+    renderer.set_global_alpha(style.opacity);
+
+    renderer.begin_path();
+    for object in objects {
+        object.borrow().append_batch_path(renderer, BatchPass::Fill);
+    }
+    renderer.set_fill_style(&style.fill);
+    renderer.fill();
+
+    if style.stroke_width > 0.0 {
+        renderer.begin_path();
+        for object in objects {
+            object.borrow().append_batch_path(renderer, BatchPass::Stroke);
+        }
+        renderer.set_stroke_style(&style.stroke);
+        renderer.set_line_width(style.stroke_width);
+        renderer.stroke();
+    }
+
+    renderer.restore();
+}
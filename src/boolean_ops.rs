@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::helper::{distance_to_polyline, point_in_polygon};
+
+// 世界坐标下的闭合多边形，首尾不重复
+pub type Contour = Vec<(f64, f64)>;
+
+// 交点和重合边的判定容差（世界坐标）
+const EPSILON: f64 = 1e-7;
+// 连接边时端点坐标按该精度对齐
+const SNAP: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BooleanOp {
+    Union,
+    Subtract,
+    Intersect,
+    Xor,
+}
+
+impl BooleanOp {
+    pub fn name(self) -> &'static str {
+        match self {
+            BooleanOp::Union => "Union",
+            BooleanOp::Subtract => "Subtract",
+            BooleanOp::Intersect => "Intersect",
+            BooleanOp::Xor => "Xor",
+        }
+    }
+}
+
+// 一条边相对另一个图形的位置；Shared 为与另一图形的某条边重合，参数表示方向是否相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgePlace {
+    Inside,
+    Outside,
+    Shared(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keep {
+    Forward,
+    Reverse,
+    Drop,
+}
+
+type Edge = ((f64, f64), (f64, f64));
+
+// 按奇偶规则计算两组轮廓的布尔运算。做法是把两边所有的边在交点处切开，
+// 按每段边中点在另一图形内、外或重合决定保留、反向还是丢弃，再把保留的边首尾相连成新的轮廓。
+// 输入先按嵌套深度统一方向（外轮廓为正、洞为负），结果沿用同样的方向
+pub fn boolean_op(subject: &[Contour], clip: &[Contour], op: BooleanOp) -> Vec<Contour> {
+    let subject = normalize(subject);
+    let clip = normalize(clip);
+    let subject_edges = edges(&subject);
+    let clip_edges = edges(&clip);
+    let (subject_parts, clip_parts) = split_edges(&subject_edges, &clip_edges);
+
+    let mut kept = Vec::new();
+    for edge in subject_parts {
+        let keep = match (classify(edge, &clip, &clip_edges), op) {
+            (EdgePlace::Outside, BooleanOp::Intersect) => Keep::Drop,
+            (EdgePlace::Outside, _) => Keep::Forward,
+            (EdgePlace::Inside, BooleanOp::Intersect) => Keep::Forward,
+            (EdgePlace::Inside, BooleanOp::Xor) => Keep::Reverse,
+            (EdgePlace::Inside, _) => Keep::Drop,
+            (EdgePlace::Shared(true), BooleanOp::Union | BooleanOp::Intersect) => Keep::Forward,
+            (EdgePlace::Shared(false), BooleanOp::Subtract) => Keep::Forward,
+            (EdgePlace::Shared(_), _) => Keep::Drop,
+        };
+        push_edge(&mut kept, edge, keep);
+    }
+    for edge in clip_parts {
+        // 重合的边已经由 subject 一侧处理
+        let keep = match (classify(edge, &subject, &subject_edges), op) {
+            (EdgePlace::Outside, BooleanOp::Union | BooleanOp::Xor) => Keep::Forward,
+            (EdgePlace::Inside, BooleanOp::Intersect) => Keep::Forward,
+            (EdgePlace::Inside, BooleanOp::Subtract | BooleanOp::Xor) => Keep::Reverse,
+            _ => Keep::Drop,
+        };
+        push_edge(&mut kept, edge, keep);
+    }
+    link(kept)
+}
+
+// 有向面积，正负表示方向
+pub fn signed_area(contour: &[(f64, f64)]) -> f64 {
+    let mut area = 0.0;
+    for (index, a) in contour.iter().enumerate() {
+        let b = contour[(index + 1) % contour.len()];
+        area += a.0 * b.1 - b.0 * a.1;
+    }
+    area / 2.0
+}
+
+// 奇偶规则下点是否在图形内
+pub fn contains_point(contours: &[Contour], point: (f64, f64)) -> bool {
+    contours
+        .iter()
+        .filter(|contour| point_in_polygon(point, contour))
+        .count()
+        % 2
+        == 1
+}
+
+fn push_edge(kept: &mut Vec<Edge>, (start, end): Edge, keep: Keep) {
+    match keep {
+        Keep::Forward => kept.push((start, end)),
+        Keep::Reverse => kept.push((end, start)),
+        Keep::Drop => {}
+    }
+}
+
+// 去掉重复点和退化的轮廓，被奇数个轮廓包含的为洞，方向与外轮廓相反
+fn normalize(contours: &[Contour]) -> Vec<Contour> {
+    let contours: Vec<Contour> = contours
+        .iter()
+        .map(|contour| clean(contour))
+        .filter(|contour| contour.len() >= 3 && signed_area(contour).abs() > EPSILON)
+        .collect();
+    contours
+        .iter()
+        .enumerate()
+        .map(|(index, contour)| {
+            let depth = contours
+                .iter()
+                .enumerate()
+                .filter(|(other, candidate)| *other != index && point_in_polygon(contour[0], candidate))
+                .count();
+            let mut contour = contour.clone();
+            if (signed_area(&contour) > 0.0) != (depth % 2 == 0) {
+                contour.reverse();
+            }
+            contour
+        })
+        .collect()
+}
+
+// 去掉相邻的重复点和共线的中间点
+fn clean(contour: &[(f64, f64)]) -> Contour {
+    let mut points: Contour = Vec::with_capacity(contour.len());
+    for point in contour {
+        if points.last().is_none_or(|last| !same_point(*last, *point)) {
+            points.push(*point);
+        }
+    }
+    while points.len() > 1 && same_point(points[0], points[points.len() - 1]) {
+        points.pop();
+    }
+
+    let mut index = 0;
+    while points.len() >= 3 && index < points.len() {
+        let count = points.len();
+        let previous = points[(index + count - 1) % count];
+        let next = points[(index + 1) % count];
+        let point = points[index];
+        let cross = (point.0 - previous.0) * (next.1 - point.1) - (point.1 - previous.1) * (next.0 - point.0);
+        let forward = (point.0 - previous.0) * (next.0 - point.0) + (point.1 - previous.1) * (next.1 - point.1);
+        if cross.abs() <= EPSILON && forward >= 0.0 {
+            points.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+    points
+}
+
+fn same_point(a: (f64, f64), b: (f64, f64)) -> bool {
+    (a.0 - b.0).abs() <= EPSILON && (a.1 - b.1).abs() <= EPSILON
+}
+
+fn edges(contours: &[Contour]) -> Vec<Edge> {
+    contours
+        .iter()
+        .flat_map(|contour| (0..contour.len()).map(move |index| (contour[index], contour[(index + 1) % contour.len()])))
+        .collect()
+}
+
+// 把两组边在彼此的交点处切开；同一个交点在两边使用完全相同的坐标
+fn split_edges(a: &[Edge], b: &[Edge]) -> (Vec<Edge>, Vec<Edge>) {
+    let mut a_cuts: Vec<Vec<(f64, (f64, f64))>> = vec![Vec::new(); a.len()];
+    let mut b_cuts: Vec<Vec<(f64, (f64, f64))>> = vec![Vec::new(); b.len()];
+    for (i, edge_a) in a.iter().enumerate() {
+        for (j, edge_b) in b.iter().enumerate() {
+            for (t, u, point) in intersections(*edge_a, *edge_b) {
+                a_cuts[i].push((t, point));
+                b_cuts[j].push((u, point));
+            }
+        }
+    }
+    (cut(a, a_cuts), cut(b, b_cuts))
+}
+
+fn cut(edges: &[Edge], cuts: Vec<Vec<(f64, (f64, f64))>>) -> Vec<Edge> {
+    let mut parts = Vec::with_capacity(edges.len());
+    for (edge, mut cuts) in edges.iter().zip(cuts) {
+        cuts.retain(|(t, _)| *t > EPSILON && *t < 1.0 - EPSILON);
+        cuts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut start = edge.0;
+        for (_, point) in cuts {
+            if !same_point(start, point) {
+                parts.push((start, point));
+                start = point;
+            }
+        }
+        if !same_point(start, edge.1) {
+            parts.push((start, edge.1));
+        }
+    }
+    parts
+}
+
+// 两条线段的交点，返回 (在 a 上的参数, 在 b 上的参数, 交点)；共线重合时返回彼此落在对方上的端点
+fn intersections(a: Edge, b: Edge) -> Vec<(f64, f64, (f64, f64))> {
+    let (p, r) = (a.0, (a.1 .0 - a.0 .0, a.1 .1 - a.0 .1));
+    let (q, s) = (b.0, (b.1 .0 - b.0 .0, b.1 .1 - b.0 .1));
+    let cross = |u: (f64, f64), v: (f64, f64)| u.0 * v.1 - u.1 * v.0;
+    let offset = (q.0 - p.0, q.1 - p.1);
+    let denominator = cross(r, s);
+    let scale = (r.0.hypot(r.1) * s.0.hypot(s.1)).max(f64::MIN_POSITIVE);
+
+    if denominator.abs() <= EPSILON * scale {
+        // 平行但不共线
+        if cross(offset, r).abs() > EPSILON * r.0.hypot(r.1).max(1.0) {
+            return Vec::new();
+        }
+        let project = |point: (f64, f64), origin: (f64, f64), direction: (f64, f64)| {
+            let length_sq = direction.0 * direction.0 + direction.1 * direction.1;
+            ((point.0 - origin.0) * direction.0 + (point.1 - origin.1) * direction.1) / length_sq
+        };
+        let mut hits = Vec::new();
+        for point in [b.0, b.1] {
+            let t = project(point, p, r);
+            if (0.0..=1.0).contains(&t) {
+                hits.push((t, project(point, q, s), point));
+            }
+        }
+        for point in [a.0, a.1] {
+            let u = project(point, q, s);
+            if (0.0..=1.0).contains(&u) {
+                hits.push((project(point, p, r), u, point));
+            }
+        }
+        return hits;
+    }
+
+    let t = cross(offset, s) / denominator;
+    let u = cross(offset, r) / denominator;
+    let range = -EPSILON..=1.0 + EPSILON;
+    if !range.contains(&t) || !range.contains(&u) {
+        return Vec::new();
+    }
+    // 交点落在端点附近时直接使用端点，避免产生极短的边
+    let point = if t <= EPSILON {
+        a.0
+    } else if t >= 1.0 - EPSILON {
+        a.1
+    } else if u <= EPSILON {
+        b.0
+    } else if u >= 1.0 - EPSILON {
+        b.1
+    } else {
+        (p.0 + r.0 * t, p.1 + r.1 * t)
+    };
+    vec![(t, u, point)]
+}
+
+fn classify((start, end): Edge, other: &[Contour], other_edges: &[Edge]) -> EdgePlace {
+    let middle = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+    let direction = (end.0 - start.0, end.1 - start.1);
+    let length = direction.0.hypot(direction.1);
+    let shared = other_edges.iter().find(|(a, b)| {
+        let other = (b.0 - a.0, b.1 - a.1);
+        let parallel = (direction.0 * other.1 - direction.1 * other.0).abs() <= SNAP * length * other.0.hypot(other.1);
+        parallel && distance_to_polyline(middle, &[*a, *b], false) <= SNAP
+    });
+    if let Some((a, b)) = shared {
+        let same = direction.0 * (b.0 - a.0) + direction.1 * (b.1 - a.1) > 0.0;
+        return EdgePlace::Shared(same);
+    }
+    if contains_point(other, middle) {
+        EdgePlace::Inside
+    } else {
+        EdgePlace::Outside
+    }
+}
+
+fn snap_key(point: (f64, f64)) -> (i64, i64) {
+    ((point.0 / SNAP).round() as i64, (point.1 / SNAP).round() as i64)
+}
+
+// 按端点把边连成闭合轮廓，无法闭合的残段丢弃
+fn link(edges: Vec<Edge>) -> Vec<Contour> {
+    let mut outgoing: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (index, (start, _)) in edges.iter().enumerate() {
+        outgoing.entry(snap_key(*start)).or_default().push(index);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut contours = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let start_key = snap_key(edges[first].0);
+        let mut contour = vec![edges[first].0];
+        let mut end = edges[first].1;
+        let closed = loop {
+            if snap_key(end) == start_key {
+                break true;
+            }
+            let next = outgoing
+                .get(&snap_key(end))
+                .and_then(|candidates| candidates.iter().copied().find(|index| !used[*index]));
+            let Some(next) = next else {
+                break false;
+            };
+            used[next] = true;
+            contour.push(edges[next].0);
+            end = edges[next].1;
+        };
+        if !closed {
+            continue;
+        }
+        let contour = clean(&contour);
+        if contour.len() >= 3 && signed_area(&contour).abs() > EPSILON {
+            contours.push(contour);
+        }
+    }
+    contours
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::element::{create_element, ObjectId, Renderable};
+use crate::error::EditingResult;
+
+// 写入系统剪贴板的文本带上格式标记，粘贴时据此识别是否为本编辑器的数据
+const CLIPBOARD_FORMAT: &str = "editing/elements@1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardEntry {
+    pub element_type: String,
+    pub data: Value,
+}
+
+impl ClipboardEntry {
+    pub fn from_object(object: &dyn Renderable) -> Self {
+        Self {
+            element_type: object.get_type().to_string(),
+            data: object.to_value(),
+        }
+    }
+
+    // 重建元素并平移 (dx, dy)；数据有效后才换上新 id，拾取颜色在加入场景时分配
+    pub fn instantiate(&self, dx: f64, dy: f64) -> EditingResult<Box<dyn Renderable>> {
+        let mut element = create_element(&self.element_type, &self.data)?;
+        *element.id_mut() = ObjectId::new();
+        let (x, y) = element.get_position();
+        element.set_position(x + dx, y + dy);
+        Ok(element)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClipboardPayload {
+    format: String,
+    elements: Vec<ClipboardEntry>,
+}
+
+#[derive(Debug, Default)]
+pub struct Clipboard {
+    entries: Vec<ClipboardEntry>,
+    // 连续粘贴时逐次累加偏移，避免副本完全重叠
+    paste_count: u32,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, entries: Vec<ClipboardEntry>) {
+        self.entries = entries;
+        self.paste_count = 0;
+    }
+
+    pub fn entries(&self) -> &[ClipboardEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn next_paste(&mut self) -> u32 {
+        self.paste_count += 1;
+        self.paste_count
+    }
+
+    pub fn to_text(&self) -> EditingResult<String> {
+        let payload = ClipboardPayload {
+            format: CLIPBOARD_FORMAT.to_string(),
+            elements: self.entries.clone(),
+        };
+        Ok(serde_json::to_string(&payload)?)
+    }
+
+    // 不是本编辑器写入的文本时返回 None
+    pub fn parse_text(text: &str) -> Option<Vec<ClipboardEntry>> {
+        let payload: ClipboardPayload = serde_json::from_str(text).ok()?;
+        (payload.format == CLIPBOARD_FORMAT).then_some(payload.elements)
+    }
+}
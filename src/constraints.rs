@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::object_manager::ObjectManager;
+use crate::scene_manager::SceneManager;
+
+// 跟随链每多一层需要多求解一遍，超过这个深度的部分留到下一帧
+const MAX_PASSES: usize = 8;
+
+fn one() -> f64 {
+    1.0
+}
+
+// 每帧渲染前求解的布局约束，结果直接写入对象属性，不记录历史
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Constraint {
+    // 对象的位置固定在视口上的某一点：anchor 为视口宽高的比例，offset 为 CSS 像素
+    PinToViewport {
+        #[serde(default)]
+        anchor_x: f64,
+        #[serde(default)]
+        anchor_y: f64,
+        #[serde(default)]
+        offset_x: f64,
+        #[serde(default)]
+        offset_y: f64,
+    },
+    // 抵消场景缩放，使对象在屏幕上保持 scale 对应的大小
+    FixedScreenSize {
+        #[serde(default = "one")]
+        scale_x: f64,
+        #[serde(default = "one")]
+        scale_y: f64,
+    },
+    // 位置跟随另一个对象，offset 为世界坐标
+    Follow {
+        target: String,
+        #[serde(default)]
+        offset_x: f64,
+        #[serde(default)]
+        offset_y: f64,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct Constraints {
+    constraints: HashMap<String, Vec<Constraint>>,
+}
+
+impl Constraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, id: &str, constraint: Constraint) {
+        self.constraints
+            .entry(id.to_string())
+            .or_default()
+            .push(constraint);
+    }
+
+    pub fn get(&self, id: &str) -> &[Constraint] {
+        self.constraints.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.constraints.remove(id).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.constraints.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
+    // 对象被删除后约束仍然保留，撤销删除时可以继续生效
+    pub fn apply(&self, object_manager: &ObjectManager, scene_manager: &SceneManager) {
+        for _ in 0..MAX_PASSES {
+            let changed = self
+                .constraints
+                .iter()
+                .filter(|(id, constraints)| self.apply_to(id, constraints, object_manager, scene_manager))
+                .count();
+            if changed == 0 {
+                break;
+            }
+        }
+    }
+
+    fn apply_to(
+        &self,
+        id: &str,
+        constraints: &[Constraint],
+        object_manager: &ObjectManager,
+        scene_manager: &SceneManager,
+    ) -> bool {
+        let Some(object) = object_manager.get(id) else {
+            return false;
+        };
+
+        let mut patch = Map::new();
+        for constraint in constraints {
+            match constraint {
+                Constraint::PinToViewport {
+                    anchor_x,
+                    anchor_y,
+                    offset_x,
+                    offset_y,
+                } => {
+                    let Some((width, height)) = scene_manager.viewport_size() else {
+                        continue;
+                    };
+                    let screen_x = width * anchor_x + offset_x;
+                    let screen_y = height * anchor_y + offset_y;
+                    if let Some((x, y)) = scene_manager.screen_to_world(screen_x, screen_y) {
+                        patch.insert("x".to_string(), x.into());
+                        patch.insert("y".to_string(), y.into());
+                    }
+                }
+                Constraint::FixedScreenSize { scale_x, scale_y } => {
+                    let zoom = scene_manager.zoom();
+                    patch.insert("scale_x".to_string(), (scale_x / zoom).into());
+                    patch.insert("scale_y".to_string(), (scale_y / zoom).into());
+                }
+                Constraint::Follow {
+                    target,
+                    offset_x,
+                    offset_y,
+                } => {
+                    if target == id {
+                        continue;
+                    }
+                    let Some(target) = object_manager.get(target) else {
+                        continue;
+                    };
+                    let (x, y) = target.borrow().position();
+                    patch.insert("x".to_string(), (x + offset_x).into());
+                    patch.insert("y".to_string(), (y + offset_y).into());
+                }
+            }
+        }
+
+        // 只写入变化的属性，避免每帧都把对象标记为脏
+        {
+            let object = object.borrow();
+            patch.retain(|key, value| object.property(key).as_ref() != Some(value));
+        }
+        if patch.is_empty() {
+            return false;
+        }
+        object.borrow_mut().update(Value::Object(patch));
+        true
+    }
+}
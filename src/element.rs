@@ -1,6 +1,33 @@
+mod chart;
+mod connector;
+mod ellipse;
+mod frame;
+mod instance;
+mod line;
+mod meta;
+mod path;
 mod rect;
-
+pub mod registry;
+mod script;
+mod sprite;
+mod stroke;
+mod text;
+mod video;
+
+pub use chart::{Chart, ChartKind};
+pub use connector::Connector;
+pub use ellipse::Ellipse;
+pub use frame::Frame;
+pub use instance::Instance;
+pub use line::Line;
+pub use meta::{ObjectMeta, METADATA_KEY};
+pub use path::{erase_path, simplify_path, Path, PathPoint};
 pub use rect::{Rect, RectOptions};
+pub use registry::{create_default_element, create_element};
+pub use script::register_script_element;
+pub use sprite::Sprite;
+pub use text::{Text, TextAlignment};
+pub use video::Video;
 
 use nalgebra as na;
 use serde_json::Value;
@@ -11,18 +38,83 @@ use std::any::{Any, TypeId};
 
 use crate::animation::{AnimationError, AnimationValue};
 use crate::app::App;
+use crate::batch::{BatchPass, BatchStyle};
+use crate::bounding_box::BoundingBox;
+use crate::error::{EditingError, EditingResult};
 use crate::renderer::Renderer;
 use crate::{animation::Animatable, helper::generate_id};
 
 use serde::{Deserialize, Serialize};
 
-use once_cell::sync::Lazy;
-use rand::Rng;
-use std::collections::HashMap;
+// 元素默认围绕几何中心旋转和缩放
+const DEFAULT_ANCHOR: f64 = 0.5;
+
+// 旧数据没有锚点字段，反序列化时补上默认值
+fn default_anchor() -> f64 {
+    DEFAULT_ANCHOR
+}
+
+// 对象响应拾取的部分，与 CSS/SVG 的 pointer-events 对应：None 的对象不会被点中，
+// 点击会落到下面的对象上；Fill、Stroke 只有填充或描边附近可以点中，没有对应部分的元素不会被点中
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PointerEvents {
+    #[default]
+    Auto,
+    None,
+    Fill,
+    Stroke,
+}
+
+impl PointerEvents {
+    pub fn fill(self) -> bool {
+        matches!(self, PointerEvents::Auto | PointerEvents::Fill)
+    }
+
+    pub fn stroke(self) -> bool {
+        matches!(self, PointerEvents::Auto | PointerEvents::Stroke)
+    }
+}
+
+// 属性在检查器中的编辑方式，由 DirtySetter 按字段类型推断
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyKind {
+    Number,
+    Boolean,
+    String,
+    Color,
+    Enum,
+    Array,
+    Object,
+}
+
+// 可编辑属性的描述，name 与 update 数据和历史记录中的键名一致
+#[derive(Debug, Clone, Serialize)]
+pub struct PropertySchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: PropertyKind,
+    // Option 字段，可以设为 null
+    pub nullable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    // Enum 的可选值
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub options: &'static [&'static str],
+    // 修改是否进入撤销历史
+    pub history: bool,
+}
 
-static mut ID_COLOR_MAP: Lazy<(HashMap<String, [u8; 4]>, HashMap<[u8; 4], String>)> =
-    Lazy::new(|| (HashMap::new(), HashMap::new()));
+// 变换后描边的最大放大倍数，用于在世界坐标和局部坐标之间换算线宽
+fn stroke_scale(scale_x: f64, scale_y: f64) -> f64 {
+    scale_x.abs().max(scale_y.abs()).max(f64::EPSILON)
+}
 
+// 反序列化只还原 id 和颜色本身；颜色由所在 App 的 ObjectManager 在加入时登记，
+// 已被占用时重新分配，所以撤销记录、导出等临时重建的元素不会占住颜色
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub struct ObjectId {
     id: String,
@@ -30,10 +122,24 @@ pub struct ObjectId {
 }
 
 impl ObjectId {
+    // 拾取颜色在加入 ObjectManager 时分配
     pub fn new() -> Self {
-        let id = generate_id();
-        let color_id = Self::generate_unique_color_id(&id);
-        Self { id, color_id }
+        Self {
+            id: generate_id(),
+            color_id: [0, 0, 0, 0],
+        }
+    }
+
+    // 使用调用方提供的 id（如外部数据模型的主键），是否与场景中已有的对象重复由 ObjectManager 在加入时检查
+    pub fn from_string(id: impl Into<String>) -> EditingResult<Self> {
+        let id = id.into();
+        if id.is_empty() {
+            return Err(EditingError::InvalidObjectId(id));
+        }
+        Ok(Self {
+            id,
+            color_id: [0, 0, 0, 0],
+        })
     }
 
     pub fn value(&self) -> &str {
@@ -49,35 +155,17 @@ impl ObjectId {
         )
     }
 
-    fn generate_unique_color_id(id: &str) -> [u8; 4] {
-        unsafe {
-            loop {
-                let color_id = Self::generate_random_color();
-                if !ID_COLOR_MAP.1.contains_key(&color_id) {
-                    ID_COLOR_MAP.0.insert(id.to_string(), color_id);
-                    ID_COLOR_MAP.1.insert(color_id, id.to_string());
-                    return color_id;
-                }
-            }
-        }
+    pub fn color_id(&self) -> [u8; 4] {
+        self.color_id
     }
 
-    fn generate_random_color() -> [u8; 4] {
-        let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
-        [
-            rng.gen(),
-            rng.gen(),
-            rng.gen(),
-            255, // 保持 alpha 通道为 255
-        ]
+    // 还没有加入过任何 ObjectManager
+    pub fn has_color(&self) -> bool {
+        self.color_id != [0, 0, 0, 0]
     }
 
-    pub fn get_id_by_color(color: [u8; 4]) -> Option<String> {
-        unsafe { ID_COLOR_MAP.1.get(&color).cloned() }
-    }
-
-    pub fn get_color_by_id(id: &str) -> Option<[u8; 4]> {
-        unsafe { ID_COLOR_MAP.0.get(id).cloned() }
+    pub fn set_color_id(&mut self, color_id: [u8; 4]) {
+        self.color_id = color_id;
     }
 }
 
@@ -91,11 +179,16 @@ pub trait Transformable {
     fn set_position(&mut self, x: f64, y: f64);
     fn set_scale(&mut self, sx: f64, sy: f64);
     fn set_skew(&mut self, skew_x: f64, skew_y: f64);
+    // transform 与 calc_transform 的结果含义相同（包括锚点），分解后写回位置、缩放、斜切和旋转
     fn apply_transform(&mut self, transform: na::Matrix1x6<f64>);
     fn get_rotation(&self) -> f64;
     fn get_position(&self) -> (f64, f64);
     fn get_scale(&self) -> (f64, f64);
 
+    // 归一化的锚点，旋转和缩放都围绕它进行
+    fn get_anchor(&self) -> (f64, f64);
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64);
+
     fn reset_transform(&mut self) {
         self.apply_transform(na::Matrix1x6::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
     }
@@ -176,7 +269,7 @@ impl Into<String> for EventType {
 
 pub trait Eventable {
     fn on(&mut self, event_type: EventType, callback: Box<dyn Fn()>) {
-        // get_event_system().add_listener(event_type, callback);
+        // app.events.add_listener(event_type, callback);
     }
 
     fn off(&mut self, event_type: EventType) {
@@ -199,6 +292,7 @@ pub trait Eventable {
 
 pub trait Renderable: Debug + Transformable + Dirty + Eventable + Any + Animatable {
     fn id(&self) -> &ObjectId;
+    fn id_mut(&mut self) -> &mut ObjectId;
 
     fn update(&mut self, data: Value);
 
@@ -206,11 +300,90 @@ pub trait Renderable: Debug + Transformable + Dirty + Eventable + Any + Animatab
     fn detach(&mut self);
     
     fn render(&self, renderer: &dyn Renderer);
+    // 绘制到拾取画布，颜色已被锁定为对象的拾取颜色；tolerance 为描边两侧额外可点中的世界坐标距离
+    fn render_hit(&self, renderer: &dyn Renderer, _tolerance: f64) {
+        self.render(renderer);
+    }
+    // 几何拾取时世界坐标 (x, y) 是否落在对象上，默认按包围盒判断
+    fn hit_test(&self, x: f64, y: f64, _tolerance: f64) -> bool {
+        self.bounding_box().contains_point(x, y)
+    }
+    // 为 None 时不参与拾取
+    fn pointer_events(&self) -> PointerEvents {
+        PointerEvents::Auto
+    }
     fn position(&self) -> (f64, f64);
+    // 世界坐标下的轴对齐包围盒
+    fn bounding_box(&self) -> BoundingBox;
     
     fn get_type(&self) -> &str;
 
     fn to_value(&self) -> Value;
+
+    // 按历史记录中的键名读取单个属性
+    fn property(&self, _name: &str) -> Option<Value> {
+        None
+    }
+
+    // 可编辑属性的描述，与 property 使用相同的键名
+    fn schema(&self) -> Vec<PropertySchema> {
+        Vec::new()
+    }
+
+    // 世界坐标下的闭合轮廓，用于布尔运算；不是封闭图形的元素返回空
+    fn outline(&self) -> Vec<Vec<(f64, f64)>> {
+        Vec::new()
+    }
+
+    // 位置由其他对象决定的元素（如连线）返回所依赖的对象 id
+    fn dependencies(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // 依赖的对象变化后调用，bounds 与 dependencies() 一一对应，对象不存在时为 None
+    fn resolve_dependencies(&mut self, _bounds: &[Option<BoundingBox>]) {}
+
+    // 容器（如框架）中的子对象 id，从下到上；子对象的坐标以 calc_transform 为坐标系
+    fn children(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // 子对象坐标系下的裁剪区域，为 None 时不裁剪
+    fn child_clip(&self) -> Option<BoundingBox> {
+        None
+    }
+
+    // 组件实例所引用的主组件 id，实例的内容按主组件当前的子对象绘制
+    fn instance_of(&self) -> Option<&str> {
+        None
+    }
+
+    // 实例对主组件中对象 id 的填充色覆盖
+    fn fill_override(&self, _id: &str) -> Option<&str> {
+        None
+    }
+
+    fn meta(&self) -> &ObjectMeta;
+    fn meta_mut(&mut self) -> &mut ObjectMeta;
+
+    // 发给 JS 的事件中描述该对象的数据
+    fn event_payload(&self) -> Value {
+        let meta = self.meta();
+        serde_json::json!({
+            "id": self.id().value(),
+            "type": self.get_type(),
+            "tags": meta.tags,
+            "metadata": meta.data,
+        })
+    }
+
+    // 返回 Some 时，相邻的同样式对象会被合并成一条路径绘制
+    fn batch_style(&self) -> Option<BatchStyle> {
+        None
+    }
+
+    // 把自身几何追加到当前路径，坐标已包含对象变换
+    fn append_batch_path(&self, _renderer: &dyn Renderer, _pass: BatchPass) {}
 }
 
 // 容器 trait
@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+
+use super::{default_anchor, Dirty, Eventable, DEFAULT_ANCHOR, ObjectId, ObjectMeta, PointerEvents, PropertySchema, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationHandle, AnimationValue, QwenAnimationBuilder}, app::App, bounding_box::BoundingBox, helper::easing, math::{anchored_transform, decompose_anchored, convert_1x6_to_3x3, transform_point}, renderer::{Filter, Renderer}
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// 没有指定颜色时依次使用的调色板
+const DEFAULT_COLORS: [&str; 6] = ["#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948"];
+// 环形图内圈用折线近似，每个扇区的最少分段数
+const MIN_ARC_SEGMENTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartKind {
+    #[default]
+    Bar,
+    Line,
+    Pie,
+}
+
+fn default_colors() -> Vec<String> {
+    DEFAULT_COLORS.iter().map(|color| color.to_string()).collect()
+}
+
+fn default_duration() -> f64 {
+    300.0
+}
+
+fn transition_done() -> f64 {
+    1.0
+}
+
+// 由数据数组生成的图表，在 (x, y, width, height) 中按当前尺寸布局，变换方式与 Rect 相同。
+// 修改 data 后在 duration 毫秒内从旧数据过渡到新数据，新增的项从 0 开始
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Chart {
+    id: ObjectId,
+    dirty: bool,
+    #[dirty_setter]
+    pub x: f64,
+    #[dirty_setter]
+    pub y: f64,
+    #[dirty_setter]
+    pub width: f64,
+    #[dirty_setter]
+    pub height: f64,
+    #[dirty_setter(options("bar", "line", "pie"))]
+    #[serde(default)]
+    pub kind: ChartKind,
+    #[dirty_setter]
+    #[serde(default)]
+    pub data: Vec<f64>,
+    // 柱子、扇区按顺序循环取色，折线使用第一个颜色
+    #[dirty_setter]
+    #[serde(default = "default_colors")]
+    pub colors: Vec<String>,
+    // 柱子之间的空隙占每格宽度的比例
+    #[dirty_setter(clamp(0.0, 0.9))]
+    #[serde(default)]
+    pub gap: f64,
+    // 环形图内半径占外半径的比例，0 为饼图
+    #[dirty_setter(clamp(0.0, 0.95))]
+    #[serde(default)]
+    pub inner_radius: f64,
+    // 折线的颜色取自 colors，stroke 用于柱子和扇区的边线
+    #[dirty_setter(color)]
+    pub stroke: String,
+    #[dirty_setter]
+    pub stroke_width: f64,
+    #[dirty_setter]
+    #[serde(default = "default_duration")]
+    pub duration: f64,
+    // 数据过渡的进度，由动画推进
+    #[dirty_setter(skip_history, clamp(0.0, 1.0))]
+    #[serde(skip, default = "transition_done")]
+    pub transition: f64,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    #[dirty_setter]
+    pub scale_x: f64,
+    #[dirty_setter]
+    pub scale_y: f64,
+    #[dirty_setter]
+    pub skew_x: f64,
+    #[dirty_setter]
+    pub skew_y: f64,
+    #[dirty_setter]
+    pub rotation: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_x: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_y: f64,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    // 过渡开始时显示的数据
+    #[serde(skip)]
+    previous: Vec<f64>,
+    #[serde(skip)]
+    transition_handle: Option<AnimationHandle>,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl Chart {
+    pub fn new(x: f64, y: f64, width: f64, height: f64, kind: ChartKind, data: Vec<f64>) -> Self {
+        Chart {
+            id: ObjectId::new(),
+            dirty: true,
+            x,
+            y,
+            width,
+            height,
+            kind,
+            data,
+            colors: default_colors(),
+            gap: 0.2,
+            inner_radius: 0.0,
+            stroke: "#ffffff".to_string(),
+            stroke_width: 1.0,
+            duration: default_duration(),
+            transition: 1.0,
+            opacity: 1.0,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
+            scale_x: 1.0,
+            scale_y: 1.0,
+            skew_x: 0.0,
+            skew_y: 0.0,
+            rotation: 0.0,
+            anchor_x: DEFAULT_ANCHOR,
+            anchor_y: DEFAULT_ANCHOR,
+            meta: ObjectMeta::default(),
+            previous: Vec::new(),
+            transition_handle: None,
+            app: None,
+        }
+    }
+
+    // 当前显示的数据，过渡中为新旧数据的插值
+    pub fn values(&self) -> Vec<f64> {
+        let t = self.transition;
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let from = self.previous.get(index).copied().unwrap_or(0.0);
+                from + (value - from) * t
+            })
+            .collect()
+    }
+
+    fn color(&self, index: usize) -> &str {
+        match self.colors.len() {
+            0 => DEFAULT_COLORS[index % DEFAULT_COLORS.len()],
+            len => &self.colors[index % len],
+        }
+    }
+
+    // 从 previous 过渡到当前 data；没有关联 App 或 duration 为 0 时直接显示新数据
+    fn start_transition(&mut self, previous: Vec<f64>) {
+        if let Some(handle) = self.transition_handle.take() {
+            handle.cancel();
+        }
+        self.previous = previous;
+        self.transition = 1.0;
+        let Some(app) = self.app.clone() else {
+            return;
+        };
+        if self.duration <= 0.0 {
+            return;
+        }
+        // 动画管理器正在推进时不能再添加动画，此时直接跳到新数据
+        let Ok(mut animation_manager) = app.animation_manager.try_borrow_mut() else {
+            return;
+        };
+        let animation = QwenAnimationBuilder::new(self.duration / 1000.0)
+            .add_property("transition", AnimationValue::Float(0.0), AnimationValue::Float(1.0))
+            .set_easing(Box::new(easing::ease_in_out_quad))
+            .build();
+        self.transition = 0.0;
+        self.transition_handle =
+            Some(animation_manager.add_animation(self.id.value().to_string(), Box::new(animation)));
+        drop(animation_manager);
+        app.request_render();
+    }
+
+    // 纵轴范围包含 0，全为 0 时给一个单位高度避免除零
+    fn value_range(values: &[f64]) -> (f64, f64) {
+        let min = values.iter().copied().fold(0.0, f64::min);
+        let max = values.iter().copied().fold(0.0, f64::max);
+        if max - min > f64::EPSILON {
+            (min, max)
+        } else {
+            (min, min + 1.0)
+        }
+    }
+
+    fn value_y(&self, value: f64, (min, max): (f64, f64)) -> f64 {
+        self.height * (max - value) / (max - min)
+    }
+
+    fn draw_bars(&self, renderer: &dyn Renderer, values: &[f64]) {
+        let range = Self::value_range(values);
+        let slot = self.width / values.len() as f64;
+        let bar_width = slot * (1.0 - self.gap);
+        renderer.set_stroke_style(&self.stroke);
+        renderer.set_line_width(self.stroke_width);
+        for (index, value) in values.iter().enumerate() {
+            let top = self.value_y(value.max(0.0), range);
+            let height = self.value_y(value.min(0.0), range) - top;
+            let x = index as f64 * slot + (slot - bar_width) / 2.0;
+            renderer.draw_rectangle(x, top, bar_width, height, self.color(index));
+            if self.stroke_width > 0.0 && height > 0.0 {
+                renderer.stroke_rect(x, top, bar_width, height);
+            }
+        }
+    }
+
+    fn draw_line(&self, renderer: &dyn Renderer, values: &[f64]) {
+        let range = Self::value_range(values);
+        let step = if values.len() > 1 { self.width / (values.len() - 1) as f64 } else { 0.0 };
+        let points: Vec<(f64, f64)> = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let x = if values.len() > 1 { index as f64 * step } else { self.width / 2.0 };
+                (x, self.value_y(*value, range))
+            })
+            .collect();
+        let color = self.color(0);
+        let line_width = self.stroke_width.max(1.0);
+        renderer.begin_path();
+        renderer.move_to(points[0].0, points[0].1);
+        for (x, y) in points.iter().skip(1) {
+            renderer.line_to(*x, *y);
+        }
+        renderer.set_stroke_style(color);
+        renderer.set_line_width(line_width);
+        renderer.stroke();
+        for (x, y) in points {
+            renderer.draw_circle(x, y, line_width * 1.5, color);
+        }
+    }
+
+    fn draw_pie(&self, renderer: &dyn Renderer, values: &[f64]) {
+        let total: f64 = values.iter().map(|value| value.max(0.0)).sum();
+        if total <= 0.0 {
+            return;
+        }
+        let (cx, cy) = (self.width / 2.0, self.height / 2.0);
+        let radius = self.width.min(self.height) / 2.0;
+        let inner = radius * self.inner_radius;
+        // 从 12 点方向顺时针排列
+        let mut start = -PI / 2.0;
+        for (index, value) in values.iter().enumerate() {
+            let sweep = value.max(0.0) / total * TAU;
+            if sweep <= 0.0 {
+                continue;
+            }
+            let end = start + sweep;
+            renderer.begin_path();
+            if inner > 0.0 {
+                renderer.move_to(cx + inner * start.cos(), cy + inner * start.sin());
+                renderer.arc(cx, cy, radius, start, end);
+                let segments = ((sweep / TAU * 64.0).ceil() as usize).max(MIN_ARC_SEGMENTS);
+                for step in (0..=segments).rev() {
+                    let angle = start + sweep * step as f64 / segments as f64;
+                    renderer.line_to(cx + inner * angle.cos(), cy + inner * angle.sin());
+                }
+            } else {
+                renderer.move_to(cx, cy);
+                renderer.arc(cx, cy, radius, start, end);
+            }
+            renderer.close_path();
+            renderer.set_fill_style(self.color(index));
+            renderer.fill();
+            if self.stroke_width > 0.0 {
+                renderer.set_stroke_style(&self.stroke);
+                renderer.set_line_width(self.stroke_width);
+                renderer.stroke();
+            }
+            start = end;
+        }
+    }
+
+    fn draw(&self, renderer: &dyn Renderer, hit: bool) {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return;
+        }
+        let binding = self.calc_transform();
+        if let [a, b, c, d, e, f] = binding.as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+        if hit {
+            renderer.draw_rectangle(0.0, 0.0, self.width, self.height, "black");
+            return;
+        }
+
+        let values = self.values();
+        if values.is_empty() {
+            return;
+        }
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        match self.kind {
+            ChartKind::Bar => self.draw_bars(renderer, &values),
+            ChartKind::Line => self.draw_line(renderer, &values),
+            ChartKind::Pie => self.draw_pie(renderer, &values),
+        }
+    }
+}
+
+impl Dirty for Chart {
+    fn set_dirty(&mut self) {
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Chart {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    // 所有修改（接口调用、撤销重做、数据绑定、同步）都经过这里，data 变化时开始过渡
+    fn update(&mut self, data: Value) {
+        let data_changed = data.get("data").is_some();
+        let shown = self.values();
+        self.update(data);
+        if data_changed && shown != self.data {
+            self.start_transition(shown);
+        }
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        self.draw(renderer, false);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, _tolerance: f64) {
+        self.draw(renderer, true);
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let transform = self.calc_transform();
+        let corners = BoundingBox::new(0.0, 0.0, self.width, self.height)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y));
+        BoundingBox::from_points(&corners)
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        if let Some(handle) = self.transition_handle.take() {
+            handle.cancel();
+        }
+        self.transition = 1.0;
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "chart"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Chart {}
+
+impl Transformable for Chart {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(
+            self.scale_x,
+            self.skew_x,
+            self.skew_y,
+            self.scale_y,
+            self.x,
+            self.y,
+        )
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        let transform = convert_1x6_to_3x3(self.get_transform());
+        let center = transform * na::Vector3::new(self.width / 2.0, self.height / 2.0, 1.0);
+        (center.x, center.y)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        anchored_transform(
+            self.get_transform(),
+            self.width,
+            self.height,
+            self.rotation,
+            (self.anchor_x, self.anchor_y),
+        )
+    }
+
+    fn set_rotation(&mut self, angle_degrees: f64) {
+        self.set_rotation(angle_degrees % 360.0);
+    }
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        self.set_x(x);
+        self.set_y(y);
+    }
+
+    fn set_scale(&mut self, sx: f64, sy: f64) {
+        self.set_scale_x(sx);
+        self.set_scale_y(sy);
+    }
+
+    fn set_skew(&mut self, skew_x: f64, skew_y: f64) {
+        self.set_skew_x(skew_x);
+        self.set_skew_y(skew_y);
+    }
+
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64) {
+        self.set_anchor_x(anchor_x);
+        self.set_anchor_y(anchor_y);
+    }
+
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        let (base, rotation) =
+            decompose_anchored(transform, self.width, self.height, (self.anchor_x, self.anchor_y));
+        self.set_x(base[4]);
+        self.set_y(base[5]);
+        self.set_scale(base[0], base[3]);
+        self.set_skew(base[1], base[2]);
+        self.set_rotation(rotation);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (self.scale_x, self.scale_y)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (self.anchor_x, self.anchor_y)
+    }
+}
+
+impl Animatable for Chart {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x", AnimationValue::Float(v)) => dirty_properties.x = Some(v),
+                ("y", AnimationValue::Float(v)) => dirty_properties.y = Some(v),
+                ("width", AnimationValue::Float(v)) => dirty_properties.width = Some(v),
+                ("height", AnimationValue::Float(v)) => dirty_properties.height = Some(v),
+                ("gap", AnimationValue::Float(v)) => dirty_properties.gap = Some(v),
+                ("inner_radius", AnimationValue::Float(v)) => dirty_properties.inner_radius = Some(v),
+                ("stroke", AnimationValue::String(v)) => dirty_properties.stroke = Some(v),
+                ("stroke_width", AnimationValue::Float(v)) => {
+                    dirty_properties.stroke_width = Some(v)
+                }
+                ("transition", AnimationValue::Float(v)) => dirty_properties.transition = Some(v),
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                ("scale_x", AnimationValue::Float(v)) => dirty_properties.scale_x = Some(v),
+                ("scale_y", AnimationValue::Float(v)) => dirty_properties.scale_y = Some(v),
+                ("skew_x", AnimationValue::Float(v)) => dirty_properties.skew_x = Some(v),
+                ("skew_y", AnimationValue::Float(v)) => dirty_properties.skew_y = Some(v),
+                ("rotation", AnimationValue::Float(v)) => dirty_properties.rotation = Some(v),
+                ("anchor_x", AnimationValue::Float(v)) => dirty_properties.anchor_x = Some(v),
+                ("anchor_y", AnimationValue::Float(v)) => dirty_properties.anchor_y = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
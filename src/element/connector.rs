@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use super::stroke::{distance_to_cubic, stroke_bounds};
+use super::{Dirty, Eventable, ObjectId, ObjectMeta, PointerEvents, PropertySchema, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, bounding_box::BoundingBox, helper::distance_to_polyline, renderer::{LineCap, LineJoin, Renderer, Filter}
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// 箭头长度相对线宽的倍数，以及最小长度
+const ARROW_SCALE: f64 = 4.0;
+const MIN_ARROW_SIZE: f64 = 8.0;
+
+fn route_on_load() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachSide {
+    // 朝向另一端所在的一侧
+    #[default]
+    Auto,
+    Top,
+    Right,
+    Bottom,
+    Left,
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Routing {
+    #[default]
+    Straight,
+    // 水平/竖直折线
+    Orthogonal,
+    // 三次贝塞尔曲线，控制点沿连接边的法线方向
+    Curved,
+}
+
+// 连接两个对象的连线，路径由两端对象的包围盒计算，端点移动后自动更新
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Connector {
+    id: ObjectId,
+    // 路径不参与序列化，加载后需要重新计算
+    #[serde(skip, default = "route_on_load")]
+    dirty: bool,
+    #[dirty_setter]
+    pub source: String,
+    #[dirty_setter]
+    pub target: String,
+    #[dirty_setter(options("auto", "top", "right", "bottom", "left", "center"))]
+    #[serde(default)]
+    pub source_side: AttachSide,
+    #[dirty_setter(options("auto", "top", "right", "bottom", "left", "center"))]
+    #[serde(default)]
+    pub target_side: AttachSide,
+    #[dirty_setter(options("straight", "orthogonal", "curved"))]
+    #[serde(default)]
+    pub routing: Routing,
+    #[dirty_setter(color)]
+    pub stroke: String,
+    #[dirty_setter]
+    pub stroke_width: f64,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    // 元素级的 CSS 滤镜效果，如模糊、投影
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    #[dirty_setter]
+    #[serde(default)]
+    pub start_arrow: bool,
+    #[dirty_setter]
+    #[serde(default)]
+    pub end_arrow: bool,
+
+    // 世界坐标下的路径；曲线时为 [起点, 控制点1, 控制点2, 终点]。任一端缺失时为空
+    #[serde(skip)]
+    points: Vec<(f64, f64)>,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl Connector {
+    pub fn new(source: &str, target: &str) -> Self {
+        Connector {
+            id: ObjectId::new(),
+            dirty: true,
+            source: source.to_string(),
+            target: target.to_string(),
+            source_side: AttachSide::Auto,
+            target_side: AttachSide::Auto,
+            routing: Routing::Straight,
+            stroke: "black".to_string(),
+            stroke_width: 2.0,
+            opacity: 1.0,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
+            start_arrow: false,
+            end_arrow: true,
+            points: Vec::new(),
+            meta: ObjectMeta::default(),
+            app: None,
+        }
+    }
+
+    fn arrow_size(&self) -> f64 {
+        (self.stroke_width * ARROW_SCALE).max(MIN_ARROW_SIZE)
+    }
+
+    // 返回连接点和该点处指向外侧的法线
+    fn attach_point(bounds: &BoundingBox, side: AttachSide, other: &BoundingBox) -> ((f64, f64), (f64, f64)) {
+        let (cx, cy) = bounds.center();
+        let side = match side {
+            AttachSide::Auto => {
+                let (ox, oy) = other.center();
+                let (dx, dy) = (ox - cx, oy - cy);
+                match (dx.abs() > dy.abs(), dx > 0.0, dy > 0.0) {
+                    (true, true, _) => AttachSide::Right,
+                    (true, false, _) => AttachSide::Left,
+                    (false, _, true) => AttachSide::Bottom,
+                    (false, _, false) => AttachSide::Top,
+                }
+            }
+            side => side,
+        };
+        match side {
+            AttachSide::Top => ((cx, bounds.y), (0.0, -1.0)),
+            AttachSide::Right => ((bounds.right(), cy), (1.0, 0.0)),
+            AttachSide::Bottom => ((cx, bounds.bottom()), (0.0, 1.0)),
+            AttachSide::Left => ((bounds.x, cy), (-1.0, 0.0)),
+            AttachSide::Center | AttachSide::Auto => ((cx, cy), (0.0, 0.0)),
+        }
+    }
+
+    fn route(&self, source: &BoundingBox, target: &BoundingBox) -> Vec<(f64, f64)> {
+        let (start, start_normal) = Self::attach_point(source, self.source_side, target);
+        let (end, end_normal) = Self::attach_point(target, self.target_side, source);
+
+        match self.routing {
+            Routing::Straight => vec![start, end],
+            Routing::Orthogonal => {
+                // 从水平边出发时先走水平线，否则先走竖直线
+                if start_normal.1 == 0.0 {
+                    let mid_x = (start.0 + end.0) / 2.0;
+                    vec![start, (mid_x, start.1), (mid_x, end.1), end]
+                } else {
+                    let mid_y = (start.1 + end.1) / 2.0;
+                    vec![start, (start.0, mid_y), (end.0, mid_y), end]
+                }
+            }
+            Routing::Curved => {
+                let distance = (end.0 - start.0).abs().max((end.1 - start.1).abs()) / 2.0;
+                vec![
+                    start,
+                    (start.0 + start_normal.0 * distance, start.1 + start_normal.1 * distance),
+                    (end.0 + end_normal.0 * distance, end.1 + end_normal.1 * distance),
+                    end,
+                ]
+            }
+        }
+    }
+
+    fn trace_path(&self, renderer: &dyn Renderer) {
+        let Some(&(x, y)) = self.points.first() else {
+            return;
+        };
+        renderer.begin_path();
+        renderer.move_to(x, y);
+        match (self.routing, self.points.as_slice()) {
+            (Routing::Curved, [_, c1, c2, end]) => {
+                renderer.bezier_curve_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1)
+            }
+            (_, points) => {
+                for (x, y) in points.iter().skip(1) {
+                    renderer.line_to(*x, *y);
+                }
+            }
+        }
+    }
+
+    // 箭头沿 from -> tip 方向
+    fn draw_arrow(&self, renderer: &dyn Renderer, from: (f64, f64), tip: (f64, f64)) {
+        let (dx, dy) = (tip.0 - from.0, tip.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            return;
+        }
+        let (ux, uy) = (dx / length, dy / length);
+        let size = self.arrow_size();
+        let (bx, by) = (tip.0 - ux * size, tip.1 - uy * size);
+        let half = size / 2.0;
+
+        renderer.begin_path();
+        renderer.move_to(tip.0, tip.1);
+        renderer.line_to(bx - uy * half, by + ux * half);
+        renderer.line_to(bx + uy * half, by - ux * half);
+        renderer.close_path();
+        renderer.fill();
+    }
+
+    fn draw(&self, renderer: &dyn Renderer, line_width: f64) {
+        let [first, second, ..] = self.points.as_slice() else {
+            return;
+        };
+        let [.., second_last, last] = self.points.as_slice() else {
+            return;
+        };
+
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        renderer.set_stroke_style(&self.stroke);
+        renderer.set_fill_style(&self.stroke);
+        renderer.set_line_width(line_width);
+        renderer.set_line_cap(LineCap::Round);
+        renderer.set_line_join(LineJoin::Round);
+        self.trace_path(renderer);
+        renderer.stroke();
+
+        if self.start_arrow {
+            self.draw_arrow(renderer, *second, *first);
+        }
+        if self.end_arrow {
+            self.draw_arrow(renderer, *second_last, *last);
+        }
+    }
+}
+
+impl Dirty for Connector {
+    fn set_dirty(&mut self) {
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Connector {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        self.draw(renderer, self.stroke_width);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, tolerance: f64) {
+        // 只有描边，pointer_events 为 fill 时不会被点中
+        if !self.pointer_events.stroke() {
+            return;
+        }
+        self.draw(renderer, self.stroke_width + 2.0 * tolerance);
+    }
+
+    fn hit_test(&self, x: f64, y: f64, tolerance: f64) -> bool {
+        let distance = match (self.routing, self.points.as_slice()) {
+            (Routing::Curved, [start, c1, c2, end]) => distance_to_cubic((x, y), [*start, *c1, *c2, *end]),
+            (_, points) => distance_to_polyline((x, y), points, false),
+        };
+        self.pointer_events.stroke() && distance <= self.stroke_width / 2.0 + tolerance
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        self.points.first().copied().unwrap_or_default()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        if self.points.is_empty() {
+            return BoundingBox::default();
+        }
+        // 箭头会超出线宽
+        stroke_bounds(&self.points, 2.0 * self.arrow_size())
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "connector"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec![self.source.clone(), self.target.clone()]
+    }
+
+    fn resolve_dependencies(&mut self, bounds: &[Option<BoundingBox>]) {
+        self.points = match bounds {
+            [Some(source), Some(target)] => self.route(source, target),
+            _ => Vec::new(),
+        };
+        self.set_dirty();
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Connector {}
+
+// 连线的几何完全由两端对象决定，自身的变换操作没有效果
+impl Transformable for Connector {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        self.get_transform()
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        self.bounding_box().center()
+    }
+
+    fn set_rotation(&mut self, _angle_degrees: f64) {}
+
+    fn set_position(&mut self, _x: f64, _y: f64) {}
+
+    fn set_scale(&mut self, _sx: f64, _sy: f64) {}
+
+    fn set_skew(&mut self, _skew_x: f64, _skew_y: f64) {}
+
+    fn set_anchor(&mut self, _anchor_x: f64, _anchor_y: f64) {}
+
+    fn apply_transform(&mut self, _transform: na::Matrix1x6<f64>) {}
+
+    fn get_rotation(&self) -> f64 {
+        0.0
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        self.position()
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (1.0, 1.0)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+}
+
+impl Animatable for Connector {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("stroke", AnimationValue::String(v)) => dirty_properties.stroke = Some(v),
+                ("stroke_width", AnimationValue::Float(v)) => {
+                    dirty_properties.stroke_width = Some(v)
+                }
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
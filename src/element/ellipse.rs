@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use super::{default_anchor, stroke_scale, Dirty, Eventable, DEFAULT_ANCHOR, PointerEvents, PropertySchema, ObjectId, ObjectMeta, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, bounding_box::BoundingBox, helper::{distance_to_polyline, point_in_polygon}, math::{anchored_transform, decompose_anchored, convert_1x6_to_3x3, transform_point}, renderer::{Filter, Renderer}
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// 几何拾取时用多边形近似椭圆的边数
+const OUTLINE_SEGMENTS: usize = 64;
+
+// 内切于 (x, y, width, height) 的椭圆，变换方式与 Rect 相同
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Ellipse {
+    id: ObjectId,
+    dirty: bool,
+    #[dirty_setter]
+    pub x: f64,
+    #[dirty_setter]
+    pub y: f64,
+    #[dirty_setter]
+    pub width: f64,
+    #[dirty_setter]
+    pub height: f64,
+    #[dirty_setter(color)]
+    pub fill: String,
+    #[dirty_setter(color)]
+    pub stroke: String,
+    #[dirty_setter]
+    pub stroke_width: f64,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    // 元素级的 CSS 滤镜效果，如模糊、投影
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    #[dirty_setter]
+    pub scale_x: f64,
+    #[dirty_setter]
+    pub scale_y: f64,
+    #[dirty_setter]
+    pub skew_x: f64,
+    #[dirty_setter]
+    pub skew_y: f64,
+    #[dirty_setter]
+    pub rotation: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_x: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_y: f64,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl Ellipse {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Ellipse {
+            id: ObjectId::new(),
+            dirty: true,
+            x,
+            y,
+            width,
+            height,
+            fill: "blue".to_string(),
+            stroke: "black".to_string(),
+            stroke_width: 2.0,
+            opacity: 1.0,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
+            scale_x: 1.0,
+            scale_y: 1.0,
+            skew_x: 0.0,
+            skew_y: 0.0,
+            rotation: 0.0,
+            anchor_x: DEFAULT_ANCHOR,
+            anchor_y: DEFAULT_ANCHOR,
+            meta: ObjectMeta::default(),
+            app: None,
+        }
+    }
+
+    fn draw(&self, renderer: &dyn Renderer) {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return;
+        }
+        let binding = self.calc_transform();
+        if let [a, b, c, d, e, f] = binding.as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+        let (radius_x, radius_y) = (self.width / 2.0, self.height / 2.0);
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        renderer.draw_ellipse(radius_x, radius_y, radius_x, radius_y, &self.fill);
+        if self.stroke_width > 0.0 {
+            // 沿用 draw_ellipse 留下的路径描边
+            renderer.set_stroke_style(&self.stroke);
+            renderer.set_line_width(self.stroke_width);
+            renderer.stroke();
+        }
+    }
+
+    // 近似椭圆轮廓的多边形，坐标未经对象变换
+    fn local_outline(&self) -> Vec<(f64, f64)> {
+        let (radius_x, radius_y) = (self.width / 2.0, self.height / 2.0);
+        (0..OUTLINE_SEGMENTS)
+            .map(|index| {
+                let angle = TAU * index as f64 / OUTLINE_SEGMENTS as f64;
+                (radius_x + radius_x * angle.cos(), radius_y + radius_y * angle.sin())
+            })
+            .collect()
+    }
+}
+
+impl Dirty for Ellipse {
+    fn set_dirty(&mut self) {
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Ellipse {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        self.draw(renderer);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, tolerance: f64) {
+        let area = self.pointer_events;
+        if area == PointerEvents::Auto {
+            return self.draw(renderer);
+        }
+        if let [a, b, c, d, e, f] = self.calc_transform().as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+        if area.fill() {
+            let (radius_x, radius_y) = (self.width / 2.0, self.height / 2.0);
+            renderer.draw_ellipse(radius_x, radius_y, radius_x, radius_y, &self.fill);
+        }
+        if area.stroke() {
+            let scale = stroke_scale(self.scale_x, self.scale_y);
+            renderer.begin_path();
+            for (index, (x, y)) in self.local_outline().into_iter().enumerate() {
+                if index == 0 {
+                    renderer.move_to(x, y);
+                } else {
+                    renderer.line_to(x, y);
+                }
+            }
+            renderer.close_path();
+            renderer.set_line_width(self.stroke_width + 2.0 * tolerance / scale);
+            renderer.stroke();
+        }
+    }
+
+    fn hit_test(&self, x: f64, y: f64, tolerance: f64) -> bool {
+        let transform = self.calc_transform();
+        let outline: Vec<(f64, f64)> = self
+            .local_outline()
+            .into_iter()
+            .map(|(x, y)| transform_point(transform, x, y))
+            .collect();
+        let reach = self.stroke_width * stroke_scale(self.scale_x, self.scale_y) / 2.0 + tolerance;
+        (self.pointer_events.stroke() && distance_to_polyline((x, y), &outline, true) <= reach)
+            || (self.pointer_events.fill() && point_in_polygon((x, y), &outline))
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let transform = self.calc_transform();
+        let corners = BoundingBox::new(0.0, 0.0, self.width, self.height)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y));
+        BoundingBox::from_points(&corners)
+    }
+
+    fn outline(&self) -> Vec<Vec<(f64, f64)>> {
+        let transform = self.calc_transform();
+        let outline = self
+            .local_outline()
+            .into_iter()
+            .map(|(x, y)| transform_point(transform, x, y))
+            .collect();
+        vec![outline]
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "ellipse"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Ellipse {}
+
+impl Transformable for Ellipse {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(
+            self.scale_x,
+            self.skew_x,
+            self.skew_y,
+            self.scale_y,
+            self.x,
+            self.y,
+        )
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        let transform = convert_1x6_to_3x3(self.get_transform());
+        let center = transform * na::Vector3::new(self.width / 2.0, self.height / 2.0, 1.0);
+        (center.x, center.y)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        anchored_transform(
+            self.get_transform(),
+            self.width,
+            self.height,
+            self.rotation,
+            (self.anchor_x, self.anchor_y),
+        )
+    }
+
+    fn set_rotation(&mut self, angle_degrees: f64) {
+        self.set_rotation(angle_degrees % 360.0);
+    }
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        self.set_x(x);
+        self.set_y(y);
+    }
+
+    fn set_scale(&mut self, sx: f64, sy: f64) {
+        self.set_scale_x(sx);
+        self.set_scale_y(sy);
+    }
+
+    fn set_skew(&mut self, skew_x: f64, skew_y: f64) {
+        self.set_skew_x(skew_x);
+        self.set_skew_y(skew_y);
+    }
+
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64) {
+        self.set_anchor_x(anchor_x);
+        self.set_anchor_y(anchor_y);
+    }
+
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        let (base, rotation) =
+            decompose_anchored(transform, self.width, self.height, (self.anchor_x, self.anchor_y));
+        self.set_x(base[4]);
+        self.set_y(base[5]);
+        self.set_scale(base[0], base[3]);
+        self.set_skew(base[1], base[2]);
+        self.set_rotation(rotation);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (self.scale_x, self.scale_y)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (self.anchor_x, self.anchor_y)
+    }
+}
+
+impl Animatable for Ellipse {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x", AnimationValue::Float(v)) => dirty_properties.x = Some(v),
+                ("y", AnimationValue::Float(v)) => dirty_properties.y = Some(v),
+                ("width", AnimationValue::Float(v)) => dirty_properties.width = Some(v),
+                ("height", AnimationValue::Float(v)) => dirty_properties.height = Some(v),
+                ("fill", AnimationValue::String(v)) => dirty_properties.fill = Some(v),
+                ("stroke", AnimationValue::String(v)) => dirty_properties.stroke = Some(v),
+                ("stroke_width", AnimationValue::Float(v)) => {
+                    dirty_properties.stroke_width = Some(v)
+                }
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                ("scale_x", AnimationValue::Float(v)) => dirty_properties.scale_x = Some(v),
+                ("scale_y", AnimationValue::Float(v)) => dirty_properties.scale_y = Some(v),
+                ("skew_x", AnimationValue::Float(v)) => dirty_properties.skew_x = Some(v),
+                ("skew_y", AnimationValue::Float(v)) => dirty_properties.skew_y = Some(v),
+                ("rotation", AnimationValue::Float(v)) => dirty_properties.rotation = Some(v),
+                ("anchor_x", AnimationValue::Float(v)) => dirty_properties.anchor_x = Some(v),
+                ("anchor_y", AnimationValue::Float(v)) => dirty_properties.anchor_y = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
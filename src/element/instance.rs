@@ -0,0 +1,324 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{default_anchor, Dirty, Eventable, DEFAULT_ANCHOR, PointerEvents, PropertySchema, ObjectId, ObjectMeta, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, bounding_box::BoundingBox, helper::{distance_to_polyline, point_in_polygon}, math::{anchored_transform, decompose_anchored, convert_1x6_to_3x3, transform_point}, renderer::Renderer
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// 组件实例，只保存所引用的主组件（component 为 true 的 Frame）和自身的变换；
+// 内容由 SceneManager 按主组件当前的子对象绘制，修改主组件后所有实例随之更新。
+// 宽高跟随主组件，fill_overrides 按主组件中的对象 id 覆盖填充色
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Instance {
+    id: ObjectId,
+    dirty: bool,
+    #[dirty_setter]
+    pub component: String,
+    #[dirty_setter]
+    pub x: f64,
+    #[dirty_setter]
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[dirty_setter]
+    #[serde(default)]
+    pub fill_overrides: BTreeMap<String, String>,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    #[dirty_setter]
+    pub scale_x: f64,
+    #[dirty_setter]
+    pub scale_y: f64,
+    #[dirty_setter]
+    pub skew_x: f64,
+    #[dirty_setter]
+    pub skew_y: f64,
+    #[dirty_setter]
+    pub rotation: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_x: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_y: f64,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl Instance {
+    pub fn new(component: impl Into<String>, x: f64, y: f64) -> Self {
+        Instance {
+            id: ObjectId::new(),
+            dirty: true,
+            component: component.into(),
+            x,
+            y,
+            width: 0.0,
+            height: 0.0,
+            fill_overrides: BTreeMap::new(),
+            pointer_events: PointerEvents::default(),
+            scale_x: 1.0,
+            scale_y: 1.0,
+            skew_x: 0.0,
+            skew_y: 0.0,
+            rotation: 0.0,
+            anchor_x: DEFAULT_ANCHOR,
+            anchor_y: DEFAULT_ANCHOR,
+            meta: ObjectMeta::default(),
+            app: None,
+        }
+    }
+
+    fn corners(&self) -> [(f64, f64); 4] {
+        let transform = self.calc_transform();
+        BoundingBox::new(0.0, 0.0, self.width, self.height)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y))
+    }
+}
+
+impl Dirty for Instance {
+    fn set_dirty(&mut self) {
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Instance {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    // 内容由 SceneManager 展开主组件绘制，实例本身没有图形
+    fn render(&self, _renderer: &dyn Renderer) {}
+
+    // 整个实例作为一个对象拾取，不会点中主组件中的对象
+    fn render_hit(&self, renderer: &dyn Renderer, _tolerance: f64) {
+        if let [a, b, c, d, e, f] = self.calc_transform().as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+        renderer.draw_rectangle(0.0, 0.0, self.width, self.height, "black");
+    }
+
+    fn hit_test(&self, x: f64, y: f64, tolerance: f64) -> bool {
+        let corners = self.corners();
+        point_in_polygon((x, y), &corners) || distance_to_polyline((x, y), &corners, true) <= tolerance
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_points(&self.corners())
+    }
+
+    fn outline(&self) -> Vec<Vec<(f64, f64)>> {
+        vec![self.corners().to_vec()]
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec![self.component.clone()]
+    }
+
+    // 宽高随主组件变化，属于派生数据，不记录历史
+    fn resolve_dependencies(&mut self, bounds: &[Option<BoundingBox>]) {
+        if let [Some(bounds)] = bounds {
+            if (bounds.width, bounds.height) != (self.width, self.height) {
+                self.width = bounds.width;
+                self.height = bounds.height;
+                self.set_dirty();
+            }
+        }
+    }
+
+    fn instance_of(&self) -> Option<&str> {
+        Some(&self.component)
+    }
+
+    fn fill_override(&self, id: &str) -> Option<&str> {
+        self.fill_overrides.get(id).map(String::as_str)
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "instance"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Instance {}
+
+impl Transformable for Instance {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(
+            self.scale_x,
+            self.skew_x,
+            self.skew_y,
+            self.scale_y,
+            self.x,
+            self.y,
+        )
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        let transform = convert_1x6_to_3x3(self.get_transform());
+        let center = transform * na::Vector3::new(self.width / 2.0, self.height / 2.0, 1.0);
+        (center.x, center.y)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        anchored_transform(
+            self.get_transform(),
+            self.width,
+            self.height,
+            self.rotation,
+            (self.anchor_x, self.anchor_y),
+        )
+    }
+
+    fn set_rotation(&mut self, angle_degrees: f64) {
+        self.set_rotation(angle_degrees % 360.0);
+    }
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        self.set_x(x);
+        self.set_y(y);
+    }
+
+    fn set_scale(&mut self, sx: f64, sy: f64) {
+        self.set_scale_x(sx);
+        self.set_scale_y(sy);
+    }
+
+    fn set_skew(&mut self, skew_x: f64, skew_y: f64) {
+        self.set_skew_x(skew_x);
+        self.set_skew_y(skew_y);
+    }
+
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64) {
+        self.set_anchor_x(anchor_x);
+        self.set_anchor_y(anchor_y);
+    }
+
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        let (base, rotation) =
+            decompose_anchored(transform, self.width, self.height, (self.anchor_x, self.anchor_y));
+        self.set_x(base[4]);
+        self.set_y(base[5]);
+        self.set_scale(base[0], base[3]);
+        self.set_skew(base[1], base[2]);
+        self.set_rotation(rotation);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (self.scale_x, self.scale_y)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (self.anchor_x, self.anchor_y)
+    }
+}
+
+impl Animatable for Instance {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x", AnimationValue::Float(v)) => dirty_properties.x = Some(v),
+                ("y", AnimationValue::Float(v)) => dirty_properties.y = Some(v),
+                ("scale_x", AnimationValue::Float(v)) => dirty_properties.scale_x = Some(v),
+                ("scale_y", AnimationValue::Float(v)) => dirty_properties.scale_y = Some(v),
+                ("skew_x", AnimationValue::Float(v)) => dirty_properties.skew_x = Some(v),
+                ("skew_y", AnimationValue::Float(v)) => dirty_properties.skew_y = Some(v),
+                ("rotation", AnimationValue::Float(v)) => dirty_properties.rotation = Some(v),
+                ("anchor_x", AnimationValue::Float(v)) => dirty_properties.anchor_x = Some(v),
+                ("anchor_y", AnimationValue::Float(v)) => dirty_properties.anchor_y = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use super::stroke::stroke_bounds;
+use super::{Dirty, Eventable, ObjectId, ObjectMeta, PointerEvents, PropertySchema, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, bounding_box::BoundingBox, helper::distance_to_segment, renderer::{LineCap, Renderer, Filter}
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// 两个端点之间的直线段，端点为世界坐标
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Line {
+    id: ObjectId,
+    dirty: bool,
+    #[dirty_setter]
+    pub x1: f64,
+    #[dirty_setter]
+    pub y1: f64,
+    #[dirty_setter]
+    pub x2: f64,
+    #[dirty_setter]
+    pub y2: f64,
+    #[dirty_setter(color)]
+    pub stroke: String,
+    #[dirty_setter]
+    pub stroke_width: f64,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    // 元素级的 CSS 滤镜效果，如模糊、投影
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl Line {
+    pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        Line {
+            id: ObjectId::new(),
+            dirty: true,
+            x1,
+            y1,
+            x2,
+            y2,
+            stroke: "black".to_string(),
+            stroke_width: 2.0,
+            opacity: 1.0,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
+            meta: ObjectMeta::default(),
+            app: None,
+        }
+    }
+
+    fn draw(&self, renderer: &dyn Renderer, line_width: f64) {
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        renderer.set_stroke_style(&self.stroke);
+        renderer.set_line_width(line_width);
+        renderer.set_line_cap(LineCap::Round);
+        renderer.begin_path();
+        renderer.move_to(self.x1, self.y1);
+        renderer.line_to(self.x2, self.y2);
+        renderer.stroke();
+    }
+}
+
+impl Dirty for Line {
+    fn set_dirty(&mut self) {
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Line {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        self.draw(renderer, self.stroke_width);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, tolerance: f64) {
+        // 只有描边，pointer_events 为 fill 时不会被点中
+        if !self.pointer_events.stroke() {
+            return;
+        }
+        self.draw(renderer, self.stroke_width + 2.0 * tolerance);
+    }
+
+    fn hit_test(&self, x: f64, y: f64, tolerance: f64) -> bool {
+        self.pointer_events.stroke()
+            && distance_to_segment((x, y), (self.x1, self.y1), (self.x2, self.y2))
+                <= self.stroke_width / 2.0 + tolerance
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x1, self.y1)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        stroke_bounds(&[(self.x1, self.y1), (self.x2, self.y2)], self.stroke_width)
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "line"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Line {}
+
+// 线段只支持平移，移动时两个端点一起移动
+impl Transformable for Line {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        self.get_transform()
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        ((self.x1 + self.x2) / 2.0, (self.y1 + self.y2) / 2.0)
+    }
+
+    fn set_rotation(&mut self, _angle_degrees: f64) {}
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        let (dx, dy) = (x - self.x1, y - self.y1);
+        self.set_multiple(DirtyUpdates {
+            x1: Some(x),
+            y1: Some(y),
+            x2: Some(self.x2 + dx),
+            y2: Some(self.y2 + dy),
+            ..Default::default()
+        });
+    }
+
+    fn set_scale(&mut self, _sx: f64, _sy: f64) {}
+
+    fn set_skew(&mut self, _skew_x: f64, _skew_y: f64) {}
+
+    fn set_anchor(&mut self, _anchor_x: f64, _anchor_y: f64) {}
+
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        Transformable::set_position(self, transform[4], transform[5]);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        0.0
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x1, self.y1)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (1.0, 1.0)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+}
+
+impl Animatable for Line {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x1", AnimationValue::Float(v)) => dirty_properties.x1 = Some(v),
+                ("y1", AnimationValue::Float(v)) => dirty_properties.y1 = Some(v),
+                ("x2", AnimationValue::Float(v)) => dirty_properties.x2 = Some(v),
+                ("y2", AnimationValue::Float(v)) => dirty_properties.y2 = Some(v),
+                ("stroke", AnimationValue::String(v)) => dirty_properties.stroke = Some(v),
+                ("stroke_width", AnimationValue::Float(v)) => {
+                    dirty_properties.stroke_width = Some(v)
+                }
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
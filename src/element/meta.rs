@@ -0,0 +1,48 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// 历史记录和同步的对象修改中写入 meta.data 的字段，值为 { 键: 值 }，null 表示删除该键
+pub const METADATA_KEY: &str = "metadata";
+
+// 应用附加在元素上的领域数据，随文档一起序列化，不参与渲染
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObjectMeta {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub data: Map<String, Value>,
+}
+
+impl ObjectMeta {
+    pub fn add_tag(&mut self, tag: impl Into<String>) -> bool {
+        self.tags.insert(tag.into())
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        self.tags.remove(tag)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.data.get(key)
+    }
+
+    // 写入 null 等同于删除该键
+    pub fn set(&mut self, key: impl Into<String>, value: Value) {
+        let key = key.into();
+        if value.is_null() {
+            self.data.remove(&key);
+        } else {
+            self.data.insert(key, value);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.data.is_empty()
+    }
+}
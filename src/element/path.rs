@@ -0,0 +1,668 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use super::stroke::{distance_to_cubic, stroke_bounds, CURVE_SAMPLES};
+use super::{Dirty, Eventable, ObjectId, ObjectMeta, PointerEvents, PropertySchema, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, boolean_ops::contains_point, bounding_box::BoundingBox, helper::{distance_to_polyline, sample_cubic}, renderer::{CachedPath, Filter, LineCap, LineJoin, Renderer}
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// 压力很小时线宽的下限，避免笔画断开
+const MIN_PRESSURE: f64 = 0.1;
+
+fn full_pressure() -> f64 {
+    1.0
+}
+
+fn smooth_by_default() -> bool {
+    true
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PathPoint {
+    pub x: f64,
+    pub y: f64,
+    // 0..1，线宽按该比例缩放；没有压力信息的输入为 1
+    #[serde(default = "full_pressure")]
+    pub pressure: f64,
+    // 编辑时拖出的控制柄，为相对该点的偏移；为 None 时按相邻点自动计算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handle_in: Option<(f64, f64)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handle_out: Option<(f64, f64)>,
+    // 尖角：两侧控制柄各自独立，没有控制柄的一侧直接连到该点
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub corner: bool,
+}
+
+impl PathPoint {
+    pub fn new(x: f64, y: f64, pressure: f64) -> Self {
+        Self {
+            x,
+            y,
+            pressure,
+            handle_in: None,
+            handle_out: None,
+            corner: false,
+        }
+    }
+}
+
+// 自由绘制的笔画，points 为相对 (x, y) 的采样点，绘制时用 Catmull-Rom 曲线平滑连接
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Path {
+    id: ObjectId,
+    dirty: bool,
+    #[dirty_setter]
+    pub x: f64,
+    #[dirty_setter]
+    pub y: f64,
+    #[dirty_setter]
+    pub points: Vec<PathPoint>,
+    #[dirty_setter(color)]
+    pub stroke: String,
+    #[dirty_setter]
+    pub stroke_width: f64,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    // 元素级的 CSS 滤镜效果，如模糊、投影
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    // 为 false 时相邻点之间直接连线
+    #[dirty_setter]
+    #[serde(default = "smooth_by_default")]
+    pub smooth: bool,
+    // 为 true 时首尾相连成封闭图形，可以填充
+    #[dirty_setter]
+    #[serde(default)]
+    pub closed: bool,
+    // 封闭图形的填充色，为空时不填充
+    #[dirty_setter(color)]
+    #[serde(default)]
+    pub fill: String,
+    // 封闭图形的其他轮廓（如布尔运算产生的洞和分离的部分），坐标与 points 相同，按奇偶规则填充
+    #[dirty_setter]
+    #[serde(default)]
+    pub contours: Vec<Vec<PathPoint>>,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    // 由 points 生成的笔画路径及其压力，几何变化时在 set_dirty 中清空
+    #[serde(skip)]
+    strokes: RefCell<Option<Vec<(f64, CachedPath)>>>,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl Path {
+    pub fn new(x: f64, y: f64, points: Vec<PathPoint>) -> Self {
+        Path {
+            id: ObjectId::new(),
+            dirty: true,
+            x,
+            y,
+            points,
+            stroke: "black".to_string(),
+            stroke_width: 2.0,
+            opacity: 1.0,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
+            smooth: true,
+            closed: false,
+            fill: String::new(),
+            contours: Vec::new(),
+            meta: ObjectMeta::default(),
+            strokes: RefCell::default(),
+            app: None,
+        }
+    }
+
+    fn width_at(&self, pressure: f64) -> f64 {
+        self.stroke_width * pressure.clamp(MIN_PRESSURE, 1.0)
+    }
+
+    fn max_width(&self) -> f64 {
+        self.points
+            .iter()
+            .map(|point| self.width_at(point.pressure))
+            .fold(0.0, f64::max)
+    }
+
+    fn uniform_pressure(&self) -> bool {
+        self.points
+            .windows(2)
+            .all(|pair| pair[0].pressure == pair[1].pressure)
+    }
+
+    // 世界坐标下相邻两点之间的三次贝塞尔曲线 [起点, 控制点1, 控制点2, 终点]
+    pub fn segments(&self) -> Vec<[(f64, f64); 4]> {
+        anchor_segments(&self.points, (self.x, self.y), self.smooth)
+    }
+
+    // points 实际绘制出的曲线，封闭时包括连回起点的一段；第 i 段从 points[i] 出发
+    pub fn edit_segments(&self) -> Vec<[(f64, f64); 4]> {
+        if self.closed {
+            self.ring_segments(&self.points)
+        } else {
+            self.segments()
+        }
+    }
+
+    // 世界坐标下一条轮廓的贝塞尔曲线，末尾连回起点
+    fn ring_segments(&self, ring: &[PathPoint]) -> Vec<[(f64, f64); 4]> {
+        let mut ring = ring.to_vec();
+        ring.extend(ring.first().copied());
+        anchor_segments(&ring, (self.x, self.y), self.smooth)
+    }
+
+    fn rings(&self) -> impl Iterator<Item = &Vec<PathPoint>> {
+        std::iter::once(&self.points).chain(self.contours.iter())
+    }
+
+    // 封闭图形的所有轮廓合成一条路径，fill、stroke 决定绘制哪一部分
+    fn draw_closed(&self, renderer: &dyn Renderer, padding: f64, fill: bool, stroke: bool) {
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        let mut strokes = self.strokes.borrow_mut();
+        let Some((_, path)) = strokes.get_or_insert_with(|| self.build_strokes()).first() else {
+            return;
+        };
+        if fill && !self.fill.is_empty() {
+            renderer.set_fill_style(&self.fill);
+            renderer.fill_path(path);
+        }
+        if stroke && self.stroke_width + padding > 0.0 {
+            renderer.set_stroke_style(&self.stroke);
+            renderer.set_line_join(LineJoin::Round);
+            renderer.set_line_width(self.stroke_width + padding);
+            renderer.stroke_path(path);
+        }
+    }
+
+    // padding 为在笔画线宽之外额外加上的宽度，拾取时用于放宽命中范围
+    fn draw(&self, renderer: &dyn Renderer, padding: f64) {
+        let Some(first) = self.points.first() else {
+            return;
+        };
+        if self.closed {
+            return self.draw_closed(renderer, padding, true, true);
+        }
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        renderer.set_stroke_style(&self.stroke);
+        renderer.set_fill_style(&self.stroke);
+        renderer.set_line_cap(LineCap::Round);
+        renderer.set_line_join(LineJoin::Round);
+
+        if self.points.len() == 1 {
+            let radius = (self.width_at(first.pressure) + padding) / 2.0;
+            renderer.begin_path();
+            renderer.arc(self.x + first.x, self.y + first.y, radius, 0.0, TAU);
+            renderer.fill();
+            return;
+        }
+
+        let mut strokes = self.strokes.borrow_mut();
+        for (pressure, path) in strokes.get_or_insert_with(|| self.build_strokes()).iter() {
+            renderer.set_line_width(self.width_at(*pressure) + padding);
+            renderer.stroke_path(path);
+        }
+    }
+
+    // 压力一致时整条笔画为一条路径；线宽随压力变化时逐段生成，圆头让相邻段无缝衔接
+    fn build_strokes(&self) -> Vec<(f64, CachedPath)> {
+        if self.closed {
+            let mut path = CachedPath::new();
+            for ring in self.rings() {
+                let segments = self.ring_segments(ring);
+                let Some(first) = segments.first() else {
+                    continue;
+                };
+                path.move_to(first[0].0, first[0].1);
+                for [_, c1, c2, end] in &segments {
+                    path.bezier_curve_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1);
+                }
+                path.close_path();
+            }
+            return if path.is_empty() { Vec::new() } else { vec![(1.0, path)] };
+        }
+        let segments = self.segments();
+        let Some(first) = segments.first() else {
+            return Vec::new();
+        };
+        if self.uniform_pressure() {
+            let mut path = CachedPath::new();
+            path.move_to(first[0].0, first[0].1);
+            for [_, c1, c2, end] in &segments {
+                path.bezier_curve_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1);
+            }
+            return vec![(self.points[0].pressure, path)];
+        }
+
+        segments
+            .iter()
+            .zip(self.points.windows(2))
+            .map(|([start, c1, c2, end], pair)| {
+                let mut path = CachedPath::new();
+                path.move_to(start.0, start.1);
+                path.bezier_curve_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1);
+                ((pair[0].pressure + pair[1].pressure) / 2.0, path)
+            })
+            .collect()
+    }
+}
+
+fn bezier_segments(points: &[(f64, f64)], smooth: bool) -> Vec<[(f64, f64); 4]> {
+    if smooth {
+        return catmull_rom(points);
+    }
+    points
+        .windows(2)
+        .map(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            let third = ((b.0 - a.0) / 3.0, (b.1 - a.1) / 3.0);
+            [a, (a.0 + third.0, a.1 + third.1), (b.0 - third.0, b.1 - third.1), b]
+        })
+        .collect()
+}
+
+// 在自动计算的曲线上应用各点的控制柄和尖角设置，坐标加上 origin
+fn anchor_segments(points: &[PathPoint], origin: (f64, f64), smooth: bool) -> Vec<[(f64, f64); 4]> {
+    let positions: Vec<(f64, f64)> = points
+        .iter()
+        .map(|point| (origin.0 + point.x, origin.1 + point.y))
+        .collect();
+    bezier_segments(&positions, smooth)
+        .into_iter()
+        .zip(points.windows(2))
+        .map(|(mut segment, pair)| {
+            segment[1] = control_point(segment[0], segment[1], pair[0].handle_out, pair[0].corner);
+            segment[2] = control_point(segment[3], segment[2], pair[1].handle_in, pair[1].corner);
+            segment
+        })
+        .collect()
+}
+
+fn control_point(anchor: (f64, f64), auto: (f64, f64), handle: Option<(f64, f64)>, corner: bool) -> (f64, f64) {
+    match handle {
+        Some((dx, dy)) => (anchor.0 + dx, anchor.1 + dy),
+        None if corner => anchor,
+        None => auto,
+    }
+}
+
+fn bezier_point(segment: &[(f64, f64); 4], t: f64) -> (f64, f64) {
+    let u = 1.0 - t;
+    let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    segment
+        .iter()
+        .zip(weights)
+        .fold((0.0, 0.0), |(x, y), (point, weight)| (x + point.0 * weight, y + point.1 * weight))
+}
+
+// 沿绘制出的曲线重新采样，相邻采样点的间距不超过 spacing，压力按线性插值
+fn sample_curve(points: &[PathPoint], smooth: bool, spacing: f64) -> Vec<PathPoint> {
+    let mut samples = Vec::new();
+    for (segment, pair) in anchor_segments(points, (0.0, 0.0), smooth).iter().zip(points.windows(2)) {
+        // 控制多边形的长度不小于曲线长度
+        let length: f64 = segment
+            .windows(2)
+            .map(|edge| ((edge[1].0 - edge[0].0).powi(2) + (edge[1].1 - edge[0].1).powi(2)).sqrt())
+            .sum();
+        let steps = (length / spacing).ceil().max(1.0) as usize;
+        for step in 0..steps {
+            let t = step as f64 / steps as f64;
+            let (x, y) = bezier_point(segment, t);
+            let pressure = pair[0].pressure + (pair[1].pressure - pair[0].pressure) * t;
+            samples.push(PathPoint::new(x, y, pressure));
+        }
+    }
+    samples.extend(points.last().copied());
+    samples
+}
+
+// 擦除与线段 from -> to 距离不超过 radius 的部分（坐标与 points 相同），返回剩下的各段；
+// 没有擦到时返回 None，全部擦除时返回空列表。只剩一个采样点的碎片直接丢弃
+pub fn erase_path(
+    points: &[PathPoint],
+    smooth: bool,
+    from: (f64, f64),
+    to: (f64, f64),
+    radius: f64,
+) -> Option<Vec<Vec<PathPoint>>> {
+    if radius <= 0.0 {
+        return None;
+    }
+    let (from, to) = (PathPoint::new(from.0, from.1, 1.0), PathPoint::new(to.0, to.1, 1.0));
+    let samples = sample_curve(points, smooth, radius / 2.0);
+    let erased: Vec<bool> = samples
+        .iter()
+        .map(|sample| distance_to_segment(sample, &from, &to) <= radius)
+        .collect();
+    if !erased.iter().any(|erased| *erased) {
+        return None;
+    }
+
+    let pieces = samples
+        .iter()
+        .zip(erased)
+        .collect::<Vec<_>>()
+        .split(|(_, erased)| *erased)
+        .filter(|run| run.len() > 1)
+        .map(|run| {
+            let run: Vec<PathPoint> = run.iter().map(|(sample, _)| **sample).collect();
+            // 重新采样后的点很密，简化到与采样间距相当的精度
+            simplify_path(&run, radius / 4.0)
+        })
+        .collect();
+    Some(pieces)
+}
+
+// 均匀 Catmull-Rom 样条转换为贝塞尔曲线，曲线经过所有点；首尾点重复作为端点外的控制点
+pub fn catmull_rom(points: &[(f64, f64)]) -> Vec<[(f64, f64); 4]> {
+    let count = points.len();
+    (0..count.saturating_sub(1))
+        .map(|i| {
+            let p0 = points[i.saturating_sub(1)];
+            let p1 = points[i];
+            let p2 = points[i + 1];
+            let p3 = points[(i + 2).min(count - 1)];
+            [
+                p1,
+                (p1.0 + (p2.0 - p0.0) / 6.0, p1.1 + (p2.1 - p0.1) / 6.0),
+                (p2.0 - (p3.0 - p1.0) / 6.0, p2.1 - (p3.1 - p1.1) / 6.0),
+                p2,
+            ]
+        })
+        .collect()
+}
+
+// Ramer–Douglas–Peucker 简化，去掉与保留折线距离小于 tolerance 的点；首尾点始终保留
+pub fn simplify_path(points: &[PathPoint], tolerance: f64) -> Vec<PathPoint> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    // 用显式栈代替递归，长笔画不会爆栈
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        let (farthest, distance) = (start + 1..end)
+            .map(|i| (i, distance_to_segment(&points[i], &points[start], &points[end])))
+            .fold((start, 0.0), |best, current| if current.1 > best.1 { current } else { best });
+        if distance > tolerance {
+            keep[farthest] = true;
+            ranges.push((start, farthest));
+            ranges.push((farthest, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(*point))
+        .collect()
+}
+
+fn distance_to_segment(point: &PathPoint, start: &PathPoint, end: &PathPoint) -> f64 {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    let (px, py) = (start.x + dx * t, start.y + dy * t);
+    ((point.x - px).powi(2) + (point.y - py).powi(2)).sqrt()
+}
+
+impl Dirty for Path {
+    fn set_dirty(&mut self) {
+        self.strokes.get_mut().take();
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Path {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        self.draw(renderer, 0.0);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, tolerance: f64) {
+        if self.closed {
+            let area = self.pointer_events;
+            return self.draw_closed(renderer, 2.0 * tolerance, area.fill(), area.stroke());
+        }
+        // 只有描边，pointer_events 为 fill 时不会被点中
+        if !self.pointer_events.stroke() {
+            return;
+        }
+        self.draw(renderer, 2.0 * tolerance);
+    }
+
+    fn hit_test(&self, x: f64, y: f64, tolerance: f64) -> bool {
+        if self.closed {
+            let outline = self.outline();
+            let reach = self.stroke_width / 2.0 + tolerance;
+            return (self.pointer_events.stroke()
+                && outline.iter().any(|ring| distance_to_polyline((x, y), ring, true) <= reach))
+                || (self.pointer_events.fill() && !self.fill.is_empty() && contains_point(&outline, (x, y)));
+        }
+        let Some(first) = self.points.first().filter(|_| self.pointer_events.stroke()) else {
+            return false;
+        };
+        let reach = self.max_width() / 2.0 + tolerance;
+        let segments = self.segments();
+        if segments.is_empty() {
+            return (x - self.x - first.x).hypot(y - self.y - first.y) <= reach;
+        }
+        segments.into_iter().any(|segment| {
+            distance_to_cubic((x, y), segment) <= reach
+        })
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let Some(first) = self.points.first() else {
+            return BoundingBox::new(self.x, self.y, 0.0, 0.0);
+        };
+        let mut corners = vec![(self.x + first.x, self.y + first.y)];
+        if self.closed {
+            for ring in self.rings() {
+                corners.extend(self.ring_segments(ring).iter().flat_map(|segment| segment.iter().copied()));
+            }
+        } else {
+            corners.extend(self.segments().iter().flat_map(|segment| segment.iter().copied()));
+        }
+        stroke_bounds(&corners, self.max_width())
+    }
+
+    // 按封闭图形处理，未封闭的笔画视为首尾相连
+    fn outline(&self) -> Vec<Vec<(f64, f64)>> {
+        let samples = if self.smooth { CURVE_SAMPLES } else { 1 };
+        self.rings()
+            .map(|ring| {
+                self.ring_segments(ring)
+                    .into_iter()
+                    .flat_map(|segment| {
+                        let mut points = sample_cubic(segment, samples);
+                        points.pop();
+                        points
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|ring| ring.len() >= 3)
+            .collect()
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "path"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Path {}
+
+// 笔画只支持平移，旋转和缩放需要先把点变换后再写回
+impl Transformable for Path {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(1.0, 0.0, 0.0, 1.0, self.x, self.y)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        self.get_transform()
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        self.bounding_box().center()
+    }
+
+    fn set_rotation(&mut self, _angle_degrees: f64) {}
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        self.set_x(x);
+        self.set_y(y);
+    }
+
+    fn set_scale(&mut self, _sx: f64, _sy: f64) {}
+
+    fn set_skew(&mut self, _skew_x: f64, _skew_y: f64) {}
+
+    fn set_anchor(&mut self, _anchor_x: f64, _anchor_y: f64) {}
+
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        self.set_x(transform[4]);
+        self.set_y(transform[5]);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        0.0
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (1.0, 1.0)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (0.0, 0.0)
+    }
+}
+
+impl Animatable for Path {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x", AnimationValue::Float(v)) => dirty_properties.x = Some(v),
+                ("y", AnimationValue::Float(v)) => dirty_properties.y = Some(v),
+                ("stroke", AnimationValue::String(v)) => dirty_properties.stroke = Some(v),
+                ("fill", AnimationValue::String(v)) => dirty_properties.fill = Some(v),
+                ("stroke_width", AnimationValue::Float(v)) => {
+                    dirty_properties.stroke_width = Some(v)
+                }
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
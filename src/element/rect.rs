@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
-use super::{Dirty, Eventable, ObjectId, Renderable, Transformable};
+use super::{default_anchor, stroke_scale, Dirty, Eventable, DEFAULT_ANCHOR, PointerEvents, PropertySchema, ObjectId, ObjectMeta, Renderable, Transformable};
 use crate::{
-    animation::{Animatable, AnimationError, AnimationValue}, app::App, helper::{convert_1x6_to_3x3, convert_3x3_to_1x6, get_rotation_matrix}, render_control::{get_render_control, UpdateBody, UpdateMessage, UpdateType}, renderer::Renderer
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, batch::{BatchPass, BatchStyle}, bounding_box::BoundingBox, helper::{distance_to_polyline, point_in_polygon}, math::{anchored_transform, decompose_anchored, convert_1x6_to_3x3, transform_point}, render_control::{UpdateBody, UpdateMessage, UpdateType}, renderer::{Filter, Renderer}
 };
 use dirty_setter::DirtySetter;
 use nalgebra as na;
@@ -24,6 +24,8 @@ pub struct RectOptions {
     pub skew_x: f64,
     pub skew_y: f64,
     pub rotation: f64,
+    pub anchor_x: f64,
+    pub anchor_y: f64,
 }
 
 impl Default for RectOptions {
@@ -42,6 +44,8 @@ impl Default for RectOptions {
             skew_x: 0.0,
             skew_y: 0.0,
             rotation: 0.0,
+            anchor_x: DEFAULT_ANCHOR,
+            anchor_y: DEFAULT_ANCHOR,
         }
     }
 }
@@ -59,14 +63,21 @@ pub struct Rect {
     pub width: f64,
     #[dirty_setter]
     pub height: f64,
-    #[dirty_setter]
+    #[dirty_setter(color)]
     pub fill: String,
-    #[dirty_setter]
+    #[dirty_setter(color)]
     pub stroke: String,
     #[dirty_setter]
     pub stroke_width: f64,
-    #[dirty_setter]
+    #[dirty_setter(clamp(0.0, 1.0))]
     pub opacity: f64,
+    // 元素级的 CSS 滤镜效果，如模糊、投影
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
     #[dirty_setter]
     pub scale_x: f64,
     #[dirty_setter]
@@ -77,6 +88,16 @@ pub struct Rect {
     pub skew_y: f64,
     #[dirty_setter]
     pub rotation: f64,
+    // 旋转和缩放的锚点，按宽高归一化，(0.5, 0.5) 为几何中心
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_x: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_y: f64,
+
+    #[serde(default)]
+    meta: ObjectMeta,
 
     #[serde(skip)]
     app: Option<App>,
@@ -84,9 +105,8 @@ pub struct Rect {
 
 impl Rect {
     pub fn new(options: RectOptions) -> Self {
-        let id = ObjectId::new();
         Rect {
-            id,
+            id: ObjectId::new(),
             x: options.x,
             y: options.y,
             width: options.width,
@@ -95,11 +115,16 @@ impl Rect {
             stroke: options.stroke,
             stroke_width: options.stroke_width,
             opacity: options.opacity,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
             scale_x: options.scale_x,
             scale_y: options.scale_y,
             skew_x: options.skew_x,
             skew_y: options.skew_y,
             rotation: options.rotation,
+            anchor_x: options.anchor_x,
+            anchor_y: options.anchor_y,
+            meta: ObjectMeta::default(),
             dirty: true,
             app: None,
         }
@@ -112,6 +137,9 @@ impl Rect {
             renderer.transform(*a, *b, *c, *d, *e, *f);
         }
         renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
         renderer.draw_rectangle(0.0, 0.0, self.width, self.height, fill);
         let offset = self.stroke_width / 2.0;
         renderer.set_stroke_style(stroke);
@@ -123,6 +151,15 @@ impl Rect {
             self.height - self.stroke_width,
         );
     }
+
+    // 描边中线在世界坐标下的四个角
+    fn stroke_outline(&self) -> [(f64, f64); 4] {
+        let offset = self.stroke_width / 2.0;
+        let transform = self.calc_transform();
+        BoundingBox::new(offset, offset, self.width - self.stroke_width, self.height - self.stroke_width)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y))
+    }
 }
 
 impl Dirty for Rect {
@@ -153,6 +190,8 @@ struct RectUpdateBoadyData {
     skew_x: Option<f64>,
     skew_y: Option<f64>,
     rotation: Option<f64>,
+    anchor_x: Option<f64>,
+    anchor_y: Option<f64>,
 }
 
 impl Renderable for Rect {
@@ -160,6 +199,10 @@ impl Renderable for Rect {
         return &self.id;
     }
 
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
     fn update(&mut self, data: Value) {
         self.update(data);
     }
@@ -168,10 +211,61 @@ impl Renderable for Rect {
         self.render_fn(renderer, &self.fill, &self.stroke)
     }
 
+    fn render_hit(&self, renderer: &dyn Renderer, tolerance: f64) {
+        let area = self.pointer_events;
+        if area == PointerEvents::Auto {
+            return self.render(renderer);
+        }
+        if let [a, b, c, d, e, f] = self.calc_transform().as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+        if area.fill() {
+            renderer.draw_rectangle(0.0, 0.0, self.width, self.height, &self.fill);
+        }
+        if area.stroke() {
+            let offset = self.stroke_width / 2.0;
+            let scale = stroke_scale(self.scale_x, self.scale_y);
+            renderer.set_line_width(self.stroke_width + 2.0 * tolerance / scale);
+            renderer.stroke_rect(
+                offset,
+                offset,
+                self.width - self.stroke_width,
+                self.height - self.stroke_width,
+            );
+        }
+    }
+
+    fn hit_test(&self, x: f64, y: f64, tolerance: f64) -> bool {
+        let outline = self.stroke_outline();
+        let reach = self.stroke_width * stroke_scale(self.scale_x, self.scale_y) / 2.0 + tolerance;
+        (self.pointer_events.stroke() && distance_to_polyline((x, y), &outline, true) <= reach)
+            || (self.pointer_events.fill() && point_in_polygon((x, y), &outline))
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
     fn position(&self) -> (f64, f64) {
         (self.x, self.y)
     }
 
+    fn bounding_box(&self) -> BoundingBox {
+        let transform = self.calc_transform();
+        let corners = BoundingBox::new(0.0, 0.0, self.width, self.height)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y));
+        BoundingBox::from_points(&corners)
+    }
+
+    fn outline(&self) -> Vec<Vec<(f64, f64)>> {
+        let transform = self.calc_transform();
+        let corners = BoundingBox::new(0.0, 0.0, self.width, self.height)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y));
+        vec![corners.to_vec()]
+    }
+
     fn attach(&mut self, app: &App) {
         self.app = Some(app.clone());
     }
@@ -184,9 +278,62 @@ impl Renderable for Rect {
         "rect"
     }
 
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    // 只有旋转和平移时线宽不会变形，才能和其他矩形共用一次 stroke
+    fn batch_style(&self) -> Option<BatchStyle> {
+        let rigid = self.scale_x == 1.0
+            && self.scale_y == 1.0
+            && self.skew_x == 0.0
+            && self.skew_y == 0.0;
+        // 带滤镜的矩形单独绘制
+        (rigid && self.filter.is_empty()).then(|| BatchStyle {
+            fill: self.fill.clone(),
+            stroke: self.stroke.clone(),
+            stroke_width: self.stroke_width,
+            opacity: self.opacity,
+        })
+    }
+
+    fn append_batch_path(&self, renderer: &dyn Renderer, pass: BatchPass) {
+        let local = match pass {
+            BatchPass::Fill => BoundingBox::new(0.0, 0.0, self.width, self.height),
+            BatchPass::Stroke => {
+                let offset = self.stroke_width / 2.0;
+                BoundingBox::new(
+                    offset,
+                    offset,
+                    self.width - self.stroke_width,
+                    self.height - self.stroke_width,
+                )
+            }
+        };
+        let transform = self.calc_transform();
+        let corners = local.corners().map(|(x, y)| transform_point(transform, x, y));
+        renderer.move_to(corners[0].0, corners[0].1);
+        for (x, y) in corners.iter().skip(1) {
+            renderer.line_to(*x, *y);
+        }
+        renderer.close_path();
+    }
+
     fn to_value(&self) -> Value {
         json!(self)
     }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
 }
 
 impl Eventable for Rect {}
@@ -211,55 +358,13 @@ impl Transformable for Rect {
     }
 
     fn calc_transform(&self) -> na::Matrix1x6<f64> {
-        let base_transform = self.get_transform();
-        let (translate_x, translate_y) = (base_transform[4], base_transform[5]);
-
-        let scale_skew_matrix = na::Matrix3::new(
-            base_transform[0],
-            base_transform[1],
-            0.0,
-            base_transform[2],
-            base_transform[3],
-            0.0,
-            0.0,
-            0.0,
-            1.0,
-        );
-
-        let translate_to_center = na::Matrix3::new(
-            1.0,
-            0.0,
-            self.width / 2.0,
-            0.0,
-            1.0,
-            self.height / 2.0,
-            0.0,
-            0.0,
-            1.0,
-        );
-
-        let translate_from_center = na::Matrix3::new(
-            1.0,
-            0.0,
-            -self.width / 2.0,
-            0.0,
-            1.0,
-            -self.height / 2.0,
-            0.0,
-            0.0,
-            1.0,
-        );
-
-        let rotation = get_rotation_matrix(self.rotation.to_radians());
-
-        let transform_matrix =
-            scale_skew_matrix * translate_to_center * rotation * translate_from_center;
-
-        let mut final_transform = convert_3x3_to_1x6(transform_matrix);
-        final_transform[4] += translate_x;
-        final_transform[5] += translate_y;
-
-        final_transform
+        anchored_transform(
+            self.get_transform(),
+            self.width,
+            self.height,
+            self.rotation,
+            (self.anchor_x, self.anchor_y),
+        )
     }
 
     fn set_rotation(&mut self, angle_degrees: f64) {
@@ -281,14 +386,19 @@ impl Transformable for Rect {
         self.set_skew_y(skew_y);
     }
 
-    fn apply_transform(&mut self, transform: nalgebra::Matrix1x6<f64>) {
-        self.set_x(transform[4]);
-        self.set_y(transform[5]);
-        self.set_scale(transform[0], transform[3]);
-        self.set_skew(transform[1], transform[2]);
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64) {
+        self.set_anchor_x(anchor_x);
+        self.set_anchor_y(anchor_y);
+    }
 
-        let angle_radians = (self.skew_y / self.scale_x).atan();
-        self.set_rotation(angle_radians.to_degrees());
+    fn apply_transform(&mut self, transform: nalgebra::Matrix1x6<f64>) {
+        let (base, rotation) =
+            decompose_anchored(transform, self.width, self.height, (self.anchor_x, self.anchor_y));
+        self.set_x(base[4]);
+        self.set_y(base[5]);
+        self.set_scale(base[0], base[3]);
+        self.set_skew(base[1], base[2]);
+        self.set_rotation(rotation);
     }
 
     fn get_rotation(&self) -> f64 {
@@ -302,49 +412,21 @@ impl Transformable for Rect {
     fn get_scale(&self) -> (f64, f64) {
         (self.scale_x, self.scale_y)
     }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (self.anchor_x, self.anchor_y)
+    }
 }
 
 impl Animatable for Rect {
     fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
-        let mut result = HashMap::new();
-
-        for property in properties {
-            match property.as_str() {
-                "x" => result.insert("x".to_string(), AnimationValue::Float(self.x)),
-                "y" => result.insert("y".to_string(), AnimationValue::Float(self.y)),
-                "width" => result.insert("width".to_string(), AnimationValue::Float(self.width)),
-                "height" => result.insert("height".to_string(), AnimationValue::Float(self.height)),
-                "fill" => result.insert(
-                    "fill".to_string(),
-                    AnimationValue::String(self.fill.clone()),
-                ),
-                "stroke" => result.insert(
-                    "stroke".to_string(),
-                    AnimationValue::String(self.stroke.clone()),
-                ),
-                "stroke_width" => result.insert(
-                    "stroke_width".to_string(),
-                    AnimationValue::Float(self.stroke_width),
-                ),
-                "opacity" => {
-                    result.insert("opacity".to_string(), AnimationValue::Float(self.opacity))
-                }
-                "scale_x" => {
-                    result.insert("scale_x".to_string(), AnimationValue::Float(self.scale_x))
-                }
-                "scale_y" => {
-                    result.insert("scale_y".to_string(), AnimationValue::Float(self.scale_y))
-                }
-                "skew_x" => result.insert("skew_x".to_string(), AnimationValue::Float(self.skew_x)),
-                "skew_y" => result.insert("skew_y".to_string(), AnimationValue::Float(self.skew_y)),
-                "rotation" => {
-                    result.insert("rotation".to_string(), AnimationValue::Float(self.rotation))
-                }
-                _ => None,
-            };
-        }
-
-        result
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
     }
 
     fn set_properties(
@@ -369,6 +451,8 @@ impl Animatable for Rect {
                 ("skew_x", AnimationValue::Float(v)) => dirty_properties.skew_x = Some(v),
                 ("skew_y", AnimationValue::Float(v)) => dirty_properties.skew_y = Some(v),
                 ("rotation", AnimationValue::Float(v)) => dirty_properties.rotation = Some(v),
+                ("anchor_x", AnimationValue::Float(v)) => dirty_properties.anchor_x = Some(v),
+                ("anchor_y", AnimationValue::Float(v)) => dirty_properties.anchor_y = Some(v),
                 _ => return Err(AnimationError::InvalidProperty(property.into())),
             }
         }
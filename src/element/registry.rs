@@ -0,0 +1,194 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde_json::Value;
+
+use super::{Chart, ChartKind, Connector, Ellipse, Frame, Instance, Line, Path, Rect, RectOptions, Renderable, Sprite, Text, Video};
+use crate::error::{EditingError, EditingResult};
+use crate::log::log_warn;
+
+// 由序列化数据（历史记录、文档、剪贴板）重建元素
+pub type ElementFactory = Rc<dyn Fn(&Value) -> EditingResult<Box<dyn Renderable>>>;
+// 以默认属性新建元素，供对象池等只知道类型名的场景使用
+pub type DefaultElementFactory = Rc<dyn Fn() -> Box<dyn Renderable>>;
+
+#[derive(Clone)]
+struct ElementEntry {
+    create: ElementFactory,
+    create_default: Option<DefaultElementFactory>,
+}
+
+thread_local! {
+    static ELEMENT_REGISTRY: RefCell<HashMap<String, ElementEntry>> = RefCell::new(builtin_elements());
+}
+
+fn builtin_elements() -> HashMap<String, ElementEntry> {
+    let mut elements = HashMap::new();
+    elements.insert(
+        "rect".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let rect = serde_json::from_value::<Rect>(data.clone())?;
+                Ok(Box::new(rect) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Rect::new(RectOptions::default())))),
+        },
+    );
+    elements.insert(
+        "connector".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let connector = serde_json::from_value::<Connector>(data.clone())?;
+                Ok(Box::new(connector) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Connector::new("", "")))),
+        },
+    );
+    elements.insert(
+        "chart".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let chart = serde_json::from_value::<Chart>(data.clone())?;
+                Ok(Box::new(chart) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| {
+                Box::new(Chart::new(0.0, 0.0, 320.0, 200.0, ChartKind::Bar, Vec::new()))
+            })),
+        },
+    );
+    elements.insert(
+        "path".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let path = serde_json::from_value::<Path>(data.clone())?;
+                Ok(Box::new(path) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Path::new(0.0, 0.0, Vec::new())))),
+        },
+    );
+    elements.insert(
+        "ellipse".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let ellipse = serde_json::from_value::<Ellipse>(data.clone())?;
+                Ok(Box::new(ellipse) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Ellipse::new(0.0, 0.0, 100.0, 100.0)))),
+        },
+    );
+    elements.insert(
+        "frame".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let frame = serde_json::from_value::<Frame>(data.clone())?;
+                Ok(Box::new(frame) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Frame::new(0.0, 0.0, 400.0, 300.0)))),
+        },
+    );
+    elements.insert(
+        "instance".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let instance = serde_json::from_value::<Instance>(data.clone())?;
+                Ok(Box::new(instance) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Instance::new("", 0.0, 0.0)))),
+        },
+    );
+    elements.insert(
+        "line".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let line = serde_json::from_value::<Line>(data.clone())?;
+                Ok(Box::new(line) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Line::new(0.0, 0.0, 100.0, 0.0)))),
+        },
+    );
+    elements.insert(
+        "text".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let text = serde_json::from_value::<Text>(data.clone())?;
+                Ok(Box::new(text) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Text::new(0.0, 0.0, "")))),
+        },
+    );
+    elements.insert(
+        "video".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let video = serde_json::from_value::<Video>(data.clone())?;
+                Ok(Box::new(video) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Video::new(0.0, 0.0, 320.0, 180.0, "")))),
+        },
+    );
+    elements.insert(
+        "sprite".to_string(),
+        ElementEntry {
+            create: Rc::new(|data: &Value| {
+                let sprite = serde_json::from_value::<Sprite>(data.clone())?;
+                Ok(Box::new(sprite) as Box<dyn Renderable>)
+            }),
+            create_default: Some(Rc::new(|| Box::new(Sprite::new(0.0, 0.0, 64.0, 64.0, "", Vec::new())))),
+        },
+    );
+    elements
+}
+
+// 注册自定义元素类型；类型名需与元素 get_type() 的返回值一致，重复注册时覆盖
+pub fn register_element(
+    element_type: impl Into<String>,
+    create: impl Fn(&Value) -> EditingResult<Box<dyn Renderable>> + 'static,
+    create_default: Option<DefaultElementFactory>,
+) {
+    let element_type = element_type.into();
+    let entry = ElementEntry {
+        create: Rc::new(create),
+        create_default,
+    };
+    ELEMENT_REGISTRY.with(|registry| {
+        if registry.borrow_mut().insert(element_type.clone(), entry).is_some() {
+            log_warn!("Element type {} registered again, previous factory replaced", element_type);
+        }
+    });
+}
+
+pub fn unregister_element(element_type: &str) -> bool {
+    ELEMENT_REGISTRY.with(|registry| registry.borrow_mut().remove(element_type).is_some())
+}
+
+pub fn is_registered(element_type: &str) -> bool {
+    ELEMENT_REGISTRY.with(|registry| registry.borrow().contains_key(element_type))
+}
+
+pub fn registered_types() -> Vec<String> {
+    let mut types: Vec<String> =
+        ELEMENT_REGISTRY.with(|registry| registry.borrow().keys().cloned().collect());
+    types.sort();
+    types
+}
+
+fn lookup(element_type: &str) -> EditingResult<ElementEntry> {
+    // 先克隆出工厂再调用，工厂内部可以再次访问注册表
+    ELEMENT_REGISTRY
+        .with(|registry| registry.borrow().get(element_type).cloned())
+        .ok_or_else(|| EditingError::UnsupportedElement(element_type.to_string()))
+}
+
+pub fn create_element(element_type: &str, data: &Value) -> EditingResult<Box<dyn Renderable>> {
+    let entry = lookup(element_type)?;
+    (entry.create)(data)
+}
+
+pub fn create_default_element(element_type: &str) -> EditingResult<Box<dyn Renderable>> {
+    let entry = lookup(element_type)?;
+    match entry.create_default {
+        Some(create_default) => Ok(create_default()),
+        None => Err(EditingError::UnsupportedElement(element_type.to_string())),
+    }
+}
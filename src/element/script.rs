@@ -0,0 +1,348 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{default_anchor, registry, Dirty, Eventable, DEFAULT_ANCHOR, ObjectId, ObjectMeta, PointerEvents, PropertySchema, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, bounding_box::BoundingBox, helper::to_js_value, math::{anchored_transform, decompose_anchored, convert_1x6_to_3x3, transform_point}, log::log_error, renderer::{Renderer, RendererProxy}
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Function;
+
+thread_local! {
+    // 元素类型 -> JS 绘制回调
+    static SCRIPT_DRAWERS: RefCell<HashMap<String, Function>> = RefCell::new(HashMap::new());
+}
+
+// 注册一个由 JS 回调绘制的元素类型，之后可以像内置类型一样创建、粘贴和撤销
+pub fn register_script_element(element_type: &str, draw: Function) {
+    SCRIPT_DRAWERS.with(|drawers| {
+        drawers.borrow_mut().insert(element_type.to_string(), draw);
+    });
+
+    let create_type = element_type.to_string();
+    let default_type = element_type.to_string();
+    registry::register_element(
+        element_type,
+        move |data: &Value| {
+            let mut element = serde_json::from_value::<ScriptElement>(data.clone())?;
+            element.element_type = create_type.clone();
+            Ok(Box::new(element) as Box<dyn Renderable>)
+        },
+        Some(Rc::new(move || {
+            Box::new(ScriptElement::new(&default_type)) as Box<dyn Renderable>
+        })),
+    );
+}
+
+// 几何和变换由 Rust 管理，外观交给 JS 绘制回调；拾取按包围矩形计算
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ScriptElement {
+    id: ObjectId,
+    dirty: bool,
+    element_type: String,
+    #[dirty_setter]
+    pub x: f64,
+    #[dirty_setter]
+    pub y: f64,
+    #[dirty_setter]
+    pub width: f64,
+    #[dirty_setter]
+    pub height: f64,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    #[dirty_setter]
+    pub scale_x: f64,
+    #[dirty_setter]
+    pub scale_y: f64,
+    #[dirty_setter]
+    pub skew_x: f64,
+    #[dirty_setter]
+    pub skew_y: f64,
+    #[dirty_setter]
+    pub rotation: f64,
+    // 旋转和缩放的锚点，按宽高归一化，(0.5, 0.5) 为几何中心
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_x: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_y: f64,
+    // 原样传给绘制回调的自定义属性
+    #[dirty_setter]
+    pub props: Value,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl ScriptElement {
+    pub fn new(element_type: &str) -> Self {
+        ScriptElement {
+            id: ObjectId::new(),
+            element_type: element_type.to_string(),
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+            opacity: 1.0,
+            pointer_events: PointerEvents::default(),
+            scale_x: 1.0,
+            scale_y: 1.0,
+            skew_x: 0.0,
+            skew_y: 0.0,
+            rotation: 0.0,
+            anchor_x: DEFAULT_ANCHOR,
+            anchor_y: DEFAULT_ANCHOR,
+            props: json!({}),
+            meta: ObjectMeta::default(),
+            dirty: true,
+            app: None,
+        }
+    }
+
+    fn apply_transform_to(&self, renderer: &dyn Renderer) {
+        let binding = self.calc_transform();
+        if let [a, b, c, d, e, f] = binding.as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+    }
+}
+
+impl Dirty for ScriptElement {
+    fn set_dirty(&mut self) {
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for ScriptElement {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        let Some(draw) = SCRIPT_DRAWERS.with(|drawers| drawers.borrow().get(&self.element_type).cloned()) else {
+            return;
+        };
+
+        self.apply_transform_to(renderer);
+        renderer.set_global_alpha(self.opacity);
+
+        let proxy = RendererProxy::new();
+        let recorder = proxy.recorder();
+        let info = to_js_value(&json!({
+            "id": self.id.value(),
+            "width": self.width,
+            "height": self.height,
+            "props": self.props,
+        }));
+        if let Err(e) = draw.call2(&JsValue::NULL, &JsValue::from(proxy), &info) {
+            log_error!("Draw callback of {} failed: {:?}", self.element_type, e);
+            return;
+        }
+        recorder.replay(renderer);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, _tolerance: f64) {
+        self.apply_transform_to(renderer);
+        renderer.draw_rectangle(0.0, 0.0, self.width, self.height, "black");
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let transform = self.calc_transform();
+        let corners = BoundingBox::new(0.0, 0.0, self.width, self.height)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y));
+        BoundingBox::from_points(&corners)
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        &self.element_type
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for ScriptElement {}
+
+impl Transformable for ScriptElement {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(
+            self.scale_x,
+            self.skew_x,
+            self.skew_y,
+            self.scale_y,
+            self.x,
+            self.y,
+        )
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        let transform = convert_1x6_to_3x3(self.get_transform());
+        let center = na::Vector3::new(self.width / 2.0, self.height / 2.0, 1.0);
+        let transformed_center = transform * center;
+        (transformed_center.x, transformed_center.y)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        anchored_transform(
+            self.get_transform(),
+            self.width,
+            self.height,
+            self.rotation,
+            (self.anchor_x, self.anchor_y),
+        )
+    }
+
+    fn set_rotation(&mut self, angle_degrees: f64) {
+        self.set_rotation(angle_degrees % 360.0);
+    }
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        self.set_x(x);
+        self.set_y(y);
+    }
+
+    fn set_scale(&mut self, sx: f64, sy: f64) {
+        self.set_scale_x(sx);
+        self.set_scale_y(sy);
+    }
+
+    fn set_skew(&mut self, skew_x: f64, skew_y: f64) {
+        self.set_skew_x(skew_x);
+        self.set_skew_y(skew_y);
+    }
+
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64) {
+        self.set_anchor_x(anchor_x);
+        self.set_anchor_y(anchor_y);
+    }
+
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        let (base, rotation) =
+            decompose_anchored(transform, self.width, self.height, (self.anchor_x, self.anchor_y));
+        self.set_x(base[4]);
+        self.set_y(base[5]);
+        self.set_scale(base[0], base[3]);
+        self.set_skew(base[1], base[2]);
+        self.set_rotation(rotation);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (self.scale_x, self.scale_y)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (self.anchor_x, self.anchor_y)
+    }
+}
+
+impl Animatable for ScriptElement {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x", AnimationValue::Float(v)) => dirty_properties.x = Some(v),
+                ("y", AnimationValue::Float(v)) => dirty_properties.y = Some(v),
+                ("width", AnimationValue::Float(v)) => dirty_properties.width = Some(v),
+                ("height", AnimationValue::Float(v)) => dirty_properties.height = Some(v),
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                ("scale_x", AnimationValue::Float(v)) => dirty_properties.scale_x = Some(v),
+                ("scale_y", AnimationValue::Float(v)) => dirty_properties.scale_y = Some(v),
+                ("rotation", AnimationValue::Float(v)) => dirty_properties.rotation = Some(v),
+                ("anchor_x", AnimationValue::Float(v)) => dirty_properties.anchor_x = Some(v),
+                ("anchor_y", AnimationValue::Float(v)) => dirty_properties.anchor_y = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,19 @@
+use crate::bounding_box::BoundingBox;
+use crate::helper::{distance_to_polyline, sample_cubic};
+
+// line、path、connector 这类只有描边的元素共用的拾取参数
+
+// 包围盒两侧留出的最小宽度，细线也能被空间索引查到
+pub const MIN_HIT_WIDTH: f64 = 8.0;
+// 几何拾取时每段三次贝塞尔曲线的采样数
+pub const CURVE_SAMPLES: usize = 16;
+
+// 点到三次贝塞尔曲线 [起点, 控制点1, 控制点2, 终点] 的近似距离
+pub fn distance_to_cubic(point: (f64, f64), curve: [(f64, f64); 4]) -> f64 {
+    distance_to_polyline(point, &sample_cubic(curve, CURVE_SAMPLES), false)
+}
+
+// 描边的包围盒，width 为描边占用的总宽度；贝塞尔曲线不会超出控制点围成的范围，points 包含控制点即可
+pub fn stroke_bounds(points: &[(f64, f64)], width: f64) -> BoundingBox {
+    BoundingBox::from_points(points).expand(width.max(MIN_HIT_WIDTH) / 2.0)
+}
@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+
+use super::{default_anchor, Dirty, Eventable, DEFAULT_ANCHOR, ObjectId, ObjectMeta, PointerEvents, PropertySchema, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, bounding_box::BoundingBox, fonts::measure_text_full, math::{anchored_transform, decompose_anchored, convert_1x6_to_3x3, transform_point}, renderer::{Renderer, TextAlign, TextBaseline, Filter}
+};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// 默认行高相对字号的倍数
+const DEFAULT_LINE_HEIGHT: f64 = 1.2;
+
+fn default_line_height() -> f64 {
+    DEFAULT_LINE_HEIGHT
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+// 排版后的一行，坐标相对文本框左上角
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    // 字形实际覆盖的水平范围，斜体或带前后空格时与 [x, x + width] 不同
+    pub ink_left: f64,
+    pub ink_right: f64,
+}
+
+impl TextLine {
+    // 拾取区域：前进宽度和字形范围的并集，高度为一整行
+    pub fn hit_box(&self, line_height: f64) -> BoundingBox {
+        let left = self.x.min(self.ink_left);
+        let right = (self.x + self.width).max(self.ink_right);
+        BoundingBox::new(left, self.y, right - left, line_height)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+    pub width: f64,
+    pub height: f64,
+    pub line_height: f64,
+}
+
+// 文本框，(x, y) 为左上角；"\n" 分段，设置 max_width 后段内按宽度自动换行
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Text {
+    id: ObjectId,
+    dirty: bool,
+    #[dirty_setter]
+    pub x: f64,
+    #[dirty_setter]
+    pub y: f64,
+    #[dirty_setter]
+    pub text: String,
+    #[dirty_setter]
+    pub font_size: f64,
+    #[dirty_setter]
+    pub font_family: String,
+    // CSS font-weight，如 "normal"、"bold"、"600"
+    #[dirty_setter]
+    pub font_weight: String,
+    #[dirty_setter(color)]
+    pub fill: String,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    // 元素级的 CSS 滤镜效果，如模糊、投影
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    // 自动换行的宽度，None 时只在 "\n" 处换行
+    #[dirty_setter]
+    #[serde(default)]
+    pub max_width: Option<f64>,
+    // 行高相对字号的倍数
+    #[dirty_setter]
+    #[serde(default = "default_line_height")]
+    pub line_height: f64,
+    #[dirty_setter(options("left", "center", "right"))]
+    #[serde(default)]
+    pub align: TextAlignment,
+    // 按段覆盖 align，第 i 项对应第 i 段，缺少的段使用 align
+    #[dirty_setter]
+    #[serde(default)]
+    pub paragraph_align: Vec<TextAlignment>,
+    #[dirty_setter]
+    pub scale_x: f64,
+    #[dirty_setter]
+    pub scale_y: f64,
+    #[dirty_setter]
+    pub rotation: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_x: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_y: f64,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+impl Text {
+    pub fn new(x: f64, y: f64, text: &str) -> Self {
+        Text {
+            id: ObjectId::new(),
+            dirty: true,
+            x,
+            y,
+            text: text.to_string(),
+            font_size: 16.0,
+            font_family: "sans-serif".to_string(),
+            font_weight: "normal".to_string(),
+            fill: "black".to_string(),
+            opacity: 1.0,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
+            max_width: None,
+            line_height: DEFAULT_LINE_HEIGHT,
+            align: TextAlignment::Left,
+            paragraph_align: Vec::new(),
+            scale_x: 1.0,
+            scale_y: 1.0,
+            rotation: 0.0,
+            anchor_x: DEFAULT_ANCHOR,
+            anchor_y: DEFAULT_ANCHOR,
+            meta: ObjectMeta::default(),
+            app: None,
+        }
+    }
+
+    // CSS font 简写
+    pub fn font(&self) -> String {
+        format!("{} {}px {}", self.font_weight, self.font_size, self.font_family)
+    }
+
+    // 未变换时文本框的尺寸
+    pub fn size(&self) -> (f64, f64) {
+        let layout = self.layout();
+        (layout.width, layout.height)
+    }
+
+    pub fn layout(&self) -> TextLayout {
+        let font = self.font();
+        let measure = |text: &str| measure_text_full(&font, self.font_size, text).width;
+        let max_width = self.max_width.filter(|width| *width > 0.0);
+        let line_height = self.font_size * self.line_height;
+
+        let mut rows = Vec::new();
+        for (index, paragraph) in self.text.split('\n').enumerate() {
+            let align = self.paragraph_align.get(index).copied().unwrap_or(self.align);
+            let lines = match max_width {
+                Some(max_width) => wrap_paragraph(paragraph, max_width, &measure),
+                None => vec![paragraph.to_string()],
+            };
+            for line in lines {
+                let metrics = measure_text_full(&font, self.font_size, &line);
+                rows.push((line, metrics, align));
+            }
+        }
+
+        let width = max_width.unwrap_or_else(|| rows.iter().map(|row| row.1.width).fold(0.0, f64::max));
+        let lines = rows
+            .into_iter()
+            .enumerate()
+            .map(|(index, (text, metrics, align))| {
+                let x = match align {
+                    TextAlignment::Left => 0.0,
+                    TextAlignment::Center => (width - metrics.width) / 2.0,
+                    TextAlignment::Right => width - metrics.width,
+                };
+                TextLine {
+                    x,
+                    y: index as f64 * line_height,
+                    text,
+                    width: metrics.width,
+                    ink_left: x - metrics.actual_bounding_box_left,
+                    ink_right: x + metrics.actual_bounding_box_right,
+                }
+            })
+            .collect::<Vec<_>>();
+        TextLayout {
+            height: lines.len() as f64 * line_height,
+            lines,
+            width,
+            line_height,
+        }
+    }
+
+    fn draw(&self, renderer: &dyn Renderer, hit: bool) {
+        let binding = self.calc_transform();
+        if let [a, b, c, d, e, f] = binding.as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        let layout = self.layout();
+        // 拾取时按每一行所占的区域命中，不要求点在笔画上
+        if hit {
+            for line in &layout.lines {
+                let hit_box = line.hit_box(layout.line_height);
+                renderer.draw_rectangle(hit_box.x, hit_box.y, hit_box.width, hit_box.height, &self.fill);
+            }
+            return;
+        }
+        renderer.set_font(&self.font());
+        renderer.set_text_align(TextAlign::Left);
+        renderer.set_text_baseline(TextBaseline::Middle);
+        renderer.set_fill_style(&self.fill);
+        for line in &layout.lines {
+            renderer.fill_text(&line.text, line.x, line.y + layout.line_height / 2.0);
+        }
+    }
+}
+
+impl Dirty for Text {
+    fn set_dirty(&mut self) {
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Text {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        self.draw(renderer, false);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, _tolerance: f64) {
+        self.draw(renderer, true);
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let layout = self.layout();
+        let transform = self.calc_transform();
+        // 字形超出文本框时（如斜体）包围盒也要包含进来
+        let local = layout
+            .lines
+            .iter()
+            .map(|line| line.hit_box(layout.line_height))
+            .fold(BoundingBox::new(0.0, 0.0, layout.width, layout.height), |union, hit_box| {
+                union.union(&hit_box)
+            });
+        let corners = local
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y));
+        BoundingBox::from_points(&corners)
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+    }
+
+    fn detach(&mut self) {
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "text"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Text {}
+
+impl Transformable for Text {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(self.scale_x, 0.0, 0.0, self.scale_y, self.x, self.y)
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        let (width, height) = self.size();
+        let transform = convert_1x6_to_3x3(self.get_transform());
+        let center = transform * na::Vector3::new(width / 2.0, height / 2.0, 1.0);
+        (center.x, center.y)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        let (width, height) = self.size();
+        anchored_transform(
+            self.get_transform(),
+            width,
+            height,
+            self.rotation,
+            (self.anchor_x, self.anchor_y),
+        )
+    }
+
+    fn set_rotation(&mut self, angle_degrees: f64) {
+        self.set_rotation(angle_degrees % 360.0);
+    }
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        self.set_x(x);
+        self.set_y(y);
+    }
+
+    fn set_scale(&mut self, sx: f64, sy: f64) {
+        self.set_scale_x(sx);
+        self.set_scale_y(sy);
+    }
+
+    // 文本不支持斜切
+    fn set_skew(&mut self, _skew_x: f64, _skew_y: f64) {}
+
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64) {
+        self.set_anchor_x(anchor_x);
+        self.set_anchor_y(anchor_y);
+    }
+
+    // 文字不支持斜切，分解出的斜切分量被忽略
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        let (width, height) = self.size();
+        let (base, rotation) =
+            decompose_anchored(transform, width, height, (self.anchor_x, self.anchor_y));
+        self.set_x(base[4]);
+        self.set_y(base[5]);
+        self.set_scale(base[0], base[3]);
+        self.set_rotation(rotation);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (self.scale_x, self.scale_y)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (self.anchor_x, self.anchor_y)
+    }
+}
+
+impl Animatable for Text {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x", AnimationValue::Float(v)) => dirty_properties.x = Some(v),
+                ("y", AnimationValue::Float(v)) => dirty_properties.y = Some(v),
+                ("text", AnimationValue::String(v)) => dirty_properties.text = Some(v),
+                ("font_size", AnimationValue::Float(v)) => dirty_properties.font_size = Some(v),
+                ("line_height", AnimationValue::Float(v)) => dirty_properties.line_height = Some(v),
+                ("fill", AnimationValue::String(v)) => dirty_properties.fill = Some(v),
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                ("scale_x", AnimationValue::Float(v)) => dirty_properties.scale_x = Some(v),
+                ("scale_y", AnimationValue::Float(v)) => dirty_properties.scale_y = Some(v),
+                ("rotation", AnimationValue::Float(v)) => dirty_properties.rotation = Some(v),
+                ("anchor_x", AnimationValue::Float(v)) => dirty_properties.anchor_x = Some(v),
+                ("anchor_y", AnimationValue::Float(v)) => dirty_properties.anchor_y = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
+
+// 贪心换行：优先在空格处断开，单个词超出宽度时按字符断开（中文等无空格文本也走这里）
+fn wrap_paragraph(paragraph: &str, max_width: f64, measure: &impl Fn(&str) -> f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in paragraph.split_inclusive(' ') {
+        let candidate = format!("{}{}", line, word);
+        if line.is_empty() || measure(candidate.trim_end()) <= max_width {
+            line = candidate;
+        } else {
+            lines.push(line.trim_end().to_string());
+            line = word.to_string();
+        }
+        while measure(line.trim_end()) > max_width {
+            let split = fitting_prefix(&line, max_width, measure);
+            if split >= line.len() {
+                break;
+            }
+            lines.push(line[..split].to_string());
+            line = line[split..].to_string();
+        }
+    }
+    lines.push(line.trim_end().to_string());
+    lines
+}
+
+// 不超过 max_width 的最长前缀的字节长度，至少包含一个字符
+fn fitting_prefix(text: &str, max_width: f64, measure: &impl Fn(&str) -> f64) -> usize {
+    let boundaries = text
+        .char_indices()
+        .map(|(index, c)| index + c.len_utf8())
+        .collect::<Vec<_>>();
+    let fits = boundaries.partition_point(|end| measure(&text[..*end]) <= max_width);
+    boundaries[fits.max(1) - 1]
+}
@@ -0,0 +1,451 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+
+use super::{default_anchor, Dirty, Eventable, DEFAULT_ANCHOR, ObjectId, ObjectMeta, PointerEvents, PropertySchema, Renderable, Transformable};
+use crate::{
+    animation::{Animatable, AnimationError, AnimationValue}, app::App, bounding_box::BoundingBox, math::{anchored_transform, decompose_anchored, convert_1x6_to_3x3, transform_point}, image::Image, renderer::{Filter, Renderer}
+};
+use crate::error::{EditingError, EditingResult};
+use crate::history::{ObjectHistoryItem, HistoryItem};
+use crate::log::log_warn;
+use dirty_setter::DirtySetter;
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::js_sys::Promise;
+use web_sys::{Event, HtmlVideoElement};
+
+// HTMLMediaElement.HAVE_CURRENT_DATA，之前没有可绘制的帧
+const HAVE_CURRENT_DATA: u16 = 2;
+// 视频还没有画面时显示的底色
+const PLACEHOLDER_FILL: &str = "#202020";
+
+type MediaListeners = Vec<(&'static str, Closure<dyn FnMut(Event)>)>;
+
+fn muted_by_default() -> bool {
+    true
+}
+
+// 在 (x, y, width, height) 中绘制视频的当前帧，变换方式与 Rect 相同；
+// 播放由 App::play_video / pause_video 控制，播放期间每帧重绘
+#[derive(Debug, Clone, DirtySetter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Video {
+    id: ObjectId,
+    dirty: bool,
+    #[dirty_setter]
+    pub x: f64,
+    #[dirty_setter]
+    pub y: f64,
+    #[dirty_setter]
+    pub width: f64,
+    #[dirty_setter]
+    pub height: f64,
+    #[dirty_setter]
+    pub src: String,
+    // 浏览器只允许静音视频在没有用户操作时播放
+    #[dirty_setter]
+    #[serde(default = "muted_by_default")]
+    pub muted: bool,
+    #[dirty_setter(rename = "loop")]
+    #[serde(rename = "loop", default)]
+    pub looped: bool,
+    #[dirty_setter(clamp(0.0, 1.0))]
+    pub opacity: f64,
+    #[dirty_setter]
+    #[serde(default)]
+    pub filter: Filter,
+    #[dirty_setter(options("auto", "none", "fill", "stroke"))]
+    #[serde(default)]
+    pub pointer_events: PointerEvents,
+    #[dirty_setter]
+    pub scale_x: f64,
+    #[dirty_setter]
+    pub scale_y: f64,
+    #[dirty_setter]
+    pub skew_x: f64,
+    #[dirty_setter]
+    pub skew_y: f64,
+    #[dirty_setter]
+    pub rotation: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_x: f64,
+    #[dirty_setter]
+    #[serde(default = "default_anchor")]
+    pub anchor_y: f64,
+
+    #[serde(default)]
+    meta: ObjectMeta,
+
+    // attach 时创建，detach 时暂停并移除
+    #[serde(skip)]
+    media: Option<Rc<VideoMedia>>,
+
+    #[serde(skip)]
+    app: Option<App>,
+}
+
+// 不插入文档的 video 元素，只用作绘制的帧来源
+struct VideoMedia {
+    element: HtmlVideoElement,
+    src: RefCell<String>,
+    // 新的一帧可用时（加载完成、跳转结束）请求重绘，暂停状态下也能看到画面
+    listeners: MediaListeners,
+}
+
+impl Debug for VideoMedia {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VideoMedia {{ src: {:?} }}", self.src.borrow())
+    }
+}
+
+impl VideoMedia {
+    fn new(app: &App) -> EditingResult<Self> {
+        let element = web_sys::window()
+            .ok_or(EditingError::WindowNotFound)?
+            .document()
+            .ok_or(EditingError::DocumentNotFound)?
+            .create_element("video")?
+            .dyn_into::<HtmlVideoElement>()
+            .map_err(|_| EditingError::Dom("Failed to create <video>".to_string()))?;
+        element.set_preload("auto");
+        element.set_cross_origin(Some("anonymous"));
+
+        let listeners: MediaListeners = ["loadeddata", "seeked"]
+            .into_iter()
+            .map(|event_type| {
+                let app = app.clone();
+                let listener = Closure::wrap(Box::new(move |_: Event| app.request_render()) as Box<dyn FnMut(Event)>);
+                (event_type, listener)
+            })
+            .collect();
+        for (event_type, listener) in &listeners {
+            element.add_event_listener_with_callback(event_type, listener.as_ref().unchecked_ref())?;
+        }
+
+        Ok(Self {
+            element,
+            src: RefCell::new(String::new()),
+            listeners,
+        })
+    }
+
+    fn sync(&self, video: &Video) {
+        self.element.set_muted(video.muted);
+        self.element.set_loop(video.looped);
+        if *self.src.borrow() != video.src {
+            self.element.set_src(&video.src);
+            *self.src.borrow_mut() = video.src.clone();
+        }
+    }
+
+    fn release(&self) {
+        let _ = self.element.pause();
+        for (event_type, listener) in &self.listeners {
+            let _ = self
+                .element
+                .remove_event_listener_with_callback(event_type, listener.as_ref().unchecked_ref());
+        }
+        // 清空 src 让浏览器释放解码器和网络连接
+        self.element.remove_attribute("src").ok();
+        self.element.load();
+    }
+}
+
+impl Video {
+    pub fn new(x: f64, y: f64, width: f64, height: f64, src: &str) -> Self {
+        Video {
+            id: ObjectId::new(),
+            dirty: true,
+            x,
+            y,
+            width,
+            height,
+            src: src.to_string(),
+            muted: true,
+            looped: false,
+            opacity: 1.0,
+            filter: Filter::default(),
+            pointer_events: PointerEvents::default(),
+            scale_x: 1.0,
+            scale_y: 1.0,
+            skew_x: 0.0,
+            skew_y: 0.0,
+            rotation: 0.0,
+            anchor_x: DEFAULT_ANCHOR,
+            anchor_y: DEFAULT_ANCHOR,
+            meta: ObjectMeta::default(),
+            media: None,
+            app: None,
+        }
+    }
+
+    // 返回 HTMLMediaElement.play() 的 Promise，浏览器拒绝播放时 reject
+    pub fn play(&self) -> EditingResult<Promise> {
+        let media = self.media.as_ref().ok_or(EditingError::NotInitialized("Video"))?;
+        Ok(media.element.play()?)
+    }
+
+    pub fn pause(&self) -> EditingResult<()> {
+        let media = self.media.as_ref().ok_or(EditingError::NotInitialized("Video"))?;
+        Ok(media.element.pause()?)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.media
+            .as_ref()
+            .is_some_and(|media| !media.element.paused() && !media.element.ended())
+    }
+
+    fn draw(&self, renderer: &dyn Renderer, hit: bool) {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return;
+        }
+        let binding = self.calc_transform();
+        if let [a, b, c, d, e, f] = binding.as_slice() {
+            renderer.transform(*a, *b, *c, *d, *e, *f);
+        }
+        if hit {
+            renderer.draw_rectangle(0.0, 0.0, self.width, self.height, PLACEHOLDER_FILL);
+            return;
+        }
+
+        renderer.set_global_alpha(self.opacity);
+        if !self.filter.is_empty() {
+            renderer.set_filter(&self.filter.to_string());
+        }
+        match &self.media {
+            Some(media) if media.element.ready_state() >= HAVE_CURRENT_DATA => {
+                renderer.draw_image_with_size(&Image::new(&media.element), 0.0, 0.0, self.width, self.height);
+            }
+            _ => renderer.draw_rectangle(0.0, 0.0, self.width, self.height, PLACEHOLDER_FILL),
+        }
+    }
+}
+
+impl Dirty for Video {
+    fn set_dirty(&mut self) {
+        if let Some(media) = &self.media {
+            media.sync(self);
+        }
+        self.set_dirty_flag(true);
+    }
+    fn set_dirty_flag(&mut self, is_dirty: bool) {
+        self.dirty = is_dirty;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+}
+
+impl Renderable for Video {
+    fn id(&self) -> &ObjectId {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut ObjectId {
+        &mut self.id
+    }
+
+    fn update(&mut self, data: Value) {
+        self.update(data);
+    }
+
+    fn render(&self, renderer: &dyn Renderer) {
+        self.draw(renderer, false);
+    }
+
+    fn render_hit(&self, renderer: &dyn Renderer, _tolerance: f64) {
+        self.draw(renderer, true);
+    }
+
+    fn pointer_events(&self) -> PointerEvents {
+        self.pointer_events
+    }
+
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let transform = self.calc_transform();
+        let corners = BoundingBox::new(0.0, 0.0, self.width, self.height)
+            .corners()
+            .map(|(x, y)| transform_point(transform, x, y));
+        BoundingBox::from_points(&corners)
+    }
+
+    fn attach(&mut self, app: &App) {
+        self.app = Some(app.clone());
+        if self.media.is_none() {
+            match VideoMedia::new(app) {
+                Ok(media) => {
+                    media.sync(self);
+                    self.media = Some(Rc::new(media));
+                }
+                Err(e) => log_warn!("Failed to create video element for {}: {}", self.id.value(), e),
+            }
+        }
+    }
+
+    fn detach(&mut self) {
+        if let Some(media) = self.media.take() {
+            media.release();
+        }
+        self.app = None;
+    }
+
+    fn get_type(&self) -> &str {
+        "video"
+    }
+
+    fn property(&self, name: &str) -> Option<Value> {
+        self.get_property(name)
+    }
+
+    fn schema(&self) -> Vec<PropertySchema> {
+        Self::property_schema()
+    }
+
+    fn to_value(&self) -> Value {
+        json!(self)
+    }
+
+    fn meta(&self) -> &ObjectMeta {
+        &self.meta
+    }
+
+    fn meta_mut(&mut self) -> &mut ObjectMeta {
+        &mut self.meta
+    }
+}
+
+impl Eventable for Video {}
+
+impl Transformable for Video {
+    fn get_transform(&self) -> na::Matrix1x6<f64> {
+        na::Matrix1x6::new(
+            self.scale_x,
+            self.skew_x,
+            self.skew_y,
+            self.scale_y,
+            self.x,
+            self.y,
+        )
+    }
+
+    fn get_center(&self) -> (f64, f64) {
+        let transform = convert_1x6_to_3x3(self.get_transform());
+        let center = transform * na::Vector3::new(self.width / 2.0, self.height / 2.0, 1.0);
+        (center.x, center.y)
+    }
+
+    fn calc_transform(&self) -> na::Matrix1x6<f64> {
+        anchored_transform(
+            self.get_transform(),
+            self.width,
+            self.height,
+            self.rotation,
+            (self.anchor_x, self.anchor_y),
+        )
+    }
+
+    fn set_rotation(&mut self, angle_degrees: f64) {
+        self.set_rotation(angle_degrees % 360.0);
+    }
+
+    fn set_position(&mut self, x: f64, y: f64) {
+        self.set_x(x);
+        self.set_y(y);
+    }
+
+    fn set_scale(&mut self, sx: f64, sy: f64) {
+        self.set_scale_x(sx);
+        self.set_scale_y(sy);
+    }
+
+    fn set_skew(&mut self, skew_x: f64, skew_y: f64) {
+        self.set_skew_x(skew_x);
+        self.set_skew_y(skew_y);
+    }
+
+    fn set_anchor(&mut self, anchor_x: f64, anchor_y: f64) {
+        self.set_anchor_x(anchor_x);
+        self.set_anchor_y(anchor_y);
+    }
+
+    fn apply_transform(&mut self, transform: na::Matrix1x6<f64>) {
+        let (base, rotation) =
+            decompose_anchored(transform, self.width, self.height, (self.anchor_x, self.anchor_y));
+        self.set_x(base[4]);
+        self.set_y(base[5]);
+        self.set_scale(base[0], base[3]);
+        self.set_skew(base[1], base[2]);
+        self.set_rotation(rotation);
+    }
+
+    fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    fn get_position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    fn get_scale(&self) -> (f64, f64) {
+        (self.scale_x, self.scale_y)
+    }
+
+    fn get_anchor(&self) -> (f64, f64) {
+        (self.anchor_x, self.anchor_y)
+    }
+}
+
+impl Animatable for Video {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        properties
+            .iter()
+            .filter_map(|property| {
+                let value = self.get_property(property)?;
+                Some((property.clone(), AnimationValue::from_json(&value)?))
+            })
+            .collect()
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let mut dirty_properties = DirtyUpdates::default();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("x", AnimationValue::Float(v)) => dirty_properties.x = Some(v),
+                ("y", AnimationValue::Float(v)) => dirty_properties.y = Some(v),
+                ("width", AnimationValue::Float(v)) => dirty_properties.width = Some(v),
+                ("height", AnimationValue::Float(v)) => dirty_properties.height = Some(v),
+                ("opacity", AnimationValue::Float(v)) => dirty_properties.opacity = Some(v),
+                ("scale_x", AnimationValue::Float(v)) => dirty_properties.scale_x = Some(v),
+                ("scale_y", AnimationValue::Float(v)) => dirty_properties.scale_y = Some(v),
+                ("skew_x", AnimationValue::Float(v)) => dirty_properties.skew_x = Some(v),
+                ("skew_y", AnimationValue::Float(v)) => dirty_properties.skew_y = Some(v),
+                ("rotation", AnimationValue::Float(v)) => dirty_properties.rotation = Some(v),
+                ("anchor_x", AnimationValue::Float(v)) => dirty_properties.anchor_x = Some(v),
+                ("anchor_y", AnimationValue::Float(v)) => dirty_properties.anchor_y = Some(v),
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
+        }
+
+        self.set_multiple(dirty_properties);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use wasm_bindgen::JsValue;
+
+#[derive(Debug)]
+pub enum EditingError {
+    WindowNotFound,
+    DocumentNotFound,
+    CanvasNotFound(String),
+    NotACanvas(String),
+    ContextUnsupported(String),
+    ContextCreationFailed(String),
+    NotInitialized(&'static str),
+    AlreadyInitialized(&'static str),
+    ObjectNotFound(String),
+    StyleNotFound(String),
+    InvalidObjectId(String),
+    DuplicateObjectId(String),
+    // 场景中的对象数已达到拾取颜色的上限
+    PickColorsExhausted(usize),
+    UnsupportedElement(String),
+    UnsupportedProperty(String),
+    SerdeError(serde_json::Error),
+    UnsupportedHistoryVersion(u32),
+    UnsupportedOpLogVersion(u32),
+    UnsupportedSessionVersion(u32),
+    InvalidSession(String),
+    // 画布交给 worker 绘制后，主线程无法读取显示画布的像素
+    UnavailableInWorker(&'static str),
+    Dom(String),
+}
+
+pub type EditingResult<T> = Result<T, EditingError>;
+
+impl fmt::Display for EditingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditingError::WindowNotFound => write!(f, "Failed to get window"),
+            EditingError::DocumentNotFound => write!(f, "Failed to get document"),
+            EditingError::CanvasNotFound(id) => write!(f, "Failed to find canvas with id: {}", id),
+            EditingError::NotACanvas(id) => write!(f, "Element with id '{}' is not a canvas", id),
+            EditingError::ContextUnsupported(context_type) => {
+                write!(f, "Unsupported context type: {}", context_type)
+            }
+            EditingError::ContextCreationFailed(context_type) => {
+                write!(f, "Failed to get {} context", context_type)
+            }
+            EditingError::NotInitialized(what) => write!(f, "{} is not initialized", what),
+            EditingError::AlreadyInitialized(what) => write!(f, "{} is already initialized", what),
+            EditingError::ObjectNotFound(id) => write!(f, "Object not found: {}", id),
+            EditingError::StyleNotFound(id) => write!(f, "Style not found: {}", id),
+            EditingError::InvalidObjectId(id) => write!(f, "Invalid object id: '{}'", id),
+            EditingError::DuplicateObjectId(id) => write!(f, "Object id already in use: {}", id),
+            EditingError::PickColorsExhausted(count) => {
+                write!(f, "Pick colors exhausted: {} objects allocated", count)
+            }
+            EditingError::UnsupportedElement(element_type) => {
+                write!(f, "Unsupported element type: {}", element_type)
+            }
+            EditingError::UnsupportedProperty(name) => write!(f, "Unsupported property: {}", name),
+            EditingError::SerdeError(e) => write!(f, "Serialization error: {}", e),
+            EditingError::UnsupportedHistoryVersion(version) => {
+                write!(f, "Unsupported history format version: {}", version)
+            }
+            EditingError::UnsupportedOpLogVersion(version) => {
+                write!(f, "Unsupported operation log format version: {}", version)
+            }
+            EditingError::UnsupportedSessionVersion(version) => {
+                write!(f, "Unsupported session format version: {}", version)
+            }
+            EditingError::InvalidSession(message) => write!(f, "Invalid session data: {}", message),
+            EditingError::UnavailableInWorker(what) => {
+                write!(f, "{} is unavailable while rendering in a worker", what)
+            }
+            EditingError::Dom(message) => write!(f, "DOM error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EditingError {}
+
+impl From<serde_json::Error> for EditingError {
+    fn from(e: serde_json::Error) -> Self {
+        EditingError::SerdeError(e)
+    }
+}
+
+// web_sys 调用失败时返回的 JsValue 统一包装为 Dom 错误
+impl From<JsValue> for EditingError {
+    fn from(value: JsValue) -> Self {
+        EditingError::Dom(value.as_string().unwrap_or_else(|| format!("{:?}", value)))
+    }
+}
+
+impl From<EditingError> for JsValue {
+    fn from(e: EditingError) -> Self {
+        web_sys::js_sys::Error::new(&e.to_string()).into()
+    }
+}
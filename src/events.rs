@@ -4,28 +4,11 @@ pub use app_events::*;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::Once;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use web_sys::js_sys::Function;
 
-static INIT: Once = Once::new();
-static mut GLOBAL_EVENT_SYSTEM: Option<EventSystem> = None;
-
-pub fn get_event_system() -> &'static EventSystem {
-    unsafe {
-        INIT.call_once(|| {
-            GLOBAL_EVENT_SYSTEM = Some(EventSystem::new());
-        });
-        GLOBAL_EVENT_SYSTEM.as_ref().unwrap()
-    }
-}
-
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
-}
+use crate::log::log_debug;
 
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
@@ -66,6 +49,12 @@ pub struct EventSystem {
     events: RefCell<HashMap<String, Vec<Function>>>,
 }
 
+impl std::fmt::Debug for EventSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EventSystem {{ events: {:?} }}", self.events.borrow().keys())
+    }
+}
+
 impl EventSystem {
     pub fn new() -> Self {
         console_error_panic_hook::set_once();
@@ -81,7 +70,7 @@ impl EventSystem {
             .or_insert_with(Vec::new)
             .push(callback.clone());
 
-        log(&format!("Added listener for event: {}", event_name));
+        log_debug!("Added listener for event: {}", event_name);
         Ok(())
     }
 
@@ -97,13 +86,13 @@ impl EventSystem {
             for listener in listeners {
                 listener.call1(&JsValue::NULL, &JsValue::from(event_data.clone()))?;
             }
-            log(&format!(
+            log_debug!(
                 "Emitted event: {} with {} listeners",
                 event_name,
                 listeners.len()
-            ));
+            );
         } else {
-            log(&format!("No listeners for event: {}", event_name));
+            log_debug!("No listeners for event: {}", event_name);
         }
         Ok(())
     }
@@ -115,28 +104,30 @@ impl EventSystem {
             listeners.retain(|l| l != callback);
             let removed_count = initial_count - listeners.len();
             if removed_count > 0 {
-                log(&format!(
+                log_debug!(
                     "Removed {} listener(s) for event: {}",
                     removed_count, event_name
-                ));
+                );
             } else {
-                log(&format!(
-                    "No matching listener found for event: {}",
-                    event_name
-                ));
+                log_debug!("No matching listener found for event: {}", event_name);
             }
         } else {
-            log(&format!("No listeners found for event: {}", event_name));
+            log_debug!("No listeners found for event: {}", event_name);
         }
         Ok(())
     }
 
     pub fn clear_listeners(&self, event_name: &str) -> Result<(), JsValue> {
         self.events.borrow_mut().remove(event_name);
-        log(&format!("Cleared all listeners for event: {}", event_name));
+        log_debug!("Cleared all listeners for event: {}", event_name);
         Ok(())
     }
 
+    pub fn clear_all_listeners(&self) {
+        self.events.borrow_mut().clear();
+        log_debug!("Cleared all listeners");
+    }
+
     pub fn get_listener_count(&self, event_name: &str) -> usize {
         self.events.borrow().get(event_name).map_or(0, |v| v.len())
     }
@@ -1,6 +1,53 @@
 use into_static_str::IntoStaticStr;
 
+#[allow(clippy::upper_case_acronyms, non_camel_case_types)]
 #[derive(IntoStaticStr)]
 pub enum AppEvent {
     READY,
+    RESIZE,
+    HISTORY_CHANGE,
+    OBJECT_DOWN,
+    OBJECT_MOVE,
+    OBJECT_UP,
+    STROKE_END,
+    SHAPE_END,
+    FONT_LOAD,
+    IMAGE_LOAD,
+    IMAGE_ERROR,
+    CONTEXT_MENU,
+    VIEWPORT_CHANGE,
+    SCENE_CHANGE,
+    DOCUMENT_CHANGE,
+    DIRTY_CHANGE,
+    SAVED,
+    REPLAY_END,
+    MEASURE,
+}
+
+impl AppEvent {
+    // JS 侧使用 DOM 风格的小写事件名
+    pub fn from_js_name(name: &str) -> Option<Self> {
+        match name {
+            "ready" => Some(AppEvent::READY),
+            "resize" => Some(AppEvent::RESIZE),
+            "historychange" => Some(AppEvent::HISTORY_CHANGE),
+            "objectdown" => Some(AppEvent::OBJECT_DOWN),
+            "objectmove" => Some(AppEvent::OBJECT_MOVE),
+            "objectup" => Some(AppEvent::OBJECT_UP),
+            "strokeend" => Some(AppEvent::STROKE_END),
+            "shapeend" => Some(AppEvent::SHAPE_END),
+            "fontload" => Some(AppEvent::FONT_LOAD),
+            "imageload" => Some(AppEvent::IMAGE_LOAD),
+            "imageerror" => Some(AppEvent::IMAGE_ERROR),
+            "contextmenu" => Some(AppEvent::CONTEXT_MENU),
+            "viewportchange" => Some(AppEvent::VIEWPORT_CHANGE),
+            "scenechange" => Some(AppEvent::SCENE_CHANGE),
+            "documentchange" => Some(AppEvent::DOCUMENT_CHANGE),
+            "dirtychange" => Some(AppEvent::DIRTY_CHANGE),
+            "saved" => Some(AppEvent::SAVED),
+            "replayend" => Some(AppEvent::REPLAY_END),
+            "measure" => Some(AppEvent::MEASURE),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,100 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::helper::measure_text_metrics;
+use crate::renderer::TextMeasurement;
+
+// 测量缓存的条目上限，超过后整体清空
+const MAX_METRIC_ENTRIES: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FontStatus {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+// 对应 CSS @font-face 的描述符，未设置的项使用浏览器默认值
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FontDescriptors {
+    pub weight: Option<String>,
+    pub style: Option<String>,
+    pub stretch: Option<String>,
+    pub unicode_range: Option<String>,
+    pub display: Option<String>,
+}
+
+// 记录通过 App::load_font 加载的字体及其状态，键为 font-family
+#[derive(Debug, Default)]
+pub struct FontManager {
+    fonts: HashMap<String, FontStatus>,
+}
+
+impl FontManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self, family: &str) -> Option<FontStatus> {
+        self.fonts.get(family).copied()
+    }
+
+    pub fn set_status(&mut self, family: &str, status: FontStatus) {
+        self.fonts.insert(family.to_string(), status);
+    }
+
+    // 没有正在加载的字体
+    pub fn is_ready(&self) -> bool {
+        !self.fonts.values().any(|status| *status == FontStatus::Loading)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MetricKey {
+    font: String,
+    font_size: u64,
+    text: String,
+}
+
+thread_local! {
+    static METRICS: RefCell<HashMap<MetricKey, TextMeasurement>> = RefCell::new(HashMap::new());
+}
+
+// 带缓存的文字测量，font 为 CSS font 简写，基线为 alphabetic
+pub fn measure_text_full(font: &str, font_size: f64, text: &str) -> TextMeasurement {
+    let key = MetricKey {
+        font: font.to_string(),
+        font_size: font_size.to_bits(),
+        text: text.to_string(),
+    };
+    if let Some(measurement) = METRICS.with(|metrics| metrics.borrow().get(&key).copied()) {
+        return measurement;
+    }
+    let measurement = measure_text_metrics(font, font_size, text);
+    METRICS.with(|metrics| {
+        let mut metrics = metrics.borrow_mut();
+        if metrics.len() >= MAX_METRIC_ENTRIES {
+            metrics.clear();
+        }
+        metrics.insert(key, measurement);
+    });
+    measurement
+}
+
+// 字体加载完成后，之前用回退字体测得的结果不再准确
+pub fn invalidate_metrics(family: &str) {
+    METRICS.with(|metrics| {
+        metrics
+            .borrow_mut()
+            .retain(|key, _| !font_uses_family(&key.font, family));
+    });
+}
+
+// font 为 CSS font 简写或 font-family 列表；按子串匹配，多清掉几项缓存没有影响
+pub fn font_uses_family(font: &str, family: &str) -> bool {
+    font.contains(family)
+}
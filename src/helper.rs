@@ -1,20 +1,29 @@
 use nalgebra as na;
 use rand::Rng;
-use serde_json::Value;
+use serde::Serialize;
 use std::sync::atomic::{AtomicU64, Ordering};
-use wasm_bindgen::JsCast;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::js_sys::{Date, Function};
-use web_sys::{console, window, Document, HtmlCanvasElement, SvgMatrix, SvgsvgElement};
-
-use crate::element::Rect;
-use crate::element::Renderable;
+use web_sys::{window, Document, HtmlCanvasElement, SvgMatrix, SvgsvgElement};
+#[cfg(target_arch = "wasm32")]
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::error::{EditingError, EditingResult};
+use crate::renderer::TextMeasurement;
+use crate::log::{log_debug, log_warn};
+
+// 按 JSON 兼容方式转换：map 转成普通对象而不是 JS Map
+pub fn to_js_value<T: Serialize + ?Sized>(value: &T) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap_or(JsValue::NULL)
+}
 
-pub fn create_svg_matrix() -> Result<SvgMatrix, String> {
+pub fn create_svg_matrix() -> EditingResult<SvgMatrix> {
     let document = web_sys::window()
-        .ok_or("Failed to get window")?
+        .ok_or(EditingError::WindowNotFound)?
         .document()
-        .ok_or("Failed to get document")?;
+        .ok_or(EditingError::DocumentNotFound)?;
 
     let svg = create_temporary_svg(&document)?;
 
@@ -22,25 +31,22 @@ pub fn create_svg_matrix() -> Result<SvgMatrix, String> {
 
     document
         .body()
-        .ok_or("Failed to get body")?
-        .remove_child(&svg.dyn_into::<web_sys::Element>().unwrap())
-        .map_err(|_| "Failed to remove temporary SVG element")?;
+        .ok_or_else(|| EditingError::Dom("Failed to get body".to_string()))?
+        .remove_child(&svg)?;
 
     Ok(matrix)
 }
 
-fn create_temporary_svg(document: &Document) -> Result<SvgsvgElement, String> {
+fn create_temporary_svg(document: &Document) -> EditingResult<SvgsvgElement> {
     let svg = document
-        .create_element_ns(Some("http://www.w3.org/2000/svg"), "svg")
-        .map_err(|_| "Failed to create SVG element")?
+        .create_element_ns(Some("http://www.w3.org/2000/svg"), "svg")?
         .dyn_into::<SvgsvgElement>()
-        .map_err(|_| "Failed to cast to SvgsvgElement")?;
+        .map_err(|_| EditingError::Dom("Failed to cast to SvgsvgElement".to_string()))?;
 
     document
         .body()
-        .ok_or("Failed to get body")?
-        .append_child(&svg)
-        .map_err(|_| "Failed to append SVG to body")?;
+        .ok_or_else(|| EditingError::Dom("Failed to get body".to_string()))?
+        .append_child(&svg)?;
 
     Ok(svg)
 }
@@ -62,80 +68,123 @@ pub fn generate_id() -> String {
     format!("{}-{:x}-{:x}", timestamp as u64, random_part, counter)
 }
 
-pub fn normalize_if_needed(mut matrix: na::Matrix1x6<f64>) -> na::Matrix1x6<f64> {
-    const NORMALIZATION_THRESHOLD: f64 = 0.9999;
-    let det = matrix[0] * matrix[3] - matrix[1] * matrix[2];
-    if (det - 1.0).abs() > NORMALIZATION_THRESHOLD {
-        let scale = det.sqrt();
-        for i in 0..4 {
-            matrix[i] /= scale;
-        }
-    }
-
-    matrix
+// 点到线段的最短距离
+pub fn distance_to_segment(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (x, y) = (start.0 + t * dx, start.1 + t * dy);
+    ((point.0 - x).powi(2) + (point.1 - y).powi(2)).sqrt()
 }
 
-pub fn normalize_3x3_if_needed(mut matrix: na::Matrix3<f64>) -> na::Matrix3<f64> {
-    const NORMALIZATION_THRESHOLD: f64 = 0.9999;
-
-    let det = matrix[(0, 0)] * matrix[(1, 1)] - matrix[(0, 1)] * matrix[(1, 0)];
+// 点到折线的最短距离，closed 为 true 时首尾相连
+pub fn distance_to_polyline(point: (f64, f64), points: &[(f64, f64)], closed: bool) -> f64 {
+    let closing = closed
+        .then(|| Some((*points.last()?, *points.first()?)))
+        .flatten();
+    points
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .chain(closing)
+        .map(|(start, end)| distance_to_segment(point, start, end))
+        .fold(f64::INFINITY, f64::min)
+}
 
-    if (det - 1.0).abs() > NORMALIZATION_THRESHOLD {
-        let scale = det.sqrt();
+// 按参数均匀采样三次贝塞尔曲线 [起点, 控制点1, 控制点2, 终点]，包含两个端点
+pub fn sample_cubic(curve: [(f64, f64); 4], steps: usize) -> Vec<(f64, f64)> {
+    let steps = steps.max(1);
+    (0..=steps)
+        .map(|step| {
+            let t = step as f64 / steps as f64;
+            let u = 1.0 - t;
+            let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+            curve
+                .iter()
+                .zip(weights)
+                .fold((0.0, 0.0), |(x, y), (point, weight)| (x + point.0 * weight, y + point.1 * weight))
+        })
+        .collect()
+}
 
-        for i in 0..2 {
-            for j in 0..3 {
-                matrix[(i, j)] /= scale;
-            }
+// 射线法判断点是否在多边形内
+pub fn point_in_polygon(point: (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(last) => *last,
+        None => return false,
+    };
+    for &current in polygon {
+        if (current.1 > point.1) != (previous.1 > point.1)
+            && point.0
+                < (previous.0 - current.0) * (point.1 - current.1) / (previous.1 - current.1) + current.0
+        {
+            inside = !inside;
         }
+        previous = current;
     }
+    inside
+}
 
-    matrix[(2, 0)] = 0.0;
-    matrix[(2, 1)] = 0.0;
-    matrix[(2, 2)] = 1.0;
-
-    matrix
+pub fn now_ms() -> f64 {
+    window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or_else(Date::now)
 }
 
-// 将 1 * 6 转为 3 * 3
-pub fn convert_1x6_to_3x3(matrix: na::Matrix1x6<f64>) -> na::Matrix3<f64> {
-    na::Matrix3::new(
-        matrix[0], matrix[2], matrix[4], matrix[1], matrix[3], matrix[5], 0.0, 0.0, 1.0,
-    )
+// 元素计算包围盒时拿不到渲染器，用一个独立的 2d 上下文测量文字，基线为默认的 alphabetic
+#[cfg(target_arch = "wasm32")]
+pub fn measure_text_metrics(font: &str, font_size: f64, text: &str) -> TextMeasurement {
+    thread_local! {
+        static MEASURE_CONTEXT: Option<OffscreenCanvasRenderingContext2d> = OffscreenCanvas::new(1, 1)
+            .ok()
+            .and_then(|canvas| canvas.get_context("2d").ok().flatten())
+            .and_then(|context| context.dyn_into().ok());
+    }
+    MEASURE_CONTEXT.with(|context| match context {
+        Some(context) => {
+            context.set_font(font);
+            context
+                .measure_text(text)
+                .map(|metrics| TextMeasurement::from(&metrics))
+                .unwrap_or_else(|_| estimate_text_metrics(font_size, text))
+        }
+        None => estimate_text_metrics(font_size, text),
+    })
 }
 
-pub fn convert_3x3_to_1x6(matrix: na::Matrix3<f64>) -> na::Matrix1x6<f64> {
-    na::Matrix1x6::new(
-        matrix[(0, 0)],
-        matrix[(1, 0)],
-        matrix[(0, 1)],
-        matrix[(1, 1)],
-        matrix[(0, 2)],
-        matrix[(1, 2)],
-    )
+// 非 wasm 目标（单元测试）下无法创建画布
+#[cfg(not(target_arch = "wasm32"))]
+pub fn measure_text_metrics(_font: &str, font_size: f64, text: &str) -> TextMeasurement {
+    estimate_text_metrics(font_size, text)
 }
 
-pub fn get_rotation_matrix(angle_radians: f64) -> na::Matrix3<f64> {
-    const EPSILON: f64 = 1e-6;
-    if angle_radians.abs() < EPSILON {
-        let sin = angle_radians;
-        let cos = 1.0 - 0.5 * angle_radians * angle_radians;
-        na::Matrix3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
-    } else {
-        let (sin, cos) = angle_radians.sin_cos();
-        na::Matrix3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
+fn estimate_text_metrics(font_size: f64, text: &str) -> TextMeasurement {
+    let width = text.chars().count() as f64 * font_size * 0.6;
+    TextMeasurement {
+        width,
+        actual_bounding_box_left: 0.0,
+        actual_bounding_box_right: width,
+        actual_bounding_box_ascent: font_size * 0.8,
+        actual_bounding_box_descent: font_size * 0.2,
+        font_bounding_box_ascent: font_size * 0.8,
+        font_bounding_box_descent: font_size * 0.2,
     }
 }
 
 pub fn print_matrice(name: &str, matrix: na::Matrix1x6<f64>) {
-    console::log_1(&JsValue::from_str(&format!(
+    log_debug!(
         "{} offset {},{}, {}, {}, {}, {}",
         name, matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5]
-    )));
+    );
 }
 
 pub fn print_matrix_3x3(name: &str, matrix: na::Matrix3<f64>) {
-    console::log_1(&JsValue::from_str(&format!(
+    log_debug!(
         "{} matrix:\n[{:.6}, {:.6}, {:.6}\n {:.6}, {:.6}, {:.6}\n {:.6}, {:.6}, {:.6}]",
         name,
         matrix[(0, 0)],
@@ -147,27 +196,23 @@ pub fn print_matrix_3x3(name: &str, matrix: na::Matrix3<f64>) {
         matrix[(2, 0)],
         matrix[(2, 1)],
         matrix[(2, 2)]
-    )));
+    );
 }
 
-pub fn get_canvas_css_size(canvas: &HtmlCanvasElement) -> Result<(u32, u32), JsValue> {
-    let window = window().expect("no global `window` exists");
+pub fn get_canvas_css_size(canvas: &HtmlCanvasElement) -> EditingResult<(u32, u32)> {
+    let window = window().ok_or(EditingError::WindowNotFound)?;
     let computed_style = window
         .get_computed_style(canvas)?
-        .expect("failed to get computed style");
+        .ok_or_else(|| EditingError::Dom("Failed to get computed style".to_string()))?;
 
     let width = computed_style.get_property_value("width")?;
     let height = computed_style.get_property_value("height")?;
 
-    console::log_1(&JsValue::from_str(&format!("计算后的宽度: '{}'", width)));
-    console::log_1(&JsValue::from_str(&format!("计算后的高度: '{}'", height)));
+    log_debug!("canvas computed size: '{}' x '{}'", width, height);
 
     let parse_px = |s: &str| -> f64 {
         s.trim_end_matches("px").parse().unwrap_or_else(|_| {
-            console::log_1(&JsValue::from_str(&format!(
-                "无法解析 '{}', 使用 offsetWidth/Height",
-                s
-            )));
+            log_warn!("Failed to parse '{}', falling back to offsetWidth/Height", s);
             -1.0
         })
     };
@@ -182,38 +227,50 @@ pub fn get_canvas_css_size(canvas: &HtmlCanvasElement) -> Result<(u32, u32), JsV
         css_height = canvas.offset_height() as f64;
     }
 
-    console::log_1(&JsValue::from_str(&format!(
-        "最终宽度: {}, 高度: {}",
-        css_width, css_height
-    )));
+    log_debug!("canvas css size: {} x {}", css_width, css_height);
 
     Ok((css_width as u32, css_height as u32))
 }
 
-pub fn get_canvas(canvas_id: &str) -> Result<HtmlCanvasElement, String> {
-    let window = window().ok_or("Failed to get window")?;
-    let document = window.document().ok_or("Failed to get document")?;
+pub fn get_canvas(canvas_id: &str) -> EditingResult<HtmlCanvasElement> {
+    let window = window().ok_or(EditingError::WindowNotFound)?;
+    let document = window.document().ok_or(EditingError::DocumentNotFound)?;
     let element = document
         .get_element_by_id(canvas_id)
-        .ok_or_else(|| format!("Failed to find canvas with id: {}", canvas_id))?;
+        .ok_or_else(|| EditingError::CanvasNotFound(canvas_id.to_string()))?;
 
     element
         .dyn_into::<HtmlCanvasElement>()
-        .map_err(|_| format!("Element with id '{}' is not a canvas", canvas_id))
+        .map_err(|_| EditingError::NotACanvas(canvas_id.to_string()))
 }
 
-pub fn get_window_dpr() -> Result<f64, JsValue> {
-    let window = window().ok_or("Failed to get window")?;
+pub fn get_window_dpr() -> EditingResult<f64> {
+    let window = window().ok_or(EditingError::WindowNotFound)?;
     let device_pixel_ratio = window.device_pixel_ratio();
-    console::log_1(&JsValue::from_str(&format!(
-        "device_pixel_ratio: {}",
-        device_pixel_ratio
-    )));
+    log_debug!("device_pixel_ratio: {}", device_pixel_ratio);
     Ok(device_pixel_ratio)
 }
 
 pub mod easing {
     use std::f64::consts::PI;
+
+    // 按 CSS 风格的名字查找缓动函数，"ease-in"/"ease-out"/"ease-in-out" 为二次曲线
+    pub fn by_name(name: &str) -> Option<fn(f64) -> f64> {
+        let easing: fn(f64) -> f64 = match name {
+            "linear" => linear,
+            "ease-in" | "ease-in-quad" => ease_in_quad,
+            "ease-out" | "ease-out-quad" => ease_out_quad,
+            "ease" | "ease-in-out" | "ease-in-out-quad" => ease_in_out_quad,
+            "ease-in-cubic" => ease_in_cubic,
+            "ease-out-cubic" => ease_out_cubic,
+            "ease-in-out-cubic" => ease_in_out_cubic,
+            "ease-in-elastic" => ease_in_elastic,
+            "ease-out-elastic" => ease_out_elastic,
+            _ => return None,
+        };
+        Some(easing)
+    }
+
     pub fn linear(t: f64) -> f64 {
         t
     }
@@ -262,20 +319,3 @@ pub mod easing {
         2.0_f64.powf(-10.0 * t) * (t - p / 4.0) * (2.0 * PI / p).sin() + 1.0
     }
 }
-
-
-pub fn create_element(element_type: &str, data: &Value) -> Result<Box<dyn Renderable>, JsValue> {
-    let element = match element_type {
-        "rect" => {
-            // 反序列化 data
-            let rect = serde_json::from_value::<Rect>(data.clone());
-            match rect {
-                Ok(rect) => Box::new(rect),
-                Err(e) => return Err(JsValue::from_str(&format!("Failed to create rect: {}", e))),
-            }
-        },
-        _ => return Err(JsValue::from_str(&format!("Unsupported element type: {}", element_type))),
-    };
-
-    Ok(element)
-}
@@ -1,12 +1,31 @@
-use std::{cell::RefCell, fmt::Debug, rc::Rc};
+use std::{cell::{Cell, RefCell}, fmt::Debug, rc::Rc};
 use serde_json::Value;
-use web_sys::{console, js_sys};
 use wasm_timer::Instant;
-use crate::{app::App, helper::create_element};
+use crate::{app::App, error::{EditingError, EditingResult}, events::AppEvent, element::create_element, helper::to_js_value, log::log_error};
+use wasm_bindgen_futures::spawn_local;
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
 
-#[derive(Clone, Debug)]
+pub const DEFAULT_MAX_UNITS: usize = 200;
+pub const DEFAULT_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+const HISTORY_FORMAT_VERSION: u32 = 1;
+
+// 非 wasm 目标（单元测试）下无法调用 js_sys
+#[cfg(target_arch = "wasm32")]
+fn timestamp_now() -> f64 {
+    web_sys::js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn timestamp_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ObjectHistoryItem {
     pub undo_data: Value, 
     pub redo_data: Value,
@@ -19,13 +38,13 @@ impl ObjectHistoryItem {
         Self {
             undo_data,
             redo_data,
-            timestamp: js_sys::Date::now(),
+            timestamp: timestamp_now(),
             object_id,
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SceneHistoryItem {
     pub undo_data: Value,
     pub redo_data: Value,
@@ -37,12 +56,32 @@ impl SceneHistoryItem {
         Self {
             undo_data,
             redo_data,
-            timestamp: js_sys::Date::now(),
+            timestamp: timestamp_now(),
+        }
+    }
+}
+
+// 共享样式的定义，undo/redo 为 Null 表示样式不存在
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StyleHistoryItem {
+    pub style_id: String,
+    pub undo_data: Value,
+    pub redo_data: Value,
+    pub timestamp: f64,
+}
+
+impl StyleHistoryItem {
+    pub fn new(style_id: String, undo_data: Value, redo_data: Value) -> Self {
+        Self {
+            style_id,
+            undo_data,
+            redo_data,
+            timestamp: timestamp_now(),
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ElementHistoryItem {
     pub element_id: String,
     pub element_type: String,
@@ -56,22 +95,160 @@ impl ElementHistoryItem {
             element_id,
             element_type,
             element_data,
-            timestamp: js_sys::Date::now(),
+            timestamp: timestamp_now(),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum HistoryItem {
     ObjectUpdate(ObjectHistoryItem),
     SceneUpdate(SceneHistoryItem),
     AddElement(ElementHistoryItem),
     RemoveElement(ElementHistoryItem),
+    StyleUpdate(StyleHistoryItem),
+}
+
+impl HistoryItem {
+    // 同一对象同一组属性的连续修改合并为一条，保留最早的 undo 数据
+    fn merge(&mut self, next: &HistoryItem) -> bool {
+        match (self, next) {
+            (HistoryItem::ObjectUpdate(current), HistoryItem::ObjectUpdate(next))
+                if current.object_id == next.object_id
+                    && same_keys(&current.redo_data, &next.redo_data) =>
+            {
+                current.redo_data = next.redo_data.clone();
+                current.timestamp = next.timestamp;
+                true
+            }
+            (HistoryItem::SceneUpdate(current), HistoryItem::SceneUpdate(next)) => {
+                current.redo_data = next.redo_data.clone();
+                current.timestamp = next.timestamp;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // 撤销一条记录等同于应用它的反向记录
+    pub fn inverse(&self) -> HistoryItem {
+        match self {
+            HistoryItem::ObjectUpdate(item) => HistoryItem::ObjectUpdate(ObjectHistoryItem {
+                undo_data: item.redo_data.clone(),
+                redo_data: item.undo_data.clone(),
+                ..item.clone()
+            }),
+            HistoryItem::SceneUpdate(item) => HistoryItem::SceneUpdate(SceneHistoryItem {
+                undo_data: item.redo_data.clone(),
+                redo_data: item.undo_data.clone(),
+                ..item.clone()
+            }),
+            HistoryItem::AddElement(item) => HistoryItem::RemoveElement(item.clone()),
+            HistoryItem::RemoveElement(item) => HistoryItem::AddElement(item.clone()),
+            HistoryItem::StyleUpdate(item) => HistoryItem::StyleUpdate(StyleHistoryItem {
+                undo_data: item.redo_data.clone(),
+                redo_data: item.undo_data.clone(),
+                ..item.clone()
+            }),
+        }
+    }
+
+    // 估算占用的字节数，只用于历史记录的容量限制
+    fn approx_size(&self) -> usize {
+        let values = match self {
+            HistoryItem::ObjectUpdate(item) => vec![&item.undo_data, &item.redo_data],
+            HistoryItem::SceneUpdate(item) => vec![&item.undo_data, &item.redo_data],
+            HistoryItem::AddElement(item) | HistoryItem::RemoveElement(item) => {
+                vec![&item.element_data]
+            }
+            HistoryItem::StyleUpdate(item) => vec![&item.undo_data, &item.redo_data],
+        };
+        std::mem::size_of::<HistoryItem>()
+            + values.iter().map(|value| value.to_string().len()).sum::<usize>()
+    }
+}
+
+fn same_keys(a: &Value, b: &Value) -> bool {
+    match (a.as_object(), b.as_object()) {
+        (Some(a), Some(b)) => a.len() == b.len() && a.keys().all(|key| b.contains_key(key)),
+        _ => false,
+    }
 }
 
+// 不同对象的属性修改互不影响，可以越过它们与同一对象更早的修改合并，
+// 多个对象同时做动画时每帧交替写入，不会因此堆积出大量记录
+fn push_item(items: &mut Vec<HistoryItem>, item: HistoryItem) {
+    for existing in items.iter_mut().rev() {
+        let skip = match (&*existing, &item) {
+            (HistoryItem::ObjectUpdate(existing), HistoryItem::ObjectUpdate(next)) => {
+                existing.object_id != next.object_id
+            }
+            _ => false,
+        };
+        if !skip {
+            if existing.merge(&item) {
+                return;
+            }
+            break;
+        }
+    }
+    items.push(item);
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct HistoryUnit {
     items: Vec<HistoryItem>,
     timestamp: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SerializedHistoryRef<'a> {
+    version: u32,
+    undo: &'a [HistoryUnit],
+    redo: &'a [HistoryUnit],
+}
+
+// 带版本号的撤销/重做栈，用于页面刷新后恢复
+#[derive(Deserialize)]
+struct SerializedHistory {
+    version: u32,
+    undo: Vec<HistoryUnit>,
+    redo: Vec<HistoryUnit>,
+}
+
+// 进行中的事务，提交后整体作为一个历史单元
+struct Transaction {
+    label: String,
+    items: Vec<HistoryItem>,
+}
+
+// 历史记录回放的目标，由 App 实现
+pub trait HistoryTarget {
+    fn apply_history_item(&self, item: &HistoryItem, is_undo: bool) -> EditingResult<()>;
+    fn request_render(&self);
+}
+
+// 作用域内标记为回放中，提前返回时也能复位；用计数支持 seek 这类嵌套调用
+pub struct ReplayGuard {
+    depth: Rc<Cell<usize>>,
+}
+
+impl ReplayGuard {
+    fn new(depth: &Rc<Cell<usize>>) -> Self {
+        depth.set(depth.get() + 1);
+        Self {
+            depth: depth.clone(),
+        }
+    }
+}
+
+impl Drop for ReplayGuard {
+    fn drop(&mut self) {
+        self.depth.set(self.depth.get() - 1);
+    }
 }
 
 #[derive(Clone)]
@@ -80,10 +257,16 @@ pub struct History {
     undo_stack: Rc<RefCell<Vec<HistoryUnit>>>,
     redo_stack: Rc<RefCell<Vec<HistoryUnit>>>,
     current_unit: Rc<RefCell<Option<HistoryUnit>>>,
+    transactions: Rc<RefCell<Vec<Transaction>>>,
     last_push_time: Rc<RefCell<Instant>>,
+    change_pending: Rc<Cell<bool>>,
+
+    // 0 表示不限制
+    max_units: usize,
+    max_bytes: usize,
 
-    is_undoing: bool,
-    is_redoing: bool,
+    // 大于 0 时正在回放历史，期间产生的 push 都会被忽略
+    replay_depth: Rc<Cell<usize>>,
 }
 
 impl Debug for History {
@@ -110,22 +293,30 @@ impl History {
             redo_stack: Rc::new(RefCell::new(Vec::new())),
             app: None,
             current_unit: Rc::new(RefCell::new(None)),
+            transactions: Rc::new(RefCell::new(Vec::new())),
             last_push_time: Rc::new(RefCell::new(Instant::now())),
+            change_pending: Rc::new(Cell::new(false)),
 
-            is_undoing: false,
-            is_redoing: false,
+            max_units: DEFAULT_MAX_UNITS,
+            max_bytes: DEFAULT_MAX_BYTES,
+
+            replay_depth: Rc::new(Cell::new(0)),
         }
     }
 
     pub fn attach(&mut self, app: &App) {
         self.app = Some(app.clone());
     }
+
+    pub fn detach(&mut self) {
+        self.app = None;
+    }
 }
 
 impl History {
     pub fn get_history_summary(&self) -> Result<JsValue, JsValue> {
         let mut summary = Vec::new();
-        for unit in self.undo_stack.borrow().iter() {
+        for (index, unit) in self.undo_stack.borrow().iter().enumerate() {
             let mut item_counts = std::collections::HashMap::new();
             for item in &unit.items {
                 *item_counts.entry(match item {
@@ -133,15 +324,19 @@ impl History {
                     HistoryItem::SceneUpdate(_) => "Scene updates",
                     HistoryItem::AddElement(_) => "Added elements",
                     HistoryItem::RemoveElement(_) => "Removed elements",
+                    HistoryItem::StyleUpdate(_) => "Style updates",
                 }).or_insert(0) += 1;
             }
 
-            let description = if unit.items.len() == 1 {
+            let description = if let Some(label) = &unit.label {
+                label.clone()
+            } else if unit.items.len() == 1 {
                 match &unit.items[0] {
                     HistoryItem::ObjectUpdate(item) => format!("Object update: {}", item.object_id),
                     HistoryItem::SceneUpdate(_) => "Scene update".to_string(),
                     HistoryItem::AddElement(item) => format!("Add element: {}", item.element_id),
                     HistoryItem::RemoveElement(item) => format!("Remove element: {}", item.element_id),
+                    HistoryItem::StyleUpdate(item) => format!("Style update: {}", item.style_id),
                 }
             } else {
                 let details: Vec<String> = item_counts.iter()
@@ -151,7 +346,9 @@ impl History {
             };
 
             summary.push(HistorySummaryItem {
+                index,
                 timestamp: unit.timestamp,
+                label: unit.label.clone(),
                 description,
                 item_count: unit.items.len(),
             });
@@ -160,7 +357,14 @@ impl History {
     }
 
     pub fn push(&mut self, item: HistoryItem) {
-        if self.is_undoing || self.is_redoing {
+        if self.is_replaying() {
+            return;
+        }
+        self.notify_document_change();
+        self.broadcast(std::slice::from_ref(&item), false);
+
+        if let Some(transaction) = self.transactions.borrow_mut().last_mut() {
+            push_item(&mut transaction.items, item);
             return;
         }
 
@@ -175,15 +379,17 @@ impl History {
             self.finalize_current_unit();
             *self.current_unit.borrow_mut() = Some(HistoryUnit { 
                 items: vec![item], 
-                timestamp: js_sys::Date::now(),
+                timestamp: timestamp_now(),
+                label: None,
             });
         } else {
-            self.current_unit.borrow_mut().as_mut().unwrap().items.push(item);
+            push_item(&mut self.current_unit.borrow_mut().as_mut().unwrap().items, item);
         }
 
         self.redo_stack.borrow_mut().clear();
 
         *self.last_push_time.borrow_mut() = now;
+        self.notify_change();
     }
 
     pub fn finalize_current_unit(&mut self) {
@@ -193,13 +399,101 @@ impl History {
                 self.undo_stack.borrow_mut().push(unit);
             }
         }
+        drop(current_unit);
+        self.schedule_compaction();
+    }
+
+    // 裁剪需要估算每个单元的大小，附加到 App 后放到空闲时执行
+    fn schedule_compaction(&self) {
+        let Some(app) = self.app.clone() else {
+            self.enforce_limits();
+            return;
+        };
+        let history = app.history.clone();
+        app.schedule_idle("history_compaction", move || {
+            if let Ok(history) = history.try_borrow() {
+                history.enforce_limits();
+            }
+        });
+    }
+
+    pub fn set_limits(&mut self, max_units: usize, max_bytes: usize) {
+        self.max_units = max_units;
+        self.max_bytes = max_bytes;
+        self.enforce_limits();
+    }
+
+    // 可撤销和可重做的单元数，正在合并的单元尚未计入
+    pub fn depth(&self) -> (usize, usize) {
+        (self.undo_stack.borrow().len(), self.redo_stack.borrow().len())
+    }
+
+    pub fn approx_size(&self) -> usize {
+        let unit_size = |unit: &HistoryUnit| unit.items.iter().map(HistoryItem::approx_size).sum::<usize>();
+        self.undo_stack.borrow().iter().map(unit_size).sum::<usize>()
+            + self.redo_stack.borrow().iter().map(unit_size).sum::<usize>()
+    }
+
+    // 超出数量或容量限制时丢弃最早的撤销单元，至少保留最近一个
+    fn enforce_limits(&self) {
+        let mut undo_stack = self.undo_stack.borrow_mut();
+        let mut excess = if self.max_units > 0 {
+            undo_stack.len().saturating_sub(self.max_units)
+        } else {
+            0
+        };
+
+        if self.max_bytes > 0 {
+            let sizes: Vec<usize> = undo_stack
+                .iter()
+                .map(|unit| unit.items.iter().map(HistoryItem::approx_size).sum())
+                .collect();
+            let mut total: usize = sizes.iter().sum::<usize>()
+                + self
+                    .redo_stack
+                    .borrow()
+                    .iter()
+                    .flat_map(|unit| unit.items.iter())
+                    .map(HistoryItem::approx_size)
+                    .sum::<usize>();
+            total -= sizes[..excess].iter().sum::<usize>();
+            while total > self.max_bytes && excess + 1 < sizes.len() {
+                total -= sizes[excess];
+                excess += 1;
+            }
+        }
+
+        if excess > 0 {
+            undo_stack.drain(..excess);
+        }
+    }
+
+    // 给正在合并的历史单元命名；没有时命名最近一个已完成的单元
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        let label = label.into();
+        if let Some(unit) = self.current_unit.borrow_mut().as_mut() {
+            unit.label = Some(label);
+            return;
+        }
+        if let Some(unit) = self.undo_stack.borrow_mut().last_mut() {
+            unit.label = Some(label);
+        }
+        self.notify_change();
+    }
+
+    // 以当前状态结束正在合并的单元并命名，作为可跳转的检查点
+    pub fn checkpoint(&mut self, label: impl Into<String>) {
+        self.set_label(label);
+        self.finalize_current_unit();
+        self.notify_change();
     }
 
     pub fn ensure_current_unit_finalized(&mut self) {
         self.finalize_current_unit();
     }
 
-    fn apply_history_unit(&self, app: &App, unit: &HistoryUnit, is_undo: bool) {
+    fn apply_history_unit(&self, target: &dyn HistoryTarget, unit: &HistoryUnit, is_undo: bool) {
+        let _guard = ReplayGuard::new(&self.replay_depth);
         let items_iter: Box<dyn Iterator<Item = &HistoryItem>> = if is_undo {
             Box::new(unit.items.iter().rev())
         } else {
@@ -207,87 +501,111 @@ impl History {
         };
 
         for item in items_iter {
-            match item {
-                HistoryItem::ObjectUpdate(item) => {
-                    let data = if is_undo { &item.undo_data } else { &item.redo_data };
-                    app.object_manager.borrow_mut().update_object(item.object_id.clone(), data.clone());
-                }
-                HistoryItem::SceneUpdate(item) => {
-                    let data = if is_undo { &item.undo_data } else { &item.redo_data };
-                    app.scene_manager.borrow_mut().update_scene(data.clone());
-                }
-                HistoryItem::AddElement(item) => {
-                    if is_undo {
-                        app.object_manager.borrow_mut().remove(&item.element_id);
-                    } else {
-                        let data = item.element_data.clone();
-                        let element_type = item.element_type.clone();
-
-                        match create_element(&element_type, &data) {
-                            Ok(element) => {
-                                app.object_manager.borrow_mut().add(element);
-                            },
-                            Err(e) => console::error_1(&format!("Failed to create element: {:?}", e).into()),
-                        }
-                    }
-                }
-                HistoryItem::RemoveElement(item) => {
-                    if is_undo {
-                        let data = item.element_data.clone();
-                        let element_type = item.element_type.clone();
-
-                        match create_element(&element_type, &data) {
-                            Ok(element) => {
-                                app.object_manager.borrow_mut().add(element);
-                            },
-                            Err(e) => console::error_1(&format!("Failed to create element: {:?}", e).into()),
-                        }
-                    } else {
-                        app.object_manager.borrow_mut().remove(&item.element_id);
-                    }
-                }
+            if let Err(e) = target.apply_history_item(item, is_undo) {
+                log_error!("Failed to apply history item: {}", e);
             }
         }
     }
 
+    pub fn is_replaying(&self) -> bool {
+        self.replay_depth.get() > 0
+    }
+
+    // guard 存在期间的修改不记录历史，由调用方之后自行记录
+    pub fn suspend(&self) -> ReplayGuard {
+        ReplayGuard::new(&self.replay_depth)
+    }
+
     pub fn undo(&mut self) -> bool {
-        self.is_undoing = true;
+        let Some(app) = self.app.clone() else {
+            return false;
+        };
+        self.step(&app, true)
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(app) = self.app.clone() else {
+            return false;
+        };
+        self.step(&app, false)
+    }
+
+    // 从一个栈弹出单元回放后压入另一个栈
+    fn step(&mut self, target: &dyn HistoryTarget, is_undo: bool) -> bool {
         self.ensure_current_unit_finalized();
-        if let Some(app) = &self.app {
-            let mut undo_stack = self.undo_stack.borrow_mut();
-            let mut redo_stack = self.redo_stack.borrow_mut();
-            
-            if let Some(unit) = undo_stack.pop() {
-                self.apply_history_unit(app, &unit, true);
-                redo_stack.push(unit);
-                app.request_render();
-                return true;
-            }
+        let (from, to) = if is_undo {
+            (&self.undo_stack, &self.redo_stack)
+        } else {
+            (&self.redo_stack, &self.undo_stack)
+        };
+
+        let Some(unit) = from.borrow_mut().pop() else {
+            return false;
+        };
+        self.apply_history_unit(target, &unit, is_undo);
+        self.broadcast(&unit.items, is_undo);
+        to.borrow_mut().push(unit);
+
+        target.request_render();
+        self.notify_change();
+        self.notify_document_change();
+        true
+    }
+
+    // 撤销到摘要中第 index 个单元刚完成时的状态
+    pub fn jump_to_checkpoint(&mut self, index: usize) -> bool {
+        self.ensure_current_unit_finalized();
+        if index >= self.undo_stack.borrow().len() {
+            return false;
         }
-        self.is_undoing = false;
-        false
+        self.seek(index + 1)
     }
 
-    pub fn redo(&mut self) -> bool {
-        self.is_redoing = true;
+    // 按时间顺序排列的全部历史单元，position 之前的已生效
+    pub fn get_timeline(&mut self) -> HistoryTimeline {
         self.ensure_current_unit_finalized();
-        if let Some(app) = &self.app {
-            let mut undo_stack = self.undo_stack.borrow_mut();
-            let mut redo_stack = self.redo_stack.borrow_mut();
-            
-            if let Some(unit) = redo_stack.pop() {
-                self.apply_history_unit(app, &unit, false);
-                undo_stack.push(unit);
-                app.request_render();
-                return true;
+        let undo_stack = self.undo_stack.borrow();
+        let redo_stack = self.redo_stack.borrow();
+        let entry = |unit: &HistoryUnit, applied: bool| TimelineEntry {
+            timestamp: unit.timestamp,
+            label: unit.label.clone(),
+            item_count: unit.items.len(),
+            applied,
+        };
+
+        HistoryTimeline {
+            position: undo_stack.len(),
+            entries: undo_stack
+                .iter()
+                .map(|unit| entry(unit, true))
+                .chain(redo_stack.iter().rev().map(|unit| entry(unit, false)))
+                .collect(),
+        }
+    }
+
+    // 撤销或重做到恰好应用了前 position 个单元的状态，0 为最初状态
+    pub fn seek(&mut self, position: usize) -> bool {
+        self.ensure_current_unit_finalized();
+        let total = self.undo_stack.borrow().len() + self.redo_stack.borrow().len();
+        if position > total {
+            return false;
+        }
+
+        loop {
+            let current = self.undo_stack.borrow().len();
+            let moved = match current.cmp(&position) {
+                std::cmp::Ordering::Greater => self.undo(),
+                std::cmp::Ordering::Less => self.redo(),
+                std::cmp::Ordering::Equal => return true,
+            };
+            if !moved {
+                return false;
             }
         }
-        self.is_redoing = false;
-        false
     }
 
     pub fn undo_to_time(&mut self, target_time: f64) -> bool {
-        self.is_undoing = true;
+        let _guard = ReplayGuard::new(&self.replay_depth);
         self.ensure_current_unit_finalized();
         if let Some(app) = &self.app {
             let mut undo_stack = self.undo_stack.borrow_mut();
@@ -301,14 +619,14 @@ impl History {
             self.apply_operations_to_current_state(app, &undo_stack, true);
 
             app.request_render();
+            self.notify_change();
             return true;
         }
-        self.is_undoing = false;
         false
     }
 
     pub fn redo_to_time(&mut self, target_time: f64) -> bool {
-        self.is_redoing = true;
+        let _guard = ReplayGuard::new(&self.replay_depth);
         if let Some(app) = &self.app {
             let mut undo_stack = self.undo_stack.borrow_mut();
             let mut redo_stack = self.redo_stack.borrow_mut();
@@ -323,9 +641,9 @@ impl History {
             self.apply_operations_to_current_state(app, &undo_stack, false);
 
             app.request_render();
+            self.notify_change();
             return true;
         }
-        self.is_redoing = false;
         false
     }
 
@@ -336,6 +654,67 @@ impl History {
         }
     }
     
+    pub fn in_transaction(&self) -> bool {
+        !self.transactions.borrow().is_empty()
+    }
+
+    // 开始一个事务；支持嵌套，内层提交后并入外层
+    pub fn begin_transaction(&mut self, label: impl Into<String>) {
+        if !self.in_transaction() {
+            self.finalize_current_unit();
+        }
+        self.transactions.borrow_mut().push(Transaction {
+            label: label.into(),
+            items: Vec::new(),
+        });
+    }
+
+    pub fn commit(&mut self) -> bool {
+        let Some(transaction) = self.transactions.borrow_mut().pop() else {
+            return false;
+        };
+
+        if let Some(parent) = self.transactions.borrow_mut().last_mut() {
+            parent.items.extend(transaction.items);
+            return true;
+        }
+
+        if !transaction.items.is_empty() {
+            self.undo_stack.borrow_mut().push(HistoryUnit {
+                items: transaction.items,
+                timestamp: timestamp_now(),
+                label: Some(transaction.label),
+            });
+            self.redo_stack.borrow_mut().clear();
+            *self.last_push_time.borrow_mut() = Instant::now();
+            self.enforce_limits();
+            self.notify_change();
+        }
+        true
+    }
+
+    // 撤销当前（最内层）事务中已经执行的操作并丢弃它们
+    pub fn rollback(&mut self) -> bool {
+        let Some(transaction) = self.transactions.borrow_mut().pop() else {
+            return false;
+        };
+
+        if let Some(app) = self.app.clone() {
+            let unit = HistoryUnit {
+                items: transaction.items,
+                timestamp: timestamp_now(),
+                label: Some(transaction.label),
+            };
+            self.apply_history_unit(&app, &unit, true);
+            app.request_render();
+            if !unit.items.is_empty() {
+                app.notify_document_change();
+                app.broadcast(&unit.items, true);
+            }
+        }
+        true
+    }
+
     pub fn can_undo(&self) -> bool {
         !self.undo_stack.borrow().is_empty() || self.current_unit.borrow().is_some()
     }
@@ -348,13 +727,272 @@ impl History {
         self.undo_stack.borrow_mut().clear();
         self.redo_stack.borrow_mut().clear();
         *self.current_unit.borrow_mut() = None;
+        self.transactions.borrow_mut().clear();
+        *self.last_push_time.borrow_mut() = Instant::now();
+        self.notify_change();
+    }
+
+    pub fn serialize(&mut self) -> EditingResult<String> {
+        self.ensure_current_unit_finalized();
+        let undo_stack = self.undo_stack.borrow();
+        let redo_stack = self.redo_stack.borrow();
+        let history = SerializedHistoryRef {
+            version: HISTORY_FORMAT_VERSION,
+            undo: &undo_stack,
+            redo: &redo_stack,
+        };
+        Ok(serde_json::to_string(&history)?)
+    }
+
+    // 替换当前的撤销/重做栈；对应的文档状态需要由调用方先行恢复
+    pub fn deserialize(&mut self, data: &str) -> EditingResult<()> {
+        let history: SerializedHistory = serde_json::from_str(data)?;
+        if history.version != HISTORY_FORMAT_VERSION {
+            return Err(EditingError::UnsupportedHistoryVersion(history.version));
+        }
+
+        *self.current_unit.borrow_mut() = None;
+        self.transactions.borrow_mut().clear();
+        *self.undo_stack.borrow_mut() = history.undo;
+        *self.redo_stack.borrow_mut() = history.redo;
         *self.last_push_time.borrow_mut() = Instant::now();
+        self.enforce_limits();
+        self.notify_change();
+        Ok(())
+    }
+
+    // 应用不来自本地编辑的操作（其他客户端的修改、操作日志回放），期间不记录历史，也不会再被同步出去
+    pub fn apply_unrecorded(&self, target: &dyn HistoryTarget, items: Vec<HistoryItem>) {
+        let unit = HistoryUnit {
+            items,
+            timestamp: timestamp_now(),
+            label: None,
+        };
+        self.apply_history_unit(target, &unit, false);
+    }
+
+    fn broadcast(&self, items: &[HistoryItem], is_undo: bool) {
+        if let Some(app) = &self.app {
+            app.broadcast(items, is_undo);
+        }
     }
+
+    fn notify_document_change(&self) {
+        if let Some(app) = &self.app {
+            app.notify_document_change();
+        }
+    }
+
+    // 栈变化后通知 JS；延后到当前调用结束，避免监听器回调时 History 仍被借用
+    fn notify_change(&self) {
+        let Some(app) = self.app.clone() else {
+            return;
+        };
+        if self.change_pending.replace(true) {
+            return;
+        }
+
+        let change_pending = self.change_pending.clone();
+        spawn_local(async move {
+            change_pending.set(false);
+            let payload = {
+                let Ok(history) = app.history.try_borrow() else {
+                    return;
+                };
+                let (undo_count, redo_count) = history.depth();
+                let state = HistoryState {
+                    can_undo: history.can_undo(),
+                    can_redo: history.can_redo(),
+                    undo_count,
+                    redo_count,
+                };
+                to_js_value(&state)
+            };
+            let _ = app.events.emit(AppEvent::HISTORY_CHANGE.into(), &payload);
+            app.schedule_autosave();
+        });
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryState {
+    can_undo: bool,
+    can_redo: bool,
+    undo_count: usize,
+    redo_count: usize,
+}
+
+impl HistoryTarget for App {
+    fn apply_history_item(&self, item: &HistoryItem, is_undo: bool) -> EditingResult<()> {
+        match item {
+            HistoryItem::ObjectUpdate(item) => {
+                let data = if is_undo { &item.undo_data } else { &item.redo_data };
+                self.object_manager.borrow_mut().update_object(item.object_id.clone(), data.clone())?;
+            }
+            HistoryItem::SceneUpdate(item) => {
+                let data = if is_undo { &item.undo_data } else { &item.redo_data };
+                self.scene_manager.borrow_mut().update_scene(data.clone())?;
+            }
+            HistoryItem::AddElement(item) => {
+                if is_undo {
+                    self.object_manager.borrow_mut().remove(&item.element_id);
+                } else {
+                    let element = create_element(&item.element_type, &item.element_data)?;
+                    self.object_manager.borrow_mut().add(element)?;
+                }
+            }
+            HistoryItem::RemoveElement(item) => {
+                if is_undo {
+                    let element = create_element(&item.element_type, &item.element_data)?;
+                    self.object_manager.borrow_mut().add(element)?;
+                } else {
+                    self.object_manager.borrow_mut().remove(&item.element_id);
+                }
+            }
+            HistoryItem::StyleUpdate(item) => {
+                let data = if is_undo { &item.undo_data } else { &item.redo_data };
+                self.apply_style_data(&item.style_id, data)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn request_render(&self) {
+        App::request_render(self);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    pub timestamp: f64,
+    pub label: Option<String>,
+    pub item_count: usize,
+    pub applied: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryTimeline {
+    pub position: usize,
+    pub entries: Vec<TimelineEntry>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct HistorySummaryItem {
+    index: usize,
     timestamp: f64,
+    label: Option<String>,
     description: String,
     item_count: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Default)]
+    struct RecordingTarget {
+        applied: RefCell<Vec<(Value, bool)>>,
+        renders: Cell<usize>,
+    }
+
+    impl HistoryTarget for RecordingTarget {
+        fn apply_history_item(&self, item: &HistoryItem, is_undo: bool) -> EditingResult<()> {
+            if let HistoryItem::ObjectUpdate(item) = item {
+                let data = if is_undo { &item.undo_data } else { &item.redo_data };
+                self.applied.borrow_mut().push((data.clone(), is_undo));
+            }
+            Ok(())
+        }
+
+        fn request_render(&self) {
+            self.renders.set(self.renders.get() + 1);
+        }
+    }
+
+    fn set_x(from: f64, to: f64) -> HistoryItem {
+        HistoryItem::ObjectUpdate(ObjectHistoryItem::new(
+            "rect".to_string(),
+            json!({ "x": from }),
+            json!({ "x": to }),
+        ))
+    }
+
+    fn edit(history: &mut History, from: f64, to: f64) {
+        history.push(set_x(from, to));
+        history.finalize_current_unit();
+    }
+
+    #[test]
+    fn undo_then_edit_is_recorded() {
+        let target = RecordingTarget::default();
+        let mut history = History::new();
+
+        edit(&mut history, 0.0, 10.0);
+        assert!(history.step(&target, true));
+        assert!(!history.is_replaying());
+
+        edit(&mut history, 0.0, 20.0);
+        assert_eq!(history.undo_stack.borrow().len(), 1);
+        assert!(!history.can_redo());
+
+        assert!(history.step(&target, true));
+        assert_eq!(
+            *target.applied.borrow(),
+            vec![(json!({ "x": 0.0 }), true), (json!({ "x": 0.0 }), true)]
+        );
+        assert_eq!(target.renders.get(), 2);
+    }
+
+    #[test]
+    fn undo_redo_edit_clears_redo_stack() {
+        let target = RecordingTarget::default();
+        let mut history = History::new();
+
+        edit(&mut history, 0.0, 10.0);
+        edit(&mut history, 10.0, 20.0);
+        assert!(history.step(&target, true));
+        assert!(history.step(&target, true));
+        assert!(history.step(&target, false));
+        assert!(history.can_undo());
+        assert!(history.can_redo());
+
+        edit(&mut history, 10.0, 30.0);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo_stack.borrow().len(), 2);
+    }
+
+    #[test]
+    fn step_on_empty_stack_leaves_history_usable() {
+        let target = RecordingTarget::default();
+        let mut history = History::new();
+
+        assert!(!history.step(&target, true));
+        assert!(!history.step(&target, false));
+        assert!(!history.is_replaying());
+
+        edit(&mut history, 0.0, 10.0);
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn pushes_during_replay_are_ignored() {
+        let mut history = History::new();
+        {
+            let _outer = ReplayGuard::new(&history.replay_depth);
+            {
+                let _inner = ReplayGuard::new(&history.replay_depth);
+            }
+            assert!(history.is_replaying());
+            edit(&mut history, 0.0, 10.0);
+            assert!(!history.can_undo());
+        }
+
+        assert!(!history.is_replaying());
+        edit(&mut history, 0.0, 10.0);
+        assert!(history.can_undo());
+    }
+}
@@ -0,0 +1,152 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::Reflect;
+use web_sys::{window, IdleDeadline, IdleRequestOptions};
+
+use crate::helper::now_ms;
+
+// 页面一直繁忙时最多等待这么久，避免任务饿死
+const IDLE_TIMEOUT_MS: u32 = 1000;
+// 剩余空闲时间不足时留到下一次空闲回调
+const MIN_REMAINING_MS: f64 = 1.0;
+// 不支持 requestIdleCallback 时用 setTimeout 模拟
+const FALLBACK_DELAY_MS: i32 = 50;
+const FALLBACK_BUDGET_MS: f64 = 8.0;
+
+struct IdleTask {
+    key: String,
+    run: Box<dyn FnOnce()>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum IdleHandle {
+    Idle(u32),
+    Timeout(i32),
+}
+
+// 在浏览器空闲时执行非紧急任务，同名任务排队期间只保留最新的一个
+#[derive(Clone, Default)]
+pub struct IdleScheduler {
+    tasks: Rc<RefCell<VecDeque<IdleTask>>>,
+    handle: Rc<Cell<Option<IdleHandle>>>,
+}
+
+impl Debug for IdleScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tasks = self.tasks.borrow();
+        let keys: Vec<&str> = tasks.iter().map(|task| task.key.as_str()).collect();
+        write!(
+            f,
+            "IdleScheduler {{ tasks: {:?}, handle: {:?} }}",
+            keys,
+            self.handle.get()
+        )
+    }
+}
+
+impl IdleScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn schedule(&self, key: impl Into<String>, task: impl FnOnce() + 'static) {
+        let key = key.into();
+        {
+            let mut tasks = self.tasks.borrow_mut();
+            match tasks.iter_mut().find(|pending| pending.key == key) {
+                Some(pending) => pending.run = Box::new(task),
+                None => tasks.push_back(IdleTask {
+                    key,
+                    run: Box::new(task),
+                }),
+            }
+        }
+        self.request();
+    }
+
+    pub fn cancel(&self, key: &str) -> bool {
+        let mut tasks = self.tasks.borrow_mut();
+        let len = tasks.len();
+        tasks.retain(|task| task.key != key);
+        tasks.len() != len
+    }
+
+    pub fn is_scheduled(&self, key: &str) -> bool {
+        self.tasks.borrow().iter().any(|task| task.key == key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.borrow().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.tasks.borrow_mut().clear();
+        let (Some(window), Some(handle)) = (window(), self.handle.take()) else {
+            return;
+        };
+        match handle {
+            IdleHandle::Idle(id) => window.cancel_idle_callback(id),
+            IdleHandle::Timeout(id) => window.clear_timeout_with_handle(id),
+        }
+    }
+
+    fn request(&self) {
+        if self.handle.get().is_some() || self.is_empty() {
+            return;
+        }
+        let Some(window) = window() else {
+            return;
+        };
+
+        let scheduler = self.clone();
+        let closure = Closure::once_into_js(move |deadline: JsValue| scheduler.run(deadline));
+        let handle = if Reflect::has(&window, &"requestIdleCallback".into()).unwrap_or(false) {
+            let options = IdleRequestOptions::new();
+            options.set_timeout(IDLE_TIMEOUT_MS);
+            window
+                .request_idle_callback_with_options(closure.unchecked_ref(), &options)
+                .ok()
+                .map(IdleHandle::Idle)
+        } else {
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    closure.unchecked_ref(),
+                    FALLBACK_DELAY_MS,
+                )
+                .ok()
+                .map(IdleHandle::Timeout)
+        };
+        self.handle.set(handle);
+    }
+
+    fn run(&self, deadline: JsValue) {
+        self.handle.set(None);
+        let deadline = deadline.dyn_into::<IdleDeadline>().ok();
+        let fallback_end = now_ms() + FALLBACK_BUDGET_MS;
+        let remaining = || match &deadline {
+            Some(deadline) => deadline.time_remaining(),
+            None => fallback_end - now_ms(),
+        };
+        // 超时触发时至少执行一个任务，保证排队的任务最终能完成
+        let mut force = deadline.as_ref().is_some_and(|deadline| deadline.did_timeout());
+
+        while force || remaining() >= MIN_REMAINING_MS {
+            // 任务执行时可能会继续调度新任务，先释放借用
+            let Some(task) = self.tasks.borrow_mut().pop_front() else {
+                break;
+            };
+            (task.run)();
+            force = false;
+        }
+        self.request();
+    }
+}
@@ -1,37 +1,46 @@
 use std::borrow::Cow;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlCanvasElement, HtmlImageElement};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, HtmlVideoElement, ImageBitmap, Response};
+
+use crate::error::{EditingError, EditingResult};
 
 pub trait ImageSource {
-    fn into_html_image_element(self) -> HtmlImageElement;
-    fn into_html_canvas_element(self) -> HtmlCanvasElement;
+    fn into_html_image_element(self) -> EditingResult<HtmlImageElement>;
+    fn into_html_canvas_element(self) -> EditingResult<HtmlCanvasElement>;
 }
 
 impl ImageSource for HtmlImageElement {
-    fn into_html_image_element(self) -> HtmlImageElement {
-        self
+    fn into_html_image_element(self) -> EditingResult<HtmlImageElement> {
+        Ok(self)
     }
 
-    fn into_html_canvas_element(self) -> HtmlCanvasElement {
+    fn into_html_canvas_element(self) -> EditingResult<HtmlCanvasElement> {
         self.into_canvas()
     }
 }
 
 impl ImageSource for HtmlCanvasElement {
-    fn into_html_image_element(self) -> HtmlImageElement {
+    fn into_html_image_element(self) -> EditingResult<HtmlImageElement> {
         self.into_image()
     }
 
-    fn into_html_canvas_element(self) -> HtmlCanvasElement {
-        self
+    fn into_html_canvas_element(self) -> EditingResult<HtmlCanvasElement> {
+        Ok(self)
     }
 }
 
+#[derive(Clone)]
 pub enum ImageDataSource<'a> {
     HtmlImage(Cow<'a, HtmlImageElement>),
     HtmlCanvas(Cow<'a, HtmlCanvasElement>),
+    // 已解码的位图，重复绘制时比 img 元素快得多；Blob、File 和 data URL 都解码为这一种
+    ImageBitmap(Cow<'a, ImageBitmap>),
+    // 绘制时取视频的当前帧
+    HtmlVideo(Cow<'a, HtmlVideoElement>),
 }
 
+#[derive(Clone)]
 pub struct Image<'a>(ImageDataSource<'a>);
 
 impl<'a> Image<'a> {
@@ -39,21 +48,78 @@ impl<'a> Image<'a> {
         Image(source.into())
     }
 
-    pub fn as_html_image_element(&self) -> HtmlImageElement {
+    pub fn source(&self) -> &ImageDataSource<'a> {
+        &self.0
+    }
+
+    // 像素尺寸，视频为当前视频流的尺寸
+    pub fn size(&self) -> (u32, u32) {
         match &self.0 {
-            ImageDataSource::HtmlImage(img) => img.clone().into_owned(),
+            ImageDataSource::HtmlImage(img) => (img.natural_width(), img.natural_height()),
+            ImageDataSource::HtmlCanvas(canvas) => (canvas.width(), canvas.height()),
+            ImageDataSource::ImageBitmap(bitmap) => (bitmap.width(), bitmap.height()),
+            ImageDataSource::HtmlVideo(video) => (video.video_width(), video.video_height()),
+        }
+    }
+
+    pub fn as_html_image_element(&self) -> EditingResult<HtmlImageElement> {
+        match &self.0 {
+            ImageDataSource::HtmlImage(img) => Ok(img.clone().into_owned()),
             ImageDataSource::HtmlCanvas(canvas) => {
                 canvas.clone().into_owned().into_html_image_element()
-            } // 处理其他类型...
+            }
+            ImageDataSource::ImageBitmap(bitmap) => {
+                bitmap.clone().into_owned().into_canvas()?.into_image()
+            }
+            ImageDataSource::HtmlVideo(video) => video.clone().into_owned().into_canvas()?.into_image(),
         }
     }
 
-    pub fn as_html_canvas_element(&self) -> HtmlCanvasElement {
+    pub fn as_html_canvas_element(&self) -> EditingResult<HtmlCanvasElement> {
         match &self.0 {
             ImageDataSource::HtmlImage(img) => img.clone().into_owned().into_html_canvas_element(),
-            ImageDataSource::HtmlCanvas(canvas) => canvas.clone().into_owned(),
-            // 处理其他类型...
+            ImageDataSource::HtmlCanvas(canvas) => Ok(canvas.clone().into_owned()),
+            ImageDataSource::ImageBitmap(bitmap) => bitmap.clone().into_owned().into_canvas(),
+            ImageDataSource::HtmlVideo(video) => video.clone().into_owned().into_canvas(),
+        }
+    }
+}
+
+impl Image<'static> {
+    // 解码 Blob 或 File（File 可直接作为 &Blob 传入）
+    pub async fn from_blob(blob: &Blob) -> EditingResult<Self> {
+        let promise = web_sys::window()
+            .ok_or(EditingError::WindowNotFound)?
+            .create_image_bitmap_with_blob(blob)?;
+        let bitmap = JsFuture::from(promise)
+            .await?
+            .dyn_into::<ImageBitmap>()
+            .map_err(|_| EditingError::Dom("createImageBitmap resolved to a non-ImageBitmap".to_string()))?;
+        Ok(Image::new(bitmap))
+    }
+
+    // 支持普通 URL 和 data URL，跨域图片需要服务端允许 CORS
+    pub async fn from_url(url: &str) -> EditingResult<Self> {
+        let response = JsFuture::from(
+            web_sys::window()
+                .ok_or(EditingError::WindowNotFound)?
+                .fetch_with_str(url),
+        )
+        .await?
+        .dyn_into::<Response>()
+        .map_err(|_| EditingError::Dom("fetch resolved to a non-Response".to_string()))?;
+        if !response.ok() {
+            return Err(EditingError::Dom(format!(
+                "Failed to fetch image {}: {}",
+                url,
+                response.status()
+            )));
         }
+        let blob = JsFuture::from(response.blob()?)
+            .await?
+            .dyn_into::<Blob>()
+            .map_err(|_| EditingError::Dom("Response.blob() resolved to a non-Blob".to_string()))?;
+        Self::from_blob(&blob).await
     }
 }
 
@@ -82,63 +148,91 @@ impl<'a> From<&'a HtmlCanvasElement> for ImageDataSource<'a> {
     }
 }
 
-// 可以为其他类型实现类似的 From trait
+impl<'a> From<ImageBitmap> for ImageDataSource<'a> {
+    fn from(bitmap: ImageBitmap) -> Self {
+        ImageDataSource::ImageBitmap(Cow::Owned(bitmap))
+    }
+}
+
+impl<'a> From<&'a ImageBitmap> for ImageDataSource<'a> {
+    fn from(bitmap: &'a ImageBitmap) -> Self {
+        ImageDataSource::ImageBitmap(Cow::Borrowed(bitmap))
+    }
+}
+
+impl<'a> From<HtmlVideoElement> for ImageDataSource<'a> {
+    fn from(video: HtmlVideoElement) -> Self {
+        ImageDataSource::HtmlVideo(Cow::Owned(video))
+    }
+}
+
+impl<'a> From<&'a HtmlVideoElement> for ImageDataSource<'a> {
+    fn from(video: &'a HtmlVideoElement) -> Self {
+        ImageDataSource::HtmlVideo(Cow::Borrowed(video))
+    }
+}
 
 pub trait IntoCanvas {
-    fn into_canvas(self) -> HtmlCanvasElement;
+    fn into_canvas(self) -> EditingResult<HtmlCanvasElement>;
 }
 
-impl IntoCanvas for HtmlImageElement {
-    fn into_canvas(self) -> HtmlCanvasElement {
-        let document = web_sys::window().unwrap().document().unwrap();
-        let canvas = document
-            .create_element("canvas")
-            .unwrap()
-            .dyn_into::<HtmlCanvasElement>()
-            .unwrap();
+fn create_element<T: JsCast>(tag: &str) -> EditingResult<T> {
+    web_sys::window()
+        .ok_or(EditingError::WindowNotFound)?
+        .document()
+        .ok_or(EditingError::DocumentNotFound)?
+        .create_element(tag)?
+        .dyn_into::<T>()
+        .map_err(|_| EditingError::Dom(format!("Failed to create <{}>", tag)))
+}
 
-        // 设置画布大小与图像一致
-        canvas.set_width(self.natural_width());
-        canvas.set_height(self.natural_height());
+// 创建指定像素尺寸的画布并取得 2D 渲染上下文
+fn create_canvas(width: u32, height: u32) -> EditingResult<(HtmlCanvasElement, CanvasRenderingContext2d)> {
+    let canvas: HtmlCanvasElement = create_element("canvas")?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let context = canvas
+        .get_context("2d")?
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or_else(|| EditingError::ContextCreationFailed("2d".to_string()))?;
+    Ok((canvas, context))
+}
 
-        // 获取 2D 渲染上下文
-        let context = canvas
-            .get_context("2d")
-            .unwrap()
-            .unwrap()
-            .dyn_into::<web_sys::CanvasRenderingContext2d>()
-            .unwrap();
+impl IntoCanvas for HtmlImageElement {
+    fn into_canvas(self) -> EditingResult<HtmlCanvasElement> {
+        // 画布大小与图像一致
+        let (canvas, context) = create_canvas(self.natural_width(), self.natural_height())?;
+        context.draw_image_with_html_image_element(&self, 0.0, 0.0)?;
+        Ok(canvas)
+    }
+}
 
-        // 将图像绘制到画布上
-        context
-            .draw_image_with_html_image_element(&self, 0.0, 0.0)
-            .expect("Failed to draw image on canvas");
+impl IntoCanvas for ImageBitmap {
+    fn into_canvas(self) -> EditingResult<HtmlCanvasElement> {
+        let (canvas, context) = create_canvas(self.width(), self.height())?;
+        context.draw_image_with_image_bitmap(&self, 0.0, 0.0)?;
+        Ok(canvas)
+    }
+}
 
-        canvas
+// 截取视频的当前帧
+impl IntoCanvas for HtmlVideoElement {
+    fn into_canvas(self) -> EditingResult<HtmlCanvasElement> {
+        let (canvas, context) = create_canvas(self.video_width(), self.video_height())?;
+        context.draw_image_with_html_video_element(&self, 0.0, 0.0)?;
+        Ok(canvas)
     }
 }
 
-// 定义新的 trait
 pub trait IntoImage {
-    fn into_image(self) -> HtmlImageElement;
+    fn into_image(self) -> EditingResult<HtmlImageElement>;
 }
 
-// 为 HtmlCanvasElement 实现 IntoImage trait
 impl IntoImage for HtmlCanvasElement {
-    fn into_image(self) -> HtmlImageElement {
-        let document = web_sys::window().unwrap().document().unwrap();
-        let image = document
-            .create_element("img")
-            .unwrap()
-            .dyn_into::<HtmlImageElement>()
-            .unwrap();
-
-        // 将画布内容转换为 data URL
-        let data_url = self.to_data_url().unwrap();
-
-        // 设置图像的 src 为画布的 data URL
-        image.set_src(&data_url);
-
-        image
+    fn into_image(self) -> EditingResult<HtmlImageElement> {
+        let image: HtmlImageElement = create_element("img")?;
+        // 以画布的 data URL 作为图像内容
+        image.set_src(&self.to_data_url()?);
+        Ok(image)
     }
 }
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use futures::channel::oneshot;
+use serde::{Deserialize, Serialize};
+
+use crate::image::Image;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageStatus {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+// 加载结果，失败时为错误信息
+pub type ImageLoadResult = Result<Image<'static>, String>;
+
+enum CacheEntry {
+    // 等待同一 URL 加载完成的调用方
+    Loading(Vec<oneshot::Sender<ImageLoadResult>>),
+    Loaded(Image<'static>),
+    Failed,
+}
+
+// 以 URL 为键的图片缓存；同一 URL 同时只加载一次
+#[derive(Default)]
+pub struct ImageCache {
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl Debug for ImageCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ImageCache {{ entries: {} }}", self.entries.len())
+    }
+}
+
+impl ImageCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self, url: &str) -> Option<ImageStatus> {
+        self.entries.get(url).map(|entry| match entry {
+            CacheEntry::Loading(_) => ImageStatus::Loading,
+            CacheEntry::Loaded(_) => ImageStatus::Loaded,
+            CacheEntry::Failed => ImageStatus::Failed,
+        })
+    }
+
+    pub fn get(&self, url: &str) -> Option<Image<'static>> {
+        match self.entries.get(url) {
+            Some(CacheEntry::Loaded(image)) => Some(image.clone()),
+            _ => None,
+        }
+    }
+
+    // 已加载的图片数及按 RGBA 解码估算的字节数
+    pub fn memory_estimate(&self) -> (usize, usize) {
+        self.entries
+            .values()
+            .filter_map(|entry| match entry {
+                CacheEntry::Loaded(image) => Some(image.size()),
+                _ => None,
+            })
+            .fold((0, 0), |(count, bytes), (width, height)| {
+                (count + 1, bytes + width as usize * height as usize * 4)
+            })
+    }
+
+    // 已有加载在进行时返回等待其结果的 Receiver；否则标记为加载中并返回 None，由调用方负责加载。
+    // 之前失败的 URL 会重新加载
+    pub fn begin(&mut self, url: &str) -> Option<oneshot::Receiver<ImageLoadResult>> {
+        if let Some(CacheEntry::Loading(waiters)) = self.entries.get_mut(url) {
+            let (sender, receiver) = oneshot::channel();
+            waiters.push(sender);
+            return Some(receiver);
+        }
+        self.entries.insert(url.to_string(), CacheEntry::Loading(Vec::new()));
+        None
+    }
+
+    pub fn finish(&mut self, url: &str, result: ImageLoadResult) {
+        let entry = match &result {
+            Ok(image) => CacheEntry::Loaded(image.clone()),
+            Err(_) => CacheEntry::Failed,
+        };
+        if let Some(CacheEntry::Loading(waiters)) = self.entries.insert(url.to_string(), entry) {
+            for waiter in waiters {
+                let _ = waiter.send(result.clone());
+            }
+        }
+    }
+
+    // 加载中的条目不移除，等待方仍会收到结果
+    pub fn remove(&mut self, url: &str) -> bool {
+        if matches!(self.entries.get(url), Some(CacheEntry::Loading(_))) {
+            return false;
+        }
+        self.entries.remove(url).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries
+            .retain(|_, entry| matches!(entry, CacheEntry::Loading(_)));
+    }
+}
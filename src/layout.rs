@@ -0,0 +1,284 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bounding_box::BoundingBox;
+
+// 节点完全重合时用于拉开的最小距离，避免力的方向无法确定
+const MIN_DISTANCE: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutAlgorithm {
+    // 力导向布局，适合无明显层次的图
+    #[default]
+    Force,
+    // 分层布局，边从上层指向下层，适合树和流程图
+    Layered,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutDirection {
+    #[default]
+    TopDown,
+    LeftRight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LayoutOptions {
+    pub algorithm: LayoutAlgorithm,
+    pub direction: LayoutDirection,
+    // 节点之间的最小间距
+    pub spacing: f64,
+    // 力导向布局的迭代次数
+    pub iterations: usize,
+    // 过渡动画时长（秒），0 表示直接移动到位
+    pub duration: f64,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: LayoutAlgorithm::Force,
+            direction: LayoutDirection::TopDown,
+            spacing: 40.0,
+            iterations: 300,
+            duration: 0.4,
+        }
+    }
+}
+
+// 计算布局后每个节点的中心位置，edges 为节点下标对 (from, to)
+pub fn compute_layout(
+    nodes: &[BoundingBox],
+    edges: &[(usize, usize)],
+    options: &LayoutOptions,
+) -> Vec<(f64, f64)> {
+    if nodes.is_empty() {
+        return Vec::new();
+    }
+    match options.algorithm {
+        LayoutAlgorithm::Force => force_layout(nodes, edges, options),
+        LayoutAlgorithm::Layered => layered_layout(nodes, edges, options),
+    }
+}
+
+// Fruchterman-Reingold：从当前位置出发迭代，结果保持原来的重心
+fn force_layout(
+    nodes: &[BoundingBox],
+    edges: &[(usize, usize)],
+    options: &LayoutOptions,
+) -> Vec<(f64, f64)> {
+    let count = nodes.len();
+    let mut positions: Vec<(f64, f64)> = nodes.iter().map(BoundingBox::center).collect();
+    let centroid = centroid(&positions);
+
+    let average_size = nodes
+        .iter()
+        .map(|bounds| bounds.width.max(bounds.height))
+        .sum::<f64>()
+        / count as f64;
+    // 理想边长
+    let k = average_size + options.spacing;
+    let mut temperature = k * (count as f64).sqrt();
+    let cooling = temperature / options.iterations.max(1) as f64;
+
+    for _ in 0..options.iterations {
+        let mut displacement = vec![(0.0, 0.0); count];
+
+        for i in 0..count {
+            for j in (i + 1)..count {
+                let (dx, dy, distance) = separation(positions[i], positions[j], i, j);
+                let force = k * k / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                displacement[i].0 += fx;
+                displacement[i].1 += fy;
+                displacement[j].0 -= fx;
+                displacement[j].1 -= fy;
+            }
+        }
+
+        for &(from, to) in edges {
+            if from == to {
+                continue;
+            }
+            let (dx, dy, distance) = separation(positions[from], positions[to], from, to);
+            let force = distance * distance / k;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            displacement[from].0 -= fx;
+            displacement[from].1 -= fy;
+            displacement[to].0 += fx;
+            displacement[to].1 += fy;
+        }
+
+        for (position, (dx, dy)) in positions.iter_mut().zip(displacement) {
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.0 += dx / length * step;
+                position.1 += dy / length * step;
+            }
+        }
+        temperature = (temperature - cooling).max(0.0);
+    }
+
+    let (cx, cy) = self::centroid(&positions);
+    positions
+        .into_iter()
+        .map(|(x, y)| (x - cx + centroid.0, y - cy + centroid.1))
+        .collect()
+}
+
+// 返回 a - b 及其长度；重合时按下标错开，保证结果确定
+fn separation(a: (f64, f64), b: (f64, f64), i: usize, j: usize) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance < MIN_DISTANCE {
+        let angle = (i * 31 + j * 17) as f64;
+        return (angle.cos() * MIN_DISTANCE, angle.sin() * MIN_DISTANCE, MIN_DISTANCE);
+    }
+    (dx, dy, distance)
+}
+
+fn centroid(points: &[(f64, f64)]) -> (f64, f64) {
+    let count = points.len().max(1) as f64;
+    let (sx, sy) = points
+        .iter()
+        .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    (sx / count, sy / count)
+}
+
+// 最长路径分层 + 一次重心排序；结果的左上角与原来所有节点的包围盒对齐
+fn layered_layout(
+    nodes: &[BoundingBox],
+    edges: &[(usize, usize)],
+    options: &LayoutOptions,
+) -> Vec<(f64, f64)> {
+    let count = nodes.len();
+    let layers = assign_layers(count, edges);
+    let layer_count = layers.iter().copied().max().unwrap_or(0) + 1;
+
+    // 层内初始按原来的位置排序，让结果尽量贴近原有布局
+    let mut rows: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    for (node, &layer) in layers.iter().enumerate() {
+        rows[layer].push(node);
+    }
+    let cross_axis = |node: usize| match options.direction {
+        LayoutDirection::TopDown => nodes[node].center().0,
+        LayoutDirection::LeftRight => nodes[node].center().1,
+    };
+    for row in rows.iter_mut() {
+        row.sort_by(|a, b| cross_axis(*a).total_cmp(&cross_axis(*b)));
+    }
+
+    // 按上一层父节点的平均序号重新排序，减少交叉
+    let mut order = vec![0.0; count];
+    for row in rows.iter() {
+        for (index, &node) in row.iter().enumerate() {
+            order[node] = index as f64;
+        }
+    }
+    for (layer, row) in rows.iter_mut().enumerate().skip(1) {
+        let barycenter = |node: usize| {
+            let parents: Vec<f64> = edges
+                .iter()
+                .filter(|(from, to)| *to == node && layers[*from] + 1 == layer)
+                .map(|(from, _)| order[*from])
+                .collect();
+            if parents.is_empty() {
+                order[node]
+            } else {
+                parents.iter().sum::<f64>() / parents.len() as f64
+            }
+        };
+        let mut keyed: Vec<(f64, usize)> = row.iter().map(|&node| (barycenter(node), node)).collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+        *row = keyed.into_iter().map(|(_, node)| node).collect();
+        for (index, &node) in row.iter().enumerate() {
+            order[node] = index as f64;
+        }
+    }
+
+    // 主轴为层的方向，交叉轴为层内排列的方向
+    let extent = |node: usize| match options.direction {
+        LayoutDirection::TopDown => (nodes[node].height, nodes[node].width),
+        LayoutDirection::LeftRight => (nodes[node].width, nodes[node].height),
+    };
+    let row_widths: Vec<f64> = rows
+        .iter()
+        .map(|row| {
+            row.iter().map(|&node| extent(node).1).sum::<f64>()
+                + options.spacing * row.len().saturating_sub(1) as f64
+        })
+        .collect();
+    let max_width = row_widths.iter().copied().fold(0.0, f64::max);
+
+    let mut positions = vec![(0.0, 0.0); count];
+    let mut main = 0.0;
+    for (row, row_width) in rows.iter().zip(row_widths) {
+        let depth = row.iter().map(|&node| extent(node).0).fold(0.0, f64::max);
+        let mut cross = (max_width - row_width) / 2.0;
+        for &node in row {
+            let node_width = extent(node).1;
+            let center = (main + depth / 2.0, cross + node_width / 2.0);
+            positions[node] = match options.direction {
+                LayoutDirection::TopDown => (center.1, center.0),
+                LayoutDirection::LeftRight => center,
+            };
+            cross += node_width + options.spacing;
+        }
+        main += depth + options.spacing;
+    }
+
+    let origin = nodes
+        .iter()
+        .skip(1)
+        .fold(nodes[0], |bounds, node| bounds.union(node));
+    positions
+        .into_iter()
+        .map(|(x, y)| (x + origin.x, y + origin.y))
+        .collect()
+}
+
+// 每个节点的层号为从入度为 0 的节点出发的最长路径长度；环上的边在遍历时被忽略
+fn assign_layers(count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut in_degree = vec![0usize; count];
+    let mut outgoing = vec![Vec::new(); count];
+    for &(from, to) in edges {
+        if from != to {
+            in_degree[to] += 1;
+            outgoing[from].push(to);
+        }
+    }
+
+    let mut layers = vec![0usize; count];
+    let mut visited = vec![false; count];
+    let mut queue: VecDeque<usize> = (0..count).filter(|&node| in_degree[node] == 0).collect();
+
+    loop {
+        while let Some(node) = queue.pop_front() {
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            for &next in &outgoing[node] {
+                if visited[next] {
+                    continue;
+                }
+                layers[next] = layers[next].max(layers[node] + 1);
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+        // 只剩环时，从序号最小的未访问节点断开继续
+        match (0..count).find(|&node| !visited[node]) {
+            Some(node) => queue.push_back(node),
+            None => break,
+        }
+    }
+    layers
+}
@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+use web_sys::console;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(LogLevel::Off),
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!("Unknown log level: {}", s)),
+        }
+    }
+}
+
+struct LoggerState {
+    level: LogLevel,
+    // 模块路径前缀 -> 级别，最长前缀优先
+    module_levels: HashMap<String, LogLevel>,
+}
+
+thread_local! {
+    static LOGGER: RefCell<LoggerState> = RefCell::new(LoggerState {
+        level: LogLevel::Info,
+        module_levels: HashMap::new(),
+    });
+}
+
+const CRATE_PREFIX: &str = concat!(env!("CARGO_CRATE_NAME"), "::");
+
+// 允许省略 crate 名，如 "scene_manager" 等价于 "graphics::scene_manager"
+fn normalize_module(module: &str) -> &str {
+    module.strip_prefix(CRATE_PREFIX).unwrap_or(module)
+}
+
+fn module_matches(module: &str, prefix: &str) -> bool {
+    module == prefix
+        || (module.starts_with(prefix) && module[prefix.len()..].starts_with("::"))
+}
+
+pub fn set_level(level: LogLevel) {
+    LOGGER.with(|logger| logger.borrow_mut().level = level);
+}
+
+pub fn level() -> LogLevel {
+    LOGGER.with(|logger| logger.borrow().level)
+}
+
+pub fn set_module_level(module: &str, level: LogLevel) {
+    let module = normalize_module(module).to_string();
+    LOGGER.with(|logger| logger.borrow_mut().module_levels.insert(module, level));
+}
+
+pub fn clear_module_levels() {
+    LOGGER.with(|logger| logger.borrow_mut().module_levels.clear());
+}
+
+pub fn enabled(level: LogLevel, module: &str) -> bool {
+    if level == LogLevel::Off {
+        return false;
+    }
+    let module = normalize_module(module);
+    LOGGER.with(|logger| {
+        let logger = logger.borrow();
+        let max_level = logger
+            .module_levels
+            .iter()
+            .filter(|(prefix, _)| module_matches(module, prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(logger.level);
+        level <= max_level
+    })
+}
+
+pub fn log(level: LogLevel, module: &str, args: fmt::Arguments) {
+    if !enabled(level, module) {
+        return;
+    }
+    let message = JsValue::from_str(&format!(
+        "[{}] {}: {}",
+        level.as_str().to_ascii_uppercase(),
+        normalize_module(module),
+        args
+    ));
+    match level {
+        LogLevel::Error => console::error_1(&message),
+        LogLevel::Warn => console::warn_1(&message),
+        LogLevel::Info => console::info_1(&message),
+        LogLevel::Debug | LogLevel::Trace => console::debug_1(&message),
+        LogLevel::Off => {}
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_log_level(level: &str) -> Result<(), JsValue> {
+    set_level(level.parse::<LogLevel>()?);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn get_log_level() -> String {
+    level().as_str().to_string()
+}
+
+#[wasm_bindgen]
+pub fn set_module_log_level(module: &str, level: &str) -> Result<(), JsValue> {
+    set_module_level(module, level.parse::<LogLevel>()?);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn clear_module_log_levels() {
+    clear_module_levels();
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::LogLevel::Error, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::LogLevel::Warn, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::LogLevel::Info, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::LogLevel::Debug, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        $crate::log::log($crate::log::LogLevel::Trace, module_path!(), format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {log_debug, log_error, log_info, log_trace, log_warn};
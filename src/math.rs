@@ -0,0 +1,341 @@
+// 仿射变换的矩阵工具，只依赖 nalgebra，可以在非 wasm 目标下测试。
+// 1 * 6 矩阵按 canvas 的 setTransform 顺序存放 [a, b, c, d, e, f]，对应
+// | a c e |
+// | b d f |
+// | 0 0 1 |
+use nalgebra as na;
+
+// 小于该角度（弧度）时用泰勒展开，避免 cos 在 1 附近的舍入
+const SMALL_ANGLE: f64 = 1e-6;
+
+// 将 1 * 6 转为 3 * 3
+pub fn convert_1x6_to_3x3(matrix: na::Matrix1x6<f64>) -> na::Matrix3<f64> {
+    na::Matrix3::new(
+        matrix[0], matrix[2], matrix[4], matrix[1], matrix[3], matrix[5], 0.0, 0.0, 1.0,
+    )
+}
+
+pub fn convert_3x3_to_1x6(matrix: na::Matrix3<f64>) -> na::Matrix1x6<f64> {
+    na::Matrix1x6::new(
+        matrix[(0, 0)],
+        matrix[(1, 0)],
+        matrix[(0, 1)],
+        matrix[(1, 1)],
+        matrix[(0, 2)],
+        matrix[(1, 2)],
+    )
+}
+
+pub fn transform_point(matrix: na::Matrix1x6<f64>, x: f64, y: f64) -> (f64, f64) {
+    (
+        matrix[0] * x + matrix[2] * y + matrix[4],
+        matrix[1] * x + matrix[3] * y + matrix[5],
+    )
+}
+
+// 行列式偏离 ±1 不超过 NORMALIZE_TOLERANCE 时视为连续相乘的误差累积，缩放回 ±1；偏离更大的是真实的缩放，
+// 保持不变。只缩放线性部分，平移保持不变；镜像（行列式为负）保留符号，退化矩阵原样返回
+pub fn normalize_if_needed(mut matrix: na::Matrix1x6<f64>) -> na::Matrix1x6<f64> {
+    const NORMALIZE_TOLERANCE: f64 = 1e-6;
+    let det = (matrix[0] * matrix[3] - matrix[1] * matrix[2]).abs();
+    let drift = (det - 1.0).abs();
+    if drift > f64::EPSILON && drift <= NORMALIZE_TOLERANCE {
+        let scale = det.sqrt();
+        for i in 0..4 {
+            matrix[i] /= scale;
+        }
+    }
+    matrix
+}
+
+// 最后一行同时恢复为 [0, 0, 1]
+pub fn normalize_3x3_if_needed(matrix: na::Matrix3<f64>) -> na::Matrix3<f64> {
+    convert_1x6_to_3x3(normalize_if_needed(convert_3x3_to_1x6(matrix)))
+}
+
+pub fn get_rotation_matrix(angle_radians: f64) -> na::Matrix3<f64> {
+    let (sin, cos) = if angle_radians.abs() < SMALL_ANGLE {
+        (angle_radians, 1.0 - 0.5 * angle_radians * angle_radians)
+    } else {
+        angle_radians.sin_cos()
+    };
+    na::Matrix3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
+}
+
+// 仿射矩阵分解出的各分量，线性部分按 [[scale_x, skew_x], [skew_y, scale_y]] * 旋转 组合，与 anchored_transform 相同
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransformParts {
+    pub translate: (f64, f64),
+    // 弧度
+    pub rotation: f64,
+    pub scale: (f64, f64),
+    pub skew: (f64, f64),
+}
+
+impl Default for TransformParts {
+    fn default() -> Self {
+        Self {
+            translate: (0.0, 0.0),
+            rotation: 0.0,
+            scale: (1.0, 1.0),
+            skew: (0.0, 0.0),
+        }
+    }
+}
+
+pub fn compose_transform(parts: &TransformParts) -> na::Matrix1x6<f64> {
+    let scale_skew = na::Matrix3::new(
+        parts.scale.0,
+        parts.skew.0,
+        0.0,
+        parts.skew.1,
+        parts.scale.1,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+    let mut matrix = convert_3x3_to_1x6(scale_skew * get_rotation_matrix(parts.rotation));
+    matrix[4] = parts.translate.0;
+    matrix[5] = parts.translate.1;
+    matrix
+}
+
+// 4 个自由度分成 5 个分量不唯一，这里取 skew_y = 0、scale_y >= 0，镜像体现为 scale_x 为负；
+// 旋转在 (-π, π] 内。这种形式的分量经 compose_transform 后可以原样分解回来
+pub fn decompose_transform(matrix: na::Matrix1x6<f64>) -> TransformParts {
+    let [a, b, c, d, e, f] = [matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5]];
+    // 第二行只含 scale_y 和旋转：(b, d) = scale_y * (sin, cos)
+    let scale_y = b.hypot(d);
+    let rotation = b.atan2(d);
+    let (sin, cos) = rotation.sin_cos();
+    // 第一行乘以旋转的逆得到 (scale_x, skew_x)
+    TransformParts {
+        translate: (e, f),
+        rotation,
+        scale: (a * cos - c * sin, scale_y),
+        skew: (a * sin + c * cos, 0.0),
+    }
+}
+
+// 以锚点 (width * anchor_x, height * anchor_y) 为中心缩放、斜切和旋转，再叠加平移；
+// base_transform 按 get_transform 排列为 [scale_x, skew_x, skew_y, scale_y, x, y]
+pub fn anchored_transform(
+    base_transform: na::Matrix1x6<f64>,
+    width: f64,
+    height: f64,
+    rotation_degrees: f64,
+    anchor: (f64, f64),
+) -> na::Matrix1x6<f64> {
+    let mut matrix = compose_transform(&TransformParts {
+        translate: (0.0, 0.0),
+        rotation: rotation_degrees.to_radians(),
+        scale: (base_transform[0], base_transform[3]),
+        skew: (base_transform[1], base_transform[2]),
+    });
+    let (pivot_x, pivot_y) = (width * anchor.0, height * anchor.1);
+    let (moved_x, moved_y) = transform_point(matrix, pivot_x, pivot_y);
+    matrix[4] = base_transform[4] + pivot_x - moved_x;
+    matrix[5] = base_transform[5] + pivot_y - moved_y;
+    matrix
+}
+
+// anchored_transform 的逆运算，返回 get_transform 排列的分量和旋转角（度），其中 skew_y 为 0
+pub fn decompose_anchored(
+    matrix: na::Matrix1x6<f64>,
+    width: f64,
+    height: f64,
+    anchor: (f64, f64),
+) -> (na::Matrix1x6<f64>, f64) {
+    let parts = decompose_transform(matrix);
+    let (pivot_x, pivot_y) = (width * anchor.0, height * anchor.1);
+    // 平移部分 = 位置 + 锚点 - 线性部分 * 锚点
+    let (moved_x, moved_y) = transform_point(matrix, pivot_x, pivot_y);
+    let base = na::Matrix1x6::new(
+        parts.scale.0,
+        parts.skew.0,
+        parts.skew.1,
+        parts.scale.1,
+        moved_x - pivot_x,
+        moved_y - pivot_y,
+    );
+    (base, parts.rotation.to_degrees())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance * (1.0 + a.abs().max(b.abs()))
+    }
+
+    // 行列式不太接近 0 的矩阵，保证可逆
+    fn invertible() -> impl Strategy<Value = na::Matrix1x6<f64>> {
+        prop::array::uniform6(-1e3..1e3f64)
+            .prop_map(|[a, b, c, d, e, f]| na::Matrix1x6::new(a, b, c, d, e, f))
+            .prop_filter("singular", |m| (m[0] * m[3] - m[1] * m[2]).abs() > 1e-3)
+    }
+
+    proptest! {
+        #[test]
+        fn conversions_round_trip(values in prop::array::uniform6(-1e6..1e6f64)) {
+            let [a, b, c, d, e, f] = values;
+            let matrix = na::Matrix1x6::new(a, b, c, d, e, f);
+            prop_assert_eq!(convert_3x3_to_1x6(convert_1x6_to_3x3(matrix)), matrix);
+        }
+
+        #[test]
+        fn transform_point_matches_matrix_product(matrix in invertible(), x in -1e3..1e3f64, y in -1e3..1e3f64) {
+            let point = convert_1x6_to_3x3(matrix) * na::Vector3::new(x, y, 1.0);
+            let (tx, ty) = transform_point(matrix, x, y);
+            prop_assert!(close(tx, point.x, 1e-12) && close(ty, point.y, 1e-12));
+        }
+
+        #[test]
+        fn inverse_round_trips_points(matrix in invertible(), x in -1e3..1e3f64, y in -1e3..1e3f64) {
+            let inverse = convert_3x3_to_1x6(convert_1x6_to_3x3(matrix).try_inverse().unwrap());
+            let (tx, ty) = transform_point(matrix, x, y);
+            let (rx, ry) = transform_point(inverse, tx, ty);
+            // 误差随条件数增长，这里的取值范围下 1e-6 足够
+            let scale = 1.0 + x.abs().max(y.abs());
+            prop_assert!((rx - x).abs() <= 1e-6 * scale && (ry - y).abs() <= 1e-6 * scale);
+        }
+
+        #[test]
+        fn rotation_is_orthonormal(angle in -10.0..10.0f64) {
+            let rotation = get_rotation_matrix(angle);
+            let identity = rotation * rotation.transpose();
+            prop_assert!((identity - na::Matrix3::identity()).abs().max() < 1e-12);
+            prop_assert!(close(rotation.determinant(), 1.0, 1e-12));
+        }
+
+        #[test]
+        fn small_angles_match_sin_cos(angle in -SMALL_ANGLE..SMALL_ANGLE) {
+            let rotation = get_rotation_matrix(angle);
+            let (sin, cos) = angle.sin_cos();
+            prop_assert!((rotation[(0, 0)] - cos).abs() < 1e-15 && (rotation[(1, 0)] - sin).abs() < 1e-15);
+        }
+
+        #[test]
+        fn rotations_compose(a in -3.0..3.0f64, b in -3.0..3.0f64) {
+            let composed = get_rotation_matrix(a) * get_rotation_matrix(b);
+            prop_assert!((composed - get_rotation_matrix(a + b)).abs().max() < 1e-12);
+        }
+
+        // 锚点在变换后只受平移影响
+        #[test]
+        fn anchor_stays_fixed(
+            width in 1.0..500.0f64,
+            height in 1.0..500.0f64,
+            rotation in -720.0..720.0f64,
+            anchor in (0.0..1.0f64, 0.0..1.0f64),
+            scale in (0.1..10.0f64, 0.1..10.0f64),
+            translate in (-1e3..1e3f64, -1e3..1e3f64),
+        ) {
+            let base = na::Matrix1x6::new(scale.0, 0.0, 0.0, scale.1, translate.0, translate.1);
+            let transform = anchored_transform(base, width, height, rotation, anchor);
+            let (pivot_x, pivot_y) = (width * anchor.0, height * anchor.1);
+            let (x, y) = transform_point(transform, pivot_x, pivot_y);
+            prop_assert!(close(x, pivot_x + translate.0, 1e-9) && close(y, pivot_y + translate.1, 1e-9));
+        }
+
+        #[test]
+        fn normalization_removes_drift(angle in -3.0..3.0f64, drift in 1e-12..1e-7f64, translate in (-1e3..1e3f64, -1e3..1e3f64)) {
+            let rotation = get_rotation_matrix(angle) * (1.0 + drift);
+            let mut matrix = convert_3x3_to_1x6(rotation);
+            matrix[4] = translate.0;
+            matrix[5] = translate.1;
+            let normalized = normalize_if_needed(matrix);
+            let det = normalized[0] * normalized[3] - normalized[1] * normalized[2];
+            prop_assert!(close(det, 1.0, 1e-12));
+            prop_assert_eq!((normalized[4], normalized[5]), translate);
+        }
+
+        // 真实的缩放和镜像不能被当成误差抹掉
+        #[test]
+        fn normalization_keeps_real_scale(angle in -3.0..3.0f64, scale in prop_oneof![0.01..0.99f64, 1.01..100.0f64], mirror in any::<bool>()) {
+            let mut rotation = get_rotation_matrix(angle) * scale;
+            if mirror {
+                rotation[(0, 0)] = -rotation[(0, 0)];
+                rotation[(1, 0)] = -rotation[(1, 0)];
+            }
+            rotation[(2, 2)] = 1.0;
+            let matrix = convert_3x3_to_1x6(rotation);
+            prop_assert_eq!(normalize_if_needed(matrix), matrix);
+        }
+
+        #[test]
+        fn normalization_keeps_mirror_sign(angle in -3.0..3.0f64, drift in 1e-12..1e-7f64) {
+            let mut rotation = get_rotation_matrix(angle) * (1.0 + drift);
+            rotation[(0, 0)] = -rotation[(0, 0)];
+            rotation[(1, 0)] = -rotation[(1, 0)];
+            let normalized = normalize_3x3_if_needed(rotation);
+            prop_assert!(close(normalized.determinant(), -1.0, 1e-12));
+            prop_assert_eq!(normalized.row(2).into_owned(), na::RowVector3::new(0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn compose_undoes_decompose(values in prop::array::uniform6(-1e3..1e3f64)) {
+            let [a, b, c, d, e, f] = values;
+            let matrix = na::Matrix1x6::new(a, b, c, d, e, f);
+            let recomposed = compose_transform(&decompose_transform(matrix));
+            prop_assert!(matrix.iter().zip(recomposed.iter()).all(|(x, y)| close(*x, *y, 1e-9)));
+        }
+
+        #[test]
+        fn decompose_undoes_compose(
+            translate in (-1e3..1e3f64, -1e3..1e3f64),
+            rotation in -3.1..3.1f64,
+            scale in (prop_oneof![-10.0..-0.1f64, 0.1..10.0f64], 0.1..10.0f64),
+            skew_x in -5.0..5.0f64,
+        ) {
+            let parts = TransformParts { translate, rotation, scale, skew: (skew_x, 0.0) };
+            let decomposed = decompose_transform(compose_transform(&parts));
+            prop_assert_eq!(decomposed.translate, translate);
+            prop_assert!(close(decomposed.rotation, rotation, 1e-9));
+            prop_assert!(close(decomposed.scale.0, scale.0, 1e-9) && close(decomposed.scale.1, scale.1, 1e-9));
+            prop_assert!(close(decomposed.skew.0, skew_x, 1e-9) && decomposed.skew.1 == 0.0);
+        }
+
+        // 斜切和旋转同时存在时，旋转角仍然按元素的属性还原
+        #[test]
+        fn anchored_round_trip(
+            width in 1.0..500.0f64,
+            height in 1.0..500.0f64,
+            anchor in (0.0..1.0f64, 0.0..1.0f64),
+            rotation in -179.0..179.0f64,
+            scale in (0.1..10.0f64, 0.1..10.0f64),
+            skew_x in -3.0..3.0f64,
+            position in (-1e3..1e3f64, -1e3..1e3f64),
+        ) {
+            let base = na::Matrix1x6::new(scale.0, skew_x, 0.0, scale.1, position.0, position.1);
+            let matrix = anchored_transform(base, width, height, rotation, anchor);
+            let (decomposed, decomposed_rotation) = decompose_anchored(matrix, width, height, anchor);
+            prop_assert!(close(decomposed_rotation, rotation, 1e-9));
+            prop_assert!(base.iter().zip(decomposed.iter()).all(|(x, y)| close(*x, *y, 1e-9)));
+        }
+
+        // 任意分量（包括 skew_y）分解后换成等价的规范形式，绘制结果不变
+        #[test]
+        fn anchored_decompose_preserves_matrix(
+            values in prop::array::uniform4(-5.0..5.0f64),
+            rotation in -720.0..720.0f64,
+            anchor in (0.0..1.0f64, 0.0..1.0f64),
+        ) {
+            let [scale_x, skew_x, skew_y, scale_y] = values;
+            let base = na::Matrix1x6::new(scale_x, skew_x, skew_y, scale_y, 12.0, -34.0);
+            let matrix = anchored_transform(base, 80.0, 60.0, rotation, anchor);
+            let (decomposed, decomposed_rotation) = decompose_anchored(matrix, 80.0, 60.0, anchor);
+            let recomposed = anchored_transform(decomposed, 80.0, 60.0, decomposed_rotation, anchor);
+            prop_assert!(matrix.iter().zip(recomposed.iter()).all(|(x, y)| close(*x, *y, 1e-9)));
+        }
+    }
+
+    #[test]
+    fn normalization_leaves_singular_matrices() {
+        let matrix = na::Matrix1x6::new(0.0, 0.0, 0.0, 0.0, 5.0, 6.0);
+        assert_eq!(normalize_if_needed(matrix), matrix);
+    }
+}
@@ -1,19 +1,31 @@
 use crate::{
-    app::App, element::Renderable, history::{ElementHistoryItem, HistoryItem}, render_control::{UpdateBody, UpdateMessage, UpdateType}
+    app::App, bounding_box::BoundingBox, element::{ObjectMeta, Renderable, METADATA_KEY}, error::{EditingError, EditingResult}, math::convert_1x6_to_3x3, history::{ElementHistoryItem, HistoryItem}, log::log_warn, render_control::{UpdateBody, UpdateMessage, UpdateType}, spatial_index::SpatialIndex
 };
 use glam::DVec2;
+use nalgebra as na;
 use serde_json::Value;
 use std::{
     cell::RefCell,
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     rc::Rc,
 };
 
+mod pick_colors;
+
+use pick_colors::PickColors;
+
+// 每种元素类型最多保留的回收对象数
+const DEFAULT_MAX_POOL_SIZE: usize = 1024;
+// 容器嵌套的最大层数，防止数据中的循环引用导致无限递归
+const MAX_NESTING: usize = 64;
+
 #[derive(Debug)]
 struct ObjectData {
     object: Rc<RefCell<Box<dyn Renderable>>>,
     last_update: f64,
     position: DVec2,
+    // 绘制顺序，越大越靠上
+    z_index: u64,
 }
 
 
@@ -21,16 +33,33 @@ struct ObjectData {
 pub struct ObjectManager {
     app: Option<App>,
     objects: HashMap<String, ObjectData>,
+    // hit 画布颜色 -> 对象 id，同时是本场景中已占用的拾取颜色
+    color_index: HashMap<[u8; 4], String>,
+    pick_colors: PickColors,
+    spatial_index: RefCell<SpatialIndex>,
+    next_z_index: u64,
+    // 元素类型 -> 已回收、可复用的对象
+    pool: HashMap<String, Vec<Box<dyn Renderable>>>,
+    max_pool_size: usize,
     update_queue: VecDeque<String>,
     total_time: f64,
+    // 子对象 id -> 所在容器 id，由容器的 children() 推导，刷新空间索引时重建
+    parents: RefCell<HashMap<String, String>>,
 }
 
 impl ObjectManager {
     pub fn new() -> Self {
         Self {
             objects: HashMap::new(),
+            color_index: HashMap::new(),
+            pick_colors: PickColors::default(),
+            spatial_index: RefCell::new(SpatialIndex::new()),
+            next_z_index: 0,
+            pool: HashMap::new(),
+            max_pool_size: DEFAULT_MAX_POOL_SIZE,
             update_queue: VecDeque::new(),
             total_time: 0.0,
+            parents: RefCell::new(HashMap::new()),
             app: None,
         }
     }
@@ -39,47 +68,86 @@ impl ObjectManager {
         self.app = Some(app.clone());
     }
 
-    pub fn add(&mut self, mut object: Box<dyn Renderable>) {
-        if let Some(app) = &self.app {
-            object.attach(app);
-            let id = object.id().value().to_string();
-            let object_id = object.id().value().to_string();
-            let object_type = object.get_type().to_string();
-            let object_value = object.to_value();
-            let position = DVec2::new(object.position().0, object.position().1);
-            let object_data = ObjectData {
-                object: Rc::new(RefCell::new(object)),
-                last_update: self.total_time,
-                position,
-            };
-    
-            self.objects.insert(id.clone(), object_data);
-            self.update_queue.push_back(id);
-            let item = ElementHistoryItem::new(object_id, object_type, object_value);
-            app.history.borrow_mut().push(HistoryItem::AddElement(item));
+    pub fn detach(&mut self) {
+        self.app = None;
+    }
+
+    pub fn destroy(&mut self) {
+        for data in self.objects.values() {
+            data.object.borrow_mut().detach();
         }
+        self.pool.clear();
+        self.clear();
+        self.detach();
+    }
 
+    // id 与场景中已有的对象重复时返回错误
+    pub fn add(&mut self, object: Box<dyn Renderable>) -> EditingResult<()> {
+        let app = self
+            .app
+            .clone()
+            .ok_or(EditingError::NotInitialized("ObjectManager"))?;
+        let object_type = object.get_type().to_string();
+        let object_id = self.insert(object, &app)?;
+        // 插入后才有拾取颜色
+        let object_value = self.objects[&object_id].object.borrow().to_value();
+
+        let item = ElementHistoryItem::new(object_id, object_type, object_value);
+        app.record_history(HistoryItem::AddElement(item));
+        Ok(())
     }
 
     pub fn remove(&mut self, id: &str) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        self.app.as_ref()?;
+        let object = self.take(id)?;
+
+        let object_id = object.borrow().id().value().to_string();
+        let object_type = object.borrow().get_type().to_string();
+        let object_value = object.borrow().to_value();
+        let item = ElementHistoryItem::new(object_id, object_type, object_value);
         if let Some(app) = &self.app {
-            if let Some(object_data) = self.objects.remove(id) {
-                self.update_queue.retain(|queue_id| queue_id != id);
-    
-                let object = object_data.object;
-                let object_id = object.borrow().id().value().to_string();
-                let object_type = object.borrow().get_type().to_string();
-                let object_value = object.borrow().to_value();
-                let item = ElementHistoryItem::new(object_id, object_type, object_value);
-                app.history.borrow_mut().push(HistoryItem::RemoveElement(item));
-    
-                Some(object)
-            } else {
-                None
-            }
-        } else {
-            None
+            app.record_history(HistoryItem::RemoveElement(item));
+        }
+        Some(object)
+    }
+
+    // 加入场景并建立索引，不记录历史；重新加入的对象（撤销删除、对象池）尽量沿用原来的拾取颜色，
+    // 没有颜色或颜色已被其他对象占用时重新分配
+    fn insert(&mut self, mut object: Box<dyn Renderable>, app: &App) -> EditingResult<String> {
+        let id = object.id().value().to_string();
+        if self.objects.contains_key(&id) {
+            return Err(EditingError::DuplicateObjectId(id));
         }
+        if !object.id().has_color() || self.color_index.contains_key(&object.id().color_id()) {
+            let color_id = self.pick_colors.allocate(&self.color_index)?;
+            object.id_mut().set_color_id(color_id);
+        }
+        object.attach(app);
+        let position = DVec2::new(object.position().0, object.position().1);
+        self.color_index.insert(object.id().color_id(), id.clone());
+        self.spatial_index.borrow_mut().insert(&id, object.bounding_box());
+        let object_data = ObjectData {
+            object: Rc::new(RefCell::new(object)),
+            last_update: self.total_time,
+            position,
+            z_index: self.next_z_index,
+        };
+        self.next_z_index += 1;
+
+        self.objects.insert(id.clone(), object_data);
+        self.update_queue.push_back(id.clone());
+        Ok(id)
+    }
+
+    // 移出场景并清理索引，不记录历史
+    fn take(&mut self, id: &str) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        let object_data = self.objects.remove(id)?;
+        self.update_queue.retain(|queue_id| queue_id != id);
+
+        let object = object_data.object;
+        self.color_index.remove(&object.borrow().id().color_id());
+        self.spatial_index.borrow_mut().remove(id);
+        Some(object)
     }
 
     pub fn get(&self, id: &str) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
@@ -90,6 +158,10 @@ impl ObjectManager {
         }
     }
 
+    pub fn get_by_color(&self, color: [u8; 4]) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        self.color_index.get(&color).and_then(|id| self.get(id))
+    }
+
     pub fn contains(&self, id: &str) -> bool {
         self.objects.contains_key(id)
     }
@@ -104,20 +176,303 @@ impl ObjectManager {
 
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.color_index.clear();
+        self.spatial_index.borrow_mut().clear();
         self.update_queue.clear();
+        self.parents.borrow_mut().clear();
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Rc<RefCell<Box<dyn Renderable>>>)> {
         self.objects.iter().map(|(id, data)| (id, &data.object))
     }
 
+    // 按 z 顺序从下到上返回
     pub fn get_objects(&self) -> Vec<Rc<RefCell<Box<dyn Renderable>>>> {
-        self.objects
+        let mut objects: Vec<&ObjectData> = self.objects.values().collect();
+        objects.sort_by_key(|data| data.z_index);
+        objects.into_iter().map(|data| data.object.clone()).collect()
+    }
+
+    // 按 ids 的顺序从下到上重新排列，不在其中的对象保持原有顺序排在最上面
+    pub fn reorder(&mut self, ids: &[String]) {
+        let mut rest: Vec<(u64, String)> = self
+            .objects
             .iter()
-            .map(|(_, data)| data.object.clone())
+            .filter(|(id, _)| !ids.contains(id))
+            .map(|(id, data)| (data.z_index, id.clone()))
+            .collect();
+        rest.sort();
+        let order = ids.iter().chain(rest.iter().map(|(_, id)| id));
+
+        let mut z_index = 0;
+        for id in order {
+            if let Some(data) = self.objects.get_mut(id) {
+                data.z_index = z_index;
+                z_index += 1;
+            }
+        }
+        self.next_z_index = z_index;
+    }
+
+    // 把属性变化过（仍标记为脏）的对象的新包围盒同步到空间索引
+    // 依赖的对象（或自身）有变化时重新计算连线等元素，需在本帧清除脏标记之前调用
+    pub fn update_dependents(&self) {
+        for data in self.objects.values() {
+            let dependencies = data.object.borrow().dependencies();
+            if dependencies.is_empty() {
+                continue;
+            }
+            let targets: Vec<_> = dependencies.iter().map(|id| self.get(id)).collect();
+            let changed = data.object.borrow().is_dirty()
+                || targets
+                    .iter()
+                    .flatten()
+                    .any(|target| target.try_borrow().is_ok_and(|target| target.is_dirty()));
+            if !changed {
+                continue;
+            }
+            let bounds: Vec<_> = targets
+                .iter()
+                .map(|target| {
+                    let target = target.as_ref()?.try_borrow().ok()?;
+                    Some(self.to_world_bounds(target.id().value(), target.bounding_box()))
+                })
+                .collect();
+            if let Ok(mut object) = data.object.try_borrow_mut() {
+                object.resolve_dependencies(&bounds);
+            }
+        }
+    }
+
+    pub fn refresh_spatial_index(&self) {
+        self.rebuild_parents();
+        let parents = self.parents.borrow();
+        let dirty: HashSet<&String> = self
+            .objects
+            .iter()
+            .filter(|(_, data)| data.object.try_borrow().is_ok_and(|object| object.is_dirty()))
+            .map(|(id, _)| id)
+            .collect();
+        // 容器变化时子对象的世界包围盒随之变化，子对象变化时不裁剪的容器的索引范围也会变化
+        let mut stale = dirty.clone();
+        for child in parents.keys() {
+            let ancestors = self.ancestors(child);
+            if ancestors.iter().any(|ancestor| dirty.contains(ancestor)) {
+                stale.insert(child);
+            }
+            if dirty.contains(child) {
+                stale.extend(ancestors.iter().filter_map(|ancestor| self.objects.get_key_value(ancestor)).map(|(id, _)| id));
+            }
+        }
+
+        let mut spatial_index = self.spatial_index.borrow_mut();
+        for id in stale {
+            if let Some(bounds) = self.extent_at(id, 0) {
+                spatial_index.insert(id, bounds);
+            }
+        }
+    }
+
+    // 重建子对象到容器的映射；同一对象被多个容器引用时归属 z 顺序靠上的容器，
+    // 正在修改而无法借用的容器沿用原来的子对象
+    fn rebuild_parents(&self) {
+        let mut parents: HashMap<String, (u64, String)> = HashMap::new();
+        let previous = self.parents.borrow();
+        for (id, data) in self.objects.iter() {
+            let children = match data.object.try_borrow() {
+                Ok(object) => object.children(),
+                Err(_) => previous
+                    .iter()
+                    .filter(|(_, parent)| *parent == id)
+                    .map(|(child, _)| child.clone())
+                    .collect(),
+            };
+            for child in children {
+                if child == *id || !self.objects.contains_key(&child) {
+                    continue;
+                }
+                let claimed = parents.get(&child).is_some_and(|(z_index, _)| *z_index > data.z_index);
+                if !claimed {
+                    parents.insert(child, (data.z_index, id.clone()));
+                }
+            }
+        }
+        drop(previous);
+        *self.parents.borrow_mut() = parents
+            .into_iter()
+            .map(|(child, (_, parent))| (child, parent))
+            .collect();
+    }
+
+    // 所在的容器，顶层对象为 None
+    pub fn parent_of(&self, id: &str) -> Option<String> {
+        self.parents.borrow().get(id).cloned()
+    }
+
+    // 从直接所在的容器到最外层容器
+    pub fn ancestors(&self, id: &str) -> Vec<String> {
+        let parents = self.parents.borrow();
+        let mut ancestors = Vec::new();
+        let mut current = id;
+        while let Some(parent) = parents.get(current) {
+            if ancestors.len() >= MAX_NESTING || ancestors.contains(parent) {
+                break;
+            }
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+        ancestors
+    }
+
+    // 容器中实际绘制的子对象，从下到上；只包括存在且归属于该容器的对象
+    pub fn children_of(&self, id: &str) -> Vec<String> {
+        let Some(object) = self.get(id) else {
+            return Vec::new();
+        };
+        let Ok(object) = object.try_borrow() else {
+            return Vec::new();
+        };
+        let parents = self.parents.borrow();
+        object
+            .children()
+            .into_iter()
+            .filter(|child| parents.get(child).is_some_and(|parent| parent == id))
             .collect()
     }
 
+    // 对象所在坐标系到世界坐标的变换，即各级容器变换的乘积；顶层对象为单位矩阵
+    pub fn parent_transform(&self, id: &str) -> na::Matrix3<f64> {
+        self.ancestors(id)
+            .iter()
+            .filter_map(|ancestor| {
+                let object = self.get(ancestor)?;
+                let transform = object.try_borrow().ok()?.calc_transform();
+                Some(convert_1x6_to_3x3(transform))
+            })
+            .fold(na::Matrix3::identity(), |transform, ancestor| ancestor * transform)
+    }
+
+    // 世界坐标点是否落在对象某一级容器的裁剪区域之外
+    pub fn is_clipped(&self, id: &str, x: f64, y: f64) -> bool {
+        self.ancestors(id).iter().any(|ancestor| {
+            let Some(object) = self.get(ancestor) else {
+                return false;
+            };
+            let Ok(object) = object.try_borrow() else {
+                return false;
+            };
+            let Some(clip) = object.child_clip() else {
+                return false;
+            };
+            let transform = self.parent_transform(ancestor) * convert_1x6_to_3x3(object.calc_transform());
+            let Some(inverse) = transform.try_inverse() else {
+                return true;
+            };
+            let point = inverse * na::Vector3::new(x, y, 1.0);
+            !clip.contains_point(point.x, point.y)
+        })
+    }
+
+    // 世界坐标下的包围盒；容器中的子对象的 bounding_box() 在容器坐标系下
+    pub fn world_bounds(&self, id: &str) -> Option<BoundingBox> {
+        let object = self.get(id)?;
+        let bounds = object.try_borrow().ok()?.bounding_box();
+        Some(self.to_world_bounds(id, bounds))
+    }
+
+    fn to_world_bounds(&self, id: &str, bounds: BoundingBox) -> BoundingBox {
+        if !self.parents.borrow().contains_key(id) {
+            return bounds;
+        }
+        let transform = self.parent_transform(id);
+        let corners = bounds.corners().map(|(x, y)| {
+            let point = transform * na::Vector3::new(x, y, 1.0);
+            (point.x, point.y)
+        });
+        BoundingBox::from_points(&corners)
+    }
+
+    // 对象在世界坐标下占据的范围，不裁剪的容器还包括超出范围的子对象；空间索引中存的是这个范围
+    pub fn extent(&self, id: &str) -> Option<BoundingBox> {
+        self.extent_at(id, 0)
+    }
+
+    // 所有顶层对象范围的并集，没有对象时为 None
+    pub fn document_bounds(&self) -> Option<BoundingBox> {
+        self.refresh_spatial_index();
+        self.iter()
+            .filter(|(id, _)| self.parent_of(id).is_none())
+            .filter_map(|(id, _)| self.extent(id))
+            .reduce(|bounds, other| bounds.union(&other))
+    }
+
+    fn extent_at(&self, id: &str, depth: usize) -> Option<BoundingBox> {
+        let mut bounds = self.world_bounds(id)?;
+        let clips = self.get(id)?.try_borrow().ok()?.child_clip().is_some();
+        if !clips && depth < MAX_NESTING {
+            for child in self.children_of(id) {
+                if let Some(child_bounds) = self.extent_at(&child, depth + 1) {
+                    bounds = bounds.union(&child_bounds);
+                }
+            }
+        }
+        Some(bounds)
+    }
+
+    // 所有对象从下到上的绘制顺序：顶层对象按 z 顺序，子对象紧跟在所在的容器之后
+    pub fn paint_order(&self) -> HashMap<String, usize> {
+        let parents = self.parents.borrow();
+        let mut roots: Vec<(&String, &ObjectData)> = self
+            .objects
+            .iter()
+            .filter(|(id, _)| !parents.contains_key(*id))
+            .collect();
+        roots.sort_by_key(|(_, data)| data.z_index);
+        drop(parents);
+
+        let mut order = HashMap::new();
+        let mut stack: Vec<(String, usize)> = roots
+            .into_iter()
+            .rev()
+            .map(|(id, _)| (id.clone(), 0))
+            .collect();
+        while let Some((id, depth)) = stack.pop() {
+            if depth < MAX_NESTING {
+                stack.extend(self.children_of(&id).into_iter().rev().map(|child| (child, depth + 1)));
+            }
+            let index = order.len();
+            order.entry(id).or_insert(index);
+        }
+        order
+    }
+
+    // 包围盒与 rect（世界坐标）相交的对象 id，按 z 顺序从下到上
+    pub fn query_rect(&self, rect: &BoundingBox) -> Vec<String> {
+        self.refresh_spatial_index();
+        let ids = self.spatial_index.borrow().query_rect(rect);
+        self.sort_by_z(ids)
+    }
+
+    // 包围盒包含该点的对象 id，按 z 顺序从下到上
+    pub fn query_point(&self, x: f64, y: f64) -> Vec<String> {
+        self.refresh_spatial_index();
+        let ids = self.spatial_index.borrow().query_point(x, y);
+        self.sort_by_z(ids)
+    }
+
+    pub fn objects_in_rect(&self, rect: &BoundingBox) -> Vec<Rc<RefCell<Box<dyn Renderable>>>> {
+        self.query_rect(rect)
+            .iter()
+            .filter_map(|id| self.get(id))
+            .collect()
+    }
+
+    fn sort_by_z(&self, mut ids: Vec<String>) -> Vec<String> {
+        ids.retain(|id| self.objects.contains_key(id));
+        ids.sort_by_key(|id| self.objects[id].z_index);
+        ids
+    }
+
     pub fn get_animatables(&self) -> Vec<Rc<RefCell<Box<dyn Renderable>>>> {
         self.objects
             .values()
@@ -132,7 +487,7 @@ impl ObjectManager {
             .collect()
     }
 
-    pub fn update_object_from_message(&mut self, messages: &Vec<UpdateMessage>) {
+    pub fn update_object_from_message(&mut self, messages: &[UpdateMessage]) {
         let mut update_objects: HashMap<String, Vec<UpdateBody>> = HashMap::new();
         for message in messages.iter() {
             if let UpdateMessage::Update(update_body) = message {
@@ -163,15 +518,107 @@ impl ObjectManager {
                         }
                     }
                 }
-                None => todo!(),
+                None => log_warn!("Skipping update for unknown object: {}", object_id),
+            }
+        }
+    }
+
+    // 从对象池取出一个该类型的对象（没有时用 create 新建），用 data 重置属性后加入场景；
+    // 池化对象用于粒子等短生命周期场景，进出场景都不记录历史
+    pub fn acquire(
+        &mut self,
+        element_type: &str,
+        data: Value,
+        create: impl FnOnce() -> EditingResult<Box<dyn Renderable>>,
+    ) -> EditingResult<String> {
+        let app = self
+            .app
+            .clone()
+            .ok_or(EditingError::NotInitialized("ObjectManager"))?;
+        let mut object = match self.pool.get_mut(element_type).and_then(|pooled| pooled.pop()) {
+            Some(object) => object,
+            None => create()?,
+        };
+        object.update(data);
+        self.insert(object, &app)
+    }
+
+    // 把对象移出场景放回对象池，再次取出时颜色未被占用则沿用
+    pub fn release(&mut self, id: &str) -> bool {
+        let Some(object) = self.take(id) else {
+            return false;
+        };
+
+        let element_type = object.borrow().get_type().to_string();
+        let pooled = self.pool.entry(element_type).or_default();
+        // 其他地方仍持有引用或池已满时直接丢弃
+        if let Ok(object) = Rc::try_unwrap(object) {
+            if pooled.len() < self.max_pool_size {
+                let mut object = object.into_inner();
+                object.detach();
+                *object.meta_mut() = ObjectMeta::default();
+                pooled.push(object);
             }
         }
+        true
     }
 
-    pub fn update_object(&mut self, id: String, data: Value) {
-        if let Some(object_data) = self.objects.get_mut(&id) {
-            let mut object = object_data.object.borrow_mut();
-            object.update(data);
+    // 预先放入新建的对象，池已满时返回 false
+    pub fn fill_pool(&mut self, object: Box<dyn Renderable>) -> bool {
+        let pooled = self.pool.entry(object.get_type().to_string()).or_default();
+        if pooled.len() >= self.max_pool_size {
+            return false;
+        }
+        pooled.push(object);
+        true
+    }
+
+    pub fn set_max_pool_size(&mut self, size: usize) {
+        self.max_pool_size = size;
+        for pooled in self.pool.values_mut() {
+            pooled.truncate(size);
+        }
+    }
+
+    pub fn pool_size(&self, element_type: &str) -> usize {
+        self.pool.get(element_type).map_or(0, Vec::len)
+    }
+
+    pub fn find_by_tag(&self, tag: &str) -> Vec<String> {
+        self.objects
+            .iter()
+            .filter(|(_, data)| data.object.borrow().meta().has_tag(tag))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    pub fn update_meta(&self, id: &str, f: impl FnOnce(&mut ObjectMeta)) -> EditingResult<()> {
+        let object_data = self
+            .objects
+            .get(id)
+            .ok_or_else(|| EditingError::ObjectNotFound(id.to_string()))?;
+        f(object_data.object.borrow_mut().meta_mut());
+        Ok(())
+    }
+
+    pub fn get_meta(&self, id: &str) -> Option<ObjectMeta> {
+        self.objects
+            .get(id)
+            .map(|data| data.object.borrow().meta().clone())
+    }
+
+    pub fn update_object(&mut self, id: String, mut data: Value) -> EditingResult<()> {
+        let object_data = self
+            .objects
+            .get_mut(&id)
+            .ok_or(EditingError::ObjectNotFound(id))?;
+        let mut object = object_data.object.borrow_mut();
+        if let Some(Value::Object(patch)) = data.as_object_mut().and_then(|data| data.remove(METADATA_KEY)) {
+            for (key, value) in patch {
+                object.meta_mut().set(key, value);
+            }
         }
+        object.update(data);
+        Ok(())
     }
 }
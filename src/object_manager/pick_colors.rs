@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use crate::error::{EditingError, EditingResult};
+
+// 拾取颜色只用 RGB 三个通道编码，0 为背景色不分配
+const MAX_COLOR_INDEX: u32 = 0x00FF_FFFF;
+// 相邻序号映射到相距很远的颜色，避免相邻对象抗锯齿边缘混合出的颜色正好是另一个对象的颜色；
+// 奇数步长在 2^24 内是一一映射，0 仍映射到 0
+const COLOR_STRIDE: u32 = 0x009E_3779;
+
+// 每个 ObjectManager 各自分配拾取颜色，不同 App 之间互不影响
+#[derive(Debug)]
+pub struct PickColors {
+    // 下一个尝试分配的序号，按顺序递增，回绕后跳过仍被占用的颜色
+    next_index: u32,
+}
+
+impl Default for PickColors {
+    fn default() -> Self {
+        Self { next_index: 1 }
+    }
+}
+
+impl PickColors {
+    // 按序号顺序分配，只有在序号回绕后才可能遇到占用的颜色，不依赖随机重试；in_use 为已占用的颜色
+    pub fn allocate<V>(&mut self, in_use: &HashMap<[u8; 4], V>) -> EditingResult<[u8; 4]> {
+        for _ in 0..MAX_COLOR_INDEX {
+            let index = self.next_index;
+            self.next_index = if index >= MAX_COLOR_INDEX { 1 } else { index + 1 };
+            let color_id = encode_color(index);
+            if !in_use.contains_key(&color_id) {
+                return Ok(color_id);
+            }
+        }
+        Err(EditingError::PickColorsExhausted(in_use.len()))
+    }
+}
+
+fn encode_color(index: u32) -> [u8; 4] {
+    let value = index.wrapping_mul(COLOR_STRIDE) & MAX_COLOR_INDEX;
+    [
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+        255, // 保持 alpha 通道为 255
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn allocate_many(count: usize) -> Vec<[u8; 4]> {
+        let mut colors = PickColors::default();
+        let mut allocated = HashMap::new();
+        (0..count)
+            .map(|index| {
+                let color = colors.allocate(&allocated).unwrap();
+                allocated.insert(color, index);
+                color
+            })
+            .collect()
+    }
+
+    #[test]
+    fn colors_are_unique_and_opaque() {
+        let colors = allocate_many(20_000);
+        let unique: HashSet<_> = colors.iter().collect();
+        assert_eq!(unique.len(), colors.len());
+        assert!(colors.iter().all(|color| color[3] == 255 && color[..3] != [0, 0, 0]));
+    }
+
+    // 相邻分配的两个对象边缘按任意比例混合，都不会得到另一个已分配的颜色
+    #[test]
+    fn blended_neighbours_do_not_decode_to_other_objects() {
+        let colors = allocate_many(5_000);
+        let allocated: HashSet<_> = colors.iter().copied().collect();
+        for pair in colors.windows(2) {
+            for t in [0.25, 0.5, 0.75] {
+                let mut blended = [0, 0, 0, 255];
+                for channel in 0..3 {
+                    let mixed = pair[0][channel] as f64 * (1.0 - t) + pair[1][channel] as f64 * t;
+                    blended[channel] = mixed.round() as u8;
+                }
+                assert!(!allocated.contains(&blended), "{:?} blends into {:?}", pair, blended);
+            }
+        }
+    }
+
+    #[test]
+    fn skips_colors_in_use() {
+        let mut colors = PickColors::default();
+        let in_use = HashMap::from([(encode_color(1), ()), (encode_color(2), ())]);
+        assert_eq!(colors.allocate(&in_use).unwrap(), encode_color(3));
+        assert_eq!(colors.allocate(&in_use).unwrap(), encode_color(4));
+    }
+}
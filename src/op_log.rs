@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{EditingError, EditingResult};
+use crate::history::{ElementHistoryItem, HistoryItem};
+
+// 操作日志格式版本，结构不兼容时递增
+pub const OP_LOG_FORMAT_VERSION: u32 = 1;
+
+// 一次修改及其发生时间（距开始录制的毫秒数），记录均为正向，撤销已转换为反向记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedOperation {
+    pub time: f64,
+    pub items: Vec<HistoryItem>,
+}
+
+// 录制开始时的文档和之后的全部修改，从同一份初始状态按顺序应用即可得到相同的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpLog {
+    pub version: u32,
+    // 开始录制的时间（Unix 毫秒），只用于展示
+    pub started_at: f64,
+    pub duration: f64,
+    // 开始时的视图和按绘制顺序排列的对象
+    pub scene: Value,
+    pub objects: Vec<ElementHistoryItem>,
+    pub operations: Vec<RecordedOperation>,
+}
+
+impl OpLog {
+    pub fn to_json(&self) -> EditingResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(data: &str) -> EditingResult<Self> {
+        let log: OpLog = serde_json::from_str(data)?;
+        if log.version != OP_LOG_FORMAT_VERSION {
+            return Err(EditingError::UnsupportedOpLogVersion(log.version));
+        }
+        Ok(log)
+    }
+}
+
+#[derive(Debug)]
+pub struct OpLogRecorder {
+    // 开始录制时的 now_ms()
+    start: f64,
+    log: OpLog,
+}
+
+impl OpLogRecorder {
+    pub fn new(start: f64, started_at: f64, scene: Value, objects: Vec<ElementHistoryItem>) -> Self {
+        Self {
+            start,
+            log: OpLog {
+                version: OP_LOG_FORMAT_VERSION,
+                started_at,
+                duration: 0.0,
+                scene,
+                objects,
+                operations: Vec::new(),
+            },
+        }
+    }
+
+    pub fn record(&mut self, now: f64, items: Vec<HistoryItem>) {
+        if items.is_empty() {
+            return;
+        }
+        self.log.operations.push(RecordedOperation {
+            time: (now - self.start).max(0.0),
+            items,
+        });
+    }
+
+    // 到目前为止的日志，录制继续进行
+    pub fn snapshot(&self, now: f64) -> OpLog {
+        OpLog {
+            duration: (now - self.start).max(0.0),
+            ..self.log.clone()
+        }
+    }
+
+    pub fn finish(mut self, now: f64) -> OpLog {
+        self.log.duration = (now - self.start).max(0.0);
+        self.log
+    }
+}
@@ -1,110 +1,204 @@
-use futures::{
-    channel::mpsc::{channel, Receiver, Sender},
-    StreamExt,
-};
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::VecDeque;
 use std::fmt::Debug;
-use std::sync::Once;
-use wasm_bindgen::JsValue;
-use wasm_bindgen_futures::spawn_local;
+use std::time::Duration;
 use wasm_timer::Instant;
-use web_sys::console;
 
-static INIT: Once = Once::new();
-static mut GLOBAL_RENDER_CONTROL: Option<RenderControl> = None;
-
-pub fn get_render_control() -> &'static mut RenderControl {
-    unsafe {
-        INIT.call_once(|| {
-            GLOBAL_RENDER_CONTROL = Some(RenderControl::new());
-        });
-        GLOBAL_RENDER_CONTROL.as_mut().unwrap()
-    }
-}
+use crate::helper::now_ms;
+use into_static_str::IntoStaticStr;
 
+// 缓存外部提交的更新消息，由 App 在每帧渲染前统一取出应用
 pub struct RenderControl {
-    sender: Sender<Vec<UpdateMessage>>,
-    receiver: Receiver<Vec<UpdateMessage>>,
     buffer: VecDeque<UpdateMessage>,
+    stats: RenderControlStats,
     last_flush: Instant,
-    flush_interval: f64,
+    // 两次应用之间的最短间隔，0 表示每帧都应用
+    flush_interval: Duration,
+    // 收到 ForceUpdate 后需要立即应用，不等下一帧
+    force: bool,
+}
+
+impl Debug for RenderControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RenderControl {{ buffered: {} }}", self.buffer.len())
+    }
 }
 
 impl RenderControl {
     pub fn new() -> Self {
-        let (sender, receiver) = channel(1);
         Self {
-            sender,
-            receiver,
             buffer: VecDeque::new(),
+            stats: RenderControlStats::default(),
             last_flush: Instant::now(),
-            flush_interval: 0.008, // 8ms
+            flush_interval: Duration::ZERO,
+            force: false,
         }
     }
 
     pub fn add_message(&mut self, message: UpdateMessage) {
         match message {
             UpdateMessage::ForceUpdate => {
-                self.flush();
-                self.buffer.clear();
+                self.force = true;
             }
             UpdateMessage::Update(update_body) => {
-                // Insert the message into the buffer based on priority
-                let insert_position = self
-                    .buffer
-                    .iter()
-                    .position(|m| {
-                        if let UpdateMessage::Update(existing_body) = m {
-                            existing_body.priority < update_body.priority
-                        } else {
-                            false
-                        }
-                    })
-                    .unwrap_or(self.buffer.len());
-                self.buffer
-                    .insert(insert_position, UpdateMessage::Update(update_body));
-                self.flush_if_needed();
+                self.stats.received += 1;
+                let Some(update_body) = self.merge(update_body) else {
+                    return;
+                };
+                self.insert_by_priority(update_body, false);
             }
         }
     }
 
-    fn flush_if_needed(&mut self) {
-        let elapsed = self.last_flush.elapsed().as_secs_f64();
-        let current_time = Instant::now().elapsed().as_secs_f64();
+    // 优先级高的先应用；同优先级保持提交顺序，放回的旧消息排在同级新消息之前
+    fn insert_by_priority(&mut self, update_body: UpdateBody, requeued: bool) {
+        let insert_position = self
+            .buffer
+            .iter()
+            .position(|m| match m {
+                UpdateMessage::Update(existing_body) if requeued => {
+                    existing_body.priority <= update_body.priority
+                }
+                UpdateMessage::Update(existing_body) => existing_body.priority < update_body.priority,
+                UpdateMessage::ForceUpdate => false,
+            })
+            .unwrap_or(self.buffer.len());
+        self.buffer
+            .insert(insert_position, UpdateMessage::Update(update_body));
+    }
 
-        if (elapsed - current_time) >= self.flush_interval {
-            self.flush();
+    // 超出帧预算未能应用的消息放回缓存，下一帧继续
+    pub fn requeue(&mut self, messages: Vec<UpdateMessage>) {
+        self.stats.applied -= messages.len() as u64;
+        self.stats.deferred += messages.len() as u64;
+        for message in messages.into_iter().rev() {
+            if let UpdateMessage::Update(update_body) = message {
+                self.insert_by_priority(update_body, true);
+            }
         }
     }
 
-    fn flush(&mut self) {
-        if !self.buffer.is_empty() {
-            let messages: Vec<UpdateMessage> = self.buffer.drain(..).collect();
-            let sender: Sender<Vec<UpdateMessage>> = self.sender.clone();
-            spawn_local(async move {
-                if let Err(e) = sender.clone().try_send(messages) {
-                    console::log_1(&JsValue::from_str(&format!(
-                        "Failed to send messages: {:#?}",
-                        e
-                    )));
-                }
-            });
-            self.last_flush = Instant::now();
+    // 同一目标、同优先级且尚未应用的更新合并为一条，同名属性只保留最新值；
+    // 无法合并（数据不是对象）时原样返回
+    fn merge(&mut self, update_body: UpdateBody) -> Option<UpdateBody> {
+        let Value::Object(new_data) = &update_body.data else {
+            return Some(update_body);
+        };
+        let existing = self.buffer.iter_mut().find_map(|message| match message {
+            UpdateMessage::Update(body)
+                if body.priority == update_body.priority
+                    && body.update_type.same_target(&update_body.update_type)
+                    && body.data.is_object() =>
+            {
+                Some(body)
+            }
+            _ => None,
+        });
+        let existing = existing?;
+        let Value::Object(existing_data) = &mut existing.data else {
+            return Some(update_body);
+        };
+
+        for (key, value) in new_data {
+            if existing_data.insert(key.clone(), value.clone()).is_some() {
+                self.stats.dropped += 1;
+            }
         }
+        existing.timestamp = update_body.timestamp;
+        self.stats.merged += 1;
+        None
+    }
+
+    pub fn stats(&self) -> RenderControlStats {
+        self.stats
     }
 
-    pub async fn receive_messages(&mut self) -> Option<Vec<UpdateMessage>> {
-        self.receiver.next().await
+    pub fn reset_stats(&mut self) {
+        self.stats = RenderControlStats::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn is_forced(&self) -> bool {
+        self.force
+    }
+
+    pub fn set_flush_interval(&mut self, interval: Duration) {
+        self.flush_interval = interval;
+    }
+
+    // 是否应在本帧应用：强制刷新、有输入更新或距上次应用已超过间隔。
+    // 未到时间的消息留在缓存中，由调用方再安排一帧，不会滞留
+    pub fn is_due(&self) -> bool {
+        if self.buffer.is_empty() {
+            return self.force;
+        }
+        let has_input = self.buffer.iter().any(|message| {
+            matches!(message, UpdateMessage::Update(body) if body.priority == UpdatePriority::Input)
+        });
+        self.force || has_input || self.last_flush.elapsed() >= self.flush_interval
+    }
+
+    // 取出当前缓存的全部消息
+    pub fn drain(&mut self) -> Vec<UpdateMessage> {
+        self.force = false;
+        self.last_flush = Instant::now();
+        self.stats.applied += self.buffer.len() as u64;
+        self.buffer.drain(..).collect()
     }
 }
 
-#[derive(Clone, Debug)]
+impl Default for RenderControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, IntoStaticStr)]
 pub enum UpdateType {
+    #[str = "object"]
     ObjectUpdate(String),
+    #[str = "scene"]
     SceneUpdate,
 }
 
+impl UpdateType {
+    fn same_target(&self, other: &UpdateType) -> bool {
+        match (self, other) {
+            (UpdateType::ObjectUpdate(a), UpdateType::ObjectUpdate(b)) => a == b,
+            (UpdateType::SceneUpdate, UpdateType::SceneUpdate) => true,
+            _ => false,
+        }
+    }
+}
+
+// received: 收到的更新消息数；merged: 并入已缓存消息的条数；
+// dropped: 被同名属性新值覆盖掉的旧值个数；applied: 实际交给渲染应用的消息数；
+// deferred: 因帧预算推迟到下一帧的次数
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct RenderControlStats {
+    pub received: u64,
+    pub merged: u64,
+    pub dropped: u64,
+    pub applied: u64,
+    pub deferred: u64,
+}
+
+// 输入 > 动画 > 后台。输入和动画每帧全部应用，后台更新受帧预算限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, IntoStaticStr)]
+pub enum UpdatePriority {
+    #[default]
+    #[str = "background"]
+    Background,
+    #[str = "animation"]
+    Animation,
+    #[str = "input"]
+    Input,
+}
+
 #[derive(Clone, Debug)]
 pub enum UpdateMessage {
     ForceUpdate,
@@ -116,7 +210,7 @@ pub struct UpdateBody {
     pub update_type: UpdateType,
     pub data: Value,
     pub timestamp: f64,
-    pub priority: u8,
+    pub priority: UpdatePriority,
 }
 
 impl UpdateBody {
@@ -124,8 +218,13 @@ impl UpdateBody {
         Self {
             update_type,
             data,
-            timestamp: Instant::now().elapsed().as_secs_f64(),
-            priority: 0, // 默认优先级为0
+            timestamp: now_ms(),
+            priority: UpdatePriority::default(),
         }
     }
+
+    pub fn with_priority(mut self, priority: UpdatePriority) -> Self {
+        self.priority = priority;
+        self
+    }
 }
@@ -0,0 +1,168 @@
+use std::fmt::Debug;
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{window, VisibilityState};
+
+// { fpsCap: 30 }，不传 fpsCap 时跟随显示器刷新率
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RenderLoopOptions {
+    pub fps_cap: Option<f64>,
+}
+
+pub struct RenderLoop {
+    running: bool,
+    hidden: bool,
+    fps_cap: Option<f64>,
+    frame_id: Option<i32>,
+    last_frame_time: Option<f64>,
+    frame_closure: Option<Closure<dyn FnMut(f64)>>,
+    visibility_closure: Option<Closure<dyn FnMut()>>,
+}
+
+impl Debug for RenderLoop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RenderLoop {{ running: {:?}, hidden: {:?}, fps_cap: {:?} }}",
+            self.running, self.hidden, self.fps_cap
+        )
+    }
+}
+
+impl Default for RenderLoop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderLoop {
+    pub fn new() -> Self {
+        Self {
+            running: false,
+            hidden: false,
+            fps_cap: None,
+            frame_id: None,
+            last_frame_time: None,
+            frame_closure: None,
+            visibility_closure: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.running && !self.hidden
+    }
+
+    pub fn set_fps_cap(&mut self, fps_cap: Option<f64>) {
+        self.fps_cap = fps_cap.filter(|fps| *fps > 0.0);
+    }
+
+    pub fn has_closures(&self) -> bool {
+        self.frame_closure.is_some()
+    }
+
+    // 帧回调只创建一次，start/stop 只负责调度，避免在回调执行中释放 Closure
+    pub fn install(
+        &mut self,
+        frame_closure: Closure<dyn FnMut(f64)>,
+        visibility_closure: Closure<dyn FnMut()>,
+    ) {
+        if let Some(document) = window().and_then(|w| w.document()) {
+            let _ = document.add_event_listener_with_callback(
+                "visibilitychange",
+                visibility_closure.as_ref().unchecked_ref(),
+            );
+            self.hidden = document.visibility_state() == VisibilityState::Hidden;
+        }
+        self.frame_closure = Some(frame_closure);
+        self.visibility_closure = Some(visibility_closure);
+    }
+
+    pub fn uninstall(&mut self) {
+        self.stop();
+        if let (Some(document), Some(closure)) = (
+            window().and_then(|w| w.document()),
+            self.visibility_closure.as_ref(),
+        ) {
+            let _ = document.remove_event_listener_with_callback(
+                "visibilitychange",
+                closure.as_ref().unchecked_ref(),
+            );
+        }
+        self.visibility_closure = None;
+        self.frame_closure = None;
+    }
+
+    pub fn start(&mut self, options: RenderLoopOptions) {
+        self.set_fps_cap(options.fps_cap);
+        if self.running {
+            return;
+        }
+        self.running = true;
+        self.last_frame_time = None;
+        self.schedule();
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+        self.cancel();
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+        if hidden {
+            self.cancel();
+        } else {
+            // 重新可见时不把隐藏期间的时间算进 delta
+            self.last_frame_time = None;
+            self.schedule();
+        }
+    }
+
+    pub fn schedule(&mut self) {
+        if !self.is_active() || self.frame_id.is_some() {
+            return;
+        }
+        if let (Some(window), Some(closure)) = (window(), self.frame_closure.as_ref()) {
+            self.frame_id = window
+                .request_animation_frame(closure.as_ref().unchecked_ref())
+                .ok();
+        }
+    }
+
+    fn cancel(&mut self) {
+        if let (Some(window), Some(frame_id)) = (window(), self.frame_id.take()) {
+            let _ = window.cancel_animation_frame(frame_id);
+        }
+    }
+
+    // 返回本帧需要处理的 delta（秒）；被 FPS 限制跳过时返回 None
+    pub fn begin_frame(&mut self, timestamp: f64) -> Option<f64> {
+        self.frame_id = None;
+        if !self.is_active() {
+            return None;
+        }
+
+        let Some(last) = self.last_frame_time else {
+            self.last_frame_time = Some(timestamp);
+            return Some(0.0);
+        };
+
+        if let Some(fps_cap) = self.fps_cap {
+            // 留一点余量，避免 rAF 时间抖动导致 60 帧被限成 30 帧
+            let min_interval = 1000.0 / fps_cap;
+            if timestamp - last < min_interval - 1.0 {
+                return None;
+            }
+        }
+
+        self.last_frame_time = Some(timestamp);
+        Some((timestamp - last) / 1000.0)
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::renderer::Renderer;
+
+const FPS_SAMPLE_SIZE: usize = 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderStats {
+    pub frame: u64,
+    pub objects_drawn: usize,
+    pub objects_culled: usize,
+    pub draw_calls: usize,
+    pub render_ms: f64,
+    pub hit_canvas_ms: f64,
+    pub fps: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct RenderStatsCollector {
+    current: RenderStats,
+    frame_times: VecDeque<f64>,
+}
+
+impl RenderStatsCollector {
+    pub fn begin_frame(&mut self, delta: f64) {
+        let frame = self.current.frame + 1;
+        self.current = RenderStats {
+            frame,
+            fps: self.current.fps,
+            ..Default::default()
+        };
+
+        if delta > 0.0 {
+            self.frame_times.push_back(delta);
+            if self.frame_times.len() > FPS_SAMPLE_SIZE {
+                self.frame_times.pop_front();
+            }
+            let total: f64 = self.frame_times.iter().sum();
+            self.current.fps = self.frame_times.len() as f64 / total;
+        }
+    }
+
+    pub fn record_objects(&mut self, drawn: usize, culled: usize) {
+        self.current.objects_drawn += drawn;
+        self.current.objects_culled += culled;
+    }
+
+    pub fn record_draw_calls(&mut self, count: usize) {
+        self.current.draw_calls += count;
+    }
+
+    pub fn record_render_ms(&mut self, ms: f64) {
+        self.current.render_ms += ms;
+    }
+
+    pub fn record_hit_canvas_ms(&mut self, ms: f64) {
+        self.current.hit_canvas_ms += ms;
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        self.current.clone()
+    }
+}
+
+impl RenderStats {
+    pub fn draw_overlay(&self, renderer: &dyn Renderer) {
+        let lines = [
+            format!("FPS: {:.1}", self.fps),
+            format!("frame: {}", self.frame),
+            format!("drawn: {}  culled: {}", self.objects_drawn, self.objects_culled),
+            format!("draw calls: {}", self.draw_calls),
+            format!("render: {:.2}ms", self.render_ms),
+            format!("hit canvas: {:.2}ms", self.hit_canvas_ms),
+        ];
+
+        let line_height = 14.0;
+        let padding = 6.0;
+        renderer.set_global_alpha(0.75);
+        renderer.draw_rectangle(
+            padding,
+            padding,
+            180.0,
+            lines.len() as f64 * line_height + padding * 2.0,
+            "black",
+        );
+        renderer.set_global_alpha(1.0);
+        renderer.set_font("12px monospace");
+        renderer.set_fill_style("#7CFC00");
+        for (i, line) in lines.iter().enumerate() {
+            renderer.fill_text(
+                line,
+                padding * 2.0,
+                padding * 2.0 + line_height * (i as f64 + 0.75),
+            );
+        }
+    }
+}
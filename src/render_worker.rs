@@ -0,0 +1,147 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::js_sys::{Array, Object, Reflect};
+use web_sys::{
+    HtmlCanvasElement, OffscreenCanvas, OffscreenCanvasRenderingContext2d, Worker, WorkerOptions,
+    WorkerType,
+};
+
+use crate::error::{EditingError, EditingResult};
+use crate::log::{log_error, log_warn};
+use crate::renderer::{OffscreenCanvas2DRenderer, RecordingRenderer, RenderCommand, Renderer};
+use crate::theme::Theme;
+
+// 主线程与 worker 之间的消息：
+// { type: "init", canvas: OffscreenCanvas }  把显示画布交给 worker
+// { type: "frame", commands: string }        JSON 编码的 RenderCommand 列表，按顺序回放
+const MESSAGE_INIT: &str = "init";
+const MESSAGE_FRAME: &str = "frame";
+
+fn message(kind: &str, key: &str, value: &JsValue) -> Result<Object, JsValue> {
+    let message = Object::new();
+    Reflect::set(&message, &"type".into(), &kind.into())?;
+    Reflect::set(&message, &key.into(), value)?;
+    Ok(message)
+}
+
+// 主线程一侧：场景仍在主线程遍历，但绘制只记录为指令，由 worker 在 OffscreenCanvas 上执行
+#[derive(Debug)]
+pub struct RenderWorker {
+    worker: Worker,
+    commands: Rc<RefCell<Vec<RenderCommand>>>,
+}
+
+impl RenderWorker {
+    // 画布交出控制权后主线程不能再获取它的绘图上下文，也不能直接修改尺寸
+    pub fn spawn(canvas: &HtmlCanvasElement, script_url: &str) -> EditingResult<Self> {
+        let offscreen = canvas.transfer_control_to_offscreen()?;
+
+        let options = WorkerOptions::new();
+        options.set_type(WorkerType::Module);
+        let worker = Worker::new_with_options(script_url, &options)?;
+
+        let init = message(MESSAGE_INIT, "canvas", &offscreen)?;
+        worker.post_message_with_transfer(&init, &Array::of1(&offscreen))?;
+
+        Ok(Self {
+            worker,
+            commands: Rc::new(RefCell::new(Vec::new())),
+        })
+    }
+
+    pub fn create_renderer(&self, theme: Theme) -> Rc<RefCell<Option<Box<dyn Renderer>>>> {
+        Rc::new(RefCell::new(Some(
+            Box::new(RecordingRenderer::new(self.commands.clone(), theme)) as Box<dyn Renderer>,
+        )))
+    }
+
+    // 调整尺寸会重置画布状态，需要和绘制指令保持顺序
+    pub fn resize(&self, width: u32, height: u32) {
+        self.commands
+            .borrow_mut()
+            .push(RenderCommand::Resize { width, height });
+    }
+
+    // 把本帧记录的指令发给 worker
+    pub fn flush(&self) -> EditingResult<()> {
+        let commands = std::mem::take(&mut *self.commands.borrow_mut());
+        if commands.is_empty() {
+            return Ok(());
+        }
+        let encoded = serde_json::to_string(&commands)?;
+        let frame = message(MESSAGE_FRAME, "commands", &encoded.into())?;
+        self.worker.post_message(&frame)?;
+        Ok(())
+    }
+
+    pub fn terminate(&self) {
+        self.commands.borrow_mut().clear();
+        self.worker.terminate();
+    }
+}
+
+// worker 一侧，在 worker 脚本中加载同一个 wasm 模块后使用：
+//   const host = new RenderWorkerHost();
+//   self.onmessage = (event) => host.handleMessage(event.data);
+#[wasm_bindgen(js_name = RenderWorkerHost)]
+#[derive(Default)]
+pub struct WorkerRenderer {
+    canvas: Option<OffscreenCanvas>,
+    renderer: Option<OffscreenCanvas2DRenderer>,
+}
+
+#[wasm_bindgen(js_class = RenderWorkerHost)]
+impl WorkerRenderer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[wasm_bindgen(js_name = handleMessage)]
+    pub fn handle_message(&mut self, message: JsValue) -> Result<(), JsValue> {
+        let kind = Reflect::get(&message, &"type".into())?.as_string();
+        match kind.as_deref() {
+            Some(MESSAGE_INIT) => {
+                let canvas: OffscreenCanvas = Reflect::get(&message, &"canvas".into())?.dyn_into()?;
+                let context: OffscreenCanvasRenderingContext2d = canvas
+                    .get_context("2d")?
+                    .and_then(|context| context.dyn_into().ok())
+                    .ok_or_else(|| EditingError::ContextCreationFailed("2d".to_string()))?;
+                // 收到的指令中颜色已经解析过
+                self.renderer = Some(OffscreenCanvas2DRenderer::new(context, Theme::default()));
+                self.canvas = Some(canvas);
+            }
+            Some(MESSAGE_FRAME) => {
+                let encoded = Reflect::get(&message, &"commands".into())?
+                    .as_string()
+                    .unwrap_or_default();
+                let commands: Vec<RenderCommand> =
+                    serde_json::from_str(&encoded).map_err(EditingError::from)?;
+                self.replay(commands);
+            }
+            _ => log_warn!("Unknown render worker message: {:?}", kind),
+        }
+        Ok(())
+    }
+}
+
+impl WorkerRenderer {
+    fn replay(&self, commands: Vec<RenderCommand>) {
+        let (Some(canvas), Some(renderer)) = (&self.canvas, &self.renderer) else {
+            log_error!("Render worker received a frame before init");
+            return;
+        };
+        for command in commands {
+            match command {
+                RenderCommand::Resize { width, height } => {
+                    canvas.set_width(width);
+                    canvas.set_height(height);
+                }
+                command => command.replay(renderer),
+            }
+        }
+    }
+}
@@ -1,12 +1,73 @@
+mod cached_path;
 mod canvas_2d_renderer;
+mod filter;
 mod offscreen_canvas_2d_renderer;
+mod recording_renderer;
+mod script_proxy;
 
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use wasm_bindgen::JsValue;
+use web_sys::TextMetrics;
 
+use crate::bounding_box::BoundingBox;
+use crate::error::EditingResult;
 use crate::image::Image;
+use crate::log::log_warn;
 
+pub use cached_path::{CachedPath, PathCommand};
 pub use canvas_2d_renderer::Canvas2DRenderer;
+pub use filter::Filter;
 pub use offscreen_canvas_2d_renderer::OffscreenCanvas2DRenderer;
+pub use recording_renderer::{RecordingRenderer, RenderCommand};
+pub use script_proxy::RendererProxy;
+
+// 位图已被关闭或图片无法解码时绘制会失败，只跳过这一次绘制
+fn report_draw_image(result: Result<(), JsValue>) {
+    if let Err(e) = result {
+        log_warn!("Failed to draw image: {:?}", e);
+    }
+}
+
+// CanvasRenderingContext2D.measureText 的结果；bounding box 的距离都相对 fill_text 的定位点，
+// left/ascent 向左、向上为正
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextMeasurement {
+    pub width: f64,
+    pub actual_bounding_box_left: f64,
+    pub actual_bounding_box_right: f64,
+    pub actual_bounding_box_ascent: f64,
+    pub actual_bounding_box_descent: f64,
+    pub font_bounding_box_ascent: f64,
+    pub font_bounding_box_descent: f64,
+}
+
+impl TextMeasurement {
+    // 字形实际覆盖的区域，(x, y) 为 fill_text 的定位点
+    pub fn ink_box(&self, x: f64, y: f64) -> BoundingBox {
+        BoundingBox::new(
+            x - self.actual_bounding_box_left,
+            y - self.actual_bounding_box_ascent,
+            self.actual_bounding_box_left + self.actual_bounding_box_right,
+            self.actual_bounding_box_ascent + self.actual_bounding_box_descent,
+        )
+    }
+}
+
+impl From<&TextMetrics> for TextMeasurement {
+    fn from(metrics: &TextMetrics) -> Self {
+        Self {
+            width: metrics.width(),
+            actual_bounding_box_left: metrics.actual_bounding_box_left(),
+            actual_bounding_box_right: metrics.actual_bounding_box_right(),
+            actual_bounding_box_ascent: metrics.actual_bounding_box_ascent(),
+            actual_bounding_box_descent: metrics.actual_bounding_box_descent(),
+            font_bounding_box_ascent: metrics.font_bounding_box_ascent(),
+            font_bounding_box_descent: metrics.font_bounding_box_descent(),
+        }
+    }
+}
 
 pub trait Renderer: Debug {
     // 清除方法
@@ -31,6 +92,11 @@ pub trait Renderer: Debug {
     fn close_path(&self);
     fn stroke(&self);
     fn fill(&self);
+    // 以当前路径裁剪后续绘制，save/restore 恢复
+    fn clip(&self);
+    // 绘制预先构建的路径，不影响当前路径
+    fn fill_path(&self, path: &CachedPath);
+    fn stroke_path(&self, path: &CachedPath);
 
     fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64);
 
@@ -38,6 +104,8 @@ pub trait Renderer: Debug {
     fn fill_text(&self, text: &str, x: f64, y: f64);
     fn stroke_text(&self, text: &str, x: f64, y: f64);
     fn measure_text(&self, text: &str) -> f64;
+    // 完整的 TextMetrics，按当前 font、text_align、text_baseline 计算
+    fn measure_text_full(&self, text: &str) -> TextMeasurement;
 
     // 图像绘制
     fn draw_image(&self, image: &Image, x: f64, y: f64);
@@ -79,6 +147,8 @@ pub trait Renderer: Debug {
     fn set_text_align(&self, align: TextAlign);
     fn set_text_baseline(&self, baseline: TextBaseline);
     fn set_global_alpha(&self, alpha: f64);
+    // ctx.filter，取值为 CSS filter 字符串，可由 Filter 生成；"none" 清除
+    fn set_filter(&self, filter: &str);
     fn set_global_composite_operation(&self, operation: CompositeOperation);
 
     // 渐变和图案
@@ -95,7 +165,7 @@ pub trait Renderer: Debug {
     fn create_pattern(&self, image: &Image, repetition: PatternRepetition) -> Box<dyn Pattern>;
 
     // 像素操作
-    fn get_image_data(&self, sx: f64, sy: f64, sw: f64, sh: f64) -> ImageData;
+    fn get_image_data(&self, sx: f64, sy: f64, sw: f64, sh: f64) -> EditingResult<ImageData>;
     fn put_image_data(&self, image_data: &ImageData, dx: f64, dy: f64);
 
     // 锁定颜色
@@ -118,6 +188,7 @@ pub trait Pattern {
 #[derive(Debug)]
 pub struct ImageData(pub web_sys::ImageData);
 
+#[derive(Debug, Serialize, Deserialize)]
 pub enum LineCap {
     Butt,
     Round,
@@ -141,6 +212,7 @@ impl From<LineCap> for String {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
 pub enum LineJoin {
     Miter,
     Round,
@@ -164,6 +236,7 @@ impl From<LineJoin> for String {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum TextAlign {
     Start,
     End,
@@ -191,6 +264,7 @@ impl From<TextAlign> for String {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum TextBaseline {
     Top,
     Hanging,
@@ -220,6 +294,7 @@ impl From<TextBaseline> for String {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
 pub enum CompositeOperation {
     SourceOver,
     SourceIn,
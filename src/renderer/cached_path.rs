@@ -0,0 +1,151 @@
+use std::cell::OnceCell;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use web_sys::Path2d;
+
+use super::Renderer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PathCommand {
+    MoveTo { x: f64, y: f64 },
+    LineTo { x: f64, y: f64 },
+    BezierCurveTo { cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64 },
+    QuadraticCurveTo { cpx: f64, cpy: f64, x: f64, y: f64 },
+    Arc { x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64 },
+    ArcTo { x1: f64, y1: f64, x2: f64, y2: f64, radius: f64 },
+    ClosePath,
+}
+
+// 预先构建、可反复绘制的路径，用于几何不常变化的对象；
+// 第一次绘制时才创建 Path2D，之后每帧只需一次 fill/stroke 调用
+#[derive(Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CachedPath {
+    commands: Vec<PathCommand>,
+    #[serde(skip)]
+    path: OnceCell<Option<Path2d>>,
+}
+
+impl fmt::Debug for CachedPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CachedPath {{ commands: {} }}", self.commands.len())
+    }
+}
+
+// Path2D 不跨对象共享，克隆后重新构建
+impl Clone for CachedPath {
+    fn clone(&self) -> Self {
+        Self::from(self.commands.clone())
+    }
+}
+
+impl From<Vec<PathCommand>> for CachedPath {
+    fn from(commands: Vec<PathCommand>) -> Self {
+        Self {
+            commands,
+            path: OnceCell::new(),
+        }
+    }
+}
+
+impl CachedPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, command: PathCommand) {
+        self.commands.push(command);
+        self.path = OnceCell::new();
+    }
+
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        self.push(PathCommand::MoveTo { x, y });
+    }
+
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        self.push(PathCommand::LineTo { x, y });
+    }
+
+    pub fn bezier_curve_to(&mut self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.push(PathCommand::BezierCurveTo { cp1x, cp1y, cp2x, cp2y, x, y });
+    }
+
+    pub fn quadratic_curve_to(&mut self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.push(PathCommand::QuadraticCurveTo { cpx, cpy, x, y });
+    }
+
+    pub fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        self.push(PathCommand::Arc { x, y, radius, start_angle, end_angle });
+    }
+
+    pub fn arc_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, radius: f64) {
+        self.push(PathCommand::ArcTo { x1, y1, x2, y2, radius });
+    }
+
+    pub fn close_path(&mut self) {
+        self.push(PathCommand::ClosePath);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.commands
+    }
+
+    // 创建失败（如非浏览器环境）时为 None，调用方退回到逐条指令绘制
+    pub fn path2d(&self) -> Option<&Path2d> {
+        self.path
+            .get_or_init(|| {
+                let path = Path2d::new().ok()?;
+                for command in &self.commands {
+                    match *command {
+                        PathCommand::MoveTo { x, y } => path.move_to(x, y),
+                        PathCommand::LineTo { x, y } => path.line_to(x, y),
+                        PathCommand::BezierCurveTo { cp1x, cp1y, cp2x, cp2y, x, y } => {
+                            path.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, x, y)
+                        }
+                        PathCommand::QuadraticCurveTo { cpx, cpy, x, y } => {
+                            path.quadratic_curve_to(cpx, cpy, x, y)
+                        }
+                        PathCommand::Arc { x, y, radius, start_angle, end_angle } => {
+                            path.arc(x, y, radius, start_angle, end_angle).ok()?
+                        }
+                        PathCommand::ArcTo { x1, y1, x2, y2, radius } => {
+                            path.arc_to(x1, y1, x2, y2, radius).ok()?
+                        }
+                        PathCommand::ClosePath => path.close_path(),
+                    }
+                }
+                Some(path)
+            })
+            .as_ref()
+    }
+
+    // 以当前路径的形式重新发出全部指令，之后可调用 fill/stroke
+    pub fn trace(&self, renderer: &dyn Renderer) {
+        renderer.begin_path();
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo { x, y } => renderer.move_to(x, y),
+                PathCommand::LineTo { x, y } => renderer.line_to(x, y),
+                PathCommand::BezierCurveTo { cp1x, cp1y, cp2x, cp2y, x, y } => {
+                    renderer.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, x, y)
+                }
+                PathCommand::QuadraticCurveTo { cpx, cpy, x, y } => {
+                    renderer.quadratic_curve_to(cpx, cpy, x, y)
+                }
+                PathCommand::Arc { x, y, radius, start_angle, end_angle } => {
+                    renderer.arc(x, y, radius, start_angle, end_angle)
+                }
+                PathCommand::ArcTo { x1, y1, x2, y2, radius } => {
+                    renderer.arc_to(x1, y1, x2, y2, radius)
+                }
+                PathCommand::ClosePath => renderer.close_path(),
+            }
+        }
+    }
+}
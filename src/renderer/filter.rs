@@ -0,0 +1,168 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// 单个 CSS 滤镜函数，长度单位为像素，角度为度
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterFunction {
+    Blur { radius: f64 },
+    Brightness { amount: f64 },
+    Contrast { amount: f64 },
+    Grayscale { amount: f64 },
+    HueRotate { degrees: f64 },
+    Invert { amount: f64 },
+    Opacity { amount: f64 },
+    Saturate { amount: f64 },
+    Sepia { amount: f64 },
+    DropShadow {
+        offset_x: f64,
+        offset_y: f64,
+        blur: f64,
+        color: String,
+    },
+}
+
+impl fmt::Display for FilterFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterFunction::Blur { radius } => write!(f, "blur({}px)", radius),
+            FilterFunction::Brightness { amount } => write!(f, "brightness({})", amount),
+            FilterFunction::Contrast { amount } => write!(f, "contrast({})", amount),
+            FilterFunction::Grayscale { amount } => write!(f, "grayscale({})", amount),
+            FilterFunction::HueRotate { degrees } => write!(f, "hue-rotate({}deg)", degrees),
+            FilterFunction::Invert { amount } => write!(f, "invert({})", amount),
+            FilterFunction::Opacity { amount } => write!(f, "opacity({})", amount),
+            FilterFunction::Saturate { amount } => write!(f, "saturate({})", amount),
+            FilterFunction::Sepia { amount } => write!(f, "sepia({})", amount),
+            FilterFunction::DropShadow {
+                offset_x,
+                offset_y,
+                blur,
+                color,
+            } => write!(f, "drop-shadow({}px {}px {}px {})", offset_x, offset_y, blur, color),
+        }
+    }
+}
+
+// 按顺序叠加的滤镜链，to_string() 得到 ctx.filter 的取值，空链为 "none"
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Filter {
+    functions: Vec<FilterFunction>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(mut self, function: FilterFunction) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    pub fn blur(self, radius: f64) -> Self {
+        self.then(FilterFunction::Blur { radius })
+    }
+
+    pub fn brightness(self, amount: f64) -> Self {
+        self.then(FilterFunction::Brightness { amount })
+    }
+
+    pub fn contrast(self, amount: f64) -> Self {
+        self.then(FilterFunction::Contrast { amount })
+    }
+
+    pub fn grayscale(self, amount: f64) -> Self {
+        self.then(FilterFunction::Grayscale { amount })
+    }
+
+    pub fn hue_rotate(self, degrees: f64) -> Self {
+        self.then(FilterFunction::HueRotate { degrees })
+    }
+
+    pub fn invert(self, amount: f64) -> Self {
+        self.then(FilterFunction::Invert { amount })
+    }
+
+    pub fn opacity(self, amount: f64) -> Self {
+        self.then(FilterFunction::Opacity { amount })
+    }
+
+    pub fn saturate(self, amount: f64) -> Self {
+        self.then(FilterFunction::Saturate { amount })
+    }
+
+    pub fn sepia(self, amount: f64) -> Self {
+        self.then(FilterFunction::Sepia { amount })
+    }
+
+    pub fn drop_shadow(self, offset_x: f64, offset_y: f64, blur: f64, color: &str) -> Self {
+        self.then(FilterFunction::DropShadow {
+            offset_x,
+            offset_y,
+            blur,
+            color: color.to_string(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    pub fn functions(&self) -> &[FilterFunction] {
+        &self.functions
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.functions.is_empty() {
+            return write!(f, "none");
+        }
+        for (index, function) in self.functions.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", function)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chained_filter_formats_as_ctx_filter() {
+        let filter = Filter::new()
+            .blur(2.0)
+            .brightness(1.2)
+            .drop_shadow(4.0, -2.0, 6.0, "rgba(0,0,0,0.5)");
+        assert_eq!(filter.functions().len(), 3);
+        assert_eq!(
+            filter.to_string(),
+            "blur(2px) brightness(1.2) drop-shadow(4px -2px 6px rgba(0,0,0,0.5))"
+        );
+        assert_eq!(
+            Filter::new()
+                .contrast(1.5)
+                .grayscale(1.0)
+                .hue_rotate(90.0)
+                .invert(0.2)
+                .opacity(0.8)
+                .saturate(2.0)
+                .sepia(0.5)
+                .to_string(),
+            "contrast(1.5) grayscale(1) hue-rotate(90deg) invert(0.2) opacity(0.8) saturate(2) sepia(0.5)"
+        );
+    }
+
+    #[test]
+    fn empty_filter_is_none() {
+        assert!(Filter::new().is_empty());
+        assert_eq!(Filter::new().to_string(), "none");
+    }
+}
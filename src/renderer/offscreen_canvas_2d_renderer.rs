@@ -2,15 +2,21 @@ use std::{cell::RefCell, f64::consts::PI, rc::Rc};
 use wasm_bindgen::JsValue;
 use web_sys::OffscreenCanvasRenderingContext2d;
 
+use crate::error::EditingResult;
+use crate::image::ImageDataSource;
+use crate::theme::Theme;
+
+use super::canvas_2d_renderer::ThemedGradient;
 use super::{
-    CompositeOperation, Gradient, Image, ImageData, LineCap, LineJoin, Pattern, PatternRepetition,
-    Renderer, TextAlign, TextBaseline,
+    CachedPath, CompositeOperation, Gradient, Image, ImageData, LineCap, LineJoin, Pattern, PatternRepetition,
+    Renderer, TextAlign, TextBaseline, TextMeasurement, report_draw_image,
 };
 
 pub struct OffscreenCanvas2DRenderer {
     context: OffscreenCanvasRenderingContext2d,
     locked_fill_color: Option<String>,
     locked_stroke_color: Option<String>,
+    theme: Theme,
 }
 
 impl std::fmt::Debug for OffscreenCanvas2DRenderer {
@@ -20,19 +26,21 @@ impl std::fmt::Debug for OffscreenCanvas2DRenderer {
 }
 
 impl OffscreenCanvas2DRenderer {
-    pub fn new(context: OffscreenCanvasRenderingContext2d) -> Self {
+    pub fn new(context: OffscreenCanvasRenderingContext2d, theme: Theme) -> Self {
         OffscreenCanvas2DRenderer {
             context,
             locked_fill_color: None,
             locked_stroke_color: None,
+            theme,
         }
     }
 
     pub fn create_renderer(
         context: OffscreenCanvasRenderingContext2d,
+        theme: Theme,
     ) -> Rc<RefCell<Option<Box<dyn Renderer>>>> {
         Rc::new(RefCell::new(Some(
-            Box::new(OffscreenCanvas2DRenderer::new(context)) as Box<dyn Renderer>,
+            Box::new(OffscreenCanvas2DRenderer::new(context, theme)) as Box<dyn Renderer>,
         )))
     }
 
@@ -41,7 +49,7 @@ impl OffscreenCanvas2DRenderer {
             self.context
                 .set_fill_style(&JsValue::from_str(locked_color));
         } else {
-            self.context.set_fill_style(&JsValue::from_str(color));
+            self.context.set_fill_style(&JsValue::from_str(&self.theme.resolve(color)));
         }
     }
 
@@ -50,7 +58,7 @@ impl OffscreenCanvas2DRenderer {
             self.context
                 .set_stroke_style(&JsValue::from_str(locked_color));
         } else {
-            self.context.set_stroke_style(&JsValue::from_str(color));
+            self.context.set_stroke_style(&JsValue::from_str(&self.theme.resolve(color)));
         }
     }
 }
@@ -153,6 +161,30 @@ impl Renderer for OffscreenCanvas2DRenderer {
         self.context.fill();
     }
 
+    fn clip(&self) {
+        self.context.clip();
+    }
+
+    fn fill_path(&self, path: &CachedPath) {
+        match path.path2d() {
+            Some(path2d) => self.context.fill_with_path_2d(path2d),
+            None => {
+                path.trace(self);
+                self.context.fill();
+            }
+        }
+    }
+
+    fn stroke_path(&self, path: &CachedPath) {
+        match path.path2d() {
+            Some(path2d) => self.context.stroke_with_path(path2d),
+            None => {
+                path.trace(self);
+                self.context.stroke();
+            }
+        }
+    }
+
     fn fill_text(&self, text: &str, x: f64, y: f64) {
         self.context.fill_text(text, x, y).unwrap();
     }
@@ -169,18 +201,41 @@ impl Renderer for OffscreenCanvas2DRenderer {
         self.context.measure_text(text).unwrap().width()
     }
 
-    fn draw_image(&self, image: &Image, x: f64, y: f64) {
-        let img = image.as_html_image_element();
+    fn measure_text_full(&self, text: &str) -> TextMeasurement {
         self.context
-            .draw_image_with_html_image_element(&img, x, y)
-            .unwrap();
+            .measure_text(text)
+            .map(|metrics| TextMeasurement::from(&metrics))
+            .unwrap_or_default()
+    }
+
+    fn draw_image(&self, image: &Image, x: f64, y: f64) {
+        let result = match image.source() {
+            ImageDataSource::HtmlImage(img) => self.context.draw_image_with_html_image_element(img, x, y),
+            ImageDataSource::HtmlCanvas(canvas) => {
+                self.context.draw_image_with_html_canvas_element(canvas, x, y)
+            }
+            ImageDataSource::ImageBitmap(bitmap) => self.context.draw_image_with_image_bitmap(bitmap, x, y),
+            ImageDataSource::HtmlVideo(video) => self.context.draw_image_with_html_video_element(video, x, y),
+        };
+        report_draw_image(result);
     }
 
     fn draw_image_with_size(&self, image: &Image, x: f64, y: f64, width: f64, height: f64) {
-        let img = image.as_html_image_element();
-        self.context
-            .draw_image_with_html_image_element_and_dw_and_dh(&img, x, y, width, height)
-            .unwrap();
+        let result = match image.source() {
+            ImageDataSource::HtmlImage(img) => self
+                .context
+                .draw_image_with_html_image_element_and_dw_and_dh(img, x, y, width, height),
+            ImageDataSource::HtmlCanvas(canvas) => self
+                .context
+                .draw_image_with_html_canvas_element_and_dw_and_dh(canvas, x, y, width, height),
+            ImageDataSource::ImageBitmap(bitmap) => self
+                .context
+                .draw_image_with_image_bitmap_and_dw_and_dh(bitmap, x, y, width, height),
+            ImageDataSource::HtmlVideo(video) => self
+                .context
+                .draw_image_with_html_video_element_and_dw_and_dh(video, x, y, width, height),
+        };
+        report_draw_image(result);
     }
 
     fn draw_image_clip(
@@ -195,12 +250,29 @@ impl Renderer for OffscreenCanvas2DRenderer {
         d_width: f64,
         d_height: f64,
     ) {
-        let img = image.as_html_image_element();
-        self.context
-            .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
-                &img, sx, sy, s_width, s_height, dx, dy, d_width, d_height,
-            )
-            .unwrap();
+        let result = match image.source() {
+            ImageDataSource::HtmlImage(img) => self
+                .context
+                .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    img, sx, sy, s_width, s_height, dx, dy, d_width, d_height,
+                ),
+            ImageDataSource::HtmlCanvas(canvas) => self
+                .context
+                .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    canvas, sx, sy, s_width, s_height, dx, dy, d_width, d_height,
+                ),
+            ImageDataSource::ImageBitmap(bitmap) => self
+                .context
+                .draw_image_with_image_bitmap_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    bitmap, sx, sy, s_width, s_height, dx, dy, d_width, d_height,
+                ),
+            ImageDataSource::HtmlVideo(video) => self
+                .context
+                .draw_image_with_html_video_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    video, sx, sy, s_width, s_height, dx, dy, d_width, d_height,
+                ),
+        };
+        report_draw_image(result);
     }
 
     fn save(&self) {
@@ -258,7 +330,7 @@ impl Renderer for OffscreenCanvas2DRenderer {
     }
 
     fn set_shadow_color(&self, color: &str) {
-        self.context.set_shadow_color(color);
+        self.context.set_shadow_color(&self.theme.resolve(color));
     }
 
     fn set_shadow_blur(&self, blur: f64) {
@@ -291,6 +363,14 @@ impl Renderer for OffscreenCanvas2DRenderer {
         self.context.set_global_alpha(alpha);
     }
 
+    fn set_filter(&self, filter: &str) {
+        // 拾取画布上的颜色必须原样保留，锁色时忽略滤镜
+        if self.locked_fill_color.is_some() {
+            return;
+        }
+        self.context.set_filter(filter);
+    }
+
     fn set_global_composite_operation(&self, operation: CompositeOperation) {
         let operation_str: String = operation.into();
         self.context
@@ -300,7 +380,10 @@ impl Renderer for OffscreenCanvas2DRenderer {
 
     fn create_linear_gradient(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Box<dyn Gradient> {
         let gradient = self.context.create_linear_gradient(x0, y0, x1, y1);
-        Box::new(gradient)
+        Box::new(ThemedGradient {
+            gradient,
+            theme: self.theme.clone(),
+        })
     }
 
     fn create_radial_gradient(
@@ -316,23 +399,36 @@ impl Renderer for OffscreenCanvas2DRenderer {
             .context
             .create_radial_gradient(x0, y0, r0, x1, y1, r1)
             .unwrap();
-        Box::new(gradient)
+        Box::new(ThemedGradient {
+            gradient,
+            theme: self.theme.clone(),
+        })
     }
 
     fn create_pattern(&self, image: &Image, repetition: PatternRepetition) -> Box<dyn Pattern> {
-        let img = image.as_html_image_element();
         let repetition_str = repetition.into();
-        let pattern = self
-            .context
-            .create_pattern_with_html_image_element(&img, repetition_str)
-            .unwrap()
-            .unwrap();
+        let pattern = match image.source() {
+            ImageDataSource::HtmlImage(img) => {
+                self.context.create_pattern_with_html_image_element(img, repetition_str)
+            }
+            ImageDataSource::HtmlCanvas(canvas) => {
+                self.context.create_pattern_with_html_canvas_element(canvas, repetition_str)
+            }
+            ImageDataSource::ImageBitmap(bitmap) => {
+                self.context.create_pattern_with_image_bitmap(bitmap, repetition_str)
+            }
+            ImageDataSource::HtmlVideo(video) => {
+                self.context.create_pattern_with_html_video_element(video, repetition_str)
+            }
+        }
+        .unwrap()
+        .unwrap();
         Box::new(pattern)
     }
 
-    fn get_image_data(&self, sx: f64, sy: f64, sw: f64, sh: f64) -> ImageData {
-        let canvas_image_data = self.context.get_image_data(sx, sy, sw, sh).unwrap();
-        ImageData(canvas_image_data)
+    fn get_image_data(&self, sx: f64, sy: f64, sw: f64, sh: f64) -> EditingResult<ImageData> {
+        let canvas_image_data = self.context.get_image_data(sx, sy, sw, sh)?;
+        Ok(ImageData(canvas_image_data))
     }
 
     fn put_image_data(&self, image_data: &ImageData, dx: f64, dy: f64) {
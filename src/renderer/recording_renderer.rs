@@ -0,0 +1,547 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use super::{
+    CachedPath, CompositeOperation, Gradient, Image, ImageData, LineCap, LineJoin, PathCommand,
+    Pattern, PatternRepetition, Renderer, TextAlign, TextBaseline, TextMeasurement,
+};
+use crate::error::{EditingError, EditingResult};
+use crate::log::log_warn;
+use crate::theme::Theme;
+
+// 可序列化的绘制指令，用于把一帧的绘制转发到 worker 中回放
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RenderCommand {
+    // 调整目标画布的像素尺寸，会重置画布状态
+    Resize { width: u32, height: u32 },
+    Clear { x: f64, y: f64, width: f64, height: f64 },
+    ClearAll,
+    DrawRectangle { x: f64, y: f64, width: f64, height: f64, color: String },
+    DrawCircle { x: f64, y: f64, radius: f64, color: String },
+    DrawEllipse { x: f64, y: f64, radius_x: f64, radius_y: f64, color: String },
+    DrawLine { x1: f64, y1: f64, x2: f64, y2: f64, color: String, width: f64 },
+    DrawPolygon { points: Vec<f64>, color: String },
+    BeginPath,
+    MoveTo { x: f64, y: f64 },
+    LineTo { x: f64, y: f64 },
+    BezierCurveTo { cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64 },
+    QuadraticCurveTo { cpx: f64, cpy: f64, x: f64, y: f64 },
+    Arc { x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64 },
+    ArcTo { x1: f64, y1: f64, x2: f64, y2: f64, radius: f64 },
+    ClosePath,
+    Stroke,
+    Fill,
+    Clip,
+    FillPath { commands: Vec<PathCommand> },
+    StrokePath { commands: Vec<PathCommand> },
+    StrokeRect { x: f64, y: f64, width: f64, height: f64 },
+    FillText { text: String, x: f64, y: f64 },
+    StrokeText { text: String, x: f64, y: f64 },
+    Save,
+    Restore,
+    SetTransform { matrix: [f64; 6] },
+    Transform { matrix: [f64; 6] },
+    Translate { x: f64, y: f64 },
+    Rotate { angle: f64 },
+    Scale { x: f64, y: f64 },
+    SetFillStyle { style: String },
+    SetStrokeStyle { style: String },
+    SetLineWidth { width: f64 },
+    SetLineCap { cap: LineCap },
+    SetLineJoin { join: LineJoin },
+    SetMiterLimit { limit: f64 },
+    SetShadowColor { color: String },
+    SetShadowBlur { blur: f64 },
+    SetShadowOffsetX { offset: f64 },
+    SetShadowOffsetY { offset: f64 },
+    SetFont { font: String },
+    SetTextAlign { align: TextAlign },
+    SetTextBaseline { baseline: TextBaseline },
+    SetGlobalAlpha { alpha: f64 },
+    SetFilter { filter: String },
+    SetGlobalCompositeOperation { operation: CompositeOperation },
+}
+
+impl RenderCommand {
+    pub fn replay(self, renderer: &dyn Renderer) {
+        match self {
+            // 尺寸由持有画布的一方处理
+            RenderCommand::Resize { .. } => {}
+            RenderCommand::Clear { x, y, width, height } => renderer.clear(x, y, width, height),
+            RenderCommand::ClearAll => renderer.clear_all(),
+            RenderCommand::DrawRectangle { x, y, width, height, color } => {
+                renderer.draw_rectangle(x, y, width, height, &color)
+            }
+            RenderCommand::DrawCircle { x, y, radius, color } => {
+                renderer.draw_circle(x, y, radius, &color)
+            }
+            RenderCommand::DrawEllipse { x, y, radius_x, radius_y, color } => {
+                renderer.draw_ellipse(x, y, radius_x, radius_y, &color)
+            }
+            RenderCommand::DrawLine { x1, y1, x2, y2, color, width } => {
+                renderer.draw_line(x1, y1, x2, y2, &color, width)
+            }
+            RenderCommand::DrawPolygon { points, color } => renderer.draw_polygon(&points, &color),
+            RenderCommand::BeginPath => renderer.begin_path(),
+            RenderCommand::MoveTo { x, y } => renderer.move_to(x, y),
+            RenderCommand::LineTo { x, y } => renderer.line_to(x, y),
+            RenderCommand::BezierCurveTo { cp1x, cp1y, cp2x, cp2y, x, y } => {
+                renderer.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, x, y)
+            }
+            RenderCommand::QuadraticCurveTo { cpx, cpy, x, y } => {
+                renderer.quadratic_curve_to(cpx, cpy, x, y)
+            }
+            RenderCommand::Arc { x, y, radius, start_angle, end_angle } => {
+                renderer.arc(x, y, radius, start_angle, end_angle)
+            }
+            RenderCommand::ArcTo { x1, y1, x2, y2, radius } => {
+                renderer.arc_to(x1, y1, x2, y2, radius)
+            }
+            RenderCommand::ClosePath => renderer.close_path(),
+            RenderCommand::Stroke => renderer.stroke(),
+            RenderCommand::Fill => renderer.fill(),
+            RenderCommand::Clip => renderer.clip(),
+            RenderCommand::FillPath { commands } => renderer.fill_path(&CachedPath::from(commands)),
+            RenderCommand::StrokePath { commands } => {
+                renderer.stroke_path(&CachedPath::from(commands))
+            }
+            RenderCommand::StrokeRect { x, y, width, height } => {
+                renderer.stroke_rect(x, y, width, height)
+            }
+            RenderCommand::FillText { text, x, y } => renderer.fill_text(&text, x, y),
+            RenderCommand::StrokeText { text, x, y } => renderer.stroke_text(&text, x, y),
+            RenderCommand::Save => renderer.save(),
+            RenderCommand::Restore => renderer.restore(),
+            RenderCommand::SetTransform { matrix: [a, b, c, d, e, f] } => {
+                renderer.set_transform(a, b, c, d, e, f)
+            }
+            RenderCommand::Transform { matrix: [a, b, c, d, e, f] } => {
+                renderer.transform(a, b, c, d, e, f)
+            }
+            RenderCommand::Translate { x, y } => renderer.translate(x, y),
+            RenderCommand::Rotate { angle } => renderer.rotate(angle),
+            RenderCommand::Scale { x, y } => renderer.scale(x, y),
+            RenderCommand::SetFillStyle { style } => renderer.set_fill_style(&style),
+            RenderCommand::SetStrokeStyle { style } => renderer.set_stroke_style(&style),
+            RenderCommand::SetLineWidth { width } => renderer.set_line_width(width),
+            RenderCommand::SetLineCap { cap } => renderer.set_line_cap(cap),
+            RenderCommand::SetLineJoin { join } => renderer.set_line_join(join),
+            RenderCommand::SetMiterLimit { limit } => renderer.set_miter_limit(limit),
+            RenderCommand::SetShadowColor { color } => renderer.set_shadow_color(&color),
+            RenderCommand::SetShadowBlur { blur } => renderer.set_shadow_blur(blur),
+            RenderCommand::SetShadowOffsetX { offset } => renderer.set_shadow_offset_x(offset),
+            RenderCommand::SetShadowOffsetY { offset } => renderer.set_shadow_offset_y(offset),
+            RenderCommand::SetFont { font } => renderer.set_font(&font),
+            RenderCommand::SetTextAlign { align } => renderer.set_text_align(align),
+            RenderCommand::SetTextBaseline { baseline } => renderer.set_text_baseline(baseline),
+            RenderCommand::SetGlobalAlpha { alpha } => renderer.set_global_alpha(alpha),
+            RenderCommand::SetFilter { filter } => renderer.set_filter(&filter),
+            RenderCommand::SetGlobalCompositeOperation { operation } => {
+                renderer.set_global_composite_operation(operation)
+            }
+        }
+    }
+}
+
+// 只记录绘制指令、不直接绘制的渲染器；图片、渐变、图案和像素读写无法序列化，会被忽略
+pub struct RecordingRenderer {
+    commands: Rc<RefCell<Vec<RenderCommand>>>,
+    // 仅用于同步测量文本宽度
+    measure_context: Option<OffscreenCanvasRenderingContext2d>,
+    // 每种不支持的操作只提示一次，避免每帧刷屏
+    warned: RefCell<HashSet<&'static str>>,
+    // 颜色变量在记录时解析，发给 worker 的指令中只有具体颜色
+    theme: Theme,
+}
+
+impl std::fmt::Debug for RecordingRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RecordingRenderer {{ commands: {} }}", self.commands.borrow().len())
+    }
+}
+
+impl RecordingRenderer {
+    pub fn new(commands: Rc<RefCell<Vec<RenderCommand>>>, theme: Theme) -> Self {
+        let measure_context = OffscreenCanvas::new(1, 1)
+            .ok()
+            .and_then(|canvas| canvas.get_context("2d").ok().flatten())
+            .and_then(|context| context.dyn_into::<OffscreenCanvasRenderingContext2d>().ok());
+        Self {
+            commands,
+            measure_context,
+            warned: RefCell::new(HashSet::new()),
+            theme,
+        }
+    }
+
+    fn push(&self, command: RenderCommand) {
+        self.commands.borrow_mut().push(command);
+    }
+
+    fn unsupported(&self, what: &'static str) {
+        if self.warned.borrow_mut().insert(what) {
+            log_warn!("RecordingRenderer does not support {}", what);
+        }
+    }
+}
+
+struct NoopGradient;
+
+impl Gradient for NoopGradient {
+    fn add_gradient_color_stop(&self, _offset: f64, _color: &str) {}
+}
+
+struct NoopPattern;
+
+impl Pattern for NoopPattern {
+    fn set_pattern_transform(&self, _a: f64, _b: f64, _c: f64, _d: f64, _e: f64, _f: f64) {}
+}
+
+impl Renderer for RecordingRenderer {
+    fn clear(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.push(RenderCommand::Clear { x, y, width, height });
+    }
+
+    fn clear_all(&self) {
+        self.push(RenderCommand::ClearAll);
+    }
+
+    fn draw_rectangle(&self, x: f64, y: f64, width: f64, height: f64, color: &str) {
+        self.push(RenderCommand::DrawRectangle {
+            x,
+            y,
+            width,
+            height,
+            color: self.theme.resolve(color).into_owned(),
+        });
+    }
+
+    fn draw_circle(&self, x: f64, y: f64, radius: f64, color: &str) {
+        self.push(RenderCommand::DrawCircle {
+            x,
+            y,
+            radius,
+            color: self.theme.resolve(color).into_owned(),
+        });
+    }
+
+    fn draw_ellipse(&self, x: f64, y: f64, radius_x: f64, radius_y: f64, color: &str) {
+        self.push(RenderCommand::DrawEllipse {
+            x,
+            y,
+            radius_x,
+            radius_y,
+            color: self.theme.resolve(color).into_owned(),
+        });
+    }
+
+    fn draw_line(&self, x1: f64, y1: f64, x2: f64, y2: f64, color: &str, width: f64) {
+        self.push(RenderCommand::DrawLine {
+            x1,
+            y1,
+            x2,
+            y2,
+            color: self.theme.resolve(color).into_owned(),
+            width,
+        });
+    }
+
+    fn draw_polygon(&self, points: &[f64], color: &str) {
+        self.push(RenderCommand::DrawPolygon {
+            points: points.to_vec(),
+            color: self.theme.resolve(color).into_owned(),
+        });
+    }
+
+    fn begin_path(&self) {
+        self.push(RenderCommand::BeginPath);
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        self.push(RenderCommand::MoveTo { x, y });
+    }
+
+    fn line_to(&self, x: f64, y: f64) {
+        self.push(RenderCommand::LineTo { x, y });
+    }
+
+    fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.push(RenderCommand::BezierCurveTo {
+            cp1x,
+            cp1y,
+            cp2x,
+            cp2y,
+            x,
+            y,
+        });
+    }
+
+    fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.push(RenderCommand::QuadraticCurveTo { cpx, cpy, x, y });
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        self.push(RenderCommand::Arc {
+            x,
+            y,
+            radius,
+            start_angle,
+            end_angle,
+        });
+    }
+
+    fn arc_to(&self, x1: f64, y1: f64, x2: f64, y2: f64, radius: f64) {
+        self.push(RenderCommand::ArcTo {
+            x1,
+            y1,
+            x2,
+            y2,
+            radius,
+        });
+    }
+
+    fn close_path(&self) {
+        self.push(RenderCommand::ClosePath);
+    }
+
+    fn stroke(&self) {
+        self.push(RenderCommand::Stroke);
+    }
+
+    fn fill(&self) {
+        self.push(RenderCommand::Fill);
+    }
+
+    fn clip(&self) {
+        self.push(RenderCommand::Clip);
+    }
+
+    fn fill_path(&self, path: &CachedPath) {
+        self.push(RenderCommand::FillPath {
+            commands: path.commands().to_vec(),
+        });
+    }
+
+    fn stroke_path(&self, path: &CachedPath) {
+        self.push(RenderCommand::StrokePath {
+            commands: path.commands().to_vec(),
+        });
+    }
+
+    fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.push(RenderCommand::StrokeRect { x, y, width, height });
+    }
+
+    fn fill_text(&self, text: &str, x: f64, y: f64) {
+        self.push(RenderCommand::FillText {
+            text: text.to_string(),
+            x,
+            y,
+        });
+    }
+
+    fn stroke_text(&self, text: &str, x: f64, y: f64) {
+        self.push(RenderCommand::StrokeText {
+            text: text.to_string(),
+            x,
+            y,
+        });
+    }
+
+    fn measure_text(&self, text: &str) -> f64 {
+        self.measure_context
+            .as_ref()
+            .and_then(|context| context.measure_text(text).ok())
+            .map_or(0.0, |metrics| metrics.width())
+    }
+
+    fn measure_text_full(&self, text: &str) -> TextMeasurement {
+        self.measure_context
+            .as_ref()
+            .and_then(|context| context.measure_text(text).ok())
+            .map(|metrics| TextMeasurement::from(&metrics))
+            .unwrap_or_default()
+    }
+
+    fn draw_image(&self, _image: &Image, _x: f64, _y: f64) {
+        self.unsupported("draw_image");
+    }
+
+    fn draw_image_with_size(&self, _image: &Image, _x: f64, _y: f64, _width: f64, _height: f64) {
+        self.unsupported("draw_image");
+    }
+
+    fn draw_image_clip(
+        &self,
+        _image: &Image,
+        _sx: f64,
+        _sy: f64,
+        _s_width: f64,
+        _s_height: f64,
+        _dx: f64,
+        _dy: f64,
+        _d_width: f64,
+        _d_height: f64,
+    ) {
+        self.unsupported("draw_image");
+    }
+
+    fn save(&self) {
+        self.push(RenderCommand::Save);
+    }
+
+    fn restore(&self) {
+        self.push(RenderCommand::Restore);
+    }
+
+    fn set_transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        self.push(RenderCommand::SetTransform {
+            matrix: [a, b, c, d, e, f],
+        });
+    }
+
+    fn transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        self.push(RenderCommand::Transform {
+            matrix: [a, b, c, d, e, f],
+        });
+    }
+
+    fn translate(&self, x: f64, y: f64) {
+        self.push(RenderCommand::Translate { x, y });
+    }
+
+    fn rotate(&self, angle: f64) {
+        self.push(RenderCommand::Rotate { angle });
+    }
+
+    fn scale(&self, x: f64, y: f64) {
+        self.push(RenderCommand::Scale { x, y });
+    }
+
+    fn set_fill_style(&self, style: &str) {
+        self.push(RenderCommand::SetFillStyle {
+            style: self.theme.resolve(style).into_owned(),
+        });
+    }
+
+    fn set_stroke_style(&self, style: &str) {
+        self.push(RenderCommand::SetStrokeStyle {
+            style: self.theme.resolve(style).into_owned(),
+        });
+    }
+
+    fn set_line_width(&self, width: f64) {
+        self.push(RenderCommand::SetLineWidth { width });
+    }
+
+    fn set_line_cap(&self, cap: LineCap) {
+        self.push(RenderCommand::SetLineCap { cap });
+    }
+
+    fn set_line_join(&self, join: LineJoin) {
+        self.push(RenderCommand::SetLineJoin { join });
+    }
+
+    fn set_miter_limit(&self, limit: f64) {
+        self.push(RenderCommand::SetMiterLimit { limit });
+    }
+
+    fn set_shadow_color(&self, color: &str) {
+        self.push(RenderCommand::SetShadowColor {
+            color: self.theme.resolve(color).into_owned(),
+        });
+    }
+
+    fn set_shadow_blur(&self, blur: f64) {
+        self.push(RenderCommand::SetShadowBlur { blur });
+    }
+
+    fn set_shadow_offset_x(&self, offset: f64) {
+        self.push(RenderCommand::SetShadowOffsetX { offset });
+    }
+
+    fn set_shadow_offset_y(&self, offset: f64) {
+        self.push(RenderCommand::SetShadowOffsetY { offset });
+    }
+
+    fn set_font(&self, font: &str) {
+        if let Some(context) = &self.measure_context {
+            context.set_font(font);
+        }
+        self.push(RenderCommand::SetFont {
+            font: font.to_string(),
+        });
+    }
+
+    // 对齐和基线会影响 measure_text_full 的结果，同步到测量上下文
+    fn set_text_align(&self, align: TextAlign) {
+        if let Some(context) = &self.measure_context {
+            context.set_text_align(align.into());
+        }
+        self.push(RenderCommand::SetTextAlign { align });
+    }
+
+    fn set_text_baseline(&self, baseline: TextBaseline) {
+        if let Some(context) = &self.measure_context {
+            context.set_text_baseline(baseline.into());
+        }
+        self.push(RenderCommand::SetTextBaseline { baseline });
+    }
+
+    fn set_global_alpha(&self, alpha: f64) {
+        self.push(RenderCommand::SetGlobalAlpha { alpha });
+    }
+
+    fn set_filter(&self, filter: &str) {
+        self.push(RenderCommand::SetFilter {
+            filter: filter.to_string(),
+        });
+    }
+
+    fn set_global_composite_operation(&self, operation: CompositeOperation) {
+        self.push(RenderCommand::SetGlobalCompositeOperation { operation });
+    }
+
+    fn create_linear_gradient(&self, _x0: f64, _y0: f64, _x1: f64, _y1: f64) -> Box<dyn Gradient> {
+        self.unsupported("gradients");
+        Box::new(NoopGradient)
+    }
+
+    fn create_radial_gradient(
+        &self,
+        _x0: f64,
+        _y0: f64,
+        _r0: f64,
+        _x1: f64,
+        _y1: f64,
+        _r1: f64,
+    ) -> Box<dyn Gradient> {
+        self.unsupported("gradients");
+        Box::new(NoopGradient)
+    }
+
+    fn create_pattern(&self, _image: &Image, _repetition: PatternRepetition) -> Box<dyn Pattern> {
+        self.unsupported("patterns");
+        Box::new(NoopPattern)
+    }
+
+    // 像素在 worker 中，主线程无法同步读取
+    fn get_image_data(&self, _sx: f64, _sy: f64, _sw: f64, _sh: f64) -> EditingResult<ImageData> {
+        Err(EditingError::UnavailableInWorker("get_image_data"))
+    }
+
+    fn put_image_data(&self, _image_data: &ImageData, _dx: f64, _dy: f64) {
+        self.unsupported("put_image_data");
+    }
+
+    // 拾取画布始终留在主线程，显示画布不需要锁定颜色
+    fn lock_color(&mut self, _color: &str) {}
+
+    fn unlock_color(&mut self) {}
+}
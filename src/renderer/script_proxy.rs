@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use super::Renderer;
+
+// JS 绘制回调产生的指令，回调返回后再统一回放到真实的 Renderer 上
+#[derive(Debug, Clone)]
+enum DrawCommand {
+    BeginPath,
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    BezierCurveTo(f64, f64, f64, f64, f64, f64),
+    QuadraticCurveTo(f64, f64, f64, f64),
+    Arc(f64, f64, f64, f64, f64),
+    ClosePath,
+    Fill,
+    Stroke,
+    FillRect(f64, f64, f64, f64),
+    StrokeRect(f64, f64, f64, f64),
+    FillText(String, f64, f64),
+    FillStyle(String),
+    StrokeStyle(String),
+    LineWidth(f64),
+    Font(String),
+    GlobalAlpha(f64),
+    Filter(String),
+    Save,
+    Restore,
+    Translate(f64, f64),
+    Rotate(f64),
+    Scale(f64, f64),
+}
+
+// 传给 JS 绘制回调的轻量渲染器，坐标为元素的局部坐标
+#[wasm_bindgen(js_name = RendererProxy)]
+pub struct RendererProxy {
+    commands: Rc<RefCell<Vec<DrawCommand>>>,
+}
+
+impl RendererProxy {
+    pub fn new() -> Self {
+        Self {
+            commands: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    // 回调结束后取走指令，JS 之后再调用代理不会有任何效果
+    pub fn recorder(&self) -> ProxyRecorder {
+        ProxyRecorder {
+            commands: self.commands.clone(),
+        }
+    }
+
+    fn push(&self, command: DrawCommand) {
+        self.commands.borrow_mut().push(command);
+    }
+}
+
+impl Default for RendererProxy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen(js_class = RendererProxy)]
+impl RendererProxy {
+    #[wasm_bindgen(js_name = beginPath)]
+    pub fn begin_path(&self) {
+        self.push(DrawCommand::BeginPath);
+    }
+
+    #[wasm_bindgen(js_name = moveTo)]
+    pub fn move_to(&self, x: f64, y: f64) {
+        self.push(DrawCommand::MoveTo(x, y));
+    }
+
+    #[wasm_bindgen(js_name = lineTo)]
+    pub fn line_to(&self, x: f64, y: f64) {
+        self.push(DrawCommand::LineTo(x, y));
+    }
+
+    #[wasm_bindgen(js_name = bezierCurveTo)]
+    pub fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.push(DrawCommand::BezierCurveTo(cp1x, cp1y, cp2x, cp2y, x, y));
+    }
+
+    #[wasm_bindgen(js_name = quadraticCurveTo)]
+    pub fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.push(DrawCommand::QuadraticCurveTo(cpx, cpy, x, y));
+    }
+
+    pub fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        self.push(DrawCommand::Arc(x, y, radius, start_angle, end_angle));
+    }
+
+    #[wasm_bindgen(js_name = closePath)]
+    pub fn close_path(&self) {
+        self.push(DrawCommand::ClosePath);
+    }
+
+    pub fn fill(&self) {
+        self.push(DrawCommand::Fill);
+    }
+
+    pub fn stroke(&self) {
+        self.push(DrawCommand::Stroke);
+    }
+
+    #[wasm_bindgen(js_name = fillRect)]
+    pub fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.push(DrawCommand::FillRect(x, y, width, height));
+    }
+
+    #[wasm_bindgen(js_name = strokeRect)]
+    pub fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.push(DrawCommand::StrokeRect(x, y, width, height));
+    }
+
+    #[wasm_bindgen(js_name = fillText)]
+    pub fn fill_text(&self, text: String, x: f64, y: f64) {
+        self.push(DrawCommand::FillText(text, x, y));
+    }
+
+    #[wasm_bindgen(js_name = setFillStyle)]
+    pub fn set_fill_style(&self, style: String) {
+        self.push(DrawCommand::FillStyle(style));
+    }
+
+    #[wasm_bindgen(js_name = setStrokeStyle)]
+    pub fn set_stroke_style(&self, style: String) {
+        self.push(DrawCommand::StrokeStyle(style));
+    }
+
+    #[wasm_bindgen(js_name = setLineWidth)]
+    pub fn set_line_width(&self, width: f64) {
+        self.push(DrawCommand::LineWidth(width));
+    }
+
+    #[wasm_bindgen(js_name = setFont)]
+    pub fn set_font(&self, font: String) {
+        self.push(DrawCommand::Font(font));
+    }
+
+    #[wasm_bindgen(js_name = setGlobalAlpha)]
+    pub fn set_global_alpha(&self, alpha: f64) {
+        self.push(DrawCommand::GlobalAlpha(alpha));
+    }
+
+    // CSS filter 字符串，如 "blur(4px) brightness(1.2)"
+    #[wasm_bindgen(js_name = setFilter)]
+    pub fn set_filter(&self, filter: String) {
+        self.push(DrawCommand::Filter(filter));
+    }
+
+    pub fn save(&self) {
+        self.push(DrawCommand::Save);
+    }
+
+    pub fn restore(&self) {
+        self.push(DrawCommand::Restore);
+    }
+
+    pub fn translate(&self, x: f64, y: f64) {
+        self.push(DrawCommand::Translate(x, y));
+    }
+
+    pub fn rotate(&self, angle: f64) {
+        self.push(DrawCommand::Rotate(angle));
+    }
+
+    pub fn scale(&self, x: f64, y: f64) {
+        self.push(DrawCommand::Scale(x, y));
+    }
+}
+
+pub struct ProxyRecorder {
+    commands: Rc<RefCell<Vec<DrawCommand>>>,
+}
+
+impl ProxyRecorder {
+    pub fn replay(self, renderer: &dyn Renderer) {
+        let commands = std::mem::take(&mut *self.commands.borrow_mut());
+        // fillRect 使用当前的填充色，与 canvas 的语义一致
+        let mut fill_style = "black".to_string();
+        for command in commands {
+            match command {
+                DrawCommand::BeginPath => renderer.begin_path(),
+                DrawCommand::MoveTo(x, y) => renderer.move_to(x, y),
+                DrawCommand::LineTo(x, y) => renderer.line_to(x, y),
+                DrawCommand::BezierCurveTo(cp1x, cp1y, cp2x, cp2y, x, y) => {
+                    renderer.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, x, y)
+                }
+                DrawCommand::QuadraticCurveTo(cpx, cpy, x, y) => {
+                    renderer.quadratic_curve_to(cpx, cpy, x, y)
+                }
+                DrawCommand::Arc(x, y, radius, start_angle, end_angle) => {
+                    renderer.arc(x, y, radius, start_angle, end_angle)
+                }
+                DrawCommand::ClosePath => renderer.close_path(),
+                DrawCommand::Fill => renderer.fill(),
+                DrawCommand::Stroke => renderer.stroke(),
+                DrawCommand::FillRect(x, y, width, height) => {
+                    renderer.draw_rectangle(x, y, width, height, &fill_style)
+                }
+                DrawCommand::StrokeRect(x, y, width, height) => {
+                    renderer.stroke_rect(x, y, width, height)
+                }
+                DrawCommand::FillText(text, x, y) => renderer.fill_text(&text, x, y),
+                DrawCommand::FillStyle(style) => {
+                    renderer.set_fill_style(&style);
+                    fill_style = style;
+                }
+                DrawCommand::StrokeStyle(style) => renderer.set_stroke_style(&style),
+                DrawCommand::LineWidth(width) => renderer.set_line_width(width),
+                DrawCommand::Font(font) => renderer.set_font(&font),
+                DrawCommand::GlobalAlpha(alpha) => renderer.set_global_alpha(alpha),
+                DrawCommand::Filter(filter) => renderer.set_filter(&filter),
+                DrawCommand::Save => renderer.save(),
+                DrawCommand::Restore => renderer.restore(),
+                DrawCommand::Translate(x, y) => renderer.translate(x, y),
+                DrawCommand::Rotate(angle) => renderer.rotate(angle),
+                DrawCommand::Scale(x, y) => renderer.scale(x, y),
+            }
+        }
+    }
+}
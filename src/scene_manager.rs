@@ -1,8 +1,26 @@
 use crate::{
-    app::App, element::{ObjectId, Renderable}, helper::{
-        convert_1x6_to_3x3, convert_3x3_to_1x6, get_canvas, get_canvas_css_size, get_window_dpr,
-    }, history::{HistoryItem, SceneHistoryItem}, object_manager::ObjectManager, renderer::{Canvas2DRenderer, OffscreenCanvas2DRenderer, Renderer}
+    animation::{Animatable, AnimationError, AnimationValue},
+    app::App, events::AppEvent, batch::{draw_batch, group_runs, RenderRun}, bounding_box::BoundingBox, element::{create_element, PointerEvents, Renderable}, error::{EditingError, EditingResult}, helper::{get_canvas, get_canvas_css_size, get_window_dpr, now_ms, to_js_value}, math::{convert_1x6_to_3x3, convert_3x3_to_1x6}, log::{log_debug, log_error, log_warn}, render_stats::{RenderStats, RenderStatsCollector}, history::{HistoryItem, SceneHistoryItem}, object_manager::ObjectManager, render_worker::RenderWorker, sync::{Presence, PresenceOverlay}, renderer::{Canvas2DRenderer, OffscreenCanvas2DRenderer, Renderer}, theme::Theme
 };
+mod grid;
+mod measure;
+mod path_handles;
+mod progressive;
+mod scrollbars;
+mod units;
+mod view;
+mod view_history;
+
+pub use grid::{BackgroundGrid, GridOptions};
+pub use measure::{DimensionLabel, MeasureOptions, MeasureOverlay, Ruler};
+pub use path_handles::{AnchorHandle, HandleHit, PathHandles};
+pub use progressive::ProgressiveOptions;
+pub use scrollbars::{ScrollThumb, ScrollbarOptions, Scrollbars};
+pub use units::{DocumentUnit, DocumentUnits};
+use progressive::ProgressiveJob;
+use view::{hit_pixel, view_matrix};
+use view_history::ViewHistory;
+
 use nalgebra as na;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -13,27 +31,88 @@ use std::{
     rc::Rc,
 };
 use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
 use wasm_timer::Instant;
 use web_sys::{
-    console, window, CanvasRenderingContext2d, HtmlCanvasElement, MouseEvent, OffscreenCanvas,
-    OffscreenCanvasRenderingContext2d,
+    window, CanvasRenderingContext2d, HtmlCanvasElement, MouseEvent, OffscreenCanvas,
+    OffscreenCanvasRenderingContext2d, ResizeObserver,
 };
 
-#[derive(Debug, Clone)]
+const MIN_SUPERSAMPLING: f64 = 0.5;
+const MAX_SUPERSAMPLING: f64 = 4.0;
+const MIN_HIT_RESOLUTION: f64 = 0.1;
+pub const DEFAULT_MIN_ZOOM: f64 = 0.1;
+pub const DEFAULT_MAX_ZOOM: f64 = 10.0;
+pub const DEFAULT_HIT_TOLERANCE: f64 = 4.0;
+// 导出图片的最大边长（像素），与常见浏览器的画布尺寸上限一致
+const MAX_EXPORT_SIZE: f64 = 16384.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CanvasContextType {
+    #[serde(rename = "2d")]
     Canvas2d,
+    #[serde(rename = "webgl2")]
     WebGl2,
 }
 
+// 拾取方式：Canvas 读取 hit 画布像素，精确到形状；Geometry 只按包围盒查询空间索引，不需要绘制 hit 画布
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitTestMode {
+    #[default]
+    Canvas,
+    Geometry,
+}
+
+// 从显示画布取到的颜色，取样区域内各通道的平均值
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PixelColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl PixelColor {
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+}
+
+// 当前视图状态；offset 为 [x, y]，size 为画布 CSS 尺寸 [width, height]，画布初始化之前为 None
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Viewport {
+    pub zoom: f64,
+    pub offset: (f64, f64),
+    pub rotation: f64,
+    pub size: Option<(f64, f64)>,
+    pub dpr: f64,
+}
+
+// "scenechange" 事件中的 old、new 也使用该结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SceneDirtyData {
     pub zoom: f64,
     pub offset_x: f64,
     pub offset_y: f64,
     pub rotation: f64,
-    pub height: u32,
-    pub width: u32,
-    pub dpr: f64,
+    // 画布初始化之前尺寸和 dpr 未知
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub dpr: Option<f64>,
+}
+
+impl SceneDirtyData {
+    // 平移、缩放和旋转是否相同，不比较画布尺寸
+    pub fn same_view(&self, other: &SceneDirtyData) -> bool {
+        self.zoom == other.zoom
+            && self.offset_x == other.offset_x
+            && self.offset_y == other.offset_y
+            && self.rotation == other.rotation
+    }
 }
 
 pub struct SceneManagerOptions {
@@ -43,6 +122,26 @@ pub struct SceneManagerOptions {
     pub height: Option<u32>,
     pub width: Option<u32>,
     pub device_pixel_ratio: Option<f64>,
+    // 显示画布相对设备像素的超采样倍数
+    pub supersampling: f64,
+    // hit 画布分辨率相对显示画布的比例，取值 (0, 1]
+    pub hit_resolution: f64,
+    // 设置后显示画布交给该脚本启动的 worker 绘制
+    pub render_worker_script: Option<String>,
+    pub hit_test_mode: HitTestMode,
+    // 描边两侧额外可点中的距离（屏幕像素），不随缩放变化
+    pub hit_tolerance: f64,
+    // 每帧绘制前填充的背景色，None 为透明
+    pub background: Option<String>,
+    pub grid: GridOptions,
+    pub scrollbars: ScrollbarOptions,
+    // 对象很多时分帧绘制
+    pub progressive: ProgressiveOptions,
+    pub units: DocumentUnits,
+    pub min_zoom: f64,
+    pub max_zoom: f64,
+    // 平移、缩放、旋转是否进入文档历史（可撤销），默认只记录到视图的后退/前进记录
+    pub record_camera_history: bool,
 }
 
 impl Default for SceneManagerOptions {
@@ -55,6 +154,19 @@ impl Default for SceneManagerOptions {
             height: None,
             width: None,
             device_pixel_ratio: Some(window_dpr),
+            supersampling: 2.0,
+            hit_resolution: 1.0,
+            render_worker_script: None,
+            hit_test_mode: HitTestMode::Canvas,
+            hit_tolerance: DEFAULT_HIT_TOLERANCE,
+            background: None,
+            grid: GridOptions::default(),
+            scrollbars: ScrollbarOptions::default(),
+            progressive: ProgressiveOptions::default(),
+            units: DocumentUnits::default(),
+            min_zoom: DEFAULT_MIN_ZOOM,
+            max_zoom: DEFAULT_MAX_ZOOM,
+            record_camera_history: false,
         }
     }
 }
@@ -62,6 +174,9 @@ impl Default for SceneManagerOptions {
 #[derive(Debug, Clone)]
 pub struct SceneManager {
     dpr: Option<f64>,
+    device_pixel_ratio: Option<f64>,
+    supersampling: f64,
+    hit_resolution: f64,
     height: Option<u32>,
     width: Option<u32>,
     context_type: CanvasContextType,
@@ -70,11 +185,36 @@ pub struct SceneManager {
     renderer: Rc<RefCell<Option<Box<dyn Renderer>>>>,
     hit_canvas: Option<Rc<RefCell<OffscreenCanvas>>>,
     hit_renderer: Rc<RefCell<Option<Box<dyn Renderer>>>>,
+    render_worker_script: Option<String>,
+    render_worker: Option<Rc<RenderWorker>>,
     object_manager: Rc<RefCell<ObjectManager>>,
 
     last_update: Instant,
+    last_frame: Cell<Option<Instant>>,
+    frame_hooks: Rc<RefCell<FrameHooks>>,
+    render_stats: Rc<RefCell<RenderStatsCollector>>,
+    show_stats: bool,
+    presence: Rc<RefCell<PresenceOverlay>>,
+    path_handles: RefCell<Option<PathHandles>>,
+    measure: RefCell<MeasureOverlay>,
+    batching: bool,
+    pending_hit_view: Rc<Cell<Option<HitView>>>,
+    progressive_options: ProgressiveOptions,
+    progressive_job: Rc<RefCell<Option<ProgressiveJob>>>,
+    debug: DebugOptions,
+    hit_test_mode: HitTestMode,
+    hit_tolerance: f64,
+    background: Option<String>,
+    theme: Theme,
+    grid: Rc<RefCell<BackgroundGrid>>,
+    scrollbars: Rc<RefCell<Scrollbars>>,
+    units: DocumentUnits,
 
     zoom: f64,
+    min_zoom: f64,
+    max_zoom: f64,
+    record_camera_history: bool,
+    view_history: Rc<RefCell<ViewHistory>>,
     offset_x: f64,
     offset_y: f64,
     rotation: f64,
@@ -84,9 +224,15 @@ pub struct SceneManager {
 
     event_handlers: Rc<RefCell<EventHandlers>>,
     event_listeners: Rc<RefCell<HashMap<String, Closure<dyn FnMut(MouseEvent)>>>>,
+    resize_listener: Rc<RefCell<Option<ResizeListener>>>,
 
     cached_transform: Cell<Option<na::Matrix1x6<f64>>>,
     transform_dirty: Cell<bool>,
+
+    // 最近一次通知给 JS 的视图，用于去掉没有实际变化的通知
+    last_viewport: Rc<Cell<Option<Viewport>>>,
+    // 尚未通知的变化开始之前的场景状态，有值时表示通知已安排
+    scene_change_origin: Rc<RefCell<Option<SceneDirtyData>>>,
     
     app: Option<App>,
 }
@@ -105,27 +251,12 @@ impl SceneManager {
             }
         }
 
-        let scale_matrix =
-            na::Matrix3::new(self.zoom, 0.0, 0.0, 0.0, self.zoom, 0.0, 0.0, 0.0, 1.0);
-
-        let cos_r = self.rotation.cos();
-        let sin_r = self.rotation.sin();
-        let rotation_matrix =
-            na::Matrix3::new(cos_r, -sin_r, 0.0, sin_r, cos_r, 0.0, 0.0, 0.0, 1.0);
-
-        let translation_matrix = na::Matrix3::new(
-            1.0,
-            0.0,
-            self.offset_x ,
-            0.0,
-            1.0,
-            self.offset_y,
-            0.0,
-            0.0,
-            1.0,
+        let transform_matrix = view_matrix(
+            self.zoom,
+            self.rotation,
+            (self.offset_x, self.offset_y),
+            (self.center_x, self.center_y),
         );
-
-        let transform_matrix = scale_matrix * rotation_matrix * translation_matrix;
         let result = convert_3x3_to_1x6(transform_matrix);
 
         self.cached_transform.set(Some(result));
@@ -136,7 +267,7 @@ impl SceneManager {
 
     pub fn set_zoom(&mut self, zoom: f64) {
         let old_data = self.get_dirty_data();
-        self.zoom = zoom.max(0.1).min(10.0); // Limit zoom range
+        self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
         let new_data = self.get_dirty_data();
         self.set_transform_direct(old_data, new_data);
     }
@@ -166,7 +297,7 @@ impl SceneManager {
 
     pub fn zoom_at(&mut self, x: f64, y: f64, factor: f64) {
         let old_data = self.get_dirty_data();
-        let new_zoom = (self.zoom * factor).max(0.1).min(10.0);
+        let new_zoom = (self.zoom * factor).clamp(self.min_zoom, self.max_zoom);
         let zoom_change = new_zoom / self.zoom;
         self.offset_x = x - (x - self.offset_x) * zoom_change;
         self.offset_y = y - (y - self.offset_y) * zoom_change;
@@ -175,6 +306,79 @@ impl SceneManager {
         self.set_transform_direct(old_data, new_data);
     }
 
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    pub fn offset(&self) -> (f64, f64) {
+        (self.offset_x, self.offset_y)
+    }
+
+    // 弧度
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    pub fn get_viewport(&self) -> Viewport {
+        Viewport {
+            zoom: self.zoom,
+            offset: (self.offset_x, self.offset_y),
+            rotation: self.rotation,
+            size: self.viewport_size(),
+            dpr: self.device_pixel_ratio.unwrap_or(1.0),
+        }
+    }
+
+    // 同一任务内的多次变化合并为一次通知并延后发出，回调中可以安全地读取场景状态：
+    // 平移、缩放或旋转变化时发出 "scenechange" { old, new }，视图有变化时发出 "viewportchange"
+    fn notify_scene_change(&self, old_data: SceneDirtyData) {
+        let Some(app) = self.app.clone() else {
+            return;
+        };
+        {
+            let mut origin = self.scene_change_origin.borrow_mut();
+            if origin.is_some() {
+                return;
+            }
+            *origin = Some(old_data);
+        }
+        let origin = self.scene_change_origin.clone();
+        let last_viewport = self.last_viewport.clone();
+        spawn_local(async move {
+            let Some(old_data) = origin.borrow_mut().take() else {
+                return;
+            };
+            let (new_data, viewport) = match app.scene_manager.try_borrow() {
+                Ok(scene_manager) => (scene_manager.get_dirty_data(), scene_manager.get_viewport()),
+                Err(_) => return,
+            };
+            if !old_data.same_view(&new_data) {
+                app.record_camera(&old_data, &new_data);
+                let payload = to_js_value(&serde_json::json!({ "old": old_data, "new": new_data }));
+                let _ = app.events.emit(AppEvent::SCENE_CHANGE.into(), &payload);
+            }
+            if last_viewport.replace(Some(viewport)) != Some(viewport) {
+                let _ = app.events.emit(AppEvent::VIEWPORT_CHANGE.into(), &to_js_value(&viewport));
+            }
+        });
+    }
+
+    // 画布的 CSS 尺寸，初始化之前未知
+    pub fn viewport_size(&self) -> Option<(f64, f64)> {
+        Some((self.width? as f64, self.height? as f64))
+    }
+
+    pub fn zoom_limits(&self) -> (f64, f64) {
+        (self.min_zoom, self.max_zoom)
+    }
+
+    // 修改范围后当前缩放也会被限制到新范围内
+    pub fn set_zoom_limits(&mut self, min_zoom: f64, max_zoom: f64) {
+        self.min_zoom = min_zoom;
+        self.max_zoom = max_zoom.max(min_zoom);
+        self.set_zoom(self.zoom);
+    }
+
     pub fn reset_transform(&mut self) {
         let old_data = self.get_dirty_data();
         self.zoom = 1.0;
@@ -187,12 +391,59 @@ impl SceneManager {
 
     pub fn set_transform_direct(&self, old_data: SceneDirtyData, new_data: SceneDirtyData) {
         self.transform_dirty.set(true);
+        self.notify_scene_change(old_data.clone());
+        if !old_data.same_view(&new_data) {
+            self.view_history.borrow_mut().record(old_data.clone(), now_ms());
+            self.scrollbars.borrow_mut().show(now_ms());
+        }
+        if let Some(app) = &self.app {
+            if self.record_camera_history {
+                match (serde_json::to_value(old_data), serde_json::to_value(new_data)) {
+                    (Ok(undo_data), Ok(redo_data)) => {
+                        let item = SceneHistoryItem::new(undo_data, redo_data);
+                        app.record_history(HistoryItem::SceneUpdate(item));
+                    }
+                    (Err(e), _) | (_, Err(e)) => log_error!("Failed to record scene history: {}", e),
+                }
+            }
+            app.request_render();
+        }
+    }
+
+    pub fn set_record_camera_history(&mut self, enabled: bool) {
+        self.record_camera_history = enabled;
+    }
+
+    // 回到上一个视图，不进入文档历史
+    pub fn view_back(&mut self) -> bool {
+        let current = self.get_dirty_data();
+        let target = self.view_history.borrow_mut().back(current.clone());
+        target.map(|target| self.restore_view(current, &target)).is_some()
+    }
+
+    pub fn view_forward(&mut self) -> bool {
+        let current = self.get_dirty_data();
+        let target = self.view_history.borrow_mut().forward(current.clone());
+        target.map(|target| self.restore_view(current, &target)).is_some()
+    }
+
+    pub fn can_view_back(&self) -> bool {
+        self.view_history.borrow().can_back()
+    }
+
+    pub fn can_view_forward(&self) -> bool {
+        self.view_history.borrow().can_forward()
+    }
+
+    // 只恢复平移、缩放和旋转，不记录到任何历史
+    fn restore_view(&mut self, old_data: SceneDirtyData, target: &SceneDirtyData) {
+        self.zoom = target.zoom.clamp(self.min_zoom, self.max_zoom);
+        self.offset_x = target.offset_x;
+        self.offset_y = target.offset_y;
+        self.rotation = target.rotation;
+        self.transform_dirty.set(true);
+        self.notify_scene_change(old_data);
         if let Some(app) = &self.app {
-            let item = SceneHistoryItem::new(
-                serde_json::to_value(old_data).unwrap(),
-                serde_json::to_value(new_data).unwrap(),
-            );
-            app.history.borrow_mut().push(HistoryItem::SceneUpdate(item));
             app.request_render();
         }
     }
@@ -234,16 +485,72 @@ impl SceneManager {
         self.set_transform_direct(old_data, new_data);
     }
 
-    fn get_dirty_data(&self) -> SceneDirtyData {
+    pub fn get_dirty_data(&self) -> SceneDirtyData {
         SceneDirtyData {
             zoom: self.zoom,
             offset_x: self.offset_x,
             offset_y: self.offset_y,
             rotation: self.rotation,
-            height: self.height.unwrap(),
-            width: self.width.unwrap(),
-            dpr: self.dpr.unwrap(),
+            height: self.height,
+            width: self.width,
+            dpr: self.dpr,
+        }
+    }
+}
+
+impl Animatable for SceneManager {
+    fn get_properties(&self, properties: &[String]) -> HashMap<String, AnimationValue> {
+        let mut result = HashMap::new();
+
+        for property in properties {
+            let value = match property.as_str() {
+                "zoom" => AnimationValue::Float(self.zoom),
+                "offset_x" => AnimationValue::Float(self.offset_x),
+                "offset_y" => AnimationValue::Float(self.offset_y),
+                "offset" => AnimationValue::Vector2D((self.offset_x, self.offset_y)),
+                "rotation" => AnimationValue::Float(self.rotation),
+                "center_x" => AnimationValue::Float(self.center_x),
+                "center_y" => AnimationValue::Float(self.center_y),
+                "center" => AnimationValue::Vector2D((self.center_x, self.center_y)),
+                _ => continue,
+            };
+            result.insert(property.clone(), value);
+        }
+
+        result
+    }
+
+    fn set_properties(
+        &mut self,
+        properties: HashMap<String, AnimationValue>,
+    ) -> Result<(), AnimationError> {
+        let old_data = self.get_dirty_data();
+        for (property, value) in properties {
+            match (property.as_str(), value) {
+                ("zoom", AnimationValue::Float(v)) => self.zoom = v.clamp(self.min_zoom, self.max_zoom),
+                ("offset_x", AnimationValue::Float(v)) => self.offset_x = v,
+                ("offset_y", AnimationValue::Float(v)) => self.offset_y = v,
+                ("offset", AnimationValue::Vector2D((x, y))) => {
+                    self.offset_x = x;
+                    self.offset_y = y;
+                }
+                ("rotation", AnimationValue::Float(v)) => self.rotation = v,
+                ("center_x", AnimationValue::Float(v)) => self.center_x = v,
+                ("center_y", AnimationValue::Float(v)) => self.center_y = v,
+                ("center", AnimationValue::Vector2D((x, y))) => {
+                    self.center_x = x;
+                    self.center_y = y;
+                }
+                _ => return Err(AnimationError::InvalidProperty(property.into())),
+            }
         }
+        let new_data = self.get_dirty_data();
+        self.set_transform_direct(old_data, new_data);
+        Ok(())
+    }
+
+    fn is_animatable(&self) -> bool {
+        true
     }
 }
 
@@ -251,6 +558,9 @@ impl SceneManager {
     pub fn new(options: SceneManagerOptions) -> Self {
         Self {
             dpr: options.device_pixel_ratio,
+            device_pixel_ratio: options.device_pixel_ratio,
+            supersampling: options.supersampling.clamp(MIN_SUPERSAMPLING, MAX_SUPERSAMPLING),
+            hit_resolution: options.hit_resolution.clamp(MIN_HIT_RESOLUTION, 1.0),
             height: options.height,
             width: options.width,
             context_type: options.context_type.unwrap_or(CanvasContextType::Canvas2d),
@@ -259,9 +569,45 @@ impl SceneManager {
             renderer: Rc::new(RefCell::new(None)),
             hit_canvas: None,
             hit_renderer: Rc::new(RefCell::new(None)),
+            render_worker_script: options.render_worker_script,
+            render_worker: None,
             object_manager: options.object_manager,
             last_update: Instant::now(),
+            last_frame: Cell::new(None),
+            frame_hooks: Rc::new(RefCell::new(FrameHooks::default())),
+            render_stats: Rc::new(RefCell::new(RenderStatsCollector::default())),
+            show_stats: false,
+            presence: Rc::new(RefCell::new(PresenceOverlay::default())),
+            path_handles: RefCell::new(None),
+            measure: RefCell::new(MeasureOverlay::default()),
+            batching: true,
+            pending_hit_view: Rc::new(Cell::new(None)),
+            progressive_options: ProgressiveOptions {
+                frame_budget_ms: options.progressive.frame_budget_ms.max(1.0),
+                ..options.progressive
+            },
+            progressive_job: Rc::new(RefCell::new(None)),
+            debug: DebugOptions::default(),
+            hit_test_mode: options.hit_test_mode,
+            hit_tolerance: options.hit_tolerance.max(0.0),
+            background: options.background,
+            theme: Theme::default(),
+            grid: {
+                let mut grid = BackgroundGrid::default();
+                grid.set_options(options.grid);
+                Rc::new(RefCell::new(grid))
+            },
+            scrollbars: {
+                let mut scrollbars = Scrollbars::default();
+                scrollbars.set_options(options.scrollbars);
+                Rc::new(RefCell::new(scrollbars))
+            },
+            units: options.units,
             zoom: 1.0,
+            min_zoom: options.min_zoom,
+            max_zoom: options.max_zoom.max(options.min_zoom),
+            record_camera_history: options.record_camera_history,
+            view_history: Rc::new(RefCell::new(ViewHistory::default())),
             offset_x: 0.0,
             offset_y: 0.0,
             rotation: 0.0,
@@ -271,10 +617,14 @@ impl SceneManager {
 
             event_handlers: Rc::new(RefCell::new(EventHandlers::default())),
             event_listeners: Rc::new(RefCell::new(HashMap::new())),
+            resize_listener: Rc::new(RefCell::new(None)),
 
             cached_transform: Cell::new(None),
             transform_dirty: Cell::new(true),
 
+            last_viewport: Rc::new(Cell::new(None)),
+            scene_change_origin: Rc::new(RefCell::new(None)),
+
             app: None,
         }
     }
@@ -287,29 +637,34 @@ impl SceneManager {
         self.app = None;
     }
 
-    pub fn update_scene(&mut self, data: Value) {
-        let dirty_data: SceneDirtyData = serde_json::from_value(data).unwrap();
+    pub fn update_scene(&mut self, data: Value) -> EditingResult<()> {
+        let dirty_data: SceneDirtyData = serde_json::from_value(data)?;
 
         self.set_zoom(dirty_data.zoom);
         self.set_offset(dirty_data.offset_x, dirty_data.offset_y);
         self.set_rotation(dirty_data.rotation);
-        self.set_height(dirty_data.height);
-        self.set_width(dirty_data.width);
-        self.set_dpr(dirty_data.dpr);
+        if let Some(height) = dirty_data.height {
+            self.set_height(height);
+        }
+        if let Some(width) = dirty_data.width {
+            self.set_width(width);
+        }
+        if let Some(dpr) = dirty_data.dpr {
+            self.set_dpr(dpr);
+        }
+        Ok(())
     }
 
     pub fn reset_to_initial_state(&mut self) {
         self.set_zoom(1.0);
         self.set_offset(0.0, 0.0);
         self.set_rotation(0.0);
-        self.set_height(self.height.unwrap());
-        self.set_width(self.width.unwrap());
-        self.set_dpr(self.dpr.unwrap());
+        *self.view_history.borrow_mut() = ViewHistory::default();
     }
 }
 
 impl SceneManager {
-    pub fn set_pixel_ratio(&mut self, ratio: f64) -> Result<(), JsValue> {
+    pub fn set_pixel_ratio(&mut self, ratio: f64) -> EditingResult<()> {
         // let (css_width, css_height) = get_canvas_css_size(&canvas)?;
         if let Some(canvas) = self.canvas.as_ref() {
             let size_canvas = get_canvas(&self.canvas_id)?;
@@ -318,73 +673,161 @@ impl SceneManager {
             let physical_width = (css_width as f64 * ratio) as u32;
             let physical_height = (css_height as f64 * ratio) as u32;
 
-            canvas.borrow_mut().set_width(physical_width);
-            canvas.borrow_mut().set_height(physical_height);
+            // 交给 worker 的画布只能由 worker 修改尺寸
+            match &self.render_worker {
+                Some(worker) => worker.resize(physical_width, physical_height),
+                None => {
+                    canvas.borrow_mut().set_width(physical_width);
+                    canvas.borrow_mut().set_height(physical_height);
+                }
+            }
 
-            // Update hit_canvas
+            // hit 画布可以用更低的分辨率
+            let hit_ratio = ratio * self.hit_resolution;
             if let Some(hit_canvas) = &mut self.hit_canvas {
-                hit_canvas.borrow_mut().set_width(physical_width);
-                hit_canvas.borrow_mut().set_height(physical_height);
+                hit_canvas
+                    .borrow_mut()
+                    .set_width((css_width as f64 * hit_ratio).ceil() as u32);
+                hit_canvas
+                    .borrow_mut()
+                    .set_height((css_height as f64 * hit_ratio).ceil() as u32);
             }
 
-            self.renderer
-                .borrow_mut()
-                .as_mut()
-                .unwrap()
-                .scale(ratio, ratio);
-            self.hit_renderer
-                .borrow_mut()
-                .as_mut()
-                .unwrap()
-                .scale(ratio, ratio);
+            for (renderer, scale) in [(&self.renderer, ratio), (&self.hit_renderer, hit_ratio)] {
+                renderer
+                    .borrow()
+                    .as_ref()
+                    .ok_or(EditingError::NotInitialized("Renderer"))?
+                    .scale(scale, scale);
+            }
         }
         self.dpr = Some(ratio);
         Ok(())
     }
 
-    pub fn set_context_type(&mut self, context_type: &str) -> Result<(), JsValue> {
+    // 显示画布和 hit 画布的像素缓冲区字节数估算
+    pub fn canvas_memory(&self) -> usize {
+        let ratio = self.dpr.unwrap_or(1.0);
+        let display = match (self.width, self.height) {
+            (Some(width), Some(height)) => (width as f64 * ratio) as usize * (height as f64 * ratio) as usize,
+            _ => 0,
+        };
+        let hit = self.hit_canvas.as_ref().map_or(0, |canvas| {
+            let canvas = canvas.borrow();
+            canvas.width() as usize * canvas.height() as usize
+        });
+        (display + hit) * 4
+    }
+
+    pub fn supersampling(&self) -> f64 {
+        self.supersampling
+    }
+
+    pub fn set_supersampling(&mut self, factor: f64) -> EditingResult<()> {
+        self.supersampling = factor.clamp(MIN_SUPERSAMPLING, MAX_SUPERSAMPLING);
+        self.set_pixel_ratio(self.display_ratio())
+    }
+
+    pub fn hit_resolution(&self) -> f64 {
+        self.hit_resolution
+    }
+
+    pub fn set_hit_resolution(&mut self, resolution: f64) -> EditingResult<()> {
+        self.hit_resolution = resolution.clamp(MIN_HIT_RESOLUTION, 1.0);
+        self.set_pixel_ratio(self.display_ratio())
+    }
+
+    pub fn hit_tolerance(&self) -> f64 {
+        self.hit_tolerance
+    }
+
+    pub fn set_hit_tolerance(&mut self, tolerance: f64) {
+        self.hit_tolerance = tolerance.max(0.0);
+    }
+
+    // 拾取容差换算到世界坐标
+    fn world_hit_tolerance(&self) -> f64 {
+        self.hit_tolerance / self.zoom
+    }
+
+    // hit 画布每个 CSS 像素对应的物理像素数
+    pub fn hit_ratio(&self) -> f64 {
+        self.dpr.unwrap_or(1.0) * self.hit_resolution
+    }
+
+    fn display_ratio(&self) -> f64 {
+        self.device_pixel_ratio.unwrap_or(1.0) * self.supersampling
+    }
+
+    pub fn set_context_type(&mut self, context_type: &str) -> EditingResult<()> {
         let context_type = match context_type {
             "2d" => CanvasContextType::Canvas2d,
             "webgl2" => CanvasContextType::WebGl2,
-            _ => return Err(JsValue::from_str("Unsupported context type")),
+            _ => return Err(EditingError::ContextUnsupported(context_type.to_string())),
         };
         self.context_type = context_type;
         Ok(())
     }
+
+    // 必须在 init 之前设置，画布一旦交给 worker 就无法收回
+    pub fn set_render_worker(&mut self, script_url: Option<String>) -> EditingResult<()> {
+        if self.canvas.is_some() {
+            return Err(EditingError::AlreadyInitialized("Canvas"));
+        }
+        self.render_worker_script = script_url;
+        Ok(())
+    }
+
+    pub fn is_worker_rendering(&self) -> bool {
+        self.render_worker.is_some()
+    }
 }
 
 impl SceneManager {
-    pub fn init(&mut self) -> Result<(), JsValue> {
-        let dpr = get_window_dpr()?;
+    pub fn init(&mut self) -> EditingResult<()> {
+        let dpr = match self.device_pixel_ratio {
+            Some(dpr) => dpr,
+            None => *self.device_pixel_ratio.insert(get_window_dpr()?),
+        };
         let canvas = get_canvas(&self.canvas_id)?;
         let (css_width, css_height) = get_canvas_css_size(&canvas)?;
 
-        self.width = Some(self.width.unwrap_or(css_width));
-        self.height = Some(self.height.unwrap_or(css_height));
+        let width = *self.width.get_or_insert(css_width);
+        let height = *self.height.get_or_insert(css_height);
 
         let hit_canvas = OffscreenCanvas::new(
-            (self.width.unwrap() as f64 * dpr) as u32,
-            (self.height.unwrap() as f64 * dpr) as u32,
-        )
-        .unwrap();
+            (width as f64 * dpr) as u32,
+            (height as f64 * dpr) as u32,
+        )?;
 
         let (renderer, hit_renderer) = match self.context_type {
             CanvasContextType::Canvas2d => {
-                let context: CanvasRenderingContext2d = canvas
-                    .get_context("2d")?
-                    .ok_or_else(|| JsValue::from_str("Failed to get 2D context"))?
-                    .dyn_into::<CanvasRenderingContext2d>()?;
-
-                let renderer = Canvas2DRenderer::create_renderer(context);
+                let renderer = match &self.render_worker_script {
+                    Some(script_url) => {
+                        let worker = Rc::new(RenderWorker::spawn(&canvas, script_url)?);
+                        let renderer = worker.create_renderer(self.theme.clone());
+                        self.render_worker = Some(worker);
+                        renderer
+                    }
+                    None => {
+                        let context: CanvasRenderingContext2d = canvas
+                            .get_context("2d")?
+                            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+                            .ok_or_else(|| EditingError::ContextCreationFailed("2d".to_string()))?;
+                        Canvas2DRenderer::create_renderer(context, self.theme.clone())
+                    }
+                };
                 let hit_context: OffscreenCanvasRenderingContext2d = hit_canvas
                     .get_context("2d")?
-                    .ok_or_else(|| JsValue::from_str("Failed to get 2D context"))?
-                    .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+                    .and_then(|context| context.dyn_into::<OffscreenCanvasRenderingContext2d>().ok())
+                    .ok_or_else(|| EditingError::ContextCreationFailed("2d".to_string()))?;
 
-                let hit_renderer = OffscreenCanvas2DRenderer::create_renderer(hit_context);
+                let hit_renderer = OffscreenCanvas2DRenderer::create_renderer(hit_context, self.theme.clone());
                 (renderer, hit_renderer)
             }
-            _ => return Err(JsValue::from_str("Unsupported context type")),
+            CanvasContextType::WebGl2 => {
+                return Err(EditingError::ContextUnsupported("webgl2".to_string()))
+            }
         };
 
         self.renderer = renderer;
@@ -392,7 +835,7 @@ impl SceneManager {
         self.canvas = Some(Rc::new(RefCell::new(canvas)));
         self.hit_canvas = Some(Rc::new(RefCell::new(hit_canvas)));
 
-        self.set_pixel_ratio(dpr * 2.0)?;
+        self.set_pixel_ratio(self.display_ratio())?;
 
         self.init_event()?;
         Ok(())
@@ -407,77 +850,613 @@ impl SceneManager {
         if let (Some(renderer), Some(hit_renderer)) = (renderer.as_mut(), hit_renderer.as_mut()) {
             self.render_scene(renderer, hit_renderer);
         }
+        self.flush_render_worker();
+    }
+
+    // worker 模式下把本帧记录的绘制指令发出去
+    fn flush_render_worker(&self) {
+        if let Some(worker) = &self.render_worker {
+            if let Err(e) = worker.flush() {
+                log_error!("Failed to post frame to render worker: {}", e);
+            }
+        }
     }
 
     fn render_scene(&self, renderer: &mut Box<dyn Renderer>, hit_renderer: &mut Box<dyn Renderer>) {
-        self.prepare_renderers(renderer, hit_renderer);
-        self.render_objects(renderer, hit_renderer);
-        self.restore_renderers(renderer, hit_renderer);
+        let delta = self.frame_delta();
+        self.render_stats.borrow_mut().begin_frame(delta);
+        let dirty_regions = self.take_dirty_regions();
+        // 新的一帧会完整重绘，丢弃上一帧未完成的精细绘制
+        self.progressive_job.borrow_mut().take();
+
+        renderer.clear_all();
+        self.fill_background(&**renderer);
+
+        self.run_frame_hooks(FrameHookStage::BeforeRender, &**renderer, delta);
+        let view = self.current_hit_view();
+        renderer.save();
+        Self::apply_view(&**renderer, &view);
+        if let Some(visible) = &view.visible {
+            self.grid.borrow_mut().draw(&**renderer, visible, self.zoom);
+        }
+        let job = self.render_objects(&**renderer, &view);
+        renderer.restore();
+
+        // hit 画布只标记为过期，等到真正需要拾取时再绘制
+        self.pending_hit_view.set(Some(view));
+
+        match job {
+            Some(job) => {
+                *self.progressive_job.borrow_mut() = Some(ProgressiveJob {
+                    delta,
+                    dirty_regions,
+                    ..job
+                });
+            }
+            None => self.finish_frame(renderer, hit_renderer, delta, &dirty_regions),
+        }
+    }
+
+    // 背景铺满整个画布，不受场景缩放和平移影响
+    fn fill_background(&self, renderer: &dyn Renderer) {
+        let (Some(background), Some(width), Some(height)) = (&self.background, self.width, self.height)
+        else {
+            return;
+        };
+        renderer.draw_rectangle(0.0, 0.0, width as f64, height as f64, background);
+    }
+
+    pub fn set_background(&mut self, background: Option<String>) {
+        self.background = background;
+    }
+
+    // 渲染器创建时持有同一份主题，修改后下一帧即按新颜色绘制
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn grid_options(&self) -> GridOptions {
+        self.grid.borrow().options().clone()
+    }
+
+    pub fn set_grid(&self, options: GridOptions) {
+        self.grid.borrow_mut().set_options(options);
+    }
+
+    pub fn units(&self) -> DocumentUnits {
+        self.units
     }
 
-    fn prepare_renderers(
+    pub fn set_units(&mut self, units: DocumentUnits) {
+        self.units = units;
+    }
+
+    pub fn hit_test_mode(&self) -> HitTestMode {
+        self.hit_test_mode
+    }
+
+    pub fn set_hit_test_mode(&mut self, mode: HitTestMode) {
+        self.hit_test_mode = mode;
+    }
+
+    // 场景对象全部绘制完成后再绘制帧尾钩子和调试信息
+    fn finish_frame(
         &self,
         renderer: &mut Box<dyn Renderer>,
         hit_renderer: &mut Box<dyn Renderer>,
+        delta: f64,
+        dirty_regions: &[BoundingBox],
     ) {
+        self.run_frame_hooks(FrameHookStage::AfterRender, &**renderer, delta);
+        self.render_presence(&**renderer, delta);
+        self.render_path_handles(&**renderer);
+        self.render_measure(&**renderer);
+        self.render_scrollbars(&**renderer);
+
+        if self.debug.enabled {
+            if self.debug.show_hit_canvas {
+                self.render_hit_canvas(hit_renderer);
+            }
+            self.render_debug(&**renderer, dirty_regions);
+        }
+        if self.show_stats {
+            self.render_stats.borrow().stats().draw_overlay(&**renderer);
+        }
+    }
+
+    // 协作者的指针和选中框画在场景之上、调试信息之下
+    fn render_presence(&self, renderer: &dyn Renderer, delta: f64) {
+        let mut presence = self.presence.borrow_mut();
+        if presence.is_empty() {
+            return;
+        }
+        presence.advance(delta);
+        let object_manager = self.object_manager.borrow();
+        presence.draw(renderer, &self.view_matrix(), |id| object_manager.world_bounds(id));
+    }
+
+    fn render_path_handles(&self, renderer: &dyn Renderer) {
+        if let Some(handles) = self.path_handles.borrow().as_ref() {
+            handles.draw(renderer, &self.view_matrix());
+        }
+    }
+
+    // 路径编辑模式的锚点覆盖层，None 表示不在编辑
+    pub fn set_path_handles(&self, handles: Option<PathHandles>) {
+        *self.path_handles.borrow_mut() = handles;
+    }
+
+    fn render_measure(&self, renderer: &dyn Renderer) {
+        let measure = self.measure.borrow();
+        if !measure.is_empty() {
+            measure.draw(renderer, &self.view_matrix(), &self.units);
+        }
+    }
+
+    pub fn set_measure_overlay(&self, overlay: MeasureOverlay) {
+        *self.measure.borrow_mut() = overlay;
+    }
+
+    fn render_scrollbars(&self, renderer: &dyn Renderer) {
+        let now = now_ms();
+        if !self.scrollbars.borrow().is_visible(now) {
+            return;
+        }
+        let thumbs = self.scroll_thumbs();
+        self.scrollbars.borrow().draw(renderer, &thumbs, now);
+    }
+
+    // 当前视图下滚动条滑块的位置（画布 CSS 像素），内容为文档范围与可见区域的并集
+    pub fn scroll_thumbs(&self) -> Vec<ScrollThumb> {
+        let (Some(view), Some(width), Some(height)) = (self.visible_world_bounds(), self.width, self.height) else {
+            return Vec::new();
+        };
+        let content = self.object_manager.borrow().document_bounds().unwrap_or(view);
+        self.scrollbars
+            .borrow()
+            .layout(&view, &content, width as f64, height as f64)
+    }
+
+    pub fn scrollbar_options(&self) -> ScrollbarOptions {
+        self.scrollbars.borrow().options().clone()
+    }
+
+    pub fn set_scrollbars(&self, options: ScrollbarOptions) {
+        self.scrollbars.borrow_mut().set_options(options);
+    }
+
+    // 显示或淡出期间需要继续绘制
+    pub fn is_scrollbar_visible(&self) -> bool {
+        self.scrollbars.borrow().is_visible(now_ms())
+    }
+
+    // 按下点（clientX/clientY）落在滑块上时开始拖动滚动条，返回 true 表示事件已被处理
+    pub fn scrollbar_pointer_down(&self, client_x: f64, client_y: f64) -> bool {
+        let Some((x, y)) = self.client_to_canvas(client_x, client_y) else {
+            return false;
+        };
+        if !self.scrollbars.borrow().is_visible(now_ms()) {
+            return false;
+        }
+        let thumbs = self.scroll_thumbs();
+        self.scrollbars.borrow_mut().begin_drag(&thumbs, x, y, now_ms())
+    }
+
+    // 拖动滑块时视口随之移动，与拖动画布平移的方向相反
+    pub fn scrollbar_pointer_move(&mut self, client_x: f64, client_y: f64) -> bool {
+        let Some((x, y)) = self.client_to_canvas(client_x, client_y) else {
+            return false;
+        };
+        let Some((dx, dy)) = self.scrollbars.borrow_mut().drag_to(x, y) else {
+            return false;
+        };
+        self.pan(-dx, -dy);
+        true
+    }
+
+    pub fn scrollbar_pointer_up(&self) -> bool {
+        let ended = self.scrollbars.borrow_mut().end_drag(now_ms());
+        if ended {
+            if let Some(app) = &self.app {
+                app.request_render();
+            }
+        }
+        ended
+    }
+
+    pub fn is_scrollbar_dragging(&self) -> bool {
+        self.scrollbars.borrow().is_dragging()
+    }
+
+    pub fn update_presence(&self, presence: Presence) {
+        self.presence.borrow_mut().update(presence);
+    }
+
+    pub fn remove_presence(&self, client_id: &str) -> bool {
+        self.presence.borrow_mut().remove(client_id)
+    }
+
+    pub fn clear_presence(&self) {
+        self.presence.borrow_mut().clear();
+    }
+
+    // 指针平滑移动期间需要继续绘制
+    pub fn is_presence_animating(&self) -> bool {
+        self.presence.borrow().is_animating()
+    }
+
+    pub fn get_render_stats(&self) -> RenderStats {
+        self.render_stats.borrow().stats()
+    }
+
+    pub fn set_show_stats(&mut self, show: bool) {
+        self.show_stats = show;
+    }
+
+    // 完整视图矩阵：世界坐标 -> 画布 CSS 像素
+    pub fn view_matrix(&self) -> na::Matrix3<f64> {
+        convert_1x6_to_3x3(self.calc_transform())
+    }
+
+    // 画布 CSS 像素 -> 世界坐标；dpr 和超采样只作用于画布分辨率，不影响这里的换算
+    pub fn screen_to_world(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let point = self.view_matrix().try_inverse()? * na::Vector3::new(x, y, 1.0);
+        Some((point.x, point.y))
+    }
+
+    pub fn world_to_screen(&self, x: f64, y: f64) -> (f64, f64) {
+        let point = self.view_matrix() * na::Vector3::new(x, y, 1.0);
+        (point.x, point.y)
+    }
+
+    // 鼠标事件的 clientX/clientY -> 世界坐标
+    pub fn client_to_world(&self, client_x: f64, client_y: f64) -> Option<(f64, f64)> {
+        let (x, y) = self.client_to_canvas(client_x, client_y)?;
+        self.screen_to_world(x, y)
+    }
+
+    // 鼠标事件的 clientX/clientY -> 画布 CSS 像素
+    fn client_to_canvas(&self, client_x: f64, client_y: f64) -> Option<(f64, f64)> {
+        let rect = self.canvas.as_ref()?.borrow().get_bounding_client_rect();
+        Some((client_x - rect.left(), client_y - rect.top()))
+    }
+
+    // 读取显示画布上画布 CSS 像素 (x, y) 处的颜色，size 为以该点为中心的取样边长（画布像素）；
+    // 画布交给 worker 绘制时无法读取，返回 UnavailableInWorker
+    pub fn pick_color(&self, x: f64, y: f64, size: u32) -> EditingResult<Option<PixelColor>> {
+        if self.is_worker_rendering() {
+            return Err(EditingError::UnavailableInWorker("pick_color"));
+        }
+        let Some(canvas) = self.canvas.as_ref() else {
+            return Ok(None);
+        };
+        let canvas = canvas.borrow();
+        let ratio = self.dpr.unwrap_or(1.0);
+        let (width, height) = (canvas.width() as i64, canvas.height() as i64);
+        let (center_x, center_y) = ((x * ratio).floor() as i64, (y * ratio).floor() as i64);
+        if center_x < 0 || center_y < 0 || center_x >= width || center_y >= height {
+            return Ok(None);
+        }
+
+        let size = size.max(1) as i64;
+        let left = (center_x - (size - 1) / 2).clamp(0, width - 1);
+        let top = (center_y - (size - 1) / 2).clamp(0, height - 1);
+        let (sample_width, sample_height) = ((left + size).min(width) - left, (top + size).min(height) - top);
+
+        let context = canvas
+            .get_context("2d")?
+            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+            .ok_or_else(|| EditingError::ContextCreationFailed("2d".to_string()))?;
+        let data = context
+            .get_image_data(left as f64, top as f64, sample_width as f64, sample_height as f64)?
+            .data();
+
+        let mut sum = [0u64; 4];
+        for pixel in data.chunks_exact(4) {
+            for (total, channel) in sum.iter_mut().zip(pixel) {
+                *total += *channel as u64;
+            }
+        }
+        let count = (data.len() / 4).max(1) as u64;
+        let [r, g, b, a] = sum.map(|total| ((total + count / 2) / count) as u8);
+        Ok(Some(PixelColor { r, g, b, a }))
+    }
+
+    // 把世界坐标区域按 scale 倍（输出像素 / 世界单位）绘制到新的离屏画布，包括背景；
+    // ids 不为 None 时只绘制其中的对象
+    pub fn render_region(
+        &self,
+        region: BoundingBox,
+        scale: f64,
+        ids: Option<&[String]>,
+    ) -> EditingResult<OffscreenCanvas> {
+        let width = (region.width * scale).ceil();
+        let height = (region.height * scale).ceil();
+        if !(width >= 1.0 && height >= 1.0 && width <= MAX_EXPORT_SIZE && height <= MAX_EXPORT_SIZE) {
+            return Err(EditingError::Dom(format!(
+                "Export size {}x{} is out of range (1..={})",
+                width, height, MAX_EXPORT_SIZE
+            )));
+        }
+
+        let canvas = OffscreenCanvas::new(width as u32, height as u32)?;
+        let context: OffscreenCanvasRenderingContext2d = canvas
+            .get_context("2d")?
+            .and_then(|context| context.dyn_into::<OffscreenCanvasRenderingContext2d>().ok())
+            .ok_or_else(|| EditingError::ContextCreationFailed("2d".to_string()))?;
+        let renderer = OffscreenCanvas2DRenderer::new(context, self.theme.clone());
+
+        if let Some(background) = &self.background {
+            renderer.draw_rectangle(0.0, 0.0, width, height, background);
+        }
+        renderer.save();
+        renderer.transform(scale, 0.0, 0.0, scale, -region.x * scale, -region.y * scale);
+        let object_manager = self.object_manager.borrow();
+        for object in object_manager.objects_in_rect(&region) {
+            let object_id = object.borrow().id().value().to_string();
+            // 子对象随所在的容器绘制
+            let included = object_manager.parent_of(&object_id).is_none()
+                && ids.is_none_or(|ids| ids.contains(&object_id));
+            if included {
+                self.render_object(&renderer, &object);
+            }
+        }
+        renderer.restore();
+        Ok(canvas)
+    }
+
+    // 世界坐标 -> 视口坐标（clientX/clientY）的变换，用于在画布上叠加 DOM 元素
+    pub fn world_to_client_matrix(&self) -> Option<na::Matrix3<f64>> {
+        let rect = self.canvas.as_ref()?.borrow().get_bounding_client_rect();
+        Some(na::Matrix3::new_translation(&na::Vector2::new(rect.left(), rect.top())) * self.view_matrix())
+    }
+
+    // 当前可见区域在世界坐标下的包围盒
+    pub fn visible_world_bounds(&self) -> Option<BoundingBox> {
+        let (width, height) = (self.width? as f64, self.height? as f64);
+        let inverse = self.view_matrix().try_inverse()?;
+        let corners = BoundingBox::new(0.0, 0.0, width, height)
+            .corners()
+            .map(|(x, y)| {
+                let point = inverse * na::Vector3::new(x, y, 1.0);
+                (point.x, point.y)
+            });
+        Some(BoundingBox::from_points(&corners))
+    }
+
+    fn frame_delta(&self) -> f64 {
+        let now = Instant::now();
+        let delta = self
+            .last_frame
+            .get()
+            .map(|last| (now - last).as_secs_f64())
+            .unwrap_or(0.0);
+        self.last_frame.set(Some(now));
+        delta
+    }
+
+    fn current_hit_view(&self) -> HitView {
+        HitView {
+            transform: self.calc_transform(),
+            visible: self.visible_world_bounds(),
+        }
+    }
+
+    fn apply_view(renderer: &dyn Renderer, view: &HitView) {
         let dpr = web_sys::window().unwrap().device_pixel_ratio() as f64;
-        let transform = self.calc_transform();
-
-        for r in &mut [renderer, hit_renderer] {
-            r.clear_all();
-            r.save();
-            r.set_line_width(1.0 / dpr);
-            
-            // Translate to the rotation center
-            r.translate(self.center_x, self.center_y);
-            
-            // Apply the transformation
-            r.transform(
-                transform[0],
-                transform[1],
-                transform[2],
-                transform[3],
-                transform[4],
-                transform[5],
-            );
-            
-            // Translate back from the rotation center
-            r.translate(-self.center_x, -self.center_y);
-        }
-    }
-
-    fn render_objects(
+        let transform = view.transform;
+
+        renderer.set_line_width(1.0 / dpr);
+
+        renderer.transform(
+            transform[0],
+            transform[1],
+            transform[2],
+            transform[3],
+            transform[4],
+            transform[5],
+        );
+    }
+
+    // 对象过多时先粗略绘制，再返回剩余的精细绘制任务
+    fn render_objects(&self, renderer: &dyn Renderer, view: &HitView) -> Option<ProgressiveJob> {
+        let total = self.object_manager.borrow().len();
+        let objects = self.visible_objects(view);
+        let drawn = objects.len();
+        self.render_stats
+            .borrow_mut()
+            .record_objects(drawn, total - drawn);
+
+        if self.should_render_progressively(drawn) {
+            let start = now_ms();
+            let draw_calls = self.render_coarse_pass(renderer, &objects);
+            {
+                let mut stats = self.render_stats.borrow_mut();
+                stats.record_render_ms(now_ms() - start);
+                stats.record_draw_calls(draw_calls);
+            }
+
+            let mut job = ProgressiveJob {
+                view: *view,
+                objects,
+                cursor: 0,
+                delta: 0.0,
+                dirty_regions: Vec::new(),
+            };
+            return (!self.render_chunk(renderer, &mut job)).then_some(job);
+        }
+
+        let start = now_ms();
+        let draw_calls = if self.batching {
+            let runs = group_runs(objects);
+            for run in runs.iter() {
+                match run {
+                    RenderRun::Single(object) => self.render_object(renderer, object),
+                    RenderRun::Batch(style, members) => draw_batch(renderer, style, members),
+                }
+            }
+            runs.len()
+        } else {
+            for object in objects.iter() {
+                self.render_object(renderer, object);
+            }
+            drawn
+        };
+
+        let mut stats = self.render_stats.borrow_mut();
+        stats.record_render_ms(now_ms() - start);
+        stats.record_draw_calls(draw_calls);
+        None
+    }
+
+    fn render_object(&self, renderer: &dyn Renderer, object: &Rc<RefCell<Box<dyn Renderable>>>) {
+        let mut steps = Vec::new();
+        self.paint_steps(object, &mut steps, true, None, &mut Vec::new());
+        for step in steps {
+            match step {
+                PaintStep::Object(object) => {
+                    renderer.save();
+                    object.borrow().render(renderer);
+                    renderer.restore();
+                }
+                PaintStep::Enter(transform, clip) => enter_container(renderer, &transform, clip),
+                PaintStep::Leave => renderer.restore(),
+            }
+        }
+    }
+
+    // 展开对象及其子对象的绘制步骤，子对象紧跟在容器之后。expand_instances 为 true 时
+    // 组件实例展开为主组件的内容，instance 为正在展开的实例，masters 为已展开的主组件，防止组件引用自身
+    fn paint_steps(
         &self,
-        renderer: &mut Box<dyn Renderer>,
-        hit_renderer: &mut Box<dyn Renderer>,
+        object: &Rc<RefCell<Box<dyn Renderable>>>,
+        steps: &mut Vec<PaintStep>,
+        expand_instances: bool,
+        instance: Option<&dyn Renderable>,
+        masters: &mut Vec<String>,
     ) {
-        let object_manager = self.object_manager.borrow();
-        for object in object_manager.get_objects() {
-            let object_borrow = object.borrow();
+        let object_ref = object.borrow();
+        let id = object_ref.id().value();
+        let overridden = instance
+            .and_then(|instance| instance.fill_override(id))
+            .and_then(|fill| with_fill(&**object_ref, fill));
+        match overridden {
+            Some(overridden) => steps.push(PaintStep::Object(Rc::new(RefCell::new(overridden)))),
+            None => steps.push(PaintStep::Object(object.clone())),
+        }
 
-            // 渲染到主画布
-            renderer.save();
-            object_borrow.render(&mut **renderer);
-            renderer.restore();
+        if let Some(master_id) = object_ref.instance_of().filter(|_| expand_instances) {
+            if !masters.iter().any(|master| master == master_id) {
+                // 把主组件的坐标系映射到实例上：实例变换 × 主组件世界变换的逆
+                let (master, master_world) = {
+                    let object_manager = self.object_manager.borrow();
+                    let master = object_manager.get(master_id);
+                    let world = master.as_ref().map(|master| {
+                        object_manager.parent_transform(master_id)
+                            * convert_1x6_to_3x3(master.borrow().calc_transform())
+                    });
+                    (master, world)
+                };
+                if let (Some(master), Some(inverse)) = (master, master_world.and_then(|world| world.try_inverse())) {
+                    let transform = convert_1x6_to_3x3(object_ref.calc_transform()) * inverse;
+                    steps.push(PaintStep::Enter(convert_3x3_to_1x6(transform), None));
+                    masters.push(master_id.to_string());
+                    self.paint_steps(&master, steps, expand_instances, Some(&**object_ref), masters);
+                    masters.pop();
+                    steps.push(PaintStep::Leave);
+                }
+            }
+        }
 
-            // 渲染到hit测试画布
+        let children = self.object_manager.borrow().children_of(id);
+        if children.is_empty() {
+            return;
+        }
+        steps.push(PaintStep::Enter(object_ref.calc_transform(), object_ref.child_clip()));
+        for child in children {
+            let child = self.object_manager.borrow().get(&child);
+            if let Some(child) = child {
+                self.paint_steps(&child, steps, expand_instances, instance, masters);
+            }
+        }
+        steps.push(PaintStep::Leave);
+    }
+
+    pub fn set_batching(&mut self, enabled: bool) {
+        self.batching = enabled;
+    }
+
+    // 如果 hit 画布已过期则按最近一帧的视图重新绘制
+    pub fn ensure_hit_canvas(&self) {
+        if self.hit_test_mode == HitTestMode::Geometry || self.pending_hit_view.get().is_none() {
+            return;
+        }
+        if let Ok(mut hit_renderer) = self.hit_renderer.try_borrow_mut() {
+            if let Some(hit_renderer) = hit_renderer.as_mut() {
+                self.render_hit_canvas(hit_renderer);
+            }
+        }
+    }
+
+    fn render_hit_canvas(&self, hit_renderer: &mut Box<dyn Renderer>) {
+        let Some(view) = self.pending_hit_view.take() else {
+            return;
+        };
+        let objects = self.visible_objects(&view);
+        let tolerance = self.world_hit_tolerance();
+
+        let start = now_ms();
+        hit_renderer.clear_all();
+        hit_renderer.save();
+        Self::apply_view(&**hit_renderer, &view);
+        let mut steps = Vec::new();
+        for object in objects.iter() {
+            // 组件实例整体拾取，不展开主组件的内容
+            self.paint_steps(object, &mut steps, false, None, &mut Vec::new());
+        }
+        for step in steps {
+            let object = match step {
+                PaintStep::Object(object) => object,
+                PaintStep::Enter(transform, clip) => {
+                    enter_container(&**hit_renderer, &transform, clip);
+                    continue;
+                }
+                PaintStep::Leave => {
+                    hit_renderer.restore();
+                    continue;
+                }
+            };
+            // 容器不响应拾取时其中的子对象仍然可以点中
+            let object_borrow = object.borrow();
+            if object_borrow.pointer_events() == PointerEvents::None {
+                continue;
+            }
             let color = object_borrow.id().color();
             let fill_color = format!("rgba({},{},{},{})", color.0, color.1, color.2, color.3);
             hit_renderer.save();
             hit_renderer.lock_color(&fill_color);
-            object_borrow.render(&mut **hit_renderer);
+            object_borrow.render_hit(&**hit_renderer, tolerance);
             hit_renderer.unlock_color();
             hit_renderer.restore();
         }
+        hit_renderer.restore();
+
+        let mut stats = self.render_stats.borrow_mut();
+        stats.record_hit_canvas_ms(now_ms() - start);
+        stats.record_draw_calls(objects.len());
     }
 
-    fn restore_renderers(
-        &self,
-        renderer: &mut Box<dyn Renderer>,
-        hit_renderer: &mut Box<dyn Renderer>,
-    ) {
-        renderer.restore();
-        hit_renderer.restore();
+    // 通过空间索引取出与可见区域相交的顶层对象，按 z 顺序排列；子对象随所在的容器绘制
+    fn visible_objects(&self, view: &HitView) -> Vec<Rc<RefCell<Box<dyn Renderable>>>> {
+        let object_manager = self.object_manager.borrow();
+        let objects = match view.visible {
+            Some(visible) => object_manager.objects_in_rect(&visible),
+            None => object_manager.get_objects(),
+        };
+        objects
+            .into_iter()
+            .filter(|object| object_manager.parent_of(object.borrow().id().value()).is_none())
+            .collect()
     }
 
     pub fn update_time(&mut self) -> f64 {
@@ -488,30 +1467,303 @@ impl SceneManager {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugOptions {
+    pub enabled: bool,
+    pub show_hit_canvas: bool,
+}
+
+impl SceneManager {
+    pub fn set_debug(&mut self, enabled: bool) {
+        self.debug.enabled = enabled;
+    }
+
+    pub fn set_debug_hit_canvas(&mut self, show: bool) {
+        self.debug.show_hit_canvas = show;
+    }
+
+    pub fn debug_options(&self) -> DebugOptions {
+        self.debug
+    }
+
+    // 收集上一帧以来发生变化的对象区域，并清除脏标记
+    fn take_dirty_regions(&self) -> Vec<BoundingBox> {
+        // 清除脏标记之前先同步空间索引
+        let object_manager = self.object_manager.borrow();
+        object_manager.refresh_spatial_index();
+        object_manager
+            .get_objects()
+            .iter()
+            .filter_map(|object| {
+                let id = object.try_borrow().ok().filter(|object| object.is_dirty())?.id().value().to_string();
+                let bounds = object_manager.world_bounds(&id);
+                object.try_borrow_mut().ok()?.set_dirty_flag(false);
+                bounds
+            })
+            .collect()
+    }
+
+    // 调试信息统一在屏幕坐标下绘制，线宽不随缩放变化
+    fn render_debug(&self, renderer: &dyn Renderer, dirty_regions: &[BoundingBox]) {
+        let view = self.view_matrix();
+        let to_screen = |(x, y): (f64, f64)| {
+            let point = view * na::Vector3::new(x, y, 1.0);
+            (point.x, point.y)
+        };
+        let trace_box = |bbox: &BoundingBox| {
+            let corners = bbox.corners().map(to_screen);
+            renderer.begin_path();
+            renderer.move_to(corners[0].0, corners[0].1);
+            for (x, y) in corners.iter().skip(1) {
+                renderer.line_to(*x, *y);
+            }
+            renderer.close_path();
+            corners
+        };
+
+        renderer.save();
+
+        // 脏区域
+        renderer.set_fill_style("rgba(255, 0, 0, 0.2)");
+        for region in dirty_regions {
+            trace_box(region);
+            renderer.fill();
+        }
+
+        // 包围盒和对象 id
+        renderer.set_line_width(1.0);
+        renderer.set_stroke_style("magenta");
+        renderer.set_fill_style("magenta");
+        renderer.set_font("10px monospace");
+        let object_manager = self.object_manager.borrow();
+        for object in object_manager.get_objects() {
+            let Ok(object) = object.try_borrow() else {
+                continue;
+            };
+            let Some(bounds) = object_manager.world_bounds(object.id().value()) else {
+                continue;
+            };
+            let corners = trace_box(&bounds);
+            renderer.stroke();
+            renderer.fill_text(object.id().value(), corners[0].0 + 2.0, corners[0].1 - 2.0);
+        }
+
+        // 旋转中心
+        let size = 8.0;
+        renderer.set_stroke_style("cyan");
+        renderer.draw_line(
+            self.center_x - size,
+            self.center_y,
+            self.center_x + size,
+            self.center_y,
+            "cyan",
+            1.0,
+        );
+        renderer.draw_line(
+            self.center_x,
+            self.center_y - size,
+            self.center_x,
+            self.center_y + size,
+            "cyan",
+            1.0,
+        );
+
+        renderer.restore();
+
+        if self.debug.show_hit_canvas {
+            self.blit_hit_canvas();
+        }
+    }
+
+    // 把 hit 画布缩小画到右下角，方便排查拾取问题
+    fn blit_hit_canvas(&self) {
+        let (Some(canvas), Some(hit_canvas), Some(width), Some(height)) =
+            (&self.canvas, &self.hit_canvas, self.width, self.height)
+        else {
+            return;
+        };
+        let Some(context) = canvas
+            .borrow()
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        else {
+            return;
+        };
+
+        let (width, height) = (width as f64, height as f64);
+        let (preview_width, preview_height) = (width / 4.0, height / 4.0);
+        let (x, y) = (width - preview_width - 8.0, height - preview_height - 8.0);
+
+        context.save();
+        context.set_fill_style(&JsValue::from_str("white"));
+        context.fill_rect(x, y, preview_width, preview_height);
+        let _ = context.draw_image_with_offscreen_canvas_and_dw_and_dh(
+            &hit_canvas.borrow(),
+            x,
+            y,
+            preview_width,
+            preview_height,
+        );
+        context.set_stroke_style(&JsValue::from_str("black"));
+        context.stroke_rect(x, y, preview_width, preview_height);
+        context.restore();
+    }
+}
+
+// 绘制 hit 画布所需的视图快照；事件回调里的 SceneManager 克隆不持有最新的视图状态
+#[derive(Debug, Clone, Copy)]
+struct HitView {
+    transform: na::Matrix1x6<f64>,
+    visible: Option<BoundingBox>,
+}
+
+// 绘制对象树的步骤；进入容器时应用容器变换和裁剪，离开时恢复
+enum PaintStep {
+    Object(Rc<RefCell<Box<dyn Renderable>>>),
+    Enter(na::Matrix1x6<f64>, Option<BoundingBox>),
+    Leave,
+}
+
+// 套用实例填充覆盖后的副本，只用于本次绘制
+fn with_fill(object: &dyn Renderable, fill: &str) -> Option<Box<dyn Renderable>> {
+    let mut value = object.to_value();
+    value.as_object_mut()?.insert("fill".to_string(), Value::String(fill.to_string()));
+    create_element(object.get_type(), &value).ok()
+}
+
+fn enter_container(renderer: &dyn Renderer, transform: &na::Matrix1x6<f64>, clip: Option<BoundingBox>) {
+    renderer.save();
+    if let [a, b, c, d, e, f] = transform.as_slice() {
+        renderer.transform(*a, *b, *c, *d, *e, *f);
+    }
+    if let Some(clip) = clip {
+        let [first, rest @ ..] = clip.corners();
+        renderer.begin_path();
+        renderer.move_to(first.0, first.1);
+        rest.iter().for_each(|(x, y)| renderer.line_to(*x, *y));
+        renderer.close_path();
+        renderer.clip();
+    }
+}
+
+pub type FrameHookId = u64;
+pub type FrameHook = Box<dyn FnMut(&dyn Renderer, f64)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameHookStage {
+    // 画布已清空、场景变换尚未应用，适合绘制背景
+    BeforeRender,
+    // 场景已绘制并恢复到屏幕坐标，适合绘制 HUD
+    AfterRender,
+}
+
+#[derive(Default)]
+struct FrameHooks {
+    next_id: FrameHookId,
+    before_render: Vec<(FrameHookId, FrameHook)>,
+    after_render: Vec<(FrameHookId, FrameHook)>,
+}
+
+impl Debug for FrameHooks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FrameHooks {{ before_render: {}, after_render: {} }}",
+            self.before_render.len(),
+            self.after_render.len()
+        )
+    }
+}
+
+impl FrameHooks {
+    fn stage_mut(&mut self, stage: FrameHookStage) -> &mut Vec<(FrameHookId, FrameHook)> {
+        match stage {
+            FrameHookStage::BeforeRender => &mut self.before_render,
+            FrameHookStage::AfterRender => &mut self.after_render,
+        }
+    }
+}
+
+impl SceneManager {
+    pub fn add_frame_hook(
+        &self,
+        stage: FrameHookStage,
+        hook: impl FnMut(&dyn Renderer, f64) + 'static,
+    ) -> FrameHookId {
+        let mut hooks = self.frame_hooks.borrow_mut();
+        hooks.next_id += 1;
+        let id = hooks.next_id;
+        hooks.stage_mut(stage).push((id, Box::new(hook)));
+        id
+    }
+
+    pub fn remove_frame_hook(&self, id: FrameHookId) -> bool {
+        let mut hooks = self.frame_hooks.borrow_mut();
+        let before = hooks.before_render.len() + hooks.after_render.len();
+        hooks.before_render.retain(|(hook_id, _)| *hook_id != id);
+        hooks.after_render.retain(|(hook_id, _)| *hook_id != id);
+        before != hooks.before_render.len() + hooks.after_render.len()
+    }
+
+    fn run_frame_hooks(&self, stage: FrameHookStage, renderer: &dyn Renderer, delta: f64) {
+        // 先取出再执行，允许钩子在回调中注册/移除其他钩子
+        let mut running = std::mem::take(self.frame_hooks.borrow_mut().stage_mut(stage));
+        if running.is_empty() {
+            return;
+        }
+        for (_, hook) in running.iter_mut() {
+            renderer.save();
+            hook(renderer, delta);
+            renderer.restore();
+        }
+        let mut hooks = self.frame_hooks.borrow_mut();
+        let added = std::mem::take(hooks.stage_mut(stage));
+        running.extend(added);
+        *hooks.stage_mut(stage) = running;
+    }
+}
+
+struct ResizeListener {
+    observer: Option<ResizeObserver>,
+    closure: Closure<dyn FnMut()>,
+}
+
+impl Debug for ResizeListener {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ResizeListener {{ observer: {} }}", self.observer.is_some())
+    }
+}
+
+type MouseHandler = Rc<RefCell<dyn Fn(&MouseEvent)>>;
+
 #[derive(Default)]
 struct EventHandlers {
-    on_mouse_move: Option<Rc<RefCell<dyn Fn(&MouseEvent)>>>,
-    on_mouse_down: Option<Rc<RefCell<dyn Fn(&MouseEvent)>>>,
-    on_mouse_up: Option<Rc<RefCell<dyn Fn(&MouseEvent)>>>,
-    on_mouse_leave: Option<Rc<RefCell<dyn Fn(&MouseEvent)>>>,
+    on_mouse_move: Option<MouseHandler>,
+    on_mouse_down: Option<MouseHandler>,
+    on_mouse_up: Option<MouseHandler>,
+    on_mouse_leave: Option<MouseHandler>,
+    on_double_click: Option<MouseHandler>,
+    on_context_menu: Option<MouseHandler>,
 }
 
 impl Debug for EventHandlers {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "EventHandlers {{ on_mouse_move, on_mouse_down, on_mouse_up, on_mouse_leave }}"
+            "EventHandlers {{ on_mouse_move, on_mouse_down, on_mouse_up, on_mouse_leave, on_double_click, on_context_menu }}"
         )
     }
 }
 
 impl SceneManager {
-    pub fn init_event(&mut self) -> Result<(), JsValue> {
+    pub fn init_event(&mut self) -> EditingResult<()> {
         let event_handlers = self.event_handlers.clone();
         let canvas = self
             .canvas
             .as_ref()
-            .ok_or_else(|| JsValue::from_str("Canvas not initialized"))?;
+            .ok_or(EditingError::NotInitialized("Canvas"))?;
 
         self.create_and_add_event_listeners(canvas.clone(), event_handlers)?;
         self.set_default_event_handlers();
@@ -523,8 +1775,17 @@ impl SceneManager {
         &mut self,
         canvas: Rc<RefCell<HtmlCanvasElement>>,
         event_handlers: Rc<RefCell<EventHandlers>>,
-    ) -> Result<(), JsValue> {
-        let event_types = ["mousemove", "mousedown", "mouseup", "mouseleave"];
+    ) -> EditingResult<()> {
+        // 指针事件同时覆盖鼠标、触摸和手写笔，事件对象是 MouseEvent 的子类
+        let event_types = [
+            "pointermove",
+            "pointerdown",
+            "pointerup",
+            "pointerleave",
+            "pointercancel",
+            "dblclick",
+            "contextmenu",
+        ];
 
         for event_type in event_types.iter() {
             let closure = self.create_event_closure(event_handlers.clone(), event_type);
@@ -547,10 +1808,12 @@ impl SceneManager {
         Closure::wrap(Box::new(move |event: MouseEvent| {
             let handlers = event_handlers.borrow();
             let handler = match event_type {
-                "mousemove" => &handlers.on_mouse_move,
-                "mousedown" => &handlers.on_mouse_down,
-                "mouseup" => &handlers.on_mouse_up,
-                "mouseleave" => &handlers.on_mouse_leave,
+                "pointermove" => &handlers.on_mouse_move,
+                "pointerdown" => &handlers.on_mouse_down,
+                "pointerup" => &handlers.on_mouse_up,
+                "pointerleave" | "pointercancel" => &handlers.on_mouse_leave,
+                "dblclick" => &handlers.on_double_click,
+                "contextmenu" => &handlers.on_context_menu,
                 _ => return,
             };
             if let Some(handler) = handler {
@@ -567,19 +1830,19 @@ impl SceneManager {
         let self_clone_down = self.clone();
         self.set_on_mouse_down(move |event| {
             if let Some(obj) = self_clone_down.get_trigger_object(&event) {
-                console::log_1(&format!("mousedown: {:#?}", obj).into());
+                log_debug!("mousedown: {:#?}", obj);
             }
         });
         let self_clone_up = self.clone();
         self.set_on_mouse_up(move |event| {
             if let Some(obj) = self_clone_up.get_trigger_object(&event) {
-                console::log_1(&format!("mouseup: {:#?}", obj).into());
+                log_debug!("mouseup: {:#?}", obj);
             }
         });
         let self_clone_leave = self.clone();
         self.set_on_mouse_leave(move |event| {
             if let Some(obj) = self_clone_leave.get_trigger_object(&event) {
-                console::log_1(&format!("mouseleave: {:#?}", obj).into());
+                log_debug!("mouseleave: {:#?}", obj);
             }
         });
     }
@@ -600,50 +1863,193 @@ impl SceneManager {
         self.event_handlers.borrow_mut().on_mouse_leave = Some(Rc::new(RefCell::new(handler)));
     }
 
+    pub fn set_on_double_click(&mut self, handler: impl Fn(&MouseEvent) + 'static) {
+        self.event_handlers.borrow_mut().on_double_click = Some(Rc::new(RefCell::new(handler)));
+    }
+
+    pub fn set_on_context_menu(&mut self, handler: impl Fn(&MouseEvent) + 'static) {
+        self.event_handlers.borrow_mut().on_context_menu = Some(Rc::new(RefCell::new(handler)));
+    }
+
     // Add a cleanup method
     pub fn cleanup(&mut self) {
+        self.unobserve_resize();
         if let Some(canvas) = &self.canvas {
             for (event_type, listener) in self.event_listeners.borrow_mut().drain() {
                 match canvas.borrow_mut().remove_event_listener_with_callback(
                     &event_type,
                     listener.as_ref().unchecked_ref(),
                 ) {
-                    Ok(_) => console::log_1(
-                        &format!("Successfully removed {} event listener", event_type).into(),
-                    ),
-                    Err(e) => console::error_1(
-                        &format!("Failed to remove {} event listener: {:?}", event_type, e).into(),
-                    ),
+                    Ok(_) => log_debug!("Successfully removed {} event listener", event_type),
+                    Err(e) => log_error!("Failed to remove {} event listener: {:?}", event_type, e),
                 }
             }
         } else {
-            console::warn_1(&"Canvas not found during cleanup".into());
+            log_warn!("Canvas not found during cleanup");
         }
     }
 
-    fn get_trigger_object(&self, event: &MouseEvent) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
-        let canvas = self.canvas.as_ref()?;
-        let rect = canvas.borrow().get_bounding_client_rect();
-        let dpr = self.dpr.unwrap_or(1.0);
+    // 释放 DOM 监听、渲染器和离屏画布
+    pub fn destroy(&mut self) {
+        self.cleanup();
+        *self.event_handlers.borrow_mut() = EventHandlers::default();
+        *self.frame_hooks.borrow_mut() = FrameHooks::default();
+        self.presence.borrow_mut().clear();
+        self.path_handles.borrow_mut().take();
+        *self.measure.borrow_mut() = MeasureOverlay::default();
+        self.progressive_job.borrow_mut().take();
+        *self.renderer.borrow_mut() = None;
+        *self.hit_renderer.borrow_mut() = None;
+        if let Some(worker) = self.render_worker.take() {
+            worker.terminate();
+        }
+        if let Some(hit_canvas) = self.hit_canvas.take() {
+            // 其他克隆可能仍持有引用，先把尺寸置零释放显存
+            hit_canvas.borrow_mut().set_width(0);
+            hit_canvas.borrow_mut().set_height(0);
+        }
+        self.canvas = None;
+        self.detach();
+    }
+
+    // 监听画布尺寸变化；不支持 ResizeObserver 时退化为 window resize
+    pub fn observe_resize(&self, callback: impl FnMut() + 'static) -> EditingResult<()> {
+        self.unobserve_resize();
+        let canvas = self
+            .canvas
+            .as_ref()
+            .ok_or(EditingError::NotInitialized("Canvas"))?;
+        let closure = Closure::wrap(Box::new(callback) as Box<dyn FnMut()>);
+
+        let observer = match ResizeObserver::new(closure.as_ref().unchecked_ref()) {
+            Ok(observer) => {
+                observer.observe(&canvas.borrow());
+                Some(observer)
+            }
+            Err(_) => {
+                log_warn!("ResizeObserver unavailable, falling back to window resize");
+                window()
+                    .ok_or(EditingError::WindowNotFound)?
+                    .add_event_listener_with_callback("resize", closure.as_ref().unchecked_ref())?;
+                None
+            }
+        };
+
+        *self.resize_listener.borrow_mut() = Some(ResizeListener { observer, closure });
+        Ok(())
+    }
 
-        let canvas_x = (event.client_x() as f64 - rect.left()) * dpr;
-        let canvas_y = (event.client_y() as f64 - rect.top()) * dpr;
+    pub fn unobserve_resize(&self) {
+        let Some(listener) = self.resize_listener.borrow_mut().take() else {
+            return;
+        };
+        match listener.observer {
+            Some(observer) => observer.disconnect(),
+            None => {
+                if let Some(window) = window() {
+                    let _ = window.remove_event_listener_with_callback(
+                        "resize",
+                        listener.closure.as_ref().unchecked_ref(),
+                    );
+                }
+            }
+        }
+    }
 
-        let transform = convert_1x6_to_3x3(self.calc_transform());
-        let inverse_transform = transform.try_inverse()?;
+    // 画布 CSS 尺寸变化时同步后备缓冲和 hit 画布，并保持视口中心对应的世界坐标不变
+    pub fn handle_resize(&mut self) -> EditingResult<Option<(u32, u32)>> {
+        let old_data = self.get_dirty_data();
+        let canvas = self
+            .canvas
+            .as_ref()
+            .ok_or(EditingError::NotInitialized("Canvas"))?;
+        let (width, height) = get_canvas_css_size(&canvas.borrow())?;
+        let (old_width, old_height) = (self.width.unwrap_or(width), self.height.unwrap_or(height));
+        if width == 0 || height == 0 || (width, height) == (old_width, old_height) {
+            return Ok(None);
+        }
+
+        // 屏幕中心的位移换算回场景偏移：delta = (zoom * R)^-1 * screen_delta
+        let dx = (width as f64 - old_width as f64) / 2.0;
+        let dy = (height as f64 - old_height as f64) / 2.0;
+        let (sin_r, cos_r) = self.rotation.sin_cos();
+        self.offset_x += (cos_r * dx + sin_r * dy) / self.zoom;
+        self.offset_y += (-sin_r * dx + cos_r * dy) / self.zoom;
+        self.transform_dirty.set(true);
 
-        let original_point = inverse_transform * na::Vector3::new(canvas_x, canvas_y, 1.0);
-        let (original_x, original_y) = (original_point[0] as f64, original_point[1] as f64);
+        self.width = Some(width);
+        self.height = Some(height);
+        self.set_pixel_ratio(self.dpr.unwrap_or(1.0))?;
+        self.notify_scene_change(old_data);
+        Ok(Some((width, height)))
+    }
 
+    pub fn get_trigger_object(&self, event: &MouseEvent) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        let canvas = self.canvas.as_ref()?;
+        let rect = canvas.borrow().get_bounding_client_rect();
+        if self.hit_test_mode == HitTestMode::Geometry {
+            let (x, y) = self.client_to_world(event.client_x() as f64, event.client_y() as f64)?;
+            return self.get_object_by_geometry(x, y);
+        }
+        let (hit_x, hit_y) = hit_pixel(
+            event.client_x() as f64 - rect.left(),
+            event.client_y() as f64 - rect.top(),
+            self.hit_ratio(),
+        );
+
+        self.ensure_hit_canvas();
         let binding = self.hit_renderer.borrow();
         let hit_renderer = binding.as_ref()?;
-        let pixel_data = hit_renderer.get_image_data(original_x, original_y, 1.0, 1.0);
+        let pixel_data = match hit_renderer.get_image_data(hit_x, hit_y, 1.0, 1.0) {
+            Ok(pixel_data) => pixel_data,
+            Err(e) => {
+                log_warn!("Failed to read hit canvas: {}", e);
+                return None;
+            }
+        };
 
         let color_id = pixel_data.0.data();
-        let object_id =
-            ObjectId::get_id_by_color([color_id[0], color_id[1], color_id[2], color_id[3]])?;
+        self.object_manager
+            .borrow()
+            .get_by_color([color_id[0], color_id[1], color_id[2], color_id[3]])
+    }
+}
 
-        self.object_manager.borrow().get(&object_id)
+impl SceneManager {
+    // 按对象的几何形状取最上层的对象，x、y 为世界坐标
+    fn get_object_by_geometry(&self, x: f64, y: f64) -> Option<Rc<RefCell<Box<dyn Renderable>>>> {
+        self.hit_test_all(x, y).into_iter().next()
+    }
+
+    // 世界坐标 (x, y) 处可拾取的所有对象，从最上层到最下层，用于在重叠的对象之间循环选择；
+    // 与拾取模式无关，总是按几何形状判断，先用容差范围查询空间索引得到候选
+    pub fn hit_test_all(&self, x: f64, y: f64) -> Vec<Rc<RefCell<Box<dyn Renderable>>>> {
+        let tolerance = self.world_hit_tolerance();
+        let area = BoundingBox::new(x - tolerance, y - tolerance, 2.0 * tolerance, 2.0 * tolerance);
+        let object_manager = self.object_manager.borrow();
+        let mut ids = object_manager.query_rect(&area);
+        if ids.len() > 1 {
+            let order = object_manager.paint_order();
+            ids.sort_by_key(|id| order.get(id).copied());
+        }
+        ids.iter()
+            .rev()
+            .filter(|id| !object_manager.is_clipped(id, x, y))
+            .filter_map(|id| {
+                let object = object_manager.get(id)?;
+                // 容器中的子对象在容器坐标系下判断
+                let transform = object_manager.parent_transform(id);
+                let inverse = transform.try_inverse()?;
+                let point = inverse * na::Vector3::new(x, y, 1.0);
+                let local_tolerance = tolerance / transform.determinant().abs().sqrt().max(f64::EPSILON);
+                let hit = {
+                    let object = object.borrow();
+                    object.pointer_events() != PointerEvents::None
+                        && object.hit_test(point.x, point.y, local_tolerance)
+                };
+                hit.then_some(object)
+            })
+            .collect()
     }
 }
 
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bounding_box::BoundingBox;
+use crate::renderer::{CachedPath, Renderer};
+
+// 每个分块的边长（格数），平移时按分块复用已构建的路径
+const CHUNK_CELLS: i64 = 16;
+// 格线在屏幕上的最小间距（CSS 像素），缩小时按 major_every 倍合并
+const MIN_SCREEN_SPACING: f64 = 8.0;
+// 超过后清空缓存，避免长时间漫游后无限增长
+const MAX_CACHED_CHUNKS: usize = 512;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridKind {
+    #[default]
+    None,
+    Lines,
+    Dots,
+}
+
+// 背景网格，按可见区域程序化生成，不限制画布范围
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GridOptions {
+    pub kind: GridKind,
+    // 相邻格线的距离（世界单位）
+    pub spacing: f64,
+    pub color: String,
+    // 每隔几格画一条主线，小于 2 时没有主线
+    pub major_every: u32,
+    pub major_color: String,
+    // 点的直径（屏幕像素）
+    pub dot_size: f64,
+}
+
+impl Default for GridOptions {
+    fn default() -> Self {
+        Self {
+            kind: GridKind::None,
+            spacing: 20.0,
+            color: "#e5e5e5".to_string(),
+            major_every: 5,
+            major_color: "#cccccc".to_string(),
+            dot_size: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ChunkKey {
+    spacing: u64,
+    // 点的半径（世界单位），随缩放变化；格线为 0
+    radius: u64,
+    x: i64,
+    y: i64,
+}
+
+#[derive(Debug)]
+struct Chunk {
+    minor: CachedPath,
+    major: CachedPath,
+}
+
+#[derive(Debug, Default)]
+pub struct BackgroundGrid {
+    options: GridOptions,
+    chunks: HashMap<ChunkKey, Chunk>,
+}
+
+impl BackgroundGrid {
+    pub fn options(&self) -> &GridOptions {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: GridOptions) {
+        self.options = options;
+        self.chunks.clear();
+    }
+
+    // 在已应用视图变换的渲染器上绘制 visible（世界坐标）内的网格
+    pub fn draw(&mut self, renderer: &dyn Renderer, visible: &BoundingBox, zoom: f64) {
+        let spacing = self.options.spacing;
+        if self.options.kind == GridKind::None || !(spacing > 0.0 && spacing.is_finite()) || zoom <= 0.0 {
+            return;
+        }
+        let Some(spacing) = self.level_spacing(zoom) else {
+            return;
+        };
+        let radius = match self.options.kind {
+            GridKind::Dots => self.options.dot_size.max(0.0) / 2.0 / zoom,
+            _ => 0.0,
+        };
+
+        let chunk_size = spacing * CHUNK_CELLS as f64;
+        let first = ((visible.x / chunk_size).floor() as i64, (visible.y / chunk_size).floor() as i64);
+        let last = (
+            ((visible.x + visible.width) / chunk_size).floor() as i64,
+            ((visible.y + visible.height) / chunk_size).floor() as i64,
+        );
+
+        if self.chunks.len() > MAX_CACHED_CHUNKS {
+            self.chunks.clear();
+        }
+        let mut keys = Vec::new();
+        for y in first.1..=last.1 {
+            for x in first.0..=last.0 {
+                let key = ChunkKey {
+                    spacing: spacing.to_bits(),
+                    radius: radius.to_bits(),
+                    x,
+                    y,
+                };
+                if !self.chunks.contains_key(&key) {
+                    let chunk = self.build_chunk(spacing, radius, x, y);
+                    self.chunks.insert(key, chunk);
+                }
+                keys.push(key);
+            }
+        }
+
+        let (options, chunks) = (&self.options, &self.chunks);
+        renderer.save();
+        match options.kind {
+            GridKind::Dots => {
+                renderer.set_fill_style(&options.color);
+                keys.iter().for_each(|key| renderer.fill_path(&chunks[key].minor));
+                renderer.set_fill_style(&options.major_color);
+                keys.iter().for_each(|key| renderer.fill_path(&chunks[key].major));
+            }
+            _ => {
+                renderer.set_line_width(1.0 / zoom);
+                renderer.set_stroke_style(&options.color);
+                keys.iter().for_each(|key| renderer.stroke_path(&chunks[key].minor));
+                renderer.set_stroke_style(&options.major_color);
+                keys.iter().for_each(|key| renderer.stroke_path(&chunks[key].major));
+            }
+        }
+        renderer.restore();
+    }
+
+    // 当前缩放下实际使用的格距，屏幕上过密时逐级放大
+    fn level_spacing(&self, zoom: f64) -> Option<f64> {
+        let factor = self.options.major_every.max(2) as f64;
+        let mut spacing = self.options.spacing;
+        while spacing * zoom < MIN_SCREEN_SPACING {
+            spacing *= factor;
+            if !spacing.is_finite() {
+                return None;
+            }
+        }
+        Some(spacing)
+    }
+
+    fn build_chunk(&self, spacing: f64, radius: f64, chunk_x: i64, chunk_y: i64) -> Chunk {
+        let major_every = self.options.major_every as i64;
+        let is_major = |index: i64| major_every >= 2 && index.rem_euclid(major_every) == 0;
+        let columns = chunk_x * CHUNK_CELLS..(chunk_x + 1) * CHUNK_CELLS;
+        let rows = chunk_y * CHUNK_CELLS..(chunk_y + 1) * CHUNK_CELLS;
+        let (left, top) = (columns.start as f64 * spacing, rows.start as f64 * spacing);
+        let (right, bottom) = (columns.end as f64 * spacing, rows.end as f64 * spacing);
+
+        let mut minor = CachedPath::new();
+        let mut major = CachedPath::new();
+        if self.options.kind == GridKind::Dots {
+            for row in rows {
+                for column in columns.clone() {
+                    let (x, y) = (column as f64 * spacing, row as f64 * spacing);
+                    let path = if is_major(row) && is_major(column) { &mut major } else { &mut minor };
+                    path.move_to(x + radius, y);
+                    path.arc(x, y, radius, 0.0, TAU);
+                }
+            }
+        } else {
+            // 每个分块只画起始边上的线，相邻分块之间不会重复
+            for column in columns {
+                let x = column as f64 * spacing;
+                let path = if is_major(column) { &mut major } else { &mut minor };
+                path.move_to(x, top);
+                path.line_to(x, bottom);
+            }
+            for row in rows {
+                let y = row as f64 * spacing;
+                let path = if is_major(row) { &mut major } else { &mut minor };
+                path.move_to(left, y);
+                path.line_to(right, y);
+            }
+        }
+        Chunk { minor, major }
+    }
+}
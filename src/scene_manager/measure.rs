@@ -0,0 +1,160 @@
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use super::DocumentUnits;
+use crate::bounding_box::BoundingBox;
+use crate::renderer::{Renderer, TextAlign, TextBaseline};
+
+const LINE_COLOR: &str = "#f76808";
+const LABEL_BACKGROUND: &str = "rgba(0, 0, 0, 0.75)";
+const LABEL_FONT: &str = "11px sans-serif";
+const LABEL_HEIGHT: f64 = 18.0;
+const LABEL_PADDING: f64 = 5.0;
+// 标签与测量线或包围盒的距离（屏幕像素）
+const LABEL_OFFSET: f64 = 14.0;
+const TICK_SIZE: f64 = 5.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MeasureOptions {
+    // 小数位数，长度按文档单位显示
+    pub precision: usize,
+    // 选中对象的尺寸或旋转变化时显示宽高和角度
+    pub dimensions: bool,
+}
+
+impl Default for MeasureOptions {
+    fn default() -> Self {
+        Self {
+            precision: 1,
+            dimensions: true,
+        }
+    }
+}
+
+impl MeasureOptions {
+    fn format_angle(&self, degrees: f64) -> String {
+        format!("{:.*}°", self.precision, degrees)
+    }
+}
+
+// 测量工具拉出的线段，世界坐标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ruler {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+}
+
+impl Ruler {
+    pub fn distance(&self) -> f64 {
+        (self.to.0 - self.from.0).hypot(self.to.1 - self.from.1)
+    }
+
+    // 与对象旋转相同，y 轴向下时顺时针为正，范围 (-180, 180]
+    pub fn angle(&self) -> f64 {
+        (self.to.1 - self.from.1).atan2(self.to.0 - self.from.0).to_degrees()
+    }
+}
+
+// 选中对象的尺寸标签，width/height 为未旋转时的大小（世界单位），rotation 为角度
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionLabel {
+    pub bounds: BoundingBox,
+    pub width: f64,
+    pub height: f64,
+    pub rotation: f64,
+}
+
+// 测量线和尺寸标签，画在场景之上；标签在屏幕坐标下绘制，大小不随缩放变化
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeasureOverlay {
+    pub options: MeasureOptions,
+    pub ruler: Option<Ruler>,
+    pub dimensions: Vec<DimensionLabel>,
+}
+
+impl MeasureOverlay {
+    pub fn is_empty(&self) -> bool {
+        self.ruler.is_none() && self.dimensions.is_empty()
+    }
+
+    pub fn draw(&self, renderer: &dyn Renderer, view: &na::Matrix3<f64>, units: &DocumentUnits) {
+        let to_screen = |(x, y): (f64, f64)| {
+            let point = view * na::Vector3::new(x, y, 1.0);
+            (point.x, point.y)
+        };
+
+        renderer.save();
+        renderer.set_font(LABEL_FONT);
+        renderer.set_text_align(TextAlign::Center);
+        renderer.set_text_baseline(TextBaseline::Middle);
+        if let Some(ruler) = &self.ruler {
+            self.draw_ruler(renderer, to_screen(ruler.from), to_screen(ruler.to), ruler, units);
+        }
+        for label in &self.dimensions {
+            let corners = label.bounds.corners().map(to_screen);
+            let bottom = corners.iter().map(|(_, y)| *y).fold(f64::MIN, f64::max);
+            let center = corners.iter().map(|(x, _)| *x).sum::<f64>() / corners.len() as f64;
+            let precision = self.options.precision;
+            let mut text = format!(
+                "{} × {}",
+                units.format_length(label.width, precision),
+                units.format_length(label.height, precision)
+            );
+            if label.rotation.abs() > f64::EPSILON {
+                text = format!("{}  {}", text, self.options.format_angle(label.rotation));
+            }
+            draw_label(renderer, &text, center, bottom + LABEL_OFFSET);
+        }
+        renderer.restore();
+    }
+
+    fn draw_ruler(
+        &self,
+        renderer: &dyn Renderer,
+        from: (f64, f64),
+        to: (f64, f64),
+        ruler: &Ruler,
+        units: &DocumentUnits,
+    ) {
+        let length = (to.0 - from.0).hypot(to.1 - from.1);
+        // 垂直于测量线的单位向量，用于端点刻度和标签位置
+        let normal = if length > f64::EPSILON {
+            (-(to.1 - from.1) / length, (to.0 - from.0) / length)
+        } else {
+            (0.0, -1.0)
+        };
+        renderer.set_stroke_style(LINE_COLOR);
+        renderer.set_line_width(1.0);
+        renderer.begin_path();
+        renderer.move_to(from.0, from.1);
+        renderer.line_to(to.0, to.1);
+        for (x, y) in [from, to] {
+            renderer.move_to(x - normal.0 * TICK_SIZE, y - normal.1 * TICK_SIZE);
+            renderer.line_to(x + normal.0 * TICK_SIZE, y + normal.1 * TICK_SIZE);
+        }
+        renderer.stroke();
+
+        // 标签放在测量线上方
+        let side = if normal.1 > 0.0 { -1.0 } else { 1.0 };
+        let text = format!(
+            "{}  {}",
+            units.format_length(ruler.distance(), self.options.precision),
+            self.options.format_angle(ruler.angle())
+        );
+        draw_label(
+            renderer,
+            &text,
+            (from.0 + to.0) / 2.0 + normal.0 * LABEL_OFFSET * side,
+            (from.1 + to.1) / 2.0 + normal.1 * LABEL_OFFSET * side,
+        );
+    }
+}
+
+// 以 (x, y) 为中心绘制带背景的文字
+fn draw_label(renderer: &dyn Renderer, text: &str, x: f64, y: f64) {
+    let width = renderer.measure_text(text) + LABEL_PADDING * 2.0;
+    renderer.draw_rectangle(x - width / 2.0, y - LABEL_HEIGHT / 2.0, width, LABEL_HEIGHT, LABEL_BACKGROUND);
+    renderer.set_fill_style("white");
+    renderer.fill_text(text, x, y);
+}
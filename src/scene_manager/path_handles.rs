@@ -0,0 +1,96 @@
+use std::f64::consts::TAU;
+
+use nalgebra as na;
+
+use crate::renderer::Renderer;
+
+// 锚点和控制点的大小（屏幕像素），不随缩放变化
+const ANCHOR_SIZE: f64 = 8.0;
+const CONTROL_RADIUS: f64 = 3.5;
+const HANDLE_COLOR: &str = "#1e88e5";
+const HANDLE_FILL: &str = "#ffffff";
+
+// 路径编辑时的一个锚点及其两侧控制点，世界坐标；端点外侧没有控制点
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnchorHandle {
+    pub point: (f64, f64),
+    pub control_in: Option<(f64, f64)>,
+    pub control_out: Option<(f64, f64)>,
+    pub corner: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleHit {
+    Anchor(usize),
+    ControlIn(usize),
+    ControlOut(usize),
+}
+
+// 路径编辑模式的锚点覆盖层；只显示选中锚点的控制点
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathHandles {
+    pub anchors: Vec<AnchorHandle>,
+    pub selected: Option<usize>,
+}
+
+impl PathHandles {
+    fn selected_anchor(&self) -> Option<(usize, &AnchorHandle)> {
+        let index = self.selected?;
+        Some((index, self.anchors.get(index)?))
+    }
+
+    // 世界坐标 point 附近 radius 内的锚点或控制点，控制点优先
+    pub fn hit(&self, point: (f64, f64), radius: f64) -> Option<HandleHit> {
+        let near = |other: (f64, f64)| (other.0 - point.0).hypot(other.1 - point.1) <= radius;
+        if let Some((index, anchor)) = self.selected_anchor() {
+            if anchor.control_out.is_some_and(near) {
+                return Some(HandleHit::ControlOut(index));
+            }
+            if anchor.control_in.is_some_and(near) {
+                return Some(HandleHit::ControlIn(index));
+            }
+        }
+        self.anchors
+            .iter()
+            .position(|anchor| near(anchor.point))
+            .map(HandleHit::Anchor)
+    }
+
+    pub fn draw(&self, renderer: &dyn Renderer, view: &na::Matrix3<f64>) {
+        let to_screen = |(x, y): (f64, f64)| {
+            let point = view * na::Vector3::new(x, y, 1.0);
+            (point.x, point.y)
+        };
+
+        renderer.save();
+        renderer.set_line_width(1.0);
+        renderer.set_stroke_style(HANDLE_COLOR);
+        if let Some((_, anchor)) = self.selected_anchor() {
+            let (x, y) = to_screen(anchor.point);
+            for control in [anchor.control_in, anchor.control_out].into_iter().flatten() {
+                let (cx, cy) = to_screen(control);
+                renderer.begin_path();
+                renderer.move_to(x, y);
+                renderer.line_to(cx, cy);
+                renderer.stroke();
+                renderer.draw_circle(cx, cy, CONTROL_RADIUS, HANDLE_COLOR);
+            }
+        }
+        // 尖角画成方块，平滑点画成圆
+        let half = ANCHOR_SIZE / 2.0;
+        for (index, anchor) in self.anchors.iter().enumerate() {
+            let (x, y) = to_screen(anchor.point);
+            let fill = if self.selected == Some(index) { HANDLE_COLOR } else { HANDLE_FILL };
+            if anchor.corner {
+                renderer.draw_rectangle(x - half, y - half, ANCHOR_SIZE, ANCHOR_SIZE, fill);
+                renderer.stroke_rect(x - half, y - half, ANCHOR_SIZE, ANCHOR_SIZE);
+            } else {
+                renderer.draw_circle(x, y, half, fill);
+                renderer.begin_path();
+                renderer.arc(x, y, half, 0.0, TAU);
+                renderer.stroke();
+            }
+        }
+        renderer.restore();
+    }
+}
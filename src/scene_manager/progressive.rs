@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{HitView, SceneManager};
+use crate::batch::BatchPass;
+use crate::bounding_box::BoundingBox;
+use crate::element::Renderable;
+use crate::helper::now_ms;
+use crate::renderer::Renderer;
+
+// { enabled, threshold, frameBudgetMs }，省略的字段使用默认值
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProgressiveOptions {
+    pub enabled: bool,
+    // 可见对象数超过该值时分帧绘制
+    pub threshold: usize,
+    // 每帧用于精细绘制的时间预算（毫秒）
+    pub frame_budget_ms: f64,
+}
+
+impl Default for ProgressiveOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 5000,
+            frame_budget_ms: 8.0,
+        }
+    }
+}
+
+// 一帧没画完的剩余工作
+pub(super) struct ProgressiveJob {
+    pub(super) view: HitView,
+    pub(super) objects: Vec<Rc<RefCell<Box<dyn Renderable>>>>,
+    pub(super) cursor: usize,
+    pub(super) delta: f64,
+    pub(super) dirty_regions: Vec<BoundingBox>,
+}
+
+impl std::fmt::Debug for ProgressiveJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ProgressiveJob {{ cursor: {}, total: {} }}",
+            self.cursor,
+            self.objects.len()
+        )
+    }
+}
+
+impl SceneManager {
+    pub fn set_progressive_options(&mut self, options: ProgressiveOptions) {
+        self.progressive_options = ProgressiveOptions {
+            frame_budget_ms: options.frame_budget_ms.max(1.0),
+            ..options
+        };
+    }
+
+    pub fn progressive_options(&self) -> ProgressiveOptions {
+        self.progressive_options
+    }
+
+    pub fn is_refining(&self) -> bool {
+        self.progressive_job.borrow().is_some()
+    }
+
+    pub(super) fn should_render_progressively(&self, object_count: usize) -> bool {
+        self.progressive_options.enabled && object_count > self.progressive_options.threshold
+    }
+
+    // 粗略绘制：只按填充色合并绘制可批处理对象的外形，不透明对象之后会被精细绘制完全覆盖
+    pub(super) fn render_coarse_pass(
+        &self,
+        renderer: &dyn Renderer,
+        objects: &[Rc<RefCell<Box<dyn Renderable>>>],
+    ) -> usize {
+        let mut by_fill: HashMap<String, Vec<_>> = HashMap::new();
+        for object in objects {
+            if let Some(style) = object.borrow().batch_style() {
+                if style.opacity >= 1.0 {
+                    by_fill.entry(style.fill).or_default().push(object);
+                }
+            }
+        }
+
+        for (fill, members) in by_fill.iter() {
+            renderer.begin_path();
+            for object in members {
+                object.borrow().append_batch_path(renderer, BatchPass::Fill);
+            }
+            renderer.set_fill_style(fill);
+            renderer.fill();
+        }
+        by_fill.len()
+    }
+
+    // 在时间预算内继续精细绘制，返回是否已全部完成
+    pub(super) fn render_chunk(&self, renderer: &dyn Renderer, job: &mut ProgressiveJob) -> bool {
+        let start = now_ms();
+        let begin = job.cursor;
+        while job.cursor < job.objects.len() {
+            self.render_object(renderer, &job.objects[job.cursor]);
+            job.cursor += 1;
+            if now_ms() - start >= self.progressive_options.frame_budget_ms {
+                break;
+            }
+        }
+
+        let mut stats = self.render_stats.borrow_mut();
+        stats.record_render_ms(now_ms() - start);
+        stats.record_draw_calls(job.cursor - begin);
+        job.cursor >= job.objects.len()
+    }
+
+    // 绘制下一批剩余对象；全部完成后补上帧尾的钩子和调试信息
+    pub fn refine(&self) {
+        let mut renderer = self.renderer.borrow_mut();
+        let mut hit_renderer = self.hit_renderer.borrow_mut();
+        let (Some(renderer), Some(hit_renderer)) = (renderer.as_mut(), hit_renderer.as_mut())
+        else {
+            return;
+        };
+        let Some(mut job) = self.progressive_job.borrow_mut().take() else {
+            return;
+        };
+
+        renderer.save();
+        Self::apply_view(&**renderer, &job.view);
+        let done = self.render_chunk(&**renderer, &mut job);
+        renderer.restore();
+
+        if done {
+            self.finish_frame(renderer, hit_renderer, job.delta, &job.dirty_regions);
+        } else {
+            *self.progressive_job.borrow_mut() = Some(job);
+        }
+        self.flush_render_worker();
+    }
+}
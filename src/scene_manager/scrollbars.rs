@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bounding_box::BoundingBox;
+use crate::renderer::Renderer;
+
+// 视口在内容范围中的位置，画在屏幕坐标下；视图变化或拖动时显示，停止操作一段时间后淡出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ScrollbarOptions {
+    pub enabled: bool,
+    // 以下长度均为屏幕像素
+    pub thickness: f64,
+    // 与画布边缘的距离
+    pub margin: f64,
+    pub min_thumb: f64,
+    pub color: String,
+    // 最后一次视图变化后保持显示的时间（毫秒），之后在 fade_ms 内淡出
+    pub hide_delay_ms: f64,
+    pub fade_ms: f64,
+}
+
+impl Default for ScrollbarOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            thickness: 8.0,
+            margin: 3.0,
+            min_thumb: 24.0,
+            color: "rgba(0, 0, 0, 0.45)".to_string(),
+            hide_delay_ms: 1200.0,
+            fade_ms: 300.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollAxis {
+    Horizontal,
+    Vertical,
+}
+
+// 滑块的屏幕矩形，以及滑块每移动一个屏幕像素视口在世界坐标中移动的距离
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollThumb {
+    pub axis: ScrollAxis,
+    pub rect: BoundingBox,
+    pub world_per_pixel: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScrollDrag {
+    axis: ScrollAxis,
+    world_per_pixel: f64,
+    // 上一次的屏幕坐标
+    last: (f64, f64),
+}
+
+#[derive(Debug, Default)]
+pub struct Scrollbars {
+    options: ScrollbarOptions,
+    shown_at: Option<f64>,
+    drag: Option<ScrollDrag>,
+}
+
+impl Scrollbars {
+    pub fn options(&self) -> &ScrollbarOptions {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: ScrollbarOptions) {
+        if !options.enabled {
+            self.drag = None;
+        }
+        self.options = options;
+    }
+
+    pub fn show(&mut self, now: f64) {
+        if self.options.enabled {
+            self.shown_at = Some(now);
+        }
+    }
+
+    // 拖动中保持完全显示
+    pub fn opacity(&self, now: f64) -> f64 {
+        if !self.options.enabled {
+            return 0.0;
+        }
+        if self.drag.is_some() {
+            return 1.0;
+        }
+        let Some(shown_at) = self.shown_at else {
+            return 0.0;
+        };
+        let fading = now - shown_at - self.options.hide_delay_ms;
+        if fading <= 0.0 {
+            1.0
+        } else if self.options.fade_ms > 0.0 {
+            (1.0 - fading / self.options.fade_ms).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    // 显示或淡出期间需要继续绘制
+    pub fn is_visible(&self, now: f64) -> bool {
+        self.opacity(now) > 0.0
+    }
+
+    // view 为可见区域，content 为内容范围（世界坐标），width/height 为画布的屏幕尺寸；
+    // 内容在某个方向上没有超出可见区域时不显示该方向的滚动条
+    pub fn layout(&self, view: &BoundingBox, content: &BoundingBox, width: f64, height: f64) -> Vec<ScrollThumb> {
+        let content = content.union(view);
+        let ScrollbarOptions { thickness, margin, min_thumb, .. } = self.options;
+        let overflow_x = content.width - view.width > f64::EPSILON;
+        let overflow_y = content.height - view.height > f64::EPSILON;
+        let mut thumbs = Vec::new();
+        // 两个方向都显示时在右下角留出空位
+        let corner = thickness + margin;
+        if overflow_x {
+            let track = width - 2.0 * margin - if overflow_y { corner } else { 0.0 };
+            if let Some((start, length, world_per_pixel)) =
+                thumb_span(track, min_thumb, view.x - content.x, view.width, content.width)
+            {
+                thumbs.push(ScrollThumb {
+                    axis: ScrollAxis::Horizontal,
+                    rect: BoundingBox::new(margin + start, height - corner, length, thickness),
+                    world_per_pixel,
+                });
+            }
+        }
+        if overflow_y {
+            let track = height - 2.0 * margin - if overflow_x { corner } else { 0.0 };
+            if let Some((start, length, world_per_pixel)) =
+                thumb_span(track, min_thumb, view.y - content.y, view.height, content.height)
+            {
+                thumbs.push(ScrollThumb {
+                    axis: ScrollAxis::Vertical,
+                    rect: BoundingBox::new(width - corner, margin + start, thickness, length),
+                    world_per_pixel,
+                });
+            }
+        }
+        thumbs
+    }
+
+    pub fn draw(&self, renderer: &dyn Renderer, thumbs: &[ScrollThumb], now: f64) {
+        let opacity = self.opacity(now);
+        if opacity <= 0.0 || thumbs.is_empty() {
+            return;
+        }
+        renderer.save();
+        renderer.set_global_alpha(opacity);
+        for thumb in thumbs {
+            let rect = &thumb.rect;
+            renderer.draw_rectangle(rect.x, rect.y, rect.width, rect.height, &self.options.color);
+        }
+        renderer.restore();
+    }
+
+    // 按下点落在滑块上时开始拖动，返回 true；隐藏时不响应
+    pub fn begin_drag(&mut self, thumbs: &[ScrollThumb], x: f64, y: f64, now: f64) -> bool {
+        if !self.is_visible(now) {
+            return false;
+        }
+        let Some(thumb) = thumbs.iter().find(|thumb| thumb.rect.contains_point(x, y)) else {
+            return false;
+        };
+        self.drag = Some(ScrollDrag {
+            axis: thumb.axis,
+            world_per_pixel: thumb.world_per_pixel,
+            last: (x, y),
+        });
+        true
+    }
+
+    // 拖动时视口应移动的世界距离 (dx, dy)；不在拖动时返回 None
+    pub fn drag_to(&mut self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let drag = self.drag.as_mut()?;
+        let (last_x, last_y) = std::mem::replace(&mut drag.last, (x, y));
+        Some(match drag.axis {
+            ScrollAxis::Horizontal => ((x - last_x) * drag.world_per_pixel, 0.0),
+            ScrollAxis::Vertical => (0.0, (y - last_y) * drag.world_per_pixel),
+        })
+    }
+
+    pub fn end_drag(&mut self, now: f64) -> bool {
+        if self.drag.take().is_none() {
+            return false;
+        }
+        self.shown_at = Some(now);
+        true
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+}
+
+// 轨道上滑块的 (起点, 长度, 每像素对应的世界距离)；offset 为视口相对内容起点的位置
+fn thumb_span(track: f64, min_thumb: f64, offset: f64, visible: f64, total: f64) -> Option<(f64, f64, f64)> {
+    if track <= 0.0 || total <= 0.0 {
+        return None;
+    }
+    let length = (track * visible / total).clamp(min_thumb.min(track), track);
+    let travel = track - length;
+    let scrollable = total - visible;
+    if travel <= 0.0 || scrollable <= 0.0 {
+        return None;
+    }
+    let start = (offset / scrollable).clamp(0.0, 1.0) * travel;
+    Some((start, length, scrollable / travel))
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+// 1 英寸 = 96 CSS 像素 = 25.4 毫米
+const PX_PER_MM: f64 = 96.0 / 25.4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentUnit {
+    #[default]
+    Px,
+    Mm,
+    M,
+}
+
+impl DocumentUnit {
+    // 未指定比例时每单位对应的世界单位数，按 96 dpi 换算
+    fn default_scale(self) -> f64 {
+        match self {
+            DocumentUnit::Px => 1.0,
+            DocumentUnit::Mm => PX_PER_MM,
+            DocumentUnit::M => PX_PER_MM * 1000.0,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            DocumentUnit::Px => "px",
+            DocumentUnit::Mm => "mm",
+            DocumentUnit::M => "m",
+        }
+    }
+}
+
+// 文档的长度单位，世界坐标仍以世界单位存储，只在显示、测量和导出时换算；
+// scale 为每个文档单位对应的世界单位数，例如平面图中 1 m 画作 50 个世界单位时为 50
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DocumentUnits {
+    pub unit: DocumentUnit,
+    pub scale: Option<f64>,
+}
+
+impl DocumentUnits {
+    pub fn world_per_unit(self) -> f64 {
+        self.scale
+            .filter(|scale| scale.is_finite() && *scale > 0.0)
+            .unwrap_or_else(|| self.unit.default_scale())
+    }
+
+    // 世界单位 -> 文档单位
+    pub fn to_units(self, length: f64) -> f64 {
+        length / self.world_per_unit()
+    }
+
+    pub fn to_world(self, length: f64) -> f64 {
+        length * self.world_per_unit()
+    }
+
+    pub fn format_length(self, length: f64, precision: usize) -> String {
+        format!("{:.*} {}", precision, self.to_units(length), self.unit.suffix())
+    }
+}
@@ -0,0 +1,88 @@
+use nalgebra as na;
+
+// 世界坐标 -> 画布 CSS 像素：先平移，再以 center 为中心旋转和缩放；绘制、拾取和坐标换算共用这一个矩阵
+pub fn view_matrix(zoom: f64, rotation: f64, offset: (f64, f64), center: (f64, f64)) -> na::Matrix3<f64> {
+    let scale = na::Matrix3::new(zoom, 0.0, 0.0, 0.0, zoom, 0.0, 0.0, 0.0, 1.0);
+    let (sin_r, cos_r) = rotation.sin_cos();
+    let rotation = na::Matrix3::new(cos_r, -sin_r, 0.0, sin_r, cos_r, 0.0, 0.0, 0.0, 1.0);
+    let translation = na::Matrix3::new_translation(&na::Vector2::new(offset.0, offset.1));
+    let to_center = na::Matrix3::new_translation(&na::Vector2::new(center.0, center.1));
+    let from_center = na::Matrix3::new_translation(&na::Vector2::new(-center.0, -center.1));
+    to_center * scale * rotation * translation * from_center
+}
+
+// 画布 CSS 像素 (x, y) 在 hit 画布上对应的像素。hit 画布绘制时已经应用了视图矩阵，
+// 这里只按分辨率缩放，不能再做视图的逆变换
+pub fn hit_pixel(x: f64, y: f64, hit_ratio: f64) -> (f64, f64) {
+    ((x * hit_ratio).floor(), (y * hit_ratio).floor())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::point_in_polygon;
+
+    const ZOOMS: [f64; 4] = [0.5, 1.0, 2.0, 3.7];
+    const ROTATIONS: [f64; 4] = [0.0, 0.4, std::f64::consts::FRAC_PI_2, -2.3];
+    const CENTERS: [(f64, f64); 3] = [(0.0, 0.0), (400.0, 300.0), (-120.0, 75.5)];
+    const HIT_RATIOS: [f64; 3] = [0.5, 1.0, 2.0];
+
+    fn views() -> impl Iterator<Item = na::Matrix3<f64>> {
+        ZOOMS.into_iter().flat_map(|zoom| {
+            ROTATIONS.into_iter().flat_map(move |rotation| {
+                CENTERS
+                    .into_iter()
+                    .map(move |center| view_matrix(zoom, rotation, (35.0, -20.0), center))
+            })
+        })
+    }
+
+    fn apply(matrix: &na::Matrix3<f64>, x: f64, y: f64) -> (f64, f64) {
+        let point = matrix * na::Vector3::new(x, y, 1.0);
+        (point.x, point.y)
+    }
+
+    #[test]
+    fn zoom_and_rotation_pivot_on_center() {
+        for zoom in ZOOMS {
+            for rotation in ROTATIONS {
+                for center in CENTERS {
+                    let view = view_matrix(zoom, rotation, (0.0, 0.0), center);
+                    let (x, y) = apply(&view, center.0, center.1);
+                    assert!((x - center.0).abs() < 1e-9 && (y - center.1).abs() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn screen_to_world_round_trip() {
+        for view in views() {
+            let inverse = view.try_inverse().unwrap();
+            for (x, y) in [(0.0, 0.0), (12.5, -40.0), (800.0, 600.0)] {
+                let (sx, sy) = apply(&view, x, y);
+                let (wx, wy) = apply(&inverse, sx, sy);
+                assert!((wx - x).abs() < 1e-6 && (wy - y).abs() < 1e-6);
+            }
+        }
+    }
+
+    // 模拟 hit 画布：先按分辨率缩放再应用视图矩阵绘制，取样点的像素中心落回对象内即为命中
+    #[test]
+    fn hit_pixel_lands_on_object() {
+        // 绕中心旋转的 60 x 40 矩形
+        let (cx, cy) = (180.0, 110.0);
+        let object = na::Matrix3::new_translation(&na::Vector2::new(cx, cy)) * na::Matrix3::new_rotation(0.3);
+        let outline = [(-30.0, -20.0), (30.0, -20.0), (30.0, 20.0), (-30.0, 20.0)].map(|(x, y)| apply(&object, x, y));
+        for view in views() {
+            for hit_ratio in HIT_RATIOS {
+                let hit_canvas = na::Matrix3::new_scaling(hit_ratio) * view;
+                let inverse = hit_canvas.try_inverse().unwrap();
+                let (sx, sy) = apply(&view, cx, cy);
+                let (px, py) = hit_pixel(sx, sy, hit_ratio);
+                let point = apply(&inverse, px + 0.5, py + 0.5);
+                assert!(point_in_polygon(point, &outline), "missed at view {view} ratio {hit_ratio}");
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use super::SceneDirtyData;
+
+// 最多保留的视图数
+const MAX_ENTRIES: usize = 50;
+// 间隔小于该值（毫秒）的连续视图变化合并为一条，如拖动平移、滚轮缩放
+const MERGE_INTERVAL_MS: f64 = 500.0;
+
+// 视图的后退/前进记录，与文档历史分开，不参与撤销重做
+#[derive(Debug, Default)]
+pub(super) struct ViewHistory {
+    back: Vec<SceneDirtyData>,
+    forward: Vec<SceneDirtyData>,
+    last_record: Option<f64>,
+}
+
+impl ViewHistory {
+    // old 为变化之前的视图；新的导航会清空前进记录
+    pub(super) fn record(&mut self, old: SceneDirtyData, now: f64) {
+        let merged = self
+            .last_record
+            .is_some_and(|last| now - last < MERGE_INTERVAL_MS);
+        self.last_record = Some(now);
+        if merged {
+            return;
+        }
+        if self.back.len() >= MAX_ENTRIES {
+            self.back.remove(0);
+        }
+        self.back.push(old);
+        self.forward.clear();
+    }
+
+    pub(super) fn back(&mut self, current: SceneDirtyData) -> Option<SceneDirtyData> {
+        let target = self.back.pop()?;
+        self.forward.push(current);
+        self.last_record = None;
+        Some(target)
+    }
+
+    pub(super) fn forward(&mut self, current: SceneDirtyData) -> Option<SceneDirtyData> {
+        let target = self.forward.pop()?;
+        self.back.push(current);
+        self.last_record = None;
+        Some(target)
+    }
+
+    pub(super) fn can_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub(super) fn can_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{EditingError, EditingResult};
+use crate::history::{ElementHistoryItem, HistoryItem};
+use crate::op_log::{OpLog, RecordedOperation, OP_LOG_FORMAT_VERSION};
+
+mod codec;
+
+// 会话格式版本，结构不兼容时递增
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+// 二进制会话的文件头，之后是 codec 编码的会话 JSON
+const SESSION_MAGIC: &[u8; 4] = b"EDSN";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionEncoding {
+    #[default]
+    Json,
+    Binary,
+}
+
+// 保存和分享用的录制会话，与 OpLog 内容相同，每条操作写成 [时间, 记录] 以减小体积
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    version: u32,
+    started_at: f64,
+    duration: f64,
+    scene: Value,
+    objects: Vec<ElementHistoryItem>,
+    operations: Vec<(f64, Vec<HistoryItem>)>,
+}
+
+impl From<OpLog> for Session {
+    fn from(log: OpLog) -> Self {
+        Self {
+            version: SESSION_FORMAT_VERSION,
+            started_at: log.started_at,
+            duration: log.duration,
+            scene: log.scene,
+            objects: log.objects,
+            operations: log
+                .operations
+                .into_iter()
+                .map(|operation| (operation.time, operation.items))
+                .collect(),
+        }
+    }
+}
+
+impl From<Session> for OpLog {
+    fn from(session: Session) -> Self {
+        Self {
+            version: OP_LOG_FORMAT_VERSION,
+            started_at: session.started_at,
+            duration: session.duration,
+            scene: session.scene,
+            objects: session.objects,
+            operations: session
+                .operations
+                .into_iter()
+                .map(|(time, items)| RecordedOperation { time, items })
+                .collect(),
+        }
+    }
+}
+
+pub fn encode_session(log: OpLog, encoding: SessionEncoding) -> EditingResult<Vec<u8>> {
+    let session = Session::from(log);
+    match encoding {
+        SessionEncoding::Json => Ok(serde_json::to_vec(&session)?),
+        SessionEncoding::Binary => {
+            let mut data = SESSION_MAGIC.to_vec();
+            data.extend(codec::encode(&serde_json::to_value(&session)?));
+            Ok(data)
+        }
+    }
+}
+
+// 按文件头识别二进制或 JSON 格式
+pub fn decode_session(data: &[u8]) -> EditingResult<OpLog> {
+    let value: Value = match data.strip_prefix(SESSION_MAGIC) {
+        Some(body) => codec::decode(body)?,
+        None => serde_json::from_slice(data)?,
+    };
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| EditingError::InvalidSession("missing version".to_string()))?;
+    if version != SESSION_FORMAT_VERSION as u64 {
+        return Err(EditingError::UnsupportedSessionVersion(version as u32));
+    }
+    let session: Session = serde_json::from_value(value)?;
+    Ok(session.into())
+}
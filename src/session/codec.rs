@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Number, Value};
+
+use crate::error::{EditingError, EditingResult};
+
+// 值的类型标记
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_FLOAT: u8 = 4;
+const TAG_STRING: u8 = 5;
+const TAG_ARRAY: u8 = 6;
+const TAG_OBJECT: u8 = 7;
+
+// 把 JSON 值编码为紧凑的二进制：字符串（包括对象的键）只在字符串表中出现一次，
+// 整数使用 zigzag 变长编码，其余数字按 8 字节小端浮点存放。
+// 布局为：字符串表（数量，每项为长度 + UTF-8），随后是根值
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut strings = StringTable::default();
+    let mut body = Vec::new();
+    encode_value(value, &mut strings, &mut body);
+
+    let mut out = Vec::with_capacity(body.len() + strings.bytes);
+    write_varint(&mut out, strings.values.len() as u64);
+    for string in &strings.values {
+        write_varint(&mut out, string.len() as u64);
+        out.extend_from_slice(string.as_bytes());
+    }
+    out.extend_from_slice(&body);
+    out
+}
+
+pub fn decode(data: &[u8]) -> EditingResult<Value> {
+    let mut reader = Reader { data, position: 0 };
+    let count = reader.varint()? as usize;
+    let mut strings = Vec::with_capacity(count.min(data.len()));
+    for _ in 0..count {
+        let len = reader.varint()? as usize;
+        let bytes = reader.take(len)?;
+        let string = std::str::from_utf8(bytes).map_err(|e| invalid(e.to_string()))?;
+        strings.push(string.to_string());
+    }
+    let value = reader.value(&strings)?;
+    if reader.position != data.len() {
+        return Err(invalid("trailing bytes"));
+    }
+    Ok(value)
+}
+
+#[derive(Default)]
+struct StringTable {
+    values: Vec<String>,
+    indices: HashMap<String, u64>,
+    bytes: usize,
+}
+
+impl StringTable {
+    fn index(&mut self, string: &str) -> u64 {
+        if let Some(&index) = self.indices.get(string) {
+            return index;
+        }
+        let index = self.values.len() as u64;
+        self.values.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        self.bytes += string.len() + 1;
+        index
+    }
+}
+
+fn encode_value(value: &Value, strings: &mut StringTable, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(TAG_NULL),
+        Value::Bool(false) => out.push(TAG_FALSE),
+        Value::Bool(true) => out.push(TAG_TRUE),
+        Value::Number(number) => match number.as_i64() {
+            Some(int) => {
+                out.push(TAG_INT);
+                write_varint(out, zigzag(int));
+            }
+            None => {
+                out.push(TAG_FLOAT);
+                out.extend_from_slice(&number.as_f64().unwrap_or(0.0).to_le_bytes());
+            }
+        },
+        Value::String(string) => {
+            out.push(TAG_STRING);
+            write_varint(out, strings.index(string));
+        }
+        Value::Array(items) => {
+            out.push(TAG_ARRAY);
+            write_varint(out, items.len() as u64);
+            for item in items {
+                encode_value(item, strings, out);
+            }
+        }
+        Value::Object(map) => {
+            out.push(TAG_OBJECT);
+            write_varint(out, map.len() as u64);
+            for (key, item) in map {
+                write_varint(out, strings.index(key));
+                encode_value(item, strings, out);
+            }
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn invalid(message: impl Into<String>) -> EditingError {
+    EditingError::InvalidSession(message.into())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> EditingResult<&[u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> EditingResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> EditingResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint is too long"))
+    }
+
+    fn string<'s>(&mut self, strings: &'s [String]) -> EditingResult<&'s String> {
+        let index = self.varint()? as usize;
+        strings.get(index).ok_or_else(|| invalid("string index out of range"))
+    }
+
+    fn value(&mut self, strings: &[String]) -> EditingResult<Value> {
+        let value = match self.byte()? {
+            TAG_NULL => Value::Null,
+            TAG_FALSE => Value::Bool(false),
+            TAG_TRUE => Value::Bool(true),
+            TAG_INT => Value::Number(unzigzag(self.varint()?).into()),
+            TAG_FLOAT => {
+                let bytes: [u8; 8] = self.take(8)?.try_into().map_err(|_| invalid("invalid float"))?;
+                Number::from_f64(f64::from_le_bytes(bytes)).map_or(Value::Null, Value::Number)
+            }
+            TAG_STRING => Value::String(self.string(strings)?.clone()),
+            TAG_ARRAY => {
+                let len = self.varint()? as usize;
+                // 长度来自输入，不按它预先分配
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.value(strings)?);
+                }
+                Value::Array(items)
+            }
+            TAG_OBJECT => {
+                let len = self.varint()? as usize;
+                let mut map = Map::new();
+                for _ in 0..len {
+                    let key = self.string(strings)?.clone();
+                    map.insert(key, self.value(strings)?);
+                }
+                Value::Object(map)
+            }
+            tag => return Err(invalid(format!("unknown value tag {}", tag))),
+        };
+        Ok(value)
+    }
+}
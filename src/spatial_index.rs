@@ -0,0 +1,130 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::bounding_box::BoundingBox;
+
+// 世界坐标下每个网格的边长
+const CELL_SIZE: f64 = 256.0;
+// 覆盖格子过多的大对象单独存放，避免插入和删除时遍历大量格子
+const MAX_CELLS_PER_OBJECT: i64 = 64;
+
+type Cell = (i64, i64);
+
+// 均匀网格空间索引，按包围盒粗筛后再精确判断相交
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+    cells: HashMap<Cell, HashSet<String>>,
+    bounds: HashMap<String, BoundingBox>,
+    large: HashSet<String>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 已存在时替换为新的包围盒
+    pub fn insert(&mut self, id: &str, bounds: BoundingBox) {
+        if self.bounds.get(id) == Some(&bounds) {
+            return;
+        }
+        self.remove(id);
+
+        match Self::cell_range(&bounds) {
+            Some((min_x, min_y, max_x, max_y)) => {
+                for cx in min_x..=max_x {
+                    for cy in min_y..=max_y {
+                        self.cells
+                            .entry((cx, cy))
+                            .or_default()
+                            .insert(id.to_string());
+                    }
+                }
+            }
+            None => {
+                self.large.insert(id.to_string());
+            }
+        }
+        self.bounds.insert(id.to_string(), bounds);
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        let Some(bounds) = self.bounds.remove(id) else {
+            return;
+        };
+        if self.large.remove(id) {
+            return;
+        }
+        if let Some((min_x, min_y, max_x, max_y)) = Self::cell_range(&bounds) {
+            for cx in min_x..=max_x {
+                for cy in min_y..=max_y {
+                    if let Some(cell) = self.cells.get_mut(&(cx, cy)) {
+                        cell.remove(id);
+                        if cell.is_empty() {
+                            self.cells.remove(&(cx, cy));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds.clear();
+        self.large.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    // 包围盒与 rect 相交的对象，顺序不固定
+    pub fn query_rect(&self, rect: &BoundingBox) -> Vec<String> {
+        let mut candidates: HashSet<&String> = self.large.iter().collect();
+        match Self::cell_range(rect) {
+            Some((min_x, min_y, max_x, max_y)) => {
+                for cx in min_x..=max_x {
+                    for cy in min_y..=max_y {
+                        if let Some(cell) = self.cells.get(&(cx, cy)) {
+                            candidates.extend(cell.iter());
+                        }
+                    }
+                }
+            }
+            // 查询范围过大时直接遍历全部对象
+            None => candidates.extend(self.bounds.keys()),
+        }
+
+        candidates
+            .into_iter()
+            .filter(|id| {
+                self.bounds
+                    .get(*id)
+                    .is_some_and(|bounds| bounds.intersects(rect))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn query_point(&self, x: f64, y: f64) -> Vec<String> {
+        self.query_rect(&BoundingBox::new(x, y, 0.0, 0.0))
+    }
+
+    fn cell_range(bounds: &BoundingBox) -> Option<(i64, i64, i64, i64)> {
+        let values = [bounds.x, bounds.y, bounds.right(), bounds.bottom()];
+        if values.iter().any(|value| !value.is_finite()) {
+            return None;
+        }
+
+        let min_x = (bounds.x / CELL_SIZE).floor() as i64;
+        let min_y = (bounds.y / CELL_SIZE).floor() as i64;
+        let max_x = (bounds.right() / CELL_SIZE).floor() as i64;
+        let max_y = (bounds.bottom() / CELL_SIZE).floor() as i64;
+        let cells = (max_x - min_x + 1).saturating_mul(max_y - min_y + 1);
+        (cells <= MAX_CELLS_PER_OBJECT).then_some((min_x, min_y, max_x, max_y))
+    }
+}
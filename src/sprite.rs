@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::image::Image;
+
+// 帧在图集中的像素区域
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpriteFrame {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+// 帧的定义方式：等大网格，或帧名到区域的映射
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SpriteFrames {
+    #[serde(rename_all = "camelCase")]
+    Grid {
+        frame_width: f64,
+        frame_height: f64,
+        columns: usize,
+        count: usize,
+    },
+    Named(HashMap<String, SpriteFrame>),
+}
+
+// 一张图片及其中按名字划分的帧，Sprite 元素通过注册名引用
+pub struct SpriteSheet {
+    image: Image<'static>,
+    frames: HashMap<String, SpriteFrame>,
+}
+
+impl Debug for SpriteSheet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SpriteSheet {{ frames: {} }}", self.frames.len())
+    }
+}
+
+impl SpriteSheet {
+    pub fn new(image: Image<'static>) -> Self {
+        Self {
+            image,
+            frames: HashMap::new(),
+        }
+    }
+
+    pub fn with_frames(mut self, frames: HashMap<String, SpriteFrame>) -> Self {
+        self.frames.extend(frames);
+        self
+    }
+
+    // 按行从左到右切分等大的帧，帧名为序号 "0"、"1"...
+    pub fn with_grid(mut self, frame_width: f64, frame_height: f64, columns: usize, count: usize) -> Self {
+        let columns = columns.max(1);
+        for index in 0..count {
+            let frame = SpriteFrame {
+                x: (index % columns) as f64 * frame_width,
+                y: (index / columns) as f64 * frame_height,
+                width: frame_width,
+                height: frame_height,
+            };
+            self.frames.insert(index.to_string(), frame);
+        }
+        self
+    }
+
+    pub fn with_layout(self, frames: SpriteFrames) -> Self {
+        match frames {
+            SpriteFrames::Grid {
+                frame_width,
+                frame_height,
+                columns,
+                count,
+            } => self.with_grid(frame_width, frame_height, columns, count),
+            SpriteFrames::Named(frames) => self.with_frames(frames),
+        }
+    }
+
+    pub fn image(&self) -> &Image<'static> {
+        &self.image
+    }
+
+    pub fn frame(&self, name: &str) -> Option<SpriteFrame> {
+        self.frames.get(name).copied()
+    }
+}
+
+thread_local! {
+    static SPRITE_SHEETS: RefCell<HashMap<String, Rc<SpriteSheet>>> = RefCell::new(HashMap::new());
+}
+
+// 同名图集会被替换
+pub fn register_sprite_sheet(name: impl Into<String>, sheet: SpriteSheet) {
+    SPRITE_SHEETS.with(|sheets| sheets.borrow_mut().insert(name.into(), Rc::new(sheet)));
+}
+
+pub fn unregister_sprite_sheet(name: &str) -> bool {
+    SPRITE_SHEETS.with(|sheets| sheets.borrow_mut().remove(name).is_some())
+}
+
+pub fn sprite_sheet(name: &str) -> Option<Rc<SpriteSheet>> {
+    SPRITE_SHEETS.with(|sheets| sheets.borrow().get(name).cloned())
+}
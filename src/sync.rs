@@ -0,0 +1,483 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::history::{HistoryItem, ObjectHistoryItem, SceneHistoryItem};
+use crate::log::log_warn;
+use crate::render_control::{UpdateBody, UpdateType};
+
+mod crdt;
+mod presence;
+
+use crdt::{LwwRegisters, Rga, Stamp};
+
+pub use crdt::ElementId;
+pub use presence::{Presence, PresenceOverlay};
+
+// 同一客户端最多缓存的乱序操作数，超过后放弃等待缺失的操作
+const MAX_PENDING: usize = 1000;
+// 路径元素中按序列合并的属性
+const POINTS_KEY: &str = "points";
+// 共享样式按样式 id 整体覆盖，与对象属性分开记录
+const STYLES_TARGET: &str = "#styles";
+
+// 客户端之间传递的一次修改。seq 在同一 client_id 内从 0 开始连续递增，接收方按它恢复发送顺序；
+// 第 i 条记录的 Lamport 时间为 clock + i，属性按它做最后写入者胜出的合并。
+// 撤销在发送前已转换为正向的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    pub client_id: String,
+    pub seq: u64,
+    pub clock: u64,
+    pub items: Vec<HistoryItem>,
+    // 绘制顺序和路径点的序列修改，在 items 之后应用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sequence: Vec<SequenceOp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SequenceList {
+    // 对象 id 按绘制顺序从下到上
+    ZOrder,
+    // 路径对象的 points
+    Points(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SequenceOp {
+    Insert {
+        list: SequenceList,
+        id: ElementId,
+        after: Option<ElementId>,
+        value: Value,
+    },
+    Remove {
+        list: SequenceList,
+        id: ElementId,
+    },
+}
+
+// 与传输方式无关的发送端，WebSocket、WebRTC、BroadcastChannel 等都可以实现；
+// 收到的远端操作交给 App::receive_operation
+pub trait SyncAdapter {
+    fn send(&self, operation: &Operation);
+
+    // 指针和选中状态，可以丢失，不需要的传输方式可以不实现
+    fn send_presence(&self, _presence: &Presence) {}
+}
+
+// 排队的 UpdateMessage 不进入历史，同步时按只有 redo 数据的历史记录发送
+impl From<&UpdateBody> for HistoryItem {
+    fn from(body: &UpdateBody) -> Self {
+        match &body.update_type {
+            UpdateType::ObjectUpdate(id) => {
+                HistoryItem::ObjectUpdate(ObjectHistoryItem::new(id.clone(), Value::Null, body.data.clone()))
+            }
+            UpdateType::SceneUpdate => {
+                HistoryItem::SceneUpdate(SceneHistoryItem::new(Value::Null, body.data.clone()))
+            }
+        }
+    }
+}
+
+fn points_of(data: &Value) -> Option<Vec<Value>> {
+    data.get(POINTS_KEY)?.as_array().cloned()
+}
+
+fn apply_points_op(rga: &mut Rga<Value>, op: SequenceOp) {
+    match op {
+        SequenceOp::Insert { id, after, value, .. } => rga.insert(id, after.as_ref(), value),
+        SequenceOp::Remove { id, .. } => rga.remove(&id),
+    }
+}
+
+// 合并远端操作后需要应用到场景的修改
+#[derive(Debug, Default)]
+pub struct RemoteChanges {
+    // 已去掉被更晚的写入覆盖的属性
+    pub items: Vec<HistoryItem>,
+    pub z_order: Option<Vec<String>>,
+    pub points: Vec<(String, Vec<Value>)>,
+}
+
+pub struct SyncSession {
+    client_id: String,
+    adapter: Rc<dyn SyncAdapter>,
+    next_seq: u64,
+    outbox: Vec<Operation>,
+    // 每个远端客户端下一条应当应用的 seq，以及提前到达、等待前面操作的部分
+    expected: HashMap<String, u64>,
+    pending: HashMap<String, BTreeMap<u64, Operation>>,
+    // 已用过的最大 Lamport 时间
+    clock: u64,
+    registers: LwwRegisters,
+    z_order: Rga<String>,
+    points: HashMap<String, Rga<Value>>,
+    // 路径对象的添加还没有收到时，先到达的 points 修改
+    orphan_points: HashMap<String, Vec<SequenceOp>>,
+    // 本地的指针和显示名称，以及最近一次发出的状态
+    name: Option<String>,
+    cursor: Option<(f64, f64)>,
+    sent_presence: Option<Presence>,
+    presence_scheduled: bool,
+}
+
+impl Debug for SyncSession {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SyncSession {{ client_id: {}, next_seq: {} }}", self.client_id, self.next_seq)
+    }
+}
+
+impl SyncSession {
+    // objects 为同步开始时按绘制顺序排列的对象及路径对象的 points；各客户端需从同一份文档开始
+    pub fn new(
+        client_id: impl Into<String>,
+        adapter: Rc<dyn SyncAdapter>,
+        objects: Vec<(String, Option<Vec<Value>>)>,
+    ) -> Self {
+        let initial = Stamp::initial();
+        let mut points = HashMap::new();
+        let mut order = Vec::with_capacity(objects.len());
+        for (id, object_points) in objects {
+            if let Some(object_points) = object_points {
+                points.insert(id.clone(), Rga::from_values(&initial, object_points));
+            }
+            order.push(id);
+        }
+        Self {
+            client_id: client_id.into(),
+            adapter,
+            next_seq: 0,
+            outbox: Vec::new(),
+            expected: HashMap::new(),
+            pending: HashMap::new(),
+            clock: 0,
+            registers: LwwRegisters::default(),
+            z_order: Rga::from_values(&initial, order),
+            points,
+            orphan_points: HashMap::new(),
+            name: None,
+            cursor: None,
+            sent_presence: None,
+            presence_scheduled: false,
+        }
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<(f64, f64)>) {
+        self.cursor = cursor;
+    }
+
+    // 返回 true 表示尚未安排发送，需要安排一次
+    pub fn schedule_presence(&mut self) -> bool {
+        !std::mem::replace(&mut self.presence_scheduled, true)
+    }
+
+    // 取出需要发送的状态，与上次发出的相同时返回 None
+    pub fn take_presence(&mut self, selection: Vec<String>) -> Option<(Rc<dyn SyncAdapter>, Presence)> {
+        self.presence_scheduled = false;
+        let presence = Presence {
+            client_id: self.client_id.clone(),
+            name: self.name.clone(),
+            cursor: self.cursor,
+            selection,
+        };
+        if self.sent_presence.as_ref() == Some(&presence) {
+            return None;
+        }
+        self.sent_presence = Some(presence.clone());
+        Some((self.adapter.clone(), presence))
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    // 本地修改编号后排入待发送队列；返回 true 表示队列原来为空，需要安排一次发送
+    pub fn queue(&mut self, items: &[HistoryItem], undo: bool) -> bool {
+        if items.is_empty() {
+            return false;
+        }
+        let mut items: Vec<HistoryItem> = if undo {
+            items.iter().rev().map(HistoryItem::inverse).collect()
+        } else {
+            items.to_vec()
+        };
+
+        let clock = self.clock + 1;
+        let mut sequence = Vec::new();
+        for (offset, item) in items.iter_mut().enumerate() {
+            let stamp = Stamp {
+                clock: clock + offset as u64,
+                client_id: self.client_id.clone(),
+            };
+            self.record_local(item, &stamp, &mut sequence);
+        }
+        self.clock = clock + items.len() as u64 - 1;
+
+        self.outbox.push(Operation {
+            client_id: self.client_id.clone(),
+            seq: self.next_seq,
+            clock,
+            items,
+            sequence,
+        });
+        self.next_seq += 1;
+        self.outbox.len() == 1
+    }
+
+    // 本地写入总是晚于已见过的写入；路径点的修改改为序列操作发送，不再整体覆盖
+    fn record_local(&mut self, item: &mut HistoryItem, stamp: &Stamp, sequence: &mut Vec<SequenceOp>) {
+        match item {
+            HistoryItem::ObjectUpdate(item) => {
+                if let Some(data) = item.redo_data.as_object() {
+                    for key in data.keys() {
+                        self.registers.accept(Some(&item.object_id), key, stamp);
+                    }
+                }
+                let new_points = points_of(&item.redo_data);
+                if let (Some(new_points), Some(rga)) = (new_points, self.points.get_mut(&item.object_id)) {
+                    let list = SequenceList::Points(item.object_id.clone());
+                    let (removed, inserted) = rga.diff(&new_points, stamp);
+                    for id in removed {
+                        rga.remove(&id);
+                        sequence.push(SequenceOp::Remove { list: list.clone(), id });
+                    }
+                    for (id, after, value) in inserted {
+                        rga.insert(id.clone(), after.as_ref(), value.clone());
+                        sequence.push(SequenceOp::Insert {
+                            list: list.clone(),
+                            id,
+                            after,
+                            value,
+                        });
+                    }
+                    for data in [&mut item.undo_data, &mut item.redo_data] {
+                        if let Some(data) = data.as_object_mut() {
+                            data.remove(POINTS_KEY);
+                        }
+                    }
+                }
+            }
+            HistoryItem::SceneUpdate(item) => {
+                if let Some(data) = item.redo_data.as_object() {
+                    for key in data.keys() {
+                        self.registers.accept(None, key, stamp);
+                    }
+                }
+            }
+            HistoryItem::AddElement(item) => {
+                let id = ElementId {
+                    stamp: stamp.clone(),
+                    index: 0,
+                };
+                let after = self.z_order.last_visible();
+                self.z_order.insert(id.clone(), after.as_ref(), item.element_id.clone());
+                sequence.push(SequenceOp::Insert {
+                    list: SequenceList::ZOrder,
+                    id,
+                    after,
+                    value: Value::String(item.element_id.clone()),
+                });
+                if let Some(points) = points_of(&item.element_data) {
+                    self.points.insert(item.element_id.clone(), Rga::from_values(stamp, points));
+                }
+            }
+            HistoryItem::RemoveElement(item) => {
+                if let Some(id) = self.z_order.find(&item.element_id) {
+                    self.z_order.remove(&id);
+                    sequence.push(SequenceOp::Remove {
+                        list: SequenceList::ZOrder,
+                        id,
+                    });
+                }
+                self.points.remove(&item.element_id);
+            }
+            HistoryItem::StyleUpdate(item) => {
+                self.registers.accept(Some(STYLES_TARGET), &item.style_id, stamp);
+            }
+        }
+    }
+
+    // 取出待发送的操作，由调用方在释放借用后交给 adapter，避免发送回调中再次访问 App
+    pub fn take_outbox(&mut self) -> (Rc<dyn SyncAdapter>, Vec<Operation>) {
+        (self.adapter.clone(), std::mem::take(&mut self.outbox))
+    }
+
+    // 返回按发送顺序可以立即应用的操作；自己发出的和重复收到的被丢弃，提前到达的暂存到缺失的操作补齐。
+    // 各客户端从同一份文档开始，seq 都从 0 开始等待
+    pub fn receive(&mut self, operation: Operation) -> Vec<Operation> {
+        if operation.client_id == self.client_id {
+            return Vec::new();
+        }
+        let client_id = operation.client_id.clone();
+        let expected = self.expected.entry(client_id.clone()).or_insert(0);
+        if operation.seq < *expected {
+            return Vec::new();
+        }
+
+        let pending = self.pending.entry(client_id).or_default();
+        pending.insert(operation.seq, operation);
+        if pending.len() > MAX_PENDING {
+            if let Some(&first) = pending.keys().next() {
+                log_warn!("Skipping missing operations {}..{} from a remote client", *expected, first);
+                *expected = first;
+            }
+        }
+
+        let mut ready = Vec::new();
+        while let Some(operation) = pending.remove(expected) {
+            *expected += 1;
+            ready.push(operation);
+        }
+        ready
+    }
+
+    // 把一条按顺序到达的远端操作合并进本地状态，返回需要应用到场景的修改。
+    // 属性按时间戳取最后一次写入，序列修改依赖的元素或路径对象还没收到时先暂存，
+    // 因此不论各客户端收到操作的先后，合并结果都相同
+    pub fn integrate(&mut self, operation: Operation) -> RemoteChanges {
+        self.clock = self.clock.max(operation.clock + operation.items.len() as u64);
+        let mut changes = RemoteChanges::default();
+        let mut touched = HashSet::new();
+
+        for (offset, item) in operation.items.into_iter().enumerate() {
+            let stamp = Stamp {
+                clock: operation.clock + offset as u64,
+                client_id: operation.client_id.clone(),
+            };
+            match item {
+                HistoryItem::ObjectUpdate(mut item) => {
+                    let target = Some(item.object_id.as_str());
+                    if let Some(data) = item.redo_data.as_object_mut() {
+                        data.retain(|key, _| self.registers.accept(target, key, &stamp));
+                    }
+                    changes.items.push(HistoryItem::ObjectUpdate(item));
+                }
+                HistoryItem::SceneUpdate(mut item) => {
+                    if let Some(data) = item.redo_data.as_object_mut() {
+                        data.retain(|key, _| self.registers.accept(None, key, &stamp));
+                    }
+                    changes.items.push(HistoryItem::SceneUpdate(item));
+                }
+                HistoryItem::AddElement(item) => {
+                    if let Some(points) = points_of(&item.element_data) {
+                        let mut rga = Rga::from_values(&stamp, points);
+                        if let Some(orphans) = self.orphan_points.remove(&item.element_id) {
+                            orphans.into_iter().for_each(|op| apply_points_op(&mut rga, op));
+                            touched.insert(item.element_id.clone());
+                        }
+                        self.points.insert(item.element_id.clone(), rga);
+                    }
+                    changes.items.push(HistoryItem::AddElement(item));
+                }
+                HistoryItem::RemoveElement(item) => {
+                    self.points.remove(&item.element_id);
+                    self.orphan_points.remove(&item.element_id);
+                    changes.items.push(HistoryItem::RemoveElement(item));
+                }
+                HistoryItem::StyleUpdate(item) => {
+                    if self.registers.accept(Some(STYLES_TARGET), &item.style_id, &stamp) {
+                        changes.items.push(HistoryItem::StyleUpdate(item));
+                    }
+                }
+            }
+        }
+
+        let mut z_changed = false;
+        for op in operation.sequence {
+            let (SequenceOp::Insert { list, .. } | SequenceOp::Remove { list, .. }) = &op;
+            match list.clone() {
+                SequenceList::ZOrder => {
+                    z_changed = true;
+                    match op {
+                        SequenceOp::Insert { id, after, value, .. } => {
+                            let Value::String(object_id) = value else {
+                                continue;
+                            };
+                            self.z_order.insert(id, after.as_ref(), object_id);
+                        }
+                        SequenceOp::Remove { id, .. } => self.z_order.remove(&id),
+                    }
+                }
+                SequenceList::Points(object_id) => {
+                    let Some(rga) = self.points.get_mut(&object_id) else {
+                        self.orphan_points.entry(object_id).or_default().push(op);
+                        continue;
+                    };
+                    apply_points_op(rga, op);
+                    touched.insert(object_id);
+                }
+            }
+        }
+
+        if z_changed {
+            changes.z_order = Some(self.z_order.values());
+        }
+        changes.points = touched
+            .into_iter()
+            .filter_map(|id| {
+                let points = self.points.get(&id)?.values();
+                Some((id, points))
+            })
+            .collect();
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NullAdapter;
+
+    impl SyncAdapter for NullAdapter {
+        fn send(&self, _operation: &Operation) {}
+    }
+
+    fn session() -> SyncSession {
+        SyncSession::new("local", Rc::new(NullAdapter), Vec::new())
+    }
+
+    fn operation(seq: u64) -> Operation {
+        Operation {
+            client_id: "remote".to_string(),
+            seq,
+            clock: seq,
+            items: Vec::new(),
+            sequence: Vec::new(),
+        }
+    }
+
+    fn seqs(operations: Vec<Operation>) -> Vec<u64> {
+        operations.into_iter().map(|operation| operation.seq).collect()
+    }
+
+    #[test]
+    fn waits_for_first_operation_when_later_one_arrives_first() {
+        let mut session = session();
+        assert!(session.receive(operation(1)).is_empty());
+        assert_eq!(seqs(session.receive(operation(0))), vec![0, 1]);
+    }
+
+    #[test]
+    fn drops_duplicates_and_own_operations() {
+        let mut session = session();
+        assert_eq!(seqs(session.receive(operation(0))), vec![0]);
+        assert!(session.receive(operation(0)).is_empty());
+        let own = Operation {
+            client_id: "local".to_string(),
+            ..operation(1)
+        };
+        assert!(session.receive(own).is_empty());
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+// Lamport 时间戳；clock 相同时按 client_id 比较，所有客户端对任意两次修改的先后判断一致
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stamp {
+    pub clock: u64,
+    pub client_id: String,
+}
+
+impl Stamp {
+    // 同步开始前已经存在的内容使用的时间戳，早于任何修改
+    pub fn initial() -> Self {
+        Self {
+            clock: 0,
+            client_id: String::new(),
+        }
+    }
+}
+
+// 序列元素的 id：插入它的操作的时间戳加上在该操作内的序号
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ElementId {
+    pub stamp: Stamp,
+    pub index: u32,
+}
+
+// 每个属性一个最后写入者胜出的寄存器，只记录时间戳，值本身保存在对象上
+#[derive(Debug, Default)]
+pub struct LwwRegisters {
+    // (对象 id，None 表示场景) -> 属性 -> 最后一次写入的时间戳
+    stamps: HashMap<Option<String>, HashMap<String, Stamp>>,
+}
+
+impl LwwRegisters {
+    // stamp 晚于已有的写入时记录并返回 true，调用方据此决定是否应用这次写入
+    pub fn accept(&mut self, target: Option<&str>, key: &str, stamp: &Stamp) -> bool {
+        let stamps = self.stamps.entry(target.map(str::to_string)).or_default();
+        match stamps.get(key) {
+            Some(existing) if existing >= stamp => false,
+            _ => {
+                stamps.insert(key.to_string(), stamp.clone());
+                true
+            }
+        }
+    }
+}
+
+// diff 的结果：(删除的元素, 依次插入的 (新元素, 插入位置之后的元素, 值))
+pub type RgaDiff<T> = (Vec<ElementId>, Vec<(ElementId, Option<ElementId>, T)>);
+
+#[derive(Debug, Clone)]
+struct RgaNode<T> {
+    id: ElementId,
+    value: T,
+    removed: bool,
+}
+
+// 复制可增长数组（RGA）：元素插入在某个已有元素之后，并发插入到同一位置时 id 大的排在前面，
+// 删除只留下墓碑。依赖的元素还没收到的操作先暂存，因此以任意顺序应用同一组操作都得到相同的序列
+#[derive(Debug, Clone)]
+pub struct Rga<T> {
+    nodes: Vec<RgaNode<T>>,
+    // after 尚未收到的插入，(id, after, value)
+    pending: Vec<(ElementId, ElementId, T)>,
+    // 先于插入到达的删除，插入时直接作为墓碑
+    tombstones: HashSet<ElementId>,
+}
+
+impl<T> Default for Rga<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            pending: Vec::new(),
+            tombstones: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Rga<T> {
+    // 用已有内容初始化，各客户端从同一份内容得到相同的 id
+    pub fn from_values(stamp: &Stamp, values: impl IntoIterator<Item = T>) -> Self {
+        let nodes = values
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| RgaNode {
+                id: ElementId {
+                    stamp: stamp.clone(),
+                    index: index as u32,
+                },
+                value,
+                removed: false,
+            })
+            .collect();
+        Self {
+            nodes,
+            ..Self::default()
+        }
+    }
+
+    pub fn values(&self) -> Vec<T> {
+        self.nodes
+            .iter()
+            .filter(|node| !node.removed)
+            .map(|node| node.value.clone())
+            .collect()
+    }
+
+    fn position(&self, id: &ElementId) -> Option<usize> {
+        self.nodes.iter().position(|node| node.id == *id)
+    }
+
+    // 第 index 个未删除的元素
+    fn visible(&self, index: usize) -> Option<&RgaNode<T>> {
+        self.nodes.iter().filter(|node| !node.removed).nth(index)
+    }
+
+    pub fn last_visible(&self) -> Option<ElementId> {
+        self.nodes.iter().rev().find(|node| !node.removed).map(|node| node.id.clone())
+    }
+
+    pub fn find(&self, value: &T) -> Option<ElementId> {
+        self.nodes
+            .iter()
+            .find(|node| !node.removed && node.value == *value)
+            .map(|node| node.id.clone())
+    }
+
+    // after 为 None 时插入到开头；after 尚未收到时暂存，等它插入后再放到它后面
+    pub fn insert(&mut self, id: ElementId, after: Option<&ElementId>, value: T) {
+        if self.position(&id).is_some() || self.pending.iter().any(|(pending, ..)| *pending == id) {
+            return;
+        }
+        match after {
+            Some(after) if self.position(after).is_none() => self.pending.push((id, after.clone(), value)),
+            _ => self.integrate(id, after.cloned(), value),
+        }
+    }
+
+    fn integrate(&mut self, id: ElementId, after: Option<ElementId>, value: T) {
+        let mut ready = vec![(id, after, value)];
+        while let Some((id, after, value)) = ready.pop() {
+            let mut index = match &after {
+                Some(after) => self.position(after).map_or(self.nodes.len(), |position| position + 1),
+                None => 0,
+            };
+            while index < self.nodes.len() && self.nodes[index].id > id {
+                index += 1;
+            }
+            let removed = self.tombstones.remove(&id);
+            self.nodes.insert(index, RgaNode {
+                id: id.clone(),
+                value,
+                removed,
+            });
+
+            // 等待这个元素的插入现在可以放入
+            let (waiting_for_id, waiting): (Vec<_>, Vec<_>) =
+                std::mem::take(&mut self.pending).into_iter().partition(|(_, after, _)| *after == id);
+            self.pending = waiting;
+            ready.extend(waiting_for_id.into_iter().map(|(id, after, value)| (id, Some(after), value)));
+        }
+    }
+
+    pub fn remove(&mut self, id: &ElementId) {
+        match self.position(id) {
+            Some(position) => self.nodes[position].removed = true,
+            None => {
+                self.tombstones.insert(id.clone());
+            }
+        }
+    }
+
+    // 把当前内容改为 values 所需的操作：只替换首尾相同部分之间的元素，新元素的 id 用 stamp 和递增序号生成
+    pub fn diff(&self, values: &[T], stamp: &Stamp) -> RgaDiff<T> {
+        let current = self.values();
+        let prefix = current
+            .iter()
+            .zip(values)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = current[prefix..]
+            .iter()
+            .rev()
+            .zip(values[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let removed = (prefix..current.len() - suffix)
+            .filter_map(|index| self.visible(index).map(|node| node.id.clone()))
+            .collect();
+        let mut after = prefix
+            .checked_sub(1)
+            .and_then(|index| self.visible(index))
+            .map(|node| node.id.clone());
+        let inserted = values[prefix..values.len() - suffix]
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let id = ElementId {
+                    stamp: stamp.clone(),
+                    index: index as u32,
+                };
+                (id.clone(), after.replace(id), value.clone())
+            })
+            .collect();
+        (removed, inserted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(ElementId, Option<ElementId>, char),
+        Remove(ElementId),
+    }
+
+    fn id(client_id: &str, clock: u64, index: u32) -> ElementId {
+        ElementId {
+            stamp: Stamp {
+                clock,
+                client_id: client_id.to_string(),
+            },
+            index,
+        }
+    }
+
+    fn apply(ops: &[Op]) -> Vec<char> {
+        let mut rga = Rga::from_values(&Stamp::initial(), ['a', 'b']);
+        for op in ops {
+            match op {
+                Op::Insert(id, after, value) => rga.insert(id.clone(), after.as_ref(), *value),
+                Op::Remove(id) => rga.remove(id),
+            }
+        }
+        rga.values()
+    }
+
+    fn permutations(ops: &[Op]) -> Vec<Vec<Op>> {
+        if ops.is_empty() {
+            return vec![Vec::new()];
+        }
+        (0..ops.len())
+            .flat_map(|index| {
+                let mut rest = ops.to_vec();
+                let first = rest.remove(index);
+                permutations(&rest).into_iter().map(move |mut order| {
+                    order.insert(0, first.clone());
+                    order
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn converges_in_any_delivery_order() {
+        let initial = |index| id("", 0, index);
+        let ops = vec![
+            // 客户端 x 在 a 之后插入两个元素
+            Op::Insert(id("x", 1, 0), Some(initial(0)), 'x'),
+            Op::Insert(id("x", 1, 1), Some(id("x", 1, 0)), 'y'),
+            // 客户端 z 同时在 a 之后插入，并删除 b
+            Op::Insert(id("z", 1, 0), Some(initial(0)), 'z'),
+            Op::Remove(initial(1)),
+            // 客户端 x 收到 z 的修改之后再删除它，并继续插入
+            Op::Remove(id("z", 1, 0)),
+            Op::Insert(id("x", 3, 0), Some(id("x", 1, 1)), 'w'),
+        ];
+        assert_eq!(apply(&ops), vec!['a', 'x', 'y', 'w']);
+        for order in permutations(&ops) {
+            assert_eq!(apply(&order), vec!['a', 'x', 'y', 'w'], "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn insert_waits_for_missing_after() {
+        let mut rga = Rga::from_values(&Stamp::initial(), ['a']);
+        rga.insert(id("x", 2, 0), Some(&id("x", 1, 0)), 'c');
+        assert_eq!(rga.values(), vec!['a']);
+        rga.insert(id("x", 1, 0), Some(&id("", 0, 0)), 'b');
+        assert_eq!(rga.values(), vec!['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn remove_before_insert_leaves_tombstone() {
+        let mut rga = Rga::from_values(&Stamp::initial(), ['a']);
+        rga.remove(&id("x", 1, 0));
+        rga.insert(id("x", 1, 0), Some(&id("", 0, 0)), 'b');
+        assert_eq!(rga.values(), vec!['a']);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+use crate::bounding_box::BoundingBox;
+use crate::renderer::Renderer;
+
+// 协作者的颜色，按 client_id 选取，各客户端看到的同一个人颜色相同
+const COLORS: [&str; 8] = [
+    "#e5484d", "#0091ff", "#30a46c", "#f76808", "#8e4ec6", "#12a594", "#d6409f", "#ad7f58",
+];
+// 指针向最新位置靠拢的时间常数（秒），收到的位置间隔较大时也能平滑移动
+const SMOOTHING_SECONDS: f64 = 0.08;
+// 单帧最多按该时长推进，长时间没有绘制后不会直接跳到目标位置
+const MAX_SMOOTHING_STEP: f64 = 0.05;
+// 与目标的距离（世界单位）小于该值时直接对齐
+const SNAP_DISTANCE: f64 = 0.01;
+const SELECTION_LINE_WIDTH: f64 = 2.0;
+const LABEL_FONT: &str = "12px sans-serif";
+const LABEL_HEIGHT: f64 = 18.0;
+const LABEL_PADDING: f64 = 5.0;
+
+// 协作者的指针位置（世界坐标）和选中的对象；频繁发送且不需要顺序，只保留每个客户端最新的一份
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Presence {
+    pub client_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    // 指针离开画布时为 None
+    #[serde(default)]
+    pub cursor: Option<(f64, f64)>,
+    #[serde(default)]
+    pub selection: Vec<String>,
+}
+
+#[derive(Debug)]
+struct RemoteUser {
+    name: Option<String>,
+    color: &'static str,
+    target: Option<(f64, f64)>,
+    // 当前绘制的位置，每帧向 target 靠拢
+    shown: Option<(f64, f64)>,
+    selection: Vec<String>,
+}
+
+fn color_for(client_id: &str) -> &'static str {
+    let hash = client_id
+        .bytes()
+        .fold(2166136261u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(16777619));
+    COLORS[hash as usize % COLORS.len()]
+}
+
+// 在场景之上绘制其他协作者的指针和选中框
+#[derive(Debug, Default)]
+pub struct PresenceOverlay {
+    users: BTreeMap<String, RemoteUser>,
+}
+
+impl PresenceOverlay {
+    pub fn update(&mut self, presence: Presence) {
+        let user = self
+            .users
+            .entry(presence.client_id)
+            .or_insert_with_key(|client_id| RemoteUser {
+                name: None,
+                color: color_for(client_id),
+                target: None,
+                shown: None,
+                selection: Vec::new(),
+            });
+        user.name = presence.name;
+        user.target = presence.cursor;
+        // 指针重新进入画布时直接出现在新位置
+        if user.shown.is_none() {
+            user.shown = presence.cursor;
+        }
+        if presence.cursor.is_none() {
+            user.shown = None;
+        }
+        user.selection = presence.selection;
+    }
+
+    pub fn remove(&mut self, client_id: &str) -> bool {
+        self.users.remove(client_id).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.users.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    // 是否还有指针没有移动到最新位置，需要继续绘制
+    pub fn is_animating(&self) -> bool {
+        self.users.values().any(|user| user.shown != user.target)
+    }
+
+    // 按帧间隔 delta（秒）把指针向最新位置移动
+    pub fn advance(&mut self, delta: f64) {
+        let t = 1.0 - (-delta.min(MAX_SMOOTHING_STEP) / SMOOTHING_SECONDS).exp();
+        for user in self.users.values_mut() {
+            let (Some((x, y)), Some(target)) = (user.shown, user.target) else {
+                continue;
+            };
+            let next = (x + (target.0 - x) * t, y + (target.1 - y) * t);
+            user.shown = if (target.0 - next.0).hypot(target.1 - next.1) < SNAP_DISTANCE {
+                Some(target)
+            } else {
+                Some(next)
+            };
+        }
+    }
+
+    // 在屏幕坐标下绘制，线宽和文字大小不随缩放变化；bounds 返回对象的包围盒，已删除的对象返回 None
+    pub fn draw(
+        &self,
+        renderer: &dyn Renderer,
+        view: &na::Matrix3<f64>,
+        bounds: impl Fn(&str) -> Option<BoundingBox>,
+    ) {
+        let to_screen = |(x, y): (f64, f64)| {
+            let point = view * na::Vector3::new(x, y, 1.0);
+            (point.x, point.y)
+        };
+
+        renderer.save();
+        renderer.set_line_width(SELECTION_LINE_WIDTH);
+        renderer.set_font(LABEL_FONT);
+        for user in self.users.values() {
+            renderer.set_stroke_style(user.color);
+            for id in &user.selection {
+                let Some(bbox) = bounds(id) else {
+                    continue;
+                };
+                let corners = bbox.corners().map(to_screen);
+                renderer.begin_path();
+                renderer.move_to(corners[0].0, corners[0].1);
+                for (x, y) in corners.iter().skip(1) {
+                    renderer.line_to(*x, *y);
+                }
+                renderer.close_path();
+                renderer.stroke();
+            }
+
+            let Some(cursor) = user.shown else {
+                continue;
+            };
+            let (x, y) = to_screen(cursor);
+            renderer.draw_polygon(
+                &[x, y, x, y + 16.0, x + 4.5, y + 12.0, x + 11.0, y + 11.0],
+                user.color,
+            );
+            if let Some(name) = &user.name {
+                let width = renderer.measure_text(name) + LABEL_PADDING * 2.0;
+                renderer.draw_rectangle(x + 10.0, y + 16.0, width, LABEL_HEIGHT, user.color);
+                renderer.set_fill_style("white");
+                renderer.fill_text(name, x + 10.0 + LABEL_PADDING, y + 16.0 + LABEL_HEIGHT - LABEL_PADDING);
+            }
+        }
+        renderer.restore();
+    }
+}
@@ -0,0 +1,56 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// 颜色变量以 "$" 开头，如 "$primary"，绘制时按当前主题解析
+const VARIABLE_PREFIX: char = '$';
+// 主题中没有定义的变量不绘制
+const UNDEFINED_COLOR: &str = "transparent";
+
+// 每个 SceneManager 各自的主题，克隆后共享同一份变量表，渲染器创建时持有一份
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    // 变量名（不含 "$"）到颜色值
+    colors: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl Theme {
+    pub fn set(&self, colors: HashMap<String, String>) {
+        *self.colors.borrow_mut() = colors;
+    }
+
+    pub fn colors(&self) -> HashMap<String, String> {
+        self.colors.borrow().clone()
+    }
+
+    // 由渲染器在设置颜色时调用，普通颜色原样返回
+    pub fn resolve<'a>(&self, color: &'a str) -> Cow<'a, str> {
+        let Some(name) = color.strip_prefix(VARIABLE_PREFIX) else {
+            return Cow::Borrowed(color);
+        };
+        match self.colors.borrow().get(name) {
+            Some(value) => Cow::Owned(value.clone()),
+            None => Cow::Borrowed(UNDEFINED_COLOR),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn themes_resolve_independently() {
+        let light = Theme::default();
+        let dark = Theme::default();
+        light.set(HashMap::from([("primary".to_string(), "#fff".to_string())]));
+        dark.set(HashMap::from([("primary".to_string(), "#000".to_string())]));
+
+        assert_eq!(light.resolve("$primary"), "#fff");
+        assert_eq!(dark.resolve("$primary"), "#000");
+        assert_eq!(light.clone().resolve("$primary"), "#fff");
+        assert_eq!(light.resolve("$missing"), UNDEFINED_COLOR);
+        assert_eq!(light.resolve("red"), "red");
+    }
+}